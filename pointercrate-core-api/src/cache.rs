@@ -0,0 +1,302 @@
+//! Module providing an in-process response cache for public `GET` endpoints
+//!
+//! Endpoints opt into caching by setting a `Cache-Control` header with a positive `max-age` (or
+//! `s-maxage`), and tag their responses with a space separated list of surrogate keys in the
+//! `Surrogate-Key` header (e.g. `demon:12 overview`). Endpoints that modify data then invalidate all
+//! cached responses tagged with a given key via [`CachePurge`], the same way one would purge a CDN.
+//!
+//! Requests made by logged in users (identified by an `Authorization` header or an access token
+//! cookie) are never cached or served from the cache, as their responses might contain data only
+//! visible to them.
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{Header, Method, Status},
+    request::{FromRequest, Outcome},
+    response::Responder,
+    routes, uri, Build, Data, Request, Response, Rocket,
+};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct CachedResponse {
+    status: Status,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    surrogate_keys: Vec<String>,
+    expires_at: Instant,
+}
+
+/// The cached responses, keyed by [`cache_key`]. Cheap to clone, all clones share the same entries
+/// (which allows background tasks to purge the cache)
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Arc<Mutex<HashMap<String, Arc<CachedResponse>>>>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        ResponseCache {
+            capacity,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Arc<CachedResponse>> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(Arc::clone(entry)),
+            Some(_) => {
+                entries.remove(key);
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: String, response: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.len() >= self.capacity {
+            let now = Instant::now();
+
+            entries.retain(|_, entry| entry.expires_at > now);
+
+            // Rather not cache something than arbitrarily evicting entries that are still valid
+            if entries.len() >= self.capacity {
+                return;
+            }
+        }
+
+        entries.insert(key, Arc::new(response));
+    }
+
+    /// Removes all cached responses tagged with the given surrogate key
+    pub fn purge(&self, surrogate_key: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| !entry.surrogate_keys.iter().any(|key| key == surrogate_key));
+    }
+
+    pub fn purge_all(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Request guard giving endpoints access to [`ResponseCache::purge`]. Purging is a no-op if
+/// response caching is disabled (that is, if no [`ResponseCacheFairing`] is attached).
+pub struct CachePurge<'r>(Option<&'r ResponseCache>);
+
+impl CachePurge<'_> {
+    pub fn purge(&self, surrogate_key: &str) {
+        if let Some(cache) = self.0 {
+            cache.purge(surrogate_key)
+        }
+    }
+
+    pub fn purge_all(&self) {
+        if let Some(cache) = self.0 {
+            cache.purge_all()
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CachePurge<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(CachePurge(request.rocket().state::<ResponseCache>()))
+    }
+}
+
+/// Per-request state of the cache lookup performed by [`ResponseCacheFairing`]
+#[derive(Default)]
+struct CacheLookup {
+    /// The key (see [`cache_key`]) the response should be cached under, if the request is cacheable
+    key: Option<String>,
+    hit: Option<Arc<CachedResponse>>,
+}
+
+/// Rocket fairing implementing the response cache.
+///
+/// Like the [maintenance fairing](crate::maintenance::MaintenanceFairing), this rewrites requests
+/// that can be answered from the cache to `GET /cached-response`, an endpoint that simply replays
+/// the cached response, since fairings cannot terminate requests themselves.
+pub struct ResponseCacheFairing {
+    capacity: usize,
+}
+
+impl ResponseCacheFairing {
+    /// Creates a new response cache holding at most `capacity` responses
+    pub fn new(capacity: usize) -> Self {
+        ResponseCacheFairing { capacity }
+    }
+}
+
+/// Extracts the time a response may be cached for from its `Cache-Control` header
+fn max_age(cache_control: &str) -> Option<Duration> {
+    let mut max_age = None;
+    let mut shared_max_age = None;
+
+    for directive in cache_control.split(',').map(str::trim) {
+        match directive.split_once('=') {
+            Some(("max-age", seconds)) => max_age = seconds.parse().ok(),
+            Some(("s-maxage", seconds)) => shared_max_age = seconds.parse().ok(),
+            None if ["no-store", "no-cache", "private"].contains(&directive) => return None,
+            _ => (),
+        }
+    }
+
+    shared_max_age.or(max_age).filter(|&seconds| seconds > 0).map(Duration::from_secs)
+}
+
+/// Responses are cached per request URI and media type preferred by the client, as some URIs serve
+/// both HTML and JSON depending on the `Accept` header
+fn cache_key(request: &Request) -> String {
+    match request.accept() {
+        Some(accept) => format!("{} {}", request.uri(), accept.preferred().media_type()),
+        None => request.uri().to_string(),
+    }
+}
+
+fn is_cacheable(request: &Request) -> bool {
+    request.method() == Method::Get && !request.headers().contains("Authorization") && request.cookies().get("access_token").is_none()
+}
+
+#[rocket::async_trait]
+impl Fairing for ResponseCacheFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Response Cache",
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        Ok(rocket
+            .manage(ResponseCache::new(self.capacity))
+            .mount("/", routes![cached_response]))
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        if !is_cacheable(request) {
+            return;
+        }
+
+        let Some(cache) = request.rocket().state::<ResponseCache>() else {
+            return;
+        };
+
+        let key = cache_key(request);
+        let hit = cache.get(&key);
+
+        if hit.is_some() {
+            request.set_uri(uri!("/cached-response"));
+        }
+
+        request.local_cache(|| CacheLookup { key: Some(key), hit });
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let lookup = request.local_cache(CacheLookup::default);
+
+        let Some(ref key) = lookup.key else { return };
+
+        if lookup.hit.is_some() || response.status() != Status::Ok {
+            return;
+        }
+
+        let Some(max_age) = response.headers().get_one("Cache-Control").and_then(max_age) else {
+            return;
+        };
+
+        let Some(cache) = request.rocket().state::<ResponseCache>() else {
+            return;
+        };
+
+        let Ok(body) = response.body_mut().to_bytes().await else {
+            return;
+        };
+
+        response.set_sized_body(body.len(), Cursor::new(body.clone()));
+
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|header| header.name() != "Set-Cookie")
+            .map(|header| (header.name().to_string(), header.value().to_string()))
+            .collect();
+        let surrogate_keys = response
+            .headers()
+            .get("Surrogate-Key")
+            .flat_map(str::split_whitespace)
+            .map(ToString::to_string)
+            .collect();
+
+        response.set_header(Header::new("X-Cache", "MISS"));
+
+        cache.insert(
+            key.clone(),
+            CachedResponse {
+                status: response.status(),
+                headers,
+                body,
+                surrogate_keys,
+                expires_at: Instant::now() + max_age,
+            },
+        );
+    }
+}
+
+struct CacheHit(Arc<CachedResponse>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheHit {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.local_cache(CacheLookup::default).hit {
+            Some(ref hit) => Outcome::Success(CacheHit(Arc::clone(hit))),
+            None => Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for CacheHit {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let etag = self.0.headers.iter().find(|(name, _)| name.eq_ignore_ascii_case("etag"));
+
+        if let (Some((_, etag)), Some(if_none_match)) = (etag, request.headers().get_one("If-None-Match")) {
+            if if_none_match.contains(etag.as_str()) {
+                return Response::build().status(Status::NotModified).ok();
+            }
+        }
+
+        let mut response = Response::build();
+
+        response.status(self.0.status);
+
+        for (name, value) in &self.0.headers {
+            response.raw_header_adjoin(name.clone(), value.clone());
+        }
+
+        response
+            .header(Header::new("X-Cache", "HIT"))
+            .sized_body(self.0.body.len(), Cursor::new(self.0.body.clone()))
+            .ok()
+    }
+}
+
+#[rocket::get("/cached-response")]
+fn cached_response(hit: CacheHit) -> CacheHit {
+    hit
+}
@@ -0,0 +1,255 @@
+//! API reference generated from the routes mounted on the rocket instance
+//!
+//! Since the reference is built from rocket's own route table at ignition, it always matches the
+//! endpoints that are actually being served, and cannot drift from the code the way hand-written
+//! documentation does.
+//!
+//! Descriptions of individual routes are taken from the doc comments of their handlers. To include a
+//! handler's doc comment in the reference, annotate it with [`documented`] and mount it via this
+//! crate's [`routes!`](crate::routes) macro instead of rocket's:
+//!
+//! ```ignore
+//! /// Retrieves a single demon
+//! #[documented(example = "/api/v2/demons/1/")]
+//! #[rocket::get("/<demon_id>")]
+//! pub async fn get(demon_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullDemon>> {
+//!     // ...
+//! }
+//!
+//! rocket.mount("/api/v2/demons/", pointercrate_core_api::routes![get])
+//! ```
+//!
+//! The optional `example` is a request (path and query) against the documented route. The reference
+//! page loads the responses to these requests when it is viewed, so on instances populated via
+//! `pointercrate seed` it shows responses built from the seeded fixtures.
+
+use crate::response::Page;
+use maud::html;
+pub use pointercrate_core_macros::documented;
+use pointercrate_core_pages::{head::HeadLike, PageFragment};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    serde::json::Json,
+    Build, Rocket, Route, State,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, marker::PhantomData, sync::Mutex};
+
+/// Documentation of a single route, generated by the [`documented`] attribute
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteDocumentation {
+    /// The doc comment of the route's handler
+    pub description: &'static str,
+
+    /// A request (path and query) against this route, whose response serves as an example
+    pub example: Option<&'static str>,
+}
+
+/// Implemented by [`documented`] for the structs rocket's route attributes generate for each handler
+pub trait Documented {
+    const DOCUMENTATION: RouteDocumentation;
+}
+
+/// The documentation of all routes created via [`routes!`](crate::routes), keyed by their names
+static DOCUMENTATION: Mutex<BTreeMap<&'static str, RouteDocumentation>> = Mutex::new(BTreeMap::new());
+
+#[doc(hidden)]
+pub fn __register(mut route: Route, name: &'static str, documentation: Option<RouteDocumentation>) -> Route {
+    if let Some(documentation) = documentation {
+        DOCUMENTATION.lock().unwrap().insert(name, documentation);
+
+        // Rocket names routes after their handler function, which is not unique across modules
+        route.name = Some(name.into());
+    }
+
+    route
+}
+
+// The `routes!` macro needs to accept both documented and undocumented handlers. This is done via
+// autoref-based specialization: method resolution prefers `__Documented` (implemented on a
+// reference) over `__Undocumented` whenever the handler implements `Documented`.
+
+#[doc(hidden)]
+pub struct __Handler<H>(PhantomData<H>);
+
+impl<H> __Handler<H> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        __Handler(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait __Documented {
+    fn documentation(&self) -> Option<RouteDocumentation>;
+}
+
+impl<H: Documented> __Documented for &__Handler<H> {
+    fn documentation(&self) -> Option<RouteDocumentation> {
+        Some(H::DOCUMENTATION)
+    }
+}
+
+#[doc(hidden)]
+pub trait __Undocumented {
+    fn documentation(&self) -> Option<RouteDocumentation>;
+}
+
+impl<H> __Undocumented for __Handler<H> {
+    fn documentation(&self) -> Option<RouteDocumentation> {
+        None
+    }
+}
+
+/// Like [`rocket::routes!`], but makes the documentation of handlers annotated with
+/// [`documented`](crate::documentation::documented) available to the
+/// [`DocumentationFairing`](crate::documentation::DocumentationFairing)
+#[macro_export]
+macro_rules! routes {
+    ($($handler:path),* $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::documentation::{__Documented as _, __Undocumented as _};
+
+        let routes: ::std::vec::Vec<::rocket::Route> = vec![$(
+            $crate::documentation::__register(
+                ::rocket::routes![$handler].remove(0),
+                concat!(module_path!(), "::", stringify!($handler)),
+                (&&$crate::documentation::__Handler::<$handler>::new()).documentation(),
+            )
+        ),*];
+
+        routes
+    }};
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DocumentedRoute {
+    pub method: String,
+
+    /// The route's URI, including dynamic segments (e.g. `<demon_id>`) and query parameters
+    pub uri: String,
+
+    /// The name of the function handling requests to this route
+    pub handler: Option<String>,
+
+    /// The media type this route accepts (for requests with a body) or produces
+    pub format: Option<String>,
+
+    /// The doc comment of the route's handler, if it is [`documented`]
+    pub description: Option<String>,
+
+    /// An example request against this route, if the handler is [`documented`] with one
+    pub example: Option<String>,
+}
+
+impl From<&Route> for DocumentedRoute {
+    fn from(route: &Route) -> Self {
+        let documentation = route
+            .name
+            .as_ref()
+            .and_then(|name| DOCUMENTATION.lock().unwrap().get(name.as_ref()).copied());
+
+        DocumentedRoute {
+            method: route.method.to_string(),
+            uri: route.uri.to_string(),
+            handler: route.name.as_ref().map(ToString::to_string),
+            format: route.format.as_ref().map(ToString::to_string),
+            description: documentation.map(|documentation| documentation.description.to_string()),
+            example: documentation.and_then(|documentation| documentation.example.map(ToString::to_string)),
+        }
+    }
+}
+
+/// All API routes, grouped by the base path they are mounted at
+#[derive(Serialize, Debug, Default)]
+pub struct ApiDocumentation(pub BTreeMap<String, Vec<DocumentedRoute>>);
+
+/// Fairing that collects all routes below `/api/` once all crates have mounted their endpoints,
+/// and mounts the documentation endpoints at `/documentation/api/` (HTML) and `/api/v1/routes/`
+/// (JSON).
+#[derive(Default)]
+pub struct DocumentationFairing;
+
+#[rocket::async_trait]
+impl Fairing for DocumentationFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "API Documentation",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        let mut documentation = ApiDocumentation::default();
+
+        for route in rocket.routes().filter(|route| route.uri.base().starts_with("/api/")) {
+            documentation.0.entry(route.uri.base().to_string()).or_default().push(route.into());
+        }
+
+        for routes in documentation.0.values_mut() {
+            routes.sort_by(|r1, r2| r1.uri.cmp(&r2.uri).then_with(|| r1.method.cmp(&r2.method)));
+        }
+
+        Ok(rocket
+            .manage(documentation)
+            .mount("/documentation/api/", rocket::routes![reference_page])
+            .mount("/api/v1/routes/", rocket::routes![reference]))
+    }
+}
+
+#[rocket::get("/")]
+fn reference(documentation: &State<ApiDocumentation>) -> Json<&ApiDocumentation> {
+    Json(documentation.inner())
+}
+
+#[rocket::get("/")]
+fn reference_page(documentation: &State<ApiDocumentation>) -> Page {
+    Page::new(
+        PageFragment::new("API Reference", "Reference of all endpoints of the pointercrate API")
+            .script("/static/core/js/documentation.js")
+            .body(html! {
+                div.m-center.flex.container {
+                    div.left {
+                        @for (base, routes) in &documentation.0 {
+                            div.panel.fade {
+                                h2.underlined.pad {
+                                    code { (base) }
+                                }
+                                table.api-reference {
+                                    @for route in routes {
+                                        tr {
+                                            td { b { (route.method) } }
+                                            td { code { (route.uri) } }
+                                            td {
+                                                @if let Some(ref format) = route.format {
+                                                    (format)
+                                                }
+                                            }
+                                        }
+                                        @if let Some(ref description) = route.description {
+                                            tr.api-description {
+                                                td colspan="3" {
+                                                    @for paragraph in description.split("\n\n") {
+                                                        p { (paragraph) }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        @if let Some(ref example) = route.example {
+                                            tr.api-description {
+                                                td colspan="3" {
+                                                    "Example: "
+                                                    a.link href=(example) { code { (example) } }
+                                                    pre.api-example data-example=(example) {}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+    )
+}
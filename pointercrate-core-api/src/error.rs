@@ -13,6 +13,32 @@ use serde_json::Value;
 
 pub type Result<T> = std::result::Result<T, ErrorResponder>;
 
+/// The authentication scheme a client is challenged to use, via the `WWW-Authenticate` header, when
+/// its request is answered with `401 UNAUTHORIZED`.
+///
+/// Authentication request guards store the scheme they accept in the request's local cache. If none
+/// did, the client is challenged to use an access token, as those are accepted by every endpoint
+/// not requiring the account password.
+///
+/// Browsers prompt for credentials when a response challenges them to use `Basic` authentication,
+/// even if the request was made by a script. Thus, requests made by our own frontend (which sets
+/// `X-Requested-With: XMLHttpRequest`, and handles `401 UNAUTHORIZED` responses itself) are not
+/// challenged to use `Basic` authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthenticationScheme {
+    Basic,
+    Bearer,
+}
+
+impl AuthenticationScheme {
+    fn challenge(self) -> &'static str {
+        match self {
+            AuthenticationScheme::Basic => r#"Basic realm="pointercrate", charset="UTF-8""#,
+            AuthenticationScheme::Bearer => r#"Bearer realm="pointercrate""#,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponder {
     message: String,
@@ -34,7 +60,7 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
 
         let status = Status::from_code(self.error_code / 100).unwrap_or(Status::InternalServerError);
 
-        if *accept == MediaType::HTML {
+        let mut response = if *accept == MediaType::HTML {
             Response::build_from(
                 Page::new(ErrorFragment {
                     status: self.error_code / 100,
@@ -44,10 +70,21 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
                 .respond_to(request)?,
             )
             .status(status)
-            .ok()
+            .finalize()
         } else {
-            Response::build_from(Json(self).respond_to(request)?).status(status).ok()
+            Response::build_from(Json(self).respond_to(request)?).status(status).finalize()
+        };
+
+        if status == Status::Unauthorized {
+            let scheme = *request.local_cache(|| AuthenticationScheme::Bearer);
+            let is_frontend = request.headers().get_one("X-Requested-With") == Some("XMLHttpRequest");
+
+            if scheme != AuthenticationScheme::Basic || !is_frontend {
+                response.set_raw_header("WWW-Authenticate", scheme.challenge());
+            }
         }
+
+        Ok(response)
     }
 }
 
@@ -10,6 +10,7 @@ use rocket::{
 
 pub struct Tagged<T: Taggable>(pub T);
 
+#[derive(Clone)]
 pub struct Precondition(Vec<String> /* ensure private constructor for type level proof of header */);
 
 impl Precondition {
@@ -1,6 +1,13 @@
+pub mod cache;
+pub mod documentation;
 pub mod error;
 pub mod etag;
+pub mod mail;
 pub mod maintenance;
+pub mod normalize;
 pub mod pagination;
+pub mod param;
 pub mod query;
+pub mod readiness;
 pub mod response;
+pub mod upload;
@@ -0,0 +1,327 @@
+//! Module for sending transactional emails
+//!
+//! Emails are sent through a [`Mailer`], of which there are two implementations:
+//! * [`SmtpMailer`], which hands mails to an SMTP relay (for example a local postfix instance)
+//! * [`LogMailer`], which just logs all mails, for development setups and tests
+//!
+//! Which one is used is determined by [`MailerHandle::from_config`]. Endpoints should never wait for
+//! a mail to be delivered, and instead use [`MailerHandle::dispatch`], which sends mails in the
+//! background.
+
+use log::{error, info};
+use pointercrate_core::config;
+use rocket::tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use std::{fmt::Display, sync::Arc};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Email {
+    pub fn registration_verification(to: String, username: &str, verification_link: &str) -> Self {
+        Email {
+            to,
+            subject: "Verify your account".to_string(),
+            body: format!(
+                "Hello {},\n\nplease verify your account by visiting the link below:\n\n{}\n\nIf you did not create an account, you can \
+                 ignore this email.",
+                username, verification_link
+            ),
+        }
+    }
+
+    pub fn password_reset(to: String, username: &str, reset_link: &str) -> Self {
+        Email {
+            to,
+            subject: "Password reset".to_string(),
+            body: format!(
+                "Hello {},\n\nsomeone (hopefully you) requested a password reset for your account. To choose a new password, visit the \
+                 link below:\n\n{}\n\nIf you did not request a password reset, you can ignore this email.",
+                username, reset_link
+            ),
+        }
+    }
+
+    pub fn record_decision(to: String, username: &str, demon: &str, status: impl Display) -> Self {
+        Email {
+            to,
+            subject: format!("Your record on {} has been {}", demon, status),
+            body: format!(
+                "Hello {},\n\nyour record on {} has been {} by the list staff.",
+                username, demon, status
+            ),
+        }
+    }
+
+    pub fn permissions_granted(to: String, username: &str, permissions: &[String]) -> Self {
+        Email {
+            to,
+            subject: "You have been granted new permissions".to_string(),
+            body: format!(
+                "Hello {},\n\nyou have been granted the following permissions: {}.",
+                username,
+                permissions.join(", ")
+            ),
+        }
+    }
+
+    pub fn watched_object_modified(to: String, username: &str, object: &str) -> Self {
+        Email {
+            to,
+            subject: format!("{} has been modified", object),
+            body: format!(
+                "Hello {},\n\n{}, which you are watching, has been modified by the list staff. You can stop watching it from your \
+                 account page.",
+                username, object
+            ),
+        }
+    }
+
+    /// Summary of all notifications a user received since their previous `frequency` (e.g. "daily")
+    /// digest
+    pub fn notification_digest(to: String, username: &str, frequency: impl Display, notifications: &[String]) -> Self {
+        let mut list = String::new();
+
+        for notification in notifications {
+            list.push_str("- ");
+            list.push_str(notification);
+            list.push('\n');
+        }
+
+        Email {
+            to,
+            subject: format!("Your {} notification digest", frequency),
+            body: format!(
+                "Hello {},\n\nhere is what happened since your last digest:\n\n{}\nYou can change how often you receive these emails, or \
+                 stop receiving them, from your account page.",
+                username, list
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MailError {
+    Io(std::io::Error),
+
+    /// The SMTP server responded with an unexpected reply
+    Smtp {
+        command: &'static str,
+        reply: String,
+    },
+
+    /// An address contained control characters, which could be used to inject SMTP commands
+    InvalidAddress(String),
+}
+
+impl From<std::io::Error> for MailError {
+    fn from(err: std::io::Error) -> Self {
+        MailError::Io(err)
+    }
+}
+
+#[rocket::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), MailError>;
+}
+
+/// [`Mailer`] that only logs the emails it is asked to send
+pub struct LogMailer;
+
+#[rocket::async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        info!("Not sending email '{}' to {}:\n{}", email.subject, email.to, email.body);
+
+        Ok(())
+    }
+}
+
+/// [`Mailer`] speaking plain SMTP to a relay.
+///
+/// Neither TLS nor authentication are supported, so the relay should run on the same machine or
+/// inside a trusted network.
+pub struct SmtpMailer {
+    server: String,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(server: String, from: String) -> Self {
+        SmtpMailer { server, from }
+    }
+}
+
+/// Makes sure an address can be put into SMTP commands and headers as is
+fn check_address(address: &str) -> Result<&str, MailError> {
+    if address.chars().any(char::is_control) {
+        return Err(MailError::InvalidAddress(address.to_string()));
+    }
+
+    Ok(address)
+}
+
+/// Turns arbitrary text (e.g. a subject containing a demon name) into a header value. Control
+/// characters are dropped, as line breaks would allow injecting headers (or, by ending the `DATA`
+/// command early, SMTP commands). Non-ASCII text is encoded as RFC 2047 encoded-words.
+fn encode_header(text: &str) -> String {
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+
+    if text.is_ascii() {
+        return text;
+    }
+
+    // An encoded-word may be at most 75 characters long, 12 of which are taken by "=?utf-8?Q?" and
+    // "?=". Characters are never split across encoded-words
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        let mut encoded = String::new();
+
+        match c {
+            ' ' => encoded.push('_'),
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '!' | '*' | '+' | '-' | '/' => encoded.push(c),
+            _ => {
+                for byte in c.to_string().bytes() {
+                    encoded.push_str(&format!("={:02X}", byte));
+                }
+            },
+        }
+
+        if word.len() + encoded.len() > 63 {
+            words.push(std::mem::take(&mut word));
+        }
+
+        word.push_str(&encoded);
+    }
+
+    words.push(word);
+
+    words
+        .into_iter()
+        .map(|word| format!("=?utf-8?Q?{}?=", word))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+async fn expect_reply(reader: &mut BufReader<TcpStream>, command: &'static str, expected_code: &'static str) -> Result<(), MailError> {
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        reader.read_line(&mut line).await?;
+
+        // Multiline replies have a '-' after the code on every line but the last
+        if line.len() < 4 || !line.starts_with(expected_code) {
+            return Err(MailError::Smtp { command, reply: line });
+        }
+
+        if line.as_bytes()[3] != b'-' {
+            return Ok(());
+        }
+    }
+}
+
+async fn command(
+    reader: &mut BufReader<TcpStream>, line: String, name: &'static str, expected_code: &'static str,
+) -> Result<(), MailError> {
+    reader.get_mut().write_all(line.as_bytes()).await?;
+    expect_reply(reader, name, expected_code).await
+}
+
+#[rocket::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let from = check_address(&self.from)?;
+        let to = check_address(&email.to)?;
+
+        let mut reader = BufReader::new(TcpStream::connect(&self.server).await?);
+
+        expect_reply(&mut reader, "CONNECT", "220").await?;
+
+        command(&mut reader, "EHLO localhost\r\n".to_string(), "EHLO", "250").await?;
+        command(&mut reader, format!("MAIL FROM:<{}>\r\n", from), "MAIL", "250").await?;
+        command(&mut reader, format!("RCPT TO:<{}>\r\n", to), "RCPT", "250").await?;
+        command(&mut reader, "DATA\r\n".to_string(), "DATA", "354").await?;
+
+        let mut message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            from,
+            to,
+            encode_header(&email.subject)
+        );
+
+        // lines() already splits at every '\n', so dropping stray '\r's leaves no line breaks but
+        // the "\r\n"s added below
+        for line in email.body.lines().map(|line| line.replace('\r', "")) {
+            // Dot-stuffing, see RFC 5321, section 4.5.2
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(&line);
+            message.push_str("\r\n");
+        }
+
+        message.push_str(".\r\n");
+
+        command(&mut reader, message, "DATA", "250").await?;
+        command(&mut reader, "QUIT\r\n".to_string(), "QUIT", "221").await
+    }
+}
+
+/// Cheaply clonable handle to the [`Mailer`] configured for this instance, meant to be put into
+/// rocket's managed state
+#[derive(Clone)]
+pub struct MailerHandle(Arc<dyn Mailer>);
+
+impl MailerHandle {
+    pub fn new(mailer: impl Mailer + 'static) -> Self {
+        MailerHandle(Arc::new(mailer))
+    }
+
+    /// Uses an [`SmtpMailer`] if an SMTP server is configured, and a [`LogMailer`] otherwise
+    pub fn from_config() -> Self {
+        match config::smtp_server() {
+            Some(server) => MailerHandle::new(SmtpMailer::new(server, config::mail_sender())),
+            None => MailerHandle::new(LogMailer),
+        }
+    }
+
+    /// Sends the given email in the background. Failures are logged, but otherwise ignored
+    pub fn dispatch(&self, email: Email) {
+        let mailer = Arc::clone(&self.0);
+
+        rocket::tokio::spawn(async move {
+            if let Err(err) = mailer.send(&email).await {
+                error!("Failed to send email '{}' to {}: {:?}", email.subject, email.to, err);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_header;
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Your record on Bloodbath"), "Your record on Bloodbath");
+        assert_eq!(
+            encode_header("Bloodbath\r\n.\r\nRCPT TO:<x@example.com>"),
+            "Bloodbath.RCPT TO:<x@example.com>"
+        );
+        assert_eq!(encode_header("Déjà vu"), "=?utf-8?Q?D=C3=A9j=C3=A0_vu?=");
+
+        let long = encode_header(&"é".repeat(20));
+
+        assert_eq!(long.split("\r\n ").count(), 2);
+        assert!(long.split("\r\n ").all(|word| word.len() <= 75));
+    }
+}
@@ -0,0 +1,147 @@
+//! Module providing a fairing that canonicalizes request paths
+//!
+//! Pointercrate's convention is for every path to end in a slash, unless its last segment names a
+//! file (that is, contains a dot, like `/static/core/main.css`). Requests whose path deviates from
+//! this convention, or contains empty segments (e.g. `/api//v1/demons`), are answered with a `308
+//! PERMANENT REDIRECT` to the canonical path, which preserves both method and body.
+//!
+//! Additionally, percent-encoded unreserved characters (e.g. `%62` for `b`) are decoded, and all
+//! remaining percent-encodings are upper-cased, so that `/Blood%62ath/` and `/Bloodbath/` reach
+//! endpoints (and the response cache) as the same path. This happens transparently, without a
+//! redirect.
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{uri::Origin, Method},
+    request::{FromRequest, Outcome},
+    response::Redirect,
+    routes, uri, Build, Data, Request, Rocket,
+};
+
+/// Rocket fairing enforcing the canonical path format described in the [module
+/// documentation](self).
+///
+/// Uses the same approach as the [maintenance fairing](crate::maintenance::MaintenanceFairing) to
+/// issue redirects. Needs to be attached before any other fairing inspecting request URIs.
+#[derive(Default)]
+pub struct UriNormalizationFairing;
+
+/// The location a request is redirected to by [`UriNormalizationFairing`]
+#[derive(Clone)]
+struct CanonicalLocation(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CanonicalLocation {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request.local_cache(|| CanonicalLocation(None)).clone())
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// Decodes percent-encoded unreserved characters and upper-cases all other percent-encodings
+fn normalize_segment(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut normalized = String::with_capacity(segment.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let encoded = bytes
+            .get(idx + 1..idx + 3)
+            .filter(|_| bytes[idx] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match encoded {
+            Some(byte) if is_unreserved(byte) => normalized.push(byte as char),
+            Some(byte) => normalized.push_str(&format!("%{:02X}", byte)),
+            None => {
+                let next = segment[idx + 1..].find('%').map_or(segment.len(), |offset| idx + 1 + offset);
+
+                normalized.push_str(&segment[idx..next]);
+                idx = next;
+                continue;
+            },
+        }
+
+        idx += 3;
+    }
+
+    normalized
+}
+
+/// Removes empty segments from the given path and enforces pointercrate's trailing slash
+/// convention. Does not touch percent-encodings.
+pub fn canonical_path(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+    match segments.last() {
+        None => "/".to_string(),
+        Some(last) if last.contains('.') => format!("/{}", segments.join("/")),
+        Some(_) => format!("/{}/", segments.join("/")),
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for UriNormalizationFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "URI Normalization",
+            kind: Kind::Ignite | Kind::Request,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        Ok(rocket.mount("/", routes![canonical_redirect]))
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let path = request.uri().path().as_str();
+        let normalized = path.split('/').map(normalize_segment).collect::<Vec<_>>().join("/");
+        let canonical = canonical_path(&normalized);
+        let query = request.uri().query().map(|query| format!("?{}", query)).unwrap_or_default();
+
+        if canonical != normalized {
+            request.local_cache(|| CanonicalLocation(Some(format!("{}{}", canonical, query))));
+            request.set_uri(uri!("/canonical-redirect"));
+            request.set_method(Method::Get);
+        } else if normalized != path {
+            match Origin::parse_owned(format!("{}{}", normalized, query)) {
+                Ok(origin) => request.set_uri(origin),
+                Err(err) => log::warn!("Failed to normalize request URI {}: {:?}", request.uri(), err),
+            }
+        }
+    }
+}
+
+#[rocket::get("/canonical-redirect")]
+fn canonical_redirect(location: CanonicalLocation) -> Option<Redirect> {
+    location.0.map(Redirect::permanent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_path, normalize_segment};
+
+    #[test]
+    fn test_canonical_path() {
+        assert_eq!(canonical_path(""), "/");
+        assert_eq!(canonical_path("//"), "/");
+        assert_eq!(canonical_path("/api/v2/demons"), "/api/v2/demons/");
+        assert_eq!(canonical_path("/api//v2/demons/"), "/api/v2/demons/");
+        assert_eq!(canonical_path("/static/core/main.css/"), "/static/core/main.css");
+    }
+
+    #[test]
+    fn test_normalize_segment() {
+        assert_eq!(normalize_segment("Blood%62ath"), "Bloodbath");
+        assert_eq!(normalize_segment("Bloodbath%2f"), "Bloodbath%2F");
+        assert_eq!(normalize_segment("Slaughterhouse%20"), "Slaughterhouse%20");
+        assert_eq!(normalize_segment("100%"), "100%");
+        assert_eq!(normalize_segment("%zz"), "%zz");
+    }
+}
@@ -1,14 +1,85 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::OnceLock,
+};
 
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use pointercrate_core::{
     error::CoreError,
-    pagination::{Paginatable, PaginationParameters, PaginationQuery},
+    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery},
+    pool::ReadConnection,
+    redact::{Redact, Redacted, ViewContext},
 };
 use rocket::serde::json::Json;
-use sqlx::PgConnection;
+use serde::{Deserialize, Serialize};
 
 use crate::response::Response2;
 
+/// Version of the pagination cursor format. Cursors of any other version are rejected, meaning
+/// clients have to restart pagination after the format changes
+const CURSOR_VERSION: u8 = 1;
+
+/// The contents of a pagination cursor.
+///
+/// Links in the `Links` header do not expose pagination parameters directly. Instead, they consist of a
+/// single `cursor` parameter, which is a signed token wrapping the complete query (keyset position,
+/// sorting and filters) of the linked page. Clients should treat cursors as opaque.
+#[derive(Serialize, Deserialize)]
+struct CursorClaims {
+    #[serde(rename = "v")]
+    version: u8,
+
+    /// The endpoint this cursor is valid for
+    endpoint: String,
+
+    /// The url-encoded query of the linked page
+    query: String,
+}
+
+/// The key cursors are signed with. Read from disk only once, since every paginated response signs
+/// several cursors
+static CURSOR_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn cursor_secret() -> &'static [u8] {
+    CURSOR_SECRET.get_or_init(pointercrate_core::config::secret)
+}
+
+fn normalize_endpoint(endpoint: &str) -> &str {
+    endpoint.trim_end_matches('/')
+}
+
+/// Wraps the given url-encoded pagination query into a cursor for the given endpoint
+pub fn encode_cursor(endpoint: &str, query: &str) -> Result<String, CoreError> {
+    let claims = CursorClaims {
+        version: CURSOR_VERSION,
+        endpoint: normalize_endpoint(endpoint).to_string(),
+        query: query.to_string(),
+    };
+
+    jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(cursor_secret()))
+        .map_err(|err| CoreError::internal_server_error(format!("Failed to sign pagination cursor: {:?}", err)))
+}
+
+/// Unwraps the url-encoded pagination query from a cursor generated via [`encode_cursor`].
+///
+/// Returns `None` if the cursor was tampered with, is of an outdated format, or was generated for a
+/// different endpoint
+pub fn decode_cursor(endpoint: &str, cursor: &str) -> Option<String> {
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+    validation.required_spec_claims = HashSet::new();
+
+    let claims = jsonwebtoken::decode::<CursorClaims>(cursor, &DecodingKey::from_secret(cursor_secret()), &validation)
+        .ok()?
+        .claims;
+
+    if claims.version != CURSOR_VERSION || claims.endpoint != normalize_endpoint(endpoint) {
+        return None;
+    }
+
+    Some(claims.query)
+}
+
 #[derive(Debug)]
 pub struct LinksBuilder {
     endpoint: &'static str,
@@ -91,31 +162,80 @@ impl LinksBuilder {
                     ))
                 })?;
 
-            buf += &format!("<{}?{}>; rel={}", self.endpoint, query_string, rel);
+            buf += &format!(
+                "<{}?cursor={}>; rel={}",
+                self.endpoint,
+                encode_cursor(self.endpoint, &query_string)?,
+                rel
+            );
         }
 
         Ok(buf)
     }
 }
 
+async fn page_and_links<Q: PaginationQuery, P: Paginatable<Q>>(
+    endpoint: &'static str, query: Q, connection: &mut impl ReadConnection,
+) -> Result<(Vec<P>, String), CoreError> {
+    let connection = &mut connection.reader();
+    let requested = query.parameters();
+    let resolved = query.with_parameters(requested.resolve(pointercrate_core::config::pagination_limits(endpoint))?);
+
+    let (objects, context) = P::page(&resolved, &mut *connection).await?;
+    let first_and_last = match P::keyset(&resolved) {
+        Some(keyset) if keyset.is_compound() => None,
+        _ => P::first_and_last(connection).await?,
+    };
+
+    let page_bounds = match (objects.first(), objects.last()) {
+        (Some(first), Some(last)) => Some((first.pagination_id(), last.pagination_id())),
+        _ => None,
+    };
+
+    // The links are generated from the query as requested, so that an omitted limit stays omitted
+    // (and subsequent pages keep using the endpoint's default)
+    let links = page_links(endpoint, &query, page_bounds, context, first_and_last)?;
+
+    Ok((objects, links))
+}
+
 pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
-    endpoint: &'static str, query: Q, connection: &mut PgConnection,
+    endpoint: &'static str, query: Q, connection: &mut impl ReadConnection,
 ) -> Result<Response2<Json<Vec<P>>>, CoreError> {
-    let parameters = query.parameters();
+    let (objects, links) = page_and_links(endpoint, query, connection).await?;
+
+    Ok(Response2::json(objects).with_header("Links", links))
+}
+
+/// Like [`pagination_response`], but redacts all objects on the page for the given [`ViewContext`]
+pub async fn redacted_pagination_response<Q: PaginationQuery, P: Paginatable<Q> + Redact>(
+    endpoint: &'static str, query: Q, context: &ViewContext, connection: &mut impl ReadConnection,
+) -> Result<Response2<Json<Vec<Redacted<P>>>>, CoreError> {
+    let (objects, links) = page_and_links::<Q, P>(endpoint, query, connection).await?;
 
-    parameters.validate()?;
+    let objects = objects.into_iter().map(|object| Redacted::new(object, context)).collect();
 
-    let (objects, context) = P::page(&query, &mut *connection).await?;
+    Ok(Response2::json(objects).with_header("Links", links))
+}
 
+/// Computes the value of the `Links` header for a page returned by [`Paginatable::page`].
+///
+/// `page_bounds` are the pagination ids of the first and last object on the page (`None` if the page
+/// is empty), and `first_and_last` is the result of [`Paginatable::first_and_last`]. Separate from
+/// [`pagination_response`] so that it can be tested without a database.
+pub fn page_links<Q: PaginationQuery>(
+    endpoint: &'static str, query: &Q, page_bounds: Option<(i32, i32)>, context: PageContext, first_and_last: Option<(i32, i32)>,
+) -> Result<String, CoreError> {
+    let parameters = query.parameters();
     let mut links = LinksBuilder::new(endpoint);
 
-    if let Some((min_id, max_id)) = P::first_and_last(connection).await? {
+    if let Some((min_id, max_id)) = first_and_last {
         links = links.with_first(min_id - 1).with_last(max_id + 1);
     }
 
     if context.has_next() {
-        let after = match objects.last() {
-            Some(obj) => obj.pagination_id(),
+        let after = match page_bounds {
+            Some((_, last)) => last,
             None => {
                 // If there exists a next page, but this page is empty, then
                 // we must have had a `before` value set (e.g. this is a page before the first object matching the pagination conditions).
@@ -135,8 +255,8 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
     }
 
     if context.has_previous() {
-        let before = match objects.first() {
-            Some(obj) => obj.pagination_id(),
+        let before = match page_bounds {
+            Some((first, _)) => first,
             None => {
                 parameters.after.ok_or_else(|| {
                     CoreError::internal_server_error(format!(
@@ -152,15 +272,15 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
         links = links.with_previous(before);
     };
 
-    Ok(Response2::json(objects).with_header("Links", links.generate(&query)?))
+    links.generate(query)
 }
 
 #[cfg(test)]
 mod tests {
-    use pointercrate_core::pagination::{PaginationParameters, PaginationQuery};
+    use pointercrate_core::pagination::{PageContext, PaginationParameters, PaginationQuery};
     use serde::Serialize;
 
-    use super::LinksBuilder;
+    use super::{decode_cursor, encode_cursor, page_links, LinksBuilder};
 
     #[derive(Debug, Default, Serialize)]
     struct DummyQuery(PaginationParameters);
@@ -175,6 +295,33 @@ mod tests {
         }
     }
 
+    /// Replaces all cursors in the given `Links` header with the queries they wrap
+    fn decode_links(header: &str) -> String {
+        header
+            .split(',')
+            .map(|link| {
+                let (url, rel) = link.split_once("; rel=").unwrap();
+                let (endpoint, cursor) = url.trim_start_matches('<').trim_end_matches('>').split_once("?cursor=").unwrap();
+
+                format!("<{}?{}>; rel={}", endpoint, decode_cursor(endpoint, cursor).unwrap(), rel)
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = encode_cursor("/dummies/", "after=10&limit=5").unwrap();
+
+        assert_eq!(decode_cursor("/dummies", &cursor).as_deref(), Some("after=10&limit=5"));
+        assert_eq!(decode_cursor("/others/", &cursor), None);
+
+        let mut tampered = cursor.clone();
+        tampered.insert(cursor.len() / 2, 'x');
+
+        assert_eq!(decode_cursor("/dummies/", &tampered), None);
+    }
+
     #[test]
     fn test_links_builder() {
         let links_header = LinksBuilder::new("/dummies")
@@ -186,8 +333,53 @@ mod tests {
             .unwrap();
 
         assert_eq!(
-            links_header,
+            decode_links(&links_header),
             "</dummies?after=0>; rel=first,</dummies?before=1971>; rel=last,</dummies?after=2>; rel=next,</dummies?before=100>; rel=prev"
         );
     }
+
+    #[test]
+    fn test_page_links_middle_page() {
+        let query = DummyQuery(PaginationParameters {
+            after: Some(10),
+            ..Default::default()
+        });
+
+        let links = page_links("/dummies", &query, Some((11, 60)), PageContext::HasPreviousAndNext, Some((1, 100))).unwrap();
+
+        assert_eq!(
+            decode_links(&links),
+            "</dummies?after=0>; rel=first,</dummies?before=101>; rel=last,</dummies?after=60>; rel=next,</dummies?before=11>; rel=prev"
+        );
+    }
+
+    #[test]
+    fn test_page_links_empty_page() {
+        let query = DummyQuery(PaginationParameters {
+            before: Some(5),
+            ..Default::default()
+        });
+
+        // An empty page before the first object still links to the objects after it
+        let links = page_links("/dummies", &query, None, PageContext::HasNext, Some((10, 20))).unwrap();
+
+        assert_eq!(
+            decode_links(&links),
+            "</dummies?after=9>; rel=first,</dummies?before=21>; rel=last,</dummies?after=4>; rel=next"
+        );
+    }
+
+    #[test]
+    fn test_page_links_inconsistent_context() {
+        // A page cannot claim a next page exists without either objects or a `before` parameter
+        assert!(page_links("/dummies", &DummyQuery::default(), None, PageContext::HasNext, None).is_err());
+    }
+
+    #[test]
+    fn test_page_links_empty_table() {
+        assert_eq!(
+            page_links("/dummies", &DummyQuery::default(), None, PageContext::Standalone, None).unwrap(),
+            ""
+        );
+    }
 }
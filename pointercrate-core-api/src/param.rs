@@ -0,0 +1,41 @@
+//! Module containing path parameter types shared between API crates
+
+use rocket::request::FromParam;
+use std::convert::Infallible;
+
+/// A path segment identifying an object either by its ID or by its name, for endpoints such as
+/// `/api/v2/demons/<demon>/` that accept both `/api/v2/demons/1/` and `/api/v2/demons/bloodbath/`.
+///
+/// Segments that parse as an integer are always treated as IDs, so objects with purely numeric
+/// names can only be addressed by ID (or via the respective lookup endpoint). Resolving a name is up
+/// to the endpoint, which is expected to do so case-insensitively and error out if the name is
+/// ambiguous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdOrName<'a> {
+    Id(i32),
+    Name(&'a str),
+}
+
+impl<'a> FromParam<'a> for IdOrName<'a> {
+    type Error = Infallible;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        Ok(match param.parse() {
+            Ok(id) => IdOrName::Id(id),
+            Err(_) => IdOrName::Name(param),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdOrName;
+    use rocket::request::FromParam;
+
+    #[test]
+    fn test_id_or_name() {
+        assert_eq!(IdOrName::from_param("1").unwrap(), IdOrName::Id(1));
+        assert_eq!(IdOrName::from_param("bloodbath").unwrap(), IdOrName::Name("bloodbath"));
+        assert_eq!(IdOrName::from_param("1bloodbath").unwrap(), IdOrName::Name("1bloodbath"));
+    }
+}
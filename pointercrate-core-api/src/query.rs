@@ -3,10 +3,26 @@ use rocket::{
     request::{FromRequest, Outcome},
     Request,
 };
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, Error};
+
+use crate::pagination::decode_cursor;
 
 pub struct Query<T: DeserializeOwned>(pub T);
 
+/// If the given query consists of a pagination cursor (see [`decode_cursor`]), returns the query
+/// wrapped by the cursor. Cursors cannot be combined with other query parameters.
+fn unwrap_cursor(endpoint: &str, query: &str) -> Result<Option<String>, serde_urlencoded::de::Error> {
+    let parameters: Vec<(String, String)> = serde_urlencoded::from_str(query)?;
+
+    match parameters.iter().find(|(key, _)| key == "cursor") {
+        None => Ok(None),
+        Some(_) if parameters.len() > 1 => Err(Error::custom("pagination cursors cannot be combined with other query parameters")),
+        Some((_, cursor)) => decode_cursor(endpoint, cursor)
+            .map(Some)
+            .ok_or_else(|| Error::custom("invalid or outdated pagination cursor")),
+    }
+}
+
 #[rocket::async_trait]
 impl<'r, T: DeserializeOwned> FromRequest<'r> for Query<T> {
     type Error = serde_urlencoded::de::Error;
@@ -14,9 +30,14 @@ impl<'r, T: DeserializeOwned> FromRequest<'r> for Query<T> {
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         match request.uri().query() {
             None => Outcome::Success(Query(serde_urlencoded::from_str("").unwrap())),
-            Some(query) => match serde_urlencoded::from_str(query.as_str()) {
-                Ok(t) => Outcome::Success(Query(t)),
-                Err(err) => Outcome::Error((Status::BadRequest, err)),
+            Some(query) => {
+                let result = unwrap_cursor(request.uri().path().as_str(), query.as_str())
+                    .and_then(|unwrapped| serde_urlencoded::from_str(unwrapped.as_deref().unwrap_or(query.as_str())));
+
+                match result {
+                    Ok(t) => Outcome::Success(Query(t)),
+                    Err(err) => Outcome::Error((Status::BadRequest, err)),
+                }
             },
         }
     }
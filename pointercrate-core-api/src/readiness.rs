@@ -0,0 +1,38 @@
+//! Readiness endpoint for load balancers and deployment tooling
+
+use pointercrate_core::pool::{PointercratePool, PoolStatistics};
+use rocket::{http::Status, serde::json::Json, State};
+use serde::Serialize;
+
+use crate::{error::Result, response::Response2};
+
+#[derive(Serialize)]
+pub struct Readiness {
+    pub applied_migrations: Vec<i64>,
+    pub pending_migrations: Vec<i64>,
+
+    /// Current utilization of the database connection pool, for monitoring
+    pub pool: PoolStatistics,
+}
+
+/// Reports the applied and pending database migrations, as well as the utilization of the database
+/// connection pool.
+///
+/// Responds with `503 SERVICE UNAVAILABLE` while migrations are still pending, as this version of
+/// pointercrate might not work against an outdated schema.
+#[rocket::get("/ready")]
+pub async fn ready(pool: &State<PointercratePool>) -> Result<Response2<Json<Readiness>>> {
+    let readiness = Readiness {
+        applied_migrations: pool.applied_migrations().await?,
+        pending_migrations: pool.pending_migrations().await?,
+        pool: pool.statistics(),
+    };
+
+    let status = if readiness.pending_migrations.is_empty() {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+
+    Ok(Response2::json(readiness).status(status))
+}
@@ -1,12 +1,15 @@
 use crate::etag::Tagged;
+use chrono::{DateTime, FixedOffset, Utc};
 use maud::{html, DOCTYPE};
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{announcement::AnnouncementCache, etag::Taggable};
 use pointercrate_core_pages::{
+    announcement::announcement_banners,
     head::{Head, HeadLike},
     PageConfiguration, PageFragment,
 };
 use rocket::{
     http::{ContentType, Header, Status},
+    request::{FromRequest, Outcome},
     response::Responder,
     serde::json::Json,
     Request, Response,
@@ -16,6 +19,45 @@ use std::{borrow::Cow, io::Cursor};
 
 pub struct Page(PageFragment);
 
+/// Preferences of the user a page is rendered for
+///
+/// Whatever authenticates a page request is expected to put the user's preferences into the
+/// request-local cache (see [`Request::local_cache`]). Pages rendered for anonymous users use the
+/// defaults. Handlers can access them via request guard, which needs to come after the
+/// authentication guard in the handler's parameter list.
+#[derive(Debug, Default, Clone)]
+pub struct ViewPreferences {
+    /// Language tag to use instead of `en`
+    pub locale: Option<String>,
+
+    /// Value of the `data-theme` attribute of the `html` element, for stylesheets to select on
+    pub theme: Option<String>,
+
+    /// Offset from UTC timestamps are displayed in. [`None`] means UTC
+    pub utc_offset: Option<FixedOffset>,
+}
+
+impl ViewPreferences {
+    /// The offset from UTC timestamps are displayed in, falling back to UTC itself
+    pub fn offset(&self) -> FixedOffset {
+        self.utc_offset.unwrap_or(FixedOffset::east_opt(0).unwrap())
+    }
+
+    /// Converts the given point in time to the user's wall clock time
+    pub fn local_time(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.offset())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r ViewPreferences {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request.local_cache(ViewPreferences::default))
+    }
+}
+
 impl Page {
     pub fn new(fragment: impl Into<PageFragment>) -> Self {
         Page(fragment.into())
@@ -33,10 +75,17 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Page {
         let page_config = request.rocket().state::<PageConfiguration>().ok_or(Status::InternalServerError)?;
 
         let fragment = self.0;
+        let preferences = request.local_cache(ViewPreferences::default);
+        let locale = preferences.locale.as_deref().unwrap_or("en");
+        let announcements = request
+            .rocket()
+            .state::<AnnouncementCache>()
+            .map(AnnouncementCache::active)
+            .unwrap_or_default();
 
         let rendered_fragment = html! {
             (DOCTYPE)
-            html lang="en" prefix="og: http://opg.me/ns#" {
+            html lang=(locale) data-theme=[preferences.theme.as_deref()] prefix="og: http://opg.me/ns#" {
                 head {
                     (page_config.head)
                     (fragment.head)
@@ -45,6 +94,7 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Page {
                     // target this element to get background image
                     div style={"width: 100%;height: 100%;position: fixed;top: 0;left: 0;background-size: cover;background-repeat: repeat-y;pointer-events: none; z-index:-1"} {}
 
+                    (announcement_banners(&announcements, preferences.offset()))
                     (page_config.nav_bar)
                     (fragment.body)
                     (page_config.footer)
@@ -96,12 +146,27 @@ impl<T> Response2<T> {
         self.status = status;
         self
     }
+
+    /// Allows this response to be cached for `max_age` seconds, tagged with the given (space
+    /// separated) surrogate keys. See the [`cache`](crate::cache) module
+    pub fn cache_for(self, max_age: u32, surrogate_keys: impl Into<Cow<'static, str>>) -> Self {
+        self.with_header("Cache-Control", format!("public, max-age={}", max_age))
+            .with_header("Surrogate-Key", surrogate_keys)
+    }
 }
 
 impl<'r, 'o: 'r, T: Responder<'r, 'o>> Responder<'r, 'o> for Response2<T> {
     fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
-        let mut response_builder = Response::build_from(self.content.respond_to(request)?);
-        response_builder.status(self.status);
+        let response = self.content.respond_to(request)?;
+        // Do not override statuses the wrapped responder chose itself (e.g. `304 NOT MODIFIED` from `Tagged`)
+        let status = if response.status() == Status::Ok {
+            self.status
+        } else {
+            response.status()
+        };
+
+        let mut response_builder = Response::build_from(response);
+        response_builder.status(status);
 
         for header in self.headers {
             response_builder.header(header);
@@ -0,0 +1,270 @@
+//! Module for accepting file uploads
+//!
+//! Uploaded files are written to a [`Storage`] backend, of which there currently only is
+//! [`LocalStorage`], which keeps them in a directory on the local file system. Which directory is
+//! used is determined by [`StorageHandle::from_config`].
+//!
+//! Files are uploaded as a field of a `multipart/form-data` body, read via [`MultipartFile`]. Each
+//! kind of upload has a dedicated body limit (see [`body_limits`], which needs to be put into
+//! rocket's configuration), instead of sharing rocket's `file` and `data-form` limits, which apply
+//! to all forms. Limits are enforced while the upload is still in progress, and uploads whose
+//! `Content-Length` already exceeds their limit are rejected before reading any of the body.
+
+use log::debug;
+use multer::{Constraints, Field, Multipart, SizeLimit};
+use pointercrate_core::{
+    config,
+    error::{CoreError, PointercrateError},
+};
+use rocket::{
+    data::{self, Limits, ToByteUnit},
+    futures::TryStreamExt,
+    http::Status,
+    tokio::{
+        fs::File,
+        io::{self as tokio_io, AsyncRead, AsyncReadExt, AsyncWriteExt},
+    },
+    Data, Request,
+};
+use std::{io, path::PathBuf, sync::Arc};
+use tokio_util::io::StreamReader;
+
+/// Limits on request body sizes, based on the `limits` section of the configuration
+///
+/// Next to rocket's `json` limit, this sets the `raw-footage` and `avatar` limits, which are only
+/// read by the respective upload endpoints.
+pub fn body_limits() -> Limits {
+    let limits = &config::get().limits;
+
+    Limits::default()
+        .limit("json", limits.json.bytes())
+        .limit("raw-footage", limits.raw_footage.bytes())
+        .limit("avatar", limits.avatar.bytes())
+}
+
+/// Fails with [`CoreError::UploadTooLarge`] if the request announces a body larger than `max_size`
+/// bytes
+fn check_content_length(request: &Request, max_size: u64) -> Result<(), CoreError> {
+    let announced_length = request
+        .headers()
+        .get_one("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok());
+
+    match announced_length {
+        Some(length) if length > max_size => Err(CoreError::UploadTooLarge { max_size }),
+        _ => Ok(()),
+    }
+}
+
+/// A file uploaded as a single field of a `multipart/form-data` request body
+///
+/// The file itself is only read once it is [stored](StorageHandle::store) or
+/// [read into memory](MultipartFile::bytes), and rejected as soon as it exceeds its limit.
+pub struct MultipartFile<'r> {
+    field: Field<'r>,
+    max_size: u64,
+}
+
+impl<'r> MultipartFile<'r> {
+    /// Finds the field with the given name in the request's `multipart/form-data` body, whose size
+    /// is bounded by the body limit of the given name (or rocket's default `file` limit, if no such
+    /// limit is configured)
+    pub async fn from_data(request: &'r Request<'_>, data: Data<'r>, limit: &str, field_name: &str) -> data::Outcome<'r, Self, CoreError> {
+        let max_size = request.limits().get(limit).unwrap_or(Limits::FILE).as_u64();
+
+        if let Err(err) = check_content_length(request, max_size) {
+            return data::Outcome::Error((Status::PayloadTooLarge, err));
+        }
+
+        let boundary = request
+            .content_type()
+            .filter(|content_type| content_type.is_form_data())
+            .and_then(|content_type| content_type.param("boundary"));
+
+        let Some(boundary) = boundary else {
+            return data::Outcome::Error((
+                Status::UnsupportedMediaType,
+                CoreError::UnsupportedMediaType {
+                    expected: "multipart/form-data",
+                },
+            ));
+        };
+
+        // Read one byte more than allowed, so that multer notices oversized bodies instead of
+        // seeing a truncated one
+        let stream = data.open((max_size + 1).bytes());
+        let constraints = Constraints::new().size_limit(SizeLimit::new().whole_stream(max_size));
+        let mut multipart = Multipart::with_reader_with_constraints(stream, boundary, constraints);
+
+        loop {
+            match multipart.next_field().await {
+                Ok(Some(field)) if field.name() == Some(field_name) => return data::Outcome::Success(MultipartFile { field, max_size }),
+                Ok(Some(_)) => continue,
+                Ok(None) => return data::Outcome::Error((Status::UnprocessableEntity, CoreError::UnprocessableEntity)),
+                Err(err) => {
+                    let err = multipart_error(&err, max_size);
+
+                    return data::Outcome::Error((Status::from_code(err.status_code()).unwrap(), err));
+                },
+            }
+        }
+    }
+
+    /// Reads the entire file into memory. Only meant for small files
+    pub async fn bytes(self) -> Result<Vec<u8>, CoreError> {
+        match self.field.bytes().await {
+            Ok(contents) => Ok(contents.to_vec()),
+            Err(err) => Err(multipart_error(&err, self.max_size)),
+        }
+    }
+}
+
+fn multipart_error(err: &multer::Error, max_size: u64) -> CoreError {
+    match err {
+        multer::Error::StreamSizeExceeded { .. } | multer::Error::FieldSizeExceeded { .. } => CoreError::UploadTooLarge { max_size },
+        _ => {
+            debug!("Rejecting malformed multipart upload: {}", err);
+
+            CoreError::UnprocessableEntity
+        },
+    }
+}
+
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores the contents of the given reader under the given key, replacing any file previously
+    /// stored under it. If reading fails, any file previously stored under the key is kept
+    async fn store(&self, key: &str, file: &mut (dyn AsyncRead + Send + Unpin)) -> io::Result<()>;
+
+    /// Stores the given contents under the given key, replacing any file previously stored under it
+    async fn write(&self, key: &str, contents: &[u8]) -> io::Result<()>;
+
+    /// Opens the file stored under the given key, if any
+    async fn open(&self, key: &str) -> io::Result<Option<Box<dyn AsyncRead + Send + Unpin>>>;
+}
+
+/// [`Storage`] backend keeping files in a directory on the local file system
+pub struct LocalStorage {
+    directory: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        LocalStorage {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Keys are chosen by pointercrate, never by users, but better be safe
+        self.directory.join(
+            key.split('/')
+                .filter(|segment| !segment.is_empty() && *segment != "..")
+                .collect::<PathBuf>(),
+        )
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn store(&self, key: &str, file: &mut (dyn AsyncRead + Send + Unpin)) -> io::Result<()> {
+        let path = self.path(key);
+        let partial_path = path.with_extension("part");
+
+        if let Some(parent) = path.parent() {
+            rocket::tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut partial = File::create(&partial_path).await?;
+
+        let copied = match tokio_io::copy(file, &mut partial).await {
+            Ok(_) => partial.flush().await,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = copied {
+            let _ = rocket::tokio::fs::remove_file(&partial_path).await;
+
+            return Err(err);
+        }
+
+        rocket::tokio::fs::rename(partial_path, path).await
+    }
+
+    async fn write(&self, key: &str, contents: &[u8]) -> io::Result<()> {
+        let path = self.path(key);
+
+        if let Some(parent) = path.parent() {
+            rocket::tokio::fs::create_dir_all(parent).await?;
+        }
+
+        rocket::tokio::fs::write(path, contents).await
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Option<Box<dyn AsyncRead + Send + Unpin>>> {
+        match File::open(self.path(key)).await {
+            Ok(file) => Ok(Some(Box::new(file))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Cheaply clonable handle to the [`Storage`] configured for this instance, meant to be put into
+/// rocket's managed state
+#[derive(Clone)]
+pub struct StorageHandle(Arc<dyn Storage>);
+
+impl StorageHandle {
+    pub fn new(storage: impl Storage + 'static) -> Self {
+        StorageHandle(Arc::new(storage))
+    }
+
+    pub fn from_config() -> Self {
+        StorageHandle::new(LocalStorage::new(config::storage_directory()))
+    }
+
+    /// Streams the given upload into storage. Fails with [`CoreError::UploadTooLarge`] if the
+    /// upload exceeds its limit while being stored
+    pub async fn store(&self, key: &str, file: MultipartFile<'_>) -> Result<(), CoreError> {
+        let max_size = file.max_size;
+        let mut reader = StreamReader::new(file.field.map_err(io::Error::other));
+
+        self.0
+            .store(key, &mut reader)
+            .await
+            .map_err(|err| match err.get_ref().and_then(|inner| inner.downcast_ref::<multer::Error>()) {
+                Some(multer_err) => multipart_error(multer_err, max_size),
+                None => CoreError::internal_server_error(format!("Failed to store upload '{}': {:?}", key, err)),
+            })
+    }
+
+    pub async fn write(&self, key: &str, contents: &[u8]) -> Result<(), CoreError> {
+        self.0
+            .write(key, contents)
+            .await
+            .map_err(|err| CoreError::internal_server_error(format!("Failed to store upload '{}': {:?}", key, err)))
+    }
+
+    pub async fn open(&self, key: &str) -> Result<Option<Box<dyn AsyncRead + Send + Unpin>>, CoreError> {
+        self.0
+            .open(key)
+            .await
+            .map_err(|err| CoreError::internal_server_error(format!("Failed to open upload '{}': {:?}", key, err)))
+    }
+
+    /// Reads the entire file stored under the given key into memory. Only meant for small files
+    pub async fn read(&self, key: &str) -> Result<Option<Vec<u8>>, CoreError> {
+        let Some(mut file) = self.open(key).await? else {
+            return Ok(None);
+        };
+
+        let mut contents = Vec::new();
+
+        file.read_to_end(&mut contents)
+            .await
+            .map_err(|err| CoreError::internal_server_error(format!("Failed to read upload '{}': {:?}", key, err)))?;
+
+        Ok(Some(contents))
+    }
+}
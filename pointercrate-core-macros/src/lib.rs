@@ -0,0 +1,356 @@
+//! Procedural macros used by pointercrate's model and API crates
+//!
+//! These are re-exported from `pointercrate_core` and `pointercrate_core_api` (next to the traits
+//! they implement), which is where their documentation lives. The generated code refers to those
+//! crates by name, so crates using the macros need to depend on them directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    meta, parse_macro_input, Data, DeriveInput, Error, Expr, ExprLit, Fields, GenericArgument, Ident, ItemFn, Lit, LitStr, Meta, Path,
+    PathArguments, Type,
+};
+
+/// See `pointercrate_core::patch`
+#[proc_macro_derive(Patchable, attributes(patch))]
+pub fn derive_patchable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    patchable(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// A single field of a generated patch struct
+struct PatchField {
+    name: Ident,
+
+    /// The type of the values clients send for this field, without the `Option` wrapping(s)
+    ty: Type,
+
+    /// Whether clients can reset this field by setting it to `null`
+    nullable: bool,
+
+    /// The permission required to set this field, if any. Defaults to the one set on the object
+    permission: Option<Path>,
+}
+
+fn patchable(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let (patch_name, patch_fields) = parse_patchable(&input)?;
+    let target = &input.ident;
+    let vis = &input.vis;
+
+    let declarations = patch_fields.iter().map(|field| {
+        let PatchField { name, ty, .. } = field;
+
+        if field.nullable {
+            quote! {
+                #[serde(default, deserialize_with = "::pointercrate_core::util::nullable")]
+                #[allow(clippy::option_option)]
+                pub #name: ::std::option::Option<::std::option::Option<#ty>>
+            }
+        } else {
+            quote! {
+                #[serde(default, deserialize_with = "::pointercrate_core::util::non_nullable")]
+                pub #name: ::std::option::Option<#ty>
+            }
+        }
+    });
+
+    let names: Vec<_> = patch_fields.iter().map(|field| &field.name).collect();
+    let name_strings: Vec<_> = names.iter().map(|name| name.to_string()).collect();
+
+    let permission_checks = patch_fields.iter().filter_map(|field| {
+        let name = &field.name;
+
+        field.permission.as_ref().map(|permission| {
+            quote! {
+                if self.#name.is_some() && !permissions.contains(&#permission) {
+                    permissions.push(#permission);
+                }
+            }
+        })
+    });
+
+    let doc = format!("A patch for [`{}`], generated via `#[derive(Patchable)]`", target);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Default, Clone, ::serde::Deserialize)]
+        #vis struct #patch_name {
+            #(#declarations,)*
+        }
+
+        impl ::pointercrate_core::patch::Patch<#target> for #patch_name {
+            fn modified_fields(&self) -> ::std::vec::Vec<&'static str> {
+                let mut fields = ::std::vec::Vec::new();
+
+                #(
+                    if self.#names.is_some() {
+                        fields.push(#name_strings);
+                    }
+                )*
+
+                fields
+            }
+
+            fn is_empty(&self) -> bool {
+                #(self.#names.is_none() &&)* true
+            }
+
+            fn required_permissions(&self) -> ::std::vec::Vec<::pointercrate_core::permission::Permission> {
+                #[allow(unused_mut)]
+                let mut permissions = ::std::vec::Vec::new();
+
+                #(#permission_checks)*
+
+                permissions
+            }
+        }
+    })
+}
+
+/// The name of the patch struct to generate for the given object, and the fields it should have
+fn parse_patchable(input: &DeriveInput) -> syn::Result<(Ident, Vec<PatchField>)> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Patchable cannot be derived for generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Patchable can only be derived for structs with named fields",
+                ))
+            },
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "Patchable can only be derived for structs")),
+    };
+
+    let mut patch_name = format_ident!("Patch{}", input.ident);
+    let mut default_permission: Option<Path> = None;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("patch")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                patch_name = meta.value()?.parse()?;
+            } else if meta.path.is_ident("permission") {
+                default_permission = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown patch option, expected `name` or `permission`"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let mut patch_fields = Vec::new();
+
+    for field in fields {
+        // Fields are named, see above
+        let ident = field.ident.as_ref().unwrap();
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("patch")) {
+            let mut patch_field = PatchField {
+                name: ident.clone(),
+                ty: option_inner(&field.ty).unwrap_or(&field.ty).clone(),
+                nullable: option_inner(&field.ty).is_some(),
+                permission: default_permission.clone(),
+            };
+
+            // A plain `#[patch]` makes the field patchable with all defaults
+            if !matches!(attr.meta, Meta::Path(_)) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        patch_field.name = meta.value()?.parse()?;
+                    } else if meta.path.is_ident("ty") {
+                        patch_field.ty = meta.value()?.parse()?;
+                    } else if meta.path.is_ident("permission") {
+                        patch_field.permission = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("non_nullable") {
+                        patch_field.nullable = false;
+                    } else {
+                        return Err(meta.error("unknown patch option, expected one of `rename`, `ty`, `permission` or `non_nullable`"));
+                    }
+
+                    Ok(())
+                })?;
+            }
+
+            patch_fields.push(patch_field);
+        }
+    }
+
+    Ok((patch_name, patch_fields))
+}
+
+/// The `T` in a field of type `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// See `pointercrate_core_api::documentation`
+#[proc_macro_attribute]
+pub fn documented(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut example: Option<LitStr> = None;
+    let parser = meta::parser(|meta| {
+        if meta.path.is_ident("example") {
+            example = Some(meta.value()?.parse()?);
+
+            Ok(())
+        } else {
+            Err(meta.error("unknown documentation option, expected `example`"))
+        }
+    });
+
+    parse_macro_input!(args with parser);
+
+    let handler = parse_macro_input!(input as ItemFn);
+
+    documented_route(handler, example).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn documented_route(handler: ItemFn, example: Option<LitStr>) -> syn::Result<TokenStream2> {
+    let lines: Vec<_> = handler
+        .attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(doc) if doc.path.is_ident("doc") => match &doc.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(line), .. }) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    // Doc comments (`/// text`) become `#[doc = " text"]`, so strip the space after the slashes
+    let description = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+
+    if description.is_empty() {
+        return Err(Error::new_spanned(
+            &handler.sig.ident,
+            "documented routes need a doc comment describing them",
+        ));
+    }
+
+    let example = match example {
+        Some(example) => quote! { ::std::option::Option::Some(#example) },
+        None => quote! { ::std::option::Option::None },
+    };
+
+    // Rocket's route attributes generate a struct with the same name as the handler, which is what
+    // `routes!` gets passed
+    let name = &handler.sig.ident;
+
+    Ok(quote! {
+        #handler
+
+        impl ::pointercrate_core_api::documentation::Documented for #name {
+            const DOCUMENTATION: ::pointercrate_core_api::documentation::RouteDocumentation =
+                ::pointercrate_core_api::documentation::RouteDocumentation {
+                    description: #description,
+                    example: #example,
+                };
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_patchable;
+    use quote::ToTokens;
+    use syn::{parse_quote, DeriveInput};
+
+    fn permissions(input: DeriveInput) -> Vec<(String, Option<String>)> {
+        let (_, fields) = parse_patchable(&input).unwrap();
+
+        fields
+            .into_iter()
+            .map(|field| {
+                (
+                    field.name.to_string(),
+                    field.permission.map(|permission| permission.into_token_stream().to_string()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_field_without_permission() {
+        let input = parse_quote! {
+            struct Object {
+                #[patch]
+                a: i32,
+
+                #[patch(permission = ADMINISTRATOR)]
+                b: i32,
+            }
+        };
+
+        assert_eq!(
+            permissions(input),
+            vec![("a".to_string(), None), ("b".to_string(), Some("ADMINISTRATOR".to_string()))]
+        );
+    }
+
+    // Endpoints such as the player PATCH endpoint rely on every field requiring the object's
+    // permission, including fields added later without an explicit `permission`
+    #[test]
+    fn test_object_permission_applies_to_all_fields() {
+        let input = parse_quote! {
+            #[patch(permission = LIST_HELPER)]
+            struct Player {
+                #[patch(rename = name, ty = String)]
+                #[patch(rename = banned, ty = bool)]
+                base: DatabasePlayer,
+
+                score: f64,
+
+                #[patch(ty = String)]
+                #[patch(rename = subdivision, ty = String)]
+                nationality: Option<Nationality>,
+
+                #[patch]
+                added_later: Option<i32>,
+
+                #[patch(permission = LIST_ADMINISTRATOR)]
+                overridden: bool,
+            }
+        };
+
+        assert_eq!(
+            permissions(input),
+            vec![
+                ("name".to_string(), Some("LIST_HELPER".to_string())),
+                ("banned".to_string(), Some("LIST_HELPER".to_string())),
+                ("nationality".to_string(), Some("LIST_HELPER".to_string())),
+                ("subdivision".to_string(), Some("LIST_HELPER".to_string())),
+                ("added_later".to_string(), Some("LIST_HELPER".to_string())),
+                ("overridden".to_string(), Some("LIST_ADMINISTRATOR".to_string())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use chrono::FixedOffset;
+use maud::{html, Markup};
+use pointercrate_core::announcement::{Announcement, Severity};
+
+/// Renders the given announcements as banners, to be placed at the top of a page. The time at which
+/// an announcement stops being shown is given at the given offset from UTC
+pub fn announcement_banners(announcements: &[Announcement], utc_offset: FixedOffset) -> Markup {
+    html! {
+        @if !announcements.is_empty() {
+            div.announcements {
+                @for announcement in announcements {
+                    @let class = match announcement.severity {
+                        Severity::Info => "announcement info",
+                        Severity::Warning => "announcement warning",
+                        Severity::Critical => "announcement critical",
+                    };
+
+                    div class = (class) {
+                        (announcement.message)
+                        @if let Some(ends_at) = announcement.ends_at {
+                            " "
+                            small {
+                                "(until " (ends_at.with_timezone(&utc_offset).format("%B %-d, %Y %H:%M UTC%:z")) ")"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
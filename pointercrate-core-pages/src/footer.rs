@@ -27,8 +27,8 @@ impl Footer {
 }
 
 pub enum FooterColumn {
-    LinkList { heading: &'static str, links: Vec<Link> },
-    Arbitrary { heading: &'static str, content: Markup },
+    LinkList { heading: String, links: Vec<Link> },
+    Arbitrary { heading: String, content: Markup },
 }
 
 pub struct Link {
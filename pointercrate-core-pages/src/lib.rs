@@ -4,12 +4,16 @@ use crate::{
     navigation::NavigationBar,
 };
 use maud::{html, Markup, Render, DOCTYPE};
+use pointercrate_core::config::ThemeConfig;
 
+pub mod announcement;
 pub mod config;
 pub mod error;
 pub mod footer;
 pub mod head;
 pub mod navigation;
+pub mod template;
+pub mod theme;
 pub mod util;
 
 pub struct PageConfiguration {
@@ -62,6 +66,15 @@ impl PageConfiguration {
     pub fn keywords(self, keywords: impl Into<String>) -> Self {
         self.meta("keywords", keywords)
     }
+
+    /// Overrides the stylesheets' default colors with the ones configured in the given theme
+    pub fn theme(mut self, theme: &ThemeConfig) -> Self {
+        self.head.other = html! {
+            (self.head.other)
+            (theme::stylesheet(theme))
+        };
+        self
+    }
 }
 
 pub struct PageFragment {
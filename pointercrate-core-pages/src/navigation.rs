@@ -25,13 +25,16 @@ struct NavigationBarItem {
 }
 
 pub struct NavigationBar {
-    logo_path: &'static str,
+    logo_path: String,
     items: Vec<TopLevelNavigationBarItem>,
 }
 
 impl NavigationBar {
-    pub fn new(logo_path: &'static str) -> Self {
-        NavigationBar { logo_path, items: vec![] }
+    pub fn new(logo_path: impl Into<String>) -> Self {
+        NavigationBar {
+            logo_path: logo_path.into(),
+            items: vec![],
+        }
     }
 
     pub fn with_item(mut self, item: TopLevelNavigationBarItem) -> Self {
@@ -0,0 +1,190 @@
+//! File based templates for server-rendered views
+//!
+//! Views whose layout and wording list teams are expected to adjust (sidebar panels, notices, ...)
+//! are rendered from [Tera](https://keats.github.io/tera/) templates instead of `maud` markup, so
+//! that changing them does not require recompiling pointercrate. Each pages crate keeps its
+//! templates in its own `templates` directory, which gets registered under a namespace on startup
+//! (the same way the crates' `static` directories are mounted). A template is then referred to by
+//! its path relative to that directory, prefixed with the namespace, e.g.
+//! `demonlist/overview/rules.html`.
+//!
+//! Besides the variables passed by the view, every template has access to the configured
+//! [`ThemeConfig`](pointercrate_core::config::ThemeConfig) as `theme`, e.g. `{{ theme.list_name }}`.
+//!
+//! In debug builds, templates are re-read from disk before every render, so edits show up on the
+//! next page load. Release builds read them once during [`init`], which also reports syntax errors
+//! right at startup.
+//!
+//! Currently rendered from templates are
+//! * in `pointercrate-demonlist-pages/templates` (namespace `demonlist`): the demonlist's sidebar
+//!   panels (`overview/`), the panels of the demon pages (`demon/`), the changelog
+//!   (`changelog/week.html`), the record manager's help text and the stats viewer's subdivision
+//!   toggle
+//! * in `pointercrate-user-pages/templates` (namespace `user`): the login page and the account
+//!   page, including its profile and user management tabs
+//!
+//! Not moved yet, and thus still requiring a recompile to change, are the demon list overview and
+//! its dropdowns, the stats viewers' tables and filters, the widgets, the demonlist's account tabs
+//! (records, players, demons, submitters, ...), the labels of all account tabs, and the page chrome
+//! of `pointercrate-core-pages` (head, navigation bar, footer and announcements).
+
+use log::{error, info};
+use maud::{html, Markup, PreEscaped};
+use pointercrate_core::config;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
+};
+use tera::Tera;
+
+pub use tera::Context;
+
+static TEMPLATES: OnceLock<Templates> = OnceLock::new();
+
+pub struct Templates {
+    directories: Vec<(&'static str, PathBuf)>,
+    tera: RwLock<Tera>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Templates::new()
+    }
+}
+
+impl Templates {
+    pub fn new() -> Self {
+        Templates {
+            directories: Vec::new(),
+            tera: RwLock::new(Tera::default()),
+        }
+    }
+
+    /// Registers all templates in the given directory (and its subdirectories) under the given
+    /// namespace
+    pub fn directory(mut self, namespace: &'static str, path: impl Into<PathBuf>) -> Self {
+        self.directories.push((namespace, path.into()));
+        self
+    }
+
+    /// Reads all registered templates from disk, replacing the currently loaded ones
+    fn reload(&self) -> Result<(), tera::Error> {
+        let mut files = Vec::new();
+
+        for (namespace, directory) in &self.directories {
+            collect_templates(directory, namespace, &mut files)?;
+        }
+
+        let mut tera = Tera::default();
+
+        tera.autoescape_on(vec![".html"]);
+        tera.add_template_files(files)?;
+
+        *self.tera.write().unwrap() = tera;
+
+        Ok(())
+    }
+
+    fn render(&self, name: &str, context: &Context) -> Result<String, tera::Error> {
+        if cfg!(debug_assertions) {
+            self.reload()?;
+        }
+
+        self.tera.read().unwrap().render(name, context)
+    }
+}
+
+/// Recursively collects the `(path, name)` pairs of all files in `directory`, where `name` is
+/// the path relative to `directory`, prefixed with `prefix`
+fn collect_templates(directory: &Path, prefix: &str, files: &mut Vec<(PathBuf, Option<String>)>) -> Result<(), tera::Error> {
+    let entries = fs::read_dir(directory)
+        .map_err(|err| tera::Error::msg(format!("Failed to read template directory {}: {}", directory.display(), err)))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|err| tera::Error::msg(format!("Failed to read template directory {}: {}", directory.display(), err)))?
+            .path();
+        let name = format!("{}/{}", prefix, path.file_name().unwrap_or_default().to_string_lossy());
+
+        if path.is_dir() {
+            collect_templates(&path, &name, files)?;
+        } else {
+            files.push((path, Some(name)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the given templates and makes them available to [`render`]. Only the first call has any
+/// effect
+pub fn init(templates: Templates) -> Result<(), tera::Error> {
+    templates.reload()?;
+
+    info!("Loaded {} templates", templates.tera.read().unwrap().get_template_names().count());
+
+    let _ = TEMPLATES.set(templates);
+
+    Ok(())
+}
+
+/// Renders the template with the given name, making the configured theme available as `theme`
+///
+/// Failing to render a template should not take down the whole page, so errors are only logged.
+/// Debug builds render the error in place of the template instead, to make mistakes obvious while
+/// editing templates.
+pub fn render(name: &str, context: &Context) -> Markup {
+    let mut context = context.clone();
+    context.insert("theme", config::theme());
+
+    let result = match TEMPLATES.get() {
+        Some(templates) => templates.render(name, &context),
+        None => Err(tera::Error::msg("templates have not been initialized")),
+    };
+
+    match result {
+        Ok(rendered) => PreEscaped(rendered),
+        Err(err) => {
+            error!("Failed to render template {}: {:?}", name, err);
+
+            if cfg!(debug_assertions) {
+                html! {
+                    pre.info-red {
+                        "Failed to render template " (name) ": " (format!("{:?}", err))
+                    }
+                }
+            } else {
+                html! {}
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, Templates};
+    use std::fs;
+
+    #[test]
+    fn test_templates_are_namespaced_and_escaped() {
+        let directory = std::env::temp_dir().join(format!("pointercrate-templates-{}", std::process::id()));
+
+        fs::create_dir_all(directory.join("panels")).unwrap();
+        fs::write(directory.join("panels/greeting.html"), "<p>Hello {{ name }}</p>").unwrap();
+
+        let templates = Templates::new().directory("test", &directory);
+        templates.reload().unwrap();
+
+        let mut context = Context::new();
+        context.insert("name", "<b>Patrick</b>");
+
+        assert_eq!(
+            templates.render("test/panels/greeting.html", &context).unwrap(),
+            "<p>Hello &lt;b&gt;Patrick&lt;&#x2F;b&gt;</p>"
+        );
+        assert!(templates.render("panels/greeting.html", &context).is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
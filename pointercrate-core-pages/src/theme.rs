@@ -0,0 +1,64 @@
+//! Applies the colors of the configured [`ThemeConfig`] to rendered pages
+//!
+//! `core.css` defines the site's colors as CSS variables. A themed page overrides these from an
+//! inline stylesheet, deriving the darker shades used for hovered and pressed elements from the
+//! configured base colors.
+
+use maud::{html, Markup, PreEscaped};
+use pointercrate_core::config::{parse_hex_color, ThemeConfig};
+
+/// The primary color `core.css` uses unless overridden by the theme
+pub const DEFAULT_PRIMARY_COLOR: &str = "#0881c6";
+
+/// The accent color `core.css` uses unless overridden by the theme
+pub const DEFAULT_ACCENT_COLOR: &str = "#f77e39";
+
+/// Scales all channels of the given `#rrggbb` color by `factor`. Returns `None` for malformed
+/// colors
+fn darken(color: &str, factor: f32) -> Option<String> {
+    let (r, g, b) = parse_hex_color(color)?;
+    let scale = |channel: u8| (channel as f32 * factor).round() as u8;
+
+    Some(format!("#{:02x}{:02x}{:02x}", scale(r), scale(g), scale(b)))
+}
+
+/// The declarations overriding the `--<name>` variable family, or nothing if `color` is unset
+fn color_variables(name: &str, color: Option<&str>) -> String {
+    let Some(color) = color else { return String::new() };
+
+    match (parse_hex_color(color), darken(color, 0.8), darken(color, 0.62)) {
+        (Some(_), Some(hover), Some(active)) => format!("--{0}: {1}; --{0}-hover: {2}; --{0}-active: {3};", name, color, hover, active),
+        _ => String::new(),
+    }
+}
+
+/// Inline stylesheet overriding the color variables of `core.css` with the theme's colors
+pub fn stylesheet(theme: &ThemeConfig) -> Markup {
+    let variables =
+        color_variables("primary-color", theme.primary_color.as_deref()) + &color_variables("accent-color", theme.accent_color.as_deref());
+
+    if variables.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        style {
+            (PreEscaped(format!(":root {{ {} }}", variables)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::color_variables;
+
+    #[test]
+    fn test_color_variables() {
+        assert_eq!(
+            color_variables("primary-color", Some("#0881c6")),
+            "--primary-color: #0881c6; --primary-color-hover: #06679e; --primary-color-active: #05507b;"
+        );
+        assert_eq!(color_variables("primary-color", None), "");
+        assert_eq!(color_variables("primary-color", Some("red; } body { display: none")), "");
+    }
+}
@@ -0,0 +1,12 @@
+// Loads the example responses on the API reference page
+
+$(document).ready(function () {
+  $("pre.api-example").each((i, elem) => {
+    var pre = $(elem);
+
+    fetch(pre.data("example"), { headers: { Accept: "application/json" } })
+      .then((response) => response.json())
+      .then((json) => pre.text(JSON.stringify(json, null, 2)))
+      .catch(() => pre.text("Failed to load example response"));
+  });
+});
@@ -1286,6 +1286,8 @@ const RATELIMITED = {
 function mkReq(method, endpoint, headers = {}, data = null) {
   headers["Content-Type"] = "application/json";
   headers["Accept"] = "application/json";
+  // Keeps browsers from prompting for credentials on 401 responses, which we handle ourselves
+  headers["X-Requested-With"] = "XMLHttpRequest";
 
   let csrf_meta = document.querySelector('meta[name="csrf_token"]');
 
@@ -0,0 +1,293 @@
+//! Site-wide announcements
+//!
+//! Announcements are short messages (e.g. "Submissions are closed this weekend") that administrators
+//! can schedule for a specific time frame. Active announcements are rendered as banners at the top
+//! of every page, and are also available via the API.
+//!
+//! Since pages are rendered synchronously, the announcements shown on them are not read from the
+//! database for every request. Instead, an [`AnnouncementCache`] holds all announcements that have
+//! not yet ended, and needs to be [reloaded](AnnouncementCache::reload) whenever they are modified.
+
+use crate::{
+    error::CoreError,
+    pool::ReadConnection,
+    util::{non_nullable, nullable},
+    validate::{validated, Validate, Validator},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::RwLock;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "info" => Severity::Info,
+            "warning" => Severity::Warning,
+            "critical" => Severity::Critical,
+            _ => panic!("invalid announcement severity: {}", sql),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Hash, PartialEq, Eq)]
+pub struct Announcement {
+    pub id: i32,
+    pub message: String,
+    pub severity: Severity,
+
+    /// The point in time from which on this announcement is shown
+    pub starts_at: DateTime<Utc>,
+
+    /// The point in time at which this announcement stops being shown. [`None`] means the
+    /// announcement is shown until it is deleted
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostAnnouncement {
+    pub message: String,
+
+    #[serde(default)]
+    pub severity: Severity,
+
+    /// Defaults to the current time
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchAnnouncement {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub message: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub severity: Option<Severity>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub starts_at: Option<DateTime<Utc>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub ends_at: Option<Option<DateTime<Utc>>>,
+}
+
+fn validate_message(message: &str, validator: &mut Validator<CoreError>) {
+    validator.length("message", message, 1..=500, || CoreError::InvalidAnnouncementMessage);
+}
+
+impl Validate for PostAnnouncement {
+    type Error = CoreError;
+
+    fn normalize(&mut self) {
+        self.message = self.message.trim().to_string();
+    }
+
+    fn validate(&self, validator: &mut Validator<CoreError>) {
+        validate_message(&self.message, validator);
+    }
+}
+
+impl Validate for PatchAnnouncement {
+    type Error = CoreError;
+
+    fn normalize(&mut self) {
+        if let Some(ref mut message) = self.message {
+            *message = message.trim().to_string();
+        }
+    }
+
+    fn validate(&self, validator: &mut Validator<CoreError>) {
+        if let Some(ref message) = self.message {
+            validate_message(message, validator);
+        }
+    }
+}
+
+struct FetchedAnnouncement {
+    id: i32,
+    message: String,
+    severity: String,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+}
+
+impl From<FetchedAnnouncement> for Announcement {
+    fn from(fetched: FetchedAnnouncement) -> Self {
+        Announcement {
+            id: fetched.id,
+            message: fetched.message,
+            severity: Severity::from_sql(&fetched.severity),
+            starts_at: fetched.starts_at,
+            ends_at: fetched.ends_at,
+        }
+    }
+}
+
+impl Announcement {
+    /// Whether this announcement should be shown at the given point in time
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && self.ends_at.is_none_or(|ends_at| ends_at > now)
+    }
+
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Announcement, CoreError> {
+        sqlx::query_as!(
+            FetchedAnnouncement,
+            "SELECT id, message, severity, starts_at, ends_at FROM announcements WHERE id = $1",
+            id
+        )
+        .fetch_optional(connection)
+        .await?
+        .map(Into::into)
+        .ok_or(CoreError::AnnouncementNotFound { announcement_id: id })
+    }
+
+    /// All announcements, including scheduled and already ended ones, most recent first
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<Announcement>, CoreError> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            FetchedAnnouncement,
+            "SELECT id, message, severity, starts_at, ends_at FROM announcements ORDER BY starts_at DESC, id DESC"
+        )
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// All announcements that have not ended yet (this includes scheduled ones), most recent first
+    pub async fn upcoming(connection: &mut PgConnection) -> Result<Vec<Announcement>, CoreError> {
+        Ok(sqlx::query_as!(
+            FetchedAnnouncement,
+            "SELECT id, message, severity, starts_at, ends_at FROM announcements WHERE ends_at IS NULL OR ends_at > NOW() ORDER BY \
+             starts_at DESC, id DESC"
+        )
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn create(data: PostAnnouncement, connection: &mut PgConnection) -> Result<Announcement, CoreError> {
+        let data = validated(data)?;
+        let starts_at = data.starts_at.unwrap_or_else(Utc::now);
+
+        if data.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+            return Err(CoreError::InvalidAnnouncementSchedule);
+        }
+
+        let id = sqlx::query!(
+            "INSERT INTO announcements (message, severity, starts_at, ends_at) VALUES ($1, $2, $3, $4) RETURNING id",
+            data.message,
+            data.severity.to_sql(),
+            starts_at,
+            data.ends_at
+        )
+        .fetch_one(connection)
+        .await?
+        .id;
+
+        Ok(Announcement {
+            id,
+            message: data.message,
+            severity: data.severity,
+            starts_at,
+            ends_at: data.ends_at,
+        })
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchAnnouncement, connection: &mut PgConnection) -> Result<Announcement, CoreError> {
+        let patch = validated(patch)?;
+
+        if let Some(message) = patch.message {
+            self.message = message;
+        }
+
+        if let Some(severity) = patch.severity {
+            self.severity = severity;
+        }
+
+        if let Some(starts_at) = patch.starts_at {
+            self.starts_at = starts_at;
+        }
+
+        if let Some(ends_at) = patch.ends_at {
+            self.ends_at = ends_at;
+        }
+
+        if self.ends_at.is_some_and(|ends_at| ends_at <= self.starts_at) {
+            return Err(CoreError::InvalidAnnouncementSchedule);
+        }
+
+        sqlx::query!(
+            "UPDATE announcements SET message = $1, severity = $2, starts_at = $3, ends_at = $4 WHERE id = $5",
+            self.message,
+            self.severity.to_sql(),
+            self.starts_at,
+            self.ends_at,
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<(), CoreError> {
+        sqlx::query!("DELETE FROM announcements WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// In-memory copy of all announcements that have not ended yet, used for rendering pages
+#[derive(Debug, Default)]
+pub struct AnnouncementCache(RwLock<Vec<Announcement>>);
+
+impl AnnouncementCache {
+    /// Re-reads all announcements from the database. Needs to be called whenever announcements are
+    /// modified
+    pub async fn reload(&self, connection: &mut PgConnection) -> Result<(), CoreError> {
+        let announcements = Announcement::upcoming(connection).await?;
+
+        *self.0.write().unwrap() = announcements;
+
+        Ok(())
+    }
+
+    /// The announcements that should currently be shown
+    pub fn active(&self) -> Vec<Announcement> {
+        let now = Utc::now();
+
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|announcement| announcement.is_active_at(now))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,7 +1,9 @@
 //! Module containing some basic structures for dealing with audit logs
 
-use chrono::NaiveDateTime;
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Debug, Clone)]
 pub struct NamedId {
@@ -11,7 +13,7 @@ pub struct NamedId {
 
 #[derive(Serialize, Debug)]
 pub struct AuditLogEntry<T> {
-    pub time: NaiveDateTime,
+    pub time: DateTime<Utc>,
     pub entry_id: i32,
     pub id: i32,
     pub user: NamedId,
@@ -24,3 +26,22 @@ pub enum AuditLogEntryType<T> {
     Modification(T),
     Deletion,
 }
+
+/// The value of a single column before and after a modification
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColumnChange {
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Structured diff of a modification, mapping the name of each changed column to its old and new
+/// value.
+///
+/// Only modifications made after diffs were introduced have one. Older entries only know the old
+/// values of a fixed set of columns.
+pub type AuditDiff = BTreeMap<String, ColumnChange>;
+
+/// Parses a diff as selected from an audit log table via `diff::TEXT`
+pub fn parse_diff(diff: Option<String>) -> Option<AuditDiff> {
+    diff.map(|diff| serde_json::from_str(&diff).expect("postgres generated invalid audit diff"))
+}
@@ -1,4 +1,4 @@
-use crate::permission::Permission;
+use crate::{permission::Permission, validate::FieldError};
 use derive_more::Display;
 use log::error;
 use serde::Serialize;
@@ -70,6 +70,12 @@ pub enum CoreError {
     )]
     NotFound,
 
+    #[display(fmt = "No announcement with id {} found", announcement_id)]
+    AnnouncementNotFound { announcement_id: i32 },
+
+    #[display(fmt = "No job with id {} found", job_id)]
+    JobNotFound { job_id: u32 },
+
     /// `405 METHOD NOT ALLOWED`
     ///
     /// Error Code `40500`
@@ -87,6 +93,13 @@ pub enum CoreError {
     )]
     Conflict,
 
+    /// `409 CONFLICT` variant returned if a job is started while another job of the same kind is
+    /// still running
+    ///
+    /// Error Code `40912`
+    #[display(fmt = "A job of this kind is already running (id {})", job_id)]
+    JobAlreadyRunning { job_id: u32 },
+
     /// `411 LENGTH REQUIRED`
     ///
     /// Error Code `41100`
@@ -107,6 +120,16 @@ pub enum CoreError {
     #[display(fmt = "The data value transmitted exceeds the capacity limit.")]
     PayloadTooLarge,
 
+    /// `413 PAYLOAD TOO LARGE` variant returned if an uploaded file exceeds the size limit of the
+    /// endpoint it was uploaded to
+    ///
+    /// Error Code `41301`
+    #[display(fmt = "The uploaded file is too large. At most {} bytes are allowed", max_size)]
+    UploadTooLarge {
+        /// The maximal size (in bytes) of files accepted by the endpoint
+        max_size: u64,
+    },
+
     /// `415 UNSUPPORTED MEDIA TYPE`
     ///
     /// Error Code `41500`
@@ -129,8 +152,14 @@ pub enum CoreError {
     /// pagination is too large or too small
     ///
     /// Error Code `42207`
-    #[display(fmt = "Invalid value for the 'limit' parameter. It must be between 1 and 100")]
-    InvalidPaginationLimit,
+    #[display(fmt = "Invalid value {} for the 'limit' parameter. It must be between 1 and {}", limit, max)]
+    InvalidPaginationLimit {
+        /// The requested limit
+        limit: i32,
+
+        /// The largest limit the endpoint accepts
+        max: i32,
+    },
 
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
@@ -171,6 +200,29 @@ pub enum CoreError {
     #[display(fmt = "Your request contains mutually exclusive fields. Please restrict yourself to one of them")]
     MutuallyExclusive,
 
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the message of an announcement is empty or
+    /// longer than 500 characters
+    ///
+    /// Error Code `42250`
+    #[display(fmt = "Announcement messages must be between 1 and 500 characters long")]
+    InvalidAnnouncementMessage,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an announcement is set to end before it starts
+    ///
+    /// Error Code `42251`
+    #[display(fmt = "An announcement cannot end before it starts")]
+    InvalidAnnouncementSchedule,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if more than one field of a request body failed
+    /// validation
+    ///
+    /// Error Code `42243`
+    #[display(fmt = "Your request contains {} invalid fields", "errors.len()")]
+    InvalidFields {
+        /// The individual problems, one per invalid field
+        errors: Vec<FieldError>,
+    },
+
     /// `428 PRECONDITION REQUIRED`
     ///
     /// Error Code `42800`
@@ -212,6 +264,16 @@ pub enum CoreError {
     )]
     QueryTimeout,
 
+    /// `500 INTERNAL SERVER ERROR` reported when postgres aborts a transaction due to a
+    /// serialization failure or deadlock with a concurrent transaction. Such transactions can simply
+    /// be retried (see [`retry_on_conflict`](crate::pool::retry_on_conflict))
+    ///
+    /// Error Code `50006`
+    #[display(
+        fmt = "Internally, a database transaction conflicted with a concurrent one. Please retry your request. If this issue persists, please notify a server administrator!"
+    )]
+    TransactionConflict,
+
     /// `500 INTERNAL SERVER ERROR` variant returned if the server fails to acquire a database
     /// connection
     ///
@@ -219,6 +281,29 @@ pub enum CoreError {
     #[display(fmt = "Failed to retrieve connection to the database. The server might be temporarily overloaded.")]
     DatabaseConnectionError,
 
+    /// `501 NOT IMPLEMENTED` variant returned if a request asks for functionality the server does
+    /// not support, such as an unsupported response format
+    ///
+    /// Error Code `50100`
+    #[display(fmt = "The server does not support the functionality required to fulfill the request.")]
+    NotImplemented,
+
+    /// `503 SERVICE UNAVAILABLE` variant returned if too many requests are already waiting for a
+    /// database connection (see [`DatabaseConfig::max_waiting`](crate::config::DatabaseConfig::max_waiting))
+    ///
+    /// Error Code `50302`
+    #[display(fmt = "The server is currently overloaded. Please try again later.")]
+    DatabaseOverloaded,
+
+    /// `504 GATEWAY TIMEOUT` variant returned if a request used up its time budget for database
+    /// work (see [`DatabaseConfig::request_timeout`](crate::config::DatabaseConfig::request_timeout))
+    /// before it could run its queries, for example because it had to wait for a connection for too
+    /// long
+    ///
+    /// Error Code `50400`
+    #[display(fmt = "The server did not manage to process your request in time. It might be temporarily overloaded.")]
+    RequestTimeout,
+
     /// `503 SERVICE UNAVAILABLE` variant returned by all non-GET (e.g. all possible mutating) requests if the server is in maintenance mode.
     ///
     /// Error Core `50301`
@@ -245,26 +330,37 @@ impl PointercrateError for CoreError {
             CoreError::Forbidden => 40300,
             CoreError::MissingPermissions { .. } => 40301,
             CoreError::NotFound => 40400,
+            CoreError::AnnouncementNotFound { .. } => 40401,
+            CoreError::JobNotFound { .. } => 40401,
             CoreError::MethodNotAllowed => 40500,
             CoreError::Conflict => 40900,
+            CoreError::JobAlreadyRunning { .. } => 40912,
             CoreError::LengthRequired => 41100,
             CoreError::PreconditionFailed => 41200,
             CoreError::PayloadTooLarge => 41300,
+            CoreError::UploadTooLarge { .. } => 41301,
             CoreError::UnsupportedMediaType { .. } => 41500,
             CoreError::UnprocessableEntity => 42200,
-            CoreError::InvalidPaginationLimit => 42207,
+            CoreError::InvalidPaginationLimit { .. } => 42207,
             CoreError::InvalidUrlScheme => 42222,
             CoreError::UrlAuthenticated => 42223,
             CoreError::InvalidUrlFormat { .. } => 42225,
             CoreError::AfterSmallerBefore => 42227,
             CoreError::MutuallyExclusive => 42229,
+            CoreError::InvalidAnnouncementMessage => 42250,
+            CoreError::InvalidAnnouncementSchedule => 42251,
+            CoreError::InvalidFields { .. } => 42243,
             CoreError::PreconditionRequired => 42800,
             CoreError::Ratelimited { .. } => 42900,
             CoreError::InternalServerError { .. } => 50000,
             CoreError::DatabaseError => 50003,
             CoreError::QueryTimeout => 50004,
             CoreError::DatabaseConnectionError => 50005,
+            CoreError::TransactionConflict => 50006,
+            CoreError::NotImplemented => 50100,
             CoreError::ReadOnlyMaintenance => 50301,
+            CoreError::DatabaseOverloaded => 50302,
+            CoreError::RequestTimeout => 50400,
         }
     }
 }
@@ -275,6 +371,8 @@ impl From<sqlx::Error> for CoreError {
 
         match error {
             sqlx::Error::Database(err) if err.code().as_deref() == Some("57014") => CoreError::QueryTimeout,
+            // serialization_failure and deadlock_detected
+            sqlx::Error::Database(err) if matches!(err.code().as_deref(), Some("40001" | "40P01")) => CoreError::TransactionConflict,
             sqlx::Error::PoolClosed | sqlx::Error::PoolTimedOut => CoreError::DatabaseConnectionError,
             _ => CoreError::DatabaseError,
         }
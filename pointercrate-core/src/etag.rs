@@ -2,9 +2,15 @@
 //!
 //! Note that the format described here is **not part of the public API**.
 //!
-//! A pointercrate ETag value has two parts: A part relevant for `PATCH` requests, which is a hash
-//! of all fields that can be modified via a direct `PATCH` request to the object represented, and a
-//! part relevant for `GET` requests, which is generally just a hash of the complete objects.
+//! A pointercrate ETag value has two parts: A part relevant for `PATCH` requests, which changes
+//! whenever a field that can be modified via a direct `PATCH` request to the object represented
+//! changes, and a part relevant for `GET` requests, which is generally just a hash of the complete
+//! objects.
+//!
+//! For objects backed by a database row with a `version` column (demons, records, players and
+//! users), the `PATCH` part is that version. It is incremented by a database trigger on every
+//! modification of a patchable column, which makes it independent of how the object is
+//! represented in memory. Other objects fall back to hashing.
 //!
 //! These two parts are unsigned 64 bit integers separated by a semicolon (`;`)
 //!
@@ -24,6 +30,7 @@ use std::{
 
 /// Trait defining methods for producing the two parts of the pointercrate ETag format
 pub trait Taggable: Hash + Serialize {
+    /// Defaults to [`Taggable::get_part`]. Objects with a `version` column should return it here
     fn patch_part(&self) -> u64 {
         self.get_part()
     }
@@ -0,0 +1,36 @@
+//! Strongly typed database IDs
+//!
+//! All of pointercrate's objects are identified by `INTEGER` primary keys. To prevent the ID of one
+//! kind of object being passed where the ID of another kind is expected, model crates wrap them in
+//! newtypes declared via [`id_type!`](crate::id_type). These are transparent to serde and sqlx, so
+//! they (de)serialize and bind exactly like the plain integers they wrap.
+
+/// Declares a newtype wrapper around an `i32` database ID
+#[macro_export]
+macro_rules! id_type {
+    ($(#[$meta: meta])* $name: ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ::serde::Serialize, ::serde::Deserialize, ::sqlx::Type)]
+        #[serde(transparent)]
+        #[sqlx(transparent)]
+        pub struct $name(pub i32);
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl From<i32> for $name {
+            fn from(id: i32) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+    };
+}
@@ -0,0 +1,194 @@
+//! Long running jobs triggered via the API
+//!
+//! Some operations (for example recomputing all scores) take too long to be performed while the
+//! client waits for a response. Instead, the endpoint triggering them registers a [`Job`] with the
+//! [`JobRegistry`], spawns the actual work onto a background task and immediately responds with
+//! `202 ACCEPTED`. The job's progress can then be inspected by polling it.
+//!
+//! Jobs can additionally produce a textual output (for example a report on what they did), which is
+//! built up while the job runs and can be retrieved via [`JobRegistry::output`].
+//!
+//! Jobs are only tracked in memory, and are forgotten when the server restarts.
+
+use crate::error::CoreError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+/// How many finished jobs are remembered. The oldest ones are forgotten first
+const FINISHED_JOBS_KEPT: usize = 50;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: u32,
+
+    /// What this job does, e.g. `recompute_scores`
+    pub kind: &'static str,
+    pub status: JobStatus,
+
+    /// The number of steps of this job that have been completed so far
+    pub completed_steps: u32,
+    pub total_steps: u32,
+
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// Description of the error that caused this job to fail, if it did
+    pub error: Option<String>,
+
+    /// Whether this job produced any output, see [`JobRegistry::output`]
+    pub has_output: bool,
+}
+
+#[derive(Debug, Default)]
+struct Jobs {
+    next_id: u32,
+    jobs: BTreeMap<u32, Job>,
+    outputs: BTreeMap<u32, String>,
+}
+
+/// The jobs started since this server started. Cheap to clone, all clones share the same jobs
+#[derive(Debug, Default, Clone)]
+pub struct JobRegistry(Arc<Mutex<Jobs>>);
+
+impl JobRegistry {
+    /// Registers a new job of the given kind. Fails if a job of the same kind is still running
+    pub fn start(&self, kind: &'static str, total_steps: u32) -> Result<JobHandle, CoreError> {
+        let mut jobs = self.0.lock().unwrap();
+
+        if let Some(running) = jobs.jobs.values().find(|job| job.kind == kind && job.status == JobStatus::Running) {
+            return Err(CoreError::JobAlreadyRunning { job_id: running.id });
+        }
+
+        jobs.next_id += 1;
+
+        let id = jobs.next_id;
+
+        jobs.jobs.insert(
+            id,
+            Job {
+                id,
+                kind,
+                status: JobStatus::Running,
+                completed_steps: 0,
+                total_steps,
+                started_at: Utc::now(),
+                finished_at: None,
+                error: None,
+                has_output: false,
+            },
+        );
+
+        Ok(JobHandle {
+            id,
+            registry: self.clone(),
+        })
+    }
+
+    pub fn get(&self, id: u32) -> Result<Job, CoreError> {
+        self.0
+            .lock()
+            .unwrap()
+            .jobs
+            .get(&id)
+            .cloned()
+            .ok_or(CoreError::JobNotFound { job_id: id })
+    }
+
+    /// The output the given job has produced so far, if any
+    pub fn output(&self, id: u32) -> Result<Option<String>, CoreError> {
+        let jobs = self.0.lock().unwrap();
+
+        match jobs.jobs.get(&id) {
+            Some(_) => Ok(jobs.outputs.get(&id).cloned()),
+            None => Err(CoreError::JobNotFound { job_id: id }),
+        }
+    }
+
+    /// All jobs still remembered, most recent first
+    pub fn all(&self) -> Vec<Job> {
+        self.0.lock().unwrap().jobs.values().rev().cloned().collect()
+    }
+
+    fn update(&self, id: u32, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.0.lock().unwrap().jobs.get_mut(&id) {
+            f(job)
+        }
+    }
+}
+
+/// Handle through which a running job reports its progress
+#[derive(Clone)]
+pub struct JobHandle {
+    id: u32,
+    registry: JobRegistry,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Marks another step of this job as completed
+    pub fn advance(&self) {
+        self.registry
+            .update(self.id, |job| job.completed_steps = (job.completed_steps + 1).min(job.total_steps));
+    }
+
+    /// Appends the given text to this job's output
+    pub fn append_output(&self, text: &str) {
+        let mut jobs = self.registry.0.lock().unwrap();
+
+        if let Some(job) = jobs.jobs.get_mut(&self.id) {
+            job.has_output = true;
+            jobs.outputs.entry(self.id).or_default().push_str(text);
+        }
+    }
+
+    /// Resets this job's progress, e.g. because its transaction had to be retried
+    pub fn restart(&self) {
+        self.registry.update(self.id, |job| job.completed_steps = 0);
+    }
+
+    pub fn finish<E: Display>(self, result: Result<(), E>) {
+        self.registry.update(self.id, |job| {
+            job.finished_at = Some(Utc::now());
+
+            match result {
+                Ok(()) => {
+                    job.status = JobStatus::Completed;
+                    job.completed_steps = job.total_steps;
+                },
+                Err(err) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(err.to_string());
+                },
+            }
+        });
+
+        let mut jobs = self.registry.0.lock().unwrap();
+        let finished: Vec<u32> = jobs
+            .jobs
+            .values()
+            .filter(|job| job.status != JobStatus::Running)
+            .map(|job| job.id)
+            .collect();
+
+        for id in finished.iter().take(finished.len().saturating_sub(FINISHED_JOBS_KEPT)) {
+            jobs.jobs.remove(id);
+            jobs.outputs.remove(id);
+        }
+    }
+}
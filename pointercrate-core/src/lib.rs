@@ -1,10 +1,21 @@
+pub mod announcement;
 pub mod audit;
 pub mod config;
 pub mod error;
 pub mod etag;
+#[macro_use]
+pub mod id;
+pub mod job;
 pub mod pagination;
+pub mod patch;
 pub mod permission;
 pub mod pool;
+pub mod redact;
 pub mod util;
+pub mod validate;
 #[macro_use]
 pub mod ratelimits;
+
+// Code generated by pointercrate-core-macros refers to `::pointercrate_core`, which this makes work
+// inside of this crate too
+extern crate self as pointercrate_core;
@@ -1,18 +1,39 @@
 use std::fmt::{Debug, Display};
 
-use crate::{error::CoreError, util::non_nullable};
+use crate::{error::CoreError, pool::Reader, util::non_nullable};
 use serde::{de::Error, Deserialize, Serialize};
-use sqlx::PgConnection;
 
-/// The maximal number of entries that can be requested per page via the `limit` parameter.
+/// The maximal number of entries that can be requested per page via the `limit` parameter, unless
+/// configured otherwise (see [`config::pagination_limits`](crate::config::pagination_limits)).
 pub const ENTRIES_PER_PAGE: i32 = 100;
 
-/// The default number of entries returned per page if the `limit` parameter was omited.
-///
-/// Try not to directly rely on this constant, and instead use `PaginationParameters::default()`
+/// The number of entries returned per page if the `limit` parameter was omited, unless configured
+/// otherwise (see [`config::pagination_limits`](crate::config::pagination_limits)).
 pub const DEFAULT_ENTRIES_PER_PAGE: i32 = 50;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// Absolute upper bound for the `limit` parameter. No configuration can allow larger pages.
+pub const MAX_ENTRIES_PER_PAGE: i32 = 500;
+
+/// The bounds of the `limit` parameter of a single paginating endpoint
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PaginationLimits {
+    /// The number of entries per page if the `limit` parameter was omitted
+    pub default: i32,
+
+    /// The maximal value of the `limit` parameter. Never larger than [`MAX_ENTRIES_PER_PAGE`]
+    pub max: i32,
+}
+
+impl Default for PaginationLimits {
+    fn default() -> Self {
+        PaginationLimits {
+            default: DEFAULT_ENTRIES_PER_PAGE,
+            max: ENTRIES_PER_PAGE,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct PaginationParameters {
     #[serde(default, deserialize_with = "from_str_non_nullable")]
     pub before: Option<i32>,
@@ -20,28 +41,36 @@ pub struct PaginationParameters {
     #[serde(default, deserialize_with = "from_str_non_nullable")]
     pub after: Option<i32>,
 
-    #[serde(
-        default = "default_limit",
-        deserialize_with = "from_str",
-        skip_serializing_if = "is_default_entries_per_page"
-    )]
-    pub limit: i32,
+    /// The requested number of entries per page. [`None`] means the endpoint's default, see
+    /// [`PaginationParameters::resolve`]
+    #[serde(default, deserialize_with = "from_str_non_nullable", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
 }
 
-impl Default for PaginationParameters {
-    fn default() -> Self {
-        Self {
-            before: None,
-            after: None,
-            limit: DEFAULT_ENTRIES_PER_PAGE,
-        }
+impl PaginationParameters {
+    /// The number of entries per page. Only meaningful after [`PaginationParameters::resolve`]
+    pub fn limit(&self) -> i32 {
+        self.limit.unwrap_or(DEFAULT_ENTRIES_PER_PAGE)
     }
-}
 
-impl PaginationParameters {
-    pub fn validate(&self) -> Result<(), CoreError> {
-        if !(1..=ENTRIES_PER_PAGE).contains(&self.limit) {
-            return Err(CoreError::InvalidPaginationLimit);
+    /// Validates these parameters against the given limits, and fills in the default limit if the
+    /// `limit` parameter was omitted
+    pub fn resolve(self, limits: PaginationLimits) -> Result<Self, CoreError> {
+        let resolved = PaginationParameters {
+            limit: Some(self.limit.unwrap_or(limits.default)),
+            ..self
+        };
+
+        resolved.validate(limits)?;
+
+        Ok(resolved)
+    }
+
+    pub fn validate(&self, limits: PaginationLimits) -> Result<(), CoreError> {
+        let max = limits.max.min(MAX_ENTRIES_PER_PAGE);
+
+        if !(1..=max).contains(&self.limit()) {
+            return Err(CoreError::InvalidPaginationLimit { limit: self.limit(), max });
         }
 
         if let (Some(after), Some(before)) = (self.before, self.after) {
@@ -62,6 +91,236 @@ impl PaginationParameters {
     }
 }
 
+/// Direction in which a column of a [`Keyset`] is sorted
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    fn reversed(self) -> Self {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    /// The operator selecting values that come after a given value when sorting in this direction
+    fn following(self) -> &'static str {
+        match self {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        }
+    }
+}
+
+/// A column paginated objects can be sorted by via the `sort` query parameter. Implemented by an enum
+/// per paginator, which acts as a whitelist of the columns clients are allowed to sort by
+pub trait SortColumn: Copy + PartialEq + Debug + 'static {
+    /// All sortable columns, together with the name used to refer to them in the `sort` parameter
+    const COLUMNS: &'static [(&'static str, Self)];
+
+    /// The SQL expression to sort by, see [`KeysetColumn::expression`]
+    fn expression(self) -> &'static str;
+}
+
+/// Value of the `sort` query parameter of a paginating endpoint.
+///
+/// Of the form `column` for ascending, and `-column` for descending order. Pages are always
+/// additionally ordered by id to break ties, see [`Keyset`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Sort<C> {
+    pub column: C,
+    pub direction: SortDirection,
+}
+
+impl<C: SortColumn> Sort<C> {
+    /// Adds the column sorted by to the given keyset
+    pub fn apply(self, keyset: Keyset) -> Keyset {
+        keyset.then_by(self.column.expression(), self.direction)
+    }
+
+    fn name(self) -> &'static str {
+        C::COLUMNS
+            .iter()
+            .find(|(_, column)| *column == self.column)
+            .map(|(name, _)| *name)
+            .expect("sort column missing from SortColumn::COLUMNS")
+    }
+}
+
+impl<C: SortColumn> Serialize for Sort<C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.direction {
+            SortDirection::Asc => serializer.serialize_str(self.name()),
+            SortDirection::Desc => serializer.serialize_str(&format!("-{}", self.name())),
+        }
+    }
+}
+
+impl<'de, C: SortColumn> Deserialize<'de> for Sort<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+
+        let (name, direction) = match value.strip_prefix('-') {
+            Some(name) => (name, SortDirection::Desc),
+            None => (value.as_str(), SortDirection::Asc),
+        };
+
+        match C::COLUMNS.iter().find(|(column_name, _)| *column_name == name) {
+            Some((_, column)) => Ok(Sort {
+                column: *column,
+                direction,
+            }),
+            None => Err(D::Error::custom(format!(
+                "cannot sort by '{}', expected one of: {}",
+                name,
+                C::COLUMNS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+}
+
+/// A column of a compound [`Keyset`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KeysetColumn {
+    /// A fully qualified SQL expression (e.g. `records.progress`) that is never `NULL`
+    pub expression: &'static str,
+    pub direction: SortDirection,
+}
+
+/// Ordering of a paginated query by a list of (potentially non-unique) columns, with the
+/// pagination id as final, ascending tie-breaker.
+///
+/// The `before` and `after` [`PaginationParameters`] keep referring to objects by their pagination
+/// id. The keyset values of the referenced object are looked up in the database, meaning the
+/// condition generated by [`Keyset::seek_condition`] selects all objects sorted strictly before
+/// (respectively after) the referenced object. If the referenced object no longer exists, the
+/// resulting page is empty.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Keyset {
+    /// The table expression (including joins) that all column expressions refer to
+    source: &'static str,
+    id_column: &'static str,
+    columns: Vec<KeysetColumn>,
+}
+
+impl Keyset {
+    /// Constructs a keyset ordering only by the given id column
+    pub fn new(source: &'static str, id_column: &'static str) -> Self {
+        Keyset {
+            source,
+            id_column,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds a column to sort by. Columns are compared in the order they were added, and all of them
+    /// take precedence over the id column
+    pub fn then_by(mut self, expression: &'static str, direction: SortDirection) -> Self {
+        self.columns.push(KeysetColumn { expression, direction });
+        self
+    }
+
+    /// Whether this keyset orders by something other than the id column
+    pub fn is_compound(&self) -> bool {
+        !self.columns.is_empty()
+    }
+
+    /// The contents of the `ORDER BY` clause for a page requested with the given parameters.
+    ///
+    /// If only `before` is set, the ordering is reversed (so that `LIMIT` selects the objects right
+    /// before `before`), analogously to [`PaginationParameters::order`]
+    pub fn order_by(&self, params: &PaginationParameters) -> String {
+        let reverse = params.order() == "DESC";
+
+        self.columns
+            .iter()
+            .map(|column| (column.expression, column.direction))
+            .chain(std::iter::once((self.id_column, SortDirection::Asc)))
+            .map(|(expression, direction)| {
+                let direction = if reverse { direction.reversed() } else { direction };
+
+                format!("{} {}", expression, direction.sql())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// A SQL condition selecting all rows sorted before the row whose id is bound to the
+    /// placeholder `before`, and after the row whose id is bound to the placeholder `after`.
+    /// Unbound (`NULL`) placeholders do not restrict the result
+    pub fn seek_condition(&self, before: &str, after: &str) -> String {
+        format!("{} AND {}", self.seek(before, true), self.seek(after, false))
+    }
+
+    fn seek(&self, placeholder: &str, backwards: bool) -> String {
+        let reference = |expression: &str| {
+            format!(
+                "(SELECT {} FROM {} WHERE {} = {})",
+                expression, self.source, self.id_column, placeholder
+            )
+        };
+
+        let mut alternatives = Vec::new();
+        let mut equalities = Vec::new();
+
+        for column in &self.columns {
+            let direction = if backwards { column.direction.reversed() } else { column.direction };
+
+            alternatives.push(
+                equalities
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(format!(
+                        "{} {} {}",
+                        column.expression,
+                        direction.following(),
+                        reference(column.expression)
+                    )))
+                    .collect::<Vec<_>>()
+                    .join(" AND "),
+            );
+
+            equalities.push(format!("{} = {}", column.expression, reference(column.expression)));
+        }
+
+        let id_direction = if backwards { SortDirection::Desc } else { SortDirection::Asc };
+
+        alternatives.push(
+            equalities
+                .into_iter()
+                .chain(std::iter::once(format!(
+                    "{} {} {}",
+                    self.id_column,
+                    id_direction.following(),
+                    placeholder
+                )))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        );
+
+        format!(
+            "({} IS NULL OR {})",
+            placeholder,
+            alternatives
+                .into_iter()
+                .map(|alt| format!("({})", alt))
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        )
+    }
+}
+
 /// Enum describing what is going on "around" a page returned by [`Pagination::page`].
 ///
 /// Describes whether [`Pagination::Item`] matching all properties of a given [`Pagination`] exist
@@ -118,7 +377,9 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
     /// Returns a page of objects matching the query described by tthe given [`PaginationQuery`].
     ///
     /// The returned list of objects must have the following properties:
-    /// - They are sorted in ascending order according to the value of [`pagination_id`].
+    /// - They are sorted in ascending order according to the value of [`pagination_id`] (or
+    ///   according to the [`Keyset`] returned by [`Paginatable::keyset`], if any. In this case, "smaller"
+    ///   and "greater" below refer to the keyset ordering).
     /// - Their ids are consecutive, meaning if the object at index `i` in the list has ID `a`, and
     ///   the object at index `i + 1` has id `b`, then there exists no object also matching all conditions
     ///   of this `Pagination` in the _database_ with an ID `c` such that `a < c < b`.
@@ -134,11 +395,20 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
     /// HOWEVER, if both `before` and `after` are set, then it should be [`PageContext::Standalone`].
     ///
     /// The number of items in the returned `Vec` must not exceed [`PaginationParameters::limit`].
-    async fn page(query: &Q, connection: &mut PgConnection) -> Result<(Vec<Self>, PageContext), sqlx::Error>;
+    async fn page(query: &Q, connection: &mut Reader<'_>) -> Result<(Vec<Self>, PageContext), sqlx::Error>;
 
-    async fn first_and_last(connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error>;
+    async fn first_and_last(connection: &mut Reader<'_>) -> Result<Option<(i32, i32)>, sqlx::Error>;
 
     fn pagination_id(&self) -> i32;
+
+    /// The [`Keyset`] the given query orders objects by. `None` means objects are simply ordered by
+    /// their [`pagination_id`].
+    ///
+    /// No `first` and `last` links are generated for queries with a compound keyset, as there is no
+    /// id that can be used to refer to "the position before the first object" in that case.
+    fn keyset(_query: &Q) -> Option<Keyset> {
+        None
+    }
 }
 
 /// Historically, pointercrate has been determining whether a new page exists by simply incrementing the "limit" parameter
@@ -160,7 +430,7 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
 /// the "extra" object into a `PageContext`. It doesn't solve the second point though.
 #[doc(hidden)]
 pub fn __pagination_compat<T>(params: &PaginationParameters, mut objects: Vec<T>) -> (Vec<T>, PageContext) {
-    let has_followup_page = objects.len() > params.limit as usize;
+    let has_followup_page = objects.len() > params.limit() as usize;
 
     if has_followup_page {
         objects.pop();
@@ -199,7 +469,7 @@ pub fn __pagination_compat<T>(params: &PaginationParameters, mut objects: Vec<T>
 #[macro_export]
 macro_rules! first_and_last {
     ($table_name: expr, $id_column: expr) => {
-        async fn first_and_last(connection: &mut PgConnection) -> std::result::Result<Option<(i32, i32)>, sqlx::Error> {
+        async fn first_and_last(connection: &mut $crate::pool::Reader<'_>) -> std::result::Result<Option<(i32, i32)>, sqlx::Error> {
             let row = sqlx::query!(
                 "SELECT CAST(MIN(" + $id_column + ") AS INTEGER), CAST(MAX(" + $id_column + ") AS INTEGER) FROM " + $table_name
             )
@@ -214,28 +484,8 @@ macro_rules! first_and_last {
     };
 }
 
-/// Helper function because serde does not allow literals/constants in #[serde(default = ...)] attributes.
-/// See also https://github.com/serde-rs/serde/issues/368
-const fn default_limit() -> i32 {
-    DEFAULT_ENTRIES_PER_PAGE
-}
-
-const fn is_default_entries_per_page(limit: &i32) -> bool {
-    *limit == DEFAULT_ENTRIES_PER_PAGE
-}
-
 // Helper function needed because serde's flatten attribute does not work with non-self describing data formats (such as url-encoding) - it thinks everything is a string.
 // See also https://github.com/nox/serde_urlencoded/issues/33
-fn from_str<'de, D, S>(deserializer: D) -> Result<S, D::Error>
-where
-    D: serde::Deserializer<'de>,
-    S: std::str::FromStr,
-    S::Err: Display,
-{
-    let s = <&str as serde::Deserialize>::deserialize(deserializer)?;
-    S::from_str(&s).map_err(|err| D::Error::custom(err.to_string()))
-}
-
 fn from_str_non_nullable<'de, S, D>(deserializer: D) -> Result<Option<S>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -246,3 +496,128 @@ where
         .map(|s| S::from_str(&s).map_err(|err| D::Error::custom(err.to_string())))
         .transpose()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Keyset, PaginationLimits, PaginationParameters, Sort, SortColumn, SortDirection, MAX_ENTRIES_PER_PAGE};
+    use crate::error::CoreError;
+    use serde::{de::IntoDeserializer, Deserialize};
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum DummyColumn {
+        Progress,
+    }
+
+    impl SortColumn for DummyColumn {
+        const COLUMNS: &'static [(&'static str, Self)] = &[("progress", DummyColumn::Progress)];
+
+        fn expression(self) -> &'static str {
+            "records.progress"
+        }
+    }
+
+    fn parse(value: &str) -> Result<Sort<DummyColumn>, serde::de::value::Error> {
+        Sort::deserialize(value.into_deserializer())
+    }
+
+    #[test]
+    fn test_sort_parameter() {
+        let sort = parse("-progress").unwrap();
+
+        assert_eq!(sort.column, DummyColumn::Progress);
+        assert_eq!(sort.direction, SortDirection::Desc);
+        assert_eq!(parse("progress").unwrap().direction, SortDirection::Asc);
+        assert!(parse("video").is_err());
+        assert_eq!(
+            sort.apply(Keyset::new("records", "records.id")),
+            Keyset::new("records", "records.id").then_by("records.progress", SortDirection::Desc)
+        );
+    }
+
+    #[test]
+    fn test_resolve_limit() {
+        let limits = PaginationLimits { default: 20, max: 40 };
+
+        assert_eq!(PaginationParameters::default().resolve(limits).unwrap().limit, Some(20));
+        assert_eq!(
+            PaginationParameters {
+                limit: Some(40),
+                ..Default::default()
+            }
+            .resolve(limits)
+            .unwrap()
+            .limit(),
+            40
+        );
+        assert_eq!(
+            PaginationParameters {
+                limit: Some(41),
+                ..Default::default()
+            }
+            .resolve(limits),
+            Err(CoreError::InvalidPaginationLimit { limit: 41, max: 40 })
+        );
+
+        // Limits beyond the hard cap are refused even if the endpoint allows them
+        let unbounded = PaginationLimits {
+            default: 20,
+            max: i32::MAX,
+        };
+
+        assert_eq!(
+            PaginationParameters {
+                limit: Some(MAX_ENTRIES_PER_PAGE + 1),
+                ..Default::default()
+            }
+            .resolve(unbounded),
+            Err(CoreError::InvalidPaginationLimit {
+                limit: MAX_ENTRIES_PER_PAGE + 1,
+                max: MAX_ENTRIES_PER_PAGE
+            })
+        );
+    }
+
+    #[test]
+    fn test_id_keyset() {
+        let keyset = Keyset::new("records", "records.id");
+
+        assert!(!keyset.is_compound());
+        assert_eq!(keyset.order_by(&PaginationParameters::default()), "records.id ASC");
+        assert_eq!(
+            keyset.seek_condition("$1", "$2"),
+            "($1 IS NULL OR (records.id < $1)) AND ($2 IS NULL OR (records.id > $2))"
+        );
+    }
+
+    #[test]
+    fn test_compound_keyset() {
+        let keyset = Keyset::new("records", "records.id").then_by("records.progress", SortDirection::Desc);
+        let reference = "(SELECT records.progress FROM records WHERE records.id = $2)";
+
+        assert!(keyset.is_compound());
+        assert_eq!(
+            keyset.seek_condition("$1", "$2").split(" AND ($2").nth(1).unwrap(),
+            format!(
+                " IS NULL OR (records.progress < {0}) OR (records.progress = {0} AND records.id > $2))",
+                reference
+            )
+        );
+    }
+
+    #[test]
+    fn test_compound_keyset_reversed() {
+        let keyset = Keyset::new("records", "records.id").then_by("records.progress", SortDirection::Desc);
+
+        assert_eq!(
+            keyset.order_by(&PaginationParameters {
+                before: Some(10),
+                ..Default::default()
+            }),
+            "records.progress ASC, records.id DESC"
+        );
+        assert_eq!(
+            keyset.order_by(&PaginationParameters::default()),
+            "records.progress DESC, records.id ASC"
+        );
+    }
+}
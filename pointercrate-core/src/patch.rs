@@ -0,0 +1,114 @@
+//! Patches of pointercrate's objects
+//!
+//! A patch has one `Option` field per modifiable attribute of some object, which is `None` if the
+//! attribute should stay unchanged. Attributes that can be reset to `null` are `Option<Option<_>>`s
+//! in the patch, where `Some(None)` resets the attribute.
+//!
+//! Instead of being written by hand, patch structs are generated by putting `#[derive(Patchable)]`
+//! on the object they modify and marking its modifiable fields with `#[patch]`. The derive
+//! generates the patch struct (named `Patch` followed by the object's name, unless set via
+//! `#[patch(name = ...)]` on the object) and its [`Patch`] implementation. A
+//! `#[patch(permission = ...)]` on the object sets the permission required for all fields that do
+//! not specify their own:
+//!
+//! ```ignore
+//! #[derive(Patchable)]
+//! pub struct User {
+//!     pub id: UserId,
+//!
+//!     #[patch(permission = MODERATOR)]
+//!     pub display_name: Option<String>,
+//!
+//!     #[patch]
+//!     pub permissions: u16,
+//! }
+//! ```
+//!
+//! Fields that are `Option`s on the object can be reset. Each `#[patch]` attribute on a field
+//! generates one field of the patch struct, configured by the following options:
+//! * `permission = PERMISSION`: Setting the field requires the given permission (see
+//!   [`Patch::required_permissions`]). Without it (and without a permission set on the object),
+//!   the field can be set by anyone who can access the patch endpoint at all
+//! * `ty = Type`: The type clients send for the field, if it differs from the field's own type (for
+//!   example a player's name instead of the full player)
+//! * `rename = name`: The name of the field in the patch. Put several `#[patch]` attributes on a
+//!   field to make it modifiable in several ways
+//! * `non_nullable`: The field is an `Option` on the object, but clients cannot reset it
+
+use crate::permission::Permission;
+pub use pointercrate_core_macros::Patchable;
+
+/// A patch for objects of type `H`, usually generated via `#[derive(Patchable)]` on `H`
+pub trait Patch<H> {
+    /// The names of all fields that are set in this patch
+    fn modified_fields(&self) -> Vec<&'static str>;
+
+    /// Whether applying this patch would be a no-op
+    fn is_empty(&self) -> bool;
+
+    /// The permissions required to set the fields set in this patch
+    fn required_permissions(&self) -> Vec<Permission>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        patch::{Patch, Patchable},
+        permission::Permission,
+    };
+    use serde_json::json;
+
+    const MODERATOR: Permission = Permission::new("Moderator", 0x1);
+
+    #[allow(dead_code)]
+    struct Base {
+        name: String,
+    }
+
+    #[derive(Patchable)]
+    #[allow(dead_code)]
+    struct Object {
+        #[patch(rename = name, ty = String, permission = MODERATOR)]
+        base: Base,
+
+        #[patch]
+        description: Option<String>,
+
+        #[patch(non_nullable)]
+        level_id: Option<u64>,
+
+        id: i32,
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let patch: PatchObject = serde_json::from_value(json!({"description": null, "level_id": 10565740})).unwrap();
+
+        assert_eq!(patch.name, None);
+        assert_eq!(patch.description, Some(None));
+        assert_eq!(patch.level_id, Some(10565740));
+        assert_eq!(patch.modified_fields(), vec!["description", "level_id"]);
+        assert!(!patch.is_empty());
+        assert!(PatchObject::default().is_empty());
+
+        assert!(serde_json::from_value::<PatchObject>(json!({"level_id": null})).is_err());
+        assert!(serde_json::from_value::<PatchObject>(json!({"name": null})).is_err());
+    }
+
+    #[test]
+    fn test_required_permissions() {
+        let patch = PatchObject {
+            description: Some(None),
+            ..Default::default()
+        };
+
+        assert!(patch.required_permissions().is_empty());
+
+        let patch = PatchObject {
+            name: Some("Bloodlust".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(patch.required_permissions(), vec![MODERATOR]);
+    }
+}
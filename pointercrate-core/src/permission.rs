@@ -1,7 +1,7 @@
 use crate::error::CoreError;
 use derive_more::Display;
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Serialize, Debug, Display, Eq, PartialEq, Clone, Copy, Hash)]
 #[serde(transparent)]
@@ -92,6 +92,10 @@ pub struct PermissionsManager {
     permissions: HashSet<Permission>,
     implication_map: HashMap<Permission, HashSet<Permission>>,
     assignable_map: HashMap<Permission, HashSet<Permission>>,
+
+    /// Named capabilities (e.g. `can_move_demons`) and the permissions granting each, so that
+    /// frontends can query what a user is allowed to do instead of replicating the rules above
+    capabilities: BTreeMap<&'static str, HashSet<Permission>>,
 }
 
 impl PermissionsManager {
@@ -106,6 +110,7 @@ impl PermissionsManager {
             permissions: permission_set,
             implication_map: HashMap::new(),
             assignable_map: HashMap::new(),
+            capabilities: BTreeMap::new(),
         }
     }
 
@@ -126,6 +131,10 @@ impl PermissionsManager {
         self.permissions.extend(other.permissions);
         self.implication_map.extend(other.implication_map);
         self.assignable_map.extend(other.assignable_map);
+
+        for (capability, permissions) in other.capabilities {
+            self.capabilities.entry(capability).or_default().extend(permissions);
+        }
     }
 
     // we should probably verify that added permissions are all part of what was in
@@ -140,6 +149,29 @@ impl PermissionsManager {
         self
     }
 
+    /// Declares that users need `permission` (or a permission implying it) for `capability`. If a
+    /// capability is granted by multiple permissions, having any one of them suffices
+    pub fn grants(mut self, capability: &'static str, permission: Permission) -> Self {
+        self.capabilities.entry(capability).or_default().insert(permission);
+        self
+    }
+
+    /// Resolves all declared capabilities for a user with the given permission bits
+    pub fn capabilities_of_bits(&self, permission_bits: u16) -> BTreeMap<&'static str, bool> {
+        let implied = self.implied_by_bits(permission_bits);
+
+        self.capabilities
+            .iter()
+            .map(|(&capability, permissions)| (capability, !implied.is_disjoint(permissions)))
+            .collect()
+    }
+
+    /// Whether a user with the given permission bits has the given capability. Undeclared
+    /// capabilities are never granted
+    pub fn has_capability(&self, permission_bits: u16, capability: &str) -> bool {
+        self.capabilities_of_bits(permission_bits).get(capability).copied().unwrap_or(false)
+    }
+
     pub fn implied_by(&self, permission: Permission) -> HashSet<Permission> {
         let mut implied = HashSet::new();
         implied.insert(permission);
@@ -189,6 +221,15 @@ impl PermissionsManager {
         assignable
     }
 
+    /// Looks up a permission by its name, ignoring case and whitespace (so that both `List Helper`
+    /// and `ListHelper` find the same permission)
+    pub fn by_name(&self, name: &str) -> Option<Permission> {
+        let normalize = |name: &str| name.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        let name = normalize(name);
+
+        self.permissions.iter().find(|perm| normalize(perm.name()) == name).copied()
+    }
+
     pub fn bits_to_permissions(&self, bits: u16) -> HashSet<Permission> {
         let mut perms = HashSet::new();
 
@@ -263,4 +304,15 @@ mod test {
     fn test_assignment() {
         assert_eq!(permission_manager().assignable_by(PERM4), set![PERM2, PERM5, PERM6]);
     }
+
+    #[test]
+    fn test_capabilities() {
+        let mut manager = permission_manager().grants("can_ban", PERM2);
+        manager.merge_with(PermissionsManager::new(vec![PERM5]).grants("can_ban", PERM5));
+
+        assert!(manager.has_capability(0x1, "can_ban"));
+        assert!(manager.has_capability(0x8, "can_ban"));
+        assert!(!manager.has_capability(0x4, "can_ban"));
+        assert!(!manager.has_capability(0x1, "can_fly"));
+    }
 }
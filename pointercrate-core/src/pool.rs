@@ -1,9 +1,235 @@
-use crate::{config, error::Result};
-use log::trace;
-use sqlx::{pool::PoolConnection, postgres::PgPoolOptions, PgConnection, Pool, Postgres, Transaction};
+use crate::{
+    config,
+    error::{CoreError, PointercrateError, Result},
+};
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use log::{info, trace, warn};
+use serde::Serialize;
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolConnection,
+    postgres::{PgPoolOptions, PgQueryResult, PgRow, PgStatement, PgTypeInfo},
+    Describe, Either, Execute, Executor, PgConnection, Pool, Postgres, Transaction,
+};
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::task::AbortHandle;
+
+/// The database migrations, embedded into the binary at compile time
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// The number of transactions retried by [`retry_on_conflict`] since startup
+static TRANSACTION_RETRIES: AtomicU64 = AtomicU64::new(0);
+
+/// How often [`retry_on_conflict`] attempts to run a transaction before giving up
+const MAX_TRANSACTION_ATTEMPTS: u32 = 5;
+
+/// Upper bound for the time [`retry_on_conflict`] waits between two attempts
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Source of the tokens identifying which request a connection was handed out to, see
+/// [`enforce_deadline`]
+static REQUEST_TOKENS: AtomicU64 = AtomicU64::new(0);
+
+pub type TransactionFuture<'c, T, E> = Pin<Box<dyn Future<Output = std::result::Result<T, E>> + Send + 'c>>;
 
 pub struct PointercratePool {
     connection_pool: Pool<Postgres>,
+
+    /// Separate, smaller pool for background work (scheduled jobs, video validation, data
+    /// refreshes), so that it cannot starve interactive requests of connections
+    background_pool: Pool<Postgres>,
+
+    /// The number of requests currently waiting for a connection to become available
+    waiting: AtomicUsize,
+
+    /// Maximal number of requests that may wait for a connection at the same time, see
+    /// [`DatabaseConfig::max_waiting`](config::DatabaseConfig::max_waiting)
+    max_waiting: usize,
+
+    /// Time budget for the database work of a single request, see [`config::request_timeout`]
+    request_timeout: Duration,
+}
+
+/// Utilization of the connection pool at some point in time
+#[derive(Serialize, Debug)]
+pub struct PoolStatistics {
+    /// The number of connections currently open, both idle and in use
+    pub size: u32,
+    pub idle: usize,
+
+    /// The number of requests currently waiting for a connection to become available
+    pub waiting: usize,
+
+    /// The number of transactions retried due to serialization failures or deadlocks since startup
+    pub transaction_retries: u64,
+}
+
+/// A connection (or transaction) handed out to a request, which is subject to the request's time
+/// budget
+///
+/// Dropping it aborts the task enforcing the budget (see [`enforce_deadline`]), as the connection
+/// goes back to the pool and no longer runs any of the request's statements.
+#[derive(Debug)]
+pub struct RequestConnection<C> {
+    connection: C,
+    _deadline: DeadlineGuard,
+}
+
+/// A transaction handed out to a request, see [`PointercratePool::transaction`]
+pub type RequestTransaction = RequestConnection<Transaction<'static, Postgres>>;
+
+/// Aborts the [`enforce_deadline`] task of a request when dropped
+#[derive(Debug)]
+struct DeadlineGuard(AbortHandle);
+
+impl Drop for DeadlineGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<C: DerefMut<Target = PgConnection>> Deref for RequestConnection<C> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.connection
+    }
+}
+
+impl<C: DerefMut<Target = PgConnection>> DerefMut for RequestConnection<C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.connection
+    }
+}
+
+impl RequestConnection<Transaction<'_, Postgres>> {
+    pub async fn commit(self) -> std::result::Result<(), sqlx::Error> {
+        self.connection.commit().await
+    }
+}
+
+/// A connection inside a `READ ONLY` transaction, for endpoints that only ever read data
+///
+/// Postgres rejects any write attempted through it, and since this type offers no way to commit
+/// its transaction, it is also impossible to accidentally persist anything through it. Dropping it
+/// rolls back the transaction.
+///
+/// Unlike the other connections handed out by [`PointercratePool`], it does not dereference to a
+/// [`PgConnection`], so it can only be passed to functions accepting a [`ReadConnection`].
+pub struct ReadOnlyConnection(RequestTransaction);
+
+impl ReadOnlyConnection {
+    /// Makes the given transaction `READ ONLY`
+    ///
+    /// Unlike the other transaction characteristics, postgres allows this at any point of a
+    /// transaction, so it also works for transactions that have already been used (e.g. to
+    /// authenticate a request).
+    pub async fn from_transaction(mut transaction: RequestTransaction) -> Result<Self> {
+        sqlx::query!("SET TRANSACTION READ ONLY").execute(&mut *transaction).await?;
+
+        Ok(ReadOnlyConnection(transaction))
+    }
+}
+
+/// Query executor for functions that only ever read data
+///
+/// Read-only model functions (`by_id`, `page`, etc.) take their connection as
+/// `&mut impl ReadConnection` and run their queries on the [`Reader`] obtained from it. As there is
+/// no way to get the underlying [`PgConnection`] back out of a `Reader`, such functions cannot call
+/// any model function that writes to the database, which all require a `&mut PgConnection`.
+#[derive(Debug)]
+pub struct Reader<'c>(&'c mut PgConnection);
+
+/// Connections that read-only model functions can run their queries on, see [`Reader`]
+pub trait ReadConnection: Send {
+    fn reader(&mut self) -> Reader<'_>;
+}
+
+impl ReadConnection for PgConnection {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(self)
+    }
+}
+
+impl ReadConnection for PoolConnection<Postgres> {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(self)
+    }
+}
+
+impl ReadConnection for Transaction<'_, Postgres> {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(self)
+    }
+}
+
+impl<C: DerefMut<Target = PgConnection> + Send> ReadConnection for RequestConnection<C> {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(self)
+    }
+}
+
+impl ReadConnection for ReadOnlyConnection {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(&mut self.0)
+    }
+}
+
+impl ReadConnection for Reader<'_> {
+    fn reader(&mut self) -> Reader<'_> {
+        Reader(self.0)
+    }
+}
+
+impl<'c> Executor<'c> for &'c mut Reader<'_> {
+    type Database = Postgres;
+
+    fn fetch_many<'e, 'q: 'e, E>(self, query: E) -> BoxStream<'e, std::result::Result<Either<PgQueryResult, PgRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        self.0.fetch_many(query)
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(self, query: E) -> BoxFuture<'e, std::result::Result<Option<PgRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        self.0.fetch_optional(query)
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self, sql: &'q str, parameters: &'e [PgTypeInfo],
+    ) -> BoxFuture<'e, std::result::Result<PgStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.0.prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(self, sql: &'q str) -> BoxFuture<'e, std::result::Result<Describe<Postgres>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.0.describe(sql)
+    }
+}
+
+/// Marks a request as waiting for a connection for as long as it is alive
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl PointercratePool {
@@ -11,21 +237,50 @@ impl PointercratePool {
         self.connection_pool.clone()
     }
 
+    /// The pool reserved for background work. Use this instead of [`PointercratePool::clone_inner`]
+    /// for anything not directly serving a request
+    pub fn clone_background(&self) -> Pool<Postgres> {
+        self.background_pool.clone()
+    }
+
     pub async fn init() -> Self {
         let pool = PointercratePool {
+            waiting: AtomicUsize::new(0),
+            max_waiting: config::get().database.max_waiting,
+            request_timeout: config::request_timeout(),
+            background_pool: PgPoolOptions::default()
+                .max_connections(config::get().database.background_connections)
+                .connect(&config::database_url())
+                .await
+                .expect("Failed to connect to pointercrate database"),
             connection_pool: PgPoolOptions::default()
-                .max_connections(20)
+                .max_connections(config::get().database.max_connections)
+                .acquire_timeout(config::request_timeout())
+                // Connections used outside of requests (e.g. by the scheduler) should not inherit
+                // the statement timeout of whatever request used them last, nor be mistaken for it
+                // by enforce_deadline
+                .after_release(|connection, _| {
+                    Box::pin(async move {
+                        sqlx::query!("RESET statement_timeout").execute(&mut *connection).await?;
+                        sqlx::query!("RESET application_name").execute(connection).await?;
+
+                        Ok(true)
+                    })
+                })
                 .connect(&config::database_url())
                 .await
                 .expect("Failed to connect to pointercrate database"),
         };
 
-        pool.run_migrations().await;
+        if config::migrate_on_startup() {
+            pool.run_migrations().await;
+        }
 
         pool
     }
 
-    async fn run_migrations(&self) {
+    /// Applies all pending migrations embedded into this binary
+    pub async fn run_migrations(&self) {
         let row = sqlx::query!(
             r#"
 SELECT EXISTS (
@@ -48,34 +303,333 @@ AND NOT EXISTS (
             panic!("Database has not been switched from diesel migrations to sqlx migrations. Please run the final migration from https://github.com/stadust/pointercrate-migration to switch")
         }
 
-        sqlx::migrate!("../migrations")
-            .run(&self.connection_pool)
-            .await
-            .expect("Failed to run migrations");
+        MIGRATOR.run(&self.connection_pool).await.expect("Failed to run migrations");
+
+        info!("Database schema is up to date");
+    }
+
+    /// Versions of all successfully applied migrations, in ascending order
+    pub async fn applied_migrations(&self) -> Result<Vec<i64>> {
+        Ok(sqlx::query!("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+            .fetch_all(&self.connection_pool)
+            .await?
+            .into_iter()
+            .map(|row| row.version)
+            .collect())
+    }
+
+    /// Versions of all migrations embedded into this binary that have not yet been applied
+    pub async fn pending_migrations(&self) -> Result<Vec<i64>> {
+        let applied = self.applied_migrations().await?;
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+
+    /// Overrides the time budget for the database work of a single request, which defaults to
+    /// [`config::request_timeout`]
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Overrides the maximal number of requests that may wait for a connection at the same time,
+    /// which defaults to [`DatabaseConfig::max_waiting`](config::DatabaseConfig::max_waiting)
+    pub fn with_max_waiting(mut self, max_waiting: usize) -> Self {
+        self.max_waiting = max_waiting;
+        self
+    }
+
+    pub fn statistics(&self) -> PoolStatistics {
+        PoolStatistics {
+            size: self.connection_pool.size(),
+            idle: self.connection_pool.num_idle(),
+            waiting: self.waiting.load(Ordering::Relaxed),
+            transaction_retries: TRANSACTION_RETRIES.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Registers the current request as waiting for a connection, rejecting it if too many requests
+    /// are waiting already (see [`DatabaseConfig::max_waiting`](config::DatabaseConfig::max_waiting))
+    fn enter_queue(&self) -> Result<WaitingGuard<'_>> {
+        let waiting = self.waiting.fetch_add(1, Ordering::Relaxed);
+        let guard = WaitingGuard(&self.waiting);
+
+        if self.max_waiting != 0 && waiting >= self.max_waiting {
+            warn!("Shedding load, {} requests are already waiting for a database connection", waiting);
+
+            return Err(CoreError::DatabaseOverloaded);
+        }
+
+        Ok(guard)
     }
 
     /// Gets a connection from the connection pool
-    pub async fn connection(&self) -> Result<PoolConnection<Postgres>> {
+    pub async fn connection(&self) -> Result<RequestConnection<PoolConnection<Postgres>>> {
+        let requested_at = Instant::now();
+        let guard = self.enter_queue()?;
         let mut connection = self.connection_pool.acquire().await?;
 
-        audit_connection(&mut *connection, 0).await?;
+        drop(guard);
+
+        let deadline = self.apply_request_timeout(&mut connection, requested_at).await?;
+        audit_connection(&mut connection, 0).await?;
+
+        Ok(RequestConnection {
+            connection,
+            _deadline: deadline,
+        })
+    }
+
+    /// Gets a connection from the connection pool that can only be used for reading data
+    pub async fn read_only_connection(&self) -> Result<ReadOnlyConnection> {
+        let requested_at = Instant::now();
+        let guard = self.enter_queue()?;
+        let mut transaction = self.connection_pool.begin().await?;
+
+        drop(guard);
+
+        // Since nothing can be written, there is no need for audit_connection
+        sqlx::query!("SET TRANSACTION READ ONLY").execute(&mut *transaction).await?;
+        let deadline = self.apply_request_timeout(&mut transaction, requested_at).await?;
+
+        Ok(ReadOnlyConnection(RequestConnection {
+            connection: transaction,
+            _deadline: deadline,
+        }))
+    }
+
+    /// Gets a connection from the pool reserved for background work. Unlike connections for
+    /// requests, these are not subject to a time budget
+    pub async fn background_connection(&self) -> Result<PoolConnection<Postgres>> {
+        let mut connection = self.background_pool.acquire().await?;
+
+        audit_connection(&mut connection, 0).await?;
 
         Ok(connection)
     }
 
-    pub async fn transaction(&self) -> Result<Transaction<'static, Postgres>> {
+    /// Starts a transaction on the pool reserved for background work. Unlike
+    /// [`PointercratePool::transaction`], it is not subject to a time budget, so it is meant for
+    /// long running work outside of requests, such as CLI commands. Changes are attributed to the
+    /// system user (id 0) in audit logs
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>> {
+        let mut transaction = self.background_pool.begin().await?;
+
+        audit_connection(&mut transaction, 0).await?;
+
+        Ok(transaction)
+    }
+
+    pub async fn transaction(&self) -> Result<RequestTransaction> {
+        let requested_at = Instant::now();
+        let guard = self.enter_queue()?;
         let mut connection = self.connection_pool.begin().await?;
 
-        audit_connection(&mut *connection, 0).await?;
+        drop(guard);
 
-        Ok(connection)
+        let deadline = self.apply_request_timeout(&mut connection, requested_at).await?;
+        audit_connection(&mut connection, 0).await?;
+
+        Ok(RequestConnection {
+            connection,
+            _deadline: deadline,
+        })
+    }
+
+    /// Limits the runtime of all queries on the given connection to what is left of the request's
+    /// time budget after waiting for the connection (see [`config::request_timeout`]). Postgres
+    /// cancels queries exceeding it, which surfaces as [`CoreError::QueryTimeout`]. If the budget was
+    /// already used up while waiting, the request is abandoned with [`CoreError::RequestTimeout`]
+    async fn apply_request_timeout(&self, connection: &mut PgConnection, requested_at: Instant) -> Result<DeadlineGuard> {
+        let waited = requested_at.elapsed();
+        let remaining = self.request_timeout.saturating_sub(waited);
+
+        if remaining.is_zero() {
+            warn!("Abandoning request after waiting {:?} for a database connection", waited);
+
+            return Err(CoreError::RequestTimeout);
+        }
+
+        let token = format!("pointercrate request {}", REQUEST_TOKENS.fetch_add(1, Ordering::Relaxed));
+
+        // statement_timeout and application_name are session settings, so they have to be reset
+        // every time a pooled connection is handed out
+        let pid = sqlx::query!(
+            r#"SELECT set_config('statement_timeout', $1, false) AS statement_timeout, set_config('application_name', $2, false) AS application_name,
+                      pg_backend_pid() AS "pid!""#,
+            remaining.as_millis().to_string(),
+            token
+        )
+        .fetch_one(connection)
+        .await?
+        .pid;
+
+        let deadline = tokio::spawn(enforce_deadline(
+            self.background_pool.clone(),
+            pid,
+            token,
+            requested_at + self.request_timeout,
+        ));
+
+        Ok(DeadlineGuard(deadline.abort_handle()))
     }
 }
 
 // Used for integration tests, when sqlx::test sets up a pool for us
 impl From<Pool<Postgres>> for PointercratePool {
     fn from(connection_pool: Pool<Postgres>) -> Self {
-        PointercratePool { connection_pool }
+        PointercratePool {
+            background_pool: connection_pool.clone(),
+            connection_pool,
+            waiting: AtomicUsize::new(0),
+            max_waiting: config::get().database.max_waiting,
+            request_timeout: config::request_timeout(),
+        }
+    }
+}
+
+/// Runs the given operation inside a transaction on a connection from the given pool, and commits
+/// it. If postgres aborts the transaction due to a serialization failure or deadlock (see
+/// [`CoreError::TransactionConflict`]), the whole operation is retried with exponential backoff,
+/// up to [`MAX_TRANSACTION_ATTEMPTS`] times.
+///
+/// Meant for bulk operations touching many rows, which are the ones most likely to conflict with
+/// concurrent transactions. Note that the operation must not have side effects outside of the
+/// database, as they would be repeated.
+///
+/// Changes made by the operation are attributed to the given user in audit logs (see
+/// [`audit_connection`]). Background jobs not acting on behalf of anyone in particular use the
+/// system user (id 0).
+pub async fn retry_on_conflict<T, E, F>(pool: &Pool<Postgres>, user_id: i32, operation: F) -> std::result::Result<T, E>
+where
+    E: PointercrateError,
+    F: for<'c> FnMut(&'c mut PgConnection) -> TransactionFuture<'c, T, E>,
+{
+    let begin = || async move {
+        let mut transaction = pool.begin().await?;
+
+        audit_connection(&mut transaction, user_id).await?;
+
+        Ok::<_, CoreError>(transaction)
+    };
+
+    retry(None, || async { begin().await.map_err(E::from) }, operation).await
+}
+
+impl PointercratePool {
+    /// Like [`retry_on_conflict`], but for operations performed on behalf of a request
+    ///
+    /// The first attempt runs in the given transaction, usually the one the request was
+    /// authenticated in. Further attempts run in fresh [transactions](PointercratePool::transaction),
+    /// in which changes are attributed to the given user in audit logs (see [`audit_connection`]).
+    pub async fn retry_on_conflict<T, E, F>(&self, transaction: RequestTransaction, user_id: i32, operation: F) -> std::result::Result<T, E>
+    where
+        E: PointercrateError,
+        F: for<'c> FnMut(&'c mut PgConnection) -> TransactionFuture<'c, T, E>,
+    {
+        let begin = || async move {
+            let mut transaction = self.transaction().await?;
+
+            audit_connection(&mut transaction, user_id).await?;
+
+            Ok(transaction)
+        };
+
+        retry(Some(transaction), || async { begin().await.map_err(E::from) }, operation).await
+    }
+}
+
+/// Transactions [`retry`] can run operations in
+trait RetryableTransaction: DerefMut<Target = PgConnection> + Send {
+    fn commit(self) -> impl Future<Output = std::result::Result<(), sqlx::Error>> + Send;
+}
+
+impl RetryableTransaction for Transaction<'_, Postgres> {
+    fn commit(self) -> impl Future<Output = std::result::Result<(), sqlx::Error>> + Send {
+        Transaction::commit(self)
+    }
+}
+
+impl RetryableTransaction for RequestConnection<Transaction<'_, Postgres>> {
+    fn commit(self) -> impl Future<Output = std::result::Result<(), sqlx::Error>> + Send {
+        RequestConnection::commit(self)
+    }
+}
+
+/// Runs the given operation in the given transaction (or a transaction obtained from `begin`, if
+/// none is given) and commits it, retrying in fresh transactions from `begin` on conflicts
+async fn retry<T, E, F, C, B, Fut>(mut transaction: Option<C>, mut begin: B, mut operation: F) -> std::result::Result<T, E>
+where
+    E: PointercrateError,
+    F: for<'c> FnMut(&'c mut PgConnection) -> TransactionFuture<'c, T, E>,
+    C: RetryableTransaction,
+    B: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<C, E>>,
+{
+    let conflict_code = CoreError::TransactionConflict.error_code();
+    let mut attempt = 1;
+
+    loop {
+        let mut transaction = match transaction.take() {
+            Some(transaction) => transaction,
+            None => begin().await?,
+        };
+
+        let result = match operation(&mut transaction).await {
+            Ok(value) => transaction.commit().await.map(|_| value).map_err(|err| CoreError::from(err).into()),
+            Err(err) => {
+                // Roll back right away instead of holding on to the connection while backing off
+                drop(transaction);
+
+                Err(err)
+            },
+        };
+
+        match result {
+            Err(err) if err.error_code() == conflict_code && attempt < MAX_TRANSACTION_ATTEMPTS => {
+                let backoff = (Duration::from_millis(25) * 2u32.pow(attempt)).min(MAX_RETRY_BACKOFF);
+
+                warn!(
+                    "Transaction conflicted with a concurrent one (attempt {}), retrying in {:?}",
+                    attempt, backoff
+                );
+
+                TRANSACTION_RETRIES.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            },
+            result => return result,
+        }
+    }
+}
+
+/// `statement_timeout` only limits single statements, so a request running many of them could still
+/// exceed its time budget. Once the budget is used up, this cancels whatever statement the request
+/// is still running on its connection, which again surfaces as [`CoreError::QueryTimeout`].
+///
+/// The connection is identified by both its backend PID and the token in its `application_name`,
+/// so that nothing is cancelled if it was returned to the pool (and maybe handed out to a different
+/// request) in the meantime
+async fn enforce_deadline(pool: Pool<Postgres>, pid: i32, token: String, deadline: Instant) {
+    tokio::time::sleep_until(deadline.into()).await;
+
+    let cancelled = sqlx::query!(
+        r#"SELECT pg_cancel_backend(pid) AS "cancelled!" FROM pg_stat_activity WHERE pid = $1 AND application_name = $2 AND state = 'active'"#,
+        pid,
+        token
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match cancelled {
+        Ok(Some(_)) => warn!("Cancelled query of request ({}) that used up its time budget", token),
+        Ok(None) => (),
+        Err(err) => warn!("Failed to enforce time budget of request ({}): {}", token, err),
     }
 }
 
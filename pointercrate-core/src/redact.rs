@@ -0,0 +1,89 @@
+//! Permission based redaction of sensitive fields
+//!
+//! Some objects contain data that only parts of the list staff are allowed to see (e.g. the
+//! submitter of a record, or the reason for a user's ban). Instead of maintaining a copy of such
+//! structs per audience, they implement [`Redact`], and endpoints serialize them via the
+//! [`Redacted`] wrapper, which removes everything the requester is not allowed to see.
+
+use crate::{etag::Taggable, permission::Permission};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::HashSet,
+    hash::{Hash, Hasher},
+};
+
+/// The permissions of whoever a response is generated for, including all permissions implied by
+/// them. The default context is that of an unauthenticated request
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ViewContext {
+    permissions: HashSet<Permission>,
+}
+
+impl ViewContext {
+    /// Constructs a context from a set of permissions. The set must already include all implied
+    /// permissions, see [`PermissionsManager::implied_by_bits`](crate::permission::PermissionsManager::implied_by_bits)
+    pub fn new(permissions: HashSet<Permission>) -> Self {
+        ViewContext { permissions }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
+
+/// Trait for objects containing fields that should not be visible to everyone
+pub trait Redact {
+    /// Removes all data from this object that should not be visible in the given context
+    fn redact(&mut self, context: &ViewContext);
+}
+
+impl<T: Redact> Redact for Option<T> {
+    fn redact(&mut self, context: &ViewContext) {
+        if let Some(inner) = self {
+            inner.redact(context)
+        }
+    }
+}
+
+impl<T: Redact> Redact for Vec<T> {
+    fn redact(&mut self, context: &ViewContext) {
+        for inner in self {
+            inner.redact(context)
+        }
+    }
+}
+
+/// An object that has been redacted for a specific [`ViewContext`], and can thus be safely
+/// serialized in responses generated for that context
+#[derive(Debug)]
+pub struct Redacted<T>(T);
+
+impl<T: Redact> Redacted<T> {
+    pub fn new(mut inner: T, context: &ViewContext) -> Self {
+        inner.redact(context);
+
+        Redacted(inner)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Serialize> Serialize for Redacted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T: Hash> Hash for Redacted<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: Taggable> Taggable for Redacted<T> {
+    fn patch_part(&self) -> u64 {
+        self.0.patch_part()
+    }
+}
@@ -0,0 +1,167 @@
+//! Declarative validation of request payloads
+//!
+//! Payloads of `POST` and `PATCH` requests implement [`Validate`], describing the constraints on each
+//! of their fields via a [`Validator`]. Calling [`validated`] on a freshly deserialized payload
+//! normalizes it and then checks all constraints at once, so that a client gets told about every
+//! invalid field instead of only the first one.
+//!
+//! Checks that require database access (uniqueness, positions, ...) are out of scope for this module
+//! and still happen in the respective `create_from`/`apply_patch` functions.
+
+use crate::error::{CoreError, PointercrateError};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+/// A single failed constraint, as reported inside [`CoreError::InvalidFields`]
+#[derive(Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct FieldError {
+    /// The name of the offending field, as it appears in the request body
+    pub field: &'static str,
+
+    /// The error code that would have been returned had this been the only invalid field
+    pub code: u16,
+
+    pub message: String,
+}
+
+/// Collects the constraint violations of a payload
+pub struct Validator<E> {
+    errors: Vec<(&'static str, E)>,
+}
+
+impl<E: PointercrateError> Default for Validator<E> {
+    fn default() -> Self {
+        Validator { errors: Vec::new() }
+    }
+}
+
+impl<E: PointercrateError> Validator<E> {
+    /// Records `error` for `field` unless `valid` holds
+    pub fn check(&mut self, field: &'static str, valid: bool, error: impl FnOnce() -> E) -> &mut Self {
+        if !valid {
+            self.errors.push((field, error()));
+        }
+        self
+    }
+
+    /// Records the error of `result` for `field`, if any
+    pub fn result<T, F: Into<E>>(&mut self, field: &'static str, result: Result<T, F>) -> &mut Self {
+        if let Err(err) = result {
+            self.errors.push((field, err.into()));
+        }
+        self
+    }
+
+    /// Checks that `value` has between `range.start()` and `range.end()` characters (inclusive)
+    pub fn length(&mut self, field: &'static str, value: &str, range: RangeInclusive<usize>, error: impl FnOnce() -> E) -> &mut Self {
+        self.check(field, range.contains(&value.chars().count()), error)
+    }
+
+    /// Checks that `value` is a valid percentage, i.e. lies between 0 and 100 (inclusive)
+    pub fn percentage(&mut self, field: &'static str, value: i16, error: impl FnOnce() -> E) -> &mut Self {
+        self.check(field, (0..=100).contains(&value), error)
+    }
+
+    /// Turns the collected violations into a result.
+    ///
+    /// If exactly one field is invalid, its error is returned unchanged, so that clients relying on
+    /// the specific error codes keep working. If multiple fields are invalid, a
+    /// [`CoreError::InvalidFields`] listing all of them is returned.
+    pub fn finish(mut self) -> Result<(), E> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.remove(0).1),
+            _ => Err(CoreError::InvalidFields {
+                errors: self
+                    .errors
+                    .into_iter()
+                    .map(|(field, error)| FieldError {
+                        field,
+                        code: error.error_code(),
+                        message: error.to_string(),
+                    })
+                    .collect(),
+            }
+            .into()),
+        }
+    }
+}
+
+pub trait Validate {
+    type Error: PointercrateError;
+
+    /// Brings the payload into canonical form (e.g. by trimming names). Called before
+    /// [`Validate::validate`]
+    fn normalize(&mut self) {}
+
+    fn validate(&self, validator: &mut Validator<Self::Error>);
+}
+
+/// Normalizes and validates the given payload, returning it if all constraints hold
+pub fn validated<T: Validate>(mut payload: T) -> Result<T, T::Error> {
+    payload.normalize();
+
+    let mut validator = Validator::default();
+    payload.validate(&mut validator);
+    validator.finish()?;
+
+    Ok(payload)
+}
+
+/// Removes leading and trailing whitespace from the given name, and collapses all inner runs of
+/// whitespace into a single space
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::CoreError,
+        validate::{normalize_name, FieldError, Validator},
+    };
+
+    #[test]
+    fn test_single_error_is_returned_unchanged() {
+        let mut validator = Validator::<CoreError>::default();
+
+        validator
+            .check("a", true, || CoreError::BadRequest)
+            .percentage("b", 101, || CoreError::UnprocessableEntity);
+
+        assert_eq!(validator.finish(), Err(CoreError::UnprocessableEntity));
+    }
+
+    #[test]
+    fn test_multiple_errors_are_collected() {
+        let mut validator = Validator::<CoreError>::default();
+
+        validator
+            .length("a", "", 1..=10, || CoreError::BadRequest)
+            .percentage("b", -1, || CoreError::UnprocessableEntity);
+
+        assert_eq!(
+            validator.finish(),
+            Err(CoreError::InvalidFields {
+                errors: vec![
+                    FieldError {
+                        field: "a",
+                        code: 40000,
+                        message: CoreError::BadRequest.to_string()
+                    },
+                    FieldError {
+                        field: "b",
+                        code: 42200,
+                        message: CoreError::UnprocessableEntity.to_string()
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Bloodbath "), "Bloodbath");
+        assert_eq!(normalize_name("Sonic \t Wave"), "Sonic Wave");
+    }
+}
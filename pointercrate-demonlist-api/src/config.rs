@@ -1,7 +1,3 @@
 pub fn submission_webhook() -> Option<String> {
-    std::env::var("DISCORD_WEBHOOK").ok()
-}
-
-pub fn abstract_api_key() -> Option<String> {
-    std::env::var("ABSTRACT_API_KEY").ok()
+    pointercrate_core::config::get().integrations.discord_webhook.clone()
 }
@@ -0,0 +1,58 @@
+//! Endpoints providing the data needed by the demonlist tabs of the account page, allowing them to
+//! be loaded lazily. Each endpoint is gated the same way as the tab it belongs to.
+
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::{
+    config,
+    demon::{current_list, Demon, MinimalDemon},
+    record::{submission_count, under_consideration_count},
+    LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_user_api::auth::ReadOnlyTokenAuth;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct RecordQueueTab {
+    /// The number of records with status `submitted`
+    pub submitted: i64,
+
+    /// The number of records with status `under consideration`
+    pub under_consideration: i64,
+
+    /// All demons records can be submitted for, used to populate the tab's demon selections
+    pub demons: Vec<MinimalDemon>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DemonEditorTab {
+    pub list_size: i16,
+    pub extended_list_size: i16,
+    pub demons: Vec<Demon>,
+}
+
+#[rocket::get("/records")]
+pub async fn record_queue(mut auth: ReadOnlyTokenAuth) -> Result<Json<RecordQueueTab>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(RecordQueueTab {
+        submitted: submission_count(&mut auth.connection).await?,
+        under_consideration: under_consideration_count(&mut auth.connection).await?,
+        demons: current_list(&mut auth.connection)
+            .await?
+            .into_iter()
+            .map(|demon| demon.base)
+            .collect(),
+    }))
+}
+
+#[rocket::get("/demons")]
+pub async fn demon_editor(mut auth: ReadOnlyTokenAuth) -> Result<Json<DemonEditorTab>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Ok(Json(DemonEditorTab {
+        list_size: config::list_size(),
+        extended_list_size: config::extended_list_size(),
+        demons: current_list(&mut auth.connection).await?,
+    }))
+}
@@ -0,0 +1,119 @@
+//! Endpoints serving images frontends need when displaying players: nation and subdivision flags,
+//! and player avatars
+//!
+//! Like the [widgets](super::widget), these can be used from any origin. Flags never change while
+//! pointercrate is running, so they may be cached for a long time. Avatars are tagged with the
+//! `avatar:<player id>` surrogate key, which is purged whenever a player's avatar changes.
+
+use crate::endpoints::widget::embeddable;
+use pointercrate_core::{config, pool::PointercratePool};
+use pointercrate_core_api::{error::Result, response::Response2, upload::StorageHandle};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::{
+        avatar::{avatar_size, PlayerAvatar},
+        PlayerId,
+    },
+};
+use pointercrate_user::MODERATOR;
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::ContentType, tokio::fs, State};
+use std::path::Path;
+
+/// How long (in seconds) flags may be cached
+const FLAG_MAX_AGE: u32 = 86400;
+
+/// How long (in seconds) avatars may be cached
+const AVATAR_MAX_AGE: u32 = 3600;
+
+pub(crate) fn avatar_key(player_id: i32) -> String {
+    format!("avatar:{}", player_id)
+}
+
+/// Normalizes an ISO country or subdivision code as given in a flag URL, optionally suffixed with
+/// `.svg`. Returns `None` for anything that cannot be such a code, so that it never makes it into a
+/// file system path
+fn flag_code(code: &str) -> Option<String> {
+    let code = code.strip_suffix(".svg").unwrap_or(code);
+
+    if code.is_empty() || code.len() > 3 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some(code.to_ascii_lowercase())
+}
+
+async fn flag(path: String) -> Option<Response2<(ContentType, Vec<u8>)>> {
+    let svg = fs::read(Path::new(&config::flags_directory()).join(path)).await.ok()?;
+
+    Some(embeddable(Response2::new((ContentType::SVG, svg)).cache_for(FLAG_MAX_AGE, "flags")))
+}
+
+#[rocket::get("/flags/<nation>")]
+pub async fn nation_flag(nation: &str) -> Result<Response2<(ContentType, Vec<u8>)>> {
+    let not_found = || DemonlistError::NationalityNotFound {
+        iso_code: nation.to_string(),
+    };
+    let nation = flag_code(nation).ok_or_else(not_found)?;
+
+    Ok(flag(format!("{}.svg", nation)).await.ok_or_else(not_found)?)
+}
+
+#[rocket::get("/flags/<nation>/<subdivision>")]
+pub async fn subdivision_flag(nation: &str, subdivision: &str) -> Result<Response2<(ContentType, Vec<u8>)>> {
+    let not_found = || DemonlistError::SubdivisionNotFound {
+        subdivision_code: subdivision.to_string(),
+        nation_code: nation.to_string(),
+    };
+    let (nation, subdivision) = flag_code(nation).zip(flag_code(subdivision)).ok_or_else(not_found)?;
+
+    Ok(flag(format!("{}/{}.svg", nation, subdivision)).await.ok_or_else(not_found)?)
+}
+
+/// The given player's avatar as PNG, in the smallest available size at least as large as `size`
+/// (see [`avatar_size`]). This is the latest approved revision of the avatar, except for
+/// moderators, who are served the latest revision even while it awaits approval
+#[rocket::get("/avatars/<player_id>?<size>")]
+pub async fn avatar(
+    player_id: i32, size: Option<u32>, auth: Option<TokenAuth>, pool: &State<PointercratePool>, storage: &State<StorageHandle>,
+) -> Result<Response2<(ContentType, Vec<u8>)>> {
+    let is_moderator = auth.as_ref().is_some_and(|auth| auth.has_permission(MODERATOR));
+    let avatar = PlayerAvatar::by_player(PlayerId(player_id), &mut pool.read_only_connection().await?).await?;
+
+    // Moderators see the latest revision, everyone else the latest approved one
+    let revision = match avatar.approved_revision {
+        _ if is_moderator => avatar.revision,
+        Some(revision) => revision,
+        None => return Err(DemonlistError::AvatarNotFound { player_id }.into()),
+    };
+
+    let png = storage
+        .read(&avatar.storage_key(revision, avatar_size(size)))
+        .await?
+        .ok_or(DemonlistError::AvatarNotFound { player_id })?;
+
+    let response = Response2::new((ContentType::PNG, png));
+
+    // Unapproved revisions are only ever served to moderators, so they must not end up in any cache
+    if avatar.approved_revision != Some(revision) {
+        return Ok(response.with_header("Cache-Control", "no-store"));
+    }
+
+    Ok(embeddable(response.cache_for(AVATAR_MAX_AGE, avatar_key(player_id))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::flag_code;
+
+    #[test]
+    fn test_flag_code() {
+        assert_eq!(flag_code("DE"), Some("de".to_string()));
+        assert_eq!(flag_code("de.svg"), Some("de".to_string()));
+        assert_eq!(flag_code("b"), Some("b".to_string()));
+        assert_eq!(flag_code(""), None);
+        assert_eq!(flag_code(".."), None);
+        assert_eq!(flag_code("../de"), None);
+        assert_eq!(flag_code("abcd"), None);
+    }
+}
@@ -0,0 +1,14 @@
+use crate::endpoints::demon::CACHE_MAX_AGE;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::changelog::{current_week, parse_week, weekly_changelog, Changelog};
+use rocket::{serde::json::Json, State};
+
+/// The changelog of the given ISO week (e.g. `2024-W42`), or of the current week if none is given
+#[rocket::get("/changelog?<week>")]
+pub async fn changelog(week: Option<&str>, pool: &State<PointercratePool>) -> Result<Response2<Json<Changelog>>> {
+    let week = week.map(parse_week).transpose()?.unwrap_or_else(current_week);
+    let changelog = weekly_changelog(week, &mut pool.read_only_connection().await?).await?;
+
+    Ok(Response2::json(changelog).cache_for(CACHE_MAX_AGE, "overview"))
+}
@@ -1,44 +1,212 @@
-use crate::ratelimits::DemonlistRatelimits;
-use pointercrate_core::{audit::AuditLogEntry, pool::PointercratePool};
+use crate::{pages::render_demon_page, ratelimits::DemonlistRatelimits};
+use pointercrate_core::{audit::AuditLogEntry, patch::Patch, pool::PointercratePool};
 use pointercrate_core_api::{
+    cache::CachePurge,
+    documentation::documented,
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
+    mail::{Email, MailerHandle},
     pagination::pagination_response,
+    param::IdOrName,
     query::Query,
-    response::Response2,
+    response::{Page, Response2, ViewPreferences},
 };
 use pointercrate_demonlist::{
-    creator::{Creator, PostCreator},
+    creator::{Creator, PatchCreator, PostCreator},
     demon::{
         audit::{DemonModificationData, MovementLogEntry},
-        Demon, DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon, PostDemon,
+        credit::{self, CreditKind, PostCredit},
+        search_demons, ArchivedDemon, Demon, DemonDraft, DemonId, DemonIdPagination, DemonPositionPagination, DemonSearchResult, FullDemon,
+        ListSection, ListedDemon, MinimalDemon, PatchDemon, PatchDemonDraft, PatchReverification, PostDemon, PostDemonDraft,
+        PostReverification, RestoreDemon, Reverification,
     },
     error::DemonlistError,
-    player::{recompute_scores, DatabasePlayer},
-    LIST_ADMINISTRATOR, LIST_MODERATOR,
+    player::{recompute_scores, DatabasePlayer, PlayerId},
+    record::{approved_record_summary, approved_records_by_nationality, approved_records_page_on, MinimalRecordP, NationalityRecordCount},
+    watch::{self, WatchTarget},
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
-use pointercrate_user_api::auth::TokenAuth;
+use pointercrate_integrate::gd::GeometryDashConnector;
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
 use rocket::{http::Status, serde::json::Json, State};
+use serde::Deserialize;
+
+/// How long (in seconds) public demon data may be served from the response cache. Modifications
+/// made through the API purge the affected responses, so this only bounds how stale data can get
+/// after changes made by background jobs (e.g. record imports), which do not purge anything
+pub(crate) const CACHE_MAX_AGE: u32 = 300;
+
+/// The surrogate key of all cached responses containing information about the given demon
+pub(crate) fn demon_key(demon_id: i32) -> String {
+    format!("demon:{}", demon_id)
+}
+
+/// The surrogate keys to tag cached API responses about the given demon with. Like the demon's
+/// page, these also carry the `overview` key, since moving other demons changes the demon's
+/// position
+pub(crate) fn demon_response_keys(demon_id: i32) -> String {
+    format!("{} overview", demon_key(demon_id))
+}
 
 #[rocket::get("/")]
 pub async fn paginate(pool: &State<PointercratePool>, pagination: Query<DemonIdPagination>) -> Result<Response2<Json<Vec<Demon>>>> {
-    Ok(pagination_response("/api/v2/demons/", pagination.0, &mut *pool.connection().await?).await?)
+    Ok(pagination_response("/api/v2/demons/", pagination.0, &mut pool.read_only_connection().await?).await?)
 }
 
+/// The demons on the list (i.e. those with a position), ordered by position. Supports the
+/// usual pagination parameters, with `before` and `after` referring to positions
+#[documented(example = "/api/v2/demons/listed/?limit=5")]
 #[rocket::get("/listed")]
 pub async fn paginate_listed(
     pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>,
 ) -> Result<Response2<Json<Vec<Demon>>>> {
-    Ok(pagination_response("/api/v2/demons/listed/", pagination.0, &mut *pool.connection().await?).await?)
+    Ok(
+        pagination_response("/api/v2/demons/listed/", pagination.0, &mut pool.read_only_connection().await?)
+            .await?
+            .cache_for(CACHE_MAX_AGE, "overview"),
+    )
+}
+
+#[rocket::get("/listed/compact")]
+pub async fn paginate_listed_compact(
+    pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>,
+) -> Result<Response2<Json<Vec<ListedDemon>>>> {
+    Ok(pagination_response(
+        "/api/v2/demons/listed/compact/",
+        pagination.0,
+        &mut pool.read_only_connection().await?,
+    )
+    .await?
+    .cache_for(CACHE_MAX_AGE, "overview"))
+}
+
+/// The demons best matching the (partial) name `q`, for search-as-you-type inputs
+#[rocket::get("/search?<q>")]
+pub async fn search(q: &str, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<DemonSearchResult>>>> {
+    let results = search_demons(q, &mut pool.read_only_connection().await?).await?;
+
+    Ok(Response2::json(results).cache_for(CACHE_MAX_AGE, "overview"))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RandomDemonQuery {
+    #[serde(default)]
+    list: Option<ListSection>,
+}
+
+#[rocket::get("/random")]
+pub async fn random(pool: &State<PointercratePool>, query: Query<RandomDemonQuery>) -> Result<Json<Demon>> {
+    Ok(Json(Demon::random(query.0.list, &mut pool.read_only_connection().await?).await?))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DemonLookupQuery {
+    name: String,
+
+    /// Name of the demon's publisher, needed if multiple demons share the given name
+    #[serde(default)]
+    publisher: Option<String>,
+}
+
+#[rocket::get("/lookup")]
+pub async fn lookup(pool: &State<PointercratePool>, query: Query<DemonLookupQuery>) -> Result<Tagged<FullDemon>> {
+    let query = query.0;
+
+    Ok(Tagged(
+        FullDemon::by_name(&query.name, query.publisher.as_deref(), &mut pool.read_only_connection().await?).await?,
+    ))
+}
+
+/// The API representation of a single demon. Since the same URLs serve the demon's page to clients
+/// asking for HTML, caches need to take the `Accept` header into account.
+///
+/// Each URL keeps its primary representation for clients that do not state a preference (i.e. send
+/// `Accept: */*` or no `Accept` header at all): JSON for the API, HTML for `/list/<position>`
+pub(crate) fn full_demon_response(demon: FullDemon) -> Response2<Tagged<FullDemon>> {
+    let demon_id = demon.demon.base.id;
+
+    Response2::tagged(demon)
+        .cache_for(CACHE_MAX_AGE, demon_response_keys(demon_id.0))
+        .with_header("Vary", "Accept")
+}
+
+/// A single demon
+///
+/// Accepts either the demon's ID or its (case insensitive) name, see [`IdOrName`]. Ambiguous names
+/// are rejected the same way as by [`lookup`]
+#[documented(example = "/api/v2/demons/1/")]
+#[rocket::get("/<demon>", format = "json", rank = 1)]
+pub async fn get(demon: IdOrName<'_>, pool: &State<PointercratePool>) -> Result<Response2<Tagged<FullDemon>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = match demon {
+        IdOrName::Id(demon_id) => FullDemon::by_id(DemonId(demon_id), &mut connection).await?,
+        IdOrName::Name(name) => FullDemon::by_name(name, None, &mut connection).await?,
+    };
+
+    Ok(full_demon_response(demon))
 }
 
-#[rocket::get("/<demon_id>")]
-pub async fn get(demon_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullDemon>> {
-    Ok(Tagged(FullDemon::by_id(demon_id, &mut *pool.connection().await?).await?))
+/// Renders the page of the given demon for clients asking for HTML, see
+/// [`demon_page`](crate::pages::demon_page)
+#[rocket::get("/<demon>?<page>", format = "html", rank = 2)]
+pub async fn get_page(
+    demon: IdOrName<'_>, page: Option<i64>, pool: &State<PointercratePool>, gd: &State<GeometryDashConnector>, auth: Option<TokenAuth>,
+    preferences: &ViewPreferences,
+) -> Result<Response2<Page>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = match demon {
+        IdOrName::Id(demon_id) => Demon::by_id(DemonId(demon_id), &mut connection).await?,
+        IdOrName::Name(name) => Demon::by_name(name, &mut connection).await?,
+    };
+
+    render_demon_page(demon, page, &mut connection, gd, auth, preferences).await
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DemonRecordsQuery {
+    /// The page to return, starting at `1`
+    #[serde(default = "first_page")]
+    page: i64,
+}
+
+fn first_page() -> i64 {
+    1
+}
+
+/// The approved records on a demon, one page at a time. The total number of approved records is
+/// returned in the `X-Total-Count` header
+#[documented(example = "/api/v2/demons/1/records/")]
+#[rocket::get("/<demon_id>/records")]
+pub async fn records(
+    demon_id: i32, pool: &State<PointercratePool>, query: Query<DemonRecordsQuery>,
+) -> Result<Response2<Json<Vec<MinimalRecordP>>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = MinimalDemon::by_id(DemonId(demon_id), &mut connection).await?;
+    let summary = approved_record_summary(&demon, &mut connection).await?;
+    let records = approved_records_page_on(&demon, query.0.page, &mut connection).await?;
+
+    Ok(Response2::json(records)
+        .with_header("X-Total-Count", summary.count.to_string())
+        .cache_for(CACHE_MAX_AGE, demon_response_keys(demon_id)))
+}
+
+/// The number of approved records on a demon per nationality of the record holders, for rendering
+/// heat maps of which countries beat the demon
+#[rocket::get("/<demon_id>/nationalities")]
+pub async fn nationalities(demon_id: i32, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<NationalityRecordCount>>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = MinimalDemon::by_id(DemonId(demon_id), &mut connection).await?;
+    let counts = approved_records_by_nationality(&demon, &mut connection).await?;
+
+    Ok(Response2::json(counts).cache_for(CACHE_MAX_AGE, demon_response_keys(demon_id)))
 }
 
 #[rocket::get("/<demon_id>/audit")]
-pub async fn audit(demon_id: i32, mut auth: TokenAuth) -> Result<Json<Vec<AuditLogEntry<DemonModificationData>>>> {
+pub async fn audit(demon_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<AuditLogEntry<DemonModificationData>>>> {
     auth.require_permission(LIST_ADMINISTRATOR)?;
 
     let log = pointercrate_demonlist::demon::audit::audit_log_for_demon(demon_id, &mut auth.connection).await?;
@@ -52,7 +220,7 @@ pub async fn audit(demon_id: i32, mut auth: TokenAuth) -> Result<Json<Vec<AuditL
 
 #[rocket::get("/<demon_id>/audit/movement")]
 pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<MovementLogEntry>>> {
-    let log = pointercrate_demonlist::demon::audit::movement_log_for_demon(demon_id, &mut *pool.connection().await?).await?;
+    let log = pointercrate_demonlist::demon::audit::movement_log_for_demon(demon_id, &mut pool.read_only_connection().await?).await?;
 
     if log.is_empty() {
         return Err(DemonlistError::DemonNotFound { demon_id }.into());
@@ -63,7 +231,7 @@ pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Resu
 
 #[rocket::post("/", data = "<data>")]
 pub async fn post(
-    mut auth: TokenAuth, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>,
+    mut auth: TokenAuth, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>, cache: CachePurge<'_>,
 ) -> Result<Response2<Tagged<FullDemon>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
@@ -73,6 +241,9 @@ pub async fn post(
 
     auth.commit().await?;
 
+    // Adding a demon shifts the positions of all demons below it
+    cache.purge("overview");
+
     let demon_id = demon.demon.base.id;
 
     Ok(Response2::tagged(demon)
@@ -81,43 +252,158 @@ pub async fn post(
 }
 
 #[rocket::patch("/<demon_id>", data = "<patch>")]
-pub async fn patch(demon_id: i32, mut auth: TokenAuth, precondition: Precondition, patch: Json<PatchDemon>) -> Result<Tagged<FullDemon>> {
-    auth.require_permission(LIST_MODERATOR)?;
+pub async fn patch(
+    demon_id: i32, auth: TokenAuth, precondition: Precondition, patch: Json<PatchDemon>, pool: &State<PointercratePool>,
+    mailer: &State<MailerHandle>, cache: CachePurge<'_>,
+) -> Result<Tagged<FullDemon>> {
+    for permission in patch.required_permissions() {
+        auth.require_permission(permission)?;
+    }
 
-    let demon = FullDemon::by_id(demon_id, &mut auth.connection)
-        .await?
-        .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+    auth.require_permission(LIST_HELPER)?;
+
+    let (demon, recipients) = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
+            let patch = patch.0.clone();
+
+            Box::pin(async move {
+                let demon = FullDemon::lock_by_id(DemonId(demon_id), connection)
+                    .await?
+                    .require_match(precondition)?;
+                let old_version = demon.demon.version;
+                let demon = demon.apply_patch(patch, connection).await?;
+
+                let recipients = if demon.demon.version != old_version {
+                    watch::email_recipients(WatchTarget::Demon(DemonId(demon_id)), connection).await?
+                } else {
+                    Vec::new()
+                };
+
+                Ok::<_, DemonlistError>((demon, recipients))
+            })
+        })
         .await?;
 
-    auth.commit().await?;
+    cache.purge(&demon_key(demon_id));
+    cache.purge("overview");
+
+    for recipient in recipients {
+        mailer.dispatch(Email::watched_object_modified(
+            recipient.email_address,
+            &recipient.name,
+            &format!("The demon {}", demon.demon.base.name),
+        ));
+    }
 
     Ok(Tagged(demon))
 }
 
+/// Swaps the positions of two demons. The `If-Match` header needs to contain the current ETags of
+/// both demons, separated by a comma
+#[rocket::post("/<demon_id>/swap/<other_id>")]
+pub async fn swap(
+    demon_id: i32, other_id: i32, auth: TokenAuth, precondition: Precondition, pool: &State<PointercratePool>,
+    mailer: &State<MailerHandle>, cache: CachePurge<'_>,
+) -> Result<Json<Vec<Demon>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let (demon, other, recipients) = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
+
+            Box::pin(async move {
+                // Always lock in ascending id order, so that two concurrent swaps of the same demons
+                // cannot deadlock
+                let lower = FullDemon::lock_by_id(DemonId(demon_id.min(other_id)), connection)
+                    .await?
+                    .require_match(precondition.clone())?
+                    .demon;
+                let higher = FullDemon::lock_by_id(DemonId(demon_id.max(other_id)), connection)
+                    .await?
+                    .require_match(precondition)?
+                    .demon;
+
+                let (mut demon, mut other) = if demon_id <= other_id { (lower, higher) } else { (higher, lower) };
+
+                demon.swap_with(&mut other, connection).await?;
+
+                let mut recipients = Vec::new();
+
+                for swapped in [&demon, &other] {
+                    for recipient in watch::email_recipients(WatchTarget::Demon(swapped.base.id), connection).await? {
+                        recipients.push((recipient, swapped.base.name.clone()));
+                    }
+                }
+
+                Ok::<_, DemonlistError>((demon, other, recipients))
+            })
+        })
+        .await?;
+
+    cache.purge(&demon_key(demon_id));
+    cache.purge(&demon_key(other_id));
+    cache.purge("overview");
+
+    for (recipient, name) in recipients {
+        mailer.dispatch(Email::watched_object_modified(
+            recipient.email_address,
+            &recipient.name,
+            &format!("The demon {}", name),
+        ));
+    }
+
+    Ok(Json(vec![demon, other]))
+}
+
 #[rocket::post("/<demon_id>/creators", data = "<creator>")]
-pub async fn post_creator(demon_id: i32, mut auth: TokenAuth, creator: Json<PostCreator>) -> Result<Response2<Json<()>>> {
+pub async fn post_creator(
+    demon_id: i32, mut auth: TokenAuth, creator: Json<PostCreator>, cache: CachePurge<'_>,
+) -> Result<Response2<Json<()>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
+    let demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
     let player = DatabasePlayer::by_name_or_create(&creator.creator, &mut auth.connection).await?;
 
     Creator::insert(&demon.base, &player, &mut auth.connection).await?;
 
     auth.commit().await?;
 
+    cache.purge(&demon_key(demon_id));
+
     Ok(Response2::json(()).status(Status::Created).with_header(
         "Location",
         format!("/api/v2/demons/{}/creators/{}/", demon.base.position, player.id),
     ))
 }
 
+#[rocket::patch("/<demon_id>/creators/<player_id>", data = "<patch>")]
+pub async fn patch_creator(
+    demon_id: i32, player_id: i32, mut auth: TokenAuth, patch: Json<PatchCreator>, cache: CachePurge<'_>,
+) -> Result<Json<Creator>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
+
+    let creator = Creator::get(&demon.base, &player, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+
+    Ok(Json(creator))
+}
+
 #[rocket::delete("/<demon_id>/creators/<player_id>")]
-pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: TokenAuth) -> Result<Status> {
+pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: TokenAuth, cache: CachePurge<'_>) -> Result<Status> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
-    let player = DatabasePlayer::by_id(player_id, &mut auth.connection).await?;
+    let demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
 
     Creator::get(&demon.base, &player, &mut auth.connection)
         .await?
@@ -126,27 +412,306 @@ pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: TokenAuth)
 
     auth.commit().await?;
 
+    cache.purge(&demon_key(demon_id));
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::post("/<demon_id>/verifiers", data = "<credit>")]
+pub async fn post_verifier(demon_id: i32, auth: TokenAuth, credit: Json<PostCredit>, cache: CachePurge<'_>) -> Result<Response2<Json<()>>> {
+    post_credit(demon_id, CreditKind::Verifier, auth, credit.0, cache).await
+}
+
+#[rocket::delete("/<demon_id>/verifiers/<player_id>")]
+pub async fn delete_verifier(demon_id: i32, player_id: i32, auth: TokenAuth, cache: CachePurge<'_>) -> Result<Status> {
+    delete_credit(demon_id, player_id, CreditKind::Verifier, auth, cache).await
+}
+
+#[rocket::post("/<demon_id>/publishers", data = "<credit>")]
+pub async fn post_publisher(
+    demon_id: i32, auth: TokenAuth, credit: Json<PostCredit>, cache: CachePurge<'_>,
+) -> Result<Response2<Json<()>>> {
+    post_credit(demon_id, CreditKind::Publisher, auth, credit.0, cache).await
+}
+
+#[rocket::delete("/<demon_id>/publishers/<player_id>")]
+pub async fn delete_publisher(demon_id: i32, player_id: i32, auth: TokenAuth, cache: CachePurge<'_>) -> Result<Status> {
+    delete_credit(demon_id, player_id, CreditKind::Publisher, auth, cache).await
+}
+
+async fn post_credit(
+    demon_id: i32, kind: CreditKind, mut auth: TokenAuth, credit: PostCredit, cache: CachePurge<'_>,
+) -> Result<Response2<Json<()>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+    let player = DatabasePlayer::by_name_or_create(&credit.player, &mut auth.connection).await?;
+
+    credit::add_credit(&demon, &player, kind, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+
+    Ok(Response2::json(())
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/demons/{}/{}s/{}/", demon_id, kind, player.id)))
+}
+
+async fn delete_credit(demon_id: i32, player_id: i32, kind: CreditKind, mut auth: TokenAuth, cache: CachePurge<'_>) -> Result<Status> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+
+    credit::remove_credit(&demon, player_id, kind, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+
     Ok(Status::NoContent)
 }
 
+#[rocket::get("/<demon_id>/reverification")]
+pub async fn reverification(demon_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<Reverification>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(Reverification::open_for(demon_id, &mut auth.connection).await?))
+}
+
+/// Marks a demon as requiring re-verification, which closes submissions for it
+#[rocket::post("/<demon_id>/reverification", data = "<data>")]
+pub async fn request_reverification(
+    demon_id: i32, mut auth: TokenAuth, data: Json<PostReverification>, cache: CachePurge<'_>,
+) -> Result<Response2<Json<Reverification>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let mut demon = Demon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+    let reverification = Reverification::request(&mut demon, data.0, auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+
+    Ok(Response2::json(reverification)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/demons/{}/reverification/", demon_id)))
+}
+
+#[rocket::patch("/<demon_id>/reverification", data = "<patch>")]
+pub async fn patch_reverification(demon_id: i32, mut auth: TokenAuth, patch: Json<PatchReverification>) -> Result<Json<Reverification>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let reverification = Reverification::open_for(demon_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(reverification))
+}
+
+/// Swaps in the candidate verification of the demon's open re-verification and reopens
+/// submissions
+#[rocket::post("/<demon_id>/reverification/complete")]
+pub async fn complete_reverification(demon_id: i32, mut auth: TokenAuth, cache: CachePurge<'_>) -> Result<Tagged<FullDemon>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let mut demon = FullDemon::by_id(DemonId(demon_id), &mut auth.connection).await?;
+
+    Reverification::open_for(demon_id, &mut auth.connection)
+        .await?
+        .complete(&mut demon.demon, auth.user.user().id.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+    cache.purge("overview");
+
+    Ok(Tagged(demon))
+}
+
+/// Deletes a demon together with all its creators and records
 #[rocket::delete("/<demon_id>")]
+pub async fn delete_demon_data(
+    demon_id: i32, auth: TokenAuth, precondition: Precondition, pool: &State<PointercratePool>, cache: CachePurge<'_>,
+) -> Result<Status> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    auth.retry_on_conflict(pool, |connection| {
+        let precondition = precondition.clone();
+
+        Box::pin(async move {
+            let demon = FullDemon::lock_by_id(DemonId(demon_id), connection).await?;
+
+            precondition.require_etag_match(&demon)?;
+
+            demon.delete_demon(connection).await?;
+
+            recompute_scores(connection).await?;
+
+            Ok::<_, DemonlistError>(())
+        })
+    })
+    .await?;
 
-/// delete all creators and records from a demon, and delete the demon itself
-pub async fn delete_demon_data(demon_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    cache.purge(&demon_key(demon_id));
+    cache.purge("overview");
+
+    Ok(Status::NoContent)
+}
+
+/// Removes a demon from the list, moving it together with its creators and records into the archive
+#[rocket::post("/<demon_id>/archive")]
+pub async fn archive(
+    demon_id: i32, mut auth: TokenAuth, precondition: Precondition, cache: CachePurge<'_>,
+) -> Result<Response2<Json<ArchivedDemon>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    // pass in the id of the demon we're trying to delete
-    let demon = FullDemon::by_id(demon_id, &mut auth.connection).await?;
+    let demon = FullDemon::lock_by_id(DemonId(demon_id), &mut auth.connection).await?;
+
+    precondition.require_etag_match(&demon)?;
+
+    let archived = demon.archive(&mut auth.connection).await?;
+
+    recompute_scores(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    cache.purge(&demon_key(demon_id));
+    cache.purge("overview");
+
+    Ok(Response2::json(archived)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/demons/archive/{}/", demon_id)))
+}
 
-    // we don't really need fulldemon here but oh well
-    FullDemon::by_id(demon.demon.base.id, &mut auth.connection)
+#[rocket::get("/archive")]
+pub async fn archived_demons(pool: &State<PointercratePool>) -> Result<Json<Vec<ArchivedDemon>>> {
+    Ok(Json(ArchivedDemon::all(&mut pool.read_only_connection().await?).await?))
+}
+
+#[rocket::get("/archive/<demon_id>", rank = 1)]
+pub async fn archived_demon(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<ArchivedDemon>> {
+    Ok(Json(ArchivedDemon::by_id(demon_id, &mut pool.read_only_connection().await?).await?))
+}
+
+#[rocket::get("/archive/<demon_id>/records", rank = 1)]
+pub async fn archived_records(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<MinimalRecordP>>> {
+    let mut connection = pool.read_only_connection().await?;
+    let demon = ArchivedDemon::by_id(demon_id, &mut connection).await?;
+
+    Ok(Json(demon.approved_records(&mut connection).await?))
+}
+
+/// Puts an archived demon back onto the list at the given position
+#[rocket::post("/archive/<demon_id>/restore", data = "<data>", rank = 1)]
+pub async fn restore_archived(
+    demon_id: i32, mut auth: TokenAuth, data: Json<RestoreDemon>, cache: CachePurge<'_>,
+) -> Result<Response2<Tagged<FullDemon>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let demon = ArchivedDemon::by_id(demon_id, &mut auth.connection)
         .await?
-        .delete_demon(&mut auth.connection)
+        .restore(data.0, &mut auth.connection)
         .await?;
 
     recompute_scores(&mut auth.connection).await?;
 
     auth.commit().await?;
 
+    cache.purge(&demon_key(demon_id));
+    cache.purge("overview");
+
+    Ok(Response2::tagged(demon)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/demons/{}/", demon_id)))
+}
+
+#[rocket::get("/drafts")]
+pub async fn drafts(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<DemonDraft>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(DemonDraft::all(&mut auth.connection).await?))
+}
+
+#[rocket::get("/drafts/<draft_id>", rank = 1)]
+pub async fn draft(draft_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<DemonDraft>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(DemonDraft::by_id(draft_id, &mut auth.connection).await?))
+}
+
+/// Creates a draft of a demon, which is invisible to the public until published
+#[rocket::post("/drafts", data = "<data>")]
+pub async fn post_draft(mut auth: TokenAuth, data: Json<PostDemonDraft>) -> Result<Response2<Json<DemonDraft>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let draft = DemonDraft::create(data.0, auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let location = format!("/api/v2/demons/drafts/{}/", draft.id);
+
+    Ok(Response2::json(draft).status(Status::Created).with_header("Location", location))
+}
+
+#[rocket::patch("/drafts/<draft_id>", data = "<patch>", rank = 1)]
+pub async fn patch_draft(draft_id: i32, mut auth: TokenAuth, patch: Json<PatchDemonDraft>) -> Result<Json<DemonDraft>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let draft = DemonDraft::by_id(draft_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(draft))
+}
+
+#[rocket::delete("/drafts/<draft_id>", rank = 1)]
+pub async fn delete_draft(draft_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    DemonDraft::by_id(draft_id, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
     Ok(Status::NoContent)
 }
+
+/// Adds a drafted demon to the list at its intended position, and deletes the draft
+#[rocket::post("/drafts/<draft_id>/publish", rank = 1)]
+pub async fn publish_draft(
+    draft_id: i32, mut auth: TokenAuth, ratelimits: &State<DemonlistRatelimits>, cache: CachePurge<'_>,
+) -> Result<Response2<Tagged<FullDemon>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let draft = DemonDraft::by_id(draft_id, &mut auth.connection).await?;
+
+    // Attempts to publish incomplete drafts do not add a demon, so they should not use up the ratelimit
+    if !draft.is_complete() {
+        return Err(DemonlistError::IncompleteDraft.into());
+    }
+
+    ratelimits.add_demon()?;
+
+    let demon = draft.publish(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    // Adding a demon shifts the positions of all demons below it
+    cache.purge("overview");
+
+    let demon_id = demon.demon.base.id;
+
+    Ok(Response2::tagged(demon)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/demons/{}/", demon_id)))
+}
@@ -0,0 +1,120 @@
+use log::{error, info};
+use pointercrate_core::{
+    error::CoreError,
+    job::{Job, JobHandle, JobRegistry},
+    pool::{retry_on_conflict, PointercratePool, TransactionFuture},
+};
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::{achievement::backfill_achievements, recompute_nation_scores, recompute_player_scores, recompute_subdivision_scores},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json, State};
+use sqlx::{PgConnection, Pool, Postgres};
+
+#[rocket::get("/")]
+pub async fn paginate(auth: TokenAuth, jobs: &State<JobRegistry>) -> Result<Json<Vec<Job>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(jobs.all()))
+}
+
+#[rocket::get("/<job_id>")]
+pub async fn get(job_id: u32, auth: TokenAuth, jobs: &State<JobRegistry>) -> Result<Json<Job>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(jobs.get(job_id)?))
+}
+
+/// Recomputes the scores of all players, nations and subdivisions in the background. Progress can
+/// be inspected via `GET /api/v1/jobs/<id>/`.
+///
+/// All scores are recomputed inside a single transaction, so the new values only become visible
+/// once all of them have been computed. Note that cached responses containing scores are not
+/// purged, and will only pick up the new values once they expire.
+#[rocket::post("/recompute-scores")]
+pub async fn recompute_scores(auth: TokenAuth, pool: &State<PointercratePool>, jobs: &State<JobRegistry>) -> Result<Response2<Json<Job>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let handle = jobs.start("recompute_scores", 3)?;
+    let job = jobs.get(handle.id())?;
+    let pool = pool.clone_background();
+
+    info!("{} started score recomputation (job {})", auth.user.user(), job.id);
+
+    rocket::tokio::spawn(run_score_recomputation(pool, auth.user.user().id.0, handle));
+
+    let location = format!("/api/v1/jobs/{}/", job.id);
+
+    Ok(Response2::json(job).status(Status::Accepted).with_header("Location", location))
+}
+
+/// Runs the recomputation on behalf of the user with the given id, to whom it is attributed in audit logs
+async fn run_score_recomputation(pool: Pool<Postgres>, user_id: i32, handle: JobHandle) {
+    let result = retry_on_conflict(&pool, user_id, |connection| recompute_all_scores(connection, handle.clone())).await;
+
+    if let Err(ref err) = result {
+        error!("Score recomputation (job {}) failed: {:?}", handle.id(), err);
+    }
+
+    handle.finish(result);
+}
+
+fn recompute_all_scores(connection: &mut PgConnection, handle: JobHandle) -> TransactionFuture<'_, (), CoreError> {
+    Box::pin(async move {
+        // Progress made by a previous, conflicted attempt was rolled back
+        handle.restart();
+
+        recompute_player_scores(&mut *connection).await?;
+        handle.advance();
+        recompute_nation_scores(&mut *connection).await?;
+        handle.advance();
+        recompute_subdivision_scores(connection).await?;
+        handle.advance();
+
+        Ok(())
+    })
+}
+
+/// Awards all achievements to all players that qualify for them. Achievements are usually awarded
+/// when a record is approved, so this only needs to be run after a new achievement was introduced
+/// (or to catch up on completions that did not go through record approval, such as verifications).
+///
+/// Players are processed one after another outside of a transaction, which is fine since awarding
+/// an achievement is idempotent.
+#[rocket::post("/backfill-achievements")]
+pub async fn backfill(auth: TokenAuth, pool: &State<PointercratePool>, jobs: &State<JobRegistry>) -> Result<Response2<Json<Job>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let handle = jobs.start("backfill_achievements", 1)?;
+    let job = jobs.get(handle.id())?;
+    let pool = pool.clone_background();
+
+    info!("{} started achievement backfill (job {})", auth.user.user(), job.id);
+
+    rocket::tokio::spawn(run_achievement_backfill(pool, handle));
+
+    let location = format!("/api/v1/jobs/{}/", job.id);
+
+    Ok(Response2::json(job).status(Status::Accepted).with_header("Location", location))
+}
+
+async fn run_achievement_backfill(pool: Pool<Postgres>, handle: JobHandle) {
+    let result = async {
+        let mut connection = pool.acquire().await?;
+        let awarded = backfill_achievements(&mut connection).await?;
+
+        info!("Achievement backfill (job {}) awarded {} achievements", handle.id(), awarded);
+
+        Ok::<_, DemonlistError>(())
+    }
+    .await;
+
+    if let Err(ref err) = result {
+        error!("Achievement backfill (job {}) failed: {:?}", handle.id(), err);
+    }
+
+    handle.finish(result);
+}
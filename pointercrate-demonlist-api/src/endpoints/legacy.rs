@@ -0,0 +1,367 @@
+//! Endpoints producing data in the formats of older pointercrate versions, for community tools that
+//! were never updated to the current API
+//!
+//! These deliberately use their own serialization structs instead of the model objects, so that
+//! changes to the current API cannot accidentally alter the legacy formats.
+//!
+//! Endpoints mounted below `/api/legacy/` mimic the pointercrate v1 API: demons are addressed by
+//! their position, players are referred to by name only, and list endpoints take the old query
+//! parameters. They translate requests into the queries of the current endpoints.
+
+use crate::endpoints::demon::{demon_response_keys, CACHE_MAX_AGE};
+use pointercrate_core::{
+    config,
+    pagination::{Paginatable, PaginationParameters},
+    pool::{PointercratePool, ReadConnection},
+};
+use pointercrate_core_api::{error::Result, query::Query, response::Response2};
+use pointercrate_demonlist::{
+    demon::{Demon, DemonId, DemonPositionPagination, MinimalDemon},
+    error::DemonlistError,
+    player::{FullPlayer, Player, PlayerId, PlayerPagination},
+    record::{approved_records_on, FullRecord, MinimalRecordP, MinimalRecordPD, RecordId, RecordPagination, RecordStatus},
+};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+
+/// A record in the flat format of the legacy record dumps, where related objects are inlined as
+/// plain fields
+#[derive(Serialize, Debug)]
+pub struct LegacyRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: &'static str,
+    pub player: String,
+    pub player_id: i32,
+    pub player_banned: bool,
+
+    /// ISO country code of the player's nationality
+    pub nationality: Option<String>,
+    pub demon: String,
+    pub demon_id: i32,
+    pub position: i16,
+}
+
+impl LegacyRecord {
+    fn new(record: MinimalRecordP, demon: &MinimalDemon) -> Self {
+        LegacyRecord {
+            id: record.id.0,
+            progress: record.progress,
+            video: record.video,
+            status: "approved",
+            player: record.player.name,
+            player_id: record.player.id.0,
+            player_banned: record.player.banned,
+            nationality: record.nationality.map(|nationality| nationality.iso_country_code),
+            demon: demon.name.clone(),
+            demon_id: demon.id.0,
+            position: demon.position,
+        }
+    }
+}
+
+/// All approved records on a demon, in the legacy dump format
+#[rocket::get("/<demon_id>/records/export")]
+pub async fn export_records(demon_id: i32, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<LegacyRecord>>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = MinimalDemon::by_id(DemonId(demon_id), &mut connection).await?;
+    let records = approved_records_on(&demon, &mut connection)
+        .await?
+        .into_iter()
+        .map(|record| LegacyRecord::new(record, &demon))
+        .collect();
+
+    Ok(Response2::json(records).cache_for(CACHE_MAX_AGE, demon_response_keys(demon_id)))
+}
+
+/// A demon in the v1 format, where publisher and verifier are given by name
+#[derive(Serialize, Debug)]
+pub struct LegacyDemon {
+    pub name: String,
+    pub position: i16,
+    pub requirement: i16,
+    pub video: Option<String>,
+    pub publisher: String,
+    pub verifier: String,
+}
+
+impl From<Demon> for LegacyDemon {
+    fn from(demon: Demon) -> Self {
+        LegacyDemon {
+            name: demon.base.name,
+            position: demon.base.position,
+            requirement: demon.requirement,
+            video: demon.video,
+            publisher: demon.publisher.name,
+            verifier: demon.verifier.name,
+        }
+    }
+}
+
+/// The query parameters understood by the v1 demon list endpoint. `after` and `before` are
+/// positions, and publishers and verifiers are filtered by name
+#[derive(Deserialize, Debug)]
+pub struct LegacyDemonPagination {
+    #[serde(flatten)]
+    params: PaginationParameters,
+
+    #[serde(default)]
+    name: Option<String>,
+
+    #[serde(default)]
+    publisher: Option<String>,
+
+    #[serde(default)]
+    verifier: Option<String>,
+
+    #[serde(default)]
+    requirement: Option<i16>,
+}
+
+impl From<LegacyDemonPagination> for DemonPositionPagination {
+    fn from(legacy: LegacyDemonPagination) -> Self {
+        DemonPositionPagination {
+            params: legacy.params,
+            name: legacy.name,
+            requirement: legacy.requirement,
+            verifier_name: legacy.verifier,
+            publisher_name: legacy.publisher,
+            ..Default::default()
+        }
+    }
+}
+
+/// The listed demons in v1 format. Unlike the current API, no `Links` header is generated, as v1
+/// clients paginate by passing the position of the last demon they received as `after`
+#[rocket::get("/")]
+pub async fn demons(pool: &State<PointercratePool>, query: Query<LegacyDemonPagination>) -> Result<Json<Vec<LegacyDemon>>> {
+    let mut query = DemonPositionPagination::from(query.0);
+
+    query.params = query.params.resolve(config::pagination_limits("/api/legacy/demons"))?;
+
+    let mut connection = pool.read_only_connection().await?;
+
+    let (demons, _) = <Demon as Paginatable<DemonPositionPagination>>::page(&query, &mut connection.reader())
+        .await
+        .map_err(DemonlistError::from)?;
+
+    Ok(Json(demons.into_iter().map(Into::into).collect()))
+}
+
+#[rocket::get("/<position>")]
+pub async fn demon(position: i16, pool: &State<PointercratePool>) -> Result<Json<LegacyDemon>> {
+    let demon = Demon::by_position(position, &mut pool.read_only_connection().await?).await?;
+
+    Ok(Json(demon.into()))
+}
+
+/// A player in the v1 format
+#[derive(Serialize, Debug)]
+pub struct LegacyPlayer {
+    pub id: i32,
+    pub name: String,
+    pub banned: bool,
+}
+
+impl From<Player> for LegacyPlayer {
+    fn from(player: Player) -> Self {
+        LegacyPlayer {
+            id: player.base.id.0,
+            name: player.base.name,
+            banned: player.base.banned,
+        }
+    }
+}
+
+/// A record as listed on a player in the v1 format, where the demon is given by name and position
+#[derive(Serialize, Debug)]
+pub struct LegacyPlayerRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub demon: String,
+    pub position: i16,
+}
+
+/// A player in the v1 format, together with their approved records and the demons they published
+/// and verified (by name)
+#[derive(Serialize, Debug)]
+pub struct LegacyFullPlayer {
+    #[serde(flatten)]
+    pub player: LegacyPlayer,
+    pub records: Vec<LegacyPlayerRecord>,
+    pub published: Vec<String>,
+    pub verified: Vec<String>,
+}
+
+impl From<FullPlayer> for LegacyFullPlayer {
+    fn from(player: FullPlayer) -> Self {
+        LegacyFullPlayer {
+            player: player.player.into(),
+            records: player
+                .records
+                .into_iter()
+                .map(|record| LegacyPlayerRecord {
+                    id: record.id.0,
+                    progress: record.progress,
+                    video: record.video,
+                    demon: record.demon.name,
+                    position: record.demon.position,
+                })
+                .collect(),
+            published: player.published.into_iter().map(|demon| demon.name).collect(),
+            verified: player.verified.into_iter().map(|demon| demon.name).collect(),
+        }
+    }
+}
+
+/// The query parameters understood by the v1 player list endpoint
+#[derive(Deserialize, Debug)]
+pub struct LegacyPlayerPagination {
+    #[serde(flatten)]
+    params: PaginationParameters,
+
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl From<LegacyPlayerPagination> for PlayerPagination {
+    fn from(legacy: LegacyPlayerPagination) -> Self {
+        PlayerPagination {
+            params: legacy.params,
+            name: legacy.name,
+            // v1 never listed banned players
+            banned: Some(false),
+            ..Default::default()
+        }
+    }
+}
+
+/// The listed players in v1 format. As for demons, no `Links` header is generated
+#[rocket::get("/")]
+pub async fn players(pool: &State<PointercratePool>, query: Query<LegacyPlayerPagination>) -> Result<Json<Vec<LegacyPlayer>>> {
+    let mut query = PlayerPagination::from(query.0);
+
+    query.params = query.params.resolve(config::pagination_limits("/api/legacy/players"))?;
+
+    let mut connection = pool.read_only_connection().await?;
+
+    let (players, _) = <Player as Paginatable<PlayerPagination>>::page(&query, &mut connection.reader())
+        .await
+        .map_err(DemonlistError::from)?;
+
+    Ok(Json(players.into_iter().map(Into::into).collect()))
+}
+
+#[rocket::get("/<player_id>")]
+pub async fn player(player_id: i32, pool: &State<PointercratePool>) -> Result<Json<LegacyFullPlayer>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let player = Player::by_id(PlayerId(player_id), &mut connection)
+        .await?
+        .upgrade(&mut connection)
+        .await?;
+
+    Ok(Json(player.into()))
+}
+
+/// A record in the v1 format, where player and demon are given by name. Only approved records are
+/// available through the v1 endpoints
+#[derive(Serialize, Debug)]
+pub struct LegacyListedRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: &'static str,
+    pub player: String,
+    pub demon: String,
+    pub position: i16,
+}
+
+impl From<MinimalRecordPD> for LegacyListedRecord {
+    fn from(record: MinimalRecordPD) -> Self {
+        LegacyListedRecord {
+            id: record.id.0,
+            progress: record.progress,
+            video: record.video,
+            status: "approved",
+            player: record.player.name,
+            demon: record.demon.name,
+            position: record.demon.position,
+        }
+    }
+}
+
+impl From<FullRecord> for LegacyListedRecord {
+    fn from(record: FullRecord) -> Self {
+        LegacyListedRecord {
+            id: record.id.0,
+            progress: record.progress,
+            video: record.video,
+            status: "approved",
+            player: record.player.name,
+            demon: record.demon.name,
+            position: record.demon.position,
+        }
+    }
+}
+
+/// The query parameters understood by the v1 record list endpoint. The demon is filtered by name,
+/// the player by ID
+#[derive(Deserialize, Debug)]
+pub struct LegacyRecordPagination {
+    #[serde(flatten)]
+    params: PaginationParameters,
+
+    #[serde(default)]
+    demon: Option<String>,
+
+    #[serde(default)]
+    player: Option<i32>,
+
+    #[serde(default)]
+    progress: Option<i16>,
+}
+
+impl From<LegacyRecordPagination> for RecordPagination {
+    fn from(legacy: LegacyRecordPagination) -> Self {
+        RecordPagination {
+            params: legacy.params,
+            demon: legacy.demon,
+            player: legacy.player,
+            progress: legacy.progress,
+            status: Some(RecordStatus::Approved),
+            ..Default::default()
+        }
+    }
+}
+
+/// The approved records in v1 format. As for demons, no `Links` header is generated
+#[rocket::get("/")]
+pub async fn records(pool: &State<PointercratePool>, query: Query<LegacyRecordPagination>) -> Result<Json<Vec<LegacyListedRecord>>> {
+    let mut query = RecordPagination::from(query.0);
+
+    query.params = query.params.resolve(config::pagination_limits("/api/legacy/records"))?;
+
+    let mut connection = pool.read_only_connection().await?;
+
+    let (records, _) = <MinimalRecordPD as Paginatable<RecordPagination>>::page(&query, &mut connection.reader())
+        .await
+        .map_err(DemonlistError::from)?;
+
+    Ok(Json(records.into_iter().map(Into::into).collect()))
+}
+
+#[rocket::get("/<record_id>")]
+pub async fn record(record_id: i32, pool: &State<PointercratePool>) -> Result<Json<LegacyListedRecord>> {
+    let record = FullRecord::by_id(RecordId(record_id), &mut pool.read_only_connection().await?).await?;
+
+    // Don't reveal the existence of unapproved records
+    if record.status != RecordStatus::Approved {
+        return Err(DemonlistError::RecordNotFound { record_id }.into());
+    }
+
+    Ok(Json(record.into()))
+}
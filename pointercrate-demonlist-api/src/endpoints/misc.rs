@@ -1,15 +1,30 @@
-use pointercrate_demonlist::config;
-use rocket::serde::json::Json;
+use crate::endpoints::demon::CACHE_MAX_AGE;
+use pointercrate_core::{config::Config, pool::PointercratePool};
+use pointercrate_core_api::{documentation::documented, error::Result, response::Response2};
+use pointercrate_demonlist::requirement::ListRequirement;
+use rocket::{serde::json::Json, State};
 use serde_json::{json, Value};
 
+/// The sizes of the main and extended list, which determine which demons records can be
+/// submitted for and how much they are worth
+#[documented(example = "/api/v1/list_information/")]
 #[rocket::get("/")]
-pub fn list_information() -> Json<Value> {
+pub fn list_information(config: &State<Config>) -> Json<Value> {
     let data = json! {
         {
-            "list_size": config::list_size(),
-            "extended_list_size": config::extended_list_size()
+            "list_size": config.list.list_size,
+            "extended_list_size": config.list.extended_list_size
         }
     };
 
     Json(data)
 }
+
+/// The requirement currently in effect for records on main list demons, for displaying it in a
+/// banner. Changes whenever the main list does, so it is purged together with the overview
+#[rocket::get("/requirement")]
+pub async fn requirement(pool: &State<PointercratePool>) -> Result<Response2<Json<ListRequirement>>> {
+    let requirement = ListRequirement::current(&mut pool.read_only_connection().await?).await?;
+
+    Ok(Response2::json(requirement).cache_for(CACHE_MAX_AGE, "overview"))
+}
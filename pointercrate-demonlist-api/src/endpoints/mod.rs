@@ -1,6 +1,14 @@
+pub(crate) mod account;
+pub(crate) mod asset;
+pub(crate) mod changelog;
 pub(crate) mod demon;
+pub(crate) mod job;
+pub(crate) mod legacy;
 pub(crate) mod misc;
 pub(crate) mod nationality;
 pub(crate) mod player;
 pub(crate) mod record;
+pub(crate) mod report;
+pub(crate) mod staff;
 pub(crate) mod submitter;
+pub(crate) mod widget;
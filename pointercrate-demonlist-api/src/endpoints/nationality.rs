@@ -1,29 +1,69 @@
 use pointercrate_core::pool::PointercratePool;
-use pointercrate_core_api::{error::Result, etag::Tagged, query::Query};
-use pointercrate_demonlist::nationality::{Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision};
+use pointercrate_core_api::{documentation::documented, error::Result, etag::Tagged, query::Query};
+use pointercrate_demonlist::{
+    nationality::{
+        ContinentStatistics, Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision,
+        SubdivisionRankingPagination,
+    },
+    player::RankedPlayer,
+    score_history::ScoreSnapshot,
+};
 use rocket::{serde::json::Json, State};
 
 #[rocket::get("/<iso_code>/subdivisions")]
 pub async fn subdivisions(pool: &State<PointercratePool>, iso_code: String) -> Result<Json<Vec<Subdivision>>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
 
     // good code
-    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut *connection).await?;
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
 
-    Ok(Json(nationality.subdivisions(&mut *connection).await?))
+    Ok(Json(nationality.subdivisions(&mut connection).await?))
 }
 
+#[rocket::get("/<iso_code>/subdivisions/<subdivision_code>/ranking")]
+pub async fn subdivision_ranking(
+    pool: &State<PointercratePool>, iso_code: String, subdivision_code: String, pagination: Query<SubdivisionRankingPagination>,
+) -> Result<Json<Vec<RankedPlayer>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
+    let subdivision = nationality
+        .subdivision_by_code(subdivision_code.to_uppercase().as_ref(), &mut connection)
+        .await?;
+
+    Ok(Json(pagination.0.page(&nationality, &subdivision, &mut connection).await?))
+}
+
+/// Per-continent score and completion statistics, used by the stats viewer's world map
+#[rocket::get("/continents")]
+pub async fn continents(pool: &State<PointercratePool>) -> Result<Json<Vec<ContinentStatistics>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    Ok(Json(ContinentStatistics::all(&mut connection).await?))
+}
+
+/// All nationalities, ordered by the combined score of their players
+#[documented(example = "/api/v1/nationalities/ranking/")]
 #[rocket::get("/ranking")]
 pub async fn ranking(pool: &State<PointercratePool>, pagination: Query<NationalityRankingPagination>) -> Result<Json<Vec<RankedNation>>> {
-    Ok(Json(pagination.0.page(&mut *pool.connection().await?).await?))
+    Ok(Json(pagination.0.page(&mut pool.read_only_connection().await?).await?))
 }
 
 #[rocket::get("/<iso_code>")]
 pub async fn nation(pool: &State<PointercratePool>, iso_code: String) -> Result<Tagged<NationalityRecord>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
 
     // good code
-    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut *connection).await?;
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
+
+    Ok(Tagged(nationality.upgrade(&mut connection).await?))
+}
+
+#[rocket::get("/<iso_code>/score-history")]
+pub async fn score_history(pool: &State<PointercratePool>, iso_code: String) -> Result<Json<Vec<ScoreSnapshot>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
 
-    Ok(Tagged(nationality.upgrade(&mut *connection).await?))
+    Ok(Json(nationality.score_history(&mut connection).await?))
 }
@@ -1,28 +1,54 @@
-use crate::{config, ratelimits::DemonlistRatelimits};
+use crate::{
+    endpoints::{asset::avatar_key, demon::demon_key},
+    ratelimits::DemonlistRatelimits,
+};
 use log::warn;
-use pointercrate_core::{error::CoreError, pool::PointercratePool};
+use pointercrate_core::{
+    config::Config,
+    error::{CoreError, PointercrateError},
+    patch::Patch,
+    pool::PointercratePool,
+};
 use pointercrate_core_api::{
+    cache::CachePurge,
+    documentation::documented,
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
+    mail::{Email, MailerHandle},
     pagination::pagination_response,
+    param::IdOrName,
     query::Query,
     response::Response2,
+    upload::{MultipartFile, StorageHandle},
 };
 use pointercrate_demonlist::{
     error::DemonlistError,
     nationality::Nationality,
     player::{
+        autocomplete_players,
+        avatar::{process_avatar, PlayerAvatar},
         claim::{ListedClaim, PatchPlayerClaim, PlayerClaim, PlayerClaimPagination},
-        DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerPagination, RankedPlayer, RankingPagination,
+        DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerAlias, PlayerId, PlayerPagination, PlayerSuggestion, PostAlias,
+        RankedPlayer, RankingPagination,
     },
+    score_history::ScoreSnapshot,
+    watch::{self, WatchTarget},
     LIST_HELPER,
 };
 use pointercrate_user::MODERATOR;
-use pointercrate_user_api::auth::TokenAuth;
-use rocket::{http::Status, serde::json::Json, State};
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
+use rocket::{
+    data::{self, FromData},
+    http::Status,
+    serde::json::Json,
+    tokio, Data, Request, State,
+};
 use serde::Deserialize;
-use std::net::IpAddr;
+use std::{collections::HashSet, net::IpAddr};
 
+/// All players, filterable by name and nation. Banned players are only included for list
+/// helpers
+#[documented(example = "/api/v1/players/?limit=5")]
 #[rocket::get("/")]
 pub async fn paginate(
     pool: &State<PointercratePool>, query: Query<PlayerPagination>, auth: Option<TokenAuth>,
@@ -37,45 +63,165 @@ pub async fn paginate(
         pagination.banned = Some(false);
     }
 
-    Ok(pagination_response("/api/v1/players/", pagination, &mut *pool.connection().await?).await?)
+    Ok(pagination_response("/api/v1/players/", pagination, &mut pool.read_only_connection().await?).await?)
 }
 
+/// Players whose name starts with `q`, for resolving player names in staff tools
+#[rocket::get("/autocomplete?<q>")]
+pub async fn autocomplete(q: &str, mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<PlayerSuggestion>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(autocomplete_players(q, &mut auth.connection).await?))
+}
+
+/// The stats viewer's player ranking, i.e. all players with a non-zero score ordered by score
+#[documented(example = "/api/v1/players/ranking/?limit=5")]
 #[rocket::get("/ranking")]
 pub async fn ranking(pool: &State<PointercratePool>, query: Query<RankingPagination>) -> Result<Response2<Json<Vec<RankedPlayer>>>> {
-    Ok(pagination_response("/api/v1/players/ranking/", query.0, &mut *pool.connection().await?).await?)
+    Ok(pagination_response("/api/v1/players/ranking/", query.0, &mut pool.read_only_connection().await?).await?)
 }
 
-#[rocket::get("/<player_id>")]
-pub async fn get(player_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
-    let mut connection = pool.connection().await?;
+/// A single player, including their records
+///
+/// Accepts either the player's ID or their (case insensitive) name, see [`IdOrName`]. Names of
+/// renamed players resolve via their aliases
+#[documented(example = "/api/v1/players/1/")]
+#[rocket::get("/<player>")]
+pub async fn get(player: IdOrName<'_>, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let player = match player {
+        IdOrName::Id(player_id) => Player::by_id(PlayerId(player_id), &mut connection).await?,
+        IdOrName::Name(name) => Player::by_name(name, &mut connection).await?,
+    };
 
-    Ok(Tagged(
-        Player::by_id(player_id, &mut *connection).await?.upgrade(&mut *connection).await?,
-    ))
+    Ok(Tagged(player.upgrade(&mut connection).await?))
 }
 
 #[rocket::patch("/<player_id>", data = "<patch>")]
 pub async fn patch(
-    player_id: i32, mut auth: TokenAuth, precondition: Precondition, patch: Json<PatchPlayer>,
+    player_id: i32, auth: TokenAuth, precondition: Precondition, patch: Json<PatchPlayer>, pool: &State<PointercratePool>,
+    mailer: &State<MailerHandle>, cache: CachePurge<'_>,
 ) -> Result<Tagged<FullPlayer>> {
-    let player = Player::by_id(player_id, &mut auth.connection)
-        .await?
-        .upgrade(&mut auth.connection)
-        .await?
-        .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+    auth.require_permission(LIST_HELPER)?;
+
+    for permission in patch.required_permissions() {
+        auth.require_permission(permission)?;
+    }
+
+    let (player, recipients, demon_ids) = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
+            let patch = patch.0.clone();
+
+            Box::pin(async move {
+                let player = Player::lock_by_id(PlayerId(player_id), connection)
+                    .await?
+                    .upgrade(connection)
+                    .await?
+                    .require_match(precondition)?;
+                let old_version = player.player.version;
+                // Banning the player rejects their records, so the affected demons need to be
+                // collected before applying the patch
+                let mut demon_ids = mentioning_demons(&player);
+                let player = player.apply_patch(patch, connection).await?;
+
+                if player.player.version == old_version {
+                    return Ok::<_, DemonlistError>((player, Vec::new(), HashSet::new()));
+                }
+
+                // Merging another player into this one adds records
+                demon_ids.extend(mentioning_demons(&player));
+
+                let recipients = watch::email_recipients(WatchTarget::Player(player.player.base.id), connection).await?;
+
+                Ok((player, recipients, demon_ids))
+            })
+        })
         .await?;
 
-    auth.commit().await?;
+    // Responses about these demons contain the player's name and (if they are not banned) records
+    for demon_id in &demon_ids {
+        cache.purge(&demon_key(*demon_id));
+    }
+
+    if !demon_ids.is_empty() {
+        cache.purge("records");
+    }
+
+    for recipient in recipients {
+        mailer.dispatch(Email::watched_object_modified(
+            recipient.email_address,
+            &recipient.name,
+            &format!("The player {}", player.player.base.name),
+        ));
+    }
 
     Ok(Tagged(player))
 }
 
+/// The demons whose cached responses mention the given player, since they hold an approved record
+/// on them or are credited on them
+fn mentioning_demons(player: &FullPlayer) -> HashSet<i32> {
+    let credited = player.created.iter().chain(&player.verified).chain(&player.published);
+
+    player
+        .records
+        .iter()
+        .map(|record| record.demon.id.0)
+        .chain(credited.map(|demon| demon.id.0))
+        .collect()
+}
+
+#[rocket::get("/<player_id>/aliases")]
+pub async fn aliases(player_id: i32, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<PlayerAlias>>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut connection).await?;
+
+    Ok(Response2::json(player.aliases(&mut connection).await?))
+}
+
+#[rocket::get("/<player_id>/score-history")]
+pub async fn score_history(player_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<ScoreSnapshot>>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut connection).await?;
+
+    Ok(Json(player.score_history(&mut connection).await?))
+}
+
+#[rocket::post("/<player_id>/aliases", data = "<data>")]
+pub async fn add_alias(player_id: i32, mut auth: TokenAuth, data: Json<PostAlias>) -> Result<Response2<Json<PlayerAlias>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
+    let alias = player.add_alias(&data.alias, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Response2::json(alias).status(Status::Created))
+}
+
+#[rocket::delete("/<player_id>/aliases/<alias>")]
+pub async fn delete_alias(player_id: i32, alias: &str, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
+
+    player.remove_alias(alias, &mut auth.connection).await?;
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
 #[rocket::put("/<player_id>/claims")]
 pub async fn put_claim(player_id: i32, mut auth: TokenAuth) -> Result<Response2<Json<PlayerClaim>>> {
+    auth.forbid_applications()?;
+
     let user_id = auth.user.user().id;
-    let player = DatabasePlayer::by_id(player_id, &mut auth.connection).await?;
-    let claim = player.initiate_claim(user_id, &mut auth.connection).await?;
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
+    let claim = player.initiate_claim(user_id.0, &mut auth.connection).await?;
 
     auth.commit().await?;
 
@@ -97,7 +243,9 @@ pub async fn patch_claim(player_id: i32, user_id: i32, mut auth: TokenAuth, data
 
     let claim = match claim {
         Ok(claim) if data.lock_submissions.is_some() => {
-            if claim.user_id != auth.user.user().id {
+            auth.forbid_applications()?;
+
+            if claim.user_id != auth.user.user().id.0 {
                 return Err(DemonlistError::ClaimNotFound {
                     member_id: user_id,
                     player_id,
@@ -141,7 +289,9 @@ pub async fn delete_claim(player_id: i32, user_id: i32, mut auth: TokenAuth) ->
 }
 
 #[rocket::get("/claims")]
-pub async fn paginate_claims(mut auth: TokenAuth, pagination: Query<PlayerClaimPagination>) -> Result<Response2<Json<Vec<ListedClaim>>>> {
+pub async fn paginate_claims(
+    mut auth: ReadOnlyTokenAuth, pagination: Query<PlayerClaimPagination>,
+) -> Result<Response2<Json<Vec<ListedClaim>>>> {
     auth.require_permission(MODERATOR)?;
 
     Ok(pagination_response("/api/v1/players/claims/", pagination.0, &mut auth.connection).await?)
@@ -161,10 +311,12 @@ struct GeolocationResponse {
 
 #[rocket::post("/<player_id>/geolocate")]
 pub async fn geolocate_nationality(
-    player_id: i32, ip: IpAddr, mut auth: TokenAuth, ratelimits: &State<DemonlistRatelimits>,
+    player_id: i32, ip: IpAddr, mut auth: TokenAuth, ratelimits: &State<DemonlistRatelimits>, config: &State<Config>,
 ) -> Result<Json<Nationality>> {
-    let mut player = Player::by_id(player_id, &mut auth.connection).await?;
-    let claim = PlayerClaim::get(auth.user.user().id, player_id, &mut auth.connection).await?;
+    auth.forbid_applications()?;
+
+    let mut player = Player::by_id(PlayerId(player_id), &mut auth.connection).await?;
+    let claim = PlayerClaim::get(auth.user.user().id.0, player_id, &mut auth.connection).await?;
 
     if !claim.verified {
         return Err(DemonlistError::ClaimUnverified.into());
@@ -174,7 +326,11 @@ pub async fn geolocate_nationality(
 
     let response = reqwest::get(format!(
         "https://ipgeolocation.abstractapi.com/v1/?api_key={}&ip_address={}&fields=security,country_code,region_iso_code",
-        config::abstract_api_key().ok_or_else(|| CoreError::internal_server_error("No API key for abstract configured"))?,
+        config
+            .integrations
+            .abstract_api_key
+            .as_ref()
+            .ok_or_else(|| CoreError::internal_server_error("No API key for abstract configured"))?,
         ip
     ))
     .await
@@ -211,3 +367,137 @@ pub async fn geolocate_nationality(
 
     Ok(Json(player.nationality.unwrap()))
 }
+
+/// Data guard for an avatar uploaded as the `file` field of a `multipart/form-data` body, read into
+/// memory
+///
+/// Uploads are bounded by the `avatar` body limit. Those announcing a `Content-Length` above it are
+/// rejected before any of the body is read.
+pub struct AvatarUpload(Vec<u8>);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for AvatarUpload {
+    type Error = CoreError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let file = match MultipartFile::from_data(request, data, "avatar", "file").await {
+            data::Outcome::Success(file) => file,
+            data::Outcome::Forward(forward) => return data::Outcome::Forward(forward),
+            data::Outcome::Error(error) => return data::Outcome::Error(error),
+        };
+
+        match file.bytes().await {
+            Ok(contents) => data::Outcome::Success(AvatarUpload(contents)),
+            Err(err) => data::Outcome::Error((Status::from_code(err.status_code()).unwrap(), err)),
+        }
+    }
+}
+
+/// Whether the authenticated user holds a verified claim on the given player. Applications never
+/// act through their owner's claims
+async fn holds_verified_claim(player_id: i32, auth: &mut TokenAuth) -> bool {
+    if auth.user.is_application() {
+        return false;
+    }
+
+    PlayerClaim::get(auth.user.user().id.0, player_id, &mut auth.connection)
+        .await
+        .is_ok_and(|claim| claim.verified)
+}
+
+/// Uploads a new avatar for the given player, replacing the current one. Only available to the
+/// holder of a verified claim on the player. The avatar is not shown publicly until a moderator
+/// approves it, until then the previously approved one (if any) stays visible
+#[rocket::put("/<player_id>/avatar", data = "<upload>")]
+pub async fn put_avatar(
+    player_id: i32, mut auth: TokenAuth, upload: std::result::Result<AvatarUpload, CoreError>, storage: &State<StorageHandle>,
+    cache: CachePurge<'_>,
+) -> Result<Response2<Json<PlayerAvatar>>> {
+    auth.forbid_applications()?;
+
+    let AvatarUpload(upload) = upload?;
+    let player = DatabasePlayer::by_id(PlayerId(player_id), &mut auth.connection).await?;
+    let claim = PlayerClaim::get(auth.user.user().id.0, player.id.0, &mut auth.connection).await?;
+
+    if !claim.verified {
+        return Err(DemonlistError::ClaimUnverified.into());
+    }
+
+    let images = tokio::task::spawn_blocking(move || process_avatar(&upload))
+        .await
+        .map_err(|err| CoreError::internal_server_error(format!("Failed to process avatar: {:?}", err)))??;
+
+    let avatar = PlayerAvatar::upload(player.id, auth.user.user().id.0, &mut auth.connection).await?;
+
+    for (size, png) in images {
+        storage.write(&avatar.storage_key(avatar.revision, size), &png).await?;
+    }
+
+    auth.commit().await?;
+
+    cache.purge(&avatar_key(player.id.0));
+
+    Ok(Response2::json(avatar)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/players/{}/avatar/", player.id)))
+}
+
+/// Information about the given player's avatar. Avatars of which no revision was approved yet are
+/// only visible to moderators
+#[rocket::get("/<player_id>/avatar")]
+pub async fn get_avatar(player_id: i32, auth: Option<TokenAuth>, pool: &State<PointercratePool>) -> Result<Tagged<PlayerAvatar>> {
+    let is_moderator = auth.as_ref().is_some_and(|auth| auth.has_permission(MODERATOR));
+    let avatar = PlayerAvatar::by_player(PlayerId(player_id), &mut pool.read_only_connection().await?).await?;
+
+    if avatar.approved_revision.is_none() && !is_moderator {
+        return Err(DemonlistError::AvatarNotFound { player_id }.into());
+    }
+
+    Ok(Tagged(avatar))
+}
+
+/// Approves the latest revision of the given player's avatar. Requires the avatar's ETag in an
+/// `If-Match` header, so that moderators only ever approve the revision they looked at
+#[rocket::post("/<player_id>/avatar/approve")]
+pub async fn approve_avatar(
+    player_id: i32, mut auth: TokenAuth, precondition: Precondition, cache: CachePurge<'_>,
+) -> Result<Tagged<PlayerAvatar>> {
+    auth.require_permission(MODERATOR)?;
+
+    let mut avatar = PlayerAvatar::by_player(PlayerId(player_id), &mut auth.connection)
+        .await?
+        .require_match(precondition)?;
+
+    avatar.approve(auth.user.user().id.0, &mut auth.connection).await?;
+    auth.commit().await?;
+
+    cache.purge(&avatar_key(player_id));
+
+    Ok(Tagged(avatar))
+}
+
+/// Removes the given player's avatar. Available to moderators and the holder of a verified claim
+/// on the player
+#[rocket::delete("/<player_id>/avatar")]
+pub async fn delete_avatar(player_id: i32, mut auth: TokenAuth, cache: CachePurge<'_>) -> Result<Status> {
+    if !holds_verified_claim(player_id, &mut auth).await {
+        auth.require_permission(MODERATOR)?;
+    }
+
+    PlayerAvatar::by_player(PlayerId(player_id), &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+    auth.commit().await?;
+
+    cache.purge(&avatar_key(player_id));
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::get("/avatars/pending")]
+pub async fn pending_avatars(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<PlayerAvatar>>> {
+    auth.require_permission(MODERATOR)?;
+
+    Ok(Json(PlayerAvatar::pending(&mut auth.connection).await?))
+}
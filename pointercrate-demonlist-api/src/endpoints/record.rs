@@ -1,51 +1,79 @@
-use crate::ratelimits::DemonlistRatelimits;
-use log::{debug, error, warn};
-use pointercrate_core::{audit::AuditLogEntry, error::CoreError, pool::PointercratePool};
+use crate::{endpoints::demon::demon_key, ratelimits::DemonlistRatelimits};
+use log::{debug, error, info, warn};
+use pointercrate_core::{
+    audit::AuditLogEntry,
+    error::{CoreError, PointercrateError},
+    job::{Job, JobHandle, JobRegistry},
+    patch::Patch,
+    pool::{audit_connection, PointercratePool, ReadConnection},
+    redact::{Redacted, ViewContext},
+};
 use pointercrate_core_api::{
+    cache::CachePurge,
+    documentation::documented,
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
+    mail::{Email, MailerHandle},
     pagination::pagination_response,
     query::Query,
     response::Response2,
+    upload::{MultipartFile, StorageHandle},
 };
 use pointercrate_demonlist::{
     error::DemonlistError,
     player::claim::PlayerClaim,
     record::{
+        appeal::{Appeal, AppealStatus, NewAppeal, PatchAppeal},
+        assignment::{self, AssignedSubmission},
         audit::RecordModificationData,
+        fingerprint::{reuses_of, VideoReuse},
+        import::{parse_import, ImportOutcome, ImportRow},
         note::{notes_on, NewNote, Note, PatchNote},
-        submission_count, FullRecord, MinimalRecordPD, PatchRecord, RecordPagination, RecordStatus, Submission,
+        records_of_user,
+        stale::{stale_submissions, StaleSubmission},
+        submission_count,
+        video_check::{dead_video_records, DeadVideoRecord},
+        FullRecord, MinimalRecordPD, PatchRecord, RecordId, RecordPagination, RecordStatus, Submission, UserRecord,
     },
     submitter::Submitter,
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
-use pointercrate_user_api::auth::TokenAuth;
-use rocket::{http::Status, serde::json::Json, tokio, State};
-use sqlx::{pool::PoolConnection, Postgres};
+use pointercrate_user::{User, UserId};
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
+use rocket::{
+    data::{self, FromData, ToByteUnit},
+    http::{ContentType, Status},
+    request::{self, FromRequest},
+    response::stream::ReaderStream,
+    serde::json::Json,
+    tokio, Data, Request, State,
+};
+use sqlx::{pool::PoolConnection, Acquire, Pool, Postgres};
 use std::net::IpAddr;
 
 /// Pagination endpoint for records in case authentication is provided
 ///
 /// Subject to the following constraints
-/// + Only users with `LIST_MODERATOR` permissions can filter by submitter.
+/// + Only users with `LIST_ADMINISTRATOR` permissions can filter by submitter, as the results
+/// include the anonymized submitters (see [`FullRecord::anonymous_submitter`]).
 /// + Only users with `LIST_HELPER` permissions can filter by record status. For all other users,
 /// the `status` property defaults to `APPROVED` (although explicitly setting the status to
 /// `APPROVED` is allowed, UNLESS we also filter by player and the player we filter by match a
 /// verified claim of the user making the request, in which case access to all records is allowed
 /// (the `status` property does not get defaulted, and filtering on it is allowed)
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, query: Query<RecordPagination>) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
+pub async fn paginate(mut auth: ReadOnlyTokenAuth, query: Query<RecordPagination>) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
     let mut pagination = query.0;
 
     if pagination.submitter.is_some() {
-        auth.require_permission(LIST_MODERATOR)?;
+        auth.require_permission(LIST_ADMINISTRATOR)?;
     }
 
-    let claim = PlayerClaim::by_user(auth.user.user().id, &mut auth.connection)
+    let claim = PlayerClaim::by_user(auth.user.user().id.0, &mut auth.connection)
         .await?
         .filter(|c| c.verified);
 
-    if (claim.is_none() || claim.map(|c| c.player.id) != pagination.player) && !auth.has_permission(LIST_HELPER) {
+    if (claim.is_none() || claim.map(|c| c.player.id.0) != pagination.player) && !auth.has_permission(LIST_HELPER) {
         if pagination.status.is_some() && pagination.status != Some(RecordStatus::Approved) {
             return Err(CoreError::MissingPermissions { required: LIST_HELPER }.into());
         }
@@ -56,11 +84,14 @@ pub async fn paginate(mut auth: TokenAuth, query: Query<RecordPagination>) -> Re
     Ok(pagination_response("/api/v1/records/", pagination, &mut auth.connection).await?)
 }
 
+/// Approved records, filterable by progress and demon (among others). Listing records of any other
+/// status requires authentication
+#[documented(example = "/api/v1/records/?limit=5")]
 #[rocket::get("/", rank = 1)]
 pub async fn unauthed_pagination(
     pool: &State<PointercratePool>, query: Query<RecordPagination>,
 ) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
     let mut pagination = query.0;
 
     if pagination.submitter.is_some() {
@@ -73,20 +104,23 @@ pub async fn unauthed_pagination(
 
     pagination.status = Some(RecordStatus::Approved);
 
-    Ok(pagination_response("/api/v1/records/", pagination, &mut *connection).await?)
+    Ok(pagination_response("/api/v1/records/", pagination, &mut connection).await?)
 }
 
 #[rocket::post("/", data = "<submission>")]
 pub async fn submit(
     ip: IpAddr, auth: Option<TokenAuth>, submission: Json<Submission>, pool: &State<PointercratePool>,
-    ratelimits: &State<DemonlistRatelimits>,
-) -> Result<Response2<Tagged<FullRecord>>> {
+    ratelimits: &State<DemonlistRatelimits>, cache: CachePurge<'_>,
+) -> Result<Response2<Tagged<Redacted<FullRecord>>>> {
     let submission = submission.0;
     let status_is_submitted = submission.status() == RecordStatus::Submitted;
     let (is_team_member, user_id) = match auth {
+        // Applications cannot submit to players whose claim their owner locked
+        Some(ref auth) if auth.user.is_application() => (auth.has_permission(LIST_HELPER), None),
         Some(ref auth) => (auth.has_permission(LIST_HELPER), Some(auth.user.user().id)),
         None => (false, None),
     };
+    let context = auth.as_ref().map(TokenAuth::view_context).unwrap_or_default();
 
     if !status_is_submitted || !submission.has_video() {
         match auth {
@@ -95,6 +129,14 @@ pub async fn submit(
         }
     }
 
+    // Accepting records below the requirement is a judgement call only list moderators get to make
+    if submission.is_grandfathered() {
+        match auth {
+            Some(ref auth) => auth.require_permission(LIST_MODERATOR)?,
+            None => return Err(CoreError::Unauthorized.into()),
+        }
+    }
+
     let mut connection = match auth {
         Some(auth) => auth.connection,
         None => pool.transaction().await?,
@@ -120,7 +162,7 @@ pub async fn submit(
     if let Some(claim) = normalized.verified_player_claim(&mut *connection).await? {
         if claim.lock_submissions {
             match user_id {
-                Some(user_id) if user_id == claim.user_id => (),
+                Some(user_id) if user_id == UserId(claim.user_id) => (),
                 _ => return Err(DemonlistError::NoThirdPartySubmissions.into()),
             }
         }
@@ -137,27 +179,28 @@ pub async fn submit(
         ratelimits.record_submission_global()?;
     }
 
-    let mut record = validated.create(submitter, &mut *connection).await?;
+    let record = validated.create(submitter, &mut *connection).await?;
 
     connection.commit().await.map_err(DemonlistError::from)?;
 
+    if record.status == RecordStatus::Approved {
+        cache.purge(&demon_key(record.demon.id.0));
+        cache.purge("records");
+    }
+
     // FIXME: This is fucking stupid
     if status_is_submitted {
         if let Some(ref video) = record.video {
             tokio::spawn(validate(
-                record.id,
+                record.id.0,
                 video.to_string(),
                 webhook_embed(&record),
-                pool.connection().await?,
+                pool.background_connection().await?,
             ));
         }
     }
 
-    if !is_team_member {
-        record.submitter = None;
-    }
-
-    let mut response = Response2::tagged(record);
+    let mut response = Response2::tagged(Redacted::new(record, &context));
 
     if status_is_submitted {
         response = response.with_header(
@@ -170,30 +213,176 @@ pub async fn submit(
 }
 
 #[rocket::get("/<record_id>")]
-pub async fn get(record_id: i32, auth: Option<TokenAuth>, pool: &State<PointercratePool>) -> Result<Tagged<FullRecord>> {
-    let is_helper = auth.as_ref().is_some_and(|auth| auth.has_permission(LIST_HELPER));
+pub async fn get(record_id: i32, auth: Option<ReadOnlyTokenAuth>, pool: &State<PointercratePool>) -> Result<Tagged<Redacted<FullRecord>>> {
+    let context = auth.as_ref().map(ReadOnlyTokenAuth::view_context).unwrap_or_default();
 
     let mut connection = match auth {
         Some(auth) => auth.connection,
-        None => pool.transaction().await?,
+        None => pool.read_only_connection().await?,
     };
 
-    let mut record = FullRecord::by_id(record_id, &mut *connection).await?;
+    let record = FullRecord::by_id(RecordId(record_id), &mut connection).await?;
 
     // TODO: allow access if auth is provided and a verified claim on the record's player is given
-    if !is_helper {
-        if record.status != RecordStatus::Approved {
-            return Err(DemonlistError::RecordNotFound { record_id }.into());
+    if !context.has_permission(LIST_HELPER) && record.status != RecordStatus::Approved {
+        return Err(DemonlistError::RecordNotFound { record_id }.into());
+    }
+
+    Ok(Tagged(Redacted::new(record, &context)))
+}
+
+/// Request guard checking whether the client may upload raw footage for the record in the path,
+/// before any of the upload is read
+///
+/// List helpers may upload raw footage for any record. Everyone else may only upload raw footage
+/// for records they submitted themselves (identified by IP) that have not been reviewed yet, and
+/// is subject to the `raw_footage_uploads` ratelimit.
+///
+/// The guard only authorizes the upload. It does not hold on to its database connection, as
+/// streaming the upload into storage can take a long time.
+pub struct RawFootageTarget {
+    context: ViewContext,
+    is_team_member: bool,
+    /// The user the raw footage change is attributed to in the audit log (0 for anonymous uploads)
+    user_id: i32,
+}
+
+impl RawFootageTarget {
+    async fn authorize(request: &Request<'_>, record_id: i32, ip: IpAddr) -> std::result::Result<Self, DemonlistError> {
+        let auth = request.guard::<Option<TokenAuth>>().await.succeeded().flatten();
+        let is_team_member = auth.as_ref().is_some_and(|auth| auth.has_permission(LIST_HELPER));
+        let context = auth.as_ref().map(TokenAuth::view_context).unwrap_or_default();
+        let user_id = auth.as_ref().map_or(0, |auth| auth.user.user().id.0);
+
+        let mut connection = match auth {
+            Some(auth) => auth.connection,
+            None => match request.rocket().state::<PointercratePool>() {
+                Some(pool) => pool.transaction().await?,
+                None => return Err(CoreError::internal_server_error("PointercratePool not retrievable from rocket state").into()),
+            },
+        };
+
+        let record = FullRecord::by_id(RecordId(record_id), &mut *connection).await?;
+
+        if !is_team_member {
+            let submitter = Submitter::by_ip(ip, &mut *connection).await?;
+            let is_submitter = submitter.is_some_and(|submitter| record.submitter.as_ref().map(|s| s.id) == Some(submitter.id));
+
+            if !is_submitter || record.status != RecordStatus::Submitted {
+                return Err(CoreError::MissingPermissions { required: LIST_HELPER }.into());
+            }
+
+            if let Some(ratelimits) = request.rocket().state::<DemonlistRatelimits>() {
+                ratelimits.raw_footage_uploads(ip)?;
+            }
         }
-        record.submitter = None;
-        record.raw_footage = None;
+
+        Ok(RawFootageTarget {
+            context,
+            is_team_member,
+            user_id,
+        })
     }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RawFootageTarget {
+    type Error = DemonlistError;
 
-    Ok(Tagged(record))
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        // Request guards run before the path is parsed, so invalid IDs are forwarded just like
+        // rocket would when parsing the `record_id` parameter
+        let Some(Ok(record_id)) = request.param::<i32>(0) else {
+            return request::Outcome::Forward(Status::UnprocessableEntity);
+        };
+
+        let Some(ip) = request.client_ip() else {
+            return request::Outcome::Forward(Status::InternalServerError);
+        };
+
+        match RawFootageTarget::authorize(request, record_id, ip).await {
+            Ok(target) => request::Outcome::Success(target),
+            Err(err) => request::Outcome::Error((Status::from_code(err.status_code()).unwrap(), err)),
+        }
+    }
+}
+
+/// Data guard for raw footage uploaded as the `file` field of a `multipart/form-data` body, bounded
+/// by the `raw-footage` body limit
+///
+/// Uploads announcing a `Content-Length` above the limit are rejected before any of the body is
+/// read. Otherwise, the file is streamed into storage, and rejected as soon as it exceeds the limit.
+pub struct RawFootageUpload<'r>(MultipartFile<'r>);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for RawFootageUpload<'r> {
+    type Error = CoreError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        MultipartFile::from_data(request, data, "raw-footage", "file")
+            .await
+            .map(RawFootageUpload)
+    }
+}
+
+fn raw_footage_key(record_id: i32) -> String {
+    format!("raw-footage/{}", record_id)
+}
+
+/// Uploads the raw footage for a record to pointercrate's storage
+///
+/// Available to list helpers, and to the record's submitter for as long as the record has not been
+/// reviewed yet.
+#[rocket::post("/<record_id>/raw-footage", data = "<upload>")]
+pub async fn upload_raw_footage(
+    record_id: i32, target: std::result::Result<RawFootageTarget, DemonlistError>,
+    upload: std::result::Result<RawFootageUpload<'_>, CoreError>, storage: &State<StorageHandle>, pool: &State<PointercratePool>,
+) -> Result<Response2<Json<Redacted<FullRecord>>>> {
+    let RawFootageTarget {
+        context,
+        is_team_member,
+        user_id,
+    } = target?;
+    let RawFootageUpload(file) = upload?;
+
+    storage.store(&raw_footage_key(record_id), file).await?;
+
+    let mut connection = pool.transaction().await?;
+
+    audit_connection(&mut connection, user_id).await?;
+
+    // The record might have been deleted or reviewed while the upload was streamed into storage
+    let mut record = FullRecord::by_id(RecordId(record_id), &mut *connection).await?;
+
+    if !is_team_member && record.status != RecordStatus::Submitted {
+        return Err(CoreError::MissingPermissions { required: LIST_HELPER }.into());
+    }
+
+    let location = format!("/api/v1/records/{}/raw-footage/", record_id);
+
+    record.set_raw_footage(location.clone(), &mut *connection).await?;
+
+    connection.commit().await.map_err(DemonlistError::from)?;
+
+    Ok(Response2::json(Redacted::new(record, &context))
+        .status(Status::Created)
+        .with_header("Location", location))
+}
+
+#[rocket::get("/<record_id>/raw-footage")]
+pub async fn raw_footage(
+    record_id: i32, auth: TokenAuth, storage: &State<StorageHandle>,
+) -> Result<ReaderStream![Box<dyn tokio::io::AsyncRead + Send + Unpin>]> {
+    auth.require_permission(LIST_HELPER)?;
+
+    match storage.open(&raw_footage_key(record_id)).await? {
+        Some(file) => Ok(ReaderStream::one(file)),
+        None => Err(DemonlistError::RecordNotFound { record_id }.into()),
+    }
 }
 
 #[rocket::get("/<record_id>/audit")]
-pub async fn audit(record_id: i32, mut auth: TokenAuth) -> Result<Json<Vec<AuditLogEntry<RecordModificationData>>>> {
+pub async fn audit(record_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<AuditLogEntry<RecordModificationData>>>> {
     auth.require_permission(LIST_ADMINISTRATOR)?;
 
     let log = pointercrate_demonlist::record::audit::audit_log_for_record(record_id, &mut auth.connection).await?;
@@ -207,48 +396,104 @@ pub async fn audit(record_id: i32, mut auth: TokenAuth) -> Result<Json<Vec<Audit
 
 #[rocket::patch("/<record_id>", data = "<patch>")]
 pub async fn patch(
-    record_id: i32, mut auth: TokenAuth, precondition: Precondition, patch: Json<PatchRecord>,
-) -> Result<Tagged<FullRecord>> {
-    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
-
-    if record.demon.position > pointercrate_demonlist::config::extended_list_size() {
-        auth.require_permission(LIST_MODERATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
+    record_id: i32, auth: TokenAuth, precondition: Precondition, patch: Json<PatchRecord>, pool: &State<PointercratePool>,
+    mailer: &State<MailerHandle>, cache: CachePurge<'_>,
+) -> Result<Tagged<Redacted<FullRecord>>> {
+    for permission in patch.required_permissions() {
+        auth.require_permission(permission)?;
     }
 
-    let record = record
-        .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+    auth.require_permission(LIST_HELPER)?;
+
+    let is_moderator = auth.has_permission(LIST_MODERATOR);
+    let context = auth.view_context();
+
+    let (record, old_demon_id, old_status) = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
+            let patch = patch.0.clone();
+
+            Box::pin(async move {
+                let record = FullRecord::lock_by_id(RecordId(record_id), connection).await?;
+
+                if record.demon.position > pointercrate_demonlist::config::extended_list_size() && !is_moderator {
+                    return Err(CoreError::MissingPermissions { required: LIST_MODERATOR }.into());
+                }
+
+                let old_status = record.status;
+                let old_demon_id = record.demon.id;
+                let record = record.require_match(precondition)?.apply_patch(patch, connection).await?;
+
+                Ok::<_, DemonlistError>((record, old_demon_id, old_status))
+            })
+        })
         .await?;
 
-    auth.commit().await?;
+    cache.purge(&demon_key(old_demon_id.0));
+    cache.purge(&demon_key(record.demon.id.0));
+    cache.purge("records");
 
-    Ok(Tagged(record))
+    if record.status != old_status {
+        let mut connection = pool.connection().await?;
+
+        if let Some(claim) = PlayerClaim::verified_claim_on(record.player.id.0, &mut connection).await? {
+            let claimant = User::by_id(UserId(claim.user_id), &mut connection).await?;
+
+            if let Some(email_address) = claimant.email_address(&mut connection).await? {
+                mailer.dispatch(Email::record_decision(
+                    email_address,
+                    claimant.name(),
+                    &record.demon.name,
+                    record.status,
+                ));
+            }
+        }
+    }
+
+    Ok(Tagged(Redacted::new(record, &context)))
 }
 
 #[rocket::delete("/<record_id>")]
-pub async fn delete(record_id: i32, mut auth: TokenAuth, precondition: Precondition) -> Result<Status> {
-    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+pub async fn delete(
+    record_id: i32, auth: TokenAuth, precondition: Precondition, pool: &State<PointercratePool>, cache: CachePurge<'_>,
+) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
 
-    if record.status == RecordStatus::Submitted && !record.was_modified(&mut auth.connection).await? {
-        auth.require_permission(LIST_HELPER)?;
-    } else {
-        auth.require_permission(LIST_MODERATOR)?;
-    }
+    let is_moderator = auth.has_permission(LIST_MODERATOR);
 
-    precondition.require_etag_match(&record)?;
+    let demon_id = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
 
-    record.delete(&mut auth.connection).await?;
-    auth.commit().await?;
+            Box::pin(async move {
+                let record = FullRecord::lock_by_id(RecordId(record_id), connection).await?;
+
+                // Helpers may only delete submissions nobody has touched yet
+                if !is_moderator && (record.status != RecordStatus::Submitted || record.was_modified(connection).await?) {
+                    return Err(CoreError::MissingPermissions { required: LIST_MODERATOR }.into());
+                }
+
+                precondition.require_etag_match(&record)?;
+
+                let demon_id = record.demon.id;
+
+                record.delete(connection).await?;
+
+                Ok::<_, DemonlistError>(demon_id)
+            })
+        })
+        .await?;
+
+    cache.purge(&demon_key(demon_id.0));
+    cache.purge("records");
 
     Ok(Status::NoContent)
 }
 
 #[rocket::get("/<record_id>/notes")]
-pub async fn get_notes(record_id: i32, mut auth: TokenAuth) -> Result<Response2<Json<Vec<Note>>>> {
+pub async fn get_notes(record_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Response2<Json<Redacted<Vec<Note>>>>> {
     let record_holder_id = sqlx::query!("SELECT player FROM records WHERE id = $1", record_id)
-        .fetch_one(&mut *auth.connection)
+        .fetch_one(&mut auth.connection.reader())
         .await
         .map_err(|err| {
             if let sqlx::Error::RowNotFound = err {
@@ -262,21 +507,21 @@ pub async fn get_notes(record_id: i32, mut auth: TokenAuth) -> Result<Response2<
     let notes = if auth.has_permission(LIST_HELPER) {
         notes_on(record_id, false, &mut auth.connection).await?
     } else {
-        match PlayerClaim::get(auth.user.user().id, record_holder_id, &mut auth.connection).await {
+        match PlayerClaim::get(auth.user.user().id.0, record_holder_id, &mut auth.connection).await {
             Ok(claim) if claim.verified => notes_on(record_id, true, &mut auth.connection).await?,
             Ok(_) | Err(DemonlistError::ClaimNotFound { .. }) => return Err(DemonlistError::RecordNotFound { record_id }.into()),
             Err(err) => return Err(err.into()),
         }
     };
 
-    Ok(Response2::json(notes))
+    Ok(Response2::json(Redacted::new(notes, &auth.view_context())))
 }
 
 #[rocket::post("/<record_id>/notes", data = "<data>")]
 pub async fn add_note(record_id: i32, mut auth: TokenAuth, data: Json<NewNote>) -> Result<Response2<Tagged<Note>>> {
     auth.require_permission(LIST_HELPER)?;
 
-    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+    let record = FullRecord::by_id(RecordId(record_id), &mut auth.connection).await?;
 
     let mut note = Note::create_on(&record, data.0, &mut auth.connection).await?;
 
@@ -339,7 +584,7 @@ async fn validate(record_id: i32, video: String, body: serde_json::Value, mut co
             } else {
                 warn!("Server response to 'GET {}' was {:?}, deleting submission!", video, response);
 
-                match FullRecord::delete_by_id(record_id, &mut *connection).await {
+                match FullRecord::delete_by_id(RecordId(record_id), &mut *connection).await {
                     Ok(_) => (),
                     Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
                 }
@@ -351,7 +596,7 @@ async fn validate(record_id: i32, video: String, body: serde_json::Value, mut co
                 error
             );
 
-            match FullRecord::delete_by_id(record_id, &mut *connection).await {
+            match FullRecord::delete_by_id(RecordId(record_id), &mut *connection).await {
                 Ok(_) => (),
                 Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
             }
@@ -385,7 +630,7 @@ fn webhook_embed(record: &FullRecord) -> serde_json::Value {
                 "title": record.demon.name,
                 "description": format!("{} just beat {}!", record.player.name, record.demon.name),
                 "footer": {
-                    "text": format!("This record has been submitted by submitter #{}", record.submitter.map(|s|s.id).unwrap_or(1))
+                    "text": format!("This record has been submitted by submitter #{}", record.submitter.map(|s|s.id.0).unwrap_or(1))
                 },
                 "author": {
                     "name": format!("{} (ID: {})", record.player.name, record.player.id),
@@ -409,3 +654,245 @@ fn webhook_embed(record: &FullRecord) -> serde_json::Value {
 
     payload
 }
+
+/// Lists all records belonging to the authenticated user (through verified claims, or because they
+/// submitted them while logged in), including their status history. Mounted at `/api/v1/auth/`
+#[rocket::get("/me/records")]
+pub async fn own_records(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<UserRecord>>> {
+    let user_id = auth.user.user().id;
+
+    Ok(Json(records_of_user(user_id.0, &mut auth.connection).await?))
+}
+
+/// The maximal size of a record import, in bytes
+const IMPORT_SIZE_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// Imports historical records from a CSV file, see [`import`](pointercrate_demonlist::record::import)
+///
+/// The rows are imported in the background, with progress available via `GET /api/v1/jobs/<id>/`.
+/// The outcome of each row can be downloaded via `GET /api/v1/records/import/<id>/`, both while the
+/// import is running and after it finished. Note that cached responses are not purged, so
+/// imported records only show up once these expire.
+#[rocket::post("/import", data = "<csv>")]
+pub async fn import(
+    ip: IpAddr, auth: TokenAuth, csv: Data<'_>, pool: &State<PointercratePool>, jobs: &State<JobRegistry>,
+) -> Result<Response2<Json<Job>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let csv = csv
+        .open(IMPORT_SIZE_LIMIT.bytes())
+        .into_string()
+        .await
+        .map_err(|_| CoreError::UnprocessableEntity)?;
+
+    if !csv.is_complete() {
+        return Err(CoreError::UploadTooLarge {
+            max_size: IMPORT_SIZE_LIMIT,
+        }
+        .into());
+    }
+
+    let rows = parse_import(&csv)?;
+    let handle = jobs.start("import_records", rows.len() as u32)?;
+    let job = jobs.get(handle.id())?;
+    let pool = pool.clone_background();
+
+    info!("{} started import of {} records (job {})", auth.user.user(), rows.len(), job.id);
+
+    rocket::tokio::spawn(run_import(pool, rows, ip, auth.user.user().id.0, handle));
+
+    let location = format!("/api/v1/jobs/{}/", job.id);
+
+    Ok(Response2::json(job).status(Status::Accepted).with_header("Location", location))
+}
+
+async fn run_import(pool: Pool<Postgres>, rows: Vec<ImportRow>, ip: IpAddr, user_id: i32, handle: JobHandle) {
+    handle.append_output(ImportOutcome::CSV_HEADER);
+
+    let result = async {
+        let mut connection = pool.acquire().await?;
+
+        audit_connection(&mut connection, user_id).await?;
+
+        let submitter = match Submitter::by_ip(ip, &mut connection).await? {
+            Some(submitter) => submitter,
+            None => Submitter::create_submitter(ip, &mut connection).await?,
+        };
+
+        for row in rows {
+            let mut transaction = connection.begin().await?;
+
+            let outcome = match row.import(submitter, &mut *transaction).await {
+                Ok(record) => {
+                    transaction.commit().await?;
+
+                    ImportOutcome {
+                        line: row.line,
+                        record: Some(record.id.0),
+                        error: None,
+                    }
+                },
+                Err(err) => {
+                    transaction.rollback().await?;
+
+                    ImportOutcome {
+                        line: row.line,
+                        record: None,
+                        error: Some(err),
+                    }
+                },
+            };
+
+            handle.append_output(&outcome.to_csv_line());
+            handle.advance();
+        }
+
+        Ok::<_, DemonlistError>(())
+    }
+    .await;
+
+    if let Err(ref err) = result {
+        error!("Record import (job {}) failed: {:?}", handle.id(), err);
+    }
+
+    handle.finish(result);
+}
+
+/// Downloads the outcome of each row of a record import as CSV
+#[rocket::get("/import/<job_id>", rank = 1)]
+pub async fn import_results(job_id: u32, auth: TokenAuth, jobs: &State<JobRegistry>) -> Result<(ContentType, String)> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    match jobs.get(job_id)?.kind {
+        "import_records" => Ok((ContentType::CSV, jobs.output(job_id)?.unwrap_or_default())),
+        _ => Err(CoreError::JobNotFound { job_id }.into()),
+    }
+}
+
+/// Appeals the rejection of a record. Only the record's submitter can do this, and only once per
+/// record, see [`appeal`](pointercrate_demonlist::record::appeal)
+#[rocket::post("/<record_id>/appeals", data = "<appeal>")]
+pub async fn appeal(
+    record_id: i32, ip: IpAddr, appeal: Json<NewAppeal>, pool: &State<PointercratePool>,
+) -> Result<Response2<Tagged<Appeal>>> {
+    let mut connection = pool.transaction().await?;
+
+    let record = FullRecord::by_id(RecordId(record_id), &mut *connection).await?;
+    let submitter = Submitter::by_ip(ip, &mut *connection)
+        .await?
+        .ok_or(DemonlistError::NotRecordSubmitter)?;
+
+    if submitter.banned {
+        return Err(DemonlistError::BannedFromSubmissions.into());
+    }
+
+    let appeal = Appeal::create(appeal.0, &record, &submitter, &mut *connection).await?;
+
+    connection.commit().await.map_err(DemonlistError::from)?;
+
+    let location = format!("/api/v1/records/appeals/{}/", appeal.id);
+
+    Ok(Response2::tagged(appeal).status(Status::Created).with_header("Location", location))
+}
+
+/// The open appeals the current user is allowed to resolve
+#[rocket::get("/appeals")]
+pub async fn appeals(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<Appeal>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let staff_id = auth.user.user().id;
+
+    Ok(Json(Appeal::queue_for(staff_id.0, AppealStatus::Open, &mut auth.connection).await?))
+}
+
+/// Approved records whose video was found to be unavailable during the periodic video checks
+#[rocket::get("/dead-videos")]
+pub async fn dead_videos(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<DeadVideoRecord>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(dead_video_records(&mut auth.connection).await?))
+}
+
+/// Submissions that have been waiting for review for longer than the configured threshold
+#[rocket::get("/stale")]
+pub async fn stale(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<StaleSubmission>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let threshold = pointercrate_demonlist::config::stale_submission_threshold();
+
+    Ok(Json(stale_submissions(threshold, &mut auth.connection).await?))
+}
+
+/// The submissions assigned to the requesting staff member that are still waiting for review
+#[rocket::get("/assigned")]
+pub async fn assigned(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<AssignedSubmission>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let member_id = auth.user.user().id;
+
+    Ok(Json(assignment::assigned_to(member_id.0, &mut auth.connection).await?))
+}
+
+#[rocket::put("/assigned/opt-out")]
+pub async fn opt_out_of_assignments(mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let member_id = auth.user.user().id;
+
+    assignment::set_opted_out(member_id.0, true, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::delete("/assigned/opt-out")]
+pub async fn opt_into_assignments(mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let member_id = auth.user.user().id;
+
+    assignment::set_opted_out(member_id.0, false, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+/// Approved records of other players whose video is the same as, or from the same channel as, the
+/// given record's video. Used by reviewers to spot stolen proof
+#[rocket::get("/<record_id>/video-reuse")]
+pub async fn video_reuse(record_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<VideoReuse>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    // Make sure we 404 for records that don't exist
+    FullRecord::by_id(RecordId(record_id), &mut auth.connection).await?;
+
+    Ok(Json(reuses_of(record_id, &mut auth.connection).await?))
+}
+
+#[rocket::get("/appeals/<appeal_id>", rank = 1)]
+pub async fn get_appeal(appeal_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Tagged<Appeal>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Ok(Tagged(Appeal::by_id(appeal_id, &mut auth.connection).await?))
+}
+
+#[rocket::patch("/appeals/<appeal_id>", data = "<patch>", rank = 1)]
+pub async fn resolve_appeal(
+    appeal_id: i32, precondition: Precondition, mut auth: TokenAuth, patch: Json<PatchAppeal>,
+) -> Result<Tagged<Appeal>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let staff_id = auth.user.user().id;
+
+    let appeal = Appeal::by_id(appeal_id, &mut auth.connection)
+        .await?
+        .require_match(precondition)?
+        .resolve(patch.0, staff_id.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(appeal))
+}
@@ -0,0 +1,79 @@
+use crate::ratelimits::DemonlistRatelimits;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{
+    error::Result,
+    etag::{Precondition, TaggableExt, Tagged},
+    pagination::pagination_response,
+    query::Query,
+    response::Response2,
+};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    report::{NewReport, PatchReport, Report, ReportId, ReportPagination},
+    submitter::Submitter,
+    LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
+use rocket::{http::Status, serde::json::Json, State};
+use std::net::IpAddr;
+
+#[rocket::post("/", data = "<report>")]
+pub async fn post(
+    ip: IpAddr, report: Json<NewReport>, pool: &State<PointercratePool>, ratelimits: &State<DemonlistRatelimits>,
+) -> Result<Response2<Tagged<Report>>> {
+    let mut connection = pool.transaction().await?;
+
+    let submitter = match Submitter::by_ip(ip, &mut *connection).await? {
+        Some(submitter) => submitter,
+        None => {
+            ratelimits.new_submitters()?;
+
+            Submitter::create_submitter(ip, &mut *connection).await?
+        },
+    };
+
+    if submitter.banned {
+        return Err(DemonlistError::BannedFromSubmissions.into());
+    }
+
+    ratelimits.reports(ip)?;
+
+    let report = Report::create_from(report.0, submitter, &mut *connection).await?;
+
+    connection.commit().await.map_err(DemonlistError::from)?;
+
+    let location = format!("/api/v1/reports/{}/", report.id);
+
+    Ok(Response2::tagged(report).status(Status::Created).with_header("Location", location))
+}
+
+#[rocket::get("/")]
+pub async fn paginate(mut auth: ReadOnlyTokenAuth, pagination: Query<ReportPagination>) -> Result<Response2<Json<Vec<Report>>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(pagination_response("/api/v1/reports/", pagination.0, &mut auth.connection).await?)
+}
+
+#[rocket::get("/<report_id>")]
+pub async fn get(report_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Tagged<Report>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Tagged(Report::by_id(ReportId(report_id), &mut auth.connection).await?))
+}
+
+#[rocket::patch("/<report_id>", data = "<patch>")]
+pub async fn patch(report_id: i32, precondition: Precondition, mut auth: TokenAuth, patch: Json<PatchReport>) -> Result<Tagged<Report>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let staff_id = auth.user.user().id;
+
+    let report = Report::by_id(ReportId(report_id), &mut auth.connection)
+        .await?
+        .require_match(precondition)?
+        .apply_patch(patch.0, staff_id.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(report))
+}
@@ -0,0 +1,158 @@
+use pointercrate_core_api::{
+    cache::CachePurge,
+    error::Result,
+    etag::{Precondition, TaggableExt, Tagged},
+    response::Response2,
+};
+use pointercrate_demonlist::{
+    list_update::{PostScheduledListUpdate, ScheduledListUpdate},
+    settings::{PatchSubmissionSettings, SubmissionSettings},
+    snapshot::{ListSnapshot, SnapshotDiff},
+    staff_activity::StaffActivity,
+    watch::{PostWatch, Watch, WatchId},
+    LIST_ADMINISTRATOR, LIST_HELPER,
+};
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
+use rocket::{http::Status, serde::json::Json};
+
+#[rocket::get("/activity?<weeks>")]
+pub async fn activity(weeks: Option<i32>, mut auth: ReadOnlyTokenAuth) -> Result<Json<StaffActivity>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let activity = StaffActivity::load(weeks.unwrap_or(12).clamp(1, 52), &mut auth.connection).await?;
+
+    Ok(Json(activity))
+}
+
+#[rocket::get("/submissions")]
+pub async fn submission_settings(mut auth: ReadOnlyTokenAuth) -> Result<Tagged<SubmissionSettings>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Tagged(SubmissionSettings::load(&mut auth.connection).await?))
+}
+
+#[rocket::patch("/submissions", data = "<patch>")]
+pub async fn patch_submission_settings(
+    mut auth: TokenAuth, precondition: Precondition, patch: Json<PatchSubmissionSettings>,
+) -> Result<Tagged<SubmissionSettings>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let settings = SubmissionSettings::load(&mut auth.connection)
+        .await?
+        .require_match(precondition)?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(settings))
+}
+
+/// Exports the entire list as a snapshot, see [`snapshot`](pointercrate_demonlist::snapshot)
+#[rocket::get("/snapshot")]
+pub async fn export_snapshot(mut auth: ReadOnlyTokenAuth) -> Result<Json<ListSnapshot>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(ListSnapshot::take(&mut auth.connection).await?))
+}
+
+/// Imports a snapshot exported from another instance, returning how it differed from the current
+/// list. If `dry_run` is set, the differences are only reported but not applied
+#[rocket::post("/snapshot?<dry_run>", data = "<snapshot>")]
+pub async fn import_snapshot(
+    dry_run: Option<bool>, mut auth: TokenAuth, snapshot: Json<ListSnapshot>, cache: CachePurge<'_>,
+) -> Result<Json<SnapshotDiff>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let dry_run = dry_run.unwrap_or(false);
+    let diff = snapshot.0.import(dry_run, &mut auth.connection).await?;
+
+    if !dry_run {
+        auth.commit().await?;
+
+        cache.purge_all();
+    }
+
+    Ok(Json(diff))
+}
+
+#[rocket::get("/scheduled-updates")]
+pub async fn scheduled_updates(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<ScheduledListUpdate>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(ScheduledListUpdate::all(&mut auth.connection).await?))
+}
+
+#[rocket::get("/scheduled-updates/<update_id>")]
+pub async fn scheduled_update(update_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<ScheduledListUpdate>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(ScheduledListUpdate::by_id(update_id, &mut auth.connection).await?))
+}
+
+/// Schedules a batch of list changes to be applied atomically at the given time, see
+/// [`list_update`](pointercrate_demonlist::list_update)
+#[rocket::post("/scheduled-updates", data = "<data>")]
+pub async fn schedule_update(mut auth: TokenAuth, data: Json<PostScheduledListUpdate>) -> Result<Response2<Json<ScheduledListUpdate>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let update = ScheduledListUpdate::create(data.0, auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let location = format!("/api/v1/staff/scheduled-updates/{}/", update.id);
+
+    Ok(Response2::json(update).status(Status::Created).with_header("Location", location))
+}
+
+#[rocket::delete("/scheduled-updates/<update_id>")]
+pub async fn cancel_scheduled_update(update_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    ScheduledListUpdate::by_id(update_id, &mut auth.connection)
+        .await?
+        .cancel(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+/// The demons and players the current user is watching, see
+/// [`watch`](pointercrate_demonlist::watch)
+#[rocket::get("/watches")]
+pub async fn watches(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<Watch>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let member_id = auth.user.user().id.0;
+
+    Ok(Json(Watch::all_of(member_id, &mut auth.connection).await?))
+}
+
+#[rocket::post("/watches", data = "<data>")]
+pub async fn watch(mut auth: TokenAuth, data: Json<PostWatch>) -> Result<Response2<Json<Watch>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let watch = Watch::create(data.0, auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Response2::json(watch).status(Status::Created))
+}
+
+#[rocket::delete("/watches/<watch_id>")]
+pub async fn unwatch(watch_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let member_id = auth.user.user().id.0;
+
+    Watch::by_id(WatchId(watch_id), member_id, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
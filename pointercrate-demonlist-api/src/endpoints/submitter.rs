@@ -6,24 +6,33 @@ use pointercrate_core_api::{
     response::Response2,
 };
 use pointercrate_demonlist::{
-    submitter::{PatchSubmitter, Submitter, SubmitterPagination},
-    LIST_MODERATOR,
+    submitter::{GeoSummaryEntry, PatchSubmitter, Submitter, SubmitterGeo, SubmitterId, SubmitterPagination},
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
-use pointercrate_user_api::auth::TokenAuth;
+use pointercrate_user_api::auth::{ReadOnlyTokenAuth, TokenAuth};
 use rocket::serde::json::Json;
+use serde::Deserialize;
 
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, pagination: Query<SubmitterPagination>) -> Result<Response2<Json<Vec<Submitter>>>> {
+pub async fn paginate(mut auth: ReadOnlyTokenAuth, pagination: Query<SubmitterPagination>) -> Result<Response2<Json<Vec<Submitter>>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
     Ok(pagination_response("/api/v1/submitters/", pagination.0, &mut auth.connection).await?)
 }
 
 #[rocket::get("/<submitter_id>")]
-pub async fn get(submitter_id: i32, mut auth: TokenAuth) -> Result<Tagged<Submitter>> {
+pub async fn get(submitter_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Tagged<Submitter>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    Ok(Tagged(Submitter::by_id(submitter_id, &mut auth.connection).await?))
+    Ok(Tagged(Submitter::by_id(SubmitterId(submitter_id), &mut auth.connection).await?))
+}
+
+/// Resolves the anonymized submitter shown on public record responses to the actual submitter
+#[rocket::get("/anonymous/<anonymous_id>", rank = 1)]
+pub async fn by_anonymous_id(anonymous_id: &str, mut auth: ReadOnlyTokenAuth) -> Result<Tagged<Submitter>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Tagged(Submitter::by_anonymous_id(anonymous_id, &mut auth.connection).await?))
 }
 
 #[rocket::patch("/<submitter_id>", data = "<patch>")]
@@ -32,7 +41,7 @@ pub async fn patch(
 ) -> Result<Tagged<Submitter>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    let submitter = Submitter::by_id(submitter_id, &mut auth.connection)
+    let submitter = Submitter::by_id(SubmitterId(submitter_id), &mut auth.connection)
         .await?
         .require_match(precondition)?
         .apply_patch(patch.0, &mut auth.connection)
@@ -42,3 +51,29 @@ pub async fn patch(
 
     Ok(Tagged(submitter))
 }
+
+#[rocket::get("/<submitter_id>/geo")]
+pub async fn geo(submitter_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Json<SubmitterGeo>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let submitter = Submitter::by_id(SubmitterId(submitter_id), &mut auth.connection).await?;
+
+    Ok(Json(submitter.geo_data(&mut auth.connection).await?))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GeoSummaryQuery {
+    #[serde(default = "default_summary_hours")]
+    hours: i32,
+}
+
+fn default_summary_hours() -> i32 {
+    24
+}
+
+#[rocket::get("/geo")]
+pub async fn geo_summary(mut auth: ReadOnlyTokenAuth, query: Query<GeoSummaryQuery>) -> Result<Json<Vec<GeoSummaryEntry>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(Submitter::geo_summary(query.0.hours, &mut auth.connection).await?))
+}
@@ -0,0 +1,181 @@
+//! Endpoints for embedding the list into other websites
+//!
+//! The JSON endpoints can be called from any origin, so that community websites can build their own
+//! cards without going through a server. For everything else, there are ready-made widget pages
+//! (see [`pointercrate_demonlist_pages::widget`]) and an [oEmbed](https://oembed.com/) provider,
+//! through which consumers like Discord or CMSes resolve widget URLs into embeddable `iframe`s.
+//!
+//! All responses can be cached. Those about records are tagged with the `records` surrogate key,
+//! which is purged whenever a record changes.
+
+use crate::endpoints::demon::CACHE_MAX_AGE;
+use maud::{html, Render};
+use pointercrate_core::{
+    config,
+    error::CoreError,
+    pool::{PointercratePool, ReadConnection},
+};
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::{
+    demon::{current_list, Demon, ListedDemon},
+    record::{latest_approved_records, RecentRecord},
+};
+use pointercrate_demonlist_pages::widget::{LatestRecordsWidget, TopDemonsWidget, WIDGET_BASE_HEIGHT, WIDGET_ENTRY_HEIGHT, WIDGET_WIDTH};
+use reqwest::Url;
+use rocket::{serde::json::Json, State};
+use serde::Serialize;
+
+/// The number of entries a widget shows, unless specified otherwise
+const DEFAULT_WIDGET_ENTRIES: i64 = 3;
+
+/// The maximal number of entries a widget can show
+const MAX_WIDGET_ENTRIES: i64 = 10;
+
+fn entries(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_WIDGET_ENTRIES).clamp(1, MAX_WIDGET_ENTRIES)
+}
+
+/// Allows the given response to be read by scripts on any website
+pub(crate) fn embeddable<T>(response: Response2<T>) -> Response2<T> {
+    response.with_header("Access-Control-Allow-Origin", "*")
+}
+
+async fn top_demons(limit: i64, connection: &mut impl ReadConnection) -> Result<Vec<Demon>> {
+    let connection = &mut connection.reader();
+    Ok(current_list(connection).await?.into_iter().take(limit as usize).collect())
+}
+
+#[rocket::get("/top?<limit>")]
+pub async fn top(limit: Option<i64>, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<ListedDemon>>>> {
+    let demons = top_demons(entries(limit), &mut pool.read_only_connection().await?).await?;
+
+    Ok(embeddable(
+        Response2::json(demons.into_iter().map(ListedDemon::from).collect()).cache_for(CACHE_MAX_AGE, "overview"),
+    ))
+}
+
+#[rocket::get("/records?<limit>")]
+pub async fn records(limit: Option<i64>, pool: &State<PointercratePool>) -> Result<Response2<Json<Vec<RecentRecord>>>> {
+    let records = latest_approved_records(entries(limit), &mut pool.read_only_connection().await?).await?;
+
+    Ok(embeddable(Response2::json(records).cache_for(CACHE_MAX_AGE, "records")))
+}
+
+#[rocket::get("/top?<limit>")]
+pub async fn top_page(limit: Option<i64>, pool: &State<PointercratePool>) -> Result<Response2<String>> {
+    let demons = top_demons(entries(limit), &mut pool.read_only_connection().await?).await?;
+
+    Ok(Response2::new(TopDemonsWidget { demons }.render().into_string())
+        .with_header("Content-Type", "text/html; charset=utf-8")
+        .cache_for(CACHE_MAX_AGE, "overview"))
+}
+
+#[rocket::get("/records?<limit>")]
+pub async fn records_page(limit: Option<i64>, pool: &State<PointercratePool>) -> Result<Response2<String>> {
+    let records = latest_approved_records(entries(limit), &mut pool.read_only_connection().await?).await?;
+
+    Ok(Response2::new(LatestRecordsWidget { records }.render().into_string())
+        .with_header("Content-Type", "text/html; charset=utf-8")
+        .cache_for(CACHE_MAX_AGE, "records"))
+}
+
+/// The widgets oEmbed consumers can ask about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Widget {
+    TopDemons,
+    LatestRecords,
+}
+
+impl Widget {
+    /// The widget shown for the given URL path. The list overview resolves to the top demons
+    fn from_path(path: &str) -> Option<Widget> {
+        match path.trim_end_matches('/') {
+            "/widgets/top" | "/list" => Some(Widget::TopDemons),
+            "/widgets/records" => Some(Widget::LatestRecords),
+            _ => None,
+        }
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Widget::TopDemons => "/widgets/top",
+            Widget::LatestRecords => "/widgets/records",
+        }
+    }
+
+    fn title(self, entries: i64) -> String {
+        match self {
+            Widget::TopDemons => format!("Top {}", entries),
+            Widget::LatestRecords => "Latest records".to_string(),
+        }
+    }
+}
+
+/// Response format of oEmbed requests for `rich` content, see section 2.3.4 of the oEmbed
+/// specification
+#[derive(Serialize, Debug)]
+pub struct OEmbed {
+    version: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: String,
+    provider_name: String,
+    provider_url: String,
+    cache_age: u32,
+    html: String,
+    width: u32,
+    height: u32,
+}
+
+/// oEmbed provider for widget URLs (`/widgets/top`, `/widgets/records` and `/list/`). The `limit`
+/// query parameter of the given URL is passed on to the widget
+#[rocket::get("/oembed?<url>&<format>&<maxwidth>&<maxheight>")]
+pub async fn oembed(url: &str, format: Option<&str>, maxwidth: Option<u32>, maxheight: Option<u32>) -> Result<Response2<Json<OEmbed>>> {
+    if format.is_some_and(|format| format != "json") {
+        return Err(CoreError::NotImplemented.into());
+    }
+
+    // Relative URLs (as used by the widgets' discovery links) resolve against this server
+    let (origin, url) = match Url::parse(url) {
+        Ok(url) if !["http", "https"].contains(&url.scheme()) => return Err(CoreError::NotFound.into()),
+        Ok(url) => (url.origin().ascii_serialization(), url),
+        Err(_) => (
+            String::new(),
+            Url::parse("http://localhost")
+                .and_then(|base| base.join(url))
+                .map_err(|_| CoreError::NotFound)?,
+        ),
+    };
+
+    let widget = Widget::from_path(url.path()).ok_or(CoreError::NotFound)?;
+    let limit = url
+        .query_pairs()
+        .find(|(key, _)| key == "limit")
+        .and_then(|(_, value)| value.parse().ok());
+    let entries = entries(limit);
+
+    let title = widget.title(entries);
+    let src = format!("{}{}?limit={}", origin, widget.path(), entries);
+    let width = maxwidth.map_or(WIDGET_WIDTH, |max| max.min(WIDGET_WIDTH));
+    let height = WIDGET_BASE_HEIGHT + entries as u32 * WIDGET_ENTRY_HEIGHT;
+    let height = maxheight.map_or(height, |max| max.min(height));
+
+    let html = html! {
+        iframe src=(src) width=(width) height=(height) frameborder="0" title=(title) {}
+    };
+
+    Ok(embeddable(
+        Response2::json(OEmbed {
+            version: "1.0",
+            kind: "rich",
+            title,
+            provider_name: config::theme().list_name.clone(),
+            provider_url: format!("{}/list/", origin),
+            cache_age: CACHE_MAX_AGE,
+            html: html.into_string(),
+            width,
+            height,
+        })
+        .cache_for(CACHE_MAX_AGE, "oembed"),
+    ))
+}
@@ -1,5 +1,6 @@
+pub use self::scheduler::scheduler;
 use crate::{endpoints::misc, ratelimits::DemonlistRatelimits};
-use pointercrate_core::pool::PointercratePool;
+use pointercrate_core::{job::JobRegistry, pool::PointercratePool};
 use pointercrate_integrate::gd::GeometryDashConnector;
 use rocket::{Build, Rocket};
 
@@ -7,84 +8,221 @@ pub(crate) mod config;
 mod endpoints;
 pub(crate) mod pages;
 pub(crate) mod ratelimits;
+mod scheduler;
 
 pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
     let ratelimits = DemonlistRatelimits::new();
-    let dash_rs = GeometryDashConnector::new(rocket.state::<PointercratePool>().unwrap().clone_inner());
+    let dash_rs = GeometryDashConnector::new(rocket.state::<PointercratePool>().unwrap().clone_background());
 
     rocket
         .manage(ratelimits)
         .manage(dash_rs)
-        .mount("/api/v1/list_information/", rocket::routes![misc::list_information])
+        .manage(JobRegistry::default())
+        .mount(
+            "/api/v1/list_information/",
+            pointercrate_core_api::routes![misc::list_information, misc::requirement],
+        )
+        .mount("/api/v1/list/", pointercrate_core_api::routes![endpoints::changelog::changelog])
+        .mount("/api/v1/auth/", pointercrate_core_api::routes![endpoints::record::own_records])
+        .mount(
+            "/api/v1/account/",
+            pointercrate_core_api::routes![endpoints::account::record_queue, endpoints::account::demon_editor],
+        )
         .mount(
             "/api/v1/submitters/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::submitter::paginate,
                 endpoints::submitter::get,
-                endpoints::submitter::patch
+                endpoints::submitter::by_anonymous_id,
+                endpoints::submitter::patch,
+                endpoints::submitter::geo,
+                endpoints::submitter::geo_summary
             ],
         )
         .mount(
             "/api/v1/records/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::record::get_notes,
                 endpoints::record::add_note,
+                endpoints::record::appeal,
+                endpoints::record::appeals,
+                endpoints::record::get_appeal,
+                endpoints::record::resolve_appeal,
                 endpoints::record::audit,
+                endpoints::record::dead_videos,
+                endpoints::record::video_reuse,
+                endpoints::record::stale,
+                endpoints::record::assigned,
+                endpoints::record::opt_out_of_assignments,
+                endpoints::record::opt_into_assignments,
                 endpoints::record::delete,
                 endpoints::record::delete_note,
                 endpoints::record::get,
+                endpoints::record::import,
+                endpoints::record::import_results,
                 endpoints::record::paginate,
                 endpoints::record::unauthed_pagination,
                 endpoints::record::patch,
                 endpoints::record::patch_note,
-                endpoints::record::submit
+                endpoints::record::raw_footage,
+                endpoints::record::submit,
+                endpoints::record::upload_raw_footage
+            ],
+        )
+        .mount(
+            "/api/v1/reports/",
+            pointercrate_core_api::routes![
+                endpoints::report::post,
+                endpoints::report::paginate,
+                endpoints::report::get,
+                endpoints::report::patch
+            ],
+        )
+        .mount(
+            "/api/v1/jobs/",
+            pointercrate_core_api::routes![
+                endpoints::job::paginate,
+                endpoints::job::get,
+                endpoints::job::recompute_scores,
+                endpoints::job::backfill
+            ],
+        )
+        .mount(
+            "/api/v1/staff/",
+            pointercrate_core_api::routes![
+                endpoints::staff::activity,
+                endpoints::staff::submission_settings,
+                endpoints::staff::patch_submission_settings,
+                endpoints::staff::export_snapshot,
+                endpoints::staff::import_snapshot,
+                endpoints::staff::scheduled_updates,
+                endpoints::staff::scheduled_update,
+                endpoints::staff::schedule_update,
+                endpoints::staff::cancel_scheduled_update,
+                endpoints::staff::watches,
+                endpoints::staff::watch,
+                endpoints::staff::unwatch
             ],
         )
         .mount(
             "/api/v1/players/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::player::get,
                 endpoints::player::paginate,
+                endpoints::player::autocomplete,
                 endpoints::player::patch,
                 endpoints::player::ranking,
+                endpoints::player::score_history,
+                endpoints::player::aliases,
+                endpoints::player::add_alias,
+                endpoints::player::delete_alias,
                 endpoints::player::put_claim,
                 endpoints::player::patch_claim,
                 endpoints::player::paginate_claims,
                 endpoints::player::delete_claim,
-                endpoints::player::geolocate_nationality
+                endpoints::player::geolocate_nationality,
+                endpoints::player::put_avatar,
+                endpoints::player::get_avatar,
+                endpoints::player::approve_avatar,
+                endpoints::player::delete_avatar,
+                endpoints::player::pending_avatars
             ],
         )
         .mount(
             "/api/v1/nationalities/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::nationality::subdivisions,
                 endpoints::nationality::ranking,
-                endpoints::nationality::nation
+                endpoints::nationality::subdivision_ranking,
+                endpoints::nationality::continents,
+                endpoints::nationality::nation,
+                endpoints::nationality::score_history
             ],
         )
+        .mount(
+            "/api/v1/assets/",
+            pointercrate_core_api::routes![
+                endpoints::asset::nation_flag,
+                endpoints::asset::subdivision_flag,
+                endpoints::asset::avatar
+            ],
+        )
+        .mount(
+            "/api/v1/widgets/",
+            pointercrate_core_api::routes![endpoints::widget::top, endpoints::widget::records, endpoints::widget::oembed],
+        )
+        .mount(
+            "/widgets/",
+            rocket::routes![endpoints::widget::top_page, endpoints::widget::records_page],
+        )
+        .mount(
+            "/api/v1/demons/",
+            pointercrate_core_api::routes![endpoints::legacy::export_records, endpoints::demon::search],
+        )
+        .mount(
+            "/api/legacy/demons/",
+            pointercrate_core_api::routes![endpoints::legacy::demons, endpoints::legacy::demon],
+        )
+        .mount(
+            "/api/legacy/players/",
+            pointercrate_core_api::routes![endpoints::legacy::players, endpoints::legacy::player],
+        )
+        .mount(
+            "/api/legacy/records/",
+            pointercrate_core_api::routes![endpoints::legacy::records, endpoints::legacy::record],
+        )
         .mount(
             "/api/v2/demons/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::demon::get,
+                endpoints::demon::get_page,
                 endpoints::demon::paginate,
                 endpoints::demon::paginate_listed,
+                endpoints::demon::paginate_listed_compact,
+                endpoints::demon::random,
+                endpoints::demon::lookup,
+                endpoints::demon::records,
+                endpoints::demon::nationalities,
                 endpoints::demon::audit,
                 endpoints::demon::movement_log,
                 endpoints::demon::patch,
+                endpoints::demon::swap,
                 endpoints::demon::post,
                 endpoints::demon::post_creator,
+                endpoints::demon::patch_creator,
                 endpoints::demon::delete_creator,
-                endpoints::demon::delete_demon_data
+                endpoints::demon::post_verifier,
+                endpoints::demon::delete_verifier,
+                endpoints::demon::post_publisher,
+                endpoints::demon::delete_publisher,
+                endpoints::demon::reverification,
+                endpoints::demon::request_reverification,
+                endpoints::demon::patch_reverification,
+                endpoints::demon::complete_reverification,
+                endpoints::demon::delete_demon_data,
+                endpoints::demon::archive,
+                endpoints::demon::archived_demons,
+                endpoints::demon::archived_demon,
+                endpoints::demon::archived_records,
+                endpoints::demon::restore_archived,
+                endpoints::demon::drafts,
+                endpoints::demon::draft,
+                endpoints::demon::post_draft,
+                endpoints::demon::patch_draft,
+                endpoints::demon::delete_draft,
+                endpoints::demon::publish_draft
             ],
         )
         .mount(
             "/list/",
             rocket::routes![
                 pages::overview,
+                pages::changelog,
                 pages::stats_viewer_redirect,
                 pages::stats_viewer,
                 pages::nation_stats_viewer,
                 pages::demon_page,
+                pages::demon_json,
                 pages::demon_permalink,
                 pages::heatmap_css
             ],
@@ -3,19 +3,31 @@ use std::collections::HashMap;
 use rocket::{response::Redirect, State};
 
 use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
-use pointercrate_core::{audit::AuditLogEntryType, pool::PointercratePool};
+use pointercrate_core::{
+    audit::AuditLogEntryType,
+    pool::{PointercratePool, ReadConnection},
+};
 use pointercrate_core_api::{
     error::Result,
-    response::{Page, Response2},
+    etag::Tagged,
+    response::{Page, Response2, ViewPreferences},
 };
 use pointercrate_core_pages::head::HeadLike;
 use pointercrate_demonlist::{
-    demon::{audit::audit_log_for_demon, current_list, list_at, FullDemon, MinimalDemon},
+    changelog::{current_week, parse_week, weekly_changelog},
+    creator::{creators_by_role, creators_of},
+    demon::{
+        audit::audit_log_for_demon,
+        credit::{credits_of, CreditKind},
+        current_list, list_at, Demon, DemonId, FullDemon, MinimalDemon,
+    },
     error::DemonlistError,
     nationality::Nationality,
+    record::{approved_record_summary, approved_records_page_on},
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_demonlist_pages::{
+    changelog::ChangelogPage,
     components::{team::Team, time_machine::Tardis},
     demon_page::{DemonMovement, DemonPage},
     overview::OverviewPage,
@@ -27,6 +39,8 @@ use pointercrate_user_api::auth::TokenAuth;
 use rand::Rng;
 use rocket::{futures::StreamExt, http::CookieJar};
 
+use crate::endpoints::demon::full_demon_response;
+
 #[rocket::get("/?statsviewer=true")]
 pub fn stats_viewer_redirect() -> Redirect {
     Redirect::to(rocket::uri!(stats_viewer))
@@ -37,11 +51,11 @@ pub async fn overview(
     pool: &State<PointercratePool>, timemachine: Option<bool>, submitter: Option<bool>, cookies: &CookieJar<'_>, auth: Option<TokenAuth>,
 ) -> Result<Page> {
     // A few months before pointercrate first went live - definitely the oldest data we have
-    let beginning_of_time = NaiveDate::from_ymd_opt(2017, 1, 4).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    let beginning_of_time = NaiveDate::from_ymd_opt(2017, 1, 4).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
 
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
 
-    let demonlist = current_list(&mut *connection).await?;
+    let demonlist = current_list(&mut connection).await?;
 
     let mut specified_when = cookies
         .get("when")
@@ -49,7 +63,7 @@ pub async fn overview(
         .flatten();
 
     // On april's fools, ignore the cookie and just pick a random day to display
-    let today = Utc::now().naive_utc();
+    let today = Utc::now();
     let is_april_1st = today.day() == 1 && today.month() == 4;
     if is_april_1st {
         let seconds_since_beginning_of_time = (today - beginning_of_time).num_seconds();
@@ -58,12 +72,12 @@ pub async fn overview(
         if let Some(date) = today.checked_sub_signed(go_back_by) {
             // We do not neccessarily know the time zone of the user here (we get it from the 'when' cookie in the normal case).
             // This however is not a problem, the UI will simply display "GMT+0" instead of the correct local timezone.
-            specified_when = Some(date.and_utc().fixed_offset());
+            specified_when = Some(date.fixed_offset());
         }
     }
 
     let specified_when = match specified_when {
-        Some(when) if when.naive_utc() < beginning_of_time => Some(DateTime::from_naive_utc_and_offset(beginning_of_time, *when.offset())),
+        Some(when) if when < beginning_of_time => Some(beginning_of_time.with_timezone(when.offset())),
         Some(when) if when >= Utc::now() => None,
         Some(when) => Some(when),
         _ => None,
@@ -72,15 +86,15 @@ pub async fn overview(
     let mut tardis = Tardis::new(timemachine.unwrap_or(false));
 
     if let Some(destination) = specified_when {
-        let demons_then = list_at(&mut *connection, destination.naive_utc()).await?;
+        let demons_then = list_at(&mut connection, destination.with_timezone(&Utc)).await?;
         tardis.activate(destination, demons_then, !is_april_1st)
     }
 
     let mut page = Page::new(OverviewPage {
         team: Team {
-            admins: User::by_permission(LIST_ADMINISTRATOR, &mut *connection).await?,
-            moderators: User::by_permission(LIST_MODERATOR, &mut *connection).await?,
-            helpers: User::by_permission(LIST_HELPER, &mut *connection).await?,
+            admins: User::by_permission(LIST_ADMINISTRATOR, &mut connection).await?,
+            moderators: User::by_permission(LIST_MODERATOR, &mut connection).await?,
+            helpers: User::by_permission(LIST_HELPER, &mut connection).await?,
         },
         demonlist,
         time_machine: tardis,
@@ -96,22 +110,55 @@ pub async fn overview(
 
 #[rocket::get("/permalink/<demon_id>")]
 pub async fn demon_permalink(demon_id: i32, pool: &State<PointercratePool>) -> Result<Redirect> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
 
-    let position = MinimalDemon::by_id(demon_id, &mut *connection).await?.position;
+    let position = MinimalDemon::by_id(DemonId(demon_id), &mut connection).await?.position;
 
-    Ok(Redirect::to(rocket::uri!("/list", demon_page(position))))
+    Ok(Redirect::to(rocket::uri!("/list", demon_page(position, _))))
 }
 
-#[rocket::get("/<position>")]
+/// Renders the page of the demon at the given position. Only one page of records (`page`, starting at
+/// `1`) is rendered server-side, further ones are loaded via the demon's records endpoint
+#[rocket::get("/<position>?<page>", format = "html", rank = 1)]
 pub async fn demon_page(
-    position: i16, pool: &State<PointercratePool>, gd: &State<GeometryDashConnector>, auth: Option<TokenAuth>,
-) -> Result<Page> {
-    let mut connection = pool.connection().await?;
+    position: i16, page: Option<i64>, pool: &State<PointercratePool>, gd: &State<GeometryDashConnector>, auth: Option<TokenAuth>,
+    preferences: &ViewPreferences,
+) -> Result<Response2<Page>> {
+    let mut connection = pool.read_only_connection().await?;
+
+    let demon = Demon::by_position(position, &mut connection).await?;
+
+    render_demon_page(demon, page, &mut connection, gd, auth, preferences).await
+}
+
+/// The demon at the given position as returned by `GET /api/v2/demons/<demon_id>`, for clients that
+/// ask for JSON instead of the rendered page
+#[rocket::get("/<position>", format = "json", rank = 2)]
+pub async fn demon_json(position: i16, pool: &State<PointercratePool>) -> Result<Response2<Tagged<FullDemon>>> {
+    let demon = FullDemon::by_position(position, &mut pool.read_only_connection().await?).await?;
+
+    Ok(full_demon_response(demon))
+}
 
-    let full_demon = FullDemon::by_position(position, &mut *connection).await?;
+/// Loads everything shown on the page of the given demon and renders it. Shared between
+/// [`demon_page`] and the demon API endpoint, which serves the page to clients asking for HTML
+pub(crate) async fn render_demon_page(
+    demon: Demon, page: Option<i64>, connection: &mut impl ReadConnection, gd: &GeometryDashConnector, auth: Option<TokenAuth>,
+    preferences: &ViewPreferences,
+) -> Result<Response2<Page>> {
+    let connection = &mut connection.reader();
+    let records_page = page.unwrap_or(1).max(1);
+    let record_summary = approved_record_summary(&demon.base, &mut *connection).await?;
+    let full_demon = FullDemon {
+        creators: creators_of(&demon.base, &mut *connection).await?,
+        creators_by_role: creators_by_role(&demon.base, &mut *connection).await?,
+        verifiers: credits_of(&demon, CreditKind::Verifier, &mut *connection).await?,
+        publishers: credits_of(&demon, CreditKind::Publisher, &mut *connection).await?,
+        records: approved_records_page_on(&demon.base, records_page, &mut *connection).await?,
+        demon,
+    };
 
-    let audit_log = audit_log_for_demon(full_demon.demon.base.id, &mut *connection).await?;
+    let audit_log = audit_log_for_demon(full_demon.demon.base.id.0, &mut *connection).await?;
 
     let mut addition_time = None;
 
@@ -121,7 +168,7 @@ pub async fn demon_page(
             AuditLogEntryType::Modification(ref modification) => match modification.position {
                 Some(old_position) if old_position > 0 => Some(DemonMovement {
                     from_position: old_position,
-                    at: entry.time,
+                    at: preferences.local_time(entry.time),
                 }),
                 _ => None,
             },
@@ -142,12 +189,13 @@ pub async fn demon_page(
                     .first()
                     .map(|m| m.from_position)
                     .unwrap_or(full_demon.demon.base.position),
-                at: addition,
+                at: preferences.local_time(addition),
             },
         );
     }
 
-    let mut page = Page::new(DemonPage {
+    let demon_id = full_demon.demon.base.id;
+    let page = Page::new(DemonPage {
         team: Team {
             admins: User::by_permission(LIST_ADMINISTRATOR, &mut *connection).await?,
             moderators: User::by_permission(LIST_MODERATOR, &mut *connection).await?,
@@ -157,21 +205,46 @@ pub async fn demon_page(
         movements: modifications,
         integration: gd.load_level_for_demon(&full_demon.demon).await,
         data: full_demon,
+        records_page,
+        record_summary,
     });
 
-    if let Some(token_auth) = auth {
-        page = page.meta("csrf_token", token_auth.user.generate_csrf_token());
-    }
+    let response = match auth {
+        Some(token_auth) => Response2::new(page.meta("csrf_token", token_auth.user.generate_csrf_token())),
+        // The page also contains the sidebar listing all demons, and thus needs purging whenever the list changes
+        None => Response2::new(page).cache_for(300, format!("demon:{} overview", demon_id)),
+    };
 
-    Ok(page)
+    // The same URLs serve JSON to API clients
+    Ok(response.with_header("Vary", "Accept"))
+}
+
+/// Renders the changelog of the given ISO week (e.g. `2024-W42`), or of the current week if none is
+/// given. The same data is available via `GET /api/v1/list/changelog`
+#[rocket::get("/changelog?<week>")]
+pub async fn changelog(
+    week: Option<&str>, pool: &State<PointercratePool>, auth: Option<TokenAuth>, preferences: &ViewPreferences,
+) -> Result<Response2<Page>> {
+    let week = week.map(parse_week).transpose()?.unwrap_or_else(current_week);
+    let changelog = weekly_changelog(week, &mut pool.read_only_connection().await?).await?;
+    let page = Page::new(ChangelogPage {
+        changelog,
+        utc_offset: preferences.offset(),
+    });
+
+    // Logged in users see the week's bounds in their own timezone
+    Ok(match auth {
+        Some(_) => Response2::new(page),
+        None => Response2::new(page).cache_for(300, "overview"),
+    })
 }
 
 #[rocket::get("/statsviewer")]
 pub async fn stats_viewer(pool: &State<PointercratePool>) -> Result<Page> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
 
     Ok(Page::new(IndividualStatsViewer {
-        nationalities_in_use: Nationality::used(&mut *connection).await?,
+        nationalities_in_use: Nationality::used(&mut connection).await?,
     }))
 }
 
@@ -182,11 +255,12 @@ pub async fn nation_stats_viewer() -> Page {
 
 #[rocket::get("/statsviewer/heatmap.css")]
 pub async fn heatmap_css(pool: &State<PointercratePool>) -> Result<Response2<String>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_only_connection().await?;
+    let mut connection = connection.reader();
     let mut css = String::new();
 
     let mut nation_scores = HashMap::new();
-    let mut nations_stream = sqlx::query!("SELECT iso_country_code, score FROM nationalities WHERE score > 0.0").fetch(&mut *connection);
+    let mut nations_stream = sqlx::query!("SELECT iso_country_code, score FROM nationalities WHERE score > 0.0").fetch(&mut connection);
 
     while let Some(row) = nations_stream.next().await {
         let row = row.map_err(DemonlistError::from)?;
@@ -206,8 +280,7 @@ pub async fn heatmap_css(pool: &State<PointercratePool>) -> Result<Response2<Str
     // un-borrow `connection`
     drop(nations_stream);
 
-    let mut subdivisions_stream =
-        sqlx::query!("SELECT nation, iso_code, score FROM subdivisions WHERE score > 0.0").fetch(&mut *connection);
+    let mut subdivisions_stream = sqlx::query!("SELECT nation, iso_code, score FROM subdivisions WHERE score > 0.0").fetch(&mut connection);
 
     while let Some(row) = subdivisions_stream.next().await {
         let row = row.map_err(DemonlistError::from)?;
@@ -13,6 +13,10 @@ ratelimits! {
         geolocate[1u32 per 2_678_400 per IpAddr] => "You can only geolocate once per month!",
 
         add_demon[1u32 per 20] => "Spam Detected, Loser!",
+
+        reports[3u32 per 3600 per IpAddr] => "You are filing too many reports!",
+
+        raw_footage_uploads[5u32 per 3600 per IpAddr] => "You are uploading too much raw footage!",
     }
 }
 
@@ -0,0 +1,301 @@
+//! Periodically running background jobs
+//!
+//! The scheduler is not part of [`setup`](crate::setup), so that integration tests do not spawn
+//! background tasks. Instances attach it explicitly via [`scheduler`].
+//!
+//! Each run of a job happens in its own transaction, which is retried if it conflicts with a
+//! concurrent one (see [`retry_on_conflict`]), and in which changes are attributed to the system
+//! user in audit logs. Jobs that talk to external services, such as the video checks, run outside
+//! of transactions instead, so that they do not hold locks while waiting on the network.
+
+use log::{error, info, warn};
+use pointercrate_core::pool::{retry_on_conflict, PointercratePool, TransactionFuture};
+use pointercrate_core_api::cache::ResponseCache;
+use pointercrate_demonlist::{
+    config,
+    error::DemonlistError,
+    list_update,
+    record::{
+        assignment, fingerprint,
+        video_check::{due_video_checks, record_video_check},
+    },
+    score, score_history,
+    settings::SubmissionSettings,
+    staff_activity::StaffActivity,
+    submitter::Submitter,
+};
+use reqwest::{StatusCode, Url};
+use rocket::fairing::AdHoc;
+use sqlx::{PgConnection, Pool, Postgres};
+use std::time::Duration;
+
+type JobFuture<'c> = TransactionFuture<'c, (), DemonlistError>;
+
+/// Fairing that starts all background jobs once rocket has launched
+pub fn scheduler() -> AdHoc {
+    AdHoc::on_liftoff("Scheduler", |rocket| {
+        Box::pin(async move {
+            let pool = rocket.state::<PointercratePool>().unwrap().clone_background();
+
+            // Scores need to reflect the configured decay before anything else touches them
+            if let Err(err) = retry_on_conflict(&pool, 0, sync_score_decay).await {
+                error!("Failed to apply configured score decay: {:?}", err);
+            }
+
+            spawn_job(
+                "staff activity aggregation",
+                Duration::from_secs(3600),
+                pool.clone(),
+                aggregate_staff_activity,
+            );
+            spawn_job(
+                "scheduled submission reopening",
+                Duration::from_secs(60),
+                pool.clone(),
+                reopen_submissions,
+            );
+            spawn_job(
+                "submitter geolocation purge",
+                Duration::from_secs(3600),
+                pool.clone(),
+                purge_geo_data,
+            );
+            spawn_job("score snapshots", Duration::from_secs(3600), pool.clone(), take_score_snapshots);
+
+            if config::auto_assign() && config::assignment_timeout() > 0 {
+                spawn_job(
+                    "stale assignment reassignment",
+                    Duration::from_secs(600),
+                    pool.clone(),
+                    reassign_stale_assignments,
+                );
+            }
+
+            spawn_video_checks(pool.clone());
+            spawn_list_updates(pool, rocket.state::<ResponseCache>().cloned());
+        })
+    })
+}
+
+fn spawn_job<F>(name: &'static str, period: Duration, pool: Pool<Postgres>, job: F)
+where
+    F: for<'c> Fn(&'c mut PgConnection) -> JobFuture<'c> + Send + Sync + 'static,
+{
+    rocket::tokio::spawn(async move {
+        let mut interval = rocket::tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = retry_on_conflict(&pool, 0, &job).await {
+                error!("Background job '{}' failed: {:?}", name, err);
+            }
+        }
+    });
+}
+
+/// Applies scheduled list updates as soon as they are due. Unlike other jobs, this one needs to
+/// purge cached responses after its transaction committed, so that the update goes live right away
+fn spawn_list_updates(pool: Pool<Postgres>, cache: Option<ResponseCache>) {
+    rocket::tokio::spawn(async move {
+        let mut interval = rocket::tokio::time::interval(Duration::from_secs(15));
+
+        loop {
+            interval.tick().await;
+
+            match retry_on_conflict(&pool, 0, apply_list_updates).await {
+                Ok(0) => (),
+                Ok(applied) => {
+                    info!("Applied {} scheduled list updates", applied);
+
+                    if let Some(ref cache) = cache {
+                        cache.purge_all();
+                    }
+                },
+                Err(err) => error!("Background job 'scheduled list updates' failed: {:?}", err),
+            }
+        }
+    });
+}
+
+/// How many record videos are checked per run of the video check job
+const VIDEO_CHECK_BATCH_SIZE: i64 = 100;
+
+/// Re-checks the videos of approved records once the configured
+/// [interval](config::video_recheck_interval) has passed since their last check
+fn spawn_video_checks(pool: Pool<Postgres>) {
+    let recheck_interval = config::video_recheck_interval();
+
+    if recheck_interval == 0 {
+        return;
+    }
+
+    rocket::tokio::spawn(async move {
+        let mut interval = rocket::tokio::time::interval(Duration::from_secs(600));
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build().unwrap();
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = check_videos(&pool, &client, recheck_interval).await {
+                error!("Background job 'video checks' failed: {:?}", err);
+            }
+        }
+    });
+}
+
+async fn check_videos(pool: &Pool<Postgres>, client: &reqwest::Client, recheck_interval: u64) -> Result<(), DemonlistError> {
+    let mut connection = pool.acquire().await?;
+    let due = due_video_checks(recheck_interval, VIDEO_CHECK_BATCH_SIZE, &mut *connection).await?;
+    let mut dead = 0;
+
+    for check in due {
+        // Inconclusive results are not recorded, so the video is checked again during the next run
+        let Some(status) = check_video(client, &check.video).await else {
+            continue;
+        };
+
+        if !status.available {
+            dead += 1;
+        }
+
+        record_video_check(check.record_id, status.available, &mut *connection).await?;
+
+        if let Some(channel) = status.channel {
+            fingerprint::set_channel(check.record_id, &channel, &mut *connection).await?;
+        }
+    }
+
+    if dead > 0 {
+        info!("Found {} records whose video is no longer available", dead);
+    }
+
+    Ok(())
+}
+
+struct VideoStatus {
+    available: bool,
+
+    /// URL of the channel that uploaded the video, if the host told us
+    channel: Option<String>,
+}
+
+/// Whether the given video can still be watched. `None` if that could not be determined, e.g.
+/// because the video host could not be reached or rate limited us.
+///
+/// YouTube and Vimeo respond to their oEmbed endpoints with an error for deleted and private
+/// videos, while the video pages themselves still load fine. For available videos, these responses
+/// also tell us the uploading channel. For any other host, we can only look at the response to
+/// requesting the video itself
+async fn check_video(client: &reqwest::Client, video: &str) -> Option<VideoStatus> {
+    let host = Url::parse(video).ok()?.host_str()?.to_string();
+
+    let (url, dead_statuses): (Url, &[StatusCode]) = match host.as_str() {
+        "www.youtube.com" => (
+            Url::parse_with_params("https://www.youtube.com/oembed", &[("format", "json"), ("url", video)]).ok()?,
+            &[
+                StatusCode::BAD_REQUEST,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::FORBIDDEN,
+                StatusCode::NOT_FOUND,
+            ],
+        ),
+        "vimeo.com" => (
+            Url::parse_with_params("https://vimeo.com/api/oembed.json", &[("url", video)]).ok()?,
+            &[StatusCode::FORBIDDEN, StatusCode::NOT_FOUND],
+        ),
+        _ => (Url::parse(video).ok()?, &[StatusCode::NOT_FOUND, StatusCode::GONE]),
+    };
+
+    match client.get(url).send().await {
+        Ok(response) if response.status().is_success() || response.status().is_redirection() => {
+            // Not an error if this fails, e.g. because the host does not support oEmbed and we got the video page instead
+            let channel = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|oembed| oembed["author_url"].as_str().map(ToString::to_string));
+
+            Some(VideoStatus { available: true, channel })
+        },
+        Ok(response) if dead_statuses.contains(&response.status()) => Some(VideoStatus {
+            available: false,
+            channel: None,
+        }),
+        Ok(response) => {
+            warn!(
+                "Checking availability of video {} yielded inconclusive response {:?}",
+                video, response
+            );
+
+            None
+        },
+        Err(err) => {
+            warn!("Failed to check availability of video {}: {:?}", video, err);
+
+            None
+        },
+    }
+}
+
+fn apply_list_updates(connection: &mut PgConnection) -> TransactionFuture<'_, usize, DemonlistError> {
+    Box::pin(list_update::apply_due(connection))
+}
+
+fn sync_score_decay(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(async move {
+        score::sync_decay(config::score_decay(), connection).await?;
+
+        Ok(())
+    })
+}
+
+fn aggregate_staff_activity(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(StaffActivity::aggregate(connection))
+}
+
+fn reopen_submissions(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(async move {
+        if SubmissionSettings::reopen_if_due(connection).await? {
+            info!("Reopened record submissions as scheduled");
+        }
+
+        Ok(())
+    })
+}
+
+fn purge_geo_data(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(async move {
+        let purged = Submitter::purge_geo_data(connection).await?;
+
+        if purged > 0 {
+            info!("Deleted expired geolocation data of {} submitters", purged);
+        }
+
+        Ok(())
+    })
+}
+
+fn take_score_snapshots(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(async move {
+        let players = score_history::take_snapshots(connection).await?;
+
+        if players > 0 {
+            info!("Took weekly score snapshot of {} players", players);
+        }
+
+        Ok(())
+    })
+}
+
+fn reassign_stale_assignments(connection: &mut PgConnection) -> JobFuture<'_> {
+    Box::pin(async move {
+        let reassigned = assignment::reassign_stale(config::assignment_timeout(), connection).await?;
+
+        if reassigned > 0 {
+            info!("Reassigned {} submissions that were not reviewed in time", reassigned);
+        }
+
+        Ok(())
+    })
+}
@@ -1,11 +1,10 @@
 use crate::components::{player_selection_dialog, player_selection_dropdown};
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core::permission::PermissionsManager;
+use pointercrate_core::{permission::PermissionsManager, pool::Reader};
 use pointercrate_core_pages::util::filtered_paginator;
 use pointercrate_demonlist::LIST_MODERATOR;
 use pointercrate_user::auth::AuthenticatedUser;
 use pointercrate_user_pages::account::AccountPageTab;
-use sqlx::PgConnection;
 
 pub struct DemonsTab;
 
@@ -33,7 +32,7 @@ impl AccountPageTab for DemonsTab {
         }
     }
 
-    async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, _connection: &mut PgConnection) -> Markup {
+    async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, _connection: &mut Reader<'_>) -> Markup {
         html! {
             div.left {
                 (demon_submitter())
@@ -1,6 +1,6 @@
 use log::error;
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core::{error::PointercrateError, permission::PermissionsManager};
+use pointercrate_core::{error::PointercrateError, permission::PermissionsManager, pool::Reader};
 use pointercrate_core_pages::{
     error::ErrorFragment,
     util::{filtered_paginator, paginator},
@@ -8,7 +8,6 @@ use pointercrate_core_pages::{
 use pointercrate_demonlist::player::claim::PlayerClaim;
 use pointercrate_user::{auth::AuthenticatedUser, MODERATOR};
 use pointercrate_user_pages::account::AccountPageTab;
-use sqlx::PgConnection;
 
 pub struct ListIntegrationTab(#[doc = "discord invite url"] pub &'static str);
 
@@ -36,8 +35,8 @@ impl AccountPageTab for ListIntegrationTab {
         }
     }
 
-    async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, connection: &mut PgConnection) -> Markup {
-        let player_claim = match PlayerClaim::by_user(user.user().id, connection).await {
+    async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, connection: &mut Reader<'_>) -> Markup {
+        let player_claim = match PlayerClaim::by_user(user.user().id.0, connection).await {
             Ok(player_claim) => player_claim,
             Err(err) => {
                 error!("Error retrieving player claim of user {}: {:?}", user.user(), err);
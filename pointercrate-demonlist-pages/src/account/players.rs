@@ -1,10 +1,9 @@
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core::{error::PointercrateError, permission::PermissionsManager};
+use pointercrate_core::{error::PointercrateError, permission::PermissionsManager, pool::Reader};
 use pointercrate_core_pages::{error::ErrorFragment, util::filtered_paginator};
 use pointercrate_demonlist::{nationality::Nationality, LIST_MODERATOR};
 use pointercrate_user::auth::AuthenticatedUser;
 use pointercrate_user_pages::account::AccountPageTab;
-use sqlx::PgConnection;
 
 pub struct PlayersPage;
 
@@ -32,7 +31,7 @@ impl AccountPageTab for PlayersPage {
         }
     }
 
-    async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, connection: &mut PgConnection) -> Markup {
+    async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, connection: &mut Reader<'_>) -> Markup {
         let nationalities = match Nationality::all(connection).await {
             Ok(nationalities) => nationalities,
             Err(err) => {
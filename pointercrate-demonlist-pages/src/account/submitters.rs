@@ -1,10 +1,9 @@
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core::permission::PermissionsManager;
+use pointercrate_core::{permission::PermissionsManager, pool::Reader};
 use pointercrate_core_pages::util::paginator;
-use pointercrate_demonlist::LIST_MODERATOR;
+use pointercrate_demonlist::{LIST_ADMINISTRATOR, LIST_MODERATOR};
 use pointercrate_user::auth::AuthenticatedUser;
 use pointercrate_user_pages::account::AccountPageTab;
-use sqlx::PgConnection;
 
 pub struct SubmittersPage;
 
@@ -32,7 +31,10 @@ impl AccountPageTab for SubmittersPage {
         }
     }
 
-    async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, _connection: &mut PgConnection) -> Markup {
+    async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, _connection: &mut Reader<'_>) -> Markup {
+        // Filtering records by submitter reveals their anonymized identifiers
+        let can_list_records = permissions.require_permission(user.user().permissions, LIST_ADMINISTRATOR).is_ok();
+
         html! {
             div.left {
                 div.panel.fade {
@@ -77,7 +79,9 @@ impl AccountPageTab for SubmittersPage {
                                         }
                                     }
                                 }
-                                span.button.blue.hover #submitter-list-records style = "margin: 15px auto 0px" {"Show records in record manager"};
+                                @if can_list_records {
+                                    span.button.blue.hover #submitter-list-records style = "margin: 15px auto 0px" {"Show records in record manager"};
+                                }
                             }
                         }
                     }
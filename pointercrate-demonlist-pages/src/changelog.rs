@@ -0,0 +1,77 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Utc};
+use maud::Markup;
+use pointercrate_core::config;
+use pointercrate_core_pages::{
+    template::{self, Context},
+    PageFragment,
+};
+use pointercrate_demonlist::changelog::{summary, Changelog, ChangelogEntry};
+use serde::Serialize;
+
+pub struct ChangelogPage {
+    pub changelog: Changelog,
+
+    /// Offset from UTC the bounds of the changelog's week are displayed in
+    pub utc_offset: FixedOffset,
+}
+
+impl From<ChangelogPage> for PageFragment {
+    fn from(page: ChangelogPage) -> Self {
+        let list_name = &config::theme().list_name;
+
+        PageFragment::new(
+            format!("Changelog {} - {}", page.changelog.week, list_name),
+            format!("All changes made to the {} during the week {}", list_name, page.changelog.week),
+        )
+        .body(page.body())
+    }
+}
+
+/// What the `demonlist/changelog/week.html` template gets to know about each change
+#[derive(Serialize)]
+struct RenderedEntry {
+    description: String,
+    link: Option<String>,
+}
+
+/// Formats the week starting `offset` weeks after the given changelog's week for use in URLs
+fn week_link(changelog: &Changelog, offset: i64) -> String {
+    let week = (changelog.start + Duration::weeks(offset)).date_naive().iso_week();
+
+    format!("/list/changelog/?week={}-W{:02}", week.year(), week.week())
+}
+
+impl ChangelogPage {
+    fn local_time(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.utc_offset).format("%B %-d, %Y %H:%M (UTC%:z)").to_string()
+    }
+
+    fn body(&self) -> Markup {
+        let changelog = &self.changelog;
+
+        let entries = changelog
+            .entries
+            .iter()
+            .map(|entry| RenderedEntry {
+                description: summary::describe(entry),
+                link: match entry {
+                    ChangelogEntry::Removed { .. } => None,
+                    ChangelogEntry::Added { demon } | ChangelogEntry::Moved { demon, .. } | ChangelogEntry::Shifted { demon, .. } => {
+                        Some(format!("/list/permalink/{}/", demon.id))
+                    },
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let mut context = Context::new();
+
+        context.insert("week", &changelog.week);
+        context.insert("start", &self.local_time(changelog.start));
+        context.insert("end", &self.local_time(changelog.end));
+        context.insert("entries", &entries);
+        context.insert("previous", &week_link(changelog, -1));
+        context.insert("next", &week_link(changelog, 1));
+
+        template::render("demonlist/changelog/week.html", &context)
+    }
+}
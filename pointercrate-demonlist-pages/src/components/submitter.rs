@@ -1,5 +1,6 @@
 use crate::components::{demon_dropdown, player_selection_dropdown};
 use maud::{html, Markup, Render};
+use pointercrate_core_pages::template::{self, Context};
 use pointercrate_demonlist::{config, demon::Demon};
 
 pub struct RecordSubmitter<'a> {
@@ -76,6 +77,16 @@ impl Render for RecordSubmitter<'_> {
                         input type = "url" name = "video" required = "" placeholder = "e.g. 'https://youtu.be/EUBtwD-e2R0'" ;
                         p.error {}
                     }
+                    h3 {
+                        "(Optional) Completion timestamp: "
+                    }
+                    p {
+                        "The time in the video at which the completion starts, so that the list team (and later everyone else) can jump right to it."
+                    }
+                    span.form-input.flex.col #id_video_timestamp {
+                        input type = "text" name = "video_timestamp" placeholder = "e.g. '1:23:45' or '12:34'" pattern = "[0-9]+(:[0-5]?[0-9]){0,2}";
+                        p.error {}
+                    }
                     h3 {
                         "(Optional) Unedited completion: "
                     }
@@ -100,6 +111,10 @@ impl Render for RecordSubmitter<'_> {
                         textarea name = "note" placeholder = "e.g. this level SUCKS and it should be removed I HATE THIS LEVEL" {}
                         p.error {}
                     }
+                    // Honeypot for spam bots, invisible to humans (see pointercrate_demonlist::record::spam)
+                    span.form-input #submit-website style = "position: absolute; left: -10000px" aria-hidden = "true" {
+                        input type = "text" name = "website" tabindex = "-1" autocomplete = "off";
+                    }
                     p {
                         "By submitting the record you acknowledge the " a.link href = "https://docs.google.com/document/d/1zW2tOWRi-qTxd2pM2FrParnVTzJjzRiGKIGGSJycKuI/edit?usp=sharing" {"submission guidelines"} "."
                     }
@@ -111,19 +126,5 @@ impl Render for RecordSubmitter<'_> {
 }
 
 pub(crate) fn submit_panel() -> Markup {
-    html! {
-        section #submit.panel.fade.js-scroll-anim data-anim = "fade" {
-            div.underlined {
-                h2 {
-                    "Submit Records"
-                }
-            }
-            p {
-                "Note: Please do not submit nonsense, it only makes it harder for us all and will get you banned. Also note that the form rejects duplicate submissions."
-            }
-            a.blue.hover.button.js-scroll data-destination = "submitter" data-reveal = "true" {
-                "Submit a record!"
-            }
-        }
-    }
+    template::render("demonlist/overview/submit.html", &Context::new())
 }
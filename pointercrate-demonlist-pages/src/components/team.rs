@@ -1,5 +1,7 @@
-use maud::{html, Markup, Render};
+use maud::{Markup, Render};
+use pointercrate_core_pages::template::{self, Context};
 use pointercrate_user::User;
+use serde::Serialize;
 
 pub struct Team {
     pub admins: Vec<User>,
@@ -7,44 +9,31 @@ pub struct Team {
     pub helpers: Vec<User>,
 }
 
+/// What the `demonlist/overview/team.html` template gets to know about each member of the list team
+#[derive(Serialize)]
+struct TeamMember<'a> {
+    name: &'a str,
+    youtube_channel: Option<&'a str>,
+}
+
+fn members(users: &[User]) -> Vec<TeamMember<'_>> {
+    users
+        .iter()
+        .map(|user| TeamMember {
+            name: user.name(),
+            youtube_channel: user.youtube_channel.as_deref(),
+        })
+        .collect()
+}
+
 impl Render for Team {
     fn render(&self) -> Markup {
-        let maybe_link = |user: &User| -> Markup {
-            html! {
-                li {
-                    @match user.youtube_channel {
-                        Some(ref channel) => a target = "_blank" href = (channel) {
-                            (user.name())
-                        },
-                        None => (user.name())
-                    }
-                }
-            }
-        };
+        let mut context = Context::new();
+
+        context.insert("admins", &members(&self.admins));
+        context.insert("moderators", &members(&self.moderators));
+        context.insert("helpers", &members(&self.helpers));
 
-        html! {
-            section.panel.fade.js-scroll-anim #editors data-anim = "fade" {
-                div.underlined {
-                    h2 {
-                        "List Team"
-                    }
-                }
-                ul style = "line-height: 30px" {
-                    @for admin in &self.admins {
-                        b {
-                            (maybe_link(admin))
-                        }
-                    }
-                    @for moderator in &self.moderators {
-                        b {
-                            (maybe_link(moderator))
-                        }
-                    }
-                    @for helper in &self.helpers {
-                        (maybe_link(helper))
-                    }
-                }
-            }
-        }
+        template::render("demonlist/overview/team.html", &context)
     }
 }
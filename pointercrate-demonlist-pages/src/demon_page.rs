@@ -5,30 +5,165 @@ use crate::{
     },
     statsviewer::stats_viewer_panel,
 };
-use chrono::NaiveDateTime;
+use chrono::{DateTime, FixedOffset};
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core_pages::{head::HeadLike, PageFragment};
+use pointercrate_core::config;
+use pointercrate_core_pages::{
+    head::HeadLike,
+    template::{self, Context},
+    PageFragment,
+};
 use pointercrate_demonlist::{
     config::{self as list_config, extended_list_size},
     demon::{Demon, FullDemon},
+    record::{ApprovedRecordSummary, APPROVED_RECORDS_PER_PAGE},
 };
 use pointercrate_integrate::gd::{DemonRating, IntegrationLevel, LevelRating, Thunk};
+use serde::Serialize;
 use url::Url;
 
 #[derive(Debug)]
 pub struct DemonMovement {
     pub from_position: i16,
-    pub at: NaiveDateTime,
+
+    /// When the movement happened, in the timezone of the user viewing the page
+    pub at: DateTime<FixedOffset>,
 }
 
 pub struct DemonPage {
     pub team: Team,
     pub demonlist: Vec<Demon>,
+
+    /// The demon shown on this page. Only contains the records on [`DemonPage::records_page`], the
+    /// remaining ones are loaded from `/api/v2/demons/<demon_id>/records/` as needed
     pub data: FullDemon,
+    pub records_page: i64,
+    pub record_summary: ApprovedRecordSummary,
     pub movements: Vec<DemonMovement>,
     pub integration: Option<IntegrationLevel>,
 }
 
+/// What the `demonlist/demon/demon.html` template gets to know about the demon
+#[derive(Serialize)]
+struct DemonPanel<'a> {
+    id: i32,
+    name: &'a str,
+    position: i16,
+    previous: Option<i16>,
+    next: Option<i16>,
+    headline: String,
+
+    /// Only set if the demon has more than three creators, in which case all of them are only
+    /// listed in a tooltip
+    first_creator: Option<String>,
+    all_creators: Option<String>,
+    short_headline: Option<String>,
+
+    description: Option<String>,
+    video: Option<String>,
+    discussion_url: Option<&'a str>,
+    enjoyment: Option<String>,
+    points: String,
+    level_id: u64,
+}
+
+/// What the `demonlist/demon/demon.html` template gets to know about the demon's level on the
+/// Geometry Dash servers
+#[derive(Serialize)]
+struct LevelInfo {
+    password: String,
+    level_id: String,
+    length: String,
+    objects: String,
+    difficulty: &'static str,
+    gd_version: String,
+    song: Option<Song>,
+}
+
+#[derive(Serialize)]
+struct Song {
+    name: String,
+    artist: String,
+    id: String,
+    link: Option<String>,
+}
+
+impl From<&IntegrationLevel> for LevelInfo {
+    fn from(level: &IntegrationLevel) -> Self {
+        let (length, objects) = match level.level_data.level_data {
+            Thunk::Processed(ref objects) => {
+                let length_in_seconds = objects.length_in_seconds() as i32;
+
+                (
+                    format!("{}m:{:02}s", length_in_seconds / 60, length_in_seconds % 60),
+                    objects.objects.len().to_string(),
+                )
+            },
+            _ => ("unreachable!()".to_string(), "unreachable!()".to_string()),
+        };
+
+        LevelInfo {
+            password: level
+                .level_data
+                .password
+                .as_processed()
+                .map(|pw| pw.to_string())
+                .unwrap_or("Unknown".to_string()),
+            level_id: level.level_id.to_string(),
+            length,
+            objects,
+            difficulty: match level.difficulty {
+                LevelRating::NotAvailable => "Unrated",
+                LevelRating::Demon(demon_rating) => match demon_rating {
+                    DemonRating::Easy => "Easy Demon",
+                    DemonRating::Medium => "Medium Demon",
+                    DemonRating::Hard => "Hard Demon",
+                    DemonRating::Insane => "Insane Demon",
+                    DemonRating::Extreme => "Extreme Demon",
+                    _ => "???",
+                },
+                _ => "Level not rated demon, list mods fucked up",
+            },
+            gd_version: level.gd_version.to_string(),
+            song: level.custom_song.as_ref().map(|song| Song {
+                name: song.name.to_string(),
+                artist: song.artist.to_string(),
+                id: song.song_id.to_string(),
+                link: match song.link {
+                    Thunk::Processed(ref link) => Some(link.to_string()),
+                    _ => None,
+                },
+            }),
+        }
+    }
+}
+
+/// What the `demonlist/demon/records.html` template gets to know about the demon
+#[derive(Serialize)]
+struct RecordsPanel<'a> {
+    id: i32,
+    position: i16,
+    submissions_open: bool,
+    submissions_closed_reason: Option<&'a str>,
+}
+
+/// What the `demonlist/demon/records.html` template gets to know about each record
+#[derive(Serialize)]
+struct RecordRow<'a> {
+    progress: i16,
+    player: &'a str,
+    video: Option<String>,
+    video_host: Option<String>,
+    enjoyment: Option<String>,
+    nationality: Option<Flag<'a>>,
+}
+
+#[derive(Serialize)]
+struct Flag<'a> {
+    code: String,
+    name: &'a str,
+}
+
 impl From<DemonPage> for PageFragment {
     fn from(page: DemonPage) -> Self {
         PageFragment::new(page.title(), page.description())
@@ -47,8 +182,9 @@ impl From<DemonPage> for PageFragment {
 impl DemonPage {
     fn title(&self) -> String {
         let mut title = format!(
-            "{} - Clicksync Challenge List",
-            self.data.demon.base.name // FIXME: flatten the structs, holy shit
+            "{} - {}",
+            self.data.demon.base.name, // FIXME: flatten the structs, holy shit
+            config::theme().list_name
         );
 
         if self.data.demon.base.position <= extended_list_size() {
@@ -126,9 +262,9 @@ impl DemonPage {
 
         for movement in &self.movements {
             let would_be_label = if year_only {
-                movement.at.date().format("%Y").to_string()
+                movement.at.date_naive().format("%Y").to_string()
             } else {
-                movement.at.date().format("%b %y").to_string()
+                movement.at.date_naive().format("%b %y").to_string()
             };
 
             match last_label {
@@ -151,34 +287,7 @@ impl DemonPage {
                 main.left {
                     (RecordSubmitter::new(false, &self.demonlist))
                     (self.demon_panel())
-                    div.panel.fade.js-scroll-anim.js-collapse data-anim = "fade" {
-                        h2.underlined.pad {
-                            "Position History"
-                            span.arrow.hover #history-trigger {}
-                        }
-                        div.js-collapse-content style="display:none"  {
-                            div.ct-chart.ct-perfect-fourth #position-chart style="display:none"{}
-
-                            table #history-table{
-                                tbody #history-table-body {
-                                    tr {
-                                        th.blue {
-                                            "Date"
-                                        }
-                                        th.blue {
-                                            "Change"
-                                        }
-                                        th.blue {
-                                            "New Position"
-                                        }
-                                        th.blue {
-                                            "Reason"
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                    (template::render("demonlist/demon/history.html", &Context::new()))
                     (self.records_panel())
                     (PreEscaped(format!("
                         <script>
@@ -201,308 +310,106 @@ impl DemonPage {
     }
 
     fn demon_panel(&self) -> Markup {
-        let position = self.data.demon.base.position;
-        let name = &self.data.demon.base.name;
-
-        let score100 = self.data.demon.score(100);
-
-        let mut total_enjoyment: i32 = 0;
-        let mut with_enjoyment = 0;
-
-        for record in &self.data.records {
-            if let Some(ref enjoyment) = record.enjoyment {
-                total_enjoyment += *enjoyment;
-                with_enjoyment += 1;
-            }
-        }
-
-        let avg_enjoyment: f32 = total_enjoyment as f32 / with_enjoyment as f32;
-
-        html! {
-            section.panel.fade.js-scroll-anim data-anim = "fade" {
-                div.underlined {
-                    h1 #demon-heading style = "overflow: hidden;cursor: pointer;" aria-label = "Copy to clipboard" {
-                        @if self.data.demon.base.position != 1 {
-                            a href=(format!("/list/{:?}", self.data.demon.base.position - 1)) {
-                                i class="fa fa-chevron-left" style="padding-right: 5%" {}
-                            }
-                        }
-                        (name)
-                        @if position as usize != self.demonlist.len() {
-                            a href=(format!("/list/{:?}", position + 1)) {
-                                i class="fa fa-chevron-right" style="padding-left: 5%" {}
-                            }
-                        }
-                    }
-                    (PreEscaped(format!(r#"
-                    <script>
-                    document.getElementById("demon-heading").addEventListener('click', () => navigator.clipboard.writeText('https://cscl.shuttleapp.rs/list/permalink/{}/?redirect'))
-                    </script>
-                    "#, self.data.demon.base.id)))
-                    h3 {
-                        @if self.data.creators.len() > 3 {
-                            "by " (self.data.creators[0].name) " and "
-                            div.tooltip {
-                                "more"
-                                div.tooltiptext.fade {
-                                    (self.data.creators.iter().map(|player| player.name.to_string()).collect::<Vec<_>>().join(", "))
-                                }
-                            }
-                            ", " (self.data.short_headline())
-                        }
-                        @else {
-                            (self.data.headline())
-                        }
-                    }
-                }
-                @if let Some(ref level) = self.integration {
-                    @if let Some(Thunk::Processed(ref description)) = level.description {
-                        div.underlined.pad {
-                            q {
-                                (description)
-                            }
-                        }
-                    }
-                }
-                @if let Some(ref video) = self.data.demon.video {
-                    @if let Some(embedded_video) = embed(video) {
-                        iframe."ratio-16-9"."js-delay-attr" style="width:90%; margin: 15px 5%" allowfullscreen="" data-attr = "src" data-attr-value = (embedded_video) {"Verification Video"}
-                    }
-                }
-                div.underlined.pad.flex.wrap #level-info {
-                    @if let Some(ref level) = self.integration {
-                        span {
-                            b {
-                                "Level Password: "
-                            }
-                            br;
-                            (level.level_data.password.as_processed().map(|pw| pw.to_string()).unwrap_or("Unknown".to_string()))
-                        }
-                        span {
-                            b {
-                                "Level ID: "
-                            }
-                            br;
-                            (level.level_id)
-                        }
-                        span {
-                            b {
-                                "Level length: "
-                            }
-                            br;
-                            @match level.level_data.level_data {
-                                Thunk::Processed(ref objects) => {
-                                    @let length_in_seconds = objects.length_in_seconds();
-
-                                    (format!("{}m:{:02}s", (length_in_seconds as i32)/ 60, (length_in_seconds as i32) % 60))
-                                }
-                                _ => "unreachable!()"
-                            }
-                        }
-                        span {
-                            b {
-                                "Object count: "
-                            }
-                            br;
-                            @match level.level_data.level_data {
-                                Thunk::Processed(ref objects) => (objects.objects.len()),
-                                _ => "unreachable!()"
-                            }
-                        }
-                        span {
-                            b {
-                                "In-Game Difficulty: "
-                            }
-                            br;
-                            @match level.difficulty {
-                                LevelRating::NotAvailable => "Unrated",
-                                LevelRating::Demon(demon_rating) => @match demon_rating {
-                                    DemonRating::Easy => "Easy Demon",
-                                    DemonRating::Medium => "Medium Demon",
-                                    DemonRating::Hard => "Hard Demon",
-                                    DemonRating::Insane => "Insane Demon",
-                                    DemonRating::Extreme => "Extreme Demon",
-                                    _ => "???"
-                                },
-                                _ => "Level not rated demon, list mods fucked up"
-                            }
-                        }
-                        span {
-                            b {
-                                "Created in:"
-                            }
-                            br;
-                            (level.gd_version)
-                        }
-                        @if let Some(ref song) = level.custom_song {
-                            span style = "width: 100%"{
-                                b {
-                                    "Newgrounds Song:"
-                                }
-                                br;
-                                @match song.link {
-                                    Thunk::Processed(ref link) => a.link href = (link) {(song.name) " by " (song.artist) " (ID " (song.song_id) ")"},
-                                    _ => "unreachable!()"
-                                }
-                            }
-                        }
-                    }
-
-                    span {
-                        b {
-                            "Points: "
-                        }
-                        br;
-                        (format!("{:.2}", score100))
-                    }
-
-                    span {
-                        b {
-                            "Enjoyment: "
-                        }
-                        br;
-                        @if avg_enjoyment > 0.0 {
-                            (format!("{:?}/10", avg_enjoyment))
-
-                        }
-                        @else {
-                            "N/A"
-                        }
-                    }
-
-                    @if self.data.demon.level_id.unwrap_or_default() == 0 {
-                        span {
-                            b {
-                                "Level ID:"
-                            }
-                            br;
-                            p {
-                                "Missing, please contact staff"
-                            }
-                        }
-                    }
-
-                    // Sync Fantasy
-
-                    @else if self.data.demon.level_id.unwrap_or_default() == 105823909 {
-                        span {
-                            b {
-                                "GDShare File:"
-                            }
-                            br;
-                            a href = {"https://drive.google.com/file/d/1ljuoN_Cs3Ommdh1OL9O0tS_lZmxVBxQg/view"} {"Download"}
-                            }
-                        }
-
-                    // X
-
-                    @else if self.data.demon.level_id.unwrap_or_default() == 106039651 {
-                        span {
-                            b {
-                                "GDShare File:"
-                            }
-                            br;
-                            a href = {"https://drive.google.com/file/d/1ByRGNWc3EGd-OcOBFE62g82Vo_vfPTfZ/view"} {"Download"}
-                            }
-                        }
-
-                    @else {
-                        span {
-                            b {
-                                "Level ID:"
-                            }
-                            br;
-                            (format!("{:?}", self.data.demon.level_id.unwrap_or_default()))
-                        }
-                    }
-
-                }
-            }
-        }
+        let demon = &self.data.demon;
+        let position = demon.base.position;
+        let avg_enjoyment = self.record_summary.average_enjoyment.unwrap_or_default() as f32;
+
+        let (first_creator, all_creators, short_headline) = if self.data.creators.len() > 3 {
+            (
+                Some(self.data.creators[0].name.to_string()),
+                Some(
+                    self.data
+                        .creators
+                        .iter()
+                        .map(|player| player.name.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                Some(self.data.short_headline()),
+            )
+        } else {
+            (None, None, None)
+        };
+
+        let description = match self.integration {
+            Some(ref level) => match level.description {
+                Some(Thunk::Processed(ref description)) => Some(description.to_string()),
+                _ => None,
+            },
+            None => None,
+        };
+
+        let panel = DemonPanel {
+            id: demon.base.id.0,
+            name: &demon.base.name,
+            position,
+            previous: (position != 1).then_some(position - 1),
+            next: (position as usize != self.demonlist.len()).then_some(position + 1),
+            headline: self.data.headline(),
+            first_creator,
+            all_creators,
+            short_headline,
+            description,
+            video: demon.video.as_deref().and_then(embed),
+            discussion_url: demon.discussion_url.as_deref(),
+            enjoyment: (avg_enjoyment > 0.0).then(|| format!("{:?}", avg_enjoyment)),
+            points: format!("{:.2}", demon.score(100)),
+            level_id: demon.level_id.unwrap_or_default(),
+        };
+
+        let mut context = Context::new();
+
+        context.insert("demon", &panel);
+        context.insert("level", &self.integration.as_ref().map(LevelInfo::from));
+
+        template::render("demonlist/demon/demon.html", &context)
     }
 
     fn records_panel(&self) -> Markup {
-        let position = self.data.demon.base.position;
-        let _name = &self.data.demon.base.name;
-
-        html! {
-            @if !self.data.records.is_empty() || position <= list_config::extended_list_size() {
-                section.records.panel.fade.js-scroll-anim data-anim = "fade" {
-                    div.underlined.pad {
-                        h2 {
-                            "Records"
-                        }
-
-
-                        @if !self.data.records.is_empty() {
-                            h4 {
-                                (self.data.records.len())
-                                " records registered."
-                            }
-                        }
-                    }
-                    @if self.data.records.is_empty() {
-                        h3 {
-                            @if position > list_config::extended_list_size() {
-                                "No records!"
-                            }
-                            @else {
-                                "No records yet! Be the first to achieve one!"
-                            }
-                        }
-                    }
-                    @else {
-                        table {
-                            tbody {
-                                tr {
-                                    th.blue {}
-                                    th.blue {
-                                        "Record Holder"
-                                    }
-                                    th.blue {
-                                        "Enjoyment"
-                                    }
-                                    th.video-link.blue {
-                                        "Video Proof"
-                                    }
-                                }
-                                @for record in &self.data.records {
-                                    tr style = { @if record.progress == 100 {"font-weight: bold"} @else {""} } {
-                                        td {
-                                            @if let Some(ref nationality) = record.nationality {
-                                                span.flag-icon style={"background-image: url(/static/demonlist/images/flags/" (nationality.iso_country_code.to_lowercase()) ".svg)"} title = (nationality.nation) {}
-                                            }
-                                        }
-                                        td {
-                                            @if let Some(ref video) = record.video {
-                                                 a href = (video) target = "_blank"{
-                                                    (record.player.name)
-                                                 }
-                                            }
-                                            @else {
-                                                (record.player.name)
-                                            }
-                                        }
-                                        td {
-                                            @if let Some(ref enjoyment) = record.enjoyment {
-                                                (format!("{}/10", enjoyment))
-                                            }
-                                        }
-                                        td.video-link {
-                                            @if let Some(ref video) = record.video {
-                                                 a.link href = (video) target = "_blank"{
-                                                     (host(video))
-                                                 }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        let demon = &self.data.demon;
+
+        let record_count = self.record_summary.count;
+        let page_count = (record_count + APPROVED_RECORDS_PER_PAGE - 1) / APPROVED_RECORDS_PER_PAGE;
+
+        let records = self
+            .data
+            .records
+            .iter()
+            .map(|record| {
+                let video = record.timestamped_video();
+
+                RecordRow {
+                    progress: record.progress,
+                    player: &record.player.name,
+                    video_host: video.as_deref().map(|video| host(video).to_string()),
+                    video,
+                    enjoyment: record.enjoyment.as_ref().map(|enjoyment| enjoyment.to_string()),
+                    nationality: record.nationality.as_ref().map(|nationality| Flag {
+                        code: nationality.iso_country_code.to_lowercase(),
+                        name: &nationality.nation,
+                    }),
                 }
-            }
-        }
+            })
+            .collect::<Vec<_>>();
+
+        let mut context = Context::new();
+
+        context.insert(
+            "demon",
+            &RecordsPanel {
+                id: demon.base.id.0,
+                position: demon.base.position,
+                submissions_open: demon.submissions_open,
+                submissions_closed_reason: demon.submissions_closed_reason.as_deref(),
+            },
+        );
+        context.insert("extended_list_size", &list_config::extended_list_size());
+        context.insert("count", &record_count);
+        context.insert("records", &records);
+        context.insert("page", &self.records_page);
+        context.insert("pages", &page_count);
+
+        template::render("demonlist/demon/records.html", &context)
     }
 }
 
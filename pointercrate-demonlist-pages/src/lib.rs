@@ -1,12 +1,15 @@
 use maud::{html, Markup};
 
+use pointercrate_core_pages::template::{self, Context};
 use pointercrate_demonlist::{config, demon::Demon};
 
 pub mod account;
+pub mod changelog;
 pub mod components;
 pub mod demon_page;
 pub mod overview;
 pub mod statsviewer;
+pub mod widget;
 
 struct ListSection {
     name: &'static str,
@@ -126,33 +129,9 @@ fn dropdown(section: &ListSection, demons: &[&Demon], current: Option<&Demon>) -
 }
 
 fn rules_panel() -> Markup {
-    html! {
-        section #rules.panel.fade.js-scroll-anim data-anim = "fade" {
-            h2.underlined.pad.clickable {
-                "Guidelines"
-            }
-            p {
-                "Read this before submitting a challenge or record to ensure a flawless experience."
-            }
-            a.blue.hover.button href = "https://docs.google.com/document/d/1zW2tOWRi-qTxd2pM2FrParnVTzJjzRiGKIGGSJycKuI/edit?usp=sharing" {
-                "Read the guidelines!"
-            }
-        }
-    }
+    template::render("demonlist/overview/rules.html", &Context::new())
 }
 
 fn nongs_panel() -> Markup {
-    html! {
-        section #rules.panel.fade.js-scroll-anim data-anim = "fade" {
-            h2.underlined.pad.clickable {
-                "Nongs"
-            }
-            p {
-                "Some challenges have songs that aren't on " a.link href = {"https://www.newgrounds.com"} {"Newgrounds"} ", so you can find all NONG songs in this Google Drive folder."
-            }
-            a.blue.hover.button href = "https://drive.google.com/drive/folders/1_P5D7jKT8oUcjk_vzWt5riqouOnnwRqB?usp=sharing" {
-                "Find a nong!"
-            }
-        }
-    }
+    template::render("demonlist/overview/nongs.html", &Context::new())
 }
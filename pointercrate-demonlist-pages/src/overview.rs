@@ -7,6 +7,7 @@ use crate::{
     statsviewer::stats_viewer_panel,
 };
 use maud::{html, Markup, PreEscaped};
+use pointercrate_core::config;
 use pointercrate_core_pages::{head::HeadLike, PageFragment};
 use pointercrate_demonlist::{
     config as list_config,
@@ -60,7 +61,9 @@ fn demon_panel(demon: &Demon, current_position: Option<i16>) -> Markup {
 
 impl From<OverviewPage> for PageFragment {
     fn from(page: OverviewPage) -> Self {
-        PageFragment::new("The CSCL", "The Clicksync Challenge List!")
+        let list_name = &config::theme().list_name;
+
+        PageFragment::new(format!("The {}", list_name), format!("The {}!", list_name))
             .module("/static/core/js/modules/form.js")
             .module("/static/demonlist/js/modules/demonlist.js")
             .module("/static/demonlist/js/demonlist.js")
@@ -74,38 +77,38 @@ impl From<OverviewPage> for PageFragment {
 impl OverviewPage {
     fn head(&self) -> Markup {
         html! {
-            (PreEscaped(r#"
+            (PreEscaped(format!(r#"
                 <script type="application/ld+json">
-                {
+                {{
                     "@context": "http://schema.org",
                     "@type": "WebPage",
-                    "breadcrumb": {
+                    "breadcrumb": {{
                         "@type": "BreadcrumbList",
                         "itemListElement": [
-                            {
+                            {{
                                 "@type": "ListItem",
                                 "position": 1,
-                                "item": {
+                                "item": {{
                                     "@id": "https://cscl.shuttleapp.rs/",
                                     "name": "cscl"
-                                }
-                            },
-                            {
+                                }}
+                            }},
+                            {{
                                 "@type": "ListItem",
                                 "position": 2,
-                                "item": {
+                                "item": {{
                                     "@id": "https://cscl.shuttleapp.rs/list/",
                                     "name": "list"
-                                }
-                            }
+                                }}
+                            }}
                         ]
-                    },
-                    "name": "The CSCL",
-                    "description": "The Clicksync Challenge List!",
+                    }},
+                    "name": "The {0}",
+                    "description": "The {0}!",
                     "url": "https://cscl.shuttleapp.rs/list/"
-                }
+                }}
                 </script>
-            "#))
+            "#, config::theme().list_name)))
             (PreEscaped(format!("
                 <script>
                     window.list_length = {0};
@@ -114,6 +117,7 @@ impl OverviewPage {
             ))
             // FIXME: abstract away
             link ref = "canonical" href = "https://cscl.shuttleapp.rs/list/";
+            link rel = "alternate" type = "application/json+oembed" href = "/api/v1/widgets/oembed?url=/list/";
         }
     }
 
@@ -1,26 +1,15 @@
 use maud::{html, Markup, PreEscaped};
-use pointercrate_core_pages::util::{dropdown, filtered_paginator, simple_dropdown};
+use pointercrate_core_pages::{
+    template::{self, Context},
+    util::{dropdown, filtered_paginator, simple_dropdown},
+};
 use pointercrate_demonlist::nationality::Nationality;
 
 pub mod individual;
 pub mod national;
 
 pub(crate) fn stats_viewer_panel() -> Markup {
-    html! {
-        section #stats.panel.fade.js-scroll-anim data-anim = "fade" {
-            div.underlined {
-                h2 {
-                    "Stats Viewer"
-                }
-            }
-            p {
-                "Get a detailed overview of who completed the most, created the most challenges or beat the hardest challenges! There is even a leaderboard to compare yourself to the very best!"
-            }
-            a.blue.hover.button #show-stats-viewer href = "/list/statsviewer/ "{
-                "Open the stats viewer!"
-            }
-        }
-    }
+    template::render("demonlist/overview/stats_viewer.html", &Context::new())
 }
 
 fn continent_panel() -> Markup {
@@ -38,21 +27,7 @@ fn continent_panel() -> Markup {
 }
 
 fn hide_subdivision_panel() -> Markup {
-    html! {
-        section.panel.fade {
-            h3.underlined {
-                "Show subdivisions"
-            }
-            p {
-                "Whether the map should display political subdivisions"
-            }
-            div.cb-container.flex.no-stretch style="margin-bottom:10px" {
-                i {"Show political subdivisions"}
-                input #show-subdivisions-checkbox type = "checkbox" checked="";
-                span.checkmark {}
-            }
-        }
-    }
+    template::render("demonlist/statsviewer/subdivisions.html", &Context::new())
 }
 
 struct StatsViewerRow(Vec<(&'static str, &'static str)>);
@@ -0,0 +1,105 @@
+//! Small, self-contained cards meant to be embedded into other websites (via an `iframe`, or
+//! through the oEmbed endpoint), showing the top of the list or the most recently approved records
+//!
+//! Unlike regular pages, widgets do not load any of the site's scripts or stylesheets, as they end
+//! up on pages we know nothing about. All links open in a new tab.
+
+use maud::{html, Markup, PreEscaped, Render, DOCTYPE};
+use pointercrate_core::config::{self, ThemeConfig};
+use pointercrate_core_pages::theme::{DEFAULT_ACCENT_COLOR, DEFAULT_PRIMARY_COLOR};
+use pointercrate_demonlist::{demon::Demon, record::RecentRecord};
+
+/// The default width (in pixels) of embedded widgets
+pub const WIDGET_WIDTH: u32 = 400;
+
+/// The height (in pixels) of a single entry of a widget. Together with [`WIDGET_BASE_HEIGHT`], this
+/// gives the height an `iframe` needs to show all entries without scrolling
+pub const WIDGET_ENTRY_HEIGHT: u32 = 52;
+
+/// The height (in pixels) of a widget's heading and footer
+pub const WIDGET_BASE_HEIGHT: u32 = 80;
+
+pub struct TopDemonsWidget {
+    pub demons: Vec<Demon>,
+}
+
+pub struct LatestRecordsWidget {
+    pub records: Vec<RecentRecord>,
+}
+
+fn widget_document(theme: &ThemeConfig, title: &str, oembed_url: &str, entries: Markup) -> Markup {
+    let primary_color = theme.primary_color.as_deref().unwrap_or(DEFAULT_PRIMARY_COLOR);
+    let accent_color = theme.accent_color.as_deref().unwrap_or(DEFAULT_ACCENT_COLOR);
+
+    html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { (title) " - " (theme.list_name) }
+                link rel="alternate" type="application/json+oembed" href=(format!("/api/v1/widgets/oembed?url={}", oembed_url));
+                style {
+                    (PreEscaped(format!(
+                        "body {{ margin: 0; font-family: Montserrat, sans-serif; font-size: 14px; color: #333; background: white; }}
+                         h1 {{ margin: 0; padding: 8px 12px; font-size: 16px; color: white; background: {0}; }}
+                         ol {{ margin: 0; padding: 0; list-style: none; }}
+                         li {{ padding: 8px 12px; border-bottom: 1px solid #eee; }}
+                         li small {{ display: block; color: #888; }}
+                         a {{ color: inherit; text-decoration: none; }}
+                         a:hover {{ text-decoration: underline; }}
+                         footer {{ padding: 6px 12px; font-size: 12px; text-align: right; }}
+                         footer a {{ color: {1}; }}",
+                        primary_color, accent_color
+                    )))
+                }
+            }
+            body {
+                h1 { (title) }
+                ol {
+                    (entries)
+                }
+                footer {
+                    a href="/list/" target="_blank" { (theme.list_name) }
+                }
+            }
+        }
+    }
+}
+
+impl Render for TopDemonsWidget {
+    fn render(&self) -> Markup {
+        let entries = html! {
+            @for demon in &self.demons {
+                li {
+                    a href = {"/list/permalink/" (demon.base.id) "/"} target="_blank" {
+                        b { "#" (demon.base.position) " - " (demon.base.name) }
+                        small { "published by " (demon.publisher.name) }
+                    }
+                }
+            }
+        };
+
+        widget_document(config::theme(), &format!("Top {}", self.demons.len()), "/widgets/top", entries)
+    }
+}
+
+impl Render for LatestRecordsWidget {
+    fn render(&self) -> Markup {
+        let entries = html! {
+            @for record in &self.records {
+                li {
+                    a href = {"/list/permalink/" (record.demon.id) "/"} target="_blank" {
+                        b { (record.player.name) }
+                        " - " (record.progress) "% on " (record.demon.name)
+                        @if let Some(approved_at) = record.approved_at {
+                            small { "approved " (approved_at.format("%Y-%m-%d %H:%M UTC")) }
+                        }
+                    }
+                }
+            }
+        };
+
+        widget_document(config::theme(), "Latest records", "/widgets/records", entries)
+    }
+}
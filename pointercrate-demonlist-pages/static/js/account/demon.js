@@ -203,10 +203,11 @@ export class DemonManager extends FilteredPaginator {
 }
 
 function deleteDemon(demon_id) {
-  
     if (confirm("Are you sure? This will irrevocably delete this level and all its records!")) {
       demonManager.output.setSuccess("Deleting, please wait...");
-      del("/api/v2/demons/" + demon_id + "/")
+      del("/api/v2/demons/" + demon_id + "/", {
+        "If-Match": demonManager.currentEtag,
+      })
         .then(() => {
           demonManager.output.setSuccess("This demon has been deleted.");
           demonManager.refresh(); 
@@ -25,6 +25,8 @@ import {
   initializeRecordSubmitter,
   generateRecord,
   embedVideo,
+  timestampedVideo,
+  formatTimestamp,
 } from "/static/demonlist/js/modules/demonlist.js";
 
 export let recordManager;
@@ -43,12 +45,15 @@ class RecordManager extends Paginator {
     this._video = document.getElementById("record-video");
     this._video_link = document.getElementById("record-video-link");
     this._raw_footage_link = document.getElementById("record-raw-footage-link");
+    this._timestamp_link = document.getElementById("record-timestamp-link");
     this._enjoyment = document.getElementById("record-enjoyment");
     this._id = document.getElementById("record-id");
     this._demon = document.getElementById("record-demon");
     this._holder = document.getElementById("record-holder");
     this._submitter = document.getElementById("record-submitter");
     this._notes = document.getElementById("record-notes");
+    this._spamContainer = document.getElementById("record-spam-container");
+    this._spamScore = document.getElementById("record-spam-score");
 
     this.dropdown = new Dropdown(
       document
@@ -179,6 +184,14 @@ class RecordManager extends Paginator {
       this._raw_footage_link.style.display = "none";
     }
     
+    if (this.currentObject.video && this.currentObject.video_timestamp !== null && this.currentObject.video_timestamp !== undefined) {
+      this._timestamp_link.href = timestampedVideo(this.currentObject.video, this.currentObject.video_timestamp);
+      this._timestamp_link.textContent = formatTimestamp(this.currentObject.video_timestamp);
+      this._timestamp_link.style.display = "initial";
+    } else {
+      this._timestamp_link.style.display = "none";
+    }
+
     this._id.innerHTML = this.currentObject.id;
     this._demon.innerHTML =
       this.currentObject.demon.name + " (" + this.currentObject.demon.id + ")";
@@ -188,9 +201,22 @@ class RecordManager extends Paginator {
       this.currentObject.player.id +
       ")";
     this._status.selectSilently(this.currentObject.status);
-    this._submitter.innerHTML = this.currentObject.submitter.id;
+    // Only visible to list administrators
+    this._submitter.innerHTML =
+      this.currentObject.submitter == null
+        ? "-"
+        : this.currentObject.submitter.id;
     this._enjoyment.innerHTML = this.currentObject.enjoyment;
 
+    let spam = this.currentObject.spam;
+
+    if (spam !== undefined && spam.score > 0) {
+      this._spamScore.textContent = spam.score + " (" + spam.reasons.join("; ") + ")";
+      this._spamContainer.style.display = "flex";
+    } else {
+      this._spamContainer.style.display = "none";
+    }
+
     // this is introducing race conditions. Oh well.
     return get("/api/v1/records/" + this.currentObject.id + "/notes").then(response => {
       // clear notes
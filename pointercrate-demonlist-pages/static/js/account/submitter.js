@@ -82,24 +82,27 @@ export function initialize(tabber) {
   submitterManager = new SubmitterManager();
   submitterManager.initialize();
 
-  document
-    .getElementById("submitter-list-records")
-    .addEventListener("click", () => {
-      if (recordManager == null) {
-        // Prevent race conditions between initialization request and the request caused by 'updateQueryData'
-        initRecords().then(() => {
-          recordManager.updateQueryData(
-            "submitter",
-            submitterManager.currentObject.id
-          );
-          tabber.selectPane("3");
-        });
-      } else {
+  let listRecords = document.getElementById("submitter-list-records");
+
+  // Only list administrators can filter records by submitter
+  if (listRecords == null) return;
+
+  listRecords.addEventListener("click", () => {
+    if (recordManager == null) {
+      // Prevent race conditions between initialization request and the request caused by 'updateQueryData'
+      initRecords().then(() => {
         recordManager.updateQueryData(
           "submitter",
           submitterManager.currentObject.id
         );
         tabber.selectPane("3");
-      }
-    });
+      });
+    } else {
+      recordManager.updateQueryData(
+        "submitter",
+        submitterManager.currentObject.id
+      );
+      tabber.selectPane("3");
+    }
+  });
 }
@@ -7,12 +7,96 @@ $(document).ready(function () {
   if(window.demon_id) {
     initializePositionChart();
     initializeHistoryTable();
+    initializeRecordsTable();
   }
 
   initializeRecordSubmitter();
   initializeTimeMachine();
 });
 
+const VIDEO_HOSTS = {
+  "www.youtube.com": "YouTube",
+  "www.twitch.tv": "Twitch",
+  "everyplay.com": "Everyplay",
+  "www.bilibili.com": "Bilibili",
+  "vimeo.com": "Vimeo",
+};
+
+function initializeRecordsTable() {
+  let table = document.getElementById("demon-records");
+  let more = document.getElementById("demon-records-next");
+
+  if (!table || !more) return;
+
+  let page = parseInt(table.dataset.page);
+  let pages = parseInt(table.dataset.pages);
+  let tableBody = table.getElementsByTagName("tbody")[0];
+
+  // Without javascript, the link navigates to the next page of the demon page instead
+  more.addEventListener("click", event => {
+    event.preventDefault();
+
+    get("/api/v2/demons/" + table.dataset.demonId + "/records/?page=" + (page + 1)).then(response => {
+      for (const record of response.data) {
+        tableBody.appendChild(createRecordRow(record));
+      }
+
+      page += 1;
+
+      if (page >= pages) {
+        more.style.display = "none";
+      }
+    });
+  });
+}
+
+function createRecordRow(record) {
+  let row = document.createElement("tr");
+  let cells = [1, 2, 3, 4].map(() => document.createElement("td"));
+  let video = record.video;
+
+  // Only YouTube timestamps are handled here, other hosts just link to the start of the video
+  if (video && record.video_timestamp !== null && new URL(video).hostname === "www.youtube.com") {
+    let url = new URL(video);
+    url.searchParams.set("t", record.video_timestamp + "s");
+    video = url.toString();
+  }
+
+  if (record.progress === 100) row.style.fontWeight = "bold";
+
+  if (record.nationality) {
+    let flag = document.createElement("span");
+    flag.classList.add("flag-icon");
+    flag.style.backgroundImage = "url(/static/demonlist/images/flags/" + record.nationality.country_code.toLowerCase() + ".svg)";
+    flag.title = record.nationality.nation;
+    cells[0].appendChild(flag);
+  }
+
+  if (video) {
+    let holder = document.createElement("a");
+    holder.href = video;
+    holder.target = "_blank";
+    holder.innerText = record.player.name;
+    cells[1].appendChild(holder);
+
+    let link = document.createElement("a");
+    link.classList.add("link");
+    link.href = video;
+    link.target = "_blank";
+    link.innerText = VIDEO_HOSTS[new URL(video).hostname] || new URL(video).hostname;
+    cells[3].appendChild(link);
+  } else {
+    cells[1].innerText = record.player.name;
+  }
+
+  if (record.enjoyment !== null) cells[2].innerText = record.enjoyment + "/10";
+
+  cells[3].classList.add("video-link");
+  cells.forEach(cell => row.appendChild(cell));
+
+  return row;
+}
+
 function initializeHistoryTable() {
   get("/api/v2/demons/" + window.demon_id + "/audit/movement/").then(response => {
     let data = response.data;
@@ -80,6 +164,11 @@ function initializeHistoryTable() {
           let name = other.name === null ? "A demon" : other["name"];
 
           reason = name + " was moved " + verb + " past this demon"
+        } else if (entry["reason"]["Swapped"] !== undefined) {
+          let other = entry["reason"]["Swapped"]["other"];
+          let name = other.name === null ? "a demon" : other["name"];
+
+          reason = "Swapped with " + name;
         }
       }
 
@@ -9,6 +9,7 @@ import {
   rangeUnderflow,
   rangeOverflow,
   tooLong,
+  patternMismatch,
   findParentWithClass,
   FilteredPaginator,
   Viewer,
@@ -32,6 +33,39 @@ export function embedVideo(video) {
   }
 }
 
+// see pointercrate_demonlist::video::with_timestamp
+export function timestampedVideo(video, seconds) {
+  if (!video || seconds === undefined || seconds === null) return video;
+
+  if (video.startsWith("https://www.youtube")) {
+    return video + "&t=" + seconds + "s";
+  }
+
+  if (video.startsWith("https://www.twitch")) {
+    return video + "?t=" + Math.floor(seconds / 3600) + "h" + (Math.floor(seconds / 60) % 60) + "m" + (seconds % 60) + "s";
+  }
+
+  if (video.startsWith("https://www.bilibili")) {
+    return video + "?t=" + seconds;
+  }
+
+  if (video.startsWith("https://vimeo")) {
+    return video + "#t=" + seconds + "s";
+  }
+
+  return video;
+}
+
+export function formatTimestamp(seconds) {
+  let pad = value => (value + "").padStart(2, "0");
+
+  if (seconds >= 3600) {
+    return Math.floor(seconds / 3600) + ":" + pad(Math.floor(seconds / 60) % 60) + ":" + pad(seconds % 60);
+  }
+
+  return Math.floor(seconds / 60) + ":" + pad(seconds % 60);
+}
+
 export function initializeTimeMachine() {
   let formHtml = document.getElementById("time-machine-form");
 
@@ -77,6 +111,7 @@ export function initializeRecordSubmitter(submitApproved = false) {
   var video = submissionForm.input("id_video");
   var rawFootage = submissionForm.input("submit-raw-footage");
   var enjoyment = submissionForm.input("id_enjoyment");
+  var videoTimestamp = submissionForm.input("id_video_timestamp");
 
   demon.addValidator(input => input.dropdown.selected !== undefined, "Please specify a demon");
   demon.setTransform(parseInt);
@@ -113,6 +148,8 @@ export function initializeRecordSubmitter(submitApproved = false) {
   enjoyment.addValidator(badInput, "Record enjoyment must be a valid integer");
   enjoyment.addValidator(stepMismatch, "Record enjoyment mustn't be a decimal");
 
+  videoTimestamp.addValidator(patternMismatch, "Please enter a timestamp like '1:23:45', '12:34' or '42'");
+
   submissionForm.onInvalid(() => gtag('event', 'record-submit-failure-frontend', {'event-category': 'demonlist'}));
   submissionForm.onSubmit(function () {
     let data = submissionForm.serialize();
@@ -153,6 +190,12 @@ export function initializeRecordSubmitter(submitApproved = false) {
           case 42233:
             rawFootage.errorText = response.data.message;
             break;
+          case 42246:
+            enjoyment.errorText = response.data.message;
+            break;
+          case 42247:
+            videoTimestamp.errorText = response.data.message;
+            break;
           default:
             submissionForm.setError(response.data.message)
         }
@@ -273,6 +316,9 @@ export function generateRecord(record) {
     case "under consideration":
       li.style.backgroundColor = "rgba(142, 230, 230, .3)";
       break;
+    case "superseded":
+      li.style.backgroundColor = "rgba(200, 200, 200, .3)";
+      break;
     default:
       break;
   }
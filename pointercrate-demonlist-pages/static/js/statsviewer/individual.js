@@ -35,14 +35,12 @@ class IndividualStatsViewer extends StatsViewer {
                 record.demon.position <= this.extended_list_size
         ).length;
 
-        let verifiedExtended = playerData.verified.filter(demon => demon.position <= this.extended_list_size && demon.position > this.list_size).length;
-        let verifiedLegacy = playerData.verified.filter(demon => demon.position > this.extended_list_size).length;
+        // Verifications are part of the player's records, but they are already listed separately
+        this.formatRecordsInto(this._beaten, beaten.filter((record) => !record.verification));
+        this.setCompletionNumber(beaten.length - legacy - extended, extended, legacy);
 
-        this.formatRecordsInto(this._beaten, beaten);
-        this.setCompletionNumber(beaten.length - legacy - extended + playerData.verified.length - verifiedExtended - verifiedLegacy, extended + verifiedExtended, legacy + verifiedLegacy);
-
-        let hardest = playerData.verified
-            .concat(beaten.map((record) => record.demon))
+        let hardest = beaten
+            .map((record) => record.demon)
             .reduce((acc, next) => (acc.position > next.position ? next : acc), {name: "None", position: 321321321321});
 
         this.setHardest(hardest.name === "None" ? undefined : hardest);
@@ -1,4 +1,4 @@
-SELECT demons.id AS "demon_id!", demons.name AS "demon_name!: String", demons.position as "position!", demons.requirement as "requirement!", demons.level_id, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END, demons.thumbnail, verifiers.id AS "verifier_id!", verifiers.name AS "verifier_name!: String", verifiers.banned AS "verifier_banned!", publishers.id AS "publisher_id!", publishers.name AS "publisher_name!: String", publishers.banned AS "publisher_banned!"
+SELECT demons.id AS "demon_id!", demons.name AS "demon_name!: String", demons.position as "position!", demons.requirement as "requirement!", demons.level_id, demons.submissions_open AS "submissions_open!", demons.submissions_closed_reason, demons.discussion_url, demons.position_locked AS "position_locked!", demons.score_weight AS "score_weight!", demons.tier, demons.enjoyment, demons.version AS "version!", CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END, demons.thumbnail, verifiers.id AS "verifier_id!", verifiers.name AS "verifier_name!: String", verifiers.banned AS "verifier_banned!", publishers.id AS "publisher_id!", publishers.name AS "publisher_name!: String", publishers.banned AS "publisher_banned!"
 FROM demons
     INNER JOIN players as publishers
         ON demons.publisher = publishers.id
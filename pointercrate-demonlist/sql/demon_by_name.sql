@@ -1,7 +1,8 @@
-SELECT demons.id AS demon_id, demons.name AS "demon_name: String", demons.position, demons.requirement, demons.level_id, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END, demons.thumbnail,
+SELECT demons.id AS demon_id, demons.name AS "demon_name: String", demons.position, demons.requirement, demons.level_id, demons.submissions_open, demons.submissions_closed_reason, demons.discussion_url, demons.position_locked, demons.score_weight, demons.tier, demons.enjoyment, demons.version, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END, demons.thumbnail,
        verifiers.id AS verifier_id, verifiers.name AS "verifier_name: String", verifiers.banned AS verifier_banned,
        publishers.id AS publisher_id, publishers.name AS "publisher_name: String", publishers.banned AS publisher_banned
 FROM demons
 INNER JOIN players AS verifiers ON verifiers.id=demons.verifier
 INNER JOIN players AS publishers ON publishers.id=demons.publisher
-WHERE demons.name=$1::CITEXT
\ No newline at end of file
+WHERE demons.name=$1::CITEXT
+ORDER BY demons.position
\ No newline at end of file
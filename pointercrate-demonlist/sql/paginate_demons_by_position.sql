@@ -1,4 +1,4 @@
-SELECT demons.id AS demon_id, demons.name::text AS demon_name, demons.position, demons.requirement, demons.level_id, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END,demons.thumbnail,
+SELECT demons.id AS demon_id, demons.name::text AS demon_name, demons.position, demons.requirement, demons.level_id, demons.submissions_open, demons.submissions_closed_reason, demons.discussion_url, demons.position_locked, demons.score_weight, demons.tier, demons.enjoyment, demons.version, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END,demons.thumbnail,
        verifiers.id AS verifier_id, verifiers.name::text AS verifier_name, verifiers.banned AS verifier_banned,
        publishers.id AS publisher_id, publishers.name::text AS publisher_name, publishers.banned AS publisher_banned
 FROM demons
@@ -16,6 +16,9 @@ WHERE (demons.position < $1 OR $1 IS NULL)
   AND (publishers.name::CITEXT = $10 OR $10 IS NULL)
   AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL)
   AND (demons.level_id = $12 OR $12 IS NULL)
+  AND (demons.tier = $13 OR $13 IS NULL)
+  AND (demons.enjoyment < $14 OR $14 IS NULL)
+  AND (demons.enjoyment > $15 OR $15 IS NULL)
   AND demons.position IS NOT NULL
 ORDER BY demons.position {}
-LIMIT $13
\ No newline at end of file
+LIMIT $16
\ No newline at end of file
@@ -1,4 +1,4 @@
-SELECT index, rank, id, name, score, subdivision, iso_country_code, nation
+SELECT index, rank, id, name, score, subdivision, iso_country_code, nation, (SELECT version FROM players WHERE players.id = ranked_players.id) AS version
 FROM ranked_players
 WHERE (index < $1 OR $1 IS NULL)
   AND (index > $2 OR $2 IS NULL)
@@ -1,12 +1,12 @@
 SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, 
        status_::text AS status, players.id AS player_id, players.name::text AS player_name, 
        players.banned AS player_banned, demons.id AS demon_id, demons.name::text AS demon_name, 
-       demons.position, records.enjoyment
+       demons.position, records.enjoyment, submitters.public_id AS anonymous_submitter
 FROM records
 INNER JOIN players ON records.player = players.id
 INNER JOIN demons ON records.demon = demons.id
-WHERE (records.id < $1 OR $1 IS NULL)
-  AND (records.id > $2 OR $2 IS NULL)
+LEFT OUTER JOIN submitters ON records.submitter = submitters.submitter_id
+WHERE {seek}
   AND (progress = $3 OR $3 IS NULL)
   AND (progress < $4 OR $4 IS NULL)
   AND (progress > $5 OR $5 IS NULL)
@@ -20,5 +20,6 @@ WHERE (records.id < $1 OR $1 IS NULL)
   AND (players.id = $14 OR $14 IS NULL)
   AND (records.submitter = $15 OR $15 IS NULL)
   AND (records.enjoyment = $16 OR $16 IS NULL)
-ORDER BY id {}
-LIMIT $17
\ No newline at end of file
+  AND (submitters.public_id = $17 OR $17 IS NULL)
+ORDER BY {order}
+LIMIT $18
\ No newline at end of file
@@ -0,0 +1,225 @@
+//! Weekly digests of how the list changed
+//!
+//! A changelog compares the list at the start of an (ISO) week to the list at its end, both
+//! reconstructed from the audit log (see [`list_at`]). Demons that were explicitly moved are told
+//! apart from those that merely shifted because of other changes by finding the largest set of
+//! demons whose relative order did not change. Everything else was moved. Shifted demons are only
+//! mentioned if they crossed into a different section of the list. The [`summary`] module turns
+//! the result into human readable text.
+//!
+//! Demons removed from the list are only known about if they were archived. Deleted demons leave
+//! no trace in the audit log, and thus are not part of any changelog.
+
+use crate::{
+    config,
+    demon::{list_at, DemonId, MinimalDemon},
+    error::{DemonlistError, Result},
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub mod summary;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSection {
+    Main,
+    Extended,
+    Legacy,
+}
+
+impl ListSection {
+    pub fn of(position: i16) -> ListSection {
+        if position <= config::list_size() {
+            ListSection::Main
+        } else if position <= config::extended_list_size() {
+            ListSection::Extended
+        } else {
+            ListSection::Legacy
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangelogEntry {
+    Added {
+        demon: MinimalDemon,
+    },
+
+    /// The demon was moved to a different position by list staff
+    Moved {
+        demon: MinimalDemon,
+        from: i16,
+    },
+
+    /// The demon was pushed into a different section of the list by other demons being added,
+    /// moved or removed
+    Shifted {
+        demon: MinimalDemon,
+        from: i16,
+    },
+
+    /// The demon was archived. Its position is the one it had at that time
+    Removed {
+        demon: MinimalDemon,
+    },
+}
+
+#[derive(Serialize, Debug)]
+pub struct Changelog {
+    /// The week this changelog covers, formatted like `2024-W42`
+    pub week: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub entries: Vec<ChangelogEntry>,
+
+    /// One human readable line per entry (see [`summary::digest`])
+    pub digest: Vec<String>,
+}
+
+/// Parses an ISO week (e.g. `2024-W42`) into the monday it starts with
+pub fn parse_week(week: &str) -> Result<NaiveDate> {
+    let invalid = || DemonlistError::InvalidWeek { week: week.to_string() };
+    let (year, week_number) = week.split_once("-W").ok_or_else(invalid)?;
+
+    NaiveDate::from_isoywd_opt(
+        year.parse().map_err(|_| invalid())?,
+        week_number.parse().map_err(|_| invalid())?,
+        Weekday::Mon,
+    )
+    .ok_or_else(invalid)
+}
+
+/// The monday the current week started with
+pub fn current_week() -> NaiveDate {
+    let today = Utc::now().date_naive();
+
+    today - Duration::days(today.weekday().num_days_from_monday() as i64)
+}
+
+/// The changelog of the week starting with the given monday
+pub async fn weekly_changelog(week: NaiveDate, connection: &mut impl ReadConnection) -> Result<Changelog> {
+    let connection = &mut connection.reader();
+    let start = week.and_time(NaiveTime::MIN).and_utc();
+    let end = start + Duration::weeks(1);
+
+    let before = list_at(&mut *connection, start).await?;
+    let after = list_at(&mut *connection, end).await?;
+
+    let archived = sqlx::query!(
+        r#"SELECT id, name::text AS "name!", position FROM archived_demons WHERE archived_at >= $1 AND archived_at < $2 ORDER BY position"#,
+        start,
+        end
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    .into_iter()
+    .map(|row| MinimalDemon {
+        id: DemonId(row.id),
+        position: row.position,
+        name: row.name,
+    })
+    .collect();
+
+    let entries = diff(
+        before.into_iter().map(|demon| demon.current_demon.base).collect(),
+        after.into_iter().map(|demon| demon.current_demon.base).collect(),
+        archived,
+    );
+    let iso_week = week.iso_week();
+
+    Ok(Changelog {
+        week: format!("{}-W{:02}", iso_week.year(), iso_week.week()),
+        start,
+        end,
+        digest: summary::digest(&entries),
+        entries,
+    })
+}
+
+/// Computes the changes between two versions of the list. `after` needs to be ordered by position.
+/// Since archived demons are not part of either version, they need to be passed separately
+fn diff(before: Vec<MinimalDemon>, after: Vec<MinimalDemon>, archived: Vec<MinimalDemon>) -> Vec<ChangelogEntry> {
+    let old_positions: HashMap<DemonId, i16> = before.into_iter().map(|demon| (demon.id, demon.position)).collect();
+
+    let (remaining, added): (Vec<_>, Vec<_>) = after.into_iter().partition(|demon| old_positions.contains_key(&demon.id));
+    let unmoved = longest_increasing_subsequence(&remaining.iter().map(|demon| old_positions[&demon.id]).collect::<Vec<_>>());
+
+    let mut moved = Vec::new();
+    let mut shifted = Vec::new();
+
+    for (index, demon) in remaining.into_iter().enumerate() {
+        let from = old_positions[&demon.id];
+
+        if !unmoved.contains(&index) && from != demon.position {
+            moved.push(ChangelogEntry::Moved { demon, from });
+        } else if ListSection::of(from) != ListSection::of(demon.position) {
+            shifted.push(ChangelogEntry::Shifted { demon, from });
+        }
+    }
+
+    added
+        .into_iter()
+        .map(|demon| ChangelogEntry::Added { demon })
+        .chain(moved)
+        .chain(shifted)
+        .chain(archived.into_iter().map(|demon| ChangelogEntry::Removed { demon }))
+        .collect()
+}
+
+/// The indices of a longest strictly increasing subsequence of `values`
+fn longest_increasing_subsequence(values: &[i16]) -> HashSet<usize> {
+    // tails[k] is the index of the smallest value ending an increasing subsequence of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; values.len()];
+
+    for (index, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+
+        if length > 0 {
+            predecessors[index] = Some(tails[length - 1]);
+        }
+
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut subsequence = HashSet::new();
+    let mut current = tails.last().copied();
+
+    while let Some(index) = current {
+        subsequence.insert(index);
+        current = predecessors[index];
+    }
+
+    subsequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{longest_increasing_subsequence, parse_week};
+    use chrono::NaiveDate;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        // The demon at old position 5 was moved to the top, everything else shifted down
+        assert_eq!(longest_increasing_subsequence(&[5, 1, 2, 3, 4]), HashSet::from([1, 2, 3, 4]));
+        assert_eq!(longest_increasing_subsequence(&[1, 2, 3]), HashSet::from([0, 1, 2]));
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_week() {
+        assert_eq!(parse_week("2024-W42").unwrap(), NaiveDate::from_ymd_opt(2024, 10, 14).unwrap());
+        assert_eq!(parse_week("2020-W53").unwrap(), NaiveDate::from_ymd_opt(2020, 12, 28).unwrap());
+        assert!(parse_week("2024-W54").is_err());
+        assert!(parse_week("2024-10-14").is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! Turns [`ChangelogEntry`]s into the text shown in changelogs, e.g. "Bloodbath was added at #3"
+//! or "Sonic Wave dropped to the legacy list"
+
+use super::{ChangelogEntry, ListSection};
+
+impl ListSection {
+    fn name(self) -> &'static str {
+        match self {
+            ListSection::Main => "main list",
+            ListSection::Extended => "extended list",
+            ListSection::Legacy => "legacy list",
+        }
+    }
+}
+
+/// A single line describing the given change
+pub fn describe(entry: &ChangelogEntry) -> String {
+    match entry {
+        ChangelogEntry::Added { demon } => format!("{} was added at #{}", demon.name, demon.position),
+        ChangelogEntry::Moved { demon, from } | ChangelogEntry::Shifted { demon, from } => {
+            let (old_section, new_section) = (ListSection::of(*from), ListSection::of(demon.position));
+
+            match (old_section == new_section, demon.position < *from) {
+                (true, true) => format!("{} was moved up from #{} to #{}", demon.name, from, demon.position),
+                (true, false) => format!("{} was moved down from #{} to #{}", demon.name, from, demon.position),
+                (false, true) => format!("{} rose to the {} (#{})", demon.name, new_section.name(), demon.position),
+                (false, false) => format!("{} dropped to the {} (#{})", demon.name, new_section.name(), demon.position),
+            }
+        },
+        ChangelogEntry::Removed { demon } => format!("{} was removed from the list (previously #{})", demon.name, demon.position),
+    }
+}
+
+/// One line per entry, in the order of the entries
+pub fn digest(entries: &[ChangelogEntry]) -> Vec<String> {
+    entries.iter().map(describe).collect()
+}
@@ -1,7 +1,56 @@
+use pointercrate_core::config::{RequirementPolicy, ScoreDecayConfig};
+
 pub fn list_size() -> i16 {
-    100
+    pointercrate_core::config::get().list.list_size
 }
 
 pub fn extended_list_size() -> i16 {
-    200
+    pointercrate_core::config::get().list.extended_list_size
+}
+
+/// Time (in seconds) after which the video of an approved record is checked for availability again.
+/// `0` disables these checks
+pub fn video_recheck_interval() -> u64 {
+    pointercrate_core::config::get().list.video_recheck_interval
+}
+
+/// Position based weights applied to record scores, see [`score`](crate::score)
+pub fn score_decay() -> &'static [ScoreDecayConfig] {
+    &pointercrate_core::config::get().list.score_decay
+}
+
+/// See [`ListRequirement`](crate::requirement::ListRequirement)
+pub fn requirement_policy() -> RequirementPolicy {
+    pointercrate_core::config::get().list.requirement_policy
+}
+
+/// Time (in seconds) after which the geolocation data of a submitter is deleted. `0` keeps it
+/// indefinitely
+pub fn geo_data_retention() -> u64 {
+    pointercrate_core::config::get().submissions.geo_data_retention
+}
+
+/// Whether new submissions are assigned to list helpers, see [`assignment`](crate::record::assignment)
+pub fn auto_assign() -> bool {
+    pointercrate_core::config::get().submissions.auto_assign
+}
+
+/// Time (in seconds) after which unreviewed submissions are assigned to a different list helper. `0`
+/// disables reassignment
+pub fn assignment_timeout() -> u64 {
+    pointercrate_core::config::get().submissions.assignment_timeout
+}
+
+/// Time (in seconds) after which submissions waiting for review count as stale, see
+/// [`stale`](crate::record::stale). `0` disables escalation
+pub fn stale_submission_threshold() -> u64 {
+    pointercrate_core::config::get().submissions.stale_after
+}
+
+pub fn geoip_country_database() -> Option<String> {
+    pointercrate_core::config::get().integrations.geoip_country_database.clone()
+}
+
+pub fn geoip_asn_database() -> Option<String> {
+    pointercrate_core::config::get().integrations.geoip_asn_database.clone()
 }
@@ -1,42 +1,43 @@
 use crate::{
-    creator::Creator,
+    creator::{Creator, CreatorRole, CreatorsByRole},
     demon::MinimalDemon,
     error::{DemonlistError, Result},
-    player::DatabasePlayer,
+    player::{DatabasePlayer, PlayerId},
 };
 use futures::stream::StreamExt;
-use sqlx::PgConnection;
+use pointercrate_core::pool::ReadConnection;
 
 impl Creator {
-    pub async fn get(demon: &MinimalDemon, player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Creator> {
-        let exists = sqlx::query!(
-            r#"SELECT EXISTS (SELECT FROM creators WHERE creator=$1 AND demon = $2) AS "result!: bool""#,
-            player.id,
-            demon.id
+    pub async fn get(demon: &MinimalDemon, player: &DatabasePlayer, connection: &mut impl ReadConnection) -> Result<Creator> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            "SELECT role FROM creators WHERE creator = $1 AND demon = $2",
+            player.id.0,
+            demon.id.0
         )
-        .fetch_one(connection)
-        .await?
-        .result;
+        .fetch_optional(connection)
+        .await?;
 
-        if exists {
-            Ok(Creator {
-                demon: demon.id,
-                creator: player.id,
-            })
-        } else {
-            Err(DemonlistError::CreatorNotFound {
-                player_id: player.id,
-                demon_id: demon.id,
-            })
+        match row {
+            Some(row) => Ok(Creator {
+                demon: demon.id.0,
+                creator: player.id.0,
+                role: row.role.as_deref().map(CreatorRole::from_sql),
+            }),
+            None => Err(DemonlistError::CreatorNotFound {
+                player_id: player.id.0,
+                demon_id: demon.id.0,
+            }),
         }
     }
 }
 
-pub async fn creators_of(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<DatabasePlayer>> {
+pub async fn creators_of(demon: &MinimalDemon, connection: &mut impl ReadConnection) -> Result<Vec<DatabasePlayer>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"SELECT players.id, players.name, players.banned FROM players INNER JOIN creators ON players.id = creators.creator WHERE 
          creators.demon = $1"#,
-        demon.id
+        demon.id.0
     )
     .fetch(connection);
     let mut players = Vec::new();
@@ -45,7 +46,7 @@ pub async fn creators_of(demon: &MinimalDemon, connection: &mut PgConnection) ->
         let row = row?;
 
         players.push(DatabasePlayer {
-            id: row.id,
+            id: PlayerId(row.id),
             name: row.name,
             banned: row.banned,
         })
@@ -54,7 +55,35 @@ pub async fn creators_of(demon: &MinimalDemon, connection: &mut PgConnection) ->
     Ok(players)
 }
 
-pub async fn created_by(player_id: i32, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+/// The creators of the given demon, grouped by what they contributed to it
+pub async fn creators_by_role(demon: &MinimalDemon, connection: &mut impl ReadConnection) -> Result<CreatorsByRole> {
+    let connection = &mut connection.reader();
+    let mut stream = sqlx::query!(
+        r#"SELECT players.id, players.name, players.banned, creators.role FROM players INNER JOIN creators ON players.id = creators.creator
+         WHERE creators.demon = $1 ORDER BY players.name"#,
+        demon.id.0
+    )
+    .fetch(connection);
+    let mut creators = CreatorsByRole::default();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        creators.push(
+            DatabasePlayer {
+                id: PlayerId(row.id),
+                name: row.name,
+                banned: row.banned,
+            },
+            row.role.as_deref().map(CreatorRole::from_sql),
+        )
+    }
+
+    Ok(creators)
+}
+
+pub async fn created_by(player_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<MinimalDemon>> {
+    let connection = &mut connection.reader();
     query_many_demons!(
         connection,
         r#"SELECT demons.id, demons.name, demons.position FROM demons INNER JOIN creators ON demons.id = creators.demon WHERE
@@ -1,15 +1,85 @@
 // pub use self::post::PostCreator;
-pub use self::get::{created_by, creators_of};
+pub use self::{
+    get::{created_by, creators_by_role, creators_of},
+    patch::PatchCreator,
+};
+use crate::player::DatabasePlayer;
 use derive_more::Display;
 pub use post::PostCreator;
+use serde::{Deserialize, Serialize};
 
 mod delete;
 mod get;
+mod patch;
 mod post;
 
-#[derive(Debug, Display, Hash)]
+#[derive(Debug, Display, Hash, Serialize)]
 #[display(fmt = "creator with id {} on demon {}", creator, demon)]
 pub struct Creator {
     demon: i32,
     creator: i32,
+    role: Option<CreatorRole>,
+}
+
+/// What a creator contributed to a demon, mostly relevant for megacollabs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum CreatorRole {
+    #[display(fmt = "host")]
+    Host,
+
+    #[display(fmt = "decorator")]
+    Decorator,
+
+    #[display(fmt = "gameplay")]
+    Gameplay,
+}
+
+impl CreatorRole {
+    fn to_sql(self) -> &'static str {
+        match self {
+            CreatorRole::Host => "host",
+            CreatorRole::Decorator => "decorator",
+            CreatorRole::Gameplay => "gameplay",
+        }
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "host" => CreatorRole::Host,
+            "decorator" => CreatorRole::Decorator,
+            "gameplay" => CreatorRole::Gameplay,
+            _ => panic!("invalid creator role: {}", sql),
+        }
+    }
+}
+
+/// A demon's creators, grouped by their [`CreatorRole`]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Hash)]
+pub struct CreatorsByRole {
+    pub host: Vec<DatabasePlayer>,
+    pub decorator: Vec<DatabasePlayer>,
+    pub gameplay: Vec<DatabasePlayer>,
+
+    /// Creators whose role is not known
+    pub unspecified: Vec<DatabasePlayer>,
+}
+
+impl CreatorsByRole {
+    /// Groups creators whose roles are not known yet, e.g. ones that were just added
+    pub fn unspecified(creators: &[DatabasePlayer]) -> Self {
+        CreatorsByRole {
+            unspecified: creators.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, player: DatabasePlayer, role: Option<CreatorRole>) {
+        match role {
+            Some(CreatorRole::Host) => self.host.push(player),
+            Some(CreatorRole::Decorator) => self.decorator.push(player),
+            Some(CreatorRole::Gameplay) => self.gameplay.push(player),
+            None => self.unspecified.push(player),
+        }
+    }
 }
@@ -0,0 +1,41 @@
+use crate::{
+    creator::{Creator, CreatorRole},
+    error::Result,
+};
+use log::info;
+use pointercrate_core::util::nullable;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchCreator {
+    #[serde(default, deserialize_with = "nullable")]
+    pub role: Option<Option<CreatorRole>>,
+}
+
+impl Creator {
+    pub async fn apply_patch(mut self, patch: PatchCreator, connection: &mut PgConnection) -> Result<Self> {
+        info!("Patching {} with {:?}", self, patch);
+
+        if let Some(role) = patch.role {
+            self.set_role(role, connection).await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn set_role(&mut self, role: Option<CreatorRole>, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE creators SET role = $1 WHERE creator = $2 AND demon = $3",
+            role.map(CreatorRole::to_sql),
+            self.creator,
+            self.demon
+        )
+        .execute(connection)
+        .await?;
+
+        self.role = role;
+
+        Ok(())
+    }
+}
@@ -20,13 +20,14 @@ impl Creator {
             Err(err) => return Err(err),
         }
 
-        let _ = sqlx::query!("INSERT INTO creators (creator, demon) VALUES ($1, $2)", player.id, demon.id)
+        let _ = sqlx::query!("INSERT INTO creators (creator, demon) VALUES ($1, $2)", player.id.0, demon.id.0)
             .execute(connection)
             .await?;
 
         Ok(Creator {
-            demon: demon.id,
-            creator: player.id,
+            demon: demon.id.0,
+            creator: player.id.0,
+            role: None,
         })
     }
 }
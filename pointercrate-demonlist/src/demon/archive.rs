@@ -0,0 +1,279 @@
+//! Demons removed from the list entirely
+//!
+//! Unlike deleting a demon, archiving it keeps all of its data around: the demon (including its
+//! creators) and its records are moved into separate tables, so that they no longer occupy a
+//! position, show up on the list or contribute to anyone's score. Archived demons and their
+//! approved records can still be viewed, and list moderators can restore an archived demon to any
+//! position on the list, which moves everything back.
+//!
+//! Records keep their IDs while archived. Notes, appeals and reports attached to them are not
+//! preserved.
+
+use crate::{
+    demon::{Demon, DemonId, FullDemon},
+    error::{DemonlistError, Result},
+    nationality::Nationality,
+    player::{DatabasePlayer, PlayerId},
+    record::{MinimalRecordP, RecordId, RecordStatus},
+};
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use futures::stream::StreamExt;
+use log::info;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, PartialEq, Eq, Hash, Display)]
+#[display(fmt = "{} (ID: {}, archived)", name, id)]
+pub struct ArchivedDemon {
+    pub id: i32,
+    pub name: String,
+
+    /// The position the demon had when it was archived
+    pub position: i16,
+    pub requirement: i16,
+    pub video: Option<String>,
+    pub verifier: DatabasePlayer,
+    pub publisher: DatabasePlayer,
+    pub creators: Vec<DatabasePlayer>,
+    pub level_id: Option<i64>,
+    pub archived_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreDemon {
+    /// The position to put the demon back at
+    position: i16,
+}
+
+impl FullDemon {
+    /// Moves this demon, its creators, additional verifiers and publishers and its records into the
+    /// archive. The positions of all demons below it shift up by one.
+    ///
+    /// Does not update any scores. Must run inside a transaction!
+    pub async fn archive(self, connection: &mut PgConnection) -> Result<ArchivedDemon> {
+        if self.demon.position_locked {
+            return Err(DemonlistError::DemonPositionLocked);
+        }
+
+        info!("Archiving demon {}", self);
+
+        let demon_id = self.demon.base.id;
+
+        sqlx::query!(
+            "INSERT INTO archived_demons SELECT (jsonb_populate_record(NULL::archived_demons, to_jsonb(demons) || \
+             jsonb_build_object('creators', ARRAY(SELECT creator FROM creators WHERE demon = $1 ORDER BY creator), 'creator_roles', \
+             ARRAY(SELECT role FROM creators WHERE demon = $1 ORDER BY creator), 'co_verifiers', ARRAY(SELECT player FROM demon_credits \
+             WHERE demon = $1 AND kind = 'verifier'), 'co_publishers', ARRAY(SELECT player FROM demon_credits WHERE demon = $1 AND kind = \
+             'publisher'), 'archived_at', NOW()))).* FROM demons WHERE id = $1",
+            demon_id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO archived_records SELECT (jsonb_populate_record(NULL::archived_records, to_jsonb(records))).* FROM records WHERE \
+             demon = $1",
+            demon_id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!("DELETE FROM records WHERE demon = $1", demon_id.0)
+            .execute(&mut *connection)
+            .await?;
+
+        FullDemon::delete_demon_data(demon_id.0, &mut *connection).await?;
+        FullDemon::shift_up(self.demon.base.position, &mut *connection).await?;
+
+        ArchivedDemon::by_id(demon_id.0, connection).await
+    }
+}
+
+impl ArchivedDemon {
+    pub async fn by_id(demon_id: i32, connection: &mut impl ReadConnection) -> Result<ArchivedDemon> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            r#"SELECT archived_demons.id, archived_demons.name::TEXT AS "name!", archived_demons.position, requirement,
+                      CASE WHEN verifiers.link_banned THEN NULL ELSE video::TEXT END, level_id, creators, archived_at,
+                      verifiers.id AS verifier_id, verifiers.name AS verifier_name, verifiers.banned AS verifier_banned,
+                      publishers.id AS publisher_id, publishers.name AS publisher_name, publishers.banned AS publisher_banned
+               FROM archived_demons
+               INNER JOIN players AS verifiers ON verifiers.id = archived_demons.verifier
+               INNER JOIN players AS publishers ON publishers.id = archived_demons.publisher
+               WHERE archived_demons.id = $1"#,
+            demon_id
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(DemonlistError::ArchivedDemonNotFound { demon_id })?;
+
+        let mut creators = Vec::new();
+        let mut stream = sqlx::query!(
+            "SELECT id, name, banned FROM players WHERE id = ANY($1) ORDER BY name",
+            &row.creators
+        )
+        .fetch(connection);
+
+        while let Some(creator) = stream.next().await {
+            let creator = creator?;
+
+            creators.push(DatabasePlayer {
+                id: PlayerId(creator.id),
+                name: creator.name,
+                banned: creator.banned,
+            })
+        }
+
+        Ok(ArchivedDemon {
+            id: row.id,
+            name: row.name,
+            position: row.position,
+            requirement: row.requirement,
+            video: row.video,
+            verifier: DatabasePlayer {
+                id: PlayerId(row.verifier_id),
+                name: row.verifier_name,
+                banned: row.verifier_banned,
+            },
+            publisher: DatabasePlayer {
+                id: PlayerId(row.publisher_id),
+                name: row.publisher_name,
+                banned: row.publisher_banned,
+            },
+            creators,
+            level_id: row.level_id,
+            archived_at: row.archived_at,
+        })
+    }
+
+    /// All archived demons, most recently archived first
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<ArchivedDemon>> {
+        let connection = &mut connection.reader();
+        let ids = sqlx::query!("SELECT id FROM archived_demons ORDER BY archived_at DESC, id DESC")
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut demons = Vec::new();
+
+        for row in ids {
+            demons.push(ArchivedDemon::by_id(row.id, &mut *connection).await?);
+        }
+
+        Ok(demons)
+    }
+
+    /// The approved records on this demon, in the same order as
+    /// [`approved_records_on`](crate::record::approved_records_on)
+    pub async fn approved_records(&self, connection: &mut impl ReadConnection) -> Result<Vec<MinimalRecordP>> {
+        let connection = &mut connection.reader();
+        let mut stream = sqlx::query!(
+            r#"SELECT archived_records.id, progress, enjoyment, CASE WHEN players.link_banned THEN NULL ELSE video::text END, video_timestamp,
+                      players.id AS player_id, players.name, players.banned, nation::TEXT, iso_country_code::TEXT
+               FROM archived_records
+               INNER JOIN players ON archived_records.player = players.id
+               LEFT OUTER JOIN nationalities ON nationality = iso_country_code
+               WHERE status_ = 'APPROVED' AND archived_records.demon = $1 AND NOT archived_records.verification
+               ORDER BY progress DESC, archived_records.id ASC"#,
+            self.id
+        )
+        .fetch(connection);
+
+        let mut records = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            records.push(MinimalRecordP {
+                id: RecordId(row.id),
+                progress: row.progress,
+                video: row.video,
+                video_timestamp: row.video_timestamp,
+                enjoyment: row.enjoyment,
+                status: RecordStatus::Approved,
+                player: DatabasePlayer {
+                    id: PlayerId(row.player_id),
+                    name: row.name,
+                    banned: row.banned,
+                },
+                nationality: match (row.nation, row.iso_country_code) {
+                    (Some(nation), Some(code)) => Some(Nationality {
+                        iso_country_code: code,
+                        nation,
+                        subdivision: None,
+                    }),
+                    _ => None,
+                },
+            })
+        }
+
+        Ok(records)
+    }
+
+    /// Puts this demon back onto the list at the given position, together with its creators and
+    /// records, and removes it from the archive
+    ///
+    /// Does not update any scores. Must run inside a transaction!
+    pub async fn restore(self, restore: RestoreDemon, connection: &mut PgConnection) -> Result<FullDemon> {
+        Demon::validate_position(restore.position, &mut *connection).await?;
+
+        if let Some(level_id) = self.level_id {
+            let taken = sqlx::query!(r#"SELECT EXISTS (SELECT 1 FROM demons WHERE level_id = $1) AS "taken!""#, level_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .taken;
+
+            if taken {
+                return Err(DemonlistError::ArchivedLevelIdTaken);
+            }
+        }
+
+        info!("Restoring {} at position {}", self, restore.position);
+
+        Demon::shift_down(restore.position, &mut *connection).await?;
+
+        sqlx::query!(
+            "INSERT INTO demons SELECT (jsonb_populate_record(NULL::demons, to_jsonb(archived_demons) || jsonb_build_object('position', \
+             $2::SMALLINT))).* FROM archived_demons WHERE id = $1",
+            self.id,
+            restore.position
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO creators (demon, creator, role) SELECT id, creator, role FROM archived_demons, UNNEST(creators, creator_roles) AS \
+             archived_creators(creator, role) WHERE id = $1",
+            self.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO demon_credits (demon, player, kind) SELECT id, UNNEST(co_verifiers), 'verifier' FROM archived_demons WHERE id = $1 \
+             UNION SELECT id, UNNEST(co_publishers), 'publisher' FROM archived_demons WHERE id = $1",
+            self.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO records SELECT (jsonb_populate_record(NULL::records, to_jsonb(archived_records))).* FROM archived_records WHERE \
+             demon = $1",
+            self.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!("DELETE FROM archived_records WHERE demon = $1", self.id)
+            .execute(&mut *connection)
+            .await?;
+
+        sqlx::query!("DELETE FROM archived_demons WHERE id = $1", self.id)
+            .execute(&mut *connection)
+            .await?;
+
+        FullDemon::by_id(DemonId(self.id), connection).await
+    }
+}
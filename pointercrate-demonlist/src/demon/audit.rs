@@ -1,11 +1,13 @@
 use crate::error::Result;
 
-use crate::demon::MinimalDemon;
-use chrono::{NaiveDateTime, NaiveTime};
+use crate::demon::{DemonId, MinimalDemon};
+use chrono::{DateTime, NaiveTime, Utc};
 use futures::StreamExt;
-use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
+use pointercrate_core::{
+    audit::{parse_diff, AuditDiff, AuditLogEntry, AuditLogEntryType, NamedId},
+    pool::ReadConnection,
+};
 use serde::Serialize;
-use sqlx::PgConnection;
 use std::collections::HashMap;
 
 #[derive(Serialize)]
@@ -16,27 +18,41 @@ pub struct DemonModificationData {
     pub video: Option<String>,
     pub verifier: Option<NamedId>,
     pub publisher: Option<NamedId>,
+
+    /// Every column changed by this modification with its old and new value. `None` for
+    /// modifications from before diffs were recorded
+    pub diff: Option<AuditDiff>,
 }
 
 #[derive(Serialize, Debug)]
 pub enum MovementReason {
     Added,
     Moved,
-    OtherAddedAbove { other: NamedId },
-    OtherMoved { other: NamedId },
+    OtherAddedAbove {
+        other: NamedId,
+    },
+    OtherMoved {
+        other: NamedId,
+    },
+
+    /// The demon traded positions with another demon
+    Swapped {
+        other: NamedId,
+    },
     Unknown,
 }
 
 #[derive(Serialize, Debug)]
 pub struct MovementLogEntry {
     reason: MovementReason,
-    time: NaiveDateTime,
+    time: DateTime<Utc>,
 
     // only `None` for the last entry in case the demon has been deleted
     new_position: Option<i16>,
 }
 
-pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection) -> Result<Vec<MovementLogEntry>> {
+pub async fn movement_log_for_demon(demon_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<MovementLogEntry>> {
+    let connection = &mut connection.reader();
     let audit_log = audit_log_for_demon(demon_id, connection).await?;
 
     let mut movement_log = Vec::new();
@@ -44,6 +60,9 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
     let mut additions = HashMap::new();
     // map time -> NamedId keeping track when movements to -1 happened
     let mut all_moves = HashMap::new();
+    // map time -> all demons whose position changed at a time this demon's position changed, to
+    // detect swaps (exactly two demons trading positions without either being moved to -1 first)
+    let mut position_changes: HashMap<DateTime<Utc>, Vec<NamedId>> = HashMap::new();
 
     {
         // non-lexical lifetimes working amazingly I see >.>
@@ -84,6 +103,24 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
         }
     }
 
+    {
+        let mut change_stream = sqlx::query!(
+            "SELECT time, demon_modifications.id, demons.name::TEXT FROM demon_modifications LEFT OUTER JOIN demons ON demons.id = \
+             demon_modifications.id WHERE demon_modifications.position <> -1 AND time IN (SELECT time FROM demon_modifications WHERE id = \
+             $1 AND position IS NOT NULL)",
+            demon_id
+        )
+        .fetch(&mut *connection);
+
+        while let Some(row) = change_stream.next().await {
+            let row = row?;
+            position_changes.entry(row.time).or_default().push(NamedId {
+                id: row.id,
+                name: row.name,
+            });
+        }
+    }
+
     for log_entry in audit_log {
         let time = log_entry.time;
 
@@ -133,16 +170,25 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
                         }),
                         None => {
                             let added_demon = additions.get(&time);
+                            let swapped_with = position_changes
+                                .get(&time)
+                                .filter(|changes| changes.len() == 2)
+                                .and_then(|changes| changes.iter().find(|change| change.id != demon_id));
 
-                            match added_demon {
-                                Some(added_demon) => movement_log.push(MovementLogEntry {
+                            match (added_demon, swapped_with) {
+                                (Some(added_demon), _) => movement_log.push(MovementLogEntry {
                                     reason: MovementReason::OtherAddedAbove {
                                         other: added_demon.clone(),
                                     },
                                     new_position: None,
                                     time,
                                 }),
-                                None => movement_log.push(MovementLogEntry {
+                                (None, Some(other)) => movement_log.push(MovementLogEntry {
+                                    reason: MovementReason::Swapped { other: other.clone() },
+                                    new_position: None,
+                                    time,
+                                }),
+                                (None, None) => movement_log.push(MovementLogEntry {
                                     reason: MovementReason::Unknown,
                                     new_position: None,
                                     time,
@@ -168,16 +214,19 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
     }
 
     // update the last entry with the current position
-    MinimalDemon::by_id(demon_id, &mut *connection).await.map(|minimal_demon| {
-        movement_log
-            .last_mut()
-            .map(|entry| entry.new_position = Some(minimal_demon.position));
-    })?;
+    MinimalDemon::by_id(DemonId(demon_id), &mut *connection)
+        .await
+        .map(|minimal_demon| {
+            movement_log
+                .last_mut()
+                .map(|entry| entry.new_position = Some(minimal_demon.position));
+        })?;
 
     Ok(movement_log)
 }
 
-pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -> Result<Vec<AuditLogEntry<DemonModificationData>>> {
+pub async fn audit_log_for_demon(demon_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<AuditLogEntry<DemonModificationData>>> {
+    let connection = &mut connection.reader();
     let mut entries = Vec::new();
 
     let addition_row = sqlx::query!(
@@ -215,7 +264,8 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
                 verifier,
                 verifiers.name::text as verifier_name,
                 publisher,
-                publishers.name::text as publisher_name
+                publishers.name::text as publisher_name,
+                diff::text
            FROM demon_modifications
            LEFT OUTER JOIN members ON members.member_id = userid
            LEFT OUTER JOIN players AS verifiers ON verifier=verifiers.id
@@ -253,6 +303,7 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
                     }),
                     None => None,
                 },
+                diff: parse_diff(row.diff),
             }),
             user: NamedId {
                 name: row.username,
@@ -0,0 +1,151 @@
+//! Additional verifiers and publishers of demons
+//!
+//! Some levels were verified by more than one player (co-verifications), or have been published
+//! more than once (republishes). Every demon has exactly one primary verifier and publisher, stored
+//! in the demon itself and returned as [`Demon::verifier`] and [`Demon::publisher`], so that clients
+//! only knowing about a single verifier and publisher keep working. Everyone else is credited
+//! separately, via the `demon_credits` table. Only the primary verifier has a
+//! [verification record](crate::record::verification).
+
+use crate::{
+    demon::Demon,
+    error::{DemonlistError, Result},
+    player::{DatabasePlayer, PlayerId},
+};
+use derive_more::Display;
+use log::info;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditKind {
+    #[display(fmt = "verifier")]
+    Verifier,
+
+    #[display(fmt = "publisher")]
+    Publisher,
+}
+
+impl CreditKind {
+    fn to_sql(self) -> &'static str {
+        match self {
+            CreditKind::Verifier => "verifier",
+            CreditKind::Publisher => "publisher",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash)]
+pub struct DemonCredit {
+    #[serde(flatten)]
+    pub player: DatabasePlayer,
+
+    /// Whether this is the demon's primary verifier or publisher
+    pub primary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostCredit {
+    pub player: String,
+}
+
+/// All verifiers or publishers of the given demon, the primary one first
+pub async fn credits_of(demon: &Demon, kind: CreditKind, connection: &mut impl ReadConnection) -> Result<Vec<DemonCredit>> {
+    let connection = &mut connection.reader();
+    let primary = match kind {
+        CreditKind::Verifier => demon.verifier.clone(),
+        CreditKind::Publisher => demon.publisher.clone(),
+    };
+
+    let additional = sqlx::query!(
+        r#"SELECT players.id, players.name::TEXT AS "name!", players.banned FROM demon_credits INNER JOIN players ON players.id =
+         demon_credits.player WHERE demon_credits.demon = $1 AND demon_credits.kind = $2 ORDER BY players.name"#,
+        demon.base.id.0,
+        kind.to_sql()
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| DemonCredit {
+        player: DatabasePlayer {
+            id: PlayerId(row.id),
+            name: row.name,
+            banned: row.banned,
+        },
+        primary: false,
+    });
+
+    Ok(std::iter::once(DemonCredit {
+        player: primary,
+        primary: true,
+    })
+    .chain(additional)
+    .collect())
+}
+
+/// Credits the given player as an additional verifier or publisher of the given demon
+pub async fn add_credit(demon: &Demon, player: &DatabasePlayer, kind: CreditKind, connection: &mut PgConnection) -> Result<()> {
+    let primary = match kind {
+        CreditKind::Verifier => &demon.verifier,
+        CreditKind::Publisher => &demon.publisher,
+    };
+
+    if primary.id == player.id {
+        return Err(DemonlistError::CreditExists { kind });
+    }
+
+    let inserted = sqlx::query!(
+        "INSERT INTO demon_credits (demon, player, kind) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        demon.base.id.0,
+        player.id.0,
+        kind.to_sql()
+    )
+    .execute(connection)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        return Err(DemonlistError::CreditExists { kind });
+    }
+
+    info!("Credited {} as additional {} of {}", player, kind, demon);
+
+    Ok(())
+}
+
+/// Removes an additional verifier or publisher from the given demon. The primary ones can only be
+/// replaced, not removed
+pub async fn remove_credit(demon: &Demon, player_id: i32, kind: CreditKind, connection: &mut PgConnection) -> Result<()> {
+    let deleted = sqlx::query!(
+        "DELETE FROM demon_credits WHERE demon = $1 AND player = $2 AND kind = $3",
+        demon.base.id.0,
+        player_id,
+        kind.to_sql()
+    )
+    .execute(connection)
+    .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(DemonlistError::CreditNotFound {
+            demon_id: demon.base.id.0,
+            player_id,
+            kind,
+        });
+    }
+
+    Ok(())
+}
+
+/// Removes additional credits that duplicate a primary verifier or publisher, e.g. after an
+/// additional verifier was made the primary one
+pub(crate) async fn remove_redundant_credits(connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM demon_credits USING demons WHERE demon_credits.demon = demons.id AND demon_credits.player = CASE demon_credits.kind \
+         WHEN 'verifier' THEN demons.verifier ELSE demons.publisher END"
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
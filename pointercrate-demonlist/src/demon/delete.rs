@@ -6,10 +6,10 @@ impl FullDemon {
     pub async fn delete_demon(self, connection: &mut PgConnection) -> Result<()> {
         info!("Deleting demon {}", self);
 
-        FullDemon::delete_all_records(self.demon.base.id, &self.demon.base.name, connection).await?;
+        FullDemon::delete_all_records(self.demon.base.id.0, &self.demon.base.name, connection).await?;
 
         // creator is stored separately from demons
-        FullDemon::delete_demon_data(self.demon.base.id, connection).await?;
+        FullDemon::delete_demon_data(self.demon.base.id.0, connection).await?;
 
         // prevent holes in the list of demons
         FullDemon::shift_up(self.demon.base.position, connection).await?;
@@ -0,0 +1,304 @@
+//! Drafts of demons not yet placed on the list
+//!
+//! Placing a new demon usually involves gathering its creators, verification and intended position
+//! before announcing it. Drafts allow list staff to prepare all of this without the demon being
+//! visible to the public. Drafts are stored separately from demons and reference players only by
+//! name, so nothing about them shows up anywhere on the public list (not even newly created
+//! players).
+//!
+//! Publishing a draft adds the demon to the list at the draft's position, exactly as if it had been
+//! added via `POST /api/v2/demons/`, and deletes the draft. All side effects of adding a demon (such
+//! as other demons shifting down) thus only happen at publish time.
+
+use crate::{
+    demon::{Demon, FullDemon, PostDemon},
+    error::{DemonlistError, Result},
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::{
+    pool::ReadConnection,
+    util::{non_nullable, nullable},
+    validate::{normalize_name, validated, Validate, Validator},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, PartialEq, Eq, Hash)]
+pub struct DemonDraft {
+    pub id: i32,
+    pub name: String,
+    pub requirement: i16,
+
+    /// The position the demon will be placed at once published
+    pub position: Option<i16>,
+    pub verifier: Option<String>,
+    pub publisher: Option<String>,
+    pub creators: Vec<String>,
+    pub video: Option<String>,
+    pub level_id: Option<i64>,
+
+    /// The member id of the list moderator that created this draft
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostDemonDraft {
+    name: String,
+    requirement: i16,
+    #[serde(default)]
+    position: Option<i16>,
+    #[serde(default)]
+    verifier: Option<String>,
+    #[serde(default)]
+    publisher: Option<String>,
+    #[serde(default)]
+    creators: Vec<String>,
+    #[serde(default)]
+    video: Option<String>,
+    #[serde(default)]
+    level_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct PatchDemonDraft {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub requirement: Option<i16>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub position: Option<Option<i16>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub verifier: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub publisher: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub creators: Option<Vec<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub video: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub level_id: Option<Option<i64>>,
+}
+
+impl Validate for PostDemonDraft {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        self.name = normalize_name(&self.name);
+
+        for name in [&mut self.verifier, &mut self.publisher].into_iter().flatten() {
+            *name = normalize_name(name);
+        }
+
+        self.creators.iter_mut().for_each(|creator| *creator = normalize_name(creator));
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        validator.percentage("requirement", self.requirement, || DemonlistError::InvalidRequirement);
+
+        if let Some(position) = self.position {
+            validator.check("position", position > 0, || DemonlistError::InvalidPosition { maximal: i16::MAX });
+        }
+
+        if let Some(ref video) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
+
+        if let Some(level_id) = self.level_id {
+            validator.result("level_id", Demon::validate_level_id(level_id));
+        }
+    }
+}
+
+impl Validate for PatchDemonDraft {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        if let Some(ref mut name) = self.name {
+            *name = normalize_name(name);
+        }
+
+        for name in [&mut self.verifier, &mut self.publisher].into_iter().flatten().flatten() {
+            *name = normalize_name(name);
+        }
+
+        if let Some(ref mut creators) = self.creators {
+            creators.iter_mut().for_each(|creator| *creator = normalize_name(creator));
+        }
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        if let Some(requirement) = self.requirement {
+            validator.percentage("requirement", requirement, || DemonlistError::InvalidRequirement);
+        }
+
+        if let Some(Some(position)) = self.position {
+            validator.check("position", position > 0, || DemonlistError::InvalidPosition { maximal: i16::MAX });
+        }
+
+        if let Some(Some(ref video)) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
+
+        if let Some(Some(level_id)) = self.level_id {
+            validator.result("level_id", Demon::validate_level_id(level_id));
+        }
+    }
+}
+
+impl DemonDraft {
+    pub async fn by_id(draft_id: i32, connection: &mut impl ReadConnection) -> Result<DemonDraft> {
+        let connection = &mut connection.reader();
+        sqlx::query_as!(
+            DemonDraft,
+            r#"SELECT id, name::TEXT AS "name!", requirement, position, verifier::TEXT, publisher::TEXT, creators, video, level_id,
+                      created_by, created_at
+               FROM demon_drafts WHERE id = $1"#,
+            draft_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::DraftNotFound { draft_id })
+    }
+
+    /// All drafts, ordered by their intended position (drafts without position last)
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<DemonDraft>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            DemonDraft,
+            r#"SELECT id, name::TEXT AS "name!", requirement, position, verifier::TEXT, publisher::TEXT, creators, video, level_id,
+                      created_by, created_at
+               FROM demon_drafts ORDER BY position NULLS LAST, id"#
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+
+    pub async fn create(data: PostDemonDraft, created_by: i32, connection: &mut PgConnection) -> Result<DemonDraft> {
+        let data = validated(data)?;
+        let video = data.video.as_deref().map(crate::video::validate).transpose()?;
+
+        info!("Creating draft of demon {}", data.name);
+
+        let id = sqlx::query!(
+            "INSERT INTO demon_drafts (name, requirement, position, verifier, publisher, creators, video, level_id, created_by) VALUES \
+             ($1::TEXT, $2, $3, $4::TEXT, $5::TEXT, $6, $7, $8, $9) RETURNING id",
+            data.name,
+            data.requirement,
+            data.position,
+            data.verifier,
+            data.publisher,
+            &data.creators,
+            video,
+            data.level_id,
+            created_by
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        DemonDraft::by_id(id, connection).await
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchDemonDraft, connection: &mut PgConnection) -> Result<DemonDraft> {
+        let patch = validated(patch)?;
+
+        if let Some(name) = patch.name {
+            self.name = name;
+        }
+        if let Some(requirement) = patch.requirement {
+            self.requirement = requirement;
+        }
+        if let Some(position) = patch.position {
+            self.position = position;
+        }
+        if let Some(verifier) = patch.verifier {
+            self.verifier = verifier;
+        }
+        if let Some(publisher) = patch.publisher {
+            self.publisher = publisher;
+        }
+        if let Some(creators) = patch.creators {
+            self.creators = creators;
+        }
+        if let Some(video) = patch.video {
+            self.video = video.as_deref().map(crate::video::validate).transpose()?;
+        }
+        if let Some(level_id) = patch.level_id {
+            self.level_id = level_id;
+        }
+
+        sqlx::query!(
+            "UPDATE demon_drafts SET name = $1::TEXT, requirement = $2, position = $3, verifier = $4::TEXT, publisher = $5::TEXT, creators = \
+             $6, video = $7, level_id = $8 WHERE id = $9",
+            self.name,
+            self.requirement,
+            self.position,
+            self.verifier,
+            self.publisher,
+            &self.creators,
+            self.video,
+            self.level_id,
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting draft {} of demon {}", self.id, self.name);
+
+        sqlx::query!("DELETE FROM demon_drafts WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether the draft's position, verifier and publisher are set, which is required for
+    /// publishing it
+    pub fn is_complete(&self) -> bool {
+        self.position.is_some() && self.verifier.is_some() && self.publisher.is_some()
+    }
+
+    /// Adds the drafted demon to the list and deletes this draft. The draft's position, verifier
+    /// and publisher need to be set.
+    ///
+    /// Must run inside a transaction!
+    pub async fn publish(self, connection: &mut PgConnection) -> Result<FullDemon> {
+        let (Some(position), Some(verifier), Some(publisher)) = (self.position, self.verifier.clone(), self.publisher.clone()) else {
+            return Err(DemonlistError::IncompleteDraft);
+        };
+
+        info!("Publishing draft {} of demon {} at position {}", self.id, self.name, position);
+
+        let demon = FullDemon::create_from(
+            PostDemon {
+                name: self.name.clone(),
+                position,
+                requirement: self.requirement,
+                verifier,
+                publisher,
+                creators: self.creators.clone(),
+                video: self.video.clone(),
+                level_id: self.level_id,
+            },
+            &mut *connection,
+        )
+        .await?;
+
+        self.delete(connection).await?;
+
+        Ok(demon)
+    }
+}
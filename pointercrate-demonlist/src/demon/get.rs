@@ -1,16 +1,22 @@
 use crate::{
-    creator::creators_of,
-    demon::{Demon, FullDemon, MinimalDemon, TimeShiftedDemon},
+    config,
+    creator::{creators_by_role, creators_of},
+    demon::{
+        credit::{credits_of, CreditKind},
+        Demon, DemonCandidate, DemonId, DemonTier, FullDemon, ListSection, MinimalDemon, TimeShiftedDemon,
+    },
     error::{DemonlistError, Result},
-    player::DatabasePlayer,
+    player::{DatabasePlayer, PlayerId},
     record::approved_records_on,
 };
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
 use sqlx::{Error, PgConnection};
 
 impl MinimalDemon {
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<MinimalDemon> {
+    pub async fn by_id(DemonId(id): DemonId, connection: &mut impl ReadConnection) -> Result<MinimalDemon> {
+        let connection = &mut connection.reader();
         sqlx::query_as!(MinimalDemon, r#"SELECT id, name, position FROM demons WHERE id = $1"#, id)
             .fetch_one(connection)
             .await
@@ -20,67 +26,93 @@ impl MinimalDemon {
             })
     }
 
-    pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<MinimalDemon> {
-        let mut stream = sqlx::query!(r#"SELECT id, name, position FROM demons WHERE name = $1"#, name.to_string()).fetch(connection);
+    /// See [`Demon::by_name`]
+    pub async fn by_name(name: &str, connection: &mut impl ReadConnection) -> Result<MinimalDemon> {
+        let connection = &mut connection.reader();
+        Demon::by_name(name, connection).await.map(|demon| demon.base)
+    }
+}
 
-        let mut demon = None;
-        let mut further_demons = Vec::new();
+/// Picks the only demon out of the results of a name based lookup, erroring out if there is none or
+/// if the name is ambiguous
+fn unique_by_name(name: &str, mut demons: Vec<FetchedDemon>) -> Result<Demon> {
+    match demons.len() {
+        0 => Err(DemonlistError::DemonNotFoundName {
+            demon_name: name.to_string(),
+        }),
+        1 => Ok(demons.remove(0).into()),
+        _ => Err(DemonlistError::DemonNameNotUnique {
+            demons: demons
+                .into_iter()
+                .map(|fetched| {
+                    let demon = Demon::from(fetched);
 
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            let current_demon = MinimalDemon {
-                id: row.id,
-                position: row.position,
-                name: row.name,
-            };
-
-            if demon.is_none() {
-                demon = Some(current_demon)
-            } else {
-                further_demons.push(current_demon)
-            }
-        }
-
-        if further_demons.is_empty() {
-            match demon {
-                Some(demon) => Ok(demon),
-                None => Err(DemonlistError::DemonNotFoundName {
-                    demon_name: name.to_string(),
-                }),
-            }
-        } else {
-            further_demons.extend(demon);
-
-            Err(DemonlistError::DemonNameNotUnique { demons: further_demons })
-        }
+                    DemonCandidate {
+                        base: demon.base,
+                        publisher: demon.publisher,
+                    }
+                })
+                .collect(),
+        }),
     }
 }
 
 impl FullDemon {
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<FullDemon> {
-        Demon::by_id(id, connection).await?.upgrade(connection).await
+    pub async fn by_id(DemonId(id): DemonId, connection: &mut impl ReadConnection) -> Result<FullDemon> {
+        let connection = &mut connection.reader();
+        Demon::by_id(DemonId(id), connection).await?.upgrade(connection).await
     }
 
-    pub async fn by_position(position: i16, connection: &mut PgConnection) -> Result<FullDemon> {
+    /// Like [`FullDemon::by_id`], but additionally locks the demon's row until the current
+    /// transaction ends, so that its version cannot change between checking it against an
+    /// `If-Match` header and modifying the demon
+    pub async fn lock_by_id(DemonId(id): DemonId, connection: &mut PgConnection) -> Result<FullDemon> {
+        sqlx::query!("SELECT id FROM demons WHERE id = $1 FOR UPDATE", id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        FullDemon::by_id(DemonId(id), connection).await
+    }
+
+    pub async fn by_position(position: i16, connection: &mut impl ReadConnection) -> Result<FullDemon> {
+        let connection = &mut connection.reader();
         Demon::by_position(position, connection).await?.upgrade(connection).await
     }
+
+    /// See [`Demon::by_name`] and [`Demon::by_name_and_publisher`]
+    pub async fn by_name(name: &str, publisher: Option<&str>, connection: &mut impl ReadConnection) -> Result<FullDemon> {
+        let connection = &mut connection.reader();
+        let demon = match publisher {
+            Some(publisher) => Demon::by_name_and_publisher(name, publisher, connection).await?,
+            None => Demon::by_name(name, connection).await?,
+        };
+
+        demon.upgrade(connection).await
+    }
 }
 
 // FIXME: optimally, we want to only have one of these
 impl Demon {
-    async fn upgrade(self, connection: &mut PgConnection) -> Result<FullDemon> {
-        let creators = creators_of(&self.base, connection).await?;
+    async fn upgrade(self, connection: &mut impl ReadConnection) -> Result<FullDemon> {
+        let connection = &mut connection.reader();
+        let creators = creators_of(&self.base, &mut *connection).await?;
+        let creators_by_role = creators_by_role(&self.base, &mut *connection).await?;
+        let verifiers = credits_of(&self, CreditKind::Verifier, &mut *connection).await?;
+        let publishers = credits_of(&self, CreditKind::Publisher, &mut *connection).await?;
         let records = approved_records_on(&self.base, connection).await?;
 
         Ok(FullDemon {
             demon: self,
             creators,
+            creators_by_role,
+            verifiers,
+            publishers,
             records,
         })
     }
 
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Demon> {
+    pub async fn by_id(DemonId(id): DemonId, connection: &mut impl ReadConnection) -> Result<Demon> {
+        let connection = &mut connection.reader();
         sqlx::query_file_as!(FetchedDemon, "sql/demon_by_id.sql", id)
             .fetch_one(connection)
             .await
@@ -91,7 +123,32 @@ impl Demon {
             })
     }
 
-    pub async fn by_position(position: i16, connection: &mut PgConnection) -> Result<Demon> {
+    /// Looks up the demon with the given name (case insensitively).
+    ///
+    /// Since multiple demons can share a name, this fails with
+    /// [`DemonlistError::DemonNameNotUnique`], listing all candidates, if the name is ambiguous. Such
+    /// lookups can be disambiguated via [`Demon::by_name_and_publisher`].
+    pub async fn by_name(name: &str, connection: &mut impl ReadConnection) -> Result<Demon> {
+        let connection = &mut connection.reader();
+        let demons = sqlx::query_file_as!(FetchedDemon, "sql/demon_by_name.sql", name)
+            .fetch_all(connection)
+            .await?;
+
+        unique_by_name(name, demons)
+    }
+
+    /// Like [`Demon::by_name`], but only considers demons published by the given player
+    pub async fn by_name_and_publisher(name: &str, publisher: &str, connection: &mut impl ReadConnection) -> Result<Demon> {
+        let connection = &mut connection.reader();
+        let demons = sqlx::query_file_as!(FetchedDemon, "sql/demon_by_name_and_publisher.sql", name, publisher)
+            .fetch_all(connection)
+            .await?;
+
+        unique_by_name(name, demons)
+    }
+
+    pub async fn by_position(position: i16, connection: &mut impl ReadConnection) -> Result<Demon> {
+        let connection = &mut connection.reader();
         sqlx::query_file_as!(FetchedDemon, "sql/demon_by_position.sql", position)
             .fetch_one(connection)
             .await
@@ -101,6 +158,37 @@ impl Demon {
                 _ => err.into(),
             })
     }
+
+    /// Gets a uniformly random demon from the given section of the list (or from the entire list,
+    /// including the legacy list, if no section is given).
+    ///
+    /// Since list positions are always consecutive, this picks a random position and looks it up,
+    /// instead of having postgres shuffle the entire table.
+    pub async fn random(section: Option<ListSection>, connection: &mut impl ReadConnection) -> Result<Demon> {
+        let connection = &mut connection.reader();
+        let max_position = Demon::max_position(connection).await?;
+
+        let (lowest, highest) = match section {
+            None => (1, max_position),
+            Some(ListSection::Main) => (1, max_position.min(config::list_size())),
+            Some(ListSection::Extended) => (config::list_size() + 1, max_position.min(config::extended_list_size())),
+        };
+
+        if highest < lowest {
+            return Err(DemonlistError::DemonNotFoundPosition { demon_position: lowest });
+        }
+
+        let position = sqlx::query!(
+            r#"SELECT (FLOOR(RANDOM() * ($2::SMALLINT - $1::SMALLINT + 1)) + $1)::SMALLINT AS "position!""#,
+            lowest,
+            highest
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .position;
+
+        Demon::by_position(position, connection).await
+    }
 }
 
 macro_rules! query_many_demons {
@@ -116,19 +204,21 @@ macro_rules! query_many_demons {
     }};
 }
 
-pub async fn published_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+pub async fn published_by(player: &DatabasePlayer, connection: &mut impl ReadConnection) -> Result<Vec<MinimalDemon>> {
+    let connection = &mut connection.reader();
     query_many_demons!(
         connection,
         r#"SELECT id, name, position FROM demons WHERE publisher = $1"#,
-        player.id
+        player.id.0
     )
 }
 
-pub async fn verified_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+pub async fn verified_by(player: &DatabasePlayer, connection: &mut impl ReadConnection) -> Result<Vec<MinimalDemon>> {
+    let connection = &mut connection.reader();
     query_many_demons!(
         connection,
         r#"SELECT id, name, position FROM demons WHERE verifier = $1"#,
-        player.id
+        player.id.0
     )
 }
 
@@ -146,13 +236,21 @@ struct FetchedDemon {
     verifier_name: String,
     verifier_banned: bool,
     level_id: Option<i64>,
+    submissions_open: bool,
+    submissions_closed_reason: Option<String>,
+    discussion_url: Option<String>,
+    position_locked: bool,
+    score_weight: f64,
+    tier: Option<String>,
+    enjoyment: Option<f64>,
+    version: i32,
 }
 
 impl From<FetchedDemon> for Demon {
     fn from(fetched: FetchedDemon) -> Self {
         Demon {
             base: MinimalDemon {
-                id: fetched.demon_id,
+                id: DemonId(fetched.demon_id),
                 name: fetched.demon_name,
                 position: fetched.position,
             },
@@ -160,21 +258,30 @@ impl From<FetchedDemon> for Demon {
             video: fetched.video,
             thumbnail: fetched.thumbnail,
             publisher: DatabasePlayer {
-                id: fetched.publisher_id,
+                id: PlayerId(fetched.publisher_id),
                 name: fetched.publisher_name,
                 banned: fetched.publisher_banned,
             },
             verifier: DatabasePlayer {
-                id: fetched.verifier_id,
+                id: PlayerId(fetched.verifier_id),
                 name: fetched.verifier_name,
                 banned: fetched.verifier_banned,
             },
             level_id: fetched.level_id.map(|id| id as u64),
+            submissions_open: fetched.submissions_open,
+            submissions_closed_reason: fetched.submissions_closed_reason,
+            discussion_url: fetched.discussion_url,
+            position_locked: fetched.position_locked,
+            score_weight: fetched.score_weight,
+            tier: fetched.tier.as_deref().map(DemonTier::from_sql),
+            enjoyment: fetched.enjoyment,
+            version: fetched.version,
         }
     }
 }
 
-pub async fn current_list(connection: &mut PgConnection) -> Result<Vec<Demon>> {
+pub async fn current_list(connection: &mut impl ReadConnection) -> Result<Vec<Demon>> {
+    let connection = &mut connection.reader();
     Ok(sqlx::query_file_as!(FetchedDemon, "sql/all_demons.sql")
         .fetch_all(connection)
         .await?
@@ -183,7 +290,8 @@ pub async fn current_list(connection: &mut PgConnection) -> Result<Vec<Demon>> {
         .collect())
 }
 
-pub async fn list_at(connection: &mut PgConnection, at: NaiveDateTime) -> Result<Vec<TimeShiftedDemon>> {
+pub async fn list_at(connection: &mut impl ReadConnection, at: DateTime<Utc>) -> Result<Vec<TimeShiftedDemon>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query_file!("sql/all_demons_at.sql", at).fetch(connection);
     let mut demons = Vec::new();
 
@@ -193,7 +301,7 @@ pub async fn list_at(connection: &mut PgConnection, at: NaiveDateTime) -> Result
         demons.push(TimeShiftedDemon {
             current_demon: Demon {
                 base: MinimalDemon {
-                    id: row.demon_id,
+                    id: DemonId(row.demon_id),
                     position: row.position,
                     name: row.demon_name,
                 },
@@ -201,16 +309,24 @@ pub async fn list_at(connection: &mut PgConnection, at: NaiveDateTime) -> Result
                 video: row.video,
                 thumbnail: row.thumbnail,
                 publisher: DatabasePlayer {
-                    id: row.publisher_id,
+                    id: PlayerId(row.publisher_id),
                     name: row.publisher_name,
                     banned: row.publisher_banned,
                 },
                 verifier: DatabasePlayer {
-                    id: row.verifier_id,
+                    id: PlayerId(row.verifier_id),
                     name: row.verifier_name,
                     banned: row.verifier_banned,
                 },
                 level_id: row.level_id.map(|i| i as u64),
+                submissions_open: row.submissions_open,
+                submissions_closed_reason: row.submissions_closed_reason,
+                discussion_url: row.discussion_url,
+                position_locked: row.position_locked,
+                score_weight: row.score_weight,
+                tier: row.tier.as_deref().map(DemonTier::from_sql),
+                enjoyment: row.enjoyment,
+                version: row.version,
             },
             position_now: row.current_position,
         })
@@ -1,31 +1,45 @@
 pub use self::{
+    archive::{ArchivedDemon, RestoreDemon},
+    credit::{CreditKind, DemonCredit},
+    draft::{DemonDraft, PatchDemonDraft, PostDemonDraft},
     get::{current_list, list_at, published_by, verified_by},
-    paginate::{DemonIdPagination, DemonPositionPagination},
-    patch::PatchDemon,
+    paginate::{DemonIdPagination, DemonPositionPagination, DemonSortColumn},
     post::PostDemon,
+    reverification::{PatchReverification, PostReverification, Reverification},
+    search::{search_demons, DemonSearchResult},
 };
 use crate::{
+    creator::CreatorsByRole,
     error::{DemonlistError, Result},
     player::DatabasePlayer,
     record::MinimalRecordP,
+    score, LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use derive_more::Display;
 use log::info;
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{etag::Taggable, patch::Patchable, pool::ReadConnection};
 use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+use std::hash::{Hash, Hasher};
+use url::Url;
 
 #[macro_use]
 mod get;
+mod archive;
 pub mod audit;
+pub mod credit;
 mod delete;
+mod draft;
 mod paginate;
 mod patch;
 mod post;
+mod reverification;
+mod search;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`Demon`]. Unlike its position, this never changes
+    DemonId
+);
 
 pub struct TimeShiftedDemon {
     pub current_demon: Demon,
@@ -33,28 +47,131 @@ pub struct TimeShiftedDemon {
 }
 
 /// Struct modelling a demon. These objects are returned from the paginating `/demons/` endpoint
-#[derive(Debug, Deserialize, Serialize, Hash, Display, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Display, PartialEq, Patchable)]
 #[display(fmt = "{}", base)]
 pub struct Demon {
     #[serde(flatten)]
+    #[patch(rename = name, ty = String, permission = LIST_MODERATOR)]
+    #[patch(rename = position, ty = i16, permission = LIST_MODERATOR)]
     pub base: MinimalDemon,
 
     /// The minimal progress a [`Player`] must achieve on this [`Demon`] to have their record
     /// accepted
+    #[patch(permission = LIST_MODERATOR)]
     pub requirement: i16,
 
+    #[patch(permission = LIST_MODERATOR)]
     pub video: Option<String>,
 
+    #[patch(permission = LIST_MODERATOR)]
     pub thumbnail: String,
 
     /// This [`Demon`]'s publisher
+    #[patch(ty = String, permission = LIST_MODERATOR)]
     pub publisher: DatabasePlayer,
 
     /// This [`Demon`]'s verifier
+    #[patch(ty = String, permission = LIST_MODERATOR)]
     pub verifier: DatabasePlayer,
 
     /// This ['Demons']'s Geometry Dash level ID
+    #[patch(non_nullable, permission = LIST_MODERATOR)]
     pub level_id: Option<u64>,
+
+    /// Whether records can currently be submitted for this [`Demon`]. List moderators can
+    /// temporarily close submissions, e.g. while the demon is being re-verified
+    #[patch(permission = LIST_MODERATOR)]
+    pub submissions_open: bool,
+
+    /// The reason given for closing submissions, if any
+    #[patch(permission = LIST_MODERATOR)]
+    pub submissions_closed_reason: Option<String>,
+
+    /// Link to a dedicated discussion thread for this [`Demon`] (for example in a Discord forum
+    /// channel), see [`validate_discussion_url`]. The only attribute list helpers can modify
+    #[patch]
+    pub discussion_url: Option<String>,
+
+    /// Whether this [`Demon`]'s position is locked. Locked demons cannot be moved until a list
+    /// administrator unlocks them again, which guards top positions against accidental edits
+    #[patch(permission = LIST_ADMINISTRATOR)]
+    pub position_locked: bool,
+
+    /// Multiplier applied to the score awarded for records on this [`Demon`]. Defaults to `1.0`,
+    /// and can be adjusted by list administrators to de-emphasize (or emphasize) specific levels in
+    /// the stats viewer
+    #[patch(permission = LIST_ADMINISTRATOR)]
+    pub score_weight: f64,
+
+    /// The difficulty tier of this [`Demon`], for lists that track more than its position. Set by
+    /// list moderators, and `None` if unknown
+    #[patch(permission = LIST_MODERATOR)]
+    pub tier: Option<DemonTier>,
+
+    /// The average enjoyment rating (on a scale from 1 to 10) given in the approved records on this
+    /// [`Demon`]. Maintained by the database, and `None` if no approved record contains a rating
+    pub enjoyment: Option<f64>,
+
+    /// Incremented whenever one of this [`Demon`]'s patchable fields changes. Used as the `PATCH`
+    /// part of ETags
+    pub version: i32,
+}
+
+// `f64` does not implement hash, so only hash the first two digits after the dot of the score
+// weight and enjoyment, analogous to how `Player` handles its score.
+impl Hash for Demon {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base.hash(state);
+        self.requirement.hash(state);
+        self.video.hash(state);
+        self.thumbnail.hash(state);
+        self.publisher.hash(state);
+        self.verifier.hash(state);
+        self.level_id.hash(state);
+        self.submissions_open.hash(state);
+        self.submissions_closed_reason.hash(state);
+        self.discussion_url.hash(state);
+        self.position_locked.hash(state);
+        ((self.score_weight * 100f64) as u64).hash(state);
+        self.tier.hash(state);
+        self.enjoyment.map(|enjoyment| (enjoyment * 100f64) as u64).hash(state);
+        self.version.hash(state);
+    }
+}
+
+/// The difficulty tiers a [`Demon`] can be assigned, analogous to Geometry Dash's demon
+/// difficulties
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DemonTier {
+    EasyDemon,
+    MediumDemon,
+    HardDemon,
+    InsaneDemon,
+    ExtremeDemon,
+}
+
+impl DemonTier {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            DemonTier::EasyDemon => "easy_demon",
+            DemonTier::MediumDemon => "medium_demon",
+            DemonTier::HardDemon => "hard_demon",
+            DemonTier::InsaneDemon => "insane_demon",
+            DemonTier::ExtremeDemon => "extreme_demon",
+        }
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "easy_demon" => DemonTier::EasyDemon,
+            "medium_demon" => DemonTier::MediumDemon,
+            "hard_demon" => DemonTier::HardDemon,
+            "insane_demon" => DemonTier::InsaneDemon,
+            "extreme_demon" => DemonTier::ExtremeDemon,
+            _ => panic!("invalid demon tier: {}", sql),
+        }
+    }
 }
 
 /// Absolutely minimal representation of a demon to be sent when a demon is part of another object
@@ -62,7 +179,7 @@ pub struct Demon {
 #[display(fmt = "{} (at {})", name, position)]
 pub struct MinimalDemon {
     /// The [`Demon`]'s unique internal pointercrate ID
-    pub id: i32,
+    pub id: DemonId,
 
     /// The [`Demon`]'s position on the demonlist
     ///
@@ -75,24 +192,77 @@ pub struct MinimalDemon {
     pub name: String,
 }
 
+/// One of several [`Demon`]s matching an ambiguous name based lookup, together with the information
+/// needed to tell it apart from the others
+#[derive(Debug, Hash, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct DemonCandidate {
+    #[serde(flatten)]
+    pub base: MinimalDemon,
+    pub publisher: DatabasePlayer,
+}
+
+/// Compact representation of a demon on the list, containing only what is needed to render a list
+/// overview. These objects are returned from the paginating `/demons/listed/compact/` endpoint
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+pub struct ListedDemon {
+    pub name: String,
+    pub position: i16,
+    pub publisher: DatabasePlayer,
+    pub video: Option<String>,
+}
+
+impl From<Demon> for ListedDemon {
+    fn from(demon: Demon) -> Self {
+        ListedDemon {
+            name: demon.base.name,
+            position: demon.base.position,
+            publisher: demon.publisher,
+            video: demon.video,
+        }
+    }
+}
+
+/// The parts of the list a [`Demon`] can be in, based on its position
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSection {
+    /// Positions `1` to [`list_size`](crate::config::list_size)
+    Main,
+
+    /// Positions after the main list, up to [`extended_list_size`](crate::config::extended_list_size)
+    Extended,
+}
+
 /// Struct modelling the "full" version of a demon.
 ///
 /// In addition to containing publisher/verifier information it also contains a list of the demon's
 /// creators and a list of accepted records
-#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Eq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Hash)]
 #[display(fmt = "{}", demon)]
 pub struct FullDemon {
     #[serde(flatten)]
     pub demon: Demon,
     pub creators: Vec<DatabasePlayer>,
+
+    /// The same players as [`FullDemon::creators`], grouped by their [role](crate::creator::CreatorRole)
+    #[serde(default)]
+    pub creators_by_role: CreatorsByRole,
+
+    /// All of this demon's verifiers (see [`credit`]), the primary one (which is also
+    /// [`Demon::verifier`]) first
+    #[serde(default)]
+    pub verifiers: Vec<DemonCredit>,
+
+    /// All of this demon's publishers, the primary one (which is also [`Demon::publisher`]) first
+    #[serde(default)]
+    pub publishers: Vec<DemonCredit>,
+
     pub records: Vec<MinimalRecordP>, //wtf
 }
 
 impl Taggable for FullDemon {
     fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.demon.hash(&mut hasher);
-        hasher.finish()
+        self.demon.version as u64
     }
 }
 
@@ -100,7 +270,7 @@ impl MinimalDemon {
     /// Queries the record requirement for this demon from the database without collecting any of
     /// the other data
     pub async fn requirement(&self, connection: &mut PgConnection) -> Result<i16> {
-        Ok(sqlx::query!("SELECT requirement FROM demons WHERE id = $1", self.id)
+        Ok(sqlx::query!("SELECT requirement FROM demons WHERE id = $1", self.id.0)
             .fetch_one(connection)
             .await?
             .requirement)
@@ -117,8 +287,8 @@ impl FullDemon {
     }
 
     pub fn headline(&self) -> String {
-        let publisher = &self.demon.publisher.name;
-        let verifier = &self.demon.verifier.name;
+        let publisher = &joined_names(&self.publishers, &self.demon.publisher);
+        let verifier = &joined_names(&self.verifiers, &self.demon.verifier);
 
         let creator = match &self.creators[..] {
             [] => "Unknown".to_string(),
@@ -166,12 +336,13 @@ impl FullDemon {
     }
 
     pub fn short_headline(&self) -> String {
-        let demon = &self.demon;
+        let publisher = joined_names(&self.publishers, &self.demon.publisher);
+        let verifier = joined_names(&self.verifiers, &self.demon.verifier);
 
-        if demon.publisher == demon.verifier {
-            format!("verified and published by {}", demon.verifier.name)
+        if publisher == verifier {
+            format!("verified and published by {}", verifier)
         } else {
-            format!("published by {}, verified by {}", demon.publisher.name, demon.verifier.name)
+            format!("published by {}, verified by {}", publisher, verifier)
         }
     }
     /// Shifts all demons' positions by one, starting from the specified position.
@@ -187,11 +358,54 @@ impl FullDemon {
     }
 }
 
+/// The names of all credited players, e.g. "Riot, Zoink and Cursed". Falls back to the primary
+/// player if `credits` was not loaded
+fn joined_names(credits: &[DemonCredit], primary: &DatabasePlayer) -> String {
+    match credits {
+        [] => primary.name.to_string(),
+        [credit] => credit.player.name.to_string(),
+        [init @ .., last] => format!(
+            "{} and {}",
+            init.iter()
+                .map(|credit| credit.player.name.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            last.player.name
+        ),
+    }
+}
+
+/// Hosts discussion threads may be located on
+pub const DISCUSSION_HOSTS: &[&str] = &[
+    "discord.com",
+    "www.discord.com",
+    "ptb.discord.com",
+    "canary.discord.com",
+    "reddit.com",
+    "www.reddit.com",
+];
+
+/// Validates that the given URL is an `https` link to one of the [`DISCUSSION_HOSTS`]
+pub fn validate_discussion_url(url: &str) -> Result<String> {
+    let parsed = Url::parse(url).map_err(|_| DemonlistError::InvalidDiscussionUrl)?;
+
+    if parsed.scheme() != "https" || !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err(DemonlistError::InvalidDiscussionUrl);
+    }
+
+    match parsed.domain() {
+        Some(host) if DISCUSSION_HOSTS.contains(&host) => Ok(parsed.to_string()),
+        _ => Err(DemonlistError::InvalidDiscussionUrl),
+    }
+}
+
 impl Demon {
-    pub fn validate_requirement(requirement: i16) -> Result<()> {
-        if !(0..=100).contains(&requirement) {
-            return Err(DemonlistError::InvalidRequirement);
-        }
+    /// Re-reads this demon's [`version`](Demon::version) after it was modified
+    pub async fn reload_version(&mut self, connection: &mut PgConnection) -> Result<()> {
+        self.version = sqlx::query!("SELECT version FROM demons WHERE id = $1", self.base.id.0)
+            .fetch_one(connection)
+            .await?
+            .version;
 
         Ok(())
     }
@@ -230,7 +444,8 @@ impl Demon {
 
     /// Gets the current max position a demon has, or `0` if there are no demons
     /// in the database
-    pub async fn max_position(connection: &mut PgConnection) -> Result<i16> {
+    pub async fn max_position(connection: &mut impl ReadConnection) -> Result<i16> {
+        let connection = &mut connection.reader();
         Ok(sqlx::query!("SELECT MAX(position) as max_position FROM demons")
             .fetch_one(connection)
             .await?
@@ -250,6 +465,8 @@ impl Demon {
             _ => 0_f64,
         };
 
+        let beaten_score = beaten_score * self.score_weight * score::position_weight(position);
+
         if progress != 100 {
             (beaten_score * (5f64.powf((progress - self.requirement) as f64 / (100f64 - self.requirement as f64)))) / 10f64
         } else {
@@ -1,15 +1,16 @@
 use crate::{
-    demon::{Demon, MinimalDemon},
+    demon::{Demon, DemonTier, ListedDemon, MinimalDemon},
     player::DatabasePlayer,
 };
 use futures::stream::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{Keyset, PageContext, Paginatable, PaginationParameters, PaginationQuery, Sort, SortColumn, __pagination_compat},
+    pool::Reader,
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::Row;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DemonIdPagination {
@@ -45,6 +46,59 @@ pub struct DemonIdPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     requirement_lt: Option<i16>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    tier: Option<DemonTier>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "enjoyment__gt")]
+    enjoyment_gt: Option<f64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "enjoyment__lt")]
+    enjoyment_lt: Option<f64>,
+
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    sort: Option<Sort<DemonSortColumn>>,
+}
+
+/// The columns demons can be sorted by via the `sort` parameter of the `/demons/` endpoint
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DemonSortColumn {
+    Name,
+    Position,
+    Requirement,
+}
+
+impl SortColumn for DemonSortColumn {
+    const COLUMNS: &'static [(&'static str, Self)] = &[
+        ("name", DemonSortColumn::Name),
+        ("position", DemonSortColumn::Position),
+        ("requirement", DemonSortColumn::Requirement),
+    ];
+
+    fn expression(self) -> &'static str {
+        match self {
+            DemonSortColumn::Name => "demons.name",
+            DemonSortColumn::Position => "demons.position",
+            DemonSortColumn::Requirement => "demons.requirement",
+        }
+    }
+}
+
+impl DemonIdPagination {
+    fn keyset(&self) -> Keyset {
+        let keyset = Keyset::new(
+            "demons INNER JOIN players AS verifiers ON verifiers.id=demons.verifier INNER JOIN players AS publishers ON \
+             publishers.id=demons.publisher",
+            "demons.id",
+        );
+
+        match self.sort {
+            Some(sort) => sort.apply(keyset),
+            None => keyset,
+        }
+    }
 }
 
 impl PaginationQuery for DemonIdPagination {
@@ -63,10 +117,14 @@ impl PaginationQuery for DemonIdPagination {
 impl Paginatable<DemonIdPagination> for Demon {
     first_and_last!("demons");
 
-    async fn page(query: &DemonIdPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+    async fn page(query: &DemonIdPagination, connection: &mut Reader<'_>) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
+        let keyset = query.keyset();
 
-        let sql_query = format!(include_str!("../../sql/paginate_demons_by_id.sql"), order);
+        let sql_query = format!(
+            include_str!("../../sql/paginate_demons_by_id.sql"),
+            seek = keyset.seek_condition("$1", "$2"),
+            order = keyset.order_by(&query.params)
+        );
 
         // FIXME(sqlx) once CITEXT is supported
         let mut stream = sqlx::query(&sql_query)
@@ -82,7 +140,10 @@ impl Paginatable<DemonIdPagination> for Demon {
             .bind(query.publisher_name.as_deref())
             .bind(query.name_contains.as_deref())
             .bind(query.level_id)
-            .bind(query.params.limit + 1)
+            .bind(query.tier.map(DemonTier::to_sql))
+            .bind(query.enjoyment_lt)
+            .bind(query.enjoyment_gt)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut demons = Vec::new();
@@ -112,6 +173,14 @@ impl Paginatable<DemonIdPagination> for Demon {
                     banned: row.get("verifier_banned"),
                 },
                 level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
+                submissions_open: row.get("submissions_open"),
+                submissions_closed_reason: row.get("submissions_closed_reason"),
+                discussion_url: row.get("discussion_url"),
+                position_locked: row.get("position_locked"),
+                score_weight: row.get("score_weight"),
+                tier: row.get::<Option<&str>, _>("tier").map(DemonTier::from_sql),
+                enjoyment: row.get("enjoyment"),
+                version: row.get("version"),
             })
         }
 
@@ -119,7 +188,11 @@ impl Paginatable<DemonIdPagination> for Demon {
     }
 
     fn pagination_id(&self) -> i32 {
-        self.base.id
+        self.base.id.0
+    }
+
+    fn keyset(query: &DemonIdPagination) -> Option<Keyset> {
+        Some(query.keyset())
     }
 }
 
@@ -157,6 +230,17 @@ pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     pub requirement_lt: Option<i16>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub tier: Option<DemonTier>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "enjoyment__gt")]
+    pub enjoyment_gt: Option<f64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "enjoyment__lt")]
+    pub enjoyment_lt: Option<f64>,
 }
 
 impl PaginationQuery for DemonPositionPagination {
@@ -175,7 +259,7 @@ impl PaginationQuery for DemonPositionPagination {
 impl Paginatable<DemonPositionPagination> for Demon {
     first_and_last!("demons", "position");
 
-    async fn page(query: &DemonPositionPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
+    async fn page(query: &DemonPositionPagination, connection: &mut Reader<'_>) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../sql/paginate_demons_by_position.sql"), order);
@@ -194,7 +278,10 @@ impl Paginatable<DemonPositionPagination> for Demon {
             .bind(query.publisher_name.as_deref())
             .bind(query.name_contains.as_deref())
             .bind(query.level_id)
-            .bind(query.params.limit + 1)
+            .bind(query.tier.map(DemonTier::to_sql))
+            .bind(query.enjoyment_lt)
+            .bind(query.enjoyment_gt)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut demons = Vec::new();
@@ -224,6 +311,14 @@ impl Paginatable<DemonPositionPagination> for Demon {
                     banned: row.get("verifier_banned"),
                 },
                 level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
+                submissions_open: row.get("submissions_open"),
+                submissions_closed_reason: row.get("submissions_closed_reason"),
+                discussion_url: row.get("discussion_url"),
+                position_locked: row.get("position_locked"),
+                score_weight: row.get("score_weight"),
+                tier: row.get::<Option<&str>, _>("tier").map(DemonTier::from_sql),
+                enjoyment: row.get("enjoyment"),
+                version: row.get("version"),
             })
         }
 
@@ -234,3 +329,17 @@ impl Paginatable<DemonPositionPagination> for Demon {
         self.base.position as i32
     }
 }
+
+impl Paginatable<DemonPositionPagination> for ListedDemon {
+    first_and_last!("demons", "position");
+
+    async fn page(query: &DemonPositionPagination, connection: &mut Reader<'_>) -> Result<(Vec<ListedDemon>, PageContext), sqlx::Error> {
+        let (demons, context) = <Demon as Paginatable<DemonPositionPagination>>::page(query, connection).await?;
+
+        Ok((demons.into_iter().map(Into::into).collect(), context))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.position as i32
+    }
+}
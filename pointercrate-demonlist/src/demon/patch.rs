@@ -1,38 +1,45 @@
 use crate::{
-    demon::{Demon, FullDemon, MinimalDemon},
+    demon::{
+        credit::{credits_of, remove_redundant_credits, CreditKind},
+        validate_discussion_url, Demon, DemonTier, FullDemon, MinimalDemon, PatchDemon,
+    },
     error::{DemonlistError, Result},
     player::{recompute_scores, DatabasePlayer},
+    record::verification::sync_verification_record,
+    watch::{notify_watchers, WatchTarget},
 };
 use log::{debug, info, warn};
-use pointercrate_core::util::{non_nullable, nullable};
-use serde::Deserialize;
+use pointercrate_core::validate::{normalize_name, validated, Validate, Validator};
 use sqlx::PgConnection;
 
-#[derive(Deserialize, Debug, Default)]
-pub struct PatchDemon {
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub name: Option<String>,
+impl Validate for PatchDemon {
+    type Error = DemonlistError;
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub position: Option<i16>,
-
-    #[serde(default, deserialize_with = "nullable")]
-    pub video: Option<Option<String>>,
-
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub thumbnail: Option<String>,
+    fn normalize(&mut self) {
+        for name in [&mut self.name, &mut self.verifier, &mut self.publisher].into_iter().flatten() {
+            *name = normalize_name(name);
+        }
+    }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub requirement: Option<i16>,
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        if let Some(requirement) = self.requirement {
+            validator.percentage("requirement", requirement, || DemonlistError::InvalidRequirement);
+        }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub verifier: Option<String>,
+        if let Some(Some(ref video)) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub publisher: Option<String>,
+        if let Some(score_weight) = self.score_weight {
+            validator.check("score_weight", (0.0..=10.0).contains(&score_weight), || {
+                DemonlistError::InvalidScoreWeight
+            });
+        }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub level_id: Option<u64>,
+        if let Some(Some(ref discussion_url)) = self.discussion_url {
+            validator.result("discussion_url", validate_discussion_url(discussion_url));
+        }
+    }
 }
 
 impl FullDemon {
@@ -46,6 +53,8 @@ impl FullDemon {
         }
 
         Ok(FullDemon {
+            verifiers: credits_of(&updated_demon, CreditKind::Verifier, &mut *connection).await?,
+            publishers: credits_of(&updated_demon, CreditKind::Publisher, &mut *connection).await?,
             demon: updated_demon,
             ..self
         })
@@ -55,12 +64,26 @@ impl FullDemon {
 impl Demon {
     /// Must run inside a transaction!
     pub async fn apply_patch(mut self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
+        let patch = validated(patch)?;
+
+        info!("Applying patch {:?} for demon {}", patch, self);
+
         // duplicate names are OK nowadays
 
+        // A list administrator can unlock and move a demon in a single request. Locking and moving at
+        // the same time is fine too, as the move happens before the lock is applied
         if let Some(position) = patch.position {
+            if self.position_locked && patch.position_locked != Some(false) && position != self.base.position {
+                return Err(DemonlistError::DemonPositionLocked);
+            }
+
             self.base.mv(position, connection).await?;
         }
 
+        if let Some(position_locked) = patch.position_locked {
+            self.set_position_locked(position_locked, connection).await?;
+        }
+
         if let Some(name) = patch.name {
             self.base.set_name(name, connection).await?;
         }
@@ -95,19 +118,151 @@ impl Demon {
             self.set_level_id(level_id as i64, connection).await?;
         }
 
+        if patch.submissions_open.is_some() || patch.submissions_closed_reason.is_some() {
+            let open = patch.submissions_open.unwrap_or(self.submissions_open);
+            let reason = patch
+                .submissions_closed_reason
+                .unwrap_or_else(|| self.submissions_closed_reason.clone());
+
+            self.set_submissions_open(open, reason, connection).await?;
+        }
+
+        if let Some(discussion_url) = patch.discussion_url {
+            self.set_discussion_url(discussion_url, connection).await?;
+        }
+
+        if let Some(score_weight) = patch.score_weight {
+            self.set_score_weight(score_weight, connection).await?;
+        }
+
+        if let Some(tier) = patch.tier {
+            self.set_tier(tier, connection).await?;
+        }
+
+        let old_version = self.version;
+
+        self.reload_version(&mut *connection).await?;
+
+        if self.version != old_version {
+            notify_watchers(
+                WatchTarget::Demon(self.base.id),
+                format!("The demon {} you are watching has been modified", self.base.name),
+                Some(format!("/list/permalink/{}/", self.base.id)),
+                connection,
+            )
+            .await?;
+        }
+
         Ok(self)
     }
 
+    pub async fn set_position_locked(&mut self, position_locked: bool, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE demons SET position_locked = $1 WHERE id = $2",
+            position_locked,
+            self.base.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.position_locked = position_locked;
+
+        Ok(())
+    }
+
+    /// Swaps the positions of this demon and the given one
+    ///
+    /// Unlike two sequential moves, this happens in a single statement, so no other demon is
+    /// shifted around and the audit log only gains one entry per swapped demon.
+    ///
+    /// Must run inside a transaction, in which both demons have been locked (see
+    /// [`FullDemon::lock_by_id`]), as their positions are written back exactly as they were read!
+    pub async fn swap_with(&mut self, other: &mut Demon, connection: &mut PgConnection) -> Result<()> {
+        if self.position_locked || other.position_locked {
+            return Err(DemonlistError::DemonPositionLocked);
+        }
+
+        if self.base.id == other.base.id {
+            warn!("No-op swap of demon {} with itself", self.base);
+
+            return Ok(());
+        }
+
+        // The unique constraint on positions is deferrable, so it is only checked once the whole
+        // statement has run
+        sqlx::query!(
+            "UPDATE demons SET position = CASE WHEN id = $1 THEN $4::SMALLINT ELSE $3::SMALLINT END WHERE id = $1 OR id = $2",
+            self.base.id.0,
+            other.base.id.0,
+            self.base.position,
+            other.base.position
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        info!("Swapped demons {} and {}", self.base, other.base);
+
+        std::mem::swap(&mut self.base.position, &mut other.base.position);
+
+        recompute_scores(&mut *connection).await?;
+
+        for demon in [&mut *self, &mut *other] {
+            demon.reload_version(&mut *connection).await?;
+
+            notify_watchers(
+                WatchTarget::Demon(demon.base.id),
+                format!("The demon {} you are watching has been modified", demon.base.name),
+                Some(format!("/list/permalink/{}/", demon.base.id)),
+                &mut *connection,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Changes the weight of this demon in the score formula, and recomputes all scores afterwards
+    pub async fn set_score_weight(&mut self, score_weight: f64, connection: &mut PgConnection) -> Result<()> {
+        if score_weight != self.score_weight {
+            sqlx::query!("UPDATE demons SET score_weight = $1 WHERE id = $2", score_weight, self.base.id.0)
+                .execute(&mut *connection)
+                .await?;
+
+            recompute_scores(connection).await?;
+
+            self.score_weight = score_weight;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_tier(&mut self, tier: Option<DemonTier>, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE demons SET tier = $1 WHERE id = $2",
+            tier.map(DemonTier::to_sql),
+            self.base.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.tier = tier;
+
+        Ok(())
+    }
+
     pub async fn set_verifier(&mut self, verifier: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
         if verifier.id != self.verifier.id {
-            sqlx::query!("UPDATE demons SET verifier = $1 WHERE id = $2", verifier.id, self.base.id)
+            sqlx::query!("UPDATE demons SET verifier = $1 WHERE id = $2", verifier.id.0, self.base.id.0)
                 .execute(&mut *connection)
                 .await?;
 
-            self.verifier.update_score(connection).await?;
-            verifier.update_score(connection).await?;
+            let old_verifier = std::mem::replace(&mut self.verifier, verifier);
+
+            remove_redundant_credits(&mut *connection).await?;
+            sync_verification_record(self, connection).await?;
 
-            self.verifier = verifier;
+            old_verifier.update_score(connection).await?;
+            self.verifier.update_score(connection).await?;
         }
 
         Ok(())
@@ -115,11 +270,13 @@ impl Demon {
 
     pub async fn set_publisher(&mut self, publisher: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
         if publisher.id != self.publisher.id {
-            sqlx::query!("UPDATE demons SET publisher = $1 WHERE id = $2", publisher.id, self.base.id)
-                .execute(connection)
+            sqlx::query!("UPDATE demons SET publisher = $1 WHERE id = $2", publisher.id.0, self.base.id.0)
+                .execute(&mut *connection)
                 .await?;
 
             self.publisher = publisher;
+
+            remove_redundant_credits(connection).await?;
         }
 
         Ok(())
@@ -131,11 +288,15 @@ impl Demon {
         }
 
         // Delete associated notes
-        sqlx::query!("DELETE FROM records WHERE demon = $1 AND progress < $2", self.base.id, requirement)
-            .execute(&mut *connection)
-            .await?;
-
-        sqlx::query!("UPDATE demons SET requirement = $1 WHERE id = $2", requirement, self.base.id)
+        sqlx::query!(
+            "DELETE FROM records WHERE demon = $1 AND progress < $2",
+            self.base.id.0,
+            requirement
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!("UPDATE demons SET requirement = $1 WHERE id = $2", requirement, self.base.id.0)
             .execute(connection)
             .await?;
 
@@ -147,27 +308,31 @@ impl Demon {
     pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
         let video = crate::video::validate(&video)?;
 
-        sqlx::query!("UPDATE demons SET video = $1::text WHERE id = $2", video, self.base.id)
-            .execute(connection)
+        sqlx::query!("UPDATE demons SET video = $1::text WHERE id = $2", video, self.base.id.0)
+            .execute(&mut *connection)
             .await?;
 
         self.video = Some(video);
 
+        sync_verification_record(self, connection).await?;
+
         Ok(())
     }
 
     pub async fn remove_video(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE demons SET video = NULL WHERE id = $1", self.base.id)
-            .execute(connection)
+        sqlx::query!("UPDATE demons SET video = NULL WHERE id = $1", self.base.id.0)
+            .execute(&mut *connection)
             .await?;
 
         self.video = None;
 
+        sync_verification_record(self, connection).await?;
+
         Ok(())
     }
 
     pub async fn set_thumbnail(&mut self, thumbnail: String, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE demons SET thumbnail = $1::text WHERE id = $2", thumbnail, self.base.id)
+        sqlx::query!("UPDATE demons SET thumbnail = $1::text WHERE id = $2", thumbnail, self.base.id.0)
             .execute(connection)
             .await?;
 
@@ -176,7 +341,7 @@ impl Demon {
         Ok(())
     }
     pub async fn set_level_id(&mut self, level_id: i64, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE demons SET level_id = $1 WHERE id = $2", level_id, self.base.id)
+        sqlx::query!("UPDATE demons SET level_id = $1 WHERE id = $2", level_id, self.base.id.0)
             .execute(connection)
             .await?;
 
@@ -184,12 +349,48 @@ impl Demon {
 
         Ok(())
     }
+
+    /// Opens or closes submissions for this demon. Reopening submissions discards the reason
+    /// previously given for closing them
+    pub async fn set_submissions_open(&mut self, open: bool, reason: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        let reason = reason.filter(|reason| !open && !reason.trim().is_empty());
+
+        sqlx::query!(
+            "UPDATE demons SET submissions_open = $1, submissions_closed_reason = $2 WHERE id = $3",
+            open,
+            reason,
+            self.base.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.submissions_open = open;
+        self.submissions_closed_reason = reason;
+
+        Ok(())
+    }
+
+    pub async fn set_discussion_url(&mut self, discussion_url: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        let discussion_url = discussion_url.map(|url| validate_discussion_url(&url)).transpose()?;
+
+        sqlx::query!(
+            "UPDATE demons SET discussion_url = $1 WHERE id = $2",
+            discussion_url,
+            self.base.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.discussion_url = discussion_url;
+
+        Ok(())
+    }
 }
 
 impl MinimalDemon {
     pub async fn set_name(&mut self, name: String, connection: &mut PgConnection) -> Result<()> {
         if self.name != name {
-            sqlx::query!("UPDATE demons SET name = $1::text WHERE id = $2", name.to_string(), self.id)
+            sqlx::query!("UPDATE demons SET name = $1::text WHERE id = $2", name.to_string(), self.id.0)
                 .execute(connection)
                 .await?;
 
@@ -220,7 +421,7 @@ impl MinimalDemon {
         // FIXME: Temporarily move the demon somewhere else because otherwise the unique constraints
         // complains. I actually dont know why, its DEFERRABLE INITIALLY IMMEDIATE (whatever
         // that means, it made it work in the python version of the demonlist)
-        sqlx::query!("UPDATE demons SET position = -1 WHERE id = $1", self.id)
+        sqlx::query!("UPDATE demons SET position = -1 WHERE id = $1", self.id.0)
             .execute(&mut *connection)
             .await?;
 
@@ -254,7 +455,7 @@ impl MinimalDemon {
 
         debug!("Performing actual move to position {}", to);
 
-        sqlx::query!("UPDATE demons SET position = $2 WHERE id = $1", self.id, to)
+        sqlx::query!("UPDATE demons SET position = $2 WHERE id = $1", self.id.0, to)
             .execute(&mut *connection)
             .await?;
 
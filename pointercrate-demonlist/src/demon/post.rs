@@ -1,23 +1,48 @@
 use crate::{
-    creator::Creator,
-    demon::{Demon, FullDemon, MinimalDemon},
-    error::Result,
+    creator::{Creator, CreatorsByRole},
+    demon::{Demon, DemonCredit, DemonId, FullDemon, MinimalDemon},
+    error::{DemonlistError, Result},
     player::{recompute_scores, DatabasePlayer},
+    record::verification::sync_verification_record,
 };
 use log::info;
+use pointercrate_core::validate::{normalize_name, validated, Validate, Validator};
 use serde::Deserialize;
 use sqlx::PgConnection;
 
 #[derive(Deserialize, Debug)]
 pub struct PostDemon {
-    name: String,
-    position: i16,
-    requirement: i16,
-    verifier: String,
-    publisher: String,
-    creators: Vec<String>,
-    video: Option<String>,
-    level_id: Option<i64>,
+    pub(crate) name: String,
+    pub(crate) position: i16,
+    pub(crate) requirement: i16,
+    pub(crate) verifier: String,
+    pub(crate) publisher: String,
+    pub(crate) creators: Vec<String>,
+    pub(crate) video: Option<String>,
+    pub(crate) level_id: Option<i64>,
+}
+
+impl Validate for PostDemon {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        self.name = normalize_name(&self.name);
+        self.verifier = normalize_name(&self.verifier);
+        self.publisher = normalize_name(&self.publisher);
+        self.creators.iter_mut().for_each(|creator| *creator = normalize_name(creator));
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        validator.percentage("requirement", self.requirement, || DemonlistError::InvalidRequirement);
+
+        if let Some(level_id) = self.level_id {
+            validator.result("level_id", Demon::validate_level_id(level_id));
+        }
+
+        if let Some(ref video) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
+    }
 }
 
 impl FullDemon {
@@ -25,8 +50,8 @@ impl FullDemon {
     pub async fn create_from(data: PostDemon, connection: &mut PgConnection) -> Result<FullDemon> {
         info!("Creating new demon from {:?}", data);
 
-        Demon::validate_requirement(data.requirement)?;
-        let level_id = data.level_id.map(Demon::validate_level_id).transpose()?;
+        let data = validated(data)?;
+        let level_id = data.level_id.map(|level_id| level_id as u64);
 
         let video = match data.video {
             Some(ref video) => Some(crate::video::validate(video)?),
@@ -42,13 +67,13 @@ impl FullDemon {
 
         let created = sqlx::query!(
             "INSERT INTO demons (name, position, requirement, video, verifier, publisher, level_id) VALUES ($1::text,$2,$3,$4::text,$5,$6,$7) \
-             RETURNING id, thumbnail",
+             RETURNING id, thumbnail, version",
             data.name.to_string(),
             data.position,
             data.requirement,
             video.as_ref(),
-            verifier.id,
-            publisher.id,
+            verifier.id.0,
+            publisher.id.0,
             data.level_id
         )
         .fetch_one(&mut *connection)
@@ -56,7 +81,7 @@ impl FullDemon {
 
         let demon = Demon {
             base: MinimalDemon {
-                id: created.id,
+                id: DemonId(created.id),
                 position: data.position,
                 name: data.name,
             },
@@ -66,6 +91,14 @@ impl FullDemon {
             publisher,
             verifier,
             level_id,
+            submissions_open: true,
+            submissions_closed_reason: None,
+            discussion_url: None,
+            position_locked: false,
+            score_weight: 1.0,
+            tier: None,
+            enjoyment: None,
+            version: created.version,
         };
 
         let mut creators = Vec::new();
@@ -77,10 +110,20 @@ impl FullDemon {
             creators.push(player);
         }
 
+        sync_verification_record(&demon, connection).await?;
         recompute_scores(connection).await?;
 
         Ok(FullDemon {
+            verifiers: vec![DemonCredit {
+                player: demon.verifier.clone(),
+                primary: true,
+            }],
+            publishers: vec![DemonCredit {
+                player: demon.publisher.clone(),
+                primary: true,
+            }],
             demon,
+            creators_by_role: CreatorsByRole::unspecified(&creators),
             creators,
             records: Vec::new(),
         })
@@ -89,6 +132,7 @@ impl FullDemon {
 
 #[cfg(test)]
 mod tests {
+    use pointercrate_core::error::CoreError;
     use sqlx::{pool::PoolConnection, Postgres};
 
     use crate::{
@@ -186,4 +230,56 @@ mod tests {
 
         assert_eq!(error, DemonlistError::InvalidLevelId);
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_multiple_invalid_fields(mut conn: PoolConnection<Postgres>) {
+        let error = FullDemon::create_from(
+            PostDemon {
+                name: "Bloodbath".to_owned(),
+                position: 1,
+                requirement: 101,
+                verifier: "Riot".to_owned(),
+                publisher: "Riot".to_owned(),
+                creators: Vec::new(),
+                video: None,
+                level_id: Some(-1),
+            },
+            &mut conn,
+        )
+        .await
+        .unwrap_err();
+
+        match error {
+            DemonlistError::Core(CoreError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors.iter().map(|error| error.field).collect::<Vec<_>>(),
+                    vec!["requirement", "level_id"]
+                )
+            },
+            _ => panic!("Unexpected error {:?}", error),
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_names_are_normalized(mut conn: PoolConnection<Postgres>) {
+        let demon = FullDemon::create_from(
+            PostDemon {
+                name: " Sonic   Wave ".to_owned(),
+                position: 1,
+                requirement: 90,
+                verifier: " Riot".to_owned(),
+                publisher: "Riot ".to_owned(),
+                creators: Vec::new(),
+                video: None,
+                level_id: None,
+            },
+            &mut conn,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(demon.demon.base.name, "Sonic Wave");
+        assert_eq!(demon.demon.verifier.name, "Riot");
+        assert_eq!(demon.demon.verifier, demon.demon.publisher);
+    }
 }
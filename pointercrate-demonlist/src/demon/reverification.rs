@@ -0,0 +1,204 @@
+//! Re-verification of demons
+//!
+//! When a game update breaks a demon (for example by changing its physics), list moderators can mark
+//! it as requiring re-verification. This closes submissions for the demon until a new verification
+//! has been found. While the re-verification is open, moderators track the candidate verifier and
+//! verification video on it. Completing the re-verification replaces the demon's verifier and video
+//! with the candidates and reopens submissions, all in the same transaction.
+//!
+//! Re-verifications remember which list moderators requested and completed them, and the changes
+//! made to the demon itself show up in its regular audit log.
+
+use crate::{
+    demon::Demon,
+    error::{DemonlistError, Result},
+    player::{DatabasePlayer, PlayerId},
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::{pool::ReadConnection, util::nullable, validate::normalize_name};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// The reason shown to submitters if none was given when requesting the re-verification
+const DEFAULT_CLOSED_REASON: &str = "This demon is being re-verified";
+
+#[derive(Debug, Serialize, PartialEq, Eq, Hash)]
+pub struct Reverification {
+    pub id: i32,
+    pub demon: i32,
+    pub reason: Option<String>,
+    pub candidate_verifier: Option<DatabasePlayer>,
+    pub candidate_video: Option<String>,
+
+    /// The member id of the list moderator that requested this re-verification
+    pub requested_by: Option<i32>,
+    pub requested_at: DateTime<Utc>,
+
+    /// The member id of the list moderator that completed this re-verification
+    pub completed_by: Option<i32>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostReverification {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchReverification {
+    /// The name of the player that is expected to re-verify the demon
+    #[serde(default, deserialize_with = "nullable")]
+    pub candidate_verifier: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub candidate_video: Option<Option<String>>,
+}
+
+impl Reverification {
+    /// The re-verification currently in progress for the given demon
+    pub async fn open_for(demon_id: i32, connection: &mut impl ReadConnection) -> Result<Reverification> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            r#"SELECT demon_reverifications.id, reason, candidate_video, requested_by, requested_at, completed_by, completed_at,
+                      players.id AS "verifier_id?", players.name::TEXT AS verifier_name, players.banned AS "verifier_banned?"
+               FROM demon_reverifications
+               LEFT OUTER JOIN players ON players.id = candidate_verifier
+               WHERE demon = $1 AND completed_at IS NULL"#,
+            demon_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::ReverificationNotFound { demon_id })?;
+
+        let candidate_verifier = match (row.verifier_id, row.verifier_name, row.verifier_banned) {
+            (Some(id), Some(name), Some(banned)) => Some(DatabasePlayer {
+                id: PlayerId(id),
+                name,
+                banned,
+            }),
+            _ => None,
+        };
+
+        Ok(Reverification {
+            id: row.id,
+            demon: demon_id,
+            reason: row.reason,
+            candidate_verifier,
+            candidate_video: row.candidate_video,
+            requested_by: row.requested_by,
+            requested_at: row.requested_at,
+            completed_by: row.completed_by,
+            completed_at: row.completed_at,
+        })
+    }
+
+    /// Marks the given demon as requiring re-verification, closing submissions for it
+    ///
+    /// Must run inside a transaction!
+    pub async fn request(
+        demon: &mut Demon, data: PostReverification, requested_by: i32, connection: &mut PgConnection,
+    ) -> Result<Reverification> {
+        let reason = data
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+
+        let in_progress = sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM demon_reverifications WHERE demon = $1 AND completed_at IS NULL) AS "exists!""#,
+            demon.base.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .exists;
+
+        if in_progress {
+            return Err(DemonlistError::ReverificationInProgress);
+        }
+
+        info!("Requesting re-verification of {} (reason: {:?})", demon, reason);
+
+        let row = sqlx::query!(
+            "INSERT INTO demon_reverifications (demon, reason, requested_by) VALUES ($1, $2, $3) RETURNING id, requested_at",
+            demon.base.id.0,
+            reason,
+            requested_by
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        let closed_reason = reason.clone().unwrap_or_else(|| DEFAULT_CLOSED_REASON.to_string());
+
+        demon.set_submissions_open(false, Some(closed_reason), &mut *connection).await?;
+        demon.reload_version(connection).await?;
+
+        Ok(Reverification {
+            id: row.id,
+            demon: demon.base.id.0,
+            reason,
+            candidate_verifier: None,
+            candidate_video: None,
+            requested_by: Some(requested_by),
+            requested_at: row.requested_at,
+            completed_by: None,
+            completed_at: None,
+        })
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchReverification, connection: &mut PgConnection) -> Result<Reverification> {
+        if let Some(candidate_verifier) = patch.candidate_verifier {
+            self.candidate_verifier = match candidate_verifier {
+                Some(name) => Some(DatabasePlayer::by_name_or_create(&normalize_name(&name), &mut *connection).await?),
+                None => None,
+            };
+        }
+
+        if let Some(candidate_video) = patch.candidate_video {
+            self.candidate_video = candidate_video.map(|video| crate::video::validate(&video)).transpose()?;
+        }
+
+        sqlx::query!(
+            "UPDATE demon_reverifications SET candidate_verifier = $1, candidate_video = $2 WHERE id = $3",
+            self.candidate_verifier.as_ref().map(|player| player.id.0),
+            self.candidate_video,
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+
+    /// Replaces the given demon's verification with the candidate one and reopens submissions for
+    /// it. Both a candidate verifier and a candidate video need to be set.
+    ///
+    /// Must run inside a transaction!
+    pub async fn complete(mut self, demon: &mut Demon, completed_by: i32, connection: &mut PgConnection) -> Result<Reverification> {
+        let (Some(verifier), Some(video)) = (self.candidate_verifier.clone(), self.candidate_video.clone()) else {
+            return Err(DemonlistError::IncompleteReverification);
+        };
+
+        info!("Completing re-verification of {} by {} ({})", demon, verifier, video);
+
+        demon.set_verifier(verifier, &mut *connection).await?;
+        demon.set_video(video, &mut *connection).await?;
+        demon.set_submissions_open(true, None, &mut *connection).await?;
+        demon.reload_version(&mut *connection).await?;
+
+        self.completed_at = Some(
+            sqlx::query!(
+                "UPDATE demon_reverifications SET completed_by = $1, completed_at = NOW() WHERE id = $2 RETURNING \
+                 completed_at AS \"completed_at!\"",
+                completed_by,
+                self.id
+            )
+            .fetch_one(connection)
+            .await?
+            .completed_at,
+        );
+        self.completed_by = Some(completed_by);
+
+        Ok(self)
+    }
+}
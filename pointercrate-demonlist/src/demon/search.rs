@@ -0,0 +1,77 @@
+//! Search-as-you-type for demons
+//!
+//! Meant to be called on every keystroke (for example by the record submission form), so the
+//! search is a single query answered from the trigram index on demon names. Matches are ranked by
+//! trigram distance, which tolerates typos, but for very short queries (with few trigrams) only
+//! prefix matches would make it past the similarity threshold. Hence prefix matches are always
+//! included.
+
+use crate::{
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+    player::{DatabasePlayer, PlayerId},
+};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+
+/// The maximal number of results returned by [`search_demons`]
+pub const SEARCH_RESULTS: i64 = 10;
+
+/// Queries longer than this are truncated, as they are unlikely to be typed by hand
+const MAX_QUERY_LENGTH: usize = 64;
+
+#[derive(Debug, Serialize)]
+pub struct DemonSearchResult {
+    #[serde(flatten)]
+    pub demon: MinimalDemon,
+    pub publisher: DatabasePlayer,
+}
+
+/// The (at most [`SEARCH_RESULTS`]) demons whose name best matches the given query, best match
+/// first. Empty if the query is blank
+pub async fn search_demons(query: &str, connection: &mut impl ReadConnection) -> Result<Vec<DemonSearchResult>> {
+    let connection = &mut connection.reader();
+    let query: String = query.trim().chars().take(MAX_QUERY_LENGTH).collect();
+
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}%", query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+
+    let mut stream = sqlx::query!(
+        r#"SELECT demons.id, demons.name AS "name: String", demons.position, players.id AS publisher_id,
+                  players.name AS "publisher_name: String", players.banned AS publisher_banned
+           FROM demons
+           INNER JOIN players ON demons.publisher = players.id
+           WHERE demons.name::TEXT % $1 OR demons.name::TEXT ILIKE $2
+           ORDER BY demons.name::TEXT <-> $1, demons.position
+           LIMIT $3"#,
+        query,
+        prefix,
+        SEARCH_RESULTS
+    )
+    .fetch(connection);
+
+    let mut results = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        results.push(DemonSearchResult {
+            demon: MinimalDemon {
+                id: DemonId(row.id),
+                position: row.position,
+                name: row.name,
+            },
+            publisher: DatabasePlayer {
+                id: PlayerId(row.publisher_id),
+                name: row.publisher_name,
+                banned: row.publisher_banned,
+            },
+        })
+    }
+
+    Ok(results)
+}
@@ -1,4 +1,8 @@
-use crate::{demon::MinimalDemon, record::RecordStatus};
+use crate::{
+    demon::{credit::CreditKind, DemonCandidate},
+    record::RecordStatus,
+};
+use chrono::{DateTime, Utc};
 use derive_more::Display;
 
 use pointercrate_core::error::{CoreError, PointercrateError};
@@ -38,15 +42,39 @@ pub enum DemonlistError {
     #[display(fmt = "This player has requested that only they themselves can submit their records")]
     NoThirdPartySubmissions,
 
+    /// `403 FORBIDDEN` variant returned if someone other than a record's submitter tries to appeal
+    /// its rejection
+    ///
+    /// Error Code `40310`
+    #[display(fmt = "Only the submitter of a record can appeal its rejection")]
+    NotRecordSubmitter,
+
+    /// `403 FORBIDDEN` variant returned if a staff member tries to resolve the appeal against a
+    /// rejection they made themselves
+    ///
+    /// Error Code `40311`
+    #[display(fmt = "Appeals have to be resolved by someone other than the staff member who rejected the record")]
+    OwnRejection,
+
     #[display(fmt = "No submitter with id {} found", id)]
     SubmitterNotFound { id: i32 },
 
+    #[display(fmt = "No submitter with anonymized id {} found", anonymous_id)]
+    AnonymousSubmitterNotFound { anonymous_id: String },
+
     #[display(fmt = "No note with id {} found on record with id {}", note_id, record_id)]
     NoteNotFound { note_id: i32, record_id: i32 },
 
     #[display(fmt = "Player with id {} is no creator of demon with id {}", player_id, demon_id)]
     CreatorNotFound { demon_id: i32, player_id: i32 },
 
+    /// `404 NOT FOUND` variant returned if a player is not credited as additional verifier or
+    /// publisher of a demon (this includes the primary ones, which cannot be removed)
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "Player with id {} is no additional {} of demon with id {}", player_id, kind, demon_id)]
+    CreditNotFound { demon_id: i32, player_id: i32, kind: CreditKind },
+
     #[display(fmt = "No nationality with iso code {} found", iso_code)]
     NationalityNotFound { iso_code: String },
 
@@ -74,6 +102,18 @@ pub enum DemonlistError {
     #[display(fmt = "No claim by user {} on player {} found", member_id, player_id)]
     ClaimNotFound { member_id: i32, player_id: i32 },
 
+    #[display(fmt = "No report with id {} found", report_id)]
+    ReportNotFound { report_id: i32 },
+
+    #[display(fmt = "Player with id {} has no alias '{}'", player_id, alias)]
+    AliasNotFound { player_id: i32, alias: String },
+
+    /// `404 NOT FOUND` variant returned if no re-verification is in progress for a demon
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No re-verification is in progress for the demon with id {}", demon_id)]
+    ReverificationNotFound { demon_id: i32 },
+
     #[display(fmt = "This player is already registered as a creator on this demon")]
     CreatorExists,
 
@@ -90,6 +130,105 @@ pub enum DemonlistError {
     )]
     ConflictingClaims { player1: String, player2: String },
 
+    /// `409 CONFLICT` variant returned if a player alias is already in use, either as another alias
+    /// or as the name of a player
+    ///
+    /// Error Code `40909`
+    #[display(fmt = "The name '{}' is already in use by a player or alias", alias)]
+    AliasTaken { alias: String },
+
+    /// `404 NOT FOUND` variant returned if no demon draft with the given id exists
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No demon draft with id {} found", draft_id)]
+    DraftNotFound { draft_id: i32 },
+
+    /// `404 NOT FOUND` variant returned if no scheduled list update with the given id exists
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No scheduled list update with id {} found", update_id)]
+    ScheduledUpdateNotFound { update_id: i32 },
+
+    /// `409 CONFLICT` variant returned if a scheduled list update that was already applied (or
+    /// failed) is cancelled
+    ///
+    /// Error Code `40913`
+    #[display(fmt = "This list update has already been carried out and can no longer be cancelled")]
+    ScheduledUpdateNotPending,
+
+    /// `404 NOT FOUND` variant returned if the current user has no watch with the given id
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No watch with id {} found", watch_id)]
+    WatchNotFound { watch_id: i32 },
+
+    /// `409 CONFLICT` variant returned if a user tries to watch an object they are already watching
+    ///
+    /// Error Code `40914`
+    #[display(fmt = "You are already watching this object")]
+    AlreadyWatching,
+
+    /// `404 NOT FOUND` variant returned if no record appeal with the given id exists
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No appeal with id {} found", appeal_id)]
+    AppealNotFound { appeal_id: i32 },
+
+    /// `409 CONFLICT` variant returned if the rejection of a record that was already appealed is
+    /// appealed again
+    ///
+    /// Error Code `40915`
+    #[display(fmt = "The rejection of this record has already been appealed")]
+    AlreadyAppealed,
+
+    /// `409 CONFLICT` variant returned if an appeal that was already accepted or denied is resolved
+    /// again
+    ///
+    /// Error Code `40916`
+    #[display(fmt = "This appeal has already been resolved")]
+    AppealResolved,
+
+    /// `409 CONFLICT` variant returned if a verification record is modified or deleted via the
+    /// record endpoints
+    ///
+    /// Error Code `40917`
+    #[display(fmt = "Verification records are maintained automatically. Change the demon's verifier or video instead")]
+    VerificationRecord,
+
+    /// `404 NOT FOUND` variant returned if no archived demon with the given id exists
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No archived demon with id {} found", demon_id)]
+    ArchivedDemonNotFound { demon_id: i32 },
+
+    /// `404 NOT FOUND` variant returned if the given player has no avatar (or, for requests not
+    /// made by moderators, no approved avatar)
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "Player with id {} has no avatar", player_id)]
+    AvatarNotFound { player_id: i32 },
+
+    /// `409 CONFLICT` variant returned if an archived demon is restored while another demon on the
+    /// list has the same level id
+    ///
+    /// Error Code `40918`
+    #[display(fmt = "Another demon on the list has the same level id as this archived demon")]
+    ArchivedLevelIdTaken,
+
+    /// `409 CONFLICT` variant returned if a player is credited as verifier or publisher of a demon
+    /// twice
+    ///
+    /// Error Code `40919`
+    #[display(fmt = "This player is already credited as {} of this demon", kind)]
+    CreditExists { kind: CreditKind },
+
+    /// `409 CONFLICT` variant returned if re-verification is requested for a demon that is already
+    /// being re-verified
+    ///
+    /// Error Code `40911`
+    #[display(fmt = "This demon is already being re-verified")]
+    ReverificationInProgress,
+
     /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to create a demon with a record
     /// requirements outside of [0, 100]
     ///
@@ -157,11 +296,13 @@ pub enum DemonlistError {
     #[display(fmt = "The given video host is not supported. Supported are 'youtube', 'vimeo', 'everyplay', 'twitch' and 'bilibili'")]
     UnsupportedVideoHost,
 
-    /// `422 UNPROCESSABLE ENTITY` variant
+    /// `409 CONFLICT` variant returned if a demon was looked up by a name shared by multiple
+    /// demons. Lists all candidates, so that the lookup can be repeated with the publisher
+    /// specified
     ///
-    /// Error Code `42228`
-    #[display(fmt = "There are multiple demons with the given name")]
-    DemonNameNotUnique { demons: Vec<MinimalDemon> },
+    /// Error Code `40910`
+    #[display(fmt = "There are multiple demons with the given name, specify the publisher to disambiguate")]
+    DemonNameNotUnique { demons: Vec<DemonCandidate> },
 
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
@@ -186,6 +327,203 @@ pub enum DemonlistError {
     /// Error Code `42235`
     #[display(fmt = "Level ID needs to be positive")]
     InvalidLevelId,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a report targets neither a record nor a
+    /// player
+    ///
+    /// Error Code `42238`
+    #[display(fmt = "A report needs to target either a record or a player")]
+    NoReportTarget,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a report's category does not apply to the
+    /// kind of object being reported (e.g. reporting a player as hacked)
+    ///
+    /// Error Code `42239`
+    #[display(fmt = "The given report category cannot be used for this kind of report")]
+    InvalidReportCategory,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a report's description is empty or longer
+    /// than 2000 characters
+    ///
+    /// Error Code `42240`
+    #[display(fmt = "A report's description must be between 1 and 2000 characters long")]
+    InvalidReportDescription,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon's discussion link is not an `https` link to
+    /// one of the allowed hosts (see [`DISCUSSION_HOSTS`](crate::demon::DISCUSSION_HOSTS))
+    ///
+    /// Error Code `42245`
+    #[display(fmt = "Discussion links must point to a Discord or Reddit thread")]
+    InvalidDiscussionUrl,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record's enjoyment rating is not between 1
+    /// and 10
+    ///
+    /// Error Code `42246`
+    #[display(fmt = "Enjoyment ratings must be between 1 and 10")]
+    InvalidEnjoyment,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the timestamp of a completion within its video
+    /// is not of the form `[[h:]m:]s`
+    ///
+    /// Error Code `42247`
+    #[display(fmt = "Video timestamps need to be of the form 'hh:mm:ss', 'mm:ss' or a number of seconds")]
+    InvalidVideoTimestamp,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a list moderator tries to move a demon whose
+    /// position has been locked by a list administrator
+    ///
+    /// Error Code `42248`
+    #[display(fmt = "This demon's position is locked. A list administrator needs to unlock it before it can be moved")]
+    DemonPositionLocked,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon's score weight is negative, or larger
+    /// than 10
+    ///
+    /// Error Code `42249`
+    #[display(fmt = "Score weights must be between 0 and 10")]
+    InvalidScoreWeight,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a re-verification is completed before both a
+    /// candidate verifier and a candidate video have been set
+    ///
+    /// Error Code `42253`
+    #[display(fmt = "A re-verification can only be completed once both a new verifier and a new verification video have been set")]
+    IncompleteReverification,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record import is missing one of the
+    /// required columns
+    ///
+    /// Error Code `42254`
+    #[display(fmt = "The import is missing the required column '{}'", column)]
+    MissingImportColumn { column: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned for rows of a record import whose value in some
+    /// column could not be parsed
+    ///
+    /// Error Code `42255`
+    #[display(fmt = "Malformed value in column '{}'", column)]
+    MalformedImportValue { column: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a list snapshot of an unsupported format
+    /// version is imported
+    ///
+    /// Error Code `42256`
+    #[display(fmt = "Unsupported snapshot version {}, expected version {}", found, expected)]
+    SnapshotVersionMismatch { expected: u32, found: u32 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon draft is published before its
+    /// position, verifier and publisher have been set
+    ///
+    /// Error Code `42257`
+    #[display(fmt = "A draft can only be published once its position, verifier and publisher have been set")]
+    IncompleteDraft,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a list update without any changes is
+    /// scheduled
+    ///
+    /// Error Code `42258`
+    #[display(fmt = "A scheduled list update needs to contain at least one change")]
+    EmptyScheduledUpdate,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a watch targets neither a demon nor a player
+    ///
+    /// Error Code `42259`
+    #[display(fmt = "A watch needs to target either a demon or a player")]
+    NoWatchTarget,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an appeal is filed against a record that is
+    /// not rejected
+    ///
+    /// Error Code `42260`
+    #[display(fmt = "Only rejected records can be appealed")]
+    RecordNotRejected,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the reason given for an appeal is empty or
+    /// longer than 2000 characters
+    ///
+    /// Error Code `42261`
+    #[display(fmt = "The reason of an appeal must be between 1 and 2000 characters long")]
+    InvalidAppealReason,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an appeal is resolved without a decision
+    ///
+    /// Error Code `42262`
+    #[display(fmt = "Appeals can only be resolved by accepting or denying them")]
+    InvalidAppealResolution,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an uploaded avatar is not a PNG, JPEG or WebP
+    /// image, or if it is larger than 4096x4096 pixels
+    ///
+    /// Error Code `42266`
+    #[display(fmt = "Avatars need to be PNG, JPEG or WebP images of at most 4096x4096 pixels")]
+    InvalidAvatar,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a week is not given as an ISO week date
+    /// (e.g. `2024-W42`)
+    ///
+    /// Error Code `42267`
+    #[display(fmt = "'{}' is not a valid ISO week (e.g. 2024-W42)", week)]
+    InvalidWeek { week: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a submitted progress record does not meet the
+    /// list wide requirement (see [`ListRequirement`](crate::requirement::ListRequirement))
+    ///
+    /// Error Code `42268`
+    #[display(fmt = "Records on main list demons currently need to have at least {}% progress", requirement)]
+    BelowListRequirement {
+        /// The requirement currently in effect for all main list demons
+        requirement: i16,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a player name is empty or longer than 100
+    /// characters
+    ///
+    /// Error Code `42244`
+    #[display(fmt = "Player names must be between 1 and 100 characters long")]
+    InvalidPlayerName,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if someone tries to submit a record for a demon
+    /// for which list moderators have temporarily closed submissions
+    ///
+    /// Error Code `42241`
+    #[display(fmt = "Submissions for this demon are currently closed")]
+    SubmissionsClosed { reason: Option<String> },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if someone tries to submit a record while list
+    /// administrators have closed submissions for the entire list
+    ///
+    /// Error Code `42242`
+    #[display(fmt = "Record submissions are currently closed")]
+    ListSubmissionsClosed {
+        reason: Option<String>,
+
+        /// The point in time at which submissions will reopen, if scheduled
+        reopen_at: Option<DateTime<Utc>>,
+    },
+
+    /// `429 TOO MANY REQUESTS` variant returned if a submitter already has the configured maximum
+    /// of submissions waiting for review
+    ///
+    /// Error Code `42901`
+    #[display(
+        fmt = "You already have {} submissions waiting for review. Please wait until some of them have been reviewed before submitting more",
+        limit
+    )]
+    TooManyPendingSubmissions { limit: u32 },
+
+    /// `429 TOO MANY REQUESTS` variant returned if a submitter submits another record for a demon
+    /// they have recently submitted a record for
+    ///
+    /// Error Code `42902`
+    #[display(fmt = "You have recently submitted a record for this demon. Try again after {}", retry_after)]
+    SubmissionCooldown { retry_after: DateTime<Utc> },
+
+    /// `429 TOO MANY REQUESTS` variant returned if a submitter files appeals too quickly
+    ///
+    /// Error Code `42903`
+    #[display(fmt = "You have recently appealed the rejection of a record. Try again after {}", retry_after)]
+    AppealCooldown { retry_after: DateTime<Utc> },
 }
 
 impl std::error::Error for DemonlistError {}
@@ -197,8 +535,10 @@ impl PointercrateError for DemonlistError {
         match self {
             Core(core) => core.error_code(),
             SubmitterNotFound { .. } => 40401,
+            AnonymousSubmitterNotFound { .. } => 40401,
             NoteNotFound { .. } => 40401,
             CreatorNotFound { .. } => 40401,
+            CreditNotFound { .. } => 40401,
             CreatorExists => 40905,
             InvalidRequirement => 42212,
             InvalidPosition { .. } => 42213,
@@ -208,6 +548,8 @@ impl PointercrateError for DemonlistError {
             ClaimUnverified => 40306,
             VpsDetected => 40307,
             NoThirdPartySubmissions => 40308,
+            NotRecordSubmitter => 40310,
+            OwnRejection => 40311,
             NationalityNotFound { .. } => 40401,
             SubdivisionNotFound { .. } => 40401,
             PlayerNotFound { .. } => 40401,
@@ -217,18 +559,63 @@ impl PointercrateError for DemonlistError {
             DemonNotFoundPosition { .. } => 40401,
             RecordNotFound { .. } => 40401,
             ClaimNotFound { .. } => 40401,
+            ReportNotFound { .. } => 40401,
+            AliasNotFound { .. } => 40401,
+            ReverificationNotFound { .. } => 40401,
+            DraftNotFound { .. } => 40401,
+            ScheduledUpdateNotFound { .. } => 40401,
+            WatchNotFound { .. } => 40401,
+            AppealNotFound { .. } => 40401,
+            ArchivedDemonNotFound { .. } => 40401,
+            AvatarNotFound { .. } => 40401,
             NoNationSet => 40907,
             ConflictingClaims { .. } => 40908,
+            AliasTaken { .. } => 40909,
+            DemonNameNotUnique { .. } => 40910,
+            ReverificationInProgress => 40911,
+            ScheduledUpdateNotPending => 40913,
+            AlreadyWatching => 40914,
+            AlreadyAppealed => 40915,
+            AppealResolved => 40916,
+            VerificationRecord => 40917,
+            ArchivedLevelIdTaken => 40918,
+            CreditExists { .. } => 40919,
             InvalidProgress { .. } => 42215,
             SubmissionExists { .. } => 42217,
             PlayerBanned => 42218,
             SubmitLegacy => 42219,
             Non100Extended => 42220,
             UnsupportedVideoHost => 42224,
-            DemonNameNotUnique { .. } => 42228,
             AlreadyClaimed => 42231,
             MalformedRawUrl => 42233,
             InvalidLevelId => 42235,
+            NoReportTarget => 42238,
+            InvalidReportCategory => 42239,
+            InvalidReportDescription => 42240,
+            InvalidPlayerName => 42244,
+            InvalidDiscussionUrl => 42245,
+            InvalidEnjoyment => 42246,
+            InvalidVideoTimestamp => 42247,
+            DemonPositionLocked => 42248,
+            InvalidScoreWeight => 42249,
+            IncompleteReverification => 42253,
+            MissingImportColumn { .. } => 42254,
+            MalformedImportValue { .. } => 42255,
+            SnapshotVersionMismatch { .. } => 42256,
+            IncompleteDraft => 42257,
+            EmptyScheduledUpdate => 42258,
+            NoWatchTarget => 42259,
+            RecordNotRejected => 42260,
+            InvalidAppealReason => 42261,
+            InvalidAppealResolution => 42262,
+            InvalidAvatar => 42266,
+            InvalidWeek { .. } => 42267,
+            BelowListRequirement { .. } => 42268,
+            SubmissionsClosed { .. } => 42241,
+            ListSubmissionsClosed { .. } => 42242,
+            TooManyPendingSubmissions { .. } => 42901,
+            SubmissionCooldown { .. } => 42902,
+            AppealCooldown { .. } => 42903,
         }
     }
 }
@@ -3,14 +3,26 @@ use pointercrate_user::ADMINISTRATOR;
 
 #[macro_use]
 pub mod demon;
+pub mod changelog;
 pub mod config;
 pub mod creator;
 pub mod error;
+pub mod list_update;
 pub mod nationality;
 pub mod player;
 pub mod record;
+pub mod report;
+pub mod requirement;
+pub mod score;
+pub mod score_history;
+#[cfg(feature = "seed")]
+pub mod seed;
+pub mod settings;
+pub mod snapshot;
+pub mod staff_activity;
 pub mod submitter;
 mod video;
+pub mod watch;
 
 pub const LIST_HELPER: Permission = Permission::new("List Helper", 0x2);
 pub const LIST_MODERATOR: Permission = Permission::new("List Moderator", 0x4);
@@ -25,4 +37,11 @@ pub fn default_permissions_manager() -> PermissionsManager {
         .assigns(LIST_ADMINISTRATOR, LIST_HELPER)
         .implies(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_MODERATOR, LIST_HELPER)
+        .grants("can_review_records", LIST_HELPER)
+        .grants("can_approve_records", LIST_HELPER)
+        .grants("can_add_demons", LIST_MODERATOR)
+        .grants("can_move_demons", LIST_MODERATOR)
+        .grants("can_manage_submitters", LIST_MODERATOR)
+        .grants("can_ban_users", LIST_MODERATOR)
+        .grants("can_view_audit_logs", LIST_ADMINISTRATOR)
 }
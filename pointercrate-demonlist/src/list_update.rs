@@ -0,0 +1,311 @@
+//! List updates scheduled for a specific point in time
+//!
+//! Weekly list updates are usually announced for an exact time. Instead of list staff applying the
+//! changes by hand at that moment, list administrators can queue them up as a
+//! [`ScheduledListUpdate`], which the scheduler applies once it is due (see [`apply_due`]).
+//!
+//! All changes of an update are applied in order and in a single transaction: either all of them
+//! go live, or (if any of them fails, e.g. because a demon was deleted in the meantime) none of
+//! them does, and the error is recorded on the update instead.
+
+use crate::{
+    demon::{Demon, DemonDraft, DemonId, FullDemon, PatchDemon},
+    error::{DemonlistError, Result},
+    player::recompute_scores,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use pointercrate_core::{
+    error::CoreError,
+    pool::{audit_connection, ReadConnection},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, PgConnection};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ListChange {
+    /// Publishes a [draft](crate::demon::DemonDraft) at its intended position
+    Place {
+        draft: i32,
+    },
+
+    Move {
+        demon: i32,
+        position: i16,
+    },
+
+    /// Deletes a demon together with all its records
+    Remove {
+        demon: i32,
+    },
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Hash)]
+pub struct ScheduledListUpdate {
+    pub id: i32,
+    pub scheduled_for: DateTime<Utc>,
+    pub description: Option<String>,
+    pub changes: Vec<ListChange>,
+
+    /// The member id of the list administrator that scheduled this update
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub applied_at: Option<DateTime<Utc>>,
+
+    /// Why applying this update failed, if it did
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostScheduledListUpdate {
+    /// The point in time at which the update should be applied
+    pub scheduled_for: DateTime<Utc>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+    pub changes: Vec<ListChange>,
+}
+
+impl ListChange {
+    fn to_sql(self) -> (&'static str, Option<i32>, Option<i32>, Option<i16>) {
+        match self {
+            ListChange::Place { draft } => ("place", None, Some(draft), None),
+            ListChange::Move { demon, position } => ("move", Some(demon), None, Some(position)),
+            ListChange::Remove { demon } => ("remove", Some(demon), None, None),
+        }
+    }
+
+    fn from_sql(action: &str, demon: Option<i32>, draft: Option<i32>, position: Option<i16>) -> Self {
+        match (action, demon, draft, position) {
+            ("place", _, Some(draft), _) => ListChange::Place { draft },
+            ("move", Some(demon), _, Some(position)) => ListChange::Move { demon, position },
+            ("remove", Some(demon), ..) => ListChange::Remove { demon },
+            _ => panic!("invalid scheduled list change: {}", action),
+        }
+    }
+
+    async fn apply(self, connection: &mut PgConnection) -> Result<()> {
+        match self {
+            ListChange::Place { draft } => {
+                DemonDraft::by_id(draft, &mut *connection).await?.publish(connection).await?;
+            },
+            ListChange::Move { demon, position } => {
+                let patch = PatchDemon {
+                    position: Some(position),
+                    ..Default::default()
+                };
+
+                Demon::by_id(DemonId(demon), &mut *connection)
+                    .await?
+                    .apply_patch(patch, connection)
+                    .await?;
+            },
+            ListChange::Remove { demon } => {
+                FullDemon::by_id(DemonId(demon), &mut *connection)
+                    .await?
+                    .delete_demon(&mut *connection)
+                    .await?;
+
+                recompute_scores(connection).await?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the demon or draft this change refers to currently exists
+    async fn validate(self, connection: &mut PgConnection) -> Result<()> {
+        match self {
+            ListChange::Place { draft } => DemonDraft::by_id(draft, connection).await.map(|_| ()),
+            ListChange::Move { demon, .. } | ListChange::Remove { demon } => Demon::by_id(DemonId(demon), connection).await.map(|_| ()),
+        }
+    }
+}
+
+impl ScheduledListUpdate {
+    pub async fn by_id(update_id: i32, connection: &mut impl ReadConnection) -> Result<ScheduledListUpdate> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            "SELECT id, scheduled_for, description, created_by, created_at, applied_at, error FROM scheduled_list_updates WHERE id = $1",
+            update_id
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(DemonlistError::ScheduledUpdateNotFound { update_id })?;
+
+        Ok(ScheduledListUpdate {
+            id: row.id,
+            scheduled_for: row.scheduled_for,
+            description: row.description,
+            changes: changes_of(row.id, connection).await?,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            applied_at: row.applied_at,
+            error: row.error,
+        })
+    }
+
+    /// All scheduled updates, most recently scheduled first
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<ScheduledListUpdate>> {
+        let connection = &mut connection.reader();
+        let ids = sqlx::query!("SELECT id FROM scheduled_list_updates ORDER BY scheduled_for DESC, id DESC")
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut updates = Vec::new();
+
+        for row in ids {
+            updates.push(ScheduledListUpdate::by_id(row.id, &mut *connection).await?);
+        }
+
+        Ok(updates)
+    }
+
+    pub async fn create(data: PostScheduledListUpdate, created_by: i32, connection: &mut PgConnection) -> Result<ScheduledListUpdate> {
+        if data.changes.is_empty() {
+            return Err(DemonlistError::EmptyScheduledUpdate);
+        }
+
+        for change in &data.changes {
+            change.validate(&mut *connection).await?;
+        }
+
+        let description = data
+            .description
+            .map(|description| description.trim().to_string())
+            .filter(|description| !description.is_empty());
+
+        let id = sqlx::query!(
+            "INSERT INTO scheduled_list_updates (scheduled_for, description, created_by) VALUES ($1, $2, $3) RETURNING id",
+            data.scheduled_for,
+            description,
+            created_by
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        for (ordinal, change) in data.changes.iter().enumerate() {
+            let (action, demon, draft, position) = change.to_sql();
+
+            sqlx::query!(
+                "INSERT INTO scheduled_list_changes (update_id, ordinal, action, demon, draft, position) VALUES ($1, $2, $3, $4, $5, $6)",
+                id,
+                ordinal as i32,
+                action,
+                demon,
+                draft,
+                position
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        info!(
+            "Scheduled list update {} with {} changes for {}",
+            id,
+            data.changes.len(),
+            data.scheduled_for
+        );
+
+        ScheduledListUpdate::by_id(id, connection).await
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.applied_at.is_none() && self.error.is_none()
+    }
+
+    /// Cancels this update. Only pending updates can be cancelled
+    pub async fn cancel(self, connection: &mut PgConnection) -> Result<()> {
+        if !self.is_pending() {
+            return Err(DemonlistError::ScheduledUpdateNotPending);
+        }
+
+        info!("Cancelling scheduled list update {}", self.id);
+
+        sqlx::query!("DELETE FROM scheduled_list_updates WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
+
+async fn changes_of(update_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<ListChange>> {
+    let connection = &mut connection.reader();
+    Ok(sqlx::query!(
+        "SELECT action, demon, draft, position FROM scheduled_list_changes WHERE update_id = $1 ORDER BY ordinal",
+        update_id
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| ListChange::from_sql(&row.action, row.demon, row.draft, row.position))
+    .collect())
+}
+
+/// Applies all pending updates that are due, oldest first. Returns the number of updates that were
+/// applied successfully.
+///
+/// Each update is applied in its own savepoint, so a failing update does not affect the others.
+/// Changes made by an update are attributed to the list administrator who scheduled it in audit
+/// logs (or to the system user, if their account no longer exists).
+pub async fn apply_due(connection: &mut PgConnection) -> Result<usize> {
+    let due = sqlx::query!(
+        "SELECT id, created_by FROM scheduled_list_updates WHERE applied_at IS NULL AND error IS NULL AND scheduled_for <= NOW() ORDER BY \
+         scheduled_for, id"
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let mut applied = 0;
+
+    for row in due {
+        let changes = changes_of(row.id, &mut *connection).await?;
+        let mut savepoint = connection.begin().await?;
+
+        audit_connection(&mut savepoint, row.created_by.unwrap_or(0)).await?;
+
+        let mut result = Ok(());
+
+        for change in changes {
+            result = change.apply(&mut *savepoint).await;
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        match result {
+            Ok(()) => {
+                savepoint.commit().await?;
+
+                sqlx::query!("UPDATE scheduled_list_updates SET applied_at = NOW() WHERE id = $1", row.id)
+                    .execute(&mut *connection)
+                    .await?;
+
+                info!("Applied scheduled list update {}", row.id);
+
+                applied += 1;
+            },
+            // Let the caller retry the whole transaction instead of recording a spurious failure
+            Err(DemonlistError::Core(CoreError::TransactionConflict)) => return Err(CoreError::TransactionConflict.into()),
+            Err(err) => {
+                savepoint.rollback().await?;
+
+                warn!("Scheduled list update {} failed: {}", row.id, err);
+
+                sqlx::query!(
+                    "UPDATE scheduled_list_updates SET error = $1 WHERE id = $2",
+                    err.to_string(),
+                    row.id
+                )
+                .execute(&mut *connection)
+                .await?;
+            },
+        }
+    }
+
+    Ok(applied)
+}
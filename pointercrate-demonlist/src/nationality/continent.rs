@@ -0,0 +1,58 @@
+use crate::{error::Result, nationality::Continent};
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+
+/// Aggregated statistics about the players from all nations on one continent, as displayed on the
+/// stats viewer's world map
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ContinentStatistics {
+    pub continent: Continent,
+
+    /// The sum of the scores of all nations on this continent
+    pub score: f64,
+
+    /// The number of (unbanned) players with a non-zero score from this continent
+    pub players: i64,
+
+    /// The number of completions (approved 100% records and verifications) by players from this
+    /// continent
+    pub completions: i64,
+
+    /// The number of different demons completed by at least one player from this continent
+    pub demons_completed: i64,
+}
+
+impl ContinentStatistics {
+    /// Statistics for every continent, including those without any players
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<ContinentStatistics>> {
+        let connection = &mut connection.reader();
+        let rows = sqlx::query!(
+            r#"WITH completions AS (
+                   SELECT nationalities.continent, score_giving.position
+                   FROM score_giving
+                   INNER JOIN players ON players.id = score_giving.player
+                   INNER JOIN nationalities ON nationalities.iso_country_code = players.nationality
+                   WHERE score_giving.progress = 100 AND NOT players.banned
+               )
+               SELECT c.continent::TEXT AS "continent!",
+                      (SELECT COALESCE(SUM(score), 0) FROM nationalities WHERE continent = c.continent) AS "score!",
+                      (SELECT COUNT(*) FROM ranked_players WHERE continent = c.continent) AS "players!",
+                      (SELECT COUNT(*) FROM completions WHERE continent = c.continent) AS "completions!",
+                      (SELECT COUNT(DISTINCT position) FROM completions WHERE continent = c.continent) AS "demons_completed!"
+               FROM UNNEST(ENUM_RANGE(NULL::continent)) AS c(continent)"#
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ContinentStatistics {
+                continent: Continent::from_sql(&row.continent),
+                score: row.score,
+                players: row.players,
+                completions: row.completions,
+                demons_completed: row.demons_completed,
+            })
+            .collect())
+    }
+}
@@ -1,13 +1,15 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{DemonId, MinimalDemon},
     error::{DemonlistError, Result},
     nationality::{BestRecord, MiniDemon, MiniDemonWithPlayers, Nationality, NationalityRecord, Subdivision},
 };
 use futures::stream::StreamExt;
-use sqlx::{Error, PgConnection};
+use pointercrate_core::pool::ReadConnection;
+use sqlx::Error;
 
 impl Nationality {
-    pub async fn subdivisions(&self, connection: &mut PgConnection) -> Result<Vec<Subdivision>> {
+    pub async fn subdivisions(&self, connection: &mut impl ReadConnection) -> Result<Vec<Subdivision>> {
+        let connection = &mut connection.reader();
         let mut stream = sqlx::query!(
             r#"SELECT iso_code as "iso_code: String", name as "name: String" FROM subdivisions WHERE nation = $1 ORDER BY name"#,
             self.iso_country_code
@@ -27,7 +29,8 @@ impl Nationality {
         Ok(subdivisions)
     }
 
-    pub async fn by_country_code_or_name(code: &str, connection: &mut PgConnection) -> Result<Nationality> {
+    pub async fn by_country_code_or_name(code: &str, connection: &mut impl ReadConnection) -> Result<Nationality> {
+        let connection = &mut connection.reader();
         sqlx::query!(
             r#"SELECT nation as "nation: String", iso_country_code as "iso_country_code: String" FROM nationalities WHERE iso_country_code = $1 or nation = $1"#,
             code.to_string() /* FIXME(sqlx 0.3) */
@@ -52,7 +55,8 @@ impl Nationality {
         })
     }
 
-    pub async fn subdivision_by_code(&self, code: &str, connection: &mut PgConnection) -> Result<Subdivision> {
+    pub async fn subdivision_by_code(&self, code: &str, connection: &mut impl ReadConnection) -> Result<Subdivision> {
+        let connection = &mut connection.reader();
         let result = sqlx::query!(
             "SELECT name FROM subdivisions WHERE iso_code = $1 AND nation = $2",
             code,
@@ -74,7 +78,8 @@ impl Nationality {
         }
     }
 
-    pub async fn all(connection: &mut PgConnection) -> Result<Vec<Nationality>> {
+    pub async fn all(connection: &mut impl ReadConnection) -> Result<Vec<Nationality>> {
+        let connection = &mut connection.reader();
         let mut stream =
             sqlx::query!(r#"SELECT nation as "nation: String", iso_country_code as "iso_country_code: String" FROM nationalities"#)
                 .fetch(connection);
@@ -93,7 +98,8 @@ impl Nationality {
         Ok(nationalities)
     }
 
-    pub async fn used(connection: &mut PgConnection) -> Result<Vec<Nationality>> {
+    pub async fn used(connection: &mut impl ReadConnection) -> Result<Vec<Nationality>> {
+        let connection = &mut connection.reader();
         let mut stream = sqlx::query!(
             r#"SELECT DISTINCT nation as "nation: String", iso_country_code as "iso_country_code: String" FROM players INNER JOIN nationalities ON nationality=iso_country_code ORDER BY nation"#
         )
@@ -113,7 +119,8 @@ impl Nationality {
         Ok(nationalities)
     }
 
-    pub async fn upgrade(self, connection: &mut PgConnection) -> Result<NationalityRecord> {
+    pub async fn upgrade(self, connection: &mut impl ReadConnection) -> Result<NationalityRecord> {
+        let connection = &mut connection.reader();
         Ok(NationalityRecord {
             best_records: best_records_in(&self, connection).await?,
             created: created_in(&self, connection).await?,
@@ -125,10 +132,11 @@ impl Nationality {
     }
 }
 
-pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+pub async fn unbeaten_in(nation: &Nationality, connection: &mut impl ReadConnection) -> Result<Vec<MinimalDemon>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"select name::text as "name!", id as "id!", position as "position!" from demons where position <= $1 except (select demons.name, demons.id, position from records inner join players on 
-         players.id=records.player inner join demons on demons.id=records.demon where status_='APPROVED' and nationality=$2 and progress=100 union select demons.name, demons.id, demons.position from demons inner join players on players.id=verifier where players.nationality=$2)"#,
+         players.id=records.player inner join demons on demons.id=records.demon where status_='APPROVED' and nationality=$2 and progress=100)"#,
         crate::config::extended_list_size(),
         nation.iso_country_code
     )
@@ -140,7 +148,7 @@ pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) ->
         let row = row?;
 
         unbeaten.push(MinimalDemon {
-            id: row.id,
+            id: DemonId(row.id),
             position: row.position,
             name: row.name,
         });
@@ -149,7 +157,8 @@ pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) ->
     Ok(unbeaten)
 }
 
-pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
+pub async fn created_in(nation: &Nationality, connection: &mut impl ReadConnection) -> Result<Vec<MiniDemonWithPlayers>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!( r#"select distinct on (demon) demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from creators inner join demons on demons.id=demon inner join players on players.id=creator where nationality=$1"#, nation.iso_country_code).fetch(connection);
 
     let mut creations = Vec::<MiniDemonWithPlayers>::new();
@@ -171,7 +180,8 @@ pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) ->
     Ok(creations)
 }
 
-pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemon>> {
+pub async fn verified_in(nation: &Nationality, connection: &mut impl ReadConnection) -> Result<Vec<MiniDemon>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=verifier where nationality=$1"#, nation.iso_country_code).fetch(connection);
 
@@ -191,7 +201,8 @@ pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) ->
     Ok(demons)
 }
 
-pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemon>> {
+pub async fn published_in(nation: &Nationality, connection: &mut impl ReadConnection) -> Result<Vec<MiniDemon>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=publisher where nationality=$1"#, nation.iso_country_code).fetch(connection);
 
@@ -211,7 +222,8 @@ pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -
     Ok(demons)
 }
 
-pub async fn best_records_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<BestRecord>> {
+pub async fn best_records_in(nation: &Nationality, connection: &mut impl ReadConnection) -> Result<Vec<BestRecord>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"SELECT progress as "progress!", demons.id AS "demon_id!", demons.name as "demon_name!: String", demons.position as "position!", players.name as "player_name!: String" FROM best_records_in($1) as records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = records.player"#,
         nation.iso_country_code
@@ -1,10 +1,12 @@
 use crate::demon::MinimalDemon;
+pub use continent::ContinentStatistics;
 use derive_more::Constructor;
-pub use paginate::{NationalityRankingPagination, RankedNation};
+pub use paginate::{NationalityRankingPagination, RankedNation, SubdivisionRankingPagination};
 use pointercrate_core::etag::Taggable;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::PgConnection;
 
+mod continent;
 mod get;
 mod paginate;
 
@@ -85,6 +87,19 @@ impl Continent {
         }
         .to_owned()
     }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "Asia" => Continent::Asia,
+            "Europe" => Continent::Europe,
+            "Australia and Oceania" => Continent::AustraliaAndOceania,
+            "Africa" => Continent::Africa,
+            "North America" => Continent::NorthAmerica,
+            "South America" => Continent::SouthAmerica,
+            "Central America" => Continent::MiddleAmerica,
+            _ => panic!("invalid continent: {}", sql),
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Continent {
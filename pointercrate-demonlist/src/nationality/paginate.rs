@@ -1,11 +1,11 @@
 use crate::{
     error::Result,
-    nationality::{Continent, Nationality},
+    nationality::{Continent, Nationality, Subdivision},
+    player::{DatabasePlayer, Player, PlayerId, RankedPlayer},
 };
 use futures::StreamExt;
-use pointercrate_core::util::non_nullable;
+use pointercrate_core::{pool::ReadConnection, util::non_nullable};
 use serde::{Deserialize, Serialize};
-use sqlx::PgConnection;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NationalityRankingPagination {
@@ -25,7 +25,8 @@ pub struct RankedNation {
 }
 
 impl NationalityRankingPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<RankedNation>> {
+    pub async fn page(&self, connection: &mut impl ReadConnection) -> Result<Vec<RankedNation>> {
+        let connection = &mut connection.reader();
         let mut stream = sqlx::query!(
             r#"SELECT rank as "rank!", score as "score!", nation as "nation!", iso_country_code as "iso_country_code!" FROM ranked_nations WHERE (STRPOS(nation, $1::CITEXT) > 
              0 OR $1 is NULL) AND (continent::text = $2 OR $2 IS NULL)"#,
@@ -53,3 +54,56 @@ impl NationalityRankingPagination {
         Ok(nations)
     }
 }
+
+/// Ranking of the players from a single subdivision. Ranks are relative to the other players in the
+/// subdivision, as opposed to the global ranks returned from the player ranking endpoint
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubdivisionRankingPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    name_contains: Option<String>,
+}
+
+impl SubdivisionRankingPagination {
+    pub async fn page(
+        &self, nation: &Nationality, subdivision: &Subdivision, connection: &mut impl ReadConnection,
+    ) -> Result<Vec<RankedPlayer>> {
+        let connection = &mut connection.reader();
+        let mut stream = sqlx::query!(
+            r#"SELECT index AS "index!", rank AS "rank!", id AS "id!", name::TEXT AS "name!", score AS "score!", version AS "version!"
+               FROM ranked_subdivision_players
+               WHERE nationality = $1 AND subdivision = $2 AND (STRPOS(name, $3::CITEXT) > 0 OR $3 is NULL)
+               ORDER BY index"#,
+            nation.iso_country_code,
+            subdivision.iso_code,
+            self.name_contains
+        )
+        .fetch(connection);
+
+        let mut players = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            players.push(RankedPlayer {
+                rank: row.rank,
+                index: row.index,
+                player: Player {
+                    base: DatabasePlayer {
+                        id: PlayerId(row.id),
+                        name: row.name,
+                        banned: false,
+                    },
+                    score: row.score,
+                    nationality: Some(Nationality {
+                        iso_country_code: nation.iso_country_code.clone(),
+                        nation: nation.nation.clone(),
+                        subdivision: Some(subdivision.clone()),
+                    }),
+                    version: row.version,
+                },
+            })
+        }
+
+        Ok(players)
+    }
+}
@@ -0,0 +1,158 @@
+//! Achievements awarded to players for reaching milestones on the list
+//!
+//! Each [`Achievement`] is a rule evaluated against a player's completions (their approved 100%
+//! records and their verifications). [`award_achievements`] checks all rules for a player and
+//! stores the achievements they newly qualify for. It is called whenever a record is approved, and
+//! can be run for all players at once via [`backfill_achievements`].
+//!
+//! Achievements are never revoked. A player that completed the #1 demon keeps that achievement even
+//! after the demon has been pushed down the list.
+
+use crate::{config::list_size, error::Result};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    /// Completed a demon on the list for the first time. Since only extreme demons are listed, this
+    /// is the player's first extreme demon
+    FirstExtreme,
+
+    /// Completed 10 different demons on the main list
+    TenMainList,
+
+    /// Completed the demon at the top of the list
+    TopOneCompletion,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [Achievement::FirstExtreme, Achievement::TenMainList, Achievement::TopOneCompletion];
+
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            Achievement::FirstExtreme => "first_extreme",
+            Achievement::TenMainList => "ten_main_list",
+            Achievement::TopOneCompletion => "top_one_completion",
+        }
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "first_extreme" => Achievement::FirstExtreme,
+            "ten_main_list" => Achievement::TenMainList,
+            "top_one_completion" => Achievement::TopOneCompletion,
+            _ => panic!("invalid achievement: {}", sql),
+        }
+    }
+
+    fn is_earned_with(self, completions: &Completions) -> bool {
+        match self {
+            Achievement::FirstExtreme => completions.total >= 1,
+            Achievement::TenMainList => completions.main_list >= 10,
+            Achievement::TopOneCompletion => completions.top_one,
+        }
+    }
+}
+
+/// Summary of a player's completions, which is all the achievement rules need to look at
+struct Completions {
+    /// The number of different demons completed
+    total: i64,
+
+    /// The number of different main list demons completed
+    main_list: i64,
+
+    /// Whether the current #1 demon was completed
+    top_one: bool,
+}
+
+impl Completions {
+    async fn of(player_id: i32, connection: &mut PgConnection) -> Result<Completions> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(DISTINCT position) AS "total!", COUNT(DISTINCT position) FILTER (WHERE position <= $2) AS "main_list!",
+                      COALESCE(BOOL_OR(position = 1), FALSE) AS "top_one!"
+               FROM score_giving WHERE player = $1 AND progress = 100"#,
+            player_id,
+            list_size()
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(Completions {
+            total: row.total,
+            main_list: row.main_list,
+            top_one: row.top_one,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct PlayerAchievement {
+    pub achievement: Achievement,
+    pub achieved_at: DateTime<Utc>,
+}
+
+/// Stores all achievements the given player newly qualifies for, and returns them
+pub async fn award_achievements(player_id: i32, connection: &mut PgConnection) -> Result<Vec<Achievement>> {
+    let completions = Completions::of(player_id, &mut *connection).await?;
+    let mut awarded = Vec::new();
+
+    for achievement in Achievement::ALL {
+        if !achievement.is_earned_with(&completions) {
+            continue;
+        }
+
+        let inserted = sqlx::query!(
+            "INSERT INTO player_achievements (player, achievement) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            player_id,
+            achievement.to_sql()
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        if inserted.rows_affected() > 0 {
+            info!("Player {} earned achievement {:?}", player_id, achievement);
+
+            awarded.push(achievement);
+        }
+    }
+
+    Ok(awarded)
+}
+
+/// Awards all achievements to all players that qualify for them, e.g. after a new achievement was
+/// added. Returns the number of achievements awarded
+pub async fn backfill_achievements(connection: &mut PgConnection) -> Result<usize> {
+    let players = sqlx::query!("SELECT DISTINCT player AS \"player!\" FROM score_giving WHERE progress = 100")
+        .fetch_all(&mut *connection)
+        .await?;
+
+    let mut awarded = 0;
+
+    for row in players {
+        awarded += award_achievements(row.player, &mut *connection).await?.len();
+    }
+
+    Ok(awarded)
+}
+
+/// All achievements of the given player, in the order they were earned
+pub async fn achievements_of(player_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<PlayerAchievement>> {
+    let connection = &mut connection.reader();
+    Ok(sqlx::query!(
+        "SELECT achievement, achieved_at FROM player_achievements WHERE player = $1 ORDER BY achieved_at, achievement",
+        player_id
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| PlayerAchievement {
+        achievement: Achievement::from_sql(&row.achievement),
+        achieved_at: row.achieved_at,
+    })
+    .collect())
+}
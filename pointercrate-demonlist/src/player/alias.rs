@@ -0,0 +1,142 @@
+//! Former names and alternate spellings of players
+//!
+//! When a player is renamed (or merged into another player), their previous name is kept as an
+//! alias. List helpers can additionally register alternate spellings manually. Whenever a player is
+//! looked up by name for a submission (see [`DatabasePlayer::by_name_or_create`]), aliases are
+//! resolved before a new player is created, which avoids most of the duplicates that would
+//! otherwise need to be merged by hand.
+
+use crate::{
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use pointercrate_core::{pool::ReadConnection, validate::normalize_name};
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgConnection};
+
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+pub struct PlayerAlias {
+    pub alias: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostAlias {
+    pub alias: String,
+}
+
+impl DatabasePlayer {
+    /// Looks up the player the given (case insensitive) alias belongs to
+    pub async fn by_alias(alias: &str, connection: &mut impl ReadConnection) -> Result<DatabasePlayer> {
+        let connection = &mut connection.reader();
+        let alias = normalize_name(alias);
+
+        let result = sqlx::query_as!(
+            DatabasePlayer,
+            "SELECT id, name, banned FROM players INNER JOIN player_aliases ON players.id = player_aliases.player WHERE \
+             player_aliases.alias = $1::CITEXT",
+            alias
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(player) => Ok(player),
+            Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFoundName { player_name: alias }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// All aliases of this player, most recently added first
+    pub async fn aliases(&self, connection: &mut impl ReadConnection) -> Result<Vec<PlayerAlias>> {
+        let connection = &mut connection.reader();
+        let mut stream = sqlx::query!(
+            "SELECT alias::text AS \"alias!\", added_at FROM player_aliases WHERE player = $1 ORDER BY added_at DESC, alias",
+            self.id.0
+        )
+        .fetch(connection);
+
+        let mut aliases = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            aliases.push(PlayerAlias {
+                alias: row.alias,
+                added_at: row.added_at,
+            })
+        }
+
+        Ok(aliases)
+    }
+
+    /// Registers a new alias for this player. Aliases need to be unique across both aliases and
+    /// player names
+    pub async fn add_alias(&self, alias: &str, connection: &mut PgConnection) -> Result<PlayerAlias> {
+        let alias = normalize_name(alias);
+
+        if alias.is_empty() || alias.chars().count() > 100 {
+            return Err(DemonlistError::InvalidPlayerName);
+        }
+
+        let taken = sqlx::query!(
+            r#"SELECT EXISTS(SELECT 1 FROM players WHERE name = $1::CITEXT) OR EXISTS(SELECT 1 FROM player_aliases WHERE alias = $1::CITEXT) AS "taken!""#,
+            alias
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .taken;
+
+        if taken {
+            return Err(DemonlistError::AliasTaken { alias });
+        }
+
+        let added_at = sqlx::query!(
+            "INSERT INTO player_aliases (alias, player) VALUES ($1::text, $2) RETURNING added_at",
+            alias,
+            self.id.0
+        )
+        .fetch_one(connection)
+        .await?
+        .added_at;
+
+        Ok(PlayerAlias { alias, added_at })
+    }
+
+    pub async fn remove_alias(&self, alias: &str, connection: &mut PgConnection) -> Result<()> {
+        let alias = normalize_name(alias);
+
+        let deleted = sqlx::query!(
+            "DELETE FROM player_aliases WHERE alias = $1::CITEXT AND player = $2",
+            alias,
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(DemonlistError::AliasNotFound {
+                player_id: self.id.0,
+                alias,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Keeps `former_name` as an alias of this player after a rename or merge. Existing aliases are
+    /// left untouched
+    pub(super) async fn retain_former_name(&self, former_name: &str, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO player_aliases (alias, player) VALUES ($1::text, $2) ON CONFLICT DO NOTHING",
+            former_name,
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Autocompletion of player names, for staff tools that need to resolve a typed name to a player
+//! (for example when changing the holder of a record)
+
+use crate::{
+    error::Result,
+    nationality::Nationality,
+    player::{DatabasePlayer, PlayerId},
+};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+
+/// The maximal number of suggestions returned by [`autocomplete_players`]
+pub const AUTOCOMPLETE_RESULTS: i64 = 10;
+
+#[derive(Debug, Serialize)]
+pub struct PlayerSuggestion {
+    #[serde(flatten)]
+    pub base: DatabasePlayer,
+    pub nationality: Option<Nationality>,
+}
+
+/// The (at most [`AUTOCOMPLETE_RESULTS`]) players whose name starts with the given prefix, ignoring
+/// case. Shorter names come first, so that an exact match is always the first suggestion. Empty if
+/// the prefix is blank
+pub async fn autocomplete_players(prefix: &str, connection: &mut impl ReadConnection) -> Result<Vec<PlayerSuggestion>> {
+    let connection = &mut connection.reader();
+    let prefix = prefix.trim();
+
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let pattern = format!("{}%", prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+
+    let mut stream = sqlx::query!(
+        r#"SELECT players.id, players.name AS "name: String", players.banned, nationalities.nation::TEXT,
+                  nationalities.iso_country_code::TEXT
+           FROM players
+           LEFT OUTER JOIN nationalities ON players.nationality = nationalities.iso_country_code
+           WHERE players.name::TEXT ILIKE $1
+           ORDER BY LENGTH(players.name::TEXT), players.name, players.id
+           LIMIT $2"#,
+        pattern,
+        AUTOCOMPLETE_RESULTS
+    )
+    .fetch(connection);
+
+    let mut suggestions = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        suggestions.push(PlayerSuggestion {
+            base: DatabasePlayer {
+                id: PlayerId(row.id),
+                name: row.name,
+                banned: row.banned,
+            },
+            nationality: match (row.nation, row.iso_country_code) {
+                (Some(nation), Some(iso_country_code)) => Some(Nationality {
+                    iso_country_code,
+                    nation,
+                    subdivision: None,
+                }),
+                _ => None,
+            },
+        })
+    }
+
+    Ok(suggestions)
+}
@@ -0,0 +1,233 @@
+//! Avatars of players, uploaded by the holders of verified claims on them
+//!
+//! Uploaded images are decoded, cropped to a centered square and re-encoded as PNGs in each of
+//! [`AVATAR_SIZES`], which both validates them and strips any metadata. All sizes are generated
+//! right away, so that serving an avatar never involves image processing. A new avatar is only
+//! served publicly once a moderator approved it. Until then, the previously approved one (if any)
+//! stays visible.
+
+use crate::{
+    error::{DemonlistError, Result},
+    player::PlayerId,
+};
+use chrono::{DateTime, Utc};
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use log::info;
+use pointercrate_core::{error::CoreError, etag::Taggable, pool::ReadConnection};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::io::Cursor;
+
+/// The sizes (in pixels) avatars can be requested in
+pub const AVATAR_SIZES: [u32; 4] = [32, 64, 128, 256];
+
+/// The largest size avatars are available in
+pub const MAX_AVATAR_SIZE: u32 = 256;
+
+/// The maximal width and height of uploaded images
+const MAX_UPLOAD_DIMENSION: u32 = 4096;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PlayerAvatar {
+    pub player_id: PlayerId,
+
+    /// The id of the member that uploaded this avatar
+    pub uploaded_by: i32,
+    pub uploaded_at: DateTime<Utc>,
+
+    /// Incremented with every upload, so that clients can tell whether a cached avatar is outdated.
+    /// Each revision's images are stored under their own keys
+    pub revision: i32,
+
+    /// Whether the latest revision was approved
+    pub approved: bool,
+
+    /// The id of the moderator that approved the latest revision
+    pub reviewed_by: Option<i32>,
+
+    /// The latest revision approved by a moderator, which is the one served publicly
+    pub approved_revision: Option<i32>,
+}
+
+impl Taggable for PlayerAvatar {}
+
+/// The smallest of [`AVATAR_SIZES`] at least as large as the requested size. If no size is
+/// requested, or if it exceeds all of them, this is [`MAX_AVATAR_SIZE`]
+pub fn avatar_size(requested: Option<u32>) -> u32 {
+    match requested {
+        Some(requested) => AVATAR_SIZES.into_iter().find(|&size| size >= requested).unwrap_or(MAX_AVATAR_SIZE),
+        None => MAX_AVATAR_SIZE,
+    }
+}
+
+/// Turns an uploaded image into an avatar, by cropping it to a centered square and scaling that to
+/// each of [`AVATAR_SIZES`]. Returns the `(size, PNG)` pairs
+///
+/// This does CPU-heavy work and should not be called from async contexts directly.
+pub fn process_avatar(upload: &[u8]) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut reader = ImageReader::new(Cursor::new(upload))
+        .with_guessed_format()
+        .map_err(|_| DemonlistError::InvalidAvatar)?;
+
+    if !matches!(reader.format(), Some(ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP)) {
+        return Err(DemonlistError::InvalidAvatar);
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_UPLOAD_DIMENSION);
+    limits.max_image_height = Some(MAX_UPLOAD_DIMENSION);
+    reader.limits(limits);
+
+    let image = reader.decode().map_err(|_| DemonlistError::InvalidAvatar)?;
+    let side = image.width().min(image.height());
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+
+    AVATAR_SIZES
+        .into_iter()
+        .map(|size| {
+            let mut png = Vec::new();
+
+            square
+                .resize_exact(size, size, FilterType::Lanczos3)
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|_| DemonlistError::InvalidAvatar)?;
+
+            Ok((size, png))
+        })
+        .collect()
+}
+
+impl PlayerAvatar {
+    /// The key under which the given revision of this avatar is kept in pointercrate's storage, in
+    /// the given size
+    pub fn storage_key(&self, revision: i32, size: u32) -> String {
+        format!("avatars/{}/{}/{}.png", self.player_id, revision, size)
+    }
+
+    pub async fn by_player(PlayerId(player_id): PlayerId, connection: &mut impl ReadConnection) -> Result<PlayerAvatar> {
+        let connection = &mut connection.reader();
+        sqlx::query_as!(
+            PlayerAvatar,
+            "SELECT player_id, uploaded_by, uploaded_at, revision, approved, reviewed_by, approved_revision FROM player_avatars WHERE player_id = \
+             $1",
+            player_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::AvatarNotFound { player_id })
+    }
+
+    /// All avatars waiting for a moderator's approval, oldest upload first
+    pub async fn pending(connection: &mut impl ReadConnection) -> Result<Vec<PlayerAvatar>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            PlayerAvatar,
+            "SELECT player_id, uploaded_by, uploaded_at, revision, approved, reviewed_by, approved_revision FROM player_avatars WHERE NOT \
+             approved ORDER BY uploaded_at"
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+
+    /// Records that the given member uploaded a new avatar for the given player, as a new revision
+    /// of the previous one (if any). The new revision needs to be approved before it is served
+    /// publicly
+    ///
+    /// The images generated by [`process_avatar`] need to be stored under the
+    /// [`storage_key`](PlayerAvatar::storage_key)s of the returned avatar's revision.
+    pub async fn upload(PlayerId(player_id): PlayerId, uploaded_by: i32, connection: &mut PgConnection) -> Result<PlayerAvatar> {
+        let avatar = sqlx::query_as!(
+            PlayerAvatar,
+            "INSERT INTO player_avatars (player_id, uploaded_by) VALUES ($1, $2) ON CONFLICT (player_id) DO UPDATE SET uploaded_by = $2, \
+             uploaded_at = NOW(), revision = player_avatars.revision + 1, approved = FALSE, reviewed_by = NULL \
+             RETURNING player_id, uploaded_by, uploaded_at, revision, approved, reviewed_by, approved_revision",
+            player_id,
+            uploaded_by
+        )
+        .fetch_one(connection)
+        .await?;
+
+        info!("Member {} uploaded a new avatar for player {}", uploaded_by, player_id);
+
+        Ok(avatar)
+    }
+
+    /// Approves the latest revision of this avatar, making it the one served publicly
+    ///
+    /// Fails with [`CoreError::PreconditionFailed`] if a new revision was uploaded since this
+    /// avatar was retrieved, as the moderator has not seen that one yet.
+    pub async fn approve(&mut self, reviewer: i32, connection: &mut PgConnection) -> Result<()> {
+        let result = sqlx::query!(
+            "UPDATE player_avatars SET approved = TRUE, reviewed_by = $1, approved_revision = revision WHERE player_id = $2 AND revision \
+             = $3",
+            reviewer,
+            self.player_id.0,
+            self.revision
+        )
+        .execute(connection)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(CoreError::PreconditionFailed.into());
+        }
+
+        info!(
+            "Member {} approved revision {} of the avatar of player {}",
+            reviewer, self.revision, self.player_id
+        );
+
+        self.approved = true;
+        self.reviewed_by = Some(reviewer);
+        self.approved_revision = Some(self.revision);
+
+        Ok(())
+    }
+
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM player_avatars WHERE player_id = $1", self.player_id.0)
+            .execute(connection)
+            .await?;
+
+        info!("Deleted avatar of player {}", self.player_id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{avatar_size, process_avatar, AVATAR_SIZES, MAX_AVATAR_SIZE};
+    use crate::error::DemonlistError;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_avatar_size() {
+        assert_eq!(avatar_size(None), MAX_AVATAR_SIZE);
+        assert_eq!(avatar_size(Some(1)), 32);
+        assert_eq!(avatar_size(Some(64)), 64);
+        assert_eq!(avatar_size(Some(65)), 128);
+        assert_eq!(avatar_size(Some(10000)), MAX_AVATAR_SIZE);
+    }
+
+    #[test]
+    fn test_process_avatar() {
+        let mut jpeg = Vec::new();
+
+        DynamicImage::ImageRgb8(RgbImage::new(600, 300))
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+
+        let avatars = process_avatar(&jpeg).unwrap();
+
+        assert_eq!(avatars.len(), AVATAR_SIZES.len());
+
+        for (size, png) in avatars {
+            let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+
+            assert_eq!((decoded.width(), decoded.height()), (size, size));
+        }
+
+        assert!(matches!(process_avatar(b"<svg></svg>"), Err(DemonlistError::InvalidAvatar)));
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     error::{DemonlistError, Result},
-    player::{claim::PlayerClaim, DatabasePlayer},
+    player::{claim::PlayerClaim, DatabasePlayer, PlayerId},
 };
+use pointercrate_core::pool::ReadConnection;
 use sqlx::PgConnection;
 
 pub struct ClaimBy {
@@ -30,7 +31,8 @@ impl PlayerClaim {
         }
     }
 
-    pub async fn by_user(user_id: i32, connection: &mut PgConnection) -> Result<Option<ClaimBy>> {
+    pub async fn by_user(user_id: i32, connection: &mut impl ReadConnection) -> Result<Option<ClaimBy>> {
+        let connection = &mut connection.reader();
         match sqlx::query!(
             r#"SELECT verified, lock_submissions, player_id, players.name::text as "name!", players.banned FROM player_claims INNER JOIN players ON player_id=players.id
              WHERE member_id = $1"#,
@@ -42,7 +44,7 @@ impl PlayerClaim {
             Ok(row) =>
                 Ok(Some(ClaimBy {
                     player: DatabasePlayer {
-                        id: row.player_id,
+                        id: PlayerId(row.player_id),
                         name: row.name,
                         banned: row.banned,
                     },
@@ -54,9 +56,10 @@ impl PlayerClaim {
         }
     }
 
-    pub async fn get(member_id: i32, player_id: i32, connection: &mut PgConnection) -> Result<PlayerClaim> {
+    pub async fn get(member_id: i32, player_id: i32, connection: &mut impl ReadConnection) -> Result<PlayerClaim> {
+        let connection = &mut connection.reader();
         match PlayerClaim::by_user(member_id, connection).await? {
-            Some(claim) if claim.player.id == player_id => Ok(PlayerClaim {
+            Some(claim) if claim.player.id == PlayerId(player_id) => Ok(PlayerClaim {
                 user_id: member_id,
                 player_id,
                 verified: claim.verified,
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 mod delete;
 mod get;
+mod notify;
 mod paginate;
 mod patch;
 mod put;
@@ -0,0 +1,18 @@
+use crate::{error::Result, player::claim::PlayerClaim};
+use pointercrate_user::notification::{Notification, NotificationKind};
+use sqlx::PgConnection;
+
+impl PlayerClaim {
+    /// Notifies the user holding a verified claim on the given player (if any)
+    ///
+    /// Must be called inside the same transaction as the modification the notification is about
+    pub async fn notify_claimant(
+        player_id: i32, kind: NotificationKind, content: String, link: Option<String>, connection: &mut PgConnection,
+    ) -> Result<()> {
+        if let Some(claim) = PlayerClaim::verified_claim_on(player_id, &mut *connection).await? {
+            Notification::create(claim.user_id, kind, content, link, connection).await?;
+        }
+
+        Ok(())
+    }
+}
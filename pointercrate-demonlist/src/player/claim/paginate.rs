@@ -3,10 +3,11 @@ use pointercrate_core::{
     audit::NamedId,
     first_and_last,
     pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pool::Reader,
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::Row;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PlayerClaimPagination {
@@ -45,7 +46,7 @@ impl PaginationQuery for PlayerClaimPagination {
 impl Paginatable<PlayerClaimPagination> for ListedClaim {
     first_and_last!("player_claims");
 
-    async fn page(query: &PlayerClaimPagination, connection: &mut PgConnection) -> Result<(Vec<ListedClaim>, PageContext), sqlx::Error> {
+    async fn page(query: &PlayerClaimPagination, connection: &mut Reader<'_>) -> Result<(Vec<ListedClaim>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../../sql/paginate_claims.sql"), order);
@@ -55,7 +56,7 @@ impl Paginatable<PlayerClaimPagination> for ListedClaim {
             .bind(query.params.after)
             .bind(query.any_name_contains.as_ref())
             .bind(query.verified)
-            .bind(query.params.limit + 1)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut claims = Vec::new();
@@ -10,7 +10,7 @@ impl DatabasePlayer {
         // check if player is already claimed and verified
         let is_claimed = sqlx::query!(
             r#"SELECT EXISTS(SELECT 1 FROM player_claims WHERE player_id = $1 AND verified) AS "is_claimed!""#,
-            self.id
+            self.id.0
         )
         .fetch_one(&mut *connection)
         .await?
@@ -31,14 +31,14 @@ impl DatabasePlayer {
         sqlx::query!(
             "INSERT INTO player_claims (member_id, player_id) VALUES ($1, $2)",
             claimed_by,
-            self.id
+            self.id.0
         )
         .execute(connection)
         .await?;
 
         Ok(PlayerClaim {
             user_id: claimed_by,
-            player_id: self.id,
+            player_id: self.id.0,
             verified: false,
             lock_submissions: false,
         })
@@ -3,17 +3,20 @@ use crate::{
     demon::{published_by, verified_by},
     error::{DemonlistError, Result},
     nationality::{Nationality, Subdivision},
-    player::{DatabasePlayer, FullPlayer, Player},
+    player::{achievement::achievements_of, DatabasePlayer, FullPlayer, Player, PlayerId},
     record::approved_records_by,
 };
+use pointercrate_core::{pool::ReadConnection, validate::normalize_name};
 use sqlx::{Error, PgConnection};
 
 impl Player {
-    pub async fn upgrade(self, connection: &mut PgConnection) -> Result<FullPlayer> {
+    pub async fn upgrade(self, connection: &mut impl ReadConnection) -> Result<FullPlayer> {
+        let connection = &mut connection.reader();
         let records = approved_records_by(&self.base, connection).await?;
         let published = published_by(&self.base, connection).await?;
         let verified = verified_by(&self.base, connection).await?;
-        let created = created_by(self.base.id, connection).await?;
+        let created = created_by(self.base.id.0, connection).await?;
+        let achievements = achievements_of(self.base.id.0, connection).await?;
 
         Ok(FullPlayer {
             player: self,
@@ -21,12 +24,14 @@ impl Player {
             created,
             verified,
             published,
+            achievements,
         })
     }
 
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Player> {
+    pub async fn by_id(PlayerId(id): PlayerId, connection: &mut impl ReadConnection) -> Result<Player> {
+        let connection = &mut connection.reader();
         let result = sqlx::query!(
-            r#"SELECT id, players.name, banned, players.score, nationalities.nation::text, iso_country_code::text, iso_code::text as subdivision_code, subdivisions.name::text as subdivision_name FROM players LEFT OUTER JOIN nationalities ON 
+            r#"SELECT id, players.name, banned, players.score, nationalities.nation::text, iso_country_code::text, iso_code::text as subdivision_code, subdivisions.name::text as subdivision_name, players.version FROM players LEFT OUTER JOIN nationalities ON 
              players.nationality = nationalities.iso_country_code LEFT OUTER JOIN subdivisions ON players.subdivision = subdivisions.iso_code WHERE id = $1 AND (subdivisions.nation=nationalities.iso_country_code or players.subdivision is null)"#,
             id
         )
@@ -53,25 +58,50 @@ impl Player {
                 };
                 Ok(Player {
                     base: DatabasePlayer {
-                        id: row.id,
+                        id: PlayerId(row.id),
                         name: row.name,
                         banned: row.banned,
                     },
                     score: row.score,
                     nationality,
+                    version: row.version,
                 })
             },
             Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFound { player_id: id }),
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Like [`Player::by_id`], but additionally locks the player's row until the current
+    /// transaction ends, so that its version cannot change between checking it against an
+    /// `If-Match` header and modifying the player
+    pub async fn lock_by_id(PlayerId(id): PlayerId, connection: &mut PgConnection) -> Result<Player> {
+        sqlx::query!("SELECT id FROM players WHERE id = $1 FOR UPDATE", id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        Player::by_id(PlayerId(id), connection).await
+    }
+
+    /// Looks up the player with the given name (case insensitively), falling back to
+    /// [aliases](DatabasePlayer::by_alias)
+    pub async fn by_name(name: &str, connection: &mut impl ReadConnection) -> Result<Player> {
+        let connection = &mut connection.reader();
+        let player = match DatabasePlayer::by_name(name, &mut *connection).await {
+            Err(DemonlistError::PlayerNotFoundName { .. }) => DatabasePlayer::by_alias(name, &mut *connection).await?,
+            result => result?,
+        };
+
+        Player::by_id(player.id, connection).await
+    }
 }
 
 impl DatabasePlayer {
-    pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+    pub async fn by_name(name: &str, connection: &mut impl ReadConnection) -> Result<DatabasePlayer> {
+        let connection = &mut connection.reader();
         let name = name.trim();
 
-        let result = sqlx::query_as!(DatabasePlayer, "SELECT id, name, banned FROM players WHERE name = $1", name)
+        let result = sqlx::query_as!(DatabasePlayer, "SELECT id, name, banned FROM players WHERE name = $1::CITEXT", name)
             .fetch_one(connection)
             .await;
 
@@ -84,7 +114,8 @@ impl DatabasePlayer {
         }
     }
 
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+    pub async fn by_id(PlayerId(id): PlayerId, connection: &mut impl ReadConnection) -> Result<DatabasePlayer> {
+        let connection = &mut connection.reader();
         let result = sqlx::query_as!(DatabasePlayer, r#"SELECT id, name, banned FROM players WHERE id = $1"#, id)
             .fetch_one(connection)
             .await;
@@ -96,10 +127,18 @@ impl DatabasePlayer {
         }
     }
 
+    /// Looks up the player with the given name, falling back to [aliases](DatabasePlayer::by_alias)
+    /// before creating a new player
     pub async fn by_name_or_create(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
-        let name = name.trim();
+        let name = normalize_name(name);
+        let name = name.as_str();
 
-        match Self::by_name(name, connection).await {
+        let result = match Self::by_name(name, &mut *connection).await {
+            Err(DemonlistError::PlayerNotFoundName { .. }) => Self::by_alias(name, &mut *connection).await,
+            result => result,
+        };
+
+        match result {
             Err(DemonlistError::PlayerNotFoundName { .. }) => {
                 let id = sqlx::query!("INSERT INTO players (name) VALUES ($1) RETURNING id", name.to_string())
                     .fetch_one(connection)
@@ -107,7 +146,7 @@ impl DatabasePlayer {
                     .id;
 
                 Ok(DatabasePlayer {
-                    id,
+                    id: PlayerId(id),
                     name: name.to_owned(),
                     banned: false,
                 })
@@ -1,26 +1,33 @@
 pub use self::{
+    alias::{PlayerAlias, PostAlias},
+    autocomplete::{autocomplete_players, PlayerSuggestion},
     paginate::{PlayerPagination, RankedPlayer, RankingPagination},
-    patch::PatchPlayer,
 };
-use crate::{demon::MinimalDemon, nationality::Nationality, record::MinimalRecordD};
+use crate::{demon::MinimalDemon, nationality::Nationality, player::achievement::PlayerAchievement, record::MinimalRecordD, LIST_HELPER};
 use derive_more::Display;
-use pointercrate_core::{error::CoreError, etag::Taggable};
+use pointercrate_core::{error::CoreError, etag::Taggable, patch::Patchable};
 use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+use std::hash::{Hash, Hasher};
 
+pub mod achievement;
+mod alias;
+mod autocomplete;
+pub mod avatar;
 pub mod claim;
 mod get;
 mod paginate;
 mod patch;
 
+pointercrate_core::id_type!(
+    /// The ID of a [`Player`]
+    PlayerId
+);
+
 #[derive(Debug, Hash, Eq, PartialEq, Serialize, Display, Clone, Deserialize)]
 #[display(fmt = "{} (ID: {})", name, id)]
 pub struct DatabasePlayer {
-    pub id: i32,
+    pub id: PlayerId,
     pub name: String,
     pub banned: bool,
 }
@@ -34,12 +41,16 @@ pub struct FullPlayer {
     pub created: Vec<MinimalDemon>,
     pub verified: Vec<MinimalDemon>,
     pub published: Vec<MinimalDemon>,
+    pub achievements: Vec<PlayerAchievement>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Display, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Display, Deserialize, Patchable)]
 #[display(fmt = "{}", base)]
+#[patch(permission = LIST_HELPER)]
 pub struct Player {
     #[serde(flatten)]
+    #[patch(rename = name, ty = String)]
+    #[patch(rename = banned, ty = bool)]
     pub base: DatabasePlayer,
 
     /// This [`Player`]'s score on the stats viewer
@@ -55,13 +66,22 @@ pub struct Player {
     /// - Demon updates
     ///   * Demon movement/addition (recompute all scores)
     ///   * Demon requirement updated (recompute all scores)
+    ///   * Demon score weight updated (recompute all scores)
     ///   * Demon verifier updated
     ///   * Demon removed
     /// - Player updates
     ///   * Player banned
     ///   * Player objects merged
     pub score: f64,
+
+    /// Patched via the country code or name of the nation, and the subdivision code
+    #[patch(ty = String)]
+    #[patch(rename = subdivision, ty = String)]
     pub nationality: Option<Nationality>,
+
+    /// Incremented whenever one of this [`Player`]'s patchable fields changes. Score updates do not
+    /// count as modifications
+    pub version: i32,
 }
 
 // `f64` does not implement hash. Most things in the pointercrate frontend only display score with an accuracy of two digits after the dot,
@@ -71,14 +91,25 @@ impl Hash for Player {
         self.base.hash(state);
         ((self.score * 100f64) as u64).hash(state);
         self.nationality.hash(state);
+        self.version.hash(state);
     }
 }
 
 impl Taggable for FullPlayer {
     fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.player.hash(&mut hasher);
-        hasher.finish()
+        self.player.version as u64
+    }
+}
+
+impl Player {
+    /// Re-reads this player's [`version`](Player::version) after it was modified
+    pub async fn reload_version(&mut self, connection: &mut PgConnection) -> Result<(), CoreError> {
+        self.version = sqlx::query!("SELECT version FROM players WHERE id = $1", self.base.id.0)
+            .fetch_one(connection)
+            .await?
+            .version;
+
+        Ok(())
     }
 }
 
@@ -88,21 +119,37 @@ impl DatabasePlayer {
         // No need to specially handle banned players - they have no approved records, so `score_of_player` will return 0
         let new_score = sqlx::query!(
             "UPDATE players SET score = coalesce(score_of_player($1), 0) WHERE id = $1 RETURNING score",
-            self.id
+            self.id.0
         )
         .fetch_one(&mut *connection)
         .await?;
 
-        sqlx::query!("UPDATE nationalities SET score = coalesce(score_of_nation(nationalities.iso_country_code), 0) FROM players WHERE players.id = $1 AND players.nationality = nationalities.iso_country_code", self.id).execute(&mut *connection).await?;
-        sqlx::query!("UPDATE subdivisions SET score = coalesce(score_of_subdivision(subdivisions.nation, subdivisions.iso_code), 0) FROM players WHERE players.id = $1 AND players.nationality = subdivisions.nation AND players.subdivision = subdivisions.iso_code", self.id).execute(&mut *connection).await?;
+        sqlx::query!("UPDATE nationalities SET score = coalesce(score_of_nation(nationalities.iso_country_code), 0) FROM players WHERE players.id = $1 AND players.nationality = nationalities.iso_country_code", self.id.0).execute(&mut *connection).await?;
+        sqlx::query!("UPDATE subdivisions SET score = coalesce(score_of_subdivision(subdivisions.nation, subdivisions.iso_code), 0) FROM players WHERE players.id = $1 AND players.nationality = subdivisions.nation AND players.subdivision = subdivisions.iso_code", self.id.0).execute(&mut *connection).await?;
 
         Ok(new_score.score)
     }
 }
 
 pub async fn recompute_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
-    sqlx::query!("SELECT recompute_player_scores();").execute(&mut *connection).await?;
-    sqlx::query!("SELECT recompute_nation_scores();").execute(&mut *connection).await?;
+    recompute_player_scores(&mut *connection).await?;
+    recompute_nation_scores(&mut *connection).await?;
+    recompute_subdivision_scores(connection).await
+}
+
+pub async fn recompute_player_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
+    sqlx::query!("SELECT recompute_player_scores();").execute(connection).await?;
+    Ok(())
+}
+
+/// Recomputes the scores of all nations. Since these are derived from player records, not player
+/// scores, this does not need to happen after [`recompute_player_scores`]
+pub async fn recompute_nation_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
+    sqlx::query!("SELECT recompute_nation_scores();").execute(connection).await?;
+    Ok(())
+}
+
+pub async fn recompute_subdivision_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
     sqlx::query!("SELECT recompute_subdivision_scores();").execute(connection).await?;
     Ok(())
 }
@@ -6,27 +6,28 @@ use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
     pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pool::Reader,
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgConnection, Row};
+use sqlx::Row;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct PlayerPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    name: Option<String>,
+    pub name: Option<String>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    name_contains: Option<String>,
+    pub name_contains: Option<String>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub banned: Option<bool>,
 
     #[serde(default, deserialize_with = "nullable")]
-    nation: Option<Option<String>>,
+    pub nation: Option<Option<String>>,
 }
 
 impl PaginationQuery for PlayerPagination {
@@ -45,7 +46,7 @@ impl PaginationQuery for PlayerPagination {
 impl Paginatable<PlayerPagination> for Player {
     first_and_last!("players");
 
-    async fn page(query: &PlayerPagination, connection: &mut PgConnection) -> Result<(Vec<Player>, PageContext), sqlx::Error> {
+    async fn page(query: &PlayerPagination, connection: &mut Reader<'_>) -> Result<(Vec<Player>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../sql/paginate_players_by_id.sql"), order);
@@ -59,7 +60,7 @@ impl Paginatable<PlayerPagination> for Player {
             .bind(query.banned)
             .bind(&query.nation)
             .bind(query.nation == Some(None))
-            .bind(query.params.limit + 1)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut players = Vec::new();
@@ -84,6 +85,7 @@ impl Paginatable<PlayerPagination> for Player {
                 },
                 score: row.get("score"),
                 nationality,
+                version: row.get("version"),
             })
         }
 
@@ -91,7 +93,7 @@ impl Paginatable<PlayerPagination> for Player {
     }
 
     fn pagination_id(&self) -> i32 {
-        self.base.id
+        self.base.id.0
     }
 }
 
@@ -128,15 +130,15 @@ impl PaginationQuery for RankingPagination {
 
 #[derive(Debug, Serialize)]
 pub struct RankedPlayer {
-    rank: i64,
+    pub(crate) rank: i64,
     #[serde(skip)]
-    index: i64,
+    pub(crate) index: i64,
     #[serde(flatten)]
-    player: Player,
+    pub(crate) player: Player,
 }
 
 impl Paginatable<RankingPagination> for RankedPlayer {
-    async fn first_and_last(connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error> {
+    async fn first_and_last(connection: &mut Reader<'_>) -> Result<Option<(i32, i32)>, sqlx::Error> {
         Ok(sqlx::query!("SELECT COUNT(*) FROM players WHERE NOT banned AND score > 0.0")
             .fetch_one(connection)
             .await?
@@ -144,7 +146,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
             .map(|max| (1, max as i32)))
     }
 
-    async fn page(query: &RankingPagination, connection: &mut PgConnection) -> Result<(Vec<RankedPlayer>, PageContext), sqlx::Error> {
+    async fn page(query: &RankingPagination, connection: &mut Reader<'_>) -> Result<(Vec<RankedPlayer>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../sql/paginate_player_ranking.sql"), order);
@@ -157,7 +159,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
             .bind(query.nation == Some(None))
             .bind(query.continent.as_ref().map(|c| c.to_sql()))
             .bind(&query.subdivision)
-            .bind(query.params.limit + 1)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut players = Vec::new();
@@ -182,6 +184,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
                 },
                 score: row.get("score"),
                 nationality,
+                version: row.get("version"),
             };
 
             players.push(RankedPlayer {
@@ -1,31 +1,42 @@
 use crate::{
     error::{DemonlistError, Result},
     nationality::Nationality,
-    player::{claim::PlayerClaim, DatabasePlayer, FullPlayer, Player},
-    record::{approved_records_by, FullRecord},
+    player::{claim::PlayerClaim, DatabasePlayer, FullPlayer, PatchPlayer, Player},
+    record::{approved_records_by, FullRecord, RecordId},
+    watch::{notify_watchers, WatchTarget},
 };
 use log::info;
-use pointercrate_core::util::{non_nullable, nullable};
-use serde::Deserialize;
+use pointercrate_core::{
+    patch::Patch,
+    validate::{normalize_name, validated, Validate, Validator},
+};
+use pointercrate_user::notification::NotificationKind;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize, Default)]
-pub struct PatchPlayer {
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub name: Option<String>,
-
-    #[serde(default, deserialize_with = "non_nullable")]
-    pub banned: Option<bool>,
+impl Validate for PatchPlayer {
+    type Error = DemonlistError;
 
-    #[serde(default, deserialize_with = "nullable")]
-    pub nationality: Option<Option<String>>,
+    fn normalize(&mut self) {
+        if let Some(ref mut name) = self.name {
+            *name = normalize_name(name);
+        }
+    }
 
-    #[serde(default, deserialize_with = "nullable")]
-    pub subdivision: Option<Option<String>>,
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 1..=100, || DemonlistError::InvalidPlayerName);
+        }
+    }
 }
 
 impl FullPlayer {
     pub async fn apply_patch(mut self, patch: PatchPlayer, connection: &mut PgConnection) -> Result<Self> {
+        let patch = validated(patch)?;
+
+        let modified = !patch.is_empty();
+
+        info!("Applying patch {:?} to {}", patch, self);
+
         let mut new_nationality = match patch.nationality {
             None => self.player.nationality.clone(),
             Some(None) => None,
@@ -65,6 +76,8 @@ impl FullPlayer {
                 self.records.clear();
             } else if !banned && self.player.base.banned {
                 self.player.base.unban(connection).await?;
+
+                self.records = approved_records_by(&self.player.base, &mut *connection).await?;
             }
         }
 
@@ -72,7 +85,35 @@ impl FullPlayer {
             self.set_name(name, connection).await?;
         }
 
-        self.player.score = self.player.base.update_score(connection).await?;
+        let old_version = self.player.version;
+
+        self.player.score = self.player.base.update_score(&mut *connection).await?;
+        self.player.reload_version(&mut *connection).await?;
+
+        if self.player.version != old_version {
+            notify_watchers(
+                WatchTarget::Player(self.player.base.id),
+                format!("The player {} you are watching has been modified", self.player.base.name),
+                None,
+                &mut *connection,
+            )
+            .await?;
+        }
+
+        if modified {
+            // The claim moves along with the player in case of a merge, so the id is still correct here
+            PlayerClaim::notify_claimant(
+                self.player.base.id.0,
+                NotificationKind::ClaimedPlayerModified,
+                format!(
+                    "Your claimed player {} has been modified by a list moderator",
+                    self.player.base.name
+                ),
+                Some("/account/".to_string()),
+                connection,
+            )
+            .await?;
+        }
 
         Ok(self)
     }
@@ -93,12 +134,23 @@ impl FullPlayer {
                 Err(DemonlistError::PlayerNotFoundName { .. }) => (),
                 Err(err) => return Err(err),
             }
+
+            // The new name cannot stay an alias (of this or any other player), while the old one
+            // becomes one
+            sqlx::query!("DELETE FROM player_aliases WHERE alias = $1::CITEXT", name)
+                .execute(&mut *connection)
+                .await?;
+
+            self.player
+                .base
+                .retain_former_name(&self.player.base.name, &mut *connection)
+                .await?;
         }
 
         sqlx::query!(
             "UPDATE players SET name = $1::text WHERE id = $2",
             name.to_string(),
-            self.player.base.id
+            self.player.base.id.0
         )
         .execute(connection)
         .await?;
@@ -114,8 +166,8 @@ impl FullPlayer {
     pub async fn merge(&mut self, with: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
         info!("Merging player {} with player {}", self, with);
 
-        let claim_on_self = PlayerClaim::verified_claim_on(self.player.base.id, &mut *connection).await?;
-        let claim_on_with = PlayerClaim::verified_claim_on(with.id, &mut *connection).await?;
+        let claim_on_self = PlayerClaim::verified_claim_on(self.player.base.id.0, &mut *connection).await?;
+        let claim_on_with = PlayerClaim::verified_claim_on(with.id.0, &mut *connection).await?;
 
         match (claim_on_self, claim_on_with) {
             (Some(_), Some(_)) => {
@@ -125,18 +177,18 @@ impl FullPlayer {
                 })
             },
             (Some(_), None) => {
-                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", with.id)
+                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", with.id.0)
                     .execute(&mut *connection)
                     .await?;
             },
             (None, Some(_)) => {
-                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", self.player.base.id)
+                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", self.player.base.id.0)
                     .execute(&mut *connection)
                     .await?;
                 sqlx::query!(
                     "UPDATE player_claims SET player_id = $1 WHERE player_id = $2",
-                    self.player.base.id,
-                    with.id
+                    self.player.base.id.0,
+                    with.id.0
                 )
                 .execute(&mut *connection)
                 .await?;
@@ -144,8 +196,8 @@ impl FullPlayer {
             (None, None) => {
                 sqlx::query!(
                     "UPDATE player_claims SET player_id = $1 WHERE player_id = $2",
-                    self.player.base.id,
-                    with.id
+                    self.player.base.id.0,
+                    with.id.0
                 )
                 .execute(&mut *connection)
                 .await?;
@@ -156,8 +208,8 @@ impl FullPlayer {
         let deleted = sqlx::query!(
             "DELETE FROM creators AS c1 WHERE c1.creator = $2 AND EXISTS (SELECT 1 FROM creators AS c2 WHERE c2.demon = c1.demon AND \
              c2.creator = $1)",
-            self.player.base.id,
-            with.id
+            self.player.base.id.0,
+            with.id.0
         )
         .execute(&mut *connection)
         .await?;
@@ -170,21 +222,46 @@ impl FullPlayer {
         );
 
         // Transfer all other creator entries over
-        let updated = sqlx::query!("UPDATE creators SET creator = $1 WHERE creator = $2", self.player.base.id, with.id)
-            .execute(&mut *connection)
-            .await?;
+        let updated = sqlx::query!(
+            "UPDATE creators SET creator = $1 WHERE creator = $2",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
 
         info!("Transferred {} creator entries from {} to {}", updated.rows_affected(), with, self);
 
-        // Transfer over verifier and publisher entries
+        // Transfer over verifier and publisher entries. Additional credits that would become duplicates are
+        // dropped first, and ones that now duplicate a primary credit afterwards
 
-        let updated_verifiers = sqlx::query!("UPDATE demons SET verifier = $1 WHERE verifier = $2", self.player.base.id, with.id)
-            .execute(&mut *connection)
-            .await?;
+        sqlx::query!(
+            "DELETE FROM demon_credits AS theirs USING demon_credits AS ours WHERE theirs.player = $2 AND ours.player = $1 AND \
+             theirs.demon = ours.demon AND theirs.kind = ours.kind",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE demon_credits SET player = $1 WHERE player = $2",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let updated_verifiers = sqlx::query!(
+            "UPDATE demons SET verifier = $1 WHERE verifier = $2",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
         let updated_publishers = sqlx::query!(
             "UPDATE demons SET publisher = $1 WHERE publisher = $2",
-            self.player.base.id,
-            with.id
+            self.player.base.id.0,
+            with.id.0
         )
         .execute(&mut *connection)
         .await?;
@@ -197,15 +274,18 @@ impl FullPlayer {
             self
         );
 
+        crate::demon::credit::remove_redundant_credits(&mut *connection).await?;
+
         // Alright so merging records is HARD. We already implemented it over in the record patching, so
-        // while somewhat inefficient maybe, we'll just call that code for each record of the current player
-        for row in sqlx::query!("SELECT id FROM records WHERE player = $1", with.id)
+        // while somewhat inefficient maybe, we'll just call that code for each record of the current player.
+        // Verification records are exempt from the uniqueness invariants and simply move over below
+        for row in sqlx::query!("SELECT id FROM records WHERE player = $1 AND NOT verification", with.id.0)
             .fetch_all(&mut *connection)
             .await?
         {
             // FIXME: this is really inefficient and can be made a lot faster by simple moving around some code
             // in the FullRecord impls
-            let mut record = FullRecord::by_id(row.id, &mut *connection).await?;
+            let mut record = FullRecord::by_id(RecordId(row.id), &mut *connection).await?;
             info!("Moving record {} over to new player {}", record, self.player.base);
             record.set_player(self.player.base.clone(), &mut *connection).await?
         }
@@ -213,14 +293,48 @@ impl FullPlayer {
         self.records = approved_records_by(&self.player.base, &mut *connection).await?;
 
         // Transfer all records over, now that they're unique
-        let updated = sqlx::query!("UPDATE records SET player = $1 WHERE player = $2", self.player.base.id, with.id)
+        let updated = sqlx::query!("UPDATE records SET player = $1 WHERE player = $2", self.player.base.id.0, with.id.0)
             .execute(&mut *connection)
             .await?;
 
         info!("Moved {} records from {} to {}", updated.rows_affected(), with, self);
 
+        // Keep the second player's name and aliases around as aliases of the merged player
+        sqlx::query!(
+            "UPDATE player_aliases SET player = $1 WHERE player = $2",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        self.player.base.retain_former_name(&with.name, &mut *connection).await?;
+
+        // The archive references players without foreign keys, so it needs to be updated by hand
+        sqlx::query!(
+            "UPDATE archived_records SET player = $1 WHERE player = $2",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE archived_demons SET verifier = CASE WHEN verifier = $2 THEN $1 ELSE verifier END, publisher = CASE WHEN publisher = \
+             $2 THEN $1 ELSE publisher END, creators = ARRAY(SELECT CASE WHEN creator = $2 THEN $1 ELSE creator END FROM UNNEST(creators, \
+             creator_roles) WITH ORDINALITY AS merged(creator, role, idx) WHERE NOT (creator = $2 AND $1 = ANY(creators)) ORDER BY idx), \
+             creator_roles = ARRAY(SELECT role FROM UNNEST(creators, creator_roles) WITH ORDINALITY AS merged(creator, role, idx) WHERE NOT \
+             (creator = $2 AND $1 = ANY(creators)) ORDER BY idx), co_verifiers = \
+             ARRAY(SELECT DISTINCT UNNEST(array_replace(co_verifiers, $2, $1))), co_publishers = ARRAY(SELECT DISTINCT \
+             UNNEST(array_replace(co_publishers, $2, $1))) WHERE verifier = $2 OR publisher = $2 OR $2 = ANY(creators) OR $2 = \
+             ANY(co_verifiers) OR $2 = ANY(co_publishers)",
+            self.player.base.id.0,
+            with.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
         // Delete the second player
-        sqlx::query!("DELETE FROM players WHERE id = $1", with.id)
+        sqlx::query!("DELETE FROM players WHERE id = $1", with.id.0)
             .execute(connection)
             .await?;
 
@@ -237,7 +351,7 @@ impl Player {
             "UPDATE players SET nationality = $1, subdivision = $2 WHERE id = $3",
             iso_country_code,
             subdivision_code,
-            self.base.id
+            self.base.id.0
         )
         .execute(&mut *connection)
         .await?;
@@ -251,9 +365,34 @@ impl Player {
     }
 }
 
+/// Note attached to every approved record rejected because its player got banned
+const BAN_NOTE: &str = "Rejected automatically: player banned";
+
+/// Note attached to every record approved again because its player got unbanned
+const UNBAN_NOTE: &str = "Approved again automatically: player unbanned";
+
 impl DatabasePlayer {
+    /// Unbans this player, approving all records that were rejected because of the ban again.
+    /// Submissions deleted during the ban are not restored.
     pub async fn unban(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE players SET banned = false WHERE id=$1", self.id)
+        sqlx::query!(
+            "INSERT INTO record_notes (record, content) SELECT id, $2 FROM records WHERE player = $1 AND rejected_by_ban",
+            self.id.0,
+            UNBAN_NOTE
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let restored = sqlx::query!(
+            "UPDATE records SET status_ = 'APPROVED', rejected_by_ban = FALSE WHERE player = $1 AND rejected_by_ban",
+            self.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        info!("Approved {} records again while unbanning {}", restored.rows_affected(), self);
+
+        sqlx::query!("UPDATE players SET banned = false WHERE id=$1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -262,11 +401,16 @@ impl DatabasePlayer {
         Ok(())
     }
 
+    /// Bans this player, deleting all their submissions and rejecting all their records. Which of
+    /// the rejected records were approved is remembered, so that [`DatabasePlayer::unban`] can undo this.
+    /// Superseded records are kept as they are, since they never count towards any stats anyway.
+    ///
+    /// Does not update any scores. Must run inside a transaction!
     pub async fn ban(&mut self, connection: &mut PgConnection) -> Result<()> {
         // Delete all submissions for this player
         let deleted = sqlx::query!(
             "DELETE FROM records WHERE player = $1 AND (status_ = 'SUBMITTED' OR status_ = 'UNDER_CONSIDERATION')",
-            self.id
+            self.id.0
         )
         .execute(&mut *connection)
         .await?;
@@ -275,17 +419,30 @@ impl DatabasePlayer {
 
         // We can simply reject all accepted records here! All submitted records were deleted above, and we
         // don't have to worry about conflicts with existing rejected record when setting status to
-        // 'rejected' since rejected records are globally unique!
+        // 'rejected' since rejected records are globally unique! Superseded records are exempt from that
+        // uniqueness, so they can simply stay superseded.
 
-        // Now, reject all previously accepted records
-        let updated = sqlx::query!("UPDATE records SET status_ = 'REJECTED' WHERE player = $1", self.id)
-            .execute(&mut *connection)
-            .await?;
+        // Now, reject all previously accepted records, remembering which ones they were so that unbanning
+        // can undo this
+        sqlx::query!(
+            "INSERT INTO record_notes (record, content) SELECT id, $2 FROM records WHERE player = $1 AND status_ = 'APPROVED'",
+            self.id.0,
+            BAN_NOTE
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let updated = sqlx::query!(
+            "UPDATE records SET status_ = 'REJECTED', rejected_by_ban = (status_ = 'APPROVED') WHERE player = $1 AND status_ <> 'SUPERSEDED'",
+            self.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
 
         info!("Rejected {} records while banning {}", updated.rows_affected(), self);
 
         // Actually ban the player
-        sqlx::query!("UPDATE players SET banned = true WHERE id = $1", self.id)
+        sqlx::query!("UPDATE players SET banned = true WHERE id = $1", self.id.0)
             .execute(connection)
             .await?;
 
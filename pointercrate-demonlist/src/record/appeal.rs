@@ -0,0 +1,259 @@
+//! Appeals against the rejection of records
+//!
+//! The submitter of a rejected record can contest the rejection exactly once. Appeals are reviewed
+//! by list moderators, but never by the staff member who rejected the record in the first place.
+//! Accepting an appeal moves the record to "under consideration", so that it gets another look.
+//!
+//! To keep submitters from flooding the queue, each submitter can only file one appeal per
+//! [`APPEAL_COOLDOWN`].
+
+use crate::{
+    error::{DemonlistError, Result},
+    record::{FullRecord, RecordId, RecordStatus},
+    submitter::Submitter,
+};
+use chrono::{DateTime, Duration, Utc};
+use derive_more::Display;
+use log::info;
+use pointercrate_core::{
+    etag::Taggable,
+    pool::ReadConnection,
+    validate::{validated, Validate, Validator},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::hash::Hash;
+
+/// How long (in seconds) a submitter has to wait after filing an appeal before they can file
+/// another one
+pub const APPEAL_COOLDOWN: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppealStatus {
+    #[display(fmt = "open")]
+    Open,
+
+    /// The rejection was overturned, and the record is under consideration again
+    #[display(fmt = "accepted")]
+    Accepted,
+
+    /// The rejection was upheld
+    #[display(fmt = "denied")]
+    Denied,
+}
+
+impl AppealStatus {
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "open" => AppealStatus::Open,
+            "accepted" => AppealStatus::Accepted,
+            "denied" => AppealStatus::Denied,
+            _ => panic!("invalid appeal status: {}", sql),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Hash, Display)]
+#[display(fmt = "appeal (ID: {}) against rejection of record {}", id, record)]
+pub struct Appeal {
+    pub id: i32,
+    pub record: i32,
+    pub reason: String,
+    pub status: AppealStatus,
+
+    /// The staff member who rejected the record, if known
+    pub rejected_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_by: Option<i32>,
+    pub resolution_note: Option<String>,
+}
+
+impl Taggable for Appeal {}
+
+#[derive(Debug, Deserialize)]
+pub struct NewAppeal {
+    reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchAppeal {
+    status: AppealStatus,
+
+    #[serde(default)]
+    resolution_note: Option<String>,
+}
+
+impl Validate for NewAppeal {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        self.reason = self.reason.trim().to_string();
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        validator.length("reason", &self.reason, 1..=2000, || DemonlistError::InvalidAppealReason);
+    }
+}
+
+impl Appeal {
+    pub async fn by_id(appeal_id: i32, connection: &mut impl ReadConnection) -> Result<Appeal> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            "SELECT id, record, reason, status, rejected_by, created_at, resolved_by, resolution_note FROM record_appeals WHERE id = $1",
+            appeal_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::AppealNotFound { appeal_id })?;
+
+        Ok(Appeal {
+            id: row.id,
+            record: row.record,
+            reason: row.reason,
+            status: AppealStatus::from_sql(&row.status),
+            rejected_by: row.rejected_by,
+            created_at: row.created_at,
+            resolved_by: row.resolved_by,
+            resolution_note: row.resolution_note,
+        })
+    }
+
+    /// All appeals with the given status that the given staff member is allowed to resolve (meaning
+    /// appeals against their own rejections are left out), oldest first
+    pub async fn queue_for(staff_id: i32, status: AppealStatus, connection: &mut impl ReadConnection) -> Result<Vec<Appeal>> {
+        let connection = &mut connection.reader();
+        let ids = sqlx::query!(
+            "SELECT id FROM record_appeals WHERE status = $1 AND rejected_by IS DISTINCT FROM $2 ORDER BY id",
+            status.to_string(),
+            staff_id
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        let mut appeals = Vec::new();
+
+        for row in ids {
+            appeals.push(Appeal::by_id(row.id, &mut *connection).await?);
+        }
+
+        Ok(appeals)
+    }
+
+    /// Files an appeal against the rejection of the given record on behalf of the given submitter
+    pub async fn create(appeal: NewAppeal, record: &FullRecord, submitter: &Submitter, connection: &mut PgConnection) -> Result<Appeal> {
+        let appeal = validated(appeal)?;
+
+        if record.submitter.as_ref().map(|s| s.id) != Some(submitter.id) {
+            return Err(DemonlistError::NotRecordSubmitter);
+        }
+
+        if record.status != RecordStatus::Rejected {
+            return Err(DemonlistError::RecordNotRejected);
+        }
+
+        let already_appealed = sqlx::query!(
+            "SELECT EXISTS (SELECT 1 FROM record_appeals WHERE record = $1) AS \"exists!\"",
+            record.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .exists;
+
+        if already_appealed {
+            return Err(DemonlistError::AlreadyAppealed);
+        }
+
+        let last_appeal = sqlx::query!(
+            "SELECT MAX(created_at) AS last_appeal FROM record_appeals WHERE submitter = $1",
+            submitter.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .last_appeal;
+
+        if let Some(last_appeal) = last_appeal {
+            let retry_after = last_appeal + Duration::seconds(APPEAL_COOLDOWN);
+
+            if retry_after > Utc::now() {
+                return Err(DemonlistError::AppealCooldown { retry_after });
+            }
+        }
+
+        // The audit log diff tells us who moved the record to 'rejected' most recently
+        let rejected_by = sqlx::query!(
+            "SELECT userid FROM record_modifications WHERE id = $1 AND diff -> 'status_' ->> 'new' = 'REJECTED' ORDER BY time DESC LIMIT \
+             1",
+            record.id.0
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .map(|row| row.userid);
+
+        let row = sqlx::query!(
+            "INSERT INTO record_appeals (record, submitter, reason, rejected_by) VALUES ($1, $2, $3, $4) RETURNING id, created_at",
+            record.id.0,
+            submitter.id.0,
+            appeal.reason,
+            rejected_by
+        )
+        .fetch_one(connection)
+        .await?;
+
+        let appeal = Appeal {
+            id: row.id,
+            record: record.id.0,
+            reason: appeal.reason,
+            status: AppealStatus::Open,
+            rejected_by,
+            created_at: row.created_at,
+            resolved_by: None,
+            resolution_note: None,
+        };
+
+        info!("Submitter {} filed {}", submitter, appeal);
+
+        Ok(appeal)
+    }
+
+    /// Accepts or denies this appeal on behalf of the staff member with the given id
+    ///
+    /// Must run inside a transaction!
+    pub async fn resolve(mut self, patch: PatchAppeal, staff_id: i32, connection: &mut PgConnection) -> Result<Appeal> {
+        if self.status != AppealStatus::Open {
+            return Err(DemonlistError::AppealResolved);
+        }
+
+        if patch.status == AppealStatus::Open {
+            return Err(DemonlistError::InvalidAppealResolution);
+        }
+
+        if self.rejected_by == Some(staff_id) {
+            return Err(DemonlistError::OwnRejection);
+        }
+
+        info!("Resolving {} as {}", self, patch.status);
+
+        if patch.status == AppealStatus::Accepted {
+            let mut record = FullRecord::by_id(RecordId(self.record), &mut *connection).await?;
+
+            record.set_status(RecordStatus::UnderConsideration, &mut *connection).await?;
+        }
+
+        sqlx::query!(
+            "UPDATE record_appeals SET status = $1, resolved_by = $2, resolution_note = $3 WHERE id = $4",
+            patch.status.to_string(),
+            staff_id,
+            patch.resolution_note,
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.status = patch.status;
+        self.resolved_by = Some(staff_id);
+        self.resolution_note = patch.resolution_note;
+
+        Ok(self)
+    }
+}
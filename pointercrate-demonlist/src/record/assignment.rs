@@ -0,0 +1,194 @@
+//! Automatic distribution of submissions among list helpers
+//!
+//! If enabled (see [`auto_assign`](crate::config::auto_assign)), every new submission is assigned
+//! to the list staff member with the fewest open assignments (submissions assigned to them that are
+//! still waiting for review), with ties going to whoever was assigned a submission least recently.
+//! This hands out submissions round-robin, while giving fewer new ones to staff with a backlog.
+//!
+//! Staff members can opt out of being assigned submissions. Submissions not reviewed within the
+//! configured [timeout](crate::config::assignment_timeout) are assigned to someone else.
+
+use crate::{
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+    player::{DatabasePlayer, PlayerId},
+    record::RecordId,
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use log::info;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// Permission bits of everyone who can review submissions
+fn reviewer_bits() -> i32 {
+    (LIST_HELPER.bit() | LIST_MODERATOR.bit() | LIST_ADMINISTRATOR.bit()) as i32
+}
+
+/// The staff member the next submission should be assigned to, if anyone is available
+async fn next_reviewer(excluding: Option<i32>, connection: &mut PgConnection) -> Result<Option<i32>> {
+    Ok(sqlx::query!(
+        r#"SELECT members.member_id FROM members
+           WHERE members.permissions::integer & $1 <> 0 AND NOT members.banned AND members.member_id IS DISTINCT FROM $2
+             AND NOT EXISTS (SELECT 1 FROM assignment_opt_outs WHERE assignment_opt_outs.member_id = members.member_id)
+           ORDER BY (SELECT COUNT(*) FROM record_assignments INNER JOIN records ON records.id = record_assignments.record_id
+                     WHERE record_assignments.member_id = members.member_id AND records.status_ = 'SUBMITTED'),
+                    (SELECT MAX(assigned_at) FROM record_assignments WHERE record_assignments.member_id = members.member_id) NULLS FIRST,
+                    members.member_id
+           LIMIT 1"#,
+        reviewer_bits(),
+        excluding
+    )
+    .fetch_optional(connection)
+    .await?
+    .map(|row| row.member_id))
+}
+
+/// Assigns the given record to the staff member next in line, replacing any previous assignment.
+/// Returns the ID of that staff member, or `None` if nobody is available
+pub async fn assign(record_id: i32, connection: &mut PgConnection) -> Result<Option<i32>> {
+    assign_excluding(record_id, None, connection).await
+}
+
+async fn assign_excluding(record_id: i32, excluding: Option<i32>, connection: &mut PgConnection) -> Result<Option<i32>> {
+    let Some(member_id) = next_reviewer(excluding, &mut *connection).await? else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        "INSERT INTO record_assignments (record_id, member_id) VALUES ($1, $2) ON CONFLICT (record_id) DO UPDATE SET member_id = \
+         EXCLUDED.member_id, assigned_at = EXCLUDED.assigned_at",
+        record_id,
+        member_id
+    )
+    .execute(connection)
+    .await?;
+
+    info!("Assigned record {} to member {}", record_id, member_id);
+
+    Ok(Some(member_id))
+}
+
+/// Assigns submissions that have not been reviewed within `timeout` seconds of being assigned to
+/// someone else. Returns the number of reassigned submissions
+pub async fn reassign_stale(timeout: u64, connection: &mut PgConnection) -> Result<usize> {
+    let stale = sqlx::query!(
+        "SELECT record_assignments.record_id, record_assignments.member_id FROM record_assignments INNER JOIN records ON records.id = \
+         record_assignments.record_id WHERE records.status_ = 'SUBMITTED' AND record_assignments.assigned_at < NOW() - \
+         make_interval(secs => $1::DOUBLE PRECISION)",
+        timeout as f64
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let mut reassigned = 0;
+
+    for assignment in stale {
+        if assign_excluding(assignment.record_id, Some(assignment.member_id), &mut *connection)
+            .await?
+            .is_some()
+        {
+            reassigned += 1;
+        }
+    }
+
+    Ok(reassigned)
+}
+
+/// Opts the given staff member out of (or back into) being assigned submissions. Submissions
+/// assigned to them while opting out are handed to someone else, or left unassigned if nobody is
+/// available
+pub async fn set_opted_out(member_id: i32, opted_out: bool, connection: &mut PgConnection) -> Result<()> {
+    if !opted_out {
+        sqlx::query!("DELETE FROM assignment_opt_outs WHERE member_id = $1", member_id)
+            .execute(connection)
+            .await?;
+
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO assignment_opt_outs (member_id) VALUES ($1) ON CONFLICT DO NOTHING",
+        member_id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    let open = sqlx::query!(
+        "DELETE FROM record_assignments USING records WHERE records.id = record_assignments.record_id AND record_assignments.member_id = \
+         $1 AND records.status_ = 'SUBMITTED' RETURNING record_assignments.record_id",
+        member_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for assignment in open {
+        assign(assignment.record_id, &mut *connection).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn is_opted_out(member_id: i32, connection: &mut PgConnection) -> Result<bool> {
+    Ok(sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM assignment_opt_outs WHERE member_id = $1) AS "exists!""#,
+        member_id
+    )
+    .fetch_one(connection)
+    .await?
+    .exists)
+}
+
+/// A submission waiting for review by the staff member it is assigned to
+#[derive(Debug, Serialize)]
+pub struct AssignedSubmission {
+    pub id: RecordId,
+    pub progress: i16,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+    pub assigned_at: DateTime<Utc>,
+}
+
+/// All submissions assigned to the given staff member that are still waiting for review, the ones
+/// assigned the longest ago first
+pub async fn assigned_to(member_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<AssignedSubmission>> {
+    let connection = &mut connection.reader();
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, records.progress, players.id AS player_id, players.name AS "player_name: String", players.banned AS
+                  player_banned, demons.id AS demon_id, demons.name AS "demon_name: String", demons.position, record_assignments.assigned_at
+           FROM record_assignments
+           INNER JOIN records ON records.id = record_assignments.record_id
+           INNER JOIN players ON records.player = players.id
+           INNER JOIN demons ON records.demon = demons.id
+           WHERE record_assignments.member_id = $1 AND records.status_ = 'SUBMITTED'
+           ORDER BY record_assignments.assigned_at, records.id"#,
+        member_id
+    )
+    .fetch(connection);
+
+    let mut submissions = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        submissions.push(AssignedSubmission {
+            id: RecordId(row.id),
+            progress: row.progress,
+            player: DatabasePlayer {
+                id: PlayerId(row.player_id),
+                name: row.player_name,
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: DemonId(row.demon_id),
+                position: row.position,
+                name: row.demon_name,
+            },
+            assigned_at: row.assigned_at,
+        })
+    }
+
+    Ok(submissions)
+}
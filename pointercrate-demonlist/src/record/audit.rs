@@ -1,9 +1,11 @@
 use crate::{error::Result, record::RecordStatus};
 
 use futures::StreamExt;
-use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
+use pointercrate_core::{
+    audit::{parse_diff, AuditDiff, AuditLogEntry, AuditLogEntryType, NamedId},
+    pool::ReadConnection,
+};
 use serde::Serialize;
-use sqlx::PgConnection;
 
 #[derive(Serialize)]
 pub struct RecordModificationData {
@@ -12,10 +14,17 @@ pub struct RecordModificationData {
     status: Option<RecordStatus>,
     player: Option<NamedId>,
     demon: Option<NamedId>,
+
+    /// Every column changed by this modification with its old and new value. `None` for
+    /// modifications from before diffs were recorded
+    diff: Option<AuditDiff>,
 }
 
 /// Gets all audit log entries for the given record, in chronological order
-pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection) -> Result<Vec<AuditLogEntry<RecordModificationData>>> {
+pub async fn audit_log_for_record(
+    record_id: i32, connection: &mut impl ReadConnection,
+) -> Result<Vec<AuditLogEntry<RecordModificationData>>> {
+    let connection = &mut connection.reader();
     let mut entries = Vec::new();
 
     let addition_row = sqlx::query!(
@@ -54,7 +63,8 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
                   players.name::TEXT AS player_name,
                   player AS player_id,
                   demons.name::TEXT AS demon_name,
-                  demon AS demon_id
+                  demon AS demon_id,
+                  diff::TEXT
                   FROM record_modifications 
                   LEFT OUTER JOIN members ON members.member_id = userid
                   LEFT OUTER JOIN players ON players.id = player
@@ -90,6 +100,7 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
                         _ => None,
                     },
                     video: modification.video,
+                    diff: parse_diff(modification.diff),
                 }),
                 user: NamedId {
                     name: modification.username,
@@ -1,9 +1,17 @@
-use crate::{error::Result, record::FullRecord};
+use crate::{
+    error::{DemonlistError, Result},
+    record::{FullRecord, RecordId},
+};
+use chrono::{DateTime, Utc};
 use log::info;
 use sqlx::PgConnection;
 
 impl FullRecord {
     pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        if self.verification {
+            return Err(DemonlistError::VerificationRecord);
+        }
+
         info!("Deleting record {}", self);
 
         FullRecord::delete_by_id(self.id, &mut *connection).await?;
@@ -15,7 +23,7 @@ impl FullRecord {
 
     /// `FullRecord::delete` should be preferred. Only exists to delete invalid submissions
     /// in the asychronous validation (which is why no score adjustment needs to take place here)
-    pub async fn delete_by_id(record_id: i32, connection: &mut PgConnection) -> Result<()> {
+    pub async fn delete_by_id(RecordId(record_id): RecordId, connection: &mut PgConnection) -> Result<()> {
         // Associated notes get deleted due to the ON DELETE CASCADE on record_notes.record
 
         sqlx::query!("DELETE FROM records WHERE id = $1", record_id)
@@ -24,4 +32,24 @@ impl FullRecord {
 
         Ok(())
     }
+
+    /// Deletes all rejected records that were submitted before the given point in time, returning
+    /// the number of deleted records.
+    ///
+    /// Since rejected records prevent resubmission of the same (player, demon)-pair, this means
+    /// purged records can be submitted again. Rejected records do not contribute to any scores, so no
+    /// recomputation is needed.
+    pub async fn purge_rejected(before: DateTime<Utc>, connection: &mut PgConnection) -> Result<u64> {
+        let purged = sqlx::query!(
+            "DELETE FROM records WHERE status_ = 'REJECTED' AND id IN (SELECT id FROM record_additions WHERE time < $1)",
+            before
+        )
+        .execute(connection)
+        .await?
+        .rows_affected();
+
+        info!("Purged {} rejected records submitted before {}", purged, before);
+
+        Ok(purged)
+    }
 }
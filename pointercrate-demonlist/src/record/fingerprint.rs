@@ -0,0 +1,108 @@
+//! Fingerprints of record videos, for detecting stolen proof
+//!
+//! A common form of fraud is submitting someone else's completion video as one's own. To catch
+//! this, every record's video is fingerprinted by its host and its ID on that host (maintained by a
+//! database trigger), and, once the video was [checked](super::video_check), by the channel that
+//! uploaded it. Submissions whose video matches an approved record of a different player are
+//! flagged during [spam scoring](super::spam), and reviewers can list all such matches via
+//! [`reuses_of`].
+
+use crate::{
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+    player::{DatabasePlayer, PlayerId},
+    record::RecordId,
+};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// Stores the channel that uploaded the given record's video
+pub async fn set_channel(record_id: i32, channel: &str, connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "UPDATE video_fingerprints SET channel = $1 WHERE record_id = $2",
+        channel,
+        record_id
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether the given video already proves an approved record of a player other than the given one
+pub async fn proves_record_of_other_player(video: &str, player_id: i32, connection: &mut PgConnection) -> Result<bool> {
+    Ok(sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM video_fingerprint($1) AS fingerprint
+                         INNER JOIN video_fingerprints ON video_fingerprints.host = fingerprint.host
+                                                      AND video_fingerprints.video_id = fingerprint.video_id
+                         INNER JOIN records ON records.id = video_fingerprints.record_id
+                         WHERE records.status_ = 'APPROVED' AND records.player <> $2) AS "exists!""#,
+        video,
+        player_id
+    )
+    .fetch_one(connection)
+    .await?
+    .exists)
+}
+
+/// An approved record of a different player sharing its video, or its video's channel, with the
+/// record being reviewed
+#[derive(Debug, Serialize)]
+pub struct VideoReuse {
+    pub id: RecordId,
+    pub video: String,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+
+    /// Whether the records link to the same video, as opposed to different videos from the same
+    /// channel
+    pub same_video: bool,
+}
+
+/// All approved records of players other than the given record's one whose video is the same as
+/// the given record's video, or was uploaded by the same channel
+pub async fn reuses_of(record_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<VideoReuse>> {
+    let connection = &mut connection.reader();
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, records.video::TEXT AS "video!", players.id AS player_id, players.name AS "player_name: String",
+                  players.banned AS player_banned, demons.id AS demon_id, demons.name AS "demon_name: String", demons.position,
+                  (theirs.host = ours.host AND theirs.video_id = ours.video_id) AS "same_video!"
+           FROM video_fingerprints AS ours
+           INNER JOIN records AS reviewed ON reviewed.id = ours.record_id
+           INNER JOIN video_fingerprints AS theirs ON (theirs.host = ours.host AND theirs.video_id = ours.video_id)
+                                                   OR theirs.channel = ours.channel
+           INNER JOIN records ON records.id = theirs.record_id
+           INNER JOIN players ON records.player = players.id
+           INNER JOIN demons ON records.demon = demons.id
+           WHERE ours.record_id = $1 AND records.status_ = 'APPROVED' AND records.player <> reviewed.player
+           ORDER BY (theirs.host = ours.host AND theirs.video_id = ours.video_id) DESC, records.id"#,
+        record_id
+    )
+    .fetch(connection);
+
+    let mut reuses = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        reuses.push(VideoReuse {
+            id: RecordId(row.id),
+            video: row.video,
+            player: DatabasePlayer {
+                id: PlayerId(row.player_id),
+                name: row.player_name,
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: DemonId(row.demon_id),
+                position: row.position,
+                name: row.demon_name,
+            },
+            same_video: row.same_video,
+        })
+    }
+
+    Ok(reuses)
+}
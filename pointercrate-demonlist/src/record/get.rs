@@ -1,18 +1,22 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{DemonId, MinimalDemon},
     error::{DemonlistError, Result},
     nationality::Nationality,
-    player::DatabasePlayer,
-    record::{FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus},
-    submitter::Submitter,
+    player::{DatabasePlayer, PlayerId},
+    record::{spam::SpamAssessment, FullRecord, MinimalRecordD, MinimalRecordP, RecordId, RecordStatus, StatusChange, UserRecord},
+    submitter::{Submitter, SubmitterId},
 };
+use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
 use sqlx::{Error, PgConnection};
 
 // Required until https://github.com/launchbadge/sqlx/pull/108 is merged
 struct FetchedRecord {
     progress: i16,
     video: Option<String>,
+    video_timestamp: Option<i32>,
     raw_footage: Option<String>,
     status: String,
     player_id: i32,
@@ -21,53 +25,81 @@ struct FetchedRecord {
     demon_id: i32,
     demon_name: String,
     position: i16,
-    submitter_id: i32,
-    submitter_banned: bool,
+    submitter_id: Option<i32>,
+    submitter_banned: Option<bool>,
+    anonymous_submitter: Option<String>,
     enjoyment: Option<i32>,
+    version: i32,
+    spam_score: i16,
+    spam_reasons: Vec<String>,
+    verification: bool,
+    video_dead_since: Option<DateTime<Utc>>,
 }
 
 impl FullRecord {
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<FullRecord> {
+    pub async fn by_id(RecordId(id): RecordId, connection: &mut impl ReadConnection) -> Result<FullRecord> {
+        let connection = &mut connection.reader();
         let result = sqlx::query_file_as!(FetchedRecord, "sql/record_by_id.sql", id)
             .fetch_one(&mut *connection)
             .await;
 
         match result {
             Ok(row) => Ok(FullRecord {
-                id,
+                id: RecordId(id),
                 progress: row.progress,
                 video: row.video,
+                video_timestamp: row.video_timestamp,
                 raw_footage: row.raw_footage,
                 enjoyment: row.enjoyment,
                 status: RecordStatus::from_sql(&row.status),
                 player: DatabasePlayer {
-                    id: row.player_id,
+                    id: PlayerId(row.player_id),
                     name: row.player_name,
                     banned: row.player_banned,
                 },
                 demon: MinimalDemon {
-                    id: row.demon_id,
+                    id: DemonId(row.demon_id),
                     position: row.position,
                     name: row.demon_name,
                 },
-                submitter: Some(Submitter {
-                    id: row.submitter_id,
-                    banned: row.submitter_banned,
+                submitter: row.submitter_id.map(|id| Submitter {
+                    id: SubmitterId(id),
+                    banned: row.submitter_banned.unwrap_or(false),
                 }),
+                anonymous_submitter: row.anonymous_submitter,
+                version: row.version,
+                spam: Some(SpamAssessment {
+                    score: row.spam_score,
+                    reasons: row.spam_reasons,
+                }),
+                verification: row.verification,
+                video_dead_since: row.video_dead_since,
             }),
 
             Err(Error::RowNotFound) => Err(DemonlistError::RecordNotFound { record_id: id }),
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Like [`FullRecord::by_id`], but additionally locks the record's row until the current
+    /// transaction ends, so that its version cannot change between checking it against an
+    /// `If-Match` header and modifying the record
+    pub async fn lock_by_id(RecordId(id): RecordId, connection: &mut PgConnection) -> Result<FullRecord> {
+        sqlx::query!("SELECT id FROM records WHERE id = $1 FOR UPDATE", id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        FullRecord::by_id(RecordId(id), connection).await
+    }
 }
 
-pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
+pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut impl ReadConnection) -> Result<Vec<MinimalRecordD>> {
+    let connection = &mut connection.reader();
     let mut stream = sqlx::query!(
         r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, demons.id AS demon_id, 
-         demons.name, demons.position FROM records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id 
+         demons.name, demons.position, records.verification FROM records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id 
          = $1 WHERE status_ = 'APPROVED' AND records.player = $1"#,
-        player.id
+        player.id.0
     )
     .fetch(connection);
 
@@ -77,26 +109,186 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
         let row = row?;
 
         records.push(MinimalRecordD {
-            id: row.id,
+            id: RecordId(row.id),
             progress: row.progress,
             video: row.video,
             status: RecordStatus::Approved,
             demon: MinimalDemon {
-                id: row.demon_id,
+                id: DemonId(row.demon_id),
                 position: row.position,
                 name: row.name,
             },
+            verification: row.verification,
+        })
+    }
+
+    Ok(records)
+}
+
+/// The number of records per page of [`approved_records_page_on`]
+pub const APPROVED_RECORDS_PER_PAGE: i64 = 50;
+
+pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut impl ReadConnection) -> Result<Vec<MinimalRecordP>> {
+    let connection = &mut connection.reader();
+    fetch_approved_records_on(demon, None, 0, connection).await
+}
+
+/// Gets the given page (starting at `1`) of the approved records on the given demon, in the same
+/// order as [`approved_records_on`]. Each page holds [`APPROVED_RECORDS_PER_PAGE`] records.
+pub async fn approved_records_page_on(
+    demon: &MinimalDemon, page: i64, connection: &mut impl ReadConnection,
+) -> Result<Vec<MinimalRecordP>> {
+    let connection = &mut connection.reader();
+    fetch_approved_records_on(
+        demon,
+        Some(APPROVED_RECORDS_PER_PAGE),
+        (page.max(1) - 1) * APPROVED_RECORDS_PER_PAGE,
+        connection,
+    )
+    .await
+}
+
+/// Aggregate information about all approved records on a demon, so that it does not need to be
+/// computed from a (potentially very long) list of records
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ApprovedRecordSummary {
+    pub count: i64,
+
+    /// The average enjoyment rating of all records that have one
+    pub average_enjoyment: Option<f64>,
+}
+
+pub async fn approved_record_summary(demon: &MinimalDemon, connection: &mut impl ReadConnection) -> Result<ApprovedRecordSummary> {
+    let connection = &mut connection.reader();
+    let row = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!", AVG(enjoyment)::FLOAT8 AS average_enjoyment FROM records WHERE status_ = 'APPROVED' AND demon = $1
+           AND NOT verification"#,
+        demon.id.0
+    )
+    .fetch_one(connection)
+    .await?;
+
+    Ok(ApprovedRecordSummary {
+        count: row.count,
+        average_enjoyment: row.average_enjoyment,
+    })
+}
+
+/// An approved record together with the time it was approved, see [`latest_approved_records`]
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RecentRecord {
+    pub id: RecordId,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+
+    /// The time the record's status last changed to 'approved' (or the time it was added, if it
+    /// was approved right away). `None` for records predating the audit log
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+/// The `limit` most recently approved records of non-banned players, newest first. Verification
+/// records are not included
+pub async fn latest_approved_records(limit: i64, connection: &mut impl ReadConnection) -> Result<Vec<RecentRecord>> {
+    let connection = &mut connection.reader();
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END AS video,
+                  players.id AS player_id, players.name AS "player_name: String", demons.id AS demon_id,
+                  demons.name AS "demon_name: String", demons.position, COALESCE(status_changes.time, record_additions.time) AS approved_at
+           FROM records
+           INNER JOIN demons ON records.demon = demons.id
+           INNER JOIN players ON records.player = players.id
+           LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+           LEFT OUTER JOIN (SELECT id, MAX(time) AS time FROM record_modifications WHERE status_ IS NOT NULL GROUP BY id) status_changes
+                ON status_changes.id = records.id
+           WHERE records.status_ = 'APPROVED' AND NOT records.verification AND NOT players.banned
+           ORDER BY approved_at DESC NULLS LAST, records.id DESC
+           LIMIT $1"#,
+        limit
+    )
+    .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(RecentRecord {
+            id: RecordId(row.id),
+            progress: row.progress,
+            video: row.video,
+            player: DatabasePlayer {
+                id: PlayerId(row.player_id),
+                name: row.player_name,
+                banned: false,
+            },
+            demon: MinimalDemon {
+                id: DemonId(row.demon_id),
+                position: row.position,
+                name: row.demon_name,
+            },
+            approved_at: row.approved_at,
         })
     }
 
     Ok(records)
 }
 
-pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<MinimalRecordP>> {
+/// The number of approved records on a demon held by players of one nation
+#[derive(Debug, Serialize, PartialEq)]
+pub struct NationalityRecordCount {
+    #[serde(flatten)]
+    pub nationality: Nationality,
+
+    /// The number of approved records (of any progress)
+    pub records: i64,
+
+    /// The number of approved records with 100% progress
+    pub completions: i64,
+}
+
+/// The approved records on a demon, grouped by the nationality of their holders, ordered by number
+/// of records (descending). Records of players without nationality are not counted.
+pub async fn approved_records_by_nationality(
+    demon: &MinimalDemon, connection: &mut impl ReadConnection,
+) -> Result<Vec<NationalityRecordCount>> {
+    let connection = &mut connection.reader();
+    Ok(sqlx::query!(
+        r#"SELECT nationalities.iso_country_code::TEXT AS "iso_country_code!", nationalities.nation::TEXT AS "nation!", COUNT(*) AS "records!",
+                  COUNT(*) FILTER (WHERE records.progress = 100) AS "completions!"
+           FROM records
+           INNER JOIN players ON players.id = records.player
+           INNER JOIN nationalities ON nationalities.iso_country_code = players.nationality
+           WHERE records.status_ = 'APPROVED' AND records.demon = $1 AND NOT records.verification AND NOT players.banned
+           GROUP BY nationalities.iso_country_code
+           ORDER BY COUNT(*) DESC, nationalities.iso_country_code"#,
+        demon.id.0
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| NationalityRecordCount {
+        nationality: Nationality {
+            iso_country_code: row.iso_country_code,
+            nation: row.nation,
+            subdivision: None,
+        },
+        records: row.records,
+        completions: row.completions,
+    })
+    .collect())
+}
+
+async fn fetch_approved_records_on(
+    demon: &MinimalDemon, limit: Option<i64>, offset: i64, connection: &mut impl ReadConnection,
+) -> Result<Vec<MinimalRecordP>> {
+    let connection = &mut connection.reader();
     struct Fetched {
         id: i32,
         progress: i16,
         video: Option<String>,
+        video_timestamp: Option<i32>,
         player_id: i32,
         name: String,
         banned: bool,
@@ -107,10 +299,12 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
 
     let mut stream = sqlx::query_as!(
         Fetched,
-        r#"SELECT records.id, progress, enjoyment, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
-         players.name, players.banned, nation::TEXT, iso_country_code::TEXT FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code WHERE status_ = 'APPROVED' AND 
-         records.demon = $1 ORDER BY progress DESC, id ASC"#,
-        demon.id
+        r#"SELECT records.id, progress, enjoyment, CASE WHEN players.link_banned THEN NULL ELSE video::text END, video_timestamp, 
+         players.id AS player_id, players.name, players.banned, nation::TEXT, iso_country_code::TEXT FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code WHERE status_ = 'APPROVED' AND 
+         records.demon = $1 AND NOT records.verification ORDER BY progress DESC, id ASC LIMIT $2 OFFSET $3"#,
+        demon.id.0,
+        limit,
+        offset
     )
     .fetch(connection);
 
@@ -120,13 +314,14 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
         let row: Fetched = row?;
 
         records.push(MinimalRecordP {
-            id: row.id,
+            id: RecordId(row.id),
             progress: row.progress,
             video: row.video,
+            video_timestamp: row.video_timestamp,
             enjoyment: row.enjoyment,
             status: RecordStatus::Approved,
             player: DatabasePlayer {
-                id: row.player_id,
+                id: PlayerId(row.player_id),
                 name: row.name,
                 banned: row.banned,
             },
@@ -144,7 +339,89 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
     Ok(records)
 }
 
-pub async fn submission_count(connection: &mut PgConnection) -> Result<i64> {
+/// Gets all records the given user is associated with, newest first.
+///
+/// These are the records of all players the user holds a verified claim on, as well as all records
+/// the user submitted while logged in (regardless of the player they were submitted for).
+pub async fn records_of_user(user_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<UserRecord>> {
+    let connection = &mut connection.reader();
+    let mut records = Vec::new();
+
+    {
+        let mut stream = sqlx::query!(
+            r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END AS video,
+                      status_::text AS "status!: String", demons.id AS demon_id, demons.name AS "demon_name: String", demons.position,
+                      players.id AS player_id, players.name AS "player_name: String", players.banned AS player_banned,
+                      record_additions.time AS "submitted_at?"
+               FROM records
+               INNER JOIN demons ON records.demon = demons.id
+               INNER JOIN players ON records.player = players.id
+               LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+               WHERE records.player IN (SELECT player_id FROM player_claims WHERE member_id = $1 AND verified)
+                  OR record_additions.userid = $1
+               ORDER BY records.id DESC"#,
+            user_id
+        )
+        .fetch(&mut *connection);
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            records.push(UserRecord {
+                id: RecordId(row.id),
+                progress: row.progress,
+                video: row.video,
+                status: RecordStatus::from_sql(&row.status),
+                demon: MinimalDemon {
+                    id: DemonId(row.demon_id),
+                    position: row.position,
+                    name: row.demon_name,
+                },
+                player: DatabasePlayer {
+                    id: PlayerId(row.player_id),
+                    name: row.player_name,
+                    banned: row.player_banned,
+                },
+                submitted_at: row.submitted_at,
+                status_history: Vec::new(),
+            })
+        }
+    }
+
+    let ids = records.iter().map(|record| record.id.0).collect::<Vec<_>>();
+
+    // For modifications, status_ holds the status the record had *before* the modification
+    let modifications = sqlx::query!(
+        r#"SELECT id, time, status_::text AS "status!: String" FROM record_modifications WHERE id = ANY($1) AND status_ IS NOT NULL
+           ORDER BY time"#,
+        &ids
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for record in &mut records {
+        let changes = modifications
+            .iter()
+            .filter(|modification| modification.id == record.id.0)
+            .collect::<Vec<_>>();
+
+        for (idx, change) in changes.iter().enumerate() {
+            record.status_history.push(StatusChange {
+                time: change.time,
+                from: RecordStatus::from_sql(&change.status),
+                to: changes
+                    .get(idx + 1)
+                    .map(|next| RecordStatus::from_sql(&next.status))
+                    .unwrap_or(record.status),
+            })
+        }
+    }
+
+    Ok(records)
+}
+
+pub async fn submission_count(connection: &mut impl ReadConnection) -> Result<i64> {
+    let connection = &mut connection.reader();
     Ok(sqlx::query!("SELECT COUNT(*) FROM records WHERE status_='SUBMITTED'")
         .fetch_one(connection)
         .await?
@@ -152,6 +429,15 @@ pub async fn submission_count(connection: &mut PgConnection) -> Result<i64> {
         .unwrap_or_default())
 }
 
+pub async fn under_consideration_count(connection: &mut impl ReadConnection) -> Result<i64> {
+    let connection = &mut connection.reader();
+    Ok(sqlx::query!("SELECT COUNT(*) FROM records WHERE status_='UNDER_CONSIDERATION'")
+        .fetch_one(connection)
+        .await?
+        .count
+        .unwrap_or_default())
+}
+
 #[cfg(test)]
 mod test {
     use sqlx::{pool::PoolConnection, Postgres};
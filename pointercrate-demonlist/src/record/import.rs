@@ -0,0 +1,227 @@
+//! Bulk import of historical records from CSV files
+//!
+//! Older list teams often kept their records in spreadsheets. To migrate them, list administrators
+//! can upload such a spreadsheet as CSV. The first line must be a header naming the columns. The
+//! columns `player`, `demon` and `progress` are required, `video` and `date` (of the form
+//! `YYYY-MM-DD`) are optional. Other columns are ignored. Demons are identified by name.
+//!
+//! Every row is imported as an approved record, going through the same validation as records added
+//! by list moderators via the API. Rows are imported independently of each other: a row that fails
+//! validation does not stop the import, and produces an [`ImportOutcome`] describing the error
+//! instead.
+
+use crate::{
+    demon::MinimalDemon,
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+    record::{post::NormalizedSubmission, FullRecord},
+    submitter::Submitter,
+};
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+
+/// A single row of a record import, not yet validated
+#[derive(Debug)]
+pub struct ImportRow {
+    /// The line in the CSV file this row was read from (starting at 1, the header being line 1)
+    pub line: usize,
+    player: String,
+    demon: String,
+    progress: String,
+    video: Option<String>,
+    date: Option<String>,
+}
+
+/// The result of importing a single [`ImportRow`]
+#[derive(Debug)]
+pub struct ImportOutcome {
+    pub line: usize,
+
+    /// The id of the record created for the row, if it was imported successfully
+    pub record: Option<i32>,
+    pub error: Option<DemonlistError>,
+}
+
+impl ImportOutcome {
+    /// The header of the CSV produced by [`ImportOutcome::to_csv_line`]
+    pub const CSV_HEADER: &'static str = "line,status,record,error\n";
+
+    pub fn to_csv_line(&self) -> String {
+        let status = if self.error.is_some() { "failed" } else { "imported" };
+        let record = self.record.map(|id| id.to_string()).unwrap_or_default();
+        let error = self.error.as_ref().map(|err| escape_csv(&err.to_string())).unwrap_or_default();
+
+        format!("{},{},{},{}\n", self.line, status, record, error)
+    }
+}
+
+/// Parses the given CSV into the rows to import. Fails if the header is missing any of the
+/// required columns
+pub fn parse_import(csv: &str) -> Result<Vec<ImportRow>> {
+    let mut lines = parse_csv(csv).into_iter();
+
+    let header: Vec<String> = match lines.next() {
+        Some((_, header)) => header.into_iter().map(|column| column.trim().to_lowercase()).collect(),
+        None => Vec::new(),
+    };
+
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let required = |name: &str| column(name).ok_or_else(|| DemonlistError::MissingImportColumn { column: name.to_string() });
+
+    let player = required("player")?;
+    let demon = required("demon")?;
+    let progress = required("progress")?;
+    let video = column("video");
+    let date = column("date");
+
+    Ok(lines
+        .map(|(line, fields)| {
+            let field = |index: usize| fields.get(index).map(|field| field.trim().to_string()).unwrap_or_default();
+            let optional_field = |index: Option<usize>| index.map(field).filter(|field| !field.is_empty());
+
+            ImportRow {
+                line,
+                player: field(player),
+                demon: field(demon),
+                progress: field(progress),
+                video: optional_field(video),
+                date: optional_field(date),
+            }
+        })
+        .collect())
+}
+
+impl ImportRow {
+    /// Validates this row and adds it to the list as an approved record
+    pub async fn import(&self, submitter: Submitter, connection: &mut PgConnection) -> Result<FullRecord> {
+        let progress = self.progress.parse::<i16>().map_err(|_| DemonlistError::MalformedImportValue {
+            column: "progress".to_string(),
+        })?;
+
+        let date = match self.date {
+            Some(ref date) => Some(
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| DemonlistError::MalformedImportValue {
+                    column: "date".to_string(),
+                })?,
+            ),
+            None => None,
+        };
+
+        let video = match self.video {
+            Some(ref video) => Some(crate::video::validate(video)?),
+            None => None,
+        };
+
+        let demon = MinimalDemon::by_name(&self.demon, &mut *connection).await?;
+        let player = DatabasePlayer::by_name_or_create(&self.player, &mut *connection).await?;
+
+        let record = NormalizedSubmission::imported(player, demon, progress, video)
+            .validate(&mut *connection)
+            .await?
+            .create(submitter, &mut *connection)
+            .await?;
+
+        // Backdate the record's addition, so that it shows up as having been added when it was
+        // originally achieved
+        if let Some(date) = date {
+            sqlx::query!(
+                "UPDATE record_additions SET time = $1 WHERE id = $2",
+                date.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                record.id.0
+            )
+            .execute(connection)
+            .await?;
+        }
+
+        Ok(record)
+    }
+}
+
+/// Splits the given CSV into lines of fields, skipping empty lines. Fields can be quoted, in which
+/// case they may contain commas, line breaks and (doubled) quotes.
+///
+/// Each line is returned together with its (1-based) line number
+fn parse_csv(csv: &str) -> Vec<(usize, Vec<String>)> {
+    let mut lines = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line_number = 1;
+    let mut line_start = 1;
+    let mut chars = csv.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => (),
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+
+                if fields.iter().any(|field| !field.trim().is_empty()) {
+                    lines.push((line_start, std::mem::take(&mut fields)));
+                } else {
+                    fields.clear();
+                }
+
+                line_number += 1;
+                line_start = line_number;
+            },
+            _ => {
+                if c == '\n' {
+                    line_number += 1;
+                }
+
+                field.push(c)
+            },
+        }
+    }
+
+    fields.push(field);
+
+    if fields.iter().any(|field| !field.trim().is_empty()) {
+        lines.push((line_start, fields));
+    }
+
+    lines
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_csv, parse_csv};
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "player,demon,progress\r\nstardust1971,Bloodbath,100\n\n\"Aeon, Air\",\"The \"\"Hell\"\"\nFactory\",60";
+
+        assert_eq!(
+            parse_csv(csv),
+            vec![
+                (1, vec!["player".to_string(), "demon".to_string(), "progress".to_string()]),
+                (2, vec!["stardust1971".to_string(), "Bloodbath".to_string(), "100".to_string()]),
+                (
+                    4,
+                    vec!["Aeon, Air".to_string(), "The \"Hell\"\nFactory".to_string(), "60".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("Demon not found"), "Demon not found");
+        assert_eq!(escape_csv("Progress, or \"else\""), "\"Progress, or \"\"else\"\"\"");
+    }
+}
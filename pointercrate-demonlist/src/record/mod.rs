@@ -9,7 +9,8 @@
 //! * 'approved' means that the record shows up on the demonlist and that further submissions for
 //!   this (player, demon) pair are only allowed with a different video and higher progress. An
 //!   approved record is unique. Whenever a record becomes 'accepted', all 'submitted' or 'under
-//!   consideration' records with lower progress are removed.
+//!   consideration' records with lower progress are removed, and a previously approved record with
+//!   lower progress becomes 'superseded'.
 //! * 'rejected' means that the record doesn't show up on the demonlist and that further submissions
 //!   with that (player, demon) pair or that video will not be permitted. A rejected record is
 //!   globally unique
@@ -20,31 +21,59 @@
 //! * 'under consideration' means essentially the same as 'submitted', only that all further
 //!   submissions for this (demon, player) tuple are disallowed. Note that this does not mean that
 //!   the 'under consideration' status makes. A record under consideration IS NOT UNIQUE!
+//! * 'superseded' means that the record used to be 'approved', but was replaced by an approved
+//!   record with higher progress. Superseded records are kept for history, but do not show up on
+//!   the demonlist or count towards any stats. A superseded record is NOT unique
+//!
+//! Additionally, every demon has exactly one _verification record_, an approved 100% record of its
+//! verifier that is created and kept up to date automatically (see [`verification`]). Verification
+//! records count towards scores like any other approved record, but they have no submitter, are
+//! not shown among a demon's records and are ignored when upholding the invariants above.
 
 pub use self::{
-    get::{approved_records_by, approved_records_on, submission_count},
-    paginate::RecordPagination,
-    patch::PatchRecord,
+    get::{
+        approved_record_summary, approved_records_by, approved_records_by_nationality, approved_records_on, approved_records_page_on,
+        latest_approved_records, records_of_user, submission_count, under_consideration_count, ApprovedRecordSummary,
+        NationalityRecordCount, RecentRecord, APPROVED_RECORDS_PER_PAGE,
+    },
+    paginate::{RecordPagination, RecordSortColumn},
     post::Submission,
 };
-use crate::{demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, submitter::Submitter};
+use crate::{
+    demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, record::spam::SpamAssessment,
+    submitter::Submitter, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+};
+use chrono::{DateTime, Utc};
 use derive_more::Display;
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{
+    etag::Taggable,
+    patch::Patchable,
+    redact::{Redact, ViewContext},
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    fmt::{Display, Formatter},
-    hash::{Hash, Hasher},
-};
+use std::fmt::{Display, Formatter};
 
+pub mod appeal;
+pub mod assignment;
 pub mod audit;
 mod delete;
+pub mod fingerprint;
 mod get;
+pub mod import;
 pub mod note;
 mod paginate;
 mod patch;
 mod post;
+pub mod spam;
+pub mod stale;
+pub mod verification;
+pub mod video_check;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`FullRecord`]
+    RecordId
+);
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum RecordStatus {
@@ -52,6 +81,7 @@ pub enum RecordStatus {
     Approved,
     Rejected,
     UnderConsideration,
+    Superseded,
 }
 
 impl RecordStatus {
@@ -61,6 +91,7 @@ impl RecordStatus {
             RecordStatus::Approved => "APPROVED",
             RecordStatus::Rejected => "REJECTED",
             RecordStatus::UnderConsideration => "UNDER_CONSIDERATION",
+            RecordStatus::Superseded => "SUPERSEDED",
         }
         .to_owned()
     }
@@ -71,6 +102,7 @@ impl RecordStatus {
             "APPROVED" => RecordStatus::Approved,
             "REJECTED" => RecordStatus::Rejected,
             "UNDER_CONSIDERATION" => RecordStatus::UnderConsideration,
+            "SUPERSEDED" => RecordStatus::Superseded,
             _ => panic!("invalid record state: {}", sql),
         }
     }
@@ -89,6 +121,7 @@ impl Display for RecordStatus {
             RecordStatus::Approved => write!(f, "approved"),
             RecordStatus::Rejected => write!(f, "rejected"),
             RecordStatus::UnderConsideration => write!(f, "under consideration"),
+            RecordStatus::Superseded => write!(f, "superseded"),
         }
     }
 }
@@ -114,86 +147,193 @@ impl<'de> Deserialize<'de> for RecordStatus {
             "submitted" => Ok(RecordStatus::Submitted),
             "rejected" => Ok(RecordStatus::Rejected),
             "under consideration" => Ok(RecordStatus::UnderConsideration),
+            "superseded" => Ok(RecordStatus::Superseded),
             _ => Err(serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(&string),
-                &"'approved', 'submitted', 'under consideration' or 'rejected'",
+                &"'approved', 'submitted', 'under consideration', 'superseded' or 'rejected'",
             )),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Display, Hash)]
+#[derive(Debug, Deserialize, Serialize, Display, Hash, Patchable)]
 #[display(fmt = "{} {} (ID: {})", player, demon, id)]
+#[patch(name = PatchRecord)]
 pub struct FullRecord {
-    pub id: i32,
+    pub id: RecordId,
+
+    #[patch]
     pub progress: i16,
+
+    #[patch]
     pub video: Option<String>,
+
+    /// The time (in seconds) into the video at which the completion happens. Patched in the
+    /// formats accepted by [`parse_timestamp`](crate::video::parse_timestamp)
+    #[patch(ty = String)]
+    pub video_timestamp: Option<i32>,
+
+    #[patch]
     pub status: RecordStatus,
+
+    /// Patched either via the player's name or their ID
+    #[patch(ty = String, permission = LIST_MODERATOR)]
+    #[patch(rename = player_id, ty = i32, permission = LIST_MODERATOR)]
     pub player: DatabasePlayer,
+
+    /// Patched either via the demon's name or its ID
+    #[patch(ty = String)]
+    #[patch(rename = demon_id, ty = i32)]
     pub demon: MinimalDemon,
     pub submitter: Option<Submitter>,
+
+    /// Stable, anonymized identifier of this record's submitter. Unlike the submitter itself, this
+    /// is public. Since anyone who sees both could map the identifiers back to submitters, the
+    /// submitter is only visible to list administrators
+    pub anonymous_submitter: Option<String>,
     pub raw_footage: Option<String>,
+
+    #[patch]
     pub enjoyment: Option<i32>,
+
+    /// Incremented whenever one of this record's patchable fields changes. Notes (which have their
+    /// own endpoints), the submitter and the raw footage cannot be patched, so they do not affect
+    /// it
+    pub version: i32,
+
+    /// The spam score this record was assigned when it was submitted. Only visible to list staff
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spam: Option<SpamAssessment>,
+
+    /// Whether this is the [verification record](verification) of its demon
+    #[serde(default)]
+    pub verification: bool,
+
+    /// Since when this record's video has been found to be unavailable by the periodic
+    /// [video checks](video_check), if it currently is
+    #[serde(default)]
+    pub video_dead_since: Option<DateTime<Utc>>,
 }
 
 impl Taggable for FullRecord {
     fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.id.hash(&mut hasher);
-        self.progress.hash(&mut hasher);
-        self.video.hash(&mut hasher);
-        self.status.hash(&mut hasher);
-        self.player.id.hash(&mut hasher);
-        self.demon.id.hash(&mut hasher);
-        // notes have sub-endpoint -> no hash
-        // submitter cannot be patched -> no hash
-        // raw footage cannot be patched -> no hash
-        hasher.finish()
+        self.version as u64
+    }
+}
+
+/// The raw footage and spam assessment of a record are only visible to list staff. The submitter is
+/// only visible to list administrators, see [`FullRecord::anonymous_submitter`]
+impl Redact for FullRecord {
+    fn redact(&mut self, context: &ViewContext) {
+        if !context.has_permission(LIST_ADMINISTRATOR) {
+            self.submitter = None;
+        }
+
+        if !context.has_permission(LIST_HELPER) {
+            self.raw_footage = None;
+            self.spam = None;
+        }
     }
 }
 
 #[derive(Debug, Hash, Serialize, Display)]
 #[display(fmt = "{} {} (ID: {})", player, demon, id)]
 pub struct MinimalRecordPD {
-    pub id: i32,
+    pub id: RecordId,
     pub progress: i16,
     pub enjoyment: Option<i32>,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub demon: MinimalDemon,
     pub player: DatabasePlayer,
+
+    /// See [`FullRecord::anonymous_submitter`]
+    pub anonymous_submitter: Option<String>,
+}
+
+/// A change of a record's status, as recorded in the audit log
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusChange {
+    pub time: DateTime<Utc>,
+    pub from: RecordStatus,
+    pub to: RecordStatus,
+}
+
+/// A record as presented to the user it belongs to, see [`records_of_user`]
+#[derive(Debug, Serialize, Deserialize, Display)]
+#[display(fmt = "{} {} (ID: {})", player, demon, id)]
+pub struct UserRecord {
+    pub id: RecordId,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: RecordStatus,
+    pub demon: MinimalDemon,
+    pub player: DatabasePlayer,
+
+    /// When this record was submitted. [`None`] for records older than the audit log
+    pub submitted_at: Option<DateTime<Utc>>,
+
+    /// All status changes of this record, in chronological order
+    pub status_history: Vec<StatusChange>,
 }
 
 #[derive(Debug, Hash, Serialize, Deserialize, Display, PartialEq, Eq)]
 #[display(fmt = " {} (ID: {})", demon, id)]
 pub struct MinimalRecordD {
-    pub id: i32,
+    pub id: RecordId,
     pub progress: i16,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub demon: MinimalDemon,
+
+    /// See [`FullRecord::verification`]
+    #[serde(default)]
+    pub verification: bool,
 }
 
 #[derive(Debug, Hash, Serialize, Deserialize, Display, PartialEq, Eq)]
 #[display(fmt = "{} - {}% (ID: {})", player, progress, id)]
 pub struct MinimalRecordP {
-    pub id: i32,
+    pub id: RecordId,
     pub progress: i16,
     pub video: Option<String>,
+    pub video_timestamp: Option<i32>,
     pub status: RecordStatus,
     pub player: DatabasePlayer,
     pub nationality: Option<Nationality>,
     pub enjoyment: Option<i32>,
 }
 
+impl MinimalRecordP {
+    /// Link to this record's video that starts playback at the completion, if its timestamp is known
+    pub fn timestamped_video(&self) -> Option<String> {
+        let video = self.video.as_deref()?;
+
+        Some(match self.video_timestamp {
+            Some(timestamp) => crate::video::with_timestamp(video, timestamp),
+            None => video.to_string(),
+        })
+    }
+}
+
 impl FullRecord {
     pub async fn was_modified(&self, connection: &mut PgConnection) -> Result<bool> {
         Ok(sqlx::query!(
             r#"SELECT EXISTS (SELECT 1 FROM record_modifications WHERE id = $1 AND status_ IS NOT NULL) AS "was_modified!: bool""#,
-            self.id
+            self.id.0
         )
         .fetch_one(&mut *connection)
         .await?
         .was_modified)
     }
+
+    /// Re-reads this record's [`version`](FullRecord::version) after it was modified
+    pub async fn reload_version(&mut self, connection: &mut PgConnection) -> Result<()> {
+        self.version = sqlx::query!("SELECT version FROM records WHERE id = $1", self.id.0)
+            .fetch_one(connection)
+            .await?
+            .version;
+
+        Ok(())
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
     record::note::Note,
 };
 use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
 use sqlx::{Error, PgConnection};
 
 struct PartialNote {
@@ -15,7 +16,8 @@ struct PartialNote {
 }
 
 impl PartialNote {
-    async fn upgrade(self, connection: &mut PgConnection) -> Result<Note> {
+    async fn upgrade(self, connection: &mut impl ReadConnection) -> Result<Note> {
+        let connection = &mut connection.reader();
         let mut stream = sqlx::query!(
             "SELECT members.name AS name FROM record_notes_modifications AS rnm INNER JOIN members ON members.member_id = rnm.userid \
              WHERE id = $1 AND content IS NOT NULL",
@@ -62,7 +64,8 @@ impl Note {
     }
 }
 
-pub async fn notes_on(record_id: i32, public_only: bool, connection: &mut PgConnection) -> Result<Vec<Note>> {
+pub async fn notes_on(record_id: i32, public_only: bool, connection: &mut impl ReadConnection) -> Result<Vec<Note>> {
+    let connection = &mut connection.reader();
     let partials = sqlx::query_as!(
         PartialNote,
         r#"SELECT id, record, content, is_public, members.name AS "author?: String", EXISTS(SELECT 1 FROM record_notes_modifications WHERE record IS NOT NULL AND 
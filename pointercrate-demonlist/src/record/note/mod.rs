@@ -4,7 +4,11 @@ mod patch;
 mod post;
 
 pub use self::{get::notes_on, patch::PatchNote, post::NewNote};
-use pointercrate_core::etag::Taggable;
+use crate::LIST_HELPER;
+use pointercrate_core::{
+    etag::Taggable,
+    redact::{Redact, ViewContext},
+};
 use serde::Deserialize;
 use serde::Serialize;
 use std::{
@@ -45,3 +49,15 @@ impl Taggable for Note {
         hasher.finish()
     }
 }
+
+/// Non-public notes are internal to list staff. Should one ever end up in a response to someone
+/// else, everything but its id is removed
+impl Redact for Note {
+    fn redact(&mut self, context: &ViewContext) {
+        if !self.is_public && !context.has_permission(LIST_HELPER) {
+            self.content.clear();
+            self.author = None;
+            self.editors.clear();
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     error::{DemonlistError, Result},
     record::{note::Note, FullRecord},
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
+use pointercrate_user::notification::{Notification, NotificationKind};
 use serde::Deserialize;
 use sqlx::PgConnection;
 
@@ -25,17 +27,19 @@ impl Note {
 
         let note_id = sqlx::query!(
             "INSERT INTO record_notes (record, content, is_public) VALUES ($1, $2, $3) RETURNING id",
-            record.id,
+            record.id.0,
             new_note.content,
             new_note.is_public,
         )
-        .fetch_one(connection)
+        .fetch_one(&mut *connection)
         .await?
         .id;
 
+        notify_mentions(record, &new_note.content, new_note.is_public, connection).await?;
+
         Ok(Note {
             id: note_id,
-            record: record.id,
+            record: record.id.0,
             content: new_note.content,
             is_public: new_note.is_public,
             transferred: false,
@@ -44,3 +48,48 @@ impl Note {
         })
     }
 }
+
+/// The lowercased usernames mentioned (as `@username`) in the given note content
+fn mentioned_names(content: &str) -> Vec<String> {
+    content
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .map(|name| name.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_').to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Notifies all users mentioned in a new note on the given record. Mentions in internal notes only
+/// notify list staff, as nobody else can read them
+async fn notify_mentions(record: &FullRecord, content: &str, is_public: bool, connection: &mut PgConnection) -> Result<()> {
+    let names = mentioned_names(content);
+
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let mentioned = sqlx::query!(
+        "SELECT member_id FROM members WHERE LOWER(name::TEXT) = ANY($1) AND NOT banned AND ($2 OR permissions::integer & $3 <> 0)",
+        &names,
+        is_public,
+        (LIST_HELPER.bit() | LIST_MODERATOR.bit() | LIST_ADMINISTRATOR.bit()) as i32
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for member in mentioned {
+        Notification::create(
+            member.member_id,
+            NotificationKind::MentionedInNote,
+            format!(
+                "You were mentioned in a note on {}'s record on {} (ID: {})",
+                record.player.name, record.demon.name, record.id
+            ),
+            None,
+            &mut *connection,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
@@ -6,36 +6,37 @@ use crate::{
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{Keyset, PageContext, Paginatable, PaginationParameters, PaginationQuery, Sort, SortColumn, __pagination_compat},
+    pool::Reader,
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{postgres::PgRow, Row};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct RecordPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
-    progress: Option<i16>,
+    pub progress: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "progress__lt")]
-    progress_lt: Option<i16>,
+    pub progress_lt: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "progress__gt")]
-    progress_gt: Option<i16>,
+    pub progress_gt: Option<i16>,
 
-    demon_position: Option<i16>,
+    pub demon_position: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "demon_position__lt")]
-    demon_position_lt: Option<i16>,
+    pub demon_position_lt: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "demon_position__gt")]
-    demon_position_gt: Option<i16>,
+    pub demon_position_gt: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub status: Option<RecordStatus>,
@@ -44,19 +45,46 @@ pub struct RecordPagination {
     pub player: Option<i32>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    demon: Option<String>,
+    pub demon: Option<String>,
 
     #[serde(default, deserialize_with = "non_nullable")]
-    demon_id: Option<i32>,
+    pub demon_id: Option<i32>,
 
     #[serde(default, deserialize_with = "nullable")]
-    video: Option<Option<String>>,
+    pub video: Option<Option<String>>,
 
     #[serde(default, deserialize_with = "nullable")]
-    enjoyment: Option<Option<i32>>,
+    pub enjoyment: Option<Option<i32>>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub submitter: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub anonymous_submitter: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Sort<RecordSortColumn>>,
+}
+
+/// The columns records can be sorted by via the `sort` parameter
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RecordSortColumn {
+    Progress,
+    DemonPosition,
+}
+
+impl SortColumn for RecordSortColumn {
+    const COLUMNS: &'static [(&'static str, Self)] = &[
+        ("progress", RecordSortColumn::Progress),
+        ("demon_position", RecordSortColumn::DemonPosition),
+    ];
+
+    fn expression(self) -> &'static str {
+        match self {
+            RecordSortColumn::Progress => "records.progress",
+            RecordSortColumn::DemonPosition => "demons.position",
+        }
+    }
 }
 
 impl PaginationQuery for RecordPagination {
@@ -72,13 +100,31 @@ impl PaginationQuery for RecordPagination {
     }
 }
 
+impl RecordPagination {
+    fn keyset(&self) -> Keyset {
+        let keyset = Keyset::new(
+            "records INNER JOIN players ON records.player = players.id INNER JOIN demons ON records.demon = demons.id",
+            "records.id",
+        );
+
+        match self.sort {
+            Some(sort) => sort.apply(keyset),
+            None => keyset,
+        }
+    }
+}
+
 impl Paginatable<RecordPagination> for MinimalRecordPD {
     first_and_last!("records");
 
-    async fn page(query: &RecordPagination, connection: &mut PgConnection) -> Result<(Vec<MinimalRecordPD>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+    async fn page(query: &RecordPagination, connection: &mut Reader<'_>) -> Result<(Vec<MinimalRecordPD>, PageContext), sqlx::Error> {
+        let keyset = query.keyset();
 
-        let sql_query = format!(include_str!("../../sql/paginate_records.sql"), order);
+        let sql_query = format!(
+            include_str!("../../sql/paginate_records.sql"),
+            seek = keyset.seek_condition("$1", "$2"),
+            order = keyset.order_by(&query.params)
+        );
 
         let mut stream = sqlx::query(&sql_query)
             .bind(query.params.before)
@@ -97,7 +143,8 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
             .bind(query.player)
             .bind(query.submitter)
             .bind(query.enjoyment)
-            .bind(query.params.limit + 1)
+            .bind(query.anonymous_submitter.as_deref())
+            .bind(query.params.limit() + 1)
             .fetch(&mut *connection);
 
         let mut records = Vec::new();
@@ -121,6 +168,7 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
                     position: row.try_get("position")?,
                     name: row.try_get("demon_name")?,
                 },
+                anonymous_submitter: row.try_get("anonymous_submitter")?,
             })
         }
 
@@ -128,6 +176,10 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
     }
 
     fn pagination_id(&self) -> i32 {
-        self.id
+        self.id.0
+    }
+
+    fn keyset(query: &RecordPagination) -> Option<Keyset> {
+        Some(query.keyset())
     }
 }
@@ -1,39 +1,39 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{DemonId, MinimalDemon},
     error::{DemonlistError, Result},
-    player::DatabasePlayer,
-    record::{FullRecord, RecordStatus},
+    player::{achievement::award_achievements, claim::PlayerClaim, DatabasePlayer, PlayerId},
+    record::{FullRecord, PatchRecord, RecordStatus},
 };
 use log::{info, warn};
 use pointercrate_core::{
     error::CoreError,
-    util::{non_nullable, nullable},
+    validate::{normalize_name, validated, Validate, Validator},
 };
-use serde::Deserialize;
+use pointercrate_user::notification::NotificationKind;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
-pub struct PatchRecord {
-    #[serde(default, deserialize_with = "non_nullable")]
-    progress: Option<i16>,
+impl Validate for PatchRecord {
+    type Error = DemonlistError;
 
-    #[serde(default, deserialize_with = "nullable")]
-    video: Option<Option<String>>,
-
-    #[serde(default, deserialize_with = "nullable")]
-    enjoyment: Option<Option<i32>>,
-
-    #[serde(default, deserialize_with = "non_nullable")]
-    status: Option<RecordStatus>,
+    fn normalize(&mut self) {
+        if let Some(ref mut player) = self.player {
+            *player = normalize_name(player);
+        }
+    }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    player: Option<String>,
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        if let Some(Some(ref video)) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    demon: Option<String>,
+        if let Some(Some(ref timestamp)) = self.video_timestamp {
+            validator.result("video_timestamp", crate::video::parse_timestamp(timestamp));
+        }
 
-    #[serde(default, deserialize_with = "non_nullable")]
-    demon_id: Option<i32>,
+        if let Some(Some(enjoyment)) = self.enjoyment {
+            validator.check("enjoyment", (1..=10).contains(&enjoyment), || DemonlistError::InvalidEnjoyment);
+        }
+    }
 }
 
 impl FullRecord {
@@ -41,6 +41,12 @@ impl FullRecord {
     pub async fn apply_patch(mut self, data: PatchRecord, connection: &mut PgConnection) -> Result<Self> {
         info!("Applying patch {:?} for record {}", data, self);
 
+        if self.verification {
+            return Err(DemonlistError::VerificationRecord);
+        }
+
+        let data = validated(data)?;
+
         if let Some(progress) = data.progress {
             self.set_progress(progress, connection).await?;
         }
@@ -52,6 +58,15 @@ impl FullRecord {
             }
         }
 
+        if let Some(timestamp) = data.video_timestamp {
+            let timestamp = match timestamp {
+                Some(ref timestamp) => Some(crate::video::parse_timestamp(timestamp)?),
+                None => None,
+            };
+
+            self.set_video_timestamp(timestamp, connection).await?;
+        }
+
         if let Some(enjoyment) = data.enjoyment {
             match enjoyment {
                 None => self.delete_enjoyment(connection).await?,
@@ -60,13 +75,31 @@ impl FullRecord {
         }
 
         if let Some(status) = data.status {
-            self.set_status(status, connection).await?
+            let old_status = self.status;
+
+            self.set_status(status, connection).await?;
+
+            if old_status != status {
+                PlayerClaim::notify_claimant(
+                    self.player.id.0,
+                    NotificationKind::RecordStatusChanged,
+                    format!("Your record on {} has been {}", self.demon.name, status),
+                    Some(format!("/list/permalink/{}/", self.demon.id)),
+                    connection,
+                )
+                .await?;
+            }
         }
 
-        if let Some(player) = data.player {
-            let player = DatabasePlayer::by_name_or_create(player.as_ref(), connection).await?;
+        let player = match (data.player, data.player_id) {
+            (Some(player_name), None) => Some(DatabasePlayer::by_name_or_create(player_name.as_ref(), connection).await?),
+            (None, Some(player_id)) => Some(DatabasePlayer::by_id(PlayerId(player_id), connection).await?),
+            (Some(_), Some(_)) => return Err(CoreError::MutuallyExclusive.into()),
+            _ => None,
+        };
 
-            self.set_player(player, connection).await?;
+        if let Some(player) = player.filter(|player| player.id != self.player.id) {
+            self.transfer_to(player, connection).await?;
         }
 
         match (data.demon, data.demon_id) {
@@ -74,21 +107,25 @@ impl FullRecord {
                 self.set_demon(MinimalDemon::by_name(demon_name.as_ref(), connection).await?, connection)
                     .await?
             },
-            (None, Some(demon_id)) => self.set_demon(MinimalDemon::by_id(demon_id, connection).await?, connection).await?,
+            (None, Some(demon_id)) => {
+                self.set_demon(MinimalDemon::by_id(DemonId(demon_id), connection).await?, connection)
+                    .await?
+            },
             (Some(_), Some(_)) => return Err(CoreError::MutuallyExclusive.into()),
             _ => (),
         }
 
         // Not all record update require recomputing scores (for example, changing status from "submitted" to "under consideration")
         // but the logic for correctly determining this is hard, and updating scores of individual players cheap, so we do not bother.
-        self.player.update_score(connection).await?;
+        self.player.update_score(&mut *connection).await?;
+        self.reload_version(connection).await?;
 
         Ok(self)
     }
 
     /// Prepared turning `self` into a (player, demon)-record (either player or demon will be
     /// changed)
-    async fn ensure_invariants(&mut self, player: i32, demon: i32, connection: &mut PgConnection) -> Result<()> {
+    async fn ensure_invariants(&mut self, player: PlayerId, demon: DemonId, connection: &mut PgConnection) -> Result<()> {
         if self.player.id == player && self.demon.id == demon {
             warn!("Record::ensure_invariants was called, but the given player and demon ids match those we already have. Doing nothing.");
 
@@ -101,17 +138,21 @@ impl FullRecord {
 
                 let notes_transferred = sqlx::query!(
                     "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.demon = $2 AND \
-                     records.player = $3",
-                    self.id,
-                    demon,
-                    player
+                     records.player = $3 AND NOT records.verification",
+                    self.id.0,
+                    demon.0,
+                    player.0
                 )
                 .execute(&mut *connection)
                 .await?;
 
-                let records_deleted = sqlx::query!("DELETE FROM records WHERE player = $1 AND demon = $2", player, demon)
-                    .execute(connection)
-                    .await?;
+                let records_deleted = sqlx::query!(
+                    "DELETE FROM records WHERE player = $1 AND demon = $2 AND NOT verification",
+                    player.0,
+                    demon.0
+                )
+                .execute(connection)
+                .await?;
 
                 info!(
                     "Turning {} into a ({}, {})-record caused the transfer of {} notes and the deletion of {} records!",
@@ -139,9 +180,9 @@ impl FullRecord {
                 let row = sqlx::query_as!(
                     _Existing,
                     "SELECT id, progress, video::TEXT FROM records WHERE status_ = 'APPROVED' AND demon = $1 AND player = $2 AND progress \
-                     > $3",
-                    demon,
-                    player,
+                     > $3 AND NOT verification",
+                    demon.0,
+                    player.0,
                     self.progress
                 )
                 .fetch_optional(&mut *connection)
@@ -164,19 +205,19 @@ impl FullRecord {
 
                 let notes_transferred = sqlx::query!(
                     "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.demon = $2 AND \
-                     records.player = $3 AND (records.status_ = 'REJECTED' OR records.progress <= $4)",
-                    self.id,
-                    demon,
-                    player,
+                     records.player = $3 AND (records.status_ = 'REJECTED' OR records.progress <= $4) AND NOT records.verification",
+                    self.id.0,
+                    demon.0,
+                    player.0,
                     self.progress
                 )
                 .execute(&mut *connection)
                 .await?;
 
                 let records_deleted = sqlx::query!(
-                    "DELETE FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR progress <= $3)",
-                    demon,
-                    player,
+                    "DELETE FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR progress <= $3) AND NOT verification",
+                    demon.0,
+                    player.0,
                     self.progress
                 )
                 .execute(connection)
@@ -192,24 +233,39 @@ impl FullRecord {
                 );
             },
             // Nothing needed to be done here!
-            RecordStatus::Submitted | RecordStatus::UnderConsideration => {},
+            RecordStatus::Submitted | RecordStatus::UnderConsideration | RecordStatus::Superseded => {},
         }
 
         Ok(())
     }
 
     pub async fn delete_video(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE records SET video = NULL WHERE id = $1", self.id)
-            .execute(connection)
+        sqlx::query!("UPDATE records SET video = NULL WHERE id = $1", self.id.0)
+            .execute(&mut *connection)
             .await?;
 
         self.video = None;
+        self.discard_video_check(connection).await?;
+
+        Ok(())
+    }
+
+    pub async fn set_video_timestamp(&mut self, timestamp: Option<i32>, connection: &mut PgConnection) -> Result<()> {
+        if timestamp == self.video_timestamp {
+            return Ok(());
+        }
+
+        sqlx::query!("UPDATE records SET video_timestamp = $1 WHERE id = $2", timestamp, self.id.0)
+            .execute(connection)
+            .await?;
+
+        self.video_timestamp = timestamp;
 
         Ok(())
     }
 
     pub async fn delete_enjoyment(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE records SET enjoyment = NULL WHERE id = $1", self.id)
+        sqlx::query!("UPDATE records SET enjoyment = NULL WHERE id = $1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -223,7 +279,7 @@ impl FullRecord {
             return Ok(());
         }
 
-        sqlx::query!("UPDATE records SET enjoyment = $1::integer WHERE id = $2", enjoyment, self.id)
+        sqlx::query!("UPDATE records SET enjoyment = $1::integer WHERE id = $2", enjoyment, self.id.0)
             .execute(connection)
             .await?;
 
@@ -232,6 +288,18 @@ impl FullRecord {
         Ok(())
     }
 
+    /// Points this record's raw footage to the given URL. Used after raw footage was uploaded to
+    /// pointercrate directly, instead of being linked to
+    pub async fn set_raw_footage(&mut self, raw_footage: String, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE records SET raw_footage = $1 WHERE id = $2", raw_footage, self.id.0)
+            .execute(connection)
+            .await?;
+
+        self.raw_footage = Some(raw_footage);
+
+        Ok(())
+    }
+
     pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
         let video = crate::video::validate(&video)?;
 
@@ -239,11 +307,24 @@ impl FullRecord {
             return Ok(());
         }
 
-        sqlx::query!("UPDATE records SET video = $1::text WHERE id = $2", video, self.id)
-            .execute(connection)
+        sqlx::query!("UPDATE records SET video = $1::text WHERE id = $2", video, self.id.0)
+            .execute(&mut *connection)
             .await?;
 
         self.video = Some(video);
+        self.discard_video_check(connection).await?;
+
+        Ok(())
+    }
+
+    /// Forgets the results of previous [video checks](super::video_check), as they were about a
+    /// video this record no longer links to
+    async fn discard_video_check(&mut self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM record_video_checks WHERE record_id = $1", self.id.0)
+            .execute(connection)
+            .await?;
+
+        self.video_dead_since = None;
 
         Ok(())
     }
@@ -257,7 +338,7 @@ impl FullRecord {
 
         self.ensure_invariants(self.player.id, self.demon.id, connection).await?;
 
-        sqlx::query!("UPDATE records SET demon = $1 WHERE id = $2", demon.id, self.id)
+        sqlx::query!("UPDATE records SET demon = $1 WHERE id = $2", demon.id.0, self.id.0)
             .execute(connection)
             .await?;
 
@@ -266,6 +347,30 @@ impl FullRecord {
         Ok(())
     }
 
+    /// Transfers this record to a different player
+    ///
+    /// Unlike [`FullRecord::set_player`], this refuses to merge the record into an approved record
+    /// the given player already has on this record's demon, as that is almost certainly a mistake
+    /// when done via the API (and would, for rejected records, delete the approved record).
+    async fn transfer_to(&mut self, player: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
+        let existing = sqlx::query!(
+            "SELECT id FROM records WHERE player = $1 AND demon = $2 AND status_ = 'APPROVED' AND NOT verification",
+            player.id.0,
+            self.demon.id.0
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if let Some(existing) = existing {
+            return Err(DemonlistError::SubmissionExists {
+                status: RecordStatus::Approved,
+                existing: existing.id,
+            });
+        }
+
+        self.set_player(player, connection).await
+    }
+
     /// Changes the holder of this record
     ///
     /// If the new player has a record that would stand in conflict with this one, this records
@@ -281,7 +386,7 @@ impl FullRecord {
 
         self.ensure_invariants(player.id, self.demon.id, connection).await?;
 
-        sqlx::query!("UPDATE records SET player = $1 WHERE id = $2", player.id, self.id)
+        sqlx::query!("UPDATE records SET player = $1 WHERE id = $2", player.id.0, self.id.0)
             .execute(&mut *connection)
             .await?;
 
@@ -302,19 +407,19 @@ impl FullRecord {
                 // states, to ensure the record will be globally unique after this
                 sqlx::query!(
                     "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.player = $2 AND \
-                     records.demon = $3",
-                    self.id,
-                    self.player.id,
-                    self.demon.id
+                     records.demon = $3 AND NOT records.verification",
+                    self.id.0,
+                    self.player.id.0,
+                    self.demon.id.0
                 )
                 .execute(&mut *connection)
                 .await?;
 
                 sqlx::query!(
-                    "DELETE FROM records WHERE id <> $1 AND player = $2 AND demon = $3",
-                    self.id,
-                    self.player.id,
-                    self.demon.id
+                    "DELETE FROM records WHERE id <> $1 AND player = $2 AND demon = $3 AND NOT verification",
+                    self.id.0,
+                    self.player.id.0,
+                    self.demon.id.0
                 )
                 .execute(&mut *connection)
                 .await?;
@@ -326,50 +431,101 @@ impl FullRecord {
             // Nothing needed here, a 'rejected' record is globally unique
             (RecordStatus::Rejected, _) => (),
 
-            (RecordStatus::Submitted, RecordStatus::Approved) | (RecordStatus::UnderConsideration, RecordStatus::Approved) => {
-                // Since a rejected record is globally unique, we know no other (player,
-                // demon)-record is 'rejected'. We also know that the submission has at least as
-                // much progress as an 'accepted' (player, demon)-record. We can therefore just
-                // delete all other records with less or equal progress to the current one
-
-                sqlx::query!(
-                    "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.player = $2 AND \
-                     records.demon = $3 AND progress <= $4",
-                    self.id,
-                    self.player.id,
-                    self.demon.id,
-                    self.progress
+            (RecordStatus::Superseded, RecordStatus::Approved) => {
+                // A superseded record has at most as much progress as the record that superseded it,
+                // so it cannot be approved again while that record is still approved
+                let existing = sqlx::query!(
+                    "SELECT id FROM records WHERE id <> $1 AND player = $2 AND demon = $3 AND status_ = 'APPROVED' AND NOT verification",
+                    self.id.0,
+                    self.player.id.0,
+                    self.demon.id.0
                 )
-                .execute(&mut *connection)
+                .fetch_optional(&mut *connection)
                 .await?;
 
-                sqlx::query!(
-                    "DELETE FROM records WHERE id <> $1 AND records.player = $2 AND records.demon = $3 AND progress <= $4",
-                    self.id,
-                    self.player.id,
-                    self.demon.id,
-                    self.progress
-                )
-                .execute(&mut *connection)
-                .await?;
+                if let Some(existing) = existing {
+                    return Err(DemonlistError::SubmissionExists {
+                        status: RecordStatus::Approved,
+                        existing: existing.id,
+                    });
+                }
+
+                self.supersede_lower_records(connection).await?;
+            },
+
+            (RecordStatus::Submitted | RecordStatus::UnderConsideration, RecordStatus::Approved) => {
+                // Since a rejected record is globally unique, we know no other (player,
+                // demon)-record is 'rejected'. We also know that the submission has at least as
+                // much progress as an 'accepted' (player, demon)-record. We can therefore just
+                // delete all other submissions with less or equal progress to the current one, and
+                // supersede the currently approved record (which is kept for history)
+                self.supersede_lower_records(connection).await?;
             },
 
             // the other cases just convert back and forth between 'submitted' and 'under consideration', which doesn't change anything
             _ => (),
         }
 
+        // Once a record's status was changed manually, unbanning its player must no longer touch it
         sqlx::query!(
-            "UPDATE records SET status_ = cast($1::text as record_status) WHERE id = $2", /* FIXME(sqlx) ridiculous query
-                                                                                           * format to trick sqlx into working
-                                                                                           * with custom types */
+            // FIXME(sqlx) ridiculous query format to trick sqlx into working with custom types
+            "UPDATE records SET status_ = cast($1::text as record_status), rejected_by_ban = FALSE WHERE id = $2",
             status.to_sql().to_string(),
-            self.id
+            self.id.0
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
         self.status = status;
 
+        if status == RecordStatus::Approved {
+            award_achievements(self.player.id.0, connection).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes all submissions of this record's (player, demon)-tuple with at most this record's
+    /// progress (transferring their notes to this record), and supersedes the approved record of
+    /// that tuple if it has at most this record's progress
+    async fn supersede_lower_records(&self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.player = $2 AND \
+             records.demon = $3 AND progress <= $4 AND status_ IN ('SUBMITTED', 'UNDER_CONSIDERATION')",
+            self.id.0,
+            self.player.id.0,
+            self.demon.id.0,
+            self.progress
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM records WHERE id <> $1 AND records.player = $2 AND records.demon = $3 AND progress <= $4 AND status_ IN \
+             ('SUBMITTED', 'UNDER_CONSIDERATION')",
+            self.id.0,
+            self.player.id.0,
+            self.demon.id.0,
+            self.progress
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let superseded = sqlx::query!(
+            "UPDATE records SET status_ = 'SUPERSEDED' WHERE id <> $1 AND player = $2 AND demon = $3 AND progress <= $4 AND status_ \
+             = 'APPROVED' AND NOT verification",
+            self.id.0,
+            self.player.id.0,
+            self.demon.id.0,
+            self.progress
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        if superseded.rows_affected() > 0 {
+            info!("Approving {} superseded the previously approved record", self);
+        }
+
         Ok(())
     }
 
@@ -389,9 +545,9 @@ impl FullRecord {
             sqlx::query!(
                 "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND player = $2 AND demon = $3 \
                  AND progress < $4 AND status_='SUBMITTED'",
-                self.id,
-                self.player.id,
-                self.demon.id,
+                self.id.0,
+                self.player.id.0,
+                self.demon.id.0,
                 progress
             )
             .execute(&mut *connection)
@@ -399,8 +555,8 @@ impl FullRecord {
 
             let deleted = sqlx::query!(
                 "DELETE FROM records WHERE player = $1 AND demon = $2 AND status_='SUBMITTED'",
-                self.player.id,
-                self.demon.id
+                self.player.id.0,
+                self.demon.id.0
             )
             .execute(&mut *connection)
             .await?;
@@ -414,7 +570,7 @@ impl FullRecord {
             );
         }
 
-        sqlx::query!("UPDATE records SET progress = $1 WHERE id = $2", progress, self.id)
+        sqlx::query!("UPDATE records SET progress = $1 WHERE id = $2", progress, self.id.0)
             .execute(connection)
             .await?;
 
@@ -1,12 +1,20 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{DemonId, MinimalDemon},
     error::{DemonlistError, Result},
     player::{claim::PlayerClaim, DatabasePlayer},
-    record::{FullRecord, RecordStatus},
+    record::{
+        assignment,
+        spam::{SpamAssessment, SpamCheck},
+        FullRecord, RecordId, RecordStatus,
+    },
+    requirement::ListRequirement,
+    settings::SubmissionSettings,
     submitter::Submitter,
 };
+use chrono::{Duration, Utc};
 use derive_more::Display;
-use log::debug;
+use log::{debug, info};
+use pointercrate_core::validate::{normalize_name, validated, Validate, Validator};
 use serde::Deserialize;
 use sqlx::PgConnection;
 use url::Url;
@@ -19,6 +27,11 @@ pub struct Submission {
     demon: i32,
     #[serde(default)]
     video: Option<String>,
+
+    /// The time within the video at which the completion happens, see
+    /// [`parse_timestamp`](crate::video::parse_timestamp)
+    #[serde(default)]
+    video_timestamp: Option<String>,
     #[serde(default)]
     raw_footage: Option<String>,
     enjoyment: Option<i32>,
@@ -28,6 +41,16 @@ pub struct Submission {
     /// An initial, submitter provided note for the demon.
     #[serde(default)]
     note: Option<String>,
+
+    /// Honeypot field hidden from humans in the submission form, see [`spam`](crate::record::spam)
+    #[serde(default, rename = "website")]
+    honeypot: Option<String>,
+
+    /// Whether to accept this record even though its progress is below the demon's current
+    /// requirement, e.g. because it was achieved before the requirement was raised. Only list
+    /// moderators can set this.
+    #[serde(default)]
+    grandfathered: bool,
 }
 
 #[derive(Debug)]
@@ -38,20 +61,51 @@ pub struct NormalizedSubmission {
     status: RecordStatus,
     enjoyment: Option<i32>,
     video: Option<String>,
+    video_timestamp: Option<i32>,
     raw_footage: Option<String>,
     note: Option<String>,
+    honeypot_filled: bool,
+    grandfathered: bool,
 }
 
 #[derive(Debug)]
 pub struct ValidatedSubmission {
     progress: i16,
     video: Option<String>,
+    video_timestamp: Option<i32>,
     raw_footage: Option<String>,
     enjoyment: Option<i32>,
     status: RecordStatus,
     player: DatabasePlayer,
     demon: MinimalDemon,
     note: Option<String>,
+    honeypot_filled: bool,
+}
+
+impl Validate for Submission {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        self.player = normalize_name(&self.player);
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        if let Some(ref video) = self.video {
+            validator.result("video", crate::video::validate(video));
+        }
+
+        if let Some(ref raw_footage) = self.raw_footage {
+            validator.check("raw_footage", Url::parse(raw_footage).is_ok(), || DemonlistError::MalformedRawUrl);
+        }
+
+        if let Some(ref timestamp) = self.video_timestamp {
+            validator.result("video_timestamp", crate::video::parse_timestamp(timestamp));
+        }
+
+        if let Some(enjoyment) = self.enjoyment {
+            validator.check("enjoyment", (1..=10).contains(&enjoyment), || DemonlistError::InvalidEnjoyment);
+        }
+    }
 }
 
 impl Submission {
@@ -63,33 +117,64 @@ impl Submission {
         self.status
     }
 
+    pub fn is_grandfathered(&self) -> bool {
+        self.grandfathered
+    }
+
     pub async fn normalize(self, connection: &mut PgConnection) -> Result<NormalizedSubmission> {
-        // validate video
-        let video = match self.video {
+        let submission = validated(self)?;
+
+        // canonicalize video
+        let video = match submission.video {
             Some(ref video) => Some(crate::video::validate(video)?),
             None => None,
         };
 
+        let video_timestamp = match submission.video_timestamp {
+            Some(ref timestamp) => Some(crate::video::parse_timestamp(timestamp)?),
+            None => None,
+        };
+
         // Resolve player and demon name against the database
-        let player = DatabasePlayer::by_name_or_create(self.player.as_ref(), connection).await?;
-        let demon = MinimalDemon::by_id(self.demon, connection).await?;
+        let player = DatabasePlayer::by_name_or_create(submission.player.as_ref(), connection).await?;
+        let demon = MinimalDemon::by_id(DemonId(submission.demon), connection).await?;
 
         Ok(NormalizedSubmission {
-            progress: self.progress,
+            progress: submission.progress,
             player,
             demon,
-            status: self.status,
+            status: submission.status,
             video,
-            raw_footage: self.raw_footage,
-            enjoyment: self.enjoyment,
-            note: self.note,
+            video_timestamp,
+            raw_footage: submission.raw_footage,
+            enjoyment: submission.enjoyment,
+            note: submission.note,
+            honeypot_filled: submission.honeypot.is_some_and(|honeypot| !honeypot.trim().is_empty()),
+            grandfathered: submission.grandfathered,
         })
     }
 }
 
 impl NormalizedSubmission {
+    /// An already approved record, as added by a [record import](crate::record::import)
+    pub(super) fn imported(player: DatabasePlayer, demon: MinimalDemon, progress: i16, video: Option<String>) -> Self {
+        NormalizedSubmission {
+            progress,
+            player,
+            demon,
+            status: RecordStatus::Approved,
+            enjoyment: None,
+            video,
+            video_timestamp: None,
+            raw_footage: None,
+            note: None,
+            honeypot_filled: false,
+            grandfathered: false,
+        }
+    }
+
     pub async fn verified_player_claim(&self, connection: &mut PgConnection) -> Result<Option<PlayerClaim>> {
-        PlayerClaim::verified_claim_on(self.player.id, connection).await
+        PlayerClaim::verified_claim_on(self.player.id.0, connection).await
     }
 
     pub async fn validate(self, connection: &mut PgConnection) -> Result<ValidatedSubmission> {
@@ -109,17 +194,52 @@ impl NormalizedSubmission {
             return Err(DemonlistError::Non100Extended);
         }
 
+        if self.status == RecordStatus::Submitted {
+            let settings = SubmissionSettings::load(&mut *connection).await?;
+
+            if !settings.accepting_submissions() {
+                return Err(DemonlistError::ListSubmissionsClosed {
+                    reason: settings.closed_reason,
+                    reopen_at: settings.reopen_at,
+                });
+            }
+
+            let demon = sqlx::query!(
+                "SELECT submissions_open, submissions_closed_reason FROM demons WHERE id = $1",
+                self.demon.id.0
+            )
+            .fetch_one(&mut *connection)
+            .await?;
+
+            if !demon.submissions_open {
+                return Err(DemonlistError::SubmissionsClosed {
+                    reason: demon.submissions_closed_reason,
+                });
+            }
+        }
+
         let requirement = self.demon.requirement(&mut *connection).await?;
 
-        if self.progress > 100 || self.progress < requirement {
+        // Grandfathered records only need to be valid percentages
+        if self.progress > 100 || self.progress < 0 || (self.progress < requirement && !self.grandfathered) {
             return Err(DemonlistError::InvalidProgress { requirement });
         }
 
+        if self.status == RecordStatus::Submitted && !self.grandfathered {
+            let list_requirement = ListRequirement::current(&mut *connection).await?;
+
+            if let Some(requirement) = list_requirement.applying_to(&self.demon) {
+                if self.progress < requirement {
+                    return Err(DemonlistError::BelowListRequirement { requirement });
+                }
+            }
+        }
+
         debug!("Submission is valid, checking for duplicates!");
         debug!("Nevermind!");
 
         if let Some(ref video) = self.video {
-            if let Some(row) = sqlx::query!(r#"SELECT id, status_::text as "status_!: String" FROM records WHERE video = $1 AND demon = $2"#, video.to_string(), self.demon.id)
+            if let Some(row) = sqlx::query!(r#"SELECT id, status_::text as "status_!: String" FROM records WHERE video = $1 AND demon = $2"#, video.to_string(), self.demon.id.0)
                 .fetch_optional(&mut *connection) // FIXME(sqlx)
                 .await?
             {
@@ -132,9 +252,9 @@ impl NormalizedSubmission {
 
         let existing = sqlx::query!(
             r#"SELECT id, status_::text as "status_!: String" FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR status_ = 
-             'UNDER_CONSIDERATION' OR (status_ = 'APPROVED' AND progress >= $3)) LIMIT 1"#,
-            self.demon.id,
-            self.player.id,
+             'UNDER_CONSIDERATION' OR (status_ = 'APPROVED' AND progress >= $3)) AND NOT verification LIMIT 1"#,
+            self.demon.id.0,
+            self.player.id.0,
             self.progress
         )
             .fetch_optional(&mut *connection)
@@ -147,64 +267,132 @@ impl NormalizedSubmission {
             });
         }
 
-        match self.raw_footage {
-            Some(ref raw) => {
-                let _ = Url::parse(raw).map_err(|_| DemonlistError::MalformedRawUrl)?;
-            },
-
-            _ => (),
-        }
-
         Ok(ValidatedSubmission {
             progress: self.progress,
             video: self.video,
+            video_timestamp: self.video_timestamp,
             raw_footage: self.raw_footage,
             enjoyment: self.enjoyment,
             status: self.status,
             player: self.player,
             demon: self.demon,
             note: self.note,
+            honeypot_filled: self.honeypot_filled,
         })
     }
 }
 
+/// Enforces the per-submitter limits configured in the `[submissions]` section of the configuration
+async fn check_submission_limits(submitter: &Submitter, demon_id: i32, connection: &mut PgConnection) -> Result<()> {
+    let limits = &pointercrate_core::config::get().submissions;
+
+    if limits.max_pending > 0 {
+        let pending = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM records WHERE submitter = $1 AND status_ = 'SUBMITTED'"#,
+            submitter.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .count;
+
+        if pending >= limits.max_pending as i64 {
+            return Err(DemonlistError::TooManyPendingSubmissions { limit: limits.max_pending });
+        }
+    }
+
+    if limits.demon_cooldown > 0 {
+        let last_submission = sqlx::query!(
+            "SELECT MAX(record_additions.time) AS last_submission FROM records INNER JOIN record_additions ON record_additions.id = \
+             records.id WHERE records.submitter = $1 AND records.demon = $2",
+            submitter.id.0,
+            demon_id
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .last_submission;
+
+        if let Some(last_submission) = last_submission {
+            let retry_after = last_submission + Duration::seconds(limits.demon_cooldown as i64);
+
+            if retry_after > Utc::now() {
+                return Err(DemonlistError::SubmissionCooldown { retry_after });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl ValidatedSubmission {
     pub async fn create(self, submitter: Submitter, connection: &mut PgConnection) -> Result<FullRecord> {
-        let id = sqlx::query!(
-            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage, enjoyment) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4, $5, $6, $7) RETURNING id",
+        let mut spam = SpamAssessment::default();
+
+        if self.status == RecordStatus::Submitted {
+            check_submission_limits(&submitter, self.demon.id.0, connection).await?;
+
+            spam = SpamCheck {
+                submitter: &submitter,
+                player_id: self.player.id.0,
+                honeypot_filled: self.honeypot_filled,
+                video: self.video.as_deref(),
+                raw_footage: self.raw_footage.as_deref(),
+                note: self.note.as_deref(),
+            }
+            .assess(connection)
+            .await?;
+
+            if spam.is_suspicious() {
+                info!(
+                    "Submission {} by submitter {} looks like spam: {:?}",
+                    self.demon, submitter.id, spam.reasons
+                );
+            }
+        }
+
+        let inserted = sqlx::query!(
+            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage, enjoyment, spam_score, spam_reasons, video_timestamp) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4, $5, $6, $7, $8, $9, $10) RETURNING id, version, (SELECT public_id FROM submitters WHERE submitter_id = $4) AS \"anonymous_submitter!\"",
             self.progress,
             self.video,
-            self.player.id,
-            submitter.id,
-            self.demon.id,
+            self.player.id.0,
+            submitter.id.0,
+            self.demon.id.0,
             self.raw_footage,
-            self.enjoyment
+            self.enjoyment,
+            spam.score,
+            &spam.reasons,
+            self.video_timestamp
         )
         .fetch_one(&mut *connection)
-        .await?
-        .id;
+        .await?;
 
         let mut record = FullRecord {
-            id,
+            id: RecordId(inserted.id),
             progress: self.progress,
             video: self.video,
+            video_timestamp: self.video_timestamp,
             raw_footage: self.raw_footage,
             status: RecordStatus::Submitted,
             player: self.player,
             demon: self.demon,
             submitter: Some(submitter),
+            anonymous_submitter: Some(inserted.anonymous_submitter),
             enjoyment: self.enjoyment,
+            version: inserted.version,
+            spam: Some(spam),
+            verification: false,
+            video_dead_since: None,
         };
 
         // Dealing with different status and upholding their invariant is complicated, we should not
         // duplicate that code!
         if self.status != RecordStatus::Submitted {
             record.set_status(self.status, &mut *connection).await?;
+            record.reload_version(&mut *connection).await?;
         }
 
         if let Some(note) = self.note {
             if !note.trim().is_empty() {
-                sqlx::query!("INSERT INTO record_notes (record, content) VALUES ($1, $2)", record.id, note)
+                sqlx::query!("INSERT INTO record_notes (record, content) VALUES ($1, $2)", record.id.0, note)
                     .execute(&mut *connection)
                     .await?;
             }
@@ -212,6 +400,8 @@ impl ValidatedSubmission {
 
         if self.status != RecordStatus::Submitted {
             record.player.update_score(connection).await?;
+        } else if crate::config::auto_assign() {
+            assignment::assign(record.id.0, connection).await?;
         }
 
         Ok(record)
@@ -221,15 +411,14 @@ impl ValidatedSubmission {
 #[cfg(test)]
 mod tests {
     use crate::{
-        demon::MinimalDemon,
+        demon::{DemonId, MinimalDemon},
         error::DemonlistError,
-        player::DatabasePlayer,
+        player::{DatabasePlayer, PlayerId},
         record::{post::NormalizedSubmission, RecordStatus},
     };
-    use pointercrate_core::pool::PointercratePool;
-    use sqlx::{Postgres, Transaction};
+    use pointercrate_core::pool::{PointercratePool, RequestTransaction};
 
-    async fn connection() -> Transaction<'static, Postgres> {
+    async fn connection() -> RequestTransaction {
         let _ = dotenv::dotenv();
 
         PointercratePool::init().await.transaction().await.unwrap()
@@ -242,20 +431,23 @@ mod tests {
         let result = NormalizedSubmission {
             progress: 100,
             player: DatabasePlayer {
-                id: 1,
+                id: PlayerId(1),
                 name: "stardust1971".to_string(),
                 banned: true,
             },
             demon: MinimalDemon {
-                id: 1,
+                id: DemonId(1),
                 position: 1,
                 name: "Bloodbath".to_string(),
             },
             status: RecordStatus::Submitted,
             video: None,
+            video_timestamp: None,
             raw_footage: None,
             enjoyment: None,
             note: None,
+            honeypot_filled: false,
+            grandfathered: false,
         }
         .validate(&mut conn)
         .await;
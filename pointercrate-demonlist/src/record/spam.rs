@@ -0,0 +1,124 @@
+//! Heuristic spam scoring of record submissions
+//!
+//! Submissions are never rejected based on their score. Instead, the score (together with the
+//! reasons that contributed to it) is stored alongside the record, so that list staff can prioritize
+//! (or deprioritize) suspicious submissions in the queue.
+
+use crate::{error::Result, record::fingerprint, submitter::Submitter};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use url::Url;
+
+/// Score from which on a submission is considered suspicious
+pub const SUSPICIOUS_SCORE: i16 = 50;
+
+/// Number of submissions a single submitter can make within an hour before further submissions are
+/// considered suspicious
+const VELOCITY_LIMIT: i64 = 5;
+
+/// Hosts of URL shorteners and IP loggers, which have no business being in a submission
+const BAD_HOSTS: &[&str] = &[
+    "bit.ly",
+    "tinyurl.com",
+    "t.co",
+    "is.gd",
+    "cutt.ly",
+    "shorturl.at",
+    "grabify.link",
+    "iplogger.org",
+    "iplogger.com",
+    "2no.co",
+    "yip.su",
+];
+
+#[derive(Debug, Default, Clone, Hash, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpamAssessment {
+    pub score: i16,
+
+    /// Human readable descriptions of everything that contributed to the score
+    pub reasons: Vec<String>,
+}
+
+impl SpamAssessment {
+    fn flag(&mut self, score: i16, reason: &str) {
+        self.score = self.score.saturating_add(score);
+        self.reasons.push(reason.to_string());
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.score >= SUSPICIOUS_SCORE
+    }
+}
+
+fn has_bad_host(url: &str) -> bool {
+    match Url::parse(url) {
+        Ok(url) => url.host_str().is_some_and(|host| {
+            let host = host.trim_start_matches("www.");
+
+            BAD_HOSTS.contains(&host)
+        }),
+        Err(_) => false,
+    }
+}
+
+/// The parts of a submission relevant for spam scoring
+pub struct SpamCheck<'a> {
+    pub submitter: &'a Submitter,
+
+    /// The player the record is submitted for
+    pub player_id: i32,
+
+    /// Whether the honeypot field (invisible to humans in the submission form) was filled out
+    pub honeypot_filled: bool,
+    pub video: Option<&'a str>,
+    pub raw_footage: Option<&'a str>,
+    pub note: Option<&'a str>,
+}
+
+impl SpamCheck<'_> {
+    pub async fn assess(&self, connection: &mut PgConnection) -> Result<SpamAssessment> {
+        let mut assessment = SpamAssessment::default();
+
+        if self.honeypot_filled {
+            assessment.flag(100, "Honeypot field was filled out");
+        }
+
+        let recent_submissions = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM records INNER JOIN record_additions ON record_additions.id = records.id WHERE
+             records.submitter = $1 AND record_additions.time > NOW() - INTERVAL '1 hour'"#,
+            self.submitter.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .count;
+
+        if recent_submissions >= VELOCITY_LIMIT {
+            assessment.flag(30, "Submitter made many submissions within the last hour");
+        }
+
+        if let Some(video) = self.video {
+            // Duplicates on the same demon are already rejected during validation
+            let reused = sqlx::query!(r#"SELECT EXISTS(SELECT 1 FROM records WHERE video = $1) AS "exists!""#, video)
+                .fetch_one(&mut *connection)
+                .await?
+                .exists;
+
+            if reused {
+                assessment.flag(40, "Video is already used for a record on a different demon");
+            }
+
+            if fingerprint::proves_record_of_other_player(video, self.player_id, &mut *connection).await? {
+                assessment.flag(50, "Video already proves an approved record of a different player");
+            }
+        }
+
+        let links_bad_host = self.video.into_iter().chain(self.raw_footage).any(has_bad_host)
+            || self.note.is_some_and(|note| note.split_whitespace().any(has_bad_host));
+
+        if links_bad_host {
+            assessment.flag(50, "Submission contains a link to a URL shortener or IP logger");
+        }
+
+        Ok(assessment)
+    }
+}
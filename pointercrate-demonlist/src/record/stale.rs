@@ -0,0 +1,100 @@
+//! Escalation of submissions that have been waiting for review for too long
+//!
+//! Submissions pending for longer than the configured
+//! [threshold](crate::config::stale_submission_threshold) end up in a separate queue for list
+//! moderators, so that they do not get lost at the bottom of the regular queue. How long a
+//! submission has been pending is measured from its entry in the record audit log. Submissions
+//! predating the audit log always count as stale.
+
+use crate::{
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+    player::{DatabasePlayer, PlayerId},
+    record::RecordId,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct StaleSubmission {
+    pub id: RecordId,
+    pub progress: i16,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+    pub submitted_at: Option<DateTime<Utc>>,
+
+    /// How long (in seconds) this submission has been waiting for review, if known
+    pub pending_for: Option<i64>,
+}
+
+/// All submissions that have been waiting for review for more than `threshold` seconds, the ones
+/// waiting the longest first. Empty if `threshold` is `0`
+pub async fn stale_submissions(threshold: u64, connection: &mut impl ReadConnection) -> Result<Vec<StaleSubmission>> {
+    let connection = &mut connection.reader();
+    if threshold == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, records.progress, players.id AS player_id, players.name AS "player_name: String",
+                  players.banned AS player_banned, demons.id AS demon_id, demons.name AS "demon_name: String", demons.position,
+                  record_additions.time AS "submitted_at?",
+                  EXTRACT(EPOCH FROM NOW() - record_additions.time)::BIGINT AS pending_for
+           FROM records
+           LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+           INNER JOIN players ON records.player = players.id
+           INNER JOIN demons ON records.demon = demons.id
+           WHERE records.status_ = 'SUBMITTED'
+             AND (record_additions.time IS NULL
+                  OR record_additions.time < NOW() - make_interval(secs => $1::DOUBLE PRECISION))
+           ORDER BY record_additions.time NULLS FIRST, records.id"#,
+        threshold as f64
+    )
+    .fetch(connection);
+
+    let mut submissions = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        submissions.push(StaleSubmission {
+            id: RecordId(row.id),
+            progress: row.progress,
+            player: DatabasePlayer {
+                id: PlayerId(row.player_id),
+                name: row.player_name,
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: DemonId(row.demon_id),
+                position: row.position,
+                name: row.demon_name,
+            },
+            submitted_at: row.submitted_at,
+            pending_for: row.pending_for,
+        })
+    }
+
+    Ok(submissions)
+}
+
+/// The number of submissions [`stale_submissions`] would return
+pub async fn stale_submission_count(threshold: u64, connection: &mut impl ReadConnection) -> Result<i64> {
+    let connection = &mut connection.reader();
+    if threshold == 0 {
+        return Ok(0);
+    }
+
+    Ok(sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM records LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+           WHERE records.status_ = 'SUBMITTED'
+             AND (record_additions.time IS NULL
+                  OR record_additions.time < NOW() - make_interval(secs => $1::DOUBLE PRECISION))"#,
+        threshold as f64
+    )
+    .fetch_one(connection)
+    .await?
+    .count)
+}
@@ -0,0 +1,53 @@
+//! Verification records
+//!
+//! The verification of a demon is represented by an approved 100% record of its verifier, flagged as
+//! [`verification`](crate::record::FullRecord::verification). This way, everything that derives
+//! statistics from approved records (most importantly scores) accounts for verifications without
+//! having to look at `demons.verifier` separately.
+//!
+//! Verification records are created together with their demon and follow its verifier and video
+//! afterwards (see [`sync_verification_record`]). They cannot be modified or deleted via the record
+//! endpoints, and they are not shown among (or counted towards) a demon's records.
+
+use crate::{demon::Demon, error::Result, record::RecordStatus};
+use log::info;
+use sqlx::PgConnection;
+
+/// Creates the verification record of the given demon, or updates it to match the demon's current
+/// verifier and video. The record is rejected while the verifier is banned.
+///
+/// Does not update any scores.
+pub(crate) async fn sync_verification_record(demon: &Demon, connection: &mut PgConnection) -> Result<()> {
+    let status = if demon.verifier.banned {
+        RecordStatus::Rejected
+    } else {
+        RecordStatus::Approved
+    };
+
+    let updated = sqlx::query!(
+        "UPDATE records SET player = $1, video = $2::TEXT, status_ = cast($3::text as record_status) WHERE demon = $4 AND verification",
+        demon.verifier.id.0,
+        demon.video,
+        status.to_sql(),
+        demon.base.id.0
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        info!("Creating verification record of {} for {}", demon.base, demon.verifier);
+
+        sqlx::query!(
+            "INSERT INTO records (progress, video, status_, player, demon, verification) VALUES (100, $1::TEXT, cast($2::text as \
+             record_status), $3, $4, TRUE)",
+            demon.video,
+            status.to_sql(),
+            demon.verifier.id.0,
+            demon.base.id.0
+        )
+        .execute(connection)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Periodic checks of whether the videos of approved records are still available
+//!
+//! Videos get deleted or made private long after the records they prove were approved. A background
+//! job regularly re-checks every approved record's video (see [`due_video_checks`]) and stores the
+//! outcome in the `record_video_checks` table. Records whose video was found to be gone end up in a
+//! queue for list staff (see [`dead_video_records`]) and have their
+//! [`video_dead_since`](super::FullRecord::video_dead_since) set.
+//!
+//! The actual availability check happens outside of this crate, as it requires talking to the video
+//! hosts.
+
+use crate::{
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+    player::{DatabasePlayer, PlayerId},
+    record::RecordId,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug)]
+pub struct DueVideoCheck {
+    pub record_id: i32,
+    pub video: String,
+}
+
+/// Up to `limit` approved records whose video has never been checked, or was last checked more than
+/// `interval` seconds ago. Records that have never been checked come first
+pub async fn due_video_checks(interval: u64, limit: i64, connection: &mut PgConnection) -> Result<Vec<DueVideoCheck>> {
+    Ok(sqlx::query_as!(
+        DueVideoCheck,
+        r#"SELECT records.id AS record_id, records.video::TEXT AS "video!"
+           FROM records
+           LEFT OUTER JOIN record_video_checks ON record_video_checks.record_id = records.id
+           WHERE records.status_ = 'APPROVED' AND records.video IS NOT NULL
+             AND (record_video_checks.checked_at IS NULL
+                  OR record_video_checks.checked_at < NOW() - make_interval(secs => $1::DOUBLE PRECISION))
+           ORDER BY record_video_checks.checked_at NULLS FIRST, records.id
+           LIMIT $2"#,
+        interval as f64,
+        limit
+    )
+    .fetch_all(connection)
+    .await?)
+}
+
+/// Stores the outcome of checking the given record's video. If the video is unavailable, the time it
+/// was first found to be unavailable is retained
+pub async fn record_video_check(record_id: i32, available: bool, connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO record_video_checks (record_id, dead_since) VALUES ($1, CASE WHEN $2 THEN NULL ELSE NOW() END)
+         ON CONFLICT (record_id) DO UPDATE SET checked_at = EXCLUDED.checked_at,
+             dead_since = CASE WHEN $2 THEN NULL ELSE COALESCE(record_video_checks.dead_since, EXCLUDED.dead_since) END",
+        record_id,
+        available
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+/// An approved record whose video was unavailable when it was last checked
+#[derive(Debug, Serialize)]
+pub struct DeadVideoRecord {
+    pub id: RecordId,
+    pub progress: i16,
+    pub video: String,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+    pub dead_since: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// All approved records whose video is currently known to be unavailable, the ones whose video has
+/// been gone for the longest first
+pub async fn dead_video_records(connection: &mut impl ReadConnection) -> Result<Vec<DeadVideoRecord>> {
+    let connection = &mut connection.reader();
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, records.progress, records.video::TEXT AS "video!", players.id AS player_id,
+                  players.name AS "player_name: String", players.banned AS player_banned, demons.id AS demon_id,
+                  demons.name AS "demon_name: String", demons.position, record_video_checks.dead_since AS "dead_since!",
+                  record_video_checks.checked_at
+           FROM record_video_checks
+           INNER JOIN records ON records.id = record_video_checks.record_id
+           INNER JOIN players ON records.player = players.id
+           INNER JOIN demons ON records.demon = demons.id
+           WHERE record_video_checks.dead_since IS NOT NULL AND records.status_ = 'APPROVED' AND records.video IS NOT NULL
+           ORDER BY record_video_checks.dead_since, records.id"#
+    )
+    .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(DeadVideoRecord {
+            id: RecordId(row.id),
+            progress: row.progress,
+            video: row.video,
+            player: DatabasePlayer {
+                id: PlayerId(row.player_id),
+                name: row.player_name,
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: DemonId(row.demon_id),
+                position: row.position,
+                name: row.demon_name,
+            },
+            dead_since: row.dead_since,
+            checked_at: row.checked_at,
+        })
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,34 @@
+use crate::{
+    error::{DemonlistError, Result},
+    report::{Report, ReportCategory, ReportId, ReportStatus},
+};
+use pointercrate_core::pool::ReadConnection;
+use sqlx::Error;
+
+impl Report {
+    pub async fn by_id(ReportId(id): ReportId, connection: &mut impl ReadConnection) -> Result<Report> {
+        let connection = &mut connection.reader();
+        let result = sqlx::query!(
+            "SELECT id, record, player, category, description, status, created_at, resolved_by, resolution_note FROM reports WHERE id = $1",
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(row) => Ok(Report {
+                id: ReportId(row.id),
+                record: row.record,
+                player: row.player,
+                category: ReportCategory::from_sql(&row.category),
+                description: row.description,
+                status: ReportStatus::from_sql(&row.status),
+                created_at: row.created_at,
+                resolved_by: row.resolved_by,
+                resolution_note: row.resolution_note,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::ReportNotFound { report_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
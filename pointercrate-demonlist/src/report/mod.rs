@@ -0,0 +1,121 @@
+//! Abuse reports on records (e.g. hacked runs) and players (e.g. impersonation)
+//!
+//! Reports can be filed by any visitor and form a review queue for list staff. Each report targets
+//! exactly one record or player.
+
+pub use self::{paginate::ReportPagination, patch::PatchReport, post::NewReport};
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use pointercrate_core::etag::Taggable;
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+
+mod get;
+mod paginate;
+mod patch;
+mod post;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`Report`]
+    ReportId
+);
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportCategory {
+    /// The run in a record was not legitimate (hacks, speedhacks, ...)
+    #[display(fmt = "hacked")]
+    Hacked,
+
+    /// The video of a record does not show the claimed progress, or belongs to someone else
+    #[display(fmt = "invalid_video")]
+    InvalidVideo,
+
+    /// A player object was created to impersonate someone
+    #[display(fmt = "impersonation")]
+    Impersonation,
+
+    /// A player's name is offensive
+    #[display(fmt = "inappropriate_name")]
+    InappropriateName,
+
+    #[display(fmt = "other")]
+    Other,
+}
+
+impl ReportCategory {
+    pub fn applies_to_records(self) -> bool {
+        matches!(self, ReportCategory::Hacked | ReportCategory::InvalidVideo | ReportCategory::Other)
+    }
+
+    pub fn applies_to_players(self) -> bool {
+        matches!(
+            self,
+            ReportCategory::Impersonation | ReportCategory::InappropriateName | ReportCategory::Other
+        )
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "hacked" => ReportCategory::Hacked,
+            "invalid_video" => ReportCategory::InvalidVideo,
+            "impersonation" => ReportCategory::Impersonation,
+            "inappropriate_name" => ReportCategory::InappropriateName,
+            "other" => ReportCategory::Other,
+            _ => panic!("invalid report category: {}", sql),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    #[display(fmt = "open")]
+    Open,
+
+    #[display(fmt = "under_review")]
+    UnderReview,
+
+    /// The report was valid, and action was taken
+    #[display(fmt = "resolved")]
+    Resolved,
+
+    /// The report was invalid
+    #[display(fmt = "dismissed")]
+    Dismissed,
+}
+
+impl ReportStatus {
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "open" => ReportStatus::Open,
+            "under_review" => ReportStatus::UnderReview,
+            "resolved" => ReportStatus::Resolved,
+            "dismissed" => ReportStatus::Dismissed,
+            _ => panic!("invalid report status: {}", sql),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Hash, Display)]
+#[display(fmt = "{} report (ID: {})", category, id)]
+pub struct Report {
+    pub id: ReportId,
+
+    /// The id of the reported record, if this report is about a record
+    pub record: Option<i32>,
+
+    /// The id of the reported player, if this report is about a player
+    pub player: Option<i32>,
+
+    pub category: ReportCategory,
+    pub description: String,
+    pub status: ReportStatus,
+    pub created_at: DateTime<Utc>,
+
+    /// The staff member who last changed the status of this report
+    pub resolved_by: Option<i32>,
+    pub resolution_note: Option<String>,
+}
+
+impl Taggable for Report {}
@@ -0,0 +1,90 @@
+use crate::report::{Report, ReportCategory, ReportStatus};
+use futures::StreamExt;
+use pointercrate_core::{
+    first_and_last,
+    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pool::Reader,
+    util::non_nullable,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize)]
+pub struct ReportPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    status: Option<ReportStatus>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    category: Option<ReportCategory>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    record: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    player: Option<i32>,
+}
+
+impl PaginationQuery for ReportPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..*self
+        }
+    }
+}
+
+impl Paginatable<ReportPagination> for Report {
+    first_and_last!("reports");
+
+    async fn page(query: &ReportPagination, connection: &mut Reader<'_>) -> Result<(Vec<Report>, PageContext), sqlx::Error> {
+        let order = query.params.order();
+
+        let sql_query = format!(
+            "SELECT id, record, player, category, description, status, created_at, resolved_by, resolution_note FROM reports WHERE (id < \
+             $1 OR $1 IS NULL) AND (id > $2 OR $2 IS NULL) AND (status = $3 OR $3 IS NULL) AND (category = $4 OR $4 IS NULL) AND (record \
+             = $5 OR $5 IS NULL) AND (player = $6 OR $6 IS NULL) ORDER BY id {} LIMIT $7",
+            order
+        );
+
+        let mut stream = sqlx::query(&sql_query)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(query.status.map(|status| status.to_string()))
+            .bind(query.category.map(|category| category.to_string()))
+            .bind(query.record)
+            .bind(query.player)
+            .bind(query.params.limit() + 1)
+            .fetch(connection);
+
+        let mut reports = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            reports.push(Report {
+                id: row.get("id"),
+                record: row.get("record"),
+                player: row.get("player"),
+                category: ReportCategory::from_sql(row.get("category")),
+                description: row.get("description"),
+                status: ReportStatus::from_sql(row.get("status")),
+                created_at: row.get("created_at"),
+                resolved_by: row.get("resolved_by"),
+                resolution_note: row.get("resolution_note"),
+            })
+        }
+
+        Ok(__pagination_compat(&query.params, reports))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.id.0
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    error::Result,
+    report::{Report, ReportStatus},
+};
+use log::info;
+use pointercrate_core::util::{non_nullable, nullable};
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchReport {
+    #[serde(default, deserialize_with = "non_nullable")]
+    status: Option<ReportStatus>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    resolution_note: Option<Option<String>>,
+}
+
+impl Report {
+    /// Applies the given patch, attributing status changes to the staff member with the given id
+    pub async fn apply_patch(mut self, patch: PatchReport, staff_id: i32, connection: &mut PgConnection) -> Result<Self> {
+        info!("Applying patch {:?} to {}", patch, self);
+
+        if let Some(status) = patch.status {
+            self.set_status(status, staff_id, connection).await?;
+        }
+
+        if let Some(resolution_note) = patch.resolution_note {
+            self.set_resolution_note(resolution_note, connection).await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn set_status(&mut self, status: ReportStatus, staff_id: i32, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE reports SET status = $1, resolved_by = $2 WHERE id = $3",
+            status.to_string(),
+            staff_id,
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.status = status;
+        self.resolved_by = Some(staff_id);
+
+        Ok(())
+    }
+
+    pub async fn set_resolution_note(&mut self, resolution_note: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE reports SET resolution_note = $1 WHERE id = $2", resolution_note, self.id.0)
+            .execute(connection)
+            .await?;
+
+        self.resolution_note = resolution_note;
+
+        Ok(())
+    }
+}
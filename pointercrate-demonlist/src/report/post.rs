@@ -0,0 +1,93 @@
+use crate::{
+    error::{DemonlistError, Result},
+    player::{DatabasePlayer, PlayerId},
+    record::{FullRecord, RecordId},
+    report::{Report, ReportCategory, ReportId, ReportStatus},
+    submitter::Submitter,
+};
+use log::info;
+use pointercrate_core::{
+    error::CoreError,
+    validate::{validated, Validate, Validator},
+};
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct NewReport {
+    #[serde(default)]
+    record: Option<i32>,
+
+    #[serde(default)]
+    player: Option<i32>,
+
+    category: ReportCategory,
+    description: String,
+}
+
+impl Validate for NewReport {
+    type Error = DemonlistError;
+
+    fn normalize(&mut self) {
+        self.description = self.description.trim().to_string();
+    }
+
+    fn validate(&self, validator: &mut Validator<DemonlistError>) {
+        validator.length("description", &self.description, 1..=2000, || {
+            DemonlistError::InvalidReportDescription
+        });
+
+        match (self.record, self.player) {
+            (Some(_), Some(_)) => validator.check("player", false, || CoreError::MutuallyExclusive.into()),
+            (None, None) => validator.check("record", false, || DemonlistError::NoReportTarget),
+            (Some(_), None) => validator.check("category", self.category.applies_to_records(), || {
+                DemonlistError::InvalidReportCategory
+            }),
+            (None, Some(_)) => validator.check("category", self.category.applies_to_players(), || {
+                DemonlistError::InvalidReportCategory
+            }),
+        };
+    }
+}
+
+impl Report {
+    pub async fn create_from(report: NewReport, submitter: Submitter, connection: &mut PgConnection) -> Result<Report> {
+        let report = validated(report)?;
+
+        // ensure the reported object exists
+        if let Some(record_id) = report.record {
+            FullRecord::by_id(RecordId(record_id), &mut *connection).await?;
+        }
+
+        if let Some(player_id) = report.player {
+            DatabasePlayer::by_id(PlayerId(player_id), &mut *connection).await?;
+        }
+
+        let row = sqlx::query!(
+            "INSERT INTO reports (record, player, category, description, submitter) VALUES ($1, $2, $3, $4, $5) RETURNING id, created_at",
+            report.record,
+            report.player,
+            report.category.to_string(),
+            report.description,
+            submitter.id.0
+        )
+        .fetch_one(connection)
+        .await?;
+
+        let report = Report {
+            id: ReportId(row.id),
+            record: report.record,
+            player: report.player,
+            category: report.category,
+            description: report.description,
+            status: ReportStatus::Open,
+            created_at: row.created_at,
+            resolved_by: None,
+            resolution_note: None,
+        };
+
+        info!("Submitter {} filed {}", submitter, report);
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,72 @@
+//! The list wide requirement for progress records
+//!
+//! Depending on the configured [`RequirementPolicy`], submitted records on main list demons either
+//! just need to meet their demon's own requirement, or additionally the requirement of the lowest
+//! main list demon. The latter changes whenever the main list does, which is why frontends display
+//! it as a banner ("the current requirement is X%") via the list information endpoint.
+
+use crate::{
+    config,
+    demon::{DemonId, MinimalDemon},
+    error::Result,
+};
+use pointercrate_core::{config::RequirementPolicy, pool::ReadConnection};
+use serde::Serialize;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ListRequirement {
+    pub policy: RequirementPolicy,
+
+    /// The progress records on main list demons need to have at least, regardless of their demon's
+    /// own requirement. `None` if the policy does not impose such a requirement, or if the main list
+    /// is empty
+    pub requirement: Option<i16>,
+
+    /// The demon the requirement was taken from
+    pub demon: Option<MinimalDemon>,
+
+    /// Text for displaying the requirement, e.g. "The current requirement is 54%"
+    pub summary: Option<String>,
+}
+
+impl ListRequirement {
+    pub async fn current(connection: &mut impl ReadConnection) -> Result<ListRequirement> {
+        let connection = &mut connection.reader();
+        let policy = config::requirement_policy();
+
+        if policy == RequirementPolicy::PerDemon {
+            return Ok(ListRequirement {
+                policy,
+                requirement: None,
+                demon: None,
+                summary: None,
+            });
+        }
+
+        let lowest = sqlx::query!(
+            r#"SELECT id, name::text AS "name!", position, requirement FROM demons WHERE position > 0 AND position <= $1 ORDER BY position DESC LIMIT 1"#,
+            config::list_size()
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(ListRequirement {
+            policy,
+            requirement: lowest.as_ref().map(|row| row.requirement),
+            summary: lowest
+                .as_ref()
+                .map(|row| format!("The current requirement is {}%", row.requirement)),
+            demon: lowest.map(|row| MinimalDemon {
+                id: DemonId(row.id),
+                position: row.position,
+                name: row.name,
+            }),
+        })
+    }
+
+    /// The list wide requirement records on the given demon need to meet, if any. Does not account
+    /// for the demon's own requirement
+    pub fn applying_to(&self, demon: &MinimalDemon) -> Option<i16> {
+        self.requirement.filter(|_| demon.position <= config::list_size())
+    }
+}
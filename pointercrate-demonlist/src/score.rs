@@ -0,0 +1,82 @@
+//! Position based decay of record scores
+//!
+//! On top of the score formula, records on demons further down the list can be worth less (or
+//! nothing at all), as configured via `list.score_decay`. The database computes scores on its own, so
+//! it keeps a copy of this configuration in the `score_decay` table, which [`sync_decay`] brings up to
+//! date at startup.
+
+use crate::{config, error::Result, player::recompute_scores};
+use log::info;
+use pointercrate_core::config::ScoreDecayConfig;
+use sqlx::PgConnection;
+
+/// The weight applied to the score of records on the demon at the given position
+pub fn position_weight(position: i16) -> f64 {
+    weight_at(config::score_decay(), position)
+}
+
+fn weight_at(decay: &[ScoreDecayConfig], position: i16) -> f64 {
+    decay
+        .iter()
+        .rev()
+        .find(|decay| decay.from <= position)
+        .map(|decay| decay.weight)
+        .unwrap_or(1.0)
+}
+
+/// Replaces the decay known to the database with the given one and recomputes all scores, unless
+/// they already match. Returns whether anything changed
+pub async fn sync_decay(decay: &[ScoreDecayConfig], connection: &mut PgConnection) -> Result<bool> {
+    let current = sqlx::query!("SELECT from_position, weight FROM score_decay ORDER BY from_position")
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| ScoreDecayConfig {
+            from: row.from_position,
+            weight: row.weight,
+        })
+        .collect::<Vec<_>>();
+
+    if current == decay {
+        return Ok(false);
+    }
+
+    info!("Score decay changed from {:?} to {:?}, recomputing all scores", current, decay);
+
+    sqlx::query!("DELETE FROM score_decay").execute(&mut *connection).await?;
+
+    for decay in decay {
+        sqlx::query!(
+            "INSERT INTO score_decay (from_position, weight) VALUES ($1, $2)",
+            decay.from,
+            decay.weight
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    recompute_scores(connection).await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::weight_at;
+    use pointercrate_core::config::ScoreDecayConfig;
+
+    #[test]
+    fn test_weight_at() {
+        let decay = [
+            ScoreDecayConfig { from: 76, weight: 0.5 },
+            ScoreDecayConfig { from: 151, weight: 0.0 },
+        ];
+
+        assert_eq!(weight_at(&decay, 1), 1.0);
+        assert_eq!(weight_at(&decay, 75), 1.0);
+        assert_eq!(weight_at(&decay, 76), 0.5);
+        assert_eq!(weight_at(&decay, 150), 0.5);
+        assert_eq!(weight_at(&decay, 151), 0.0);
+        assert_eq!(weight_at(&[], 151), 1.0);
+    }
+}
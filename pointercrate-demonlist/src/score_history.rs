@@ -0,0 +1,70 @@
+//! Weekly snapshots of player and nation scores
+//!
+//! Scores are only ever stored for the current state of the list, so reconstructing how a player's
+//! score developed over time would require replaying the audit log. Instead, [`take_snapshots`]
+//! copies the current scores into the `player_score_snapshots` and `nation_score_snapshots` tables.
+//! It is meant to be run periodically, and takes at most one snapshot per week, so running it more
+//! often than that is harmless.
+//!
+//! Players and nations without any score are not included in snapshots.
+
+use crate::{error::Result, nationality::Nationality, player::DatabasePlayer};
+use chrono::NaiveDate;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ScoreSnapshot {
+    /// The monday of the week this snapshot was taken in
+    pub week: NaiveDate,
+    pub score: f64,
+}
+
+/// Takes this week's snapshot of all player and nation scores, unless it has already been taken.
+/// Returns the number of players included in the snapshot (`0` if there was nothing to do)
+pub async fn take_snapshots(connection: &mut PgConnection) -> Result<u64> {
+    let players = sqlx::query!(
+        "INSERT INTO player_score_snapshots (player, week, score) SELECT id, date_trunc('week', CURRENT_DATE)::DATE, score FROM players \
+         WHERE score > 0 AND NOT banned ON CONFLICT DO NOTHING"
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO nation_score_snapshots (nation, week, score) SELECT iso_country_code, date_trunc('week', CURRENT_DATE)::DATE, score \
+         FROM nationalities WHERE score > 0 ON CONFLICT DO NOTHING"
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(players.rows_affected())
+}
+
+impl DatabasePlayer {
+    /// All score snapshots taken of this player, oldest first
+    pub async fn score_history(&self, connection: &mut impl ReadConnection) -> Result<Vec<ScoreSnapshot>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            ScoreSnapshot,
+            "SELECT week, score FROM player_score_snapshots WHERE player = $1 ORDER BY week",
+            self.id.0
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+}
+
+impl Nationality {
+    /// All score snapshots taken of this nation, oldest first
+    pub async fn score_history(&self, connection: &mut impl ReadConnection) -> Result<Vec<ScoreSnapshot>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            ScoreSnapshot,
+            "SELECT week, score FROM nation_score_snapshots WHERE nation = $1 ORDER BY week",
+            self.iso_country_code
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+}
@@ -0,0 +1,198 @@
+//! Populates an empty database with a realistic looking demonlist, for development and demo
+//! instances as well as integration tests.
+//!
+//! All randomness is derived from a fixed seed, so two databases seeded with the same
+//! [`SeedConfig`] have identical contents.
+
+use crate::{player::recompute_scores, record::RecordStatus, submitter::Submitter, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR};
+use log::info;
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    ADMINISTRATOR, MODERATOR,
+};
+use sqlx::PgConnection;
+use std::{collections::HashSet, error::Error, net::IpAddr, str::FromStr};
+
+/// The password of all staff accounts created by [`seed`]
+pub const STAFF_PASSWORD: &str = "seeded password";
+
+#[derive(Debug, Clone)]
+pub struct SeedConfig {
+    pub demons: i16,
+    pub players: usize,
+    pub records: usize,
+    pub seed: u64,
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        SeedConfig {
+            demons: 150,
+            players: 1000,
+            records: 5000,
+            seed: 1971,
+        }
+    }
+}
+
+/// Simple xorshift generator, as seeding does not need anything fancier
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Random number in `[low, high)`
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low)
+    }
+
+    /// `true` with the given probability (in percent)
+    fn chance(&mut self, percent: u64) -> bool {
+        self.range(0, 100) < percent
+    }
+}
+
+/// Seeds the database according to the given config.
+///
+/// Creates the staff accounts `admin`, `moderator` and `helper` (all with password
+/// [`STAFF_PASSWORD`]), the given number of players (most of them with a nationality), demons, and
+/// records. Should only be run against an empty database, and inside a transaction.
+pub async fn seed(config: &SeedConfig, connection: &mut PgConnection) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if config.players == 0 {
+        return Err("Cannot seed a list without players".into());
+    }
+
+    let mut rng = Rng(config.seed.max(1));
+
+    let staff = [
+        ("admin", ADMINISTRATOR.bit() | LIST_ADMINISTRATOR.bit()),
+        ("moderator", MODERATOR.bit() | LIST_MODERATOR.bit()),
+        ("helper", LIST_HELPER.bit()),
+    ];
+
+    for (name, permissions) in staff {
+        let user = AuthenticatedUser::register(
+            Registration {
+                name: name.to_string(),
+                password: STAFF_PASSWORD.to_string(),
+            },
+            &mut *connection,
+        )
+        .await?;
+
+        user.into_user().set_permissions(permissions, &mut *connection).await?;
+    }
+
+    let localhost = IpAddr::from_str("127.0.0.1").unwrap();
+    let submitter = match Submitter::by_ip(localhost, &mut *connection).await? {
+        Some(submitter) => submitter,
+        None => Submitter::create_submitter(localhost, &mut *connection).await?,
+    };
+
+    let nationalities =
+        sqlx::query!(r#"SELECT iso_country_code as "iso_country_code: String" FROM nationalities ORDER BY iso_country_code"#)
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|row| row.iso_country_code)
+            .collect::<Vec<_>>();
+
+    let mut players = Vec::with_capacity(config.players);
+
+    for i in 0..config.players {
+        let nationality = match nationalities.is_empty() || rng.chance(15) {
+            true => None,
+            false => Some(nationalities[rng.range(0, nationalities.len() as u64) as usize].clone()),
+        };
+
+        let id = sqlx::query!(
+            "INSERT INTO players (name, nationality) VALUES ($1::TEXT::CITEXT, $2) RETURNING id",
+            format!("Player {}", i + 1),
+            nationality
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        players.push(id);
+    }
+
+    let mut demons = Vec::with_capacity(config.demons as usize);
+
+    for position in 1..=config.demons {
+        let requirement = rng.range(40, 101) as i16;
+        let verifier = players[rng.range(0, players.len() as u64) as usize];
+        let publisher = players[rng.range(0, players.len() as u64) as usize];
+
+        let id = sqlx::query!(
+            "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5) RETURNING id",
+            format!("Demon {}", position),
+            position,
+            requirement,
+            verifier,
+            publisher
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        sqlx::query!("INSERT INTO creators (demon, creator) VALUES ($1, $2)", id, publisher)
+            .execute(&mut *connection)
+            .await?;
+
+        demons.push((id, requirement));
+    }
+
+    // Records need to be unique per (player, demon)-pair, so we cannot generate more than this
+    let record_count = config.records.min(players.len() * demons.len());
+    let mut pairs = HashSet::with_capacity(record_count);
+
+    while pairs.len() < record_count {
+        let player = players[rng.range(0, players.len() as u64) as usize];
+        let (demon, requirement) = demons[rng.range(0, demons.len() as u64) as usize];
+
+        if !pairs.insert((player, demon)) {
+            continue;
+        }
+
+        let progress = match rng.chance(70) {
+            true => 100,
+            false => rng.range(requirement as u64, 101) as i16,
+        };
+
+        let status = match rng.range(0, 100) {
+            0..=84 => RecordStatus::Approved,
+            85..=94 => RecordStatus::Submitted,
+            _ => RecordStatus::Rejected,
+        };
+
+        sqlx::query!(
+            "INSERT INTO records (progress, status_, player, submitter, demon, video) VALUES ($1, $2::text::record_status, $3, $4, $5, \
+             $6::TEXT)",
+            progress,
+            status.to_sql(),
+            player,
+            submitter.id.0,
+            demon,
+            format!("https://www.youtube.com/watch?v=seeded{}", pairs.len())
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    recompute_scores(connection).await?;
+
+    info!(
+        "Seeded database with {} players, {} demons and {} records",
+        players.len(),
+        demons.len(),
+        record_count
+    );
+
+    Ok(())
+}
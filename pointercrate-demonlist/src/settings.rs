@@ -0,0 +1,100 @@
+//! List-wide settings that list administrators can change at runtime
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use pointercrate_core::{
+    etag::Taggable,
+    pool::ReadConnection,
+    util::{non_nullable, nullable},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// Settings for closing all record submissions at once, e.g. during list updates
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct SubmissionSettings {
+    pub submissions_open: bool,
+
+    /// The reason given for closing submissions, if any
+    pub closed_reason: Option<String>,
+
+    /// The point in time at which closed submissions automatically reopen
+    pub reopen_at: Option<DateTime<Utc>>,
+}
+
+impl Taggable for SubmissionSettings {}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchSubmissionSettings {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub submissions_open: Option<bool>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub closed_reason: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub reopen_at: Option<Option<DateTime<Utc>>>,
+}
+
+impl SubmissionSettings {
+    pub async fn load(connection: &mut impl ReadConnection) -> Result<SubmissionSettings> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            SubmissionSettings,
+            "SELECT submissions_open, closed_reason, reopen_at FROM submission_settings"
+        )
+        .fetch_one(connection)
+        .await?)
+    }
+
+    /// Whether record submissions are currently accepted. Submissions whose scheduled reopen time
+    /// has passed count as open, even if the scheduler did not get around to reopening them yet
+    pub fn accepting_submissions(&self) -> bool {
+        self.submissions_open || self.reopen_at.is_some_and(|reopen_at| reopen_at <= Utc::now())
+    }
+
+    /// Applies the given patch. Opening submissions discards any closing reason and scheduled
+    /// reopen time
+    pub async fn apply_patch(mut self, patch: PatchSubmissionSettings, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(submissions_open) = patch.submissions_open {
+            self.submissions_open = submissions_open;
+        }
+
+        if let Some(closed_reason) = patch.closed_reason {
+            self.closed_reason = closed_reason.filter(|reason| !reason.trim().is_empty());
+        }
+
+        if let Some(reopen_at) = patch.reopen_at {
+            self.reopen_at = reopen_at;
+        }
+
+        if self.submissions_open {
+            self.closed_reason = None;
+            self.reopen_at = None;
+        }
+
+        sqlx::query!(
+            "UPDATE submission_settings SET submissions_open = $1, closed_reason = $2, reopen_at = $3",
+            self.submissions_open,
+            self.closed_reason,
+            self.reopen_at
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+
+    /// Reopens submissions if their scheduled reopen time has passed. Returns whether submissions
+    /// were reopened
+    pub async fn reopen_if_due(connection: &mut PgConnection) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE submission_settings SET submissions_open = TRUE, closed_reason = NULL, reopen_at = NULL WHERE NOT submissions_open \
+             AND reopen_at <= NOW()"
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
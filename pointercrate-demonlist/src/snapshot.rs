@@ -0,0 +1,339 @@
+//! Snapshots of the list state, for mirroring a list between instances
+//!
+//! A [`ListSnapshot`] contains every demon together with its position, verification, publisher and
+//! creators. It references players by name and contains no ids, since those differ between
+//! instances. This makes it possible to export the list of e.g. a production instance and to load
+//! it into a staging instance.
+//!
+//! Importing a snapshot does not overwrite the list. Demons are matched against the existing ones,
+//! by level id if the snapshot has one and by name and publisher otherwise. Only the differences
+//! are applied: unknown demons are added, and differing fields of matched demons are updated.
+//! Demons that exist locally but are not part of the snapshot are left untouched.
+//!
+//! Videos are only ever set, never removed by an import, since videos of link banned verifiers are
+//! not included in snapshots.
+
+use crate::{
+    creator::Creator,
+    demon::{current_list, Demon, DemonId, DemonTier, FullDemon, MinimalDemon, PatchDemon, PostDemon},
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+
+/// Version of the snapshot format. Needs to be incremented whenever [`ListSnapshot`] or
+/// [`SnapshotDemon`] change, and snapshots of a different version are refused by
+/// [`ListSnapshot::import`]
+///
+/// Version 2 made [`ListSnapshot::created_at`] an RFC 3339 timestamp with offset.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSnapshot {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+
+    /// All demons, ordered by position
+    pub demons: Vec<SnapshotDemon>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SnapshotDemon {
+    pub name: String,
+    pub position: i16,
+    pub requirement: i16,
+    pub level_id: Option<u64>,
+    pub video: Option<String>,
+    pub verifier: String,
+    pub publisher: String,
+
+    /// The names of the demon's creators, in alphabetical order
+    pub creators: Vec<String>,
+    pub score_weight: f64,
+    pub tier: Option<DemonTier>,
+}
+
+/// The differences between a snapshot and the list it is imported into
+#[derive(Debug, Serialize, Default, PartialEq)]
+pub struct SnapshotDiff {
+    /// Names of the demons in the snapshot that do not exist locally
+    pub added: Vec<String>,
+    pub changed: Vec<ChangedDemon>,
+    pub unchanged: usize,
+
+    /// Names of the local demons that are not part of the snapshot
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChangedDemon {
+    pub name: String,
+
+    /// The fields whose values differ from the snapshot
+    pub fields: Vec<&'static str>,
+}
+
+impl SnapshotDemon {
+    fn matches(&self, other: &SnapshotDemon) -> bool {
+        match (self.level_id, other.level_id) {
+            (Some(level_id), Some(other_level_id)) => level_id == other_level_id,
+            _ => self.name.to_lowercase() == other.name.to_lowercase() && self.publisher.to_lowercase() == other.publisher.to_lowercase(),
+        }
+    }
+
+    /// The fields of `self` that differ from `target`
+    fn differing_fields(&self, target: &SnapshotDemon) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+
+        if self.name != target.name {
+            fields.push("name");
+        }
+        if self.position != target.position {
+            fields.push("position");
+        }
+        if self.requirement != target.requirement {
+            fields.push("requirement");
+        }
+        if target.level_id.is_some() && self.level_id != target.level_id {
+            fields.push("level_id");
+        }
+        if target.video.is_some() && self.video != target.video {
+            fields.push("video");
+        }
+        if self.verifier.to_lowercase() != target.verifier.to_lowercase() {
+            fields.push("verifier");
+        }
+        if self.publisher.to_lowercase() != target.publisher.to_lowercase() {
+            fields.push("publisher");
+        }
+        if self.creators != target.creators {
+            fields.push("creators");
+        }
+        if self.score_weight != target.score_weight {
+            fields.push("score_weight");
+        }
+        if self.tier != target.tier {
+            fields.push("tier");
+        }
+
+        fields
+    }
+}
+
+impl ListSnapshot {
+    pub async fn take(connection: &mut impl ReadConnection) -> Result<ListSnapshot> {
+        let connection = &mut connection.reader();
+        Ok(ListSnapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            demons: local_demons(connection).await?.into_iter().map(|(_, demon)| demon).collect(),
+        })
+    }
+
+    /// Compares this snapshot against the current list, and applies the differences unless
+    /// `dry_run` is set.
+    ///
+    /// Must run inside a transaction!
+    pub async fn import(mut self, dry_run: bool, connection: &mut PgConnection) -> Result<SnapshotDiff> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(DemonlistError::SnapshotVersionMismatch {
+                expected: SNAPSHOT_VERSION,
+                found: self.version,
+            });
+        }
+
+        for demon in &mut self.demons {
+            demon.creators.sort_by_key(|creator| creator.to_lowercase());
+        }
+
+        self.demons.sort_by_key(|demon| demon.position);
+
+        let mut local = local_demons(&mut *connection).await?;
+        let mut diff = SnapshotDiff::default();
+
+        // The local demon matched to each demon of the snapshot (if any)
+        let mut matched = Vec::new();
+
+        for target in &self.demons {
+            match local.iter().position(|(_, demon)| demon.matches(target)) {
+                Some(index) => matched.push(Some(local.remove(index))),
+                None => matched.push(None),
+            }
+        }
+
+        diff.missing = local.into_iter().map(|(_, demon)| demon.name).collect();
+
+        for (target, current) in self.demons.iter().zip(&matched) {
+            match current {
+                None => diff.added.push(target.name.clone()),
+                Some((_, current)) => {
+                    let fields = current.differing_fields(target);
+
+                    if fields.is_empty() {
+                        diff.unchanged += 1;
+                    } else {
+                        diff.changed.push(ChangedDemon {
+                            name: target.name.clone(),
+                            fields,
+                        });
+                    }
+                },
+            }
+        }
+
+        if dry_run {
+            return Ok(diff);
+        }
+
+        info!(
+            "Importing list snapshot: {} demons added, {} changed",
+            diff.added.len(),
+            diff.changed.len()
+        );
+
+        let mut demons = Vec::new();
+
+        for (target, current) in self.demons.iter().zip(matched) {
+            let demon = match current {
+                None => create(target, connection).await?,
+                Some((id, current)) => update(id, &current, target, connection).await?,
+            };
+
+            demons.push((demon, target.position));
+        }
+
+        // Positions are assigned from the top of the list downwards. Each move only shifts demons
+        // further down the list, which have not been assigned a position yet. Demons not part of
+        // the snapshot can still end up shifted in between. Position locks are deliberately
+        // ignored, since the point of an import is to mirror the snapshot's order.
+        let maximal_position = Demon::max_position(&mut *connection).await?;
+
+        for (demon, position) in demons {
+            // Earlier moves might have shifted this demon, so its position needs to be reloaded
+            let mut demon = MinimalDemon::by_id(demon.id, &mut *connection).await?;
+
+            demon.mv(position.clamp(1, maximal_position), &mut *connection).await?;
+        }
+
+        Ok(diff)
+    }
+}
+
+/// All local demons, together with their ids, ordered by position
+async fn local_demons(connection: &mut impl ReadConnection) -> Result<Vec<(i32, SnapshotDemon)>> {
+    let connection = &mut connection.reader();
+    let mut creators: HashMap<i32, Vec<String>> = HashMap::new();
+
+    for row in sqlx::query!(
+        "SELECT creators.demon, players.name::text AS \"name!\" FROM creators INNER JOIN players ON players.id = creators.creator"
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    {
+        creators.entry(row.demon).or_default().push(row.name);
+    }
+
+    Ok(current_list(connection)
+        .await?
+        .into_iter()
+        .map(|demon| {
+            let mut creators = creators.remove(&demon.base.id.0).unwrap_or_default();
+
+            creators.sort_by_key(|creator| creator.to_lowercase());
+
+            (
+                demon.base.id.0,
+                SnapshotDemon {
+                    name: demon.base.name,
+                    position: demon.base.position,
+                    requirement: demon.requirement,
+                    level_id: demon.level_id,
+                    video: demon.video,
+                    verifier: demon.verifier.name,
+                    publisher: demon.publisher.name,
+                    creators,
+                    score_weight: demon.score_weight,
+                    tier: demon.tier,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Adds the given demon to the bottom of the list. It is moved to its actual position later
+async fn create(target: &SnapshotDemon, connection: &mut PgConnection) -> Result<MinimalDemon> {
+    let position = Demon::max_position(&mut *connection).await? + 1;
+
+    let demon = FullDemon::create_from(
+        PostDemon {
+            name: target.name.clone(),
+            position,
+            requirement: target.requirement,
+            verifier: target.verifier.clone(),
+            publisher: target.publisher.clone(),
+            creators: target.creators.clone(),
+            video: target.video.clone(),
+            level_id: target.level_id.map(|level_id| level_id as i64),
+        },
+        &mut *connection,
+    )
+    .await?
+    .demon;
+
+    let patch = PatchDemon {
+        score_weight: Some(target.score_weight).filter(|&score_weight| score_weight != demon.score_weight),
+        tier: Some(target.tier).filter(|&tier| tier != demon.tier),
+        ..Default::default()
+    };
+
+    Ok(demon.apply_patch(patch, connection).await?.base)
+}
+
+async fn update(id: i32, current: &SnapshotDemon, target: &SnapshotDemon, connection: &mut PgConnection) -> Result<MinimalDemon> {
+    let fields = current.differing_fields(target);
+    let differs = |field| fields.contains(&field);
+
+    let demon = Demon::by_id(DemonId(id), &mut *connection).await?;
+
+    let patch = PatchDemon {
+        name: Some(target.name.clone()).filter(|_| differs("name")),
+        requirement: Some(target.requirement).filter(|_| differs("requirement")),
+        level_id: target.level_id.filter(|_| differs("level_id")),
+        video: target.video.clone().map(Some).filter(|_| differs("video")),
+        verifier: Some(target.verifier.clone()).filter(|_| differs("verifier")),
+        publisher: Some(target.publisher.clone()).filter(|_| differs("publisher")),
+        score_weight: Some(target.score_weight).filter(|_| differs("score_weight")),
+        tier: Some(target.tier).filter(|_| differs("tier")),
+        ..Default::default()
+    };
+
+    let demon = demon.apply_patch(patch, &mut *connection).await?.base;
+
+    if differs("creators") {
+        for name in &current.creators {
+            if !target.creators.iter().any(|creator| creator.to_lowercase() == name.to_lowercase()) {
+                let player = DatabasePlayer::by_name(name, &mut *connection).await?;
+
+                Creator::get(&demon, &player, &mut *connection)
+                    .await?
+                    .delete(&mut *connection)
+                    .await?;
+            }
+        }
+
+        for name in &target.creators {
+            if !current.creators.iter().any(|creator| creator.to_lowercase() == name.to_lowercase()) {
+                let player = DatabasePlayer::by_name_or_create(name, &mut *connection).await?;
+
+                Creator::insert(&demon, &player, &mut *connection).await?;
+            }
+        }
+    }
+
+    Ok(demon)
+}
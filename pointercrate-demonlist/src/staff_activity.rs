@@ -0,0 +1,143 @@
+//! Statistics about the workload of list staff
+//!
+//! A record counts as reviewed by the staff member who first moved it out of the
+//! [`RecordStatus::Submitted`] state (or deleted it while it was still submitted). The review latency
+//! is the time between the record's submission and this review.
+//!
+//! Since computing these numbers requires scanning the entire record audit log, they are aggregated
+//! per week into the `staff_activity_stats` table by [`StaffActivity::aggregate`], which is meant to
+//! be run periodically.
+//!
+//! [`RecordStatus::Submitted`]: crate::record::RecordStatus::Submitted
+
+use crate::{config, error::Result, record::stale::stale_submission_count};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::StreamExt;
+use pointercrate_core::{audit::NamedId, pool::ReadConnection};
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize)]
+pub struct WeeklyActivity {
+    /// The monday of the week this entry is about
+    pub week: NaiveDate,
+    pub member: NamedId,
+    pub records_reviewed: i32,
+
+    /// The average time (in seconds) the records reviewed by this member had been waiting for review
+    pub average_review_latency: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingSubmission {
+    pub id: i32,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffActivity {
+    /// Per-member statistics, most recent week first
+    pub weeks: Vec<WeeklyActivity>,
+    pub pending_submissions: i64,
+    pub oldest_pending_submission: Option<PendingSubmission>,
+
+    /// Number of pending submissions that have been escalated as [stale](crate::record::stale)
+    pub stale_submissions: i64,
+}
+
+impl StaffActivity {
+    /// Gets the aggregated statistics of the last `weeks` weeks (including the current one), together
+    /// with live information about the submission queue
+    pub async fn load(weeks: i32, connection: &mut impl ReadConnection) -> Result<StaffActivity> {
+        let connection = &mut connection.reader();
+        let mut activity = Vec::new();
+
+        {
+            let mut stream = sqlx::query!(
+                r#"SELECT week, staff_activity_stats.member_id, members.name AS "name?", records_reviewed, average_review_latency
+                   FROM staff_activity_stats
+                   LEFT OUTER JOIN members ON members.member_id = staff_activity_stats.member_id
+                   WHERE week > CURRENT_DATE - 7 * $1::INTEGER
+                   ORDER BY week DESC, records_reviewed DESC"#,
+                weeks
+            )
+            .fetch(&mut *connection);
+
+            while let Some(row) = stream.next().await {
+                let row = row?;
+
+                activity.push(WeeklyActivity {
+                    week: row.week,
+                    member: NamedId {
+                        id: row.member_id,
+                        name: row.name,
+                    },
+                    records_reviewed: row.records_reviewed,
+                    average_review_latency: row.average_review_latency,
+                })
+            }
+        }
+
+        let pending_submissions = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM records WHERE status_ = 'SUBMITTED'"#)
+            .fetch_one(&mut *connection)
+            .await?
+            .count;
+
+        let oldest_pending_submission = sqlx::query!(
+            "SELECT records.id, record_additions.time AS \"submitted_at?\" FROM records LEFT OUTER JOIN record_additions ON \
+             record_additions.id = records.id WHERE status_ = 'SUBMITTED' ORDER BY records.id LIMIT 1"
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .map(|row| PendingSubmission {
+            id: row.id,
+            submitted_at: row.submitted_at,
+        });
+
+        let stale_submissions = stale_submission_count(config::stale_submission_threshold(), &mut *connection).await?;
+
+        Ok(StaffActivity {
+            weeks: activity,
+            pending_submissions,
+            oldest_pending_submission,
+            stale_submissions,
+        })
+    }
+
+    /// Refreshes the `staff_activity_stats` table from the record audit log.
+    ///
+    /// Only the most recently aggregated week (which might not have been complete at the time) and
+    /// the weeks after it are recomputed. Reviews done by the system user (id 0), such as the
+    /// automatic removal of submissions with dead videos, are not counted.
+    pub async fn aggregate(connection: &mut PgConnection) -> Result<()> {
+        let since = sqlx::query!("SELECT MAX(week) AS since FROM staff_activity_stats")
+            .fetch_one(&mut *connection)
+            .await?
+            .since;
+
+        sqlx::query!("DELETE FROM staff_activity_stats WHERE week >= $1", since)
+            .execute(&mut *connection)
+            .await?;
+
+        // For updates, record_modifications.status_ is only set if the status changed, and holds
+        // the old status. For deletions, it holds the status at the time of deletion
+        sqlx::query!(
+            "INSERT INTO staff_activity_stats (member_id, week, records_reviewed, average_review_latency)
+             SELECT record_modifications.userid,
+                    DATE_TRUNC('week', record_modifications.time)::DATE AS week,
+                    COUNT(*)::INTEGER,
+                    COALESCE(AVG(EXTRACT(EPOCH FROM record_modifications.time - record_additions.time)), 0)::DOUBLE PRECISION
+             FROM record_modifications
+             LEFT OUTER JOIN record_additions ON record_additions.id = record_modifications.id
+             WHERE record_modifications.status_ = 'SUBMITTED'
+               AND record_modifications.userid <> 0
+               AND ($1::DATE IS NULL OR record_modifications.time >= $1::DATE)
+             GROUP BY record_modifications.userid, week",
+            since
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        Ok(())
+    }
+}
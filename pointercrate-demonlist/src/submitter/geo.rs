@@ -0,0 +1,199 @@
+//! Coarse geolocation of submitters
+//!
+//! If MaxMind GeoIP2/GeoLite2 databases are configured, the country and autonomous system of a
+//! submitter's IP address are recorded when the [`Submitter`] is created. Nothing more precise (like
+//! cities or coordinates) is ever stored, and the data is deleted again after the configured
+//! retention period (see [`Submitter::purge_geo_data`]). It exists solely to help list administrators
+//! recognize waves of spam submissions coming from VPN providers.
+
+use crate::{config, error::Result, submitter::Submitter};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use log::{error, info};
+use maxminddb::{geoip2, Reader};
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::{net::IpAddr, sync::OnceLock};
+
+static DATABASES: OnceLock<GeoIpDatabases> = OnceLock::new();
+
+struct GeoIpDatabases {
+    country: Option<Reader<Vec<u8>>>,
+    asn: Option<Reader<Vec<u8>>>,
+}
+
+fn open_database(kind: &str, path: Option<String>) -> Option<Reader<Vec<u8>>> {
+    let path = path?;
+
+    match Reader::open_readfile(&path) {
+        Ok(reader) => {
+            info!("Loaded GeoIP {} database from {}", kind, path);
+
+            Some(reader)
+        },
+        Err(err) => {
+            error!("Failed to open GeoIP {} database at {}: {:?}", kind, path, err);
+
+            None
+        },
+    }
+}
+
+fn databases() -> &'static GeoIpDatabases {
+    DATABASES.get_or_init(|| GeoIpDatabases {
+        country: open_database("country", config::geoip_country_database()),
+        asn: open_database("ASN", config::geoip_asn_database()),
+    })
+}
+
+/// The geolocation data recorded about a submitter
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct GeoData {
+    /// ISO 3166-1 alpha-2 code of the country the submitter's IP address is located in
+    pub country_code: Option<String>,
+
+    /// The autonomous system the submitter's IP address belongs to
+    pub asn: Option<i32>,
+    pub asn_organization: Option<String>,
+}
+
+impl GeoData {
+    /// Looks up the given IP address in the configured GeoIP databases. Returns `None` if no
+    /// database is configured, or none of them know the address
+    pub fn lookup(ip: IpAddr) -> Option<GeoData> {
+        let databases = databases();
+
+        let mut data = GeoData::default();
+
+        if let Some(ref reader) = databases.country {
+            if let Ok(country) = reader.lookup::<geoip2::Country>(ip) {
+                data.country_code = country.country.and_then(|country| country.iso_code).map(ToString::to_string);
+            }
+        }
+
+        if let Some(ref reader) = databases.asn {
+            if let Ok(asn) = reader.lookup::<geoip2::Asn>(ip) {
+                data.asn = asn.autonomous_system_number.map(|asn| asn as i32);
+                data.asn_organization = asn.autonomous_system_organization.map(ToString::to_string);
+            }
+        }
+
+        if data == GeoData::default() {
+            None
+        } else {
+            Some(data)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitterGeo {
+    pub submitter_id: i32,
+
+    #[serde(flatten)]
+    pub data: GeoData,
+
+    /// When this data was recorded. `None` if nothing was ever recorded, or the data has been
+    /// deleted after exceeding the retention period
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+/// Number of submitters created recently from a specific country/autonomous system
+#[derive(Debug, Serialize)]
+pub struct GeoSummaryEntry {
+    #[serde(flatten)]
+    pub data: GeoData,
+    pub submitters: i64,
+    pub banned: i64,
+}
+
+impl Submitter {
+    pub(super) async fn record_geo_data(&self, data: GeoData, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE submitters SET country_code = $1, asn = $2, asn_organization = $3, geo_recorded_at = NOW() \
+             WHERE submitter_id = $4",
+            data.country_code,
+            data.asn,
+            data.asn_organization,
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn geo_data(&self, connection: &mut impl ReadConnection) -> Result<SubmitterGeo> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            "SELECT country_code::TEXT, asn, asn_organization, geo_recorded_at FROM submitters WHERE submitter_id = $1",
+            self.id.0
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(SubmitterGeo {
+            submitter_id: self.id.0,
+            data: GeoData {
+                country_code: row.country_code,
+                asn: row.asn,
+                asn_organization: row.asn_organization,
+            },
+            recorded_at: row.geo_recorded_at,
+        })
+    }
+
+    /// Groups all submitters whose geolocation data was recorded in the last `hours` hours by
+    /// country and autonomous system, most common first
+    pub async fn geo_summary(hours: i32, connection: &mut impl ReadConnection) -> Result<Vec<GeoSummaryEntry>> {
+        let connection = &mut connection.reader();
+        let mut stream = sqlx::query!(
+            r#"SELECT country_code::TEXT, asn, MAX(asn_organization) AS asn_organization, COUNT(*) AS "submitters!",
+                      COUNT(*) FILTER (WHERE banned) AS "banned!"
+               FROM submitters
+               WHERE geo_recorded_at > NOW() - MAKE_INTERVAL(hours => $1)
+               GROUP BY country_code, asn
+               ORDER BY 4 DESC, country_code, asn"#,
+            hours
+        )
+        .fetch(connection);
+
+        let mut summary = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            summary.push(GeoSummaryEntry {
+                data: GeoData {
+                    country_code: row.country_code,
+                    asn: row.asn,
+                    asn_organization: row.asn_organization,
+                },
+                submitters: row.submitters,
+                banned: row.banned,
+            })
+        }
+
+        Ok(summary)
+    }
+
+    /// Deletes all geolocation data older than the configured retention period, returning the number
+    /// of affected submitters
+    pub async fn purge_geo_data(connection: &mut PgConnection) -> Result<u64> {
+        let retention = config::geo_data_retention();
+
+        if retention == 0 {
+            return Ok(0);
+        }
+
+        Ok(sqlx::query!(
+            "UPDATE submitters SET country_code = NULL, asn = NULL, asn_organization = NULL, geo_recorded_at = NULL WHERE \
+             geo_recorded_at < NOW() - MAKE_INTERVAL(secs => $1)",
+            retention as f64
+        )
+        .execute(connection)
+        .await?
+        .rows_affected())
+    }
+}
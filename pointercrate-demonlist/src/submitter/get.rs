@@ -1,23 +1,44 @@
 use crate::{
     error::{DemonlistError, Result},
-    submitter::Submitter,
+    submitter::{Submitter, SubmitterId},
 };
+use pointercrate_core::pool::ReadConnection;
 use sqlx::{Error, PgConnection};
 use std::net::IpAddr;
 
 impl Submitter {
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Submitter> {
+    pub async fn by_id(SubmitterId(id): SubmitterId, connection: &mut impl ReadConnection) -> Result<Submitter> {
+        let connection = &mut connection.reader();
         let result = sqlx::query!("SELECT submitter_id, banned FROM submitters WHERE submitter_id = $1", id)
             .fetch_one(connection)
             .await;
 
         match result {
-            Ok(row) => Ok(Submitter { id, banned: row.banned }),
+            Ok(row) => Ok(Submitter {
+                id: SubmitterId(id),
+                banned: row.banned,
+            }),
             Err(Error::RowNotFound) => Err(DemonlistError::SubmitterNotFound { id }),
             Err(err) => Err(err.into()),
         }
     }
 
+    /// Resolves an anonymized submitter identifier, as shown on public record responses, back to
+    /// the submitter it belongs to
+    pub async fn by_anonymous_id(anonymous_id: &str, connection: &mut impl ReadConnection) -> Result<Submitter> {
+        let connection = &mut connection.reader();
+        sqlx::query!("SELECT submitter_id, banned FROM submitters WHERE public_id = $1", anonymous_id)
+            .fetch_optional(connection)
+            .await?
+            .map(|row| Submitter {
+                id: SubmitterId(row.submitter_id),
+                banned: row.banned,
+            })
+            .ok_or_else(|| DemonlistError::AnonymousSubmitterNotFound {
+                anonymous_id: anonymous_id.to_string(),
+            })
+    }
+
     pub async fn by_ip(ip: IpAddr, connection: &mut PgConnection) -> Result<Option<Submitter>> {
         Ok(sqlx::query!(
             "SELECT submitter_id, banned FROM submitters WHERE ip_address = cast($1::text as inet)",
@@ -26,7 +47,7 @@ impl Submitter {
         .fetch_optional(&mut *connection)
         .await?
         .map(|row| Submitter {
-            id: row.submitter_id,
+            id: SubmitterId(row.submitter_id),
             banned: row.banned,
         }))
     }
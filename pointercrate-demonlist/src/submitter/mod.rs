@@ -2,19 +2,26 @@ use derive_more::Display;
 use serde::Deserialize;
 use serde::Serialize;
 
+pub use geo::{GeoData, GeoSummaryEntry, SubmitterGeo};
 pub use paginate::SubmitterPagination;
 pub use patch::PatchSubmitter;
 use pointercrate_core::etag::Taggable;
 
+mod geo;
 mod get;
 mod paginate;
 mod patch;
 mod post;
 
+pointercrate_core::id_type!(
+    /// The ID of a [`Submitter`]
+    SubmitterId
+);
+
 #[derive(Debug, Deserialize, Serialize, Hash, Display, Copy, Clone, PartialEq, Eq)]
 #[display(fmt = "{} (Banned: {})", id, banned)]
 pub struct Submitter {
-    pub id: i32,
+    pub id: SubmitterId,
     pub banned: bool,
 }
 
@@ -3,10 +3,11 @@ use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
     pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pool::Reader,
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::Row;
 
 #[derive(Deserialize, Debug, Clone, Copy, Serialize)]
 pub struct SubmitterPagination {
@@ -33,7 +34,7 @@ impl PaginationQuery for SubmitterPagination {
 impl Paginatable<SubmitterPagination> for Submitter {
     first_and_last!("submitters", "submitter_id");
 
-    async fn page(query: &SubmitterPagination, connection: &mut PgConnection) -> Result<(Vec<Submitter>, PageContext), sqlx::Error> {
+    async fn page(query: &SubmitterPagination, connection: &mut Reader<'_>) -> Result<(Vec<Submitter>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!("SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND (banned = $3 OR $3 IS NULL) ORDER BY submitter_id {} LIMIT $4", order);
@@ -42,7 +43,7 @@ impl Paginatable<SubmitterPagination> for Submitter {
             .bind(query.params.before)
             .bind(query.params.after)
             .bind(query.banned)
-            .bind(query.params.limit + 1)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut submitters = Vec::new();
@@ -60,6 +61,6 @@ impl Paginatable<SubmitterPagination> for Submitter {
     }
 
     fn pagination_id(&self) -> i32 {
-        self.id
+        self.id.0
     }
 }
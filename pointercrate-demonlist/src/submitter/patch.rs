@@ -12,11 +12,11 @@ pub struct PatchSubmitter {
 
 impl Submitter {
     pub async fn ban(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE submitters SET banned = true WHERE submitter_id = $1", self.id)
+        sqlx::query!("UPDATE submitters SET banned = true WHERE submitter_id = $1", self.id.0)
             .execute(&mut *connection)
             .await?;
 
-        let deleted = sqlx::query!("DELETE FROM records WHERE submitter = $1 AND status_ = 'SUBMITTED'", self.id)
+        let deleted = sqlx::query!("DELETE FROM records WHERE submitter = $1 AND status_ = 'SUBMITTED'", self.id.0)
             .execute(connection)
             .await?;
 
@@ -32,7 +32,7 @@ impl Submitter {
     }
 
     pub async fn unban(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE submitters SET banned = false WHERE submitter_id = $1", self.id)
+        sqlx::query!("UPDATE submitters SET banned = false WHERE submitter_id = $1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -1,4 +1,7 @@
-use crate::{error::Result, submitter::Submitter};
+use crate::{
+    error::Result,
+    submitter::{GeoData, Submitter, SubmitterId},
+};
 use sqlx::PgConnection;
 use std::net::IpAddr;
 
@@ -8,10 +11,19 @@ impl Submitter {
             "INSERT INTO submitters (ip_address) VALUES (cast($1::text as inet)) RETURNING submitter_id",
             ip.to_string()
         )
-        .fetch_one(connection)
+        .fetch_one(&mut *connection)
         .await?
         .submitter_id;
 
-        Ok(Submitter { id, banned: false })
+        let submitter = Submitter {
+            id: SubmitterId(id),
+            banned: false,
+        };
+
+        if let Some(data) = GeoData::lookup(ip) {
+            submitter.record_geo_data(data, connection).await?;
+        }
+
+        Ok(submitter)
     }
 }
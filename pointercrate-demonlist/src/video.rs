@@ -109,3 +109,91 @@ pub fn validate(url: &str) -> Result<String> {
         Err(CoreError::UnprocessableEntity.into())
     }
 }
+
+/// Parses the timestamp of a completion within its video, given as `h:mm:ss`, `m:ss` or a plain
+/// number of seconds, into a number of seconds
+pub fn parse_timestamp(timestamp: &str) -> Result<i32> {
+    let mut seconds = 0i32;
+    let components = timestamp.trim().split(':').collect::<Vec<_>>();
+
+    if components.len() > 3 {
+        return Err(DemonlistError::InvalidVideoTimestamp);
+    }
+
+    for (idx, component) in components.iter().enumerate() {
+        if component.is_empty() || !component.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(DemonlistError::InvalidVideoTimestamp);
+        }
+
+        let value: i32 = component.parse().map_err(|_| DemonlistError::InvalidVideoTimestamp)?;
+
+        // Minutes and seconds following a larger unit need to be below 60
+        if idx > 0 && value >= 60 {
+            return Err(DemonlistError::InvalidVideoTimestamp);
+        }
+
+        seconds = seconds
+            .checked_mul(60)
+            .and_then(|seconds| seconds.checked_add(value))
+            .ok_or(DemonlistError::InvalidVideoTimestamp)?;
+    }
+
+    Ok(seconds)
+}
+
+/// Turns a (canonicalized, see [`validate`]) video link into one that starts playback at the given
+/// number of seconds. Links to hosts not supporting this are returned unchanged.
+pub fn with_timestamp(video: &str, seconds: i32) -> String {
+    let Ok(mut url) = Url::parse(video) else {
+        return video.to_string();
+    };
+
+    match url.domain() {
+        Some("www.youtube.com") => {
+            url.query_pairs_mut().append_pair("t", &format!("{}s", seconds));
+        },
+        Some("www.twitch.tv") => {
+            url.query_pairs_mut()
+                .append_pair("t", &format!("{}h{}m{}s", seconds / 3600, seconds / 60 % 60, seconds % 60));
+        },
+        Some("www.bilibili.com") => {
+            url.query_pairs_mut().append_pair("t", &seconds.to_string());
+        },
+        Some("vimeo.com") => url.set_fragment(Some(&format!("t={}s", seconds))),
+        _ => (),
+    }
+
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_timestamp, with_timestamp};
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("42"), Ok(42));
+        assert_eq!(parse_timestamp("3:05"), Ok(185));
+        assert_eq!(parse_timestamp("1:02:03"), Ok(3723));
+        assert!(parse_timestamp("1:60").is_err());
+        assert!(parse_timestamp("1::3").is_err());
+        assert!(parse_timestamp("-5").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+    }
+
+    #[test]
+    fn test_with_timestamp() {
+        assert_eq!(
+            with_timestamp("https://www.youtube.com/watch?v=dQw4w9WgXcQ", 185),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=185s"
+        );
+        assert_eq!(
+            with_timestamp("https://www.twitch.tv/videos/12345", 3723),
+            "https://www.twitch.tv/videos/12345?t=1h2m3s"
+        );
+        assert_eq!(
+            with_timestamp("https://everyplay.com/videos/1", 10),
+            "https://everyplay.com/videos/1"
+        );
+    }
+}
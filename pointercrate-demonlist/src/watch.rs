@@ -0,0 +1,191 @@
+//! Staff watches on individual demons and players
+//!
+//! A list staff member watching a demon or player gets a notification whenever that object is
+//! modified, and optionally an email. Notifications are created inside the transaction of the
+//! modification (see [`notify_watchers`]), while emails are only sent by the API layer once that
+//! transaction has been committed (see [`email_recipients`]).
+//!
+//! Members are never notified about modifications they made themselves.
+
+use crate::{
+    demon::{Demon, DemonId},
+    error::{DemonlistError, Result},
+    player::{DatabasePlayer, PlayerId},
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::{error::CoreError, pool::ReadConnection};
+use pointercrate_user::notification::{Notification, NotificationKind};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`Watch`]
+    WatchId
+);
+
+/// An object that can be watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchTarget {
+    Demon(DemonId),
+    Player(PlayerId),
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Hash)]
+pub struct Watch {
+    pub id: WatchId,
+    pub demon: Option<DemonId>,
+    pub player: Option<PlayerId>,
+
+    /// Whether the watcher also wants to be notified via email
+    pub email: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostWatch {
+    #[serde(default)]
+    pub demon: Option<DemonId>,
+
+    #[serde(default)]
+    pub player: Option<PlayerId>,
+
+    #[serde(default)]
+    pub email: bool,
+}
+
+/// A watcher that wants to be notified about a modification via email
+#[derive(Debug)]
+pub struct EmailRecipient {
+    pub name: String,
+    pub email_address: String,
+}
+
+impl WatchTarget {
+    fn split(self) -> (Option<i32>, Option<i32>) {
+        match self {
+            WatchTarget::Demon(demon_id) => (Some(demon_id.0), None),
+            WatchTarget::Player(player_id) => (None, Some(player_id.0)),
+        }
+    }
+}
+
+impl Watch {
+    /// All watches of the given member, most recent first
+    pub async fn all_of(member_id: i32, connection: &mut impl ReadConnection) -> Result<Vec<Watch>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query_as!(
+            Watch,
+            r#"SELECT id, demon AS "demon: DemonId", player AS "player: PlayerId", email, created_at FROM watches WHERE member_id = $1 ORDER BY id DESC"#,
+            member_id
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+
+    /// Gets the watch with the given id, if it belongs to the given member
+    pub async fn by_id(WatchId(watch_id): WatchId, member_id: i32, connection: &mut PgConnection) -> Result<Watch> {
+        sqlx::query_as!(
+            Watch,
+            r#"SELECT id, demon AS "demon: DemonId", player AS "player: PlayerId", email, created_at FROM watches WHERE id = $1 AND member_id = $2"#,
+            watch_id,
+            member_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::WatchNotFound { watch_id })
+    }
+
+    pub async fn create(data: PostWatch, member_id: i32, connection: &mut PgConnection) -> Result<Watch> {
+        let target = match (data.demon, data.player) {
+            (Some(_), Some(_)) => return Err(CoreError::MutuallyExclusive.into()),
+            (None, None) => return Err(DemonlistError::NoWatchTarget),
+            (Some(demon_id), None) => WatchTarget::Demon(Demon::by_id(demon_id, &mut *connection).await?.base.id),
+            (None, Some(player_id)) => WatchTarget::Player(DatabasePlayer::by_id(player_id, &mut *connection).await?.id),
+        };
+
+        let (demon, player) = target.split();
+
+        let exists = sqlx::query!(
+            "SELECT EXISTS (SELECT 1 FROM watches WHERE member_id = $1 AND (demon = $2 OR player = $3)) AS \"exists!\"",
+            member_id,
+            demon,
+            player
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .exists;
+
+        if exists {
+            return Err(DemonlistError::AlreadyWatching);
+        }
+
+        info!("Member {} is now watching {:?}", member_id, target);
+
+        Ok(sqlx::query_as!(
+            Watch,
+            r#"INSERT INTO watches (member_id, demon, player, email) VALUES ($1, $2, $3, $4)
+               RETURNING id, demon AS "demon: DemonId", player AS "player: PlayerId", email, created_at"#,
+            member_id,
+            demon,
+            player,
+            data.email
+        )
+        .fetch_one(connection)
+        .await?)
+    }
+
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM watches WHERE id = $1", self.id.0)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Notifies everyone watching the given object, except for the member making the modification
+///
+/// Must be called inside the same transaction as the modification the notification is about
+pub async fn notify_watchers(target: WatchTarget, content: String, link: Option<String>, connection: &mut PgConnection) -> Result<()> {
+    let (demon, player) = target.split();
+
+    let watchers = sqlx::query!(
+        "SELECT member_id FROM watches WHERE (demon = $1 OR player = $2) AND member_id <> (SELECT id FROM active_user LIMIT 1)",
+        demon,
+        player
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for watcher in watchers {
+        Notification::create(
+            watcher.member_id,
+            NotificationKind::WatchedObjectModified,
+            content.clone(),
+            link.clone(),
+            &mut *connection,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Everyone watching the given object who wants to be notified via email and has an email address
+/// set, except for the member making the modification
+pub async fn email_recipients(target: WatchTarget, connection: &mut PgConnection) -> Result<Vec<EmailRecipient>> {
+    let (demon, player) = target.split();
+
+    Ok(sqlx::query_as!(
+        EmailRecipient,
+        r#"SELECT members.name, members.email_address::TEXT AS "email_address!"
+           FROM watches INNER JOIN members ON members.member_id = watches.member_id
+           WHERE (demon = $1 OR player = $2) AND watches.email AND members.email_address IS NOT NULL
+             AND watches.member_id <> (SELECT id FROM active_user LIMIT 1)"#,
+        demon,
+        player
+    )
+    .fetch_all(connection)
+    .await?)
+}
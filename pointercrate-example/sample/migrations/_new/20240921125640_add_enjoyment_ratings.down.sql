@@ -0,0 +1,5 @@
+ALTER TABLE IF EXISTS public.records
+    DROP COLUMN enjoyment;
+
+ALTER TABLE IF EXISTS public.rec_backup
+    DROP COLUMN enjoyment;
@@ -0,0 +1,52 @@
+ALTER TABLE members
+    ADD COLUMN banned BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN ban_reason TEXT NULL,
+    ADD COLUMN banned_until TIMESTAMP WITHOUT TIME ZONE NULL,
+    -- Part of the signing key of access tokens. Incrementing it invalidates all tokens of the account without
+    -- requiring knowledge of the account's password
+    ADD COLUMN token_generation INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE user_modifications
+    ADD COLUMN banned BOOLEAN NULL,
+    ADD COLUMN ban_reason TEXT NULL,
+    ADD COLUMN banned_until TIMESTAMP WITHOUT TIME ZONE NULL;
+
+CREATE OR REPLACE FUNCTION audit_user_modification() RETURNS trigger as $user_modification_trigger$
+DECLARE
+    display_name_change CITEXT;
+    youtube_channel_change VARCHAR(200);
+    permissions_change BIT(16);
+    banned_change BOOLEAN;
+    ban_reason_change TEXT;
+    banned_until_change TIMESTAMP WITHOUT TIME ZONE;
+BEGIN
+    IF (OLD.display_name <> NEW.display_name) THEN
+        display_name_change = OLD.display_name;
+    END IF;
+
+    IF (OLD.youtube_channel <> NEW.youtube_channel) THEN
+        youtube_channel_change = OLD.youtube_channel;
+    END IF;
+
+    IF (OLD.permissions <> NEW.permissions) THEN
+        permissions_change = OLD.permissions;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.ban_reason IS DISTINCT FROM NEW.ban_reason) THEN
+        ban_reason_change = OLD.ban_reason;
+    END IF;
+
+    IF (OLD.banned_until IS DISTINCT FROM NEW.banned_until) THEN
+        banned_until_change = OLD.banned_until;
+    END IF;
+
+    INSERT INTO user_modifications (userid, id, display_name, youtube_channel, permissions, banned, ban_reason, banned_until)
+        (SELECT id, NEW.member_id, display_name_change, youtube_channel_change, permissions_change, banned_change, ban_reason_change, banned_until_change FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$user_modification_trigger$ LANGUAGE plpgsql;
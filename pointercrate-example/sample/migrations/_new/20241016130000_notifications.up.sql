@@ -0,0 +1,11 @@
+CREATE TABLE notifications (
+    id SERIAL PRIMARY KEY,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    content TEXT NOT NULL,
+    link TEXT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    read BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX notifications_member_id_read_idx ON notifications(member_id, read);
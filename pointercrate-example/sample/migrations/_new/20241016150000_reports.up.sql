@@ -0,0 +1,17 @@
+CREATE TABLE reports (
+    id SERIAL PRIMARY KEY,
+    record INTEGER NULL REFERENCES records(id) ON DELETE CASCADE,
+    player INTEGER NULL REFERENCES players(id) ON DELETE CASCADE,
+    category TEXT NOT NULL,
+    description TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    submitter INTEGER NOT NULL REFERENCES submitters(submitter_id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    resolved_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    resolution_note TEXT NULL,
+
+    -- every report targets exactly one object
+    CHECK ((record IS NULL) <> (player IS NULL))
+);
+
+CREATE INDEX reports_status_idx ON reports(status);
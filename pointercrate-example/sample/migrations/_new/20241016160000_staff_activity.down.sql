@@ -0,0 +1 @@
+DROP TABLE staff_activity_stats;
@@ -0,0 +1,11 @@
+-- Weekly aggregates of how many submitted records each staff member reviewed (meaning: moved out of
+-- the 'SUBMITTED' state, or deleted), and how long these records were waiting for that review.
+-- Periodically refreshed from the audit log, see `StaffActivity::aggregate`.
+CREATE TABLE staff_activity_stats (
+    member_id INTEGER NOT NULL, -- no foreign key, as the audit log also references deleted members
+    week DATE NOT NULL,
+    records_reviewed INTEGER NOT NULL,
+    average_review_latency DOUBLE PRECISION NOT NULL, -- in seconds
+
+    PRIMARY KEY (member_id, week)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE demons DROP COLUMN submissions_closed_reason;
+ALTER TABLE demons DROP COLUMN submissions_open;
@@ -0,0 +1,4 @@
+-- Allows list moderators to temporarily stop accepting submissions for a demon, e.g. while it is
+-- being re-verified
+ALTER TABLE demons ADD COLUMN submissions_open BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE demons ADD COLUMN submissions_closed_reason TEXT NULL;
@@ -0,0 +1,10 @@
+-- List-wide submission settings. This table always contains exactly one row.
+CREATE TABLE submission_settings (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    submissions_open BOOLEAN NOT NULL DEFAULT TRUE,
+    closed_reason TEXT NULL,
+    -- point in time at which submissions are automatically reopened
+    reopen_at TIMESTAMP WITHOUT TIME ZONE NULL
+);
+
+INSERT INTO submission_settings DEFAULT VALUES;
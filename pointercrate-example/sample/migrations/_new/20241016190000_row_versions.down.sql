@@ -0,0 +1,11 @@
+DROP TRIGGER members_version ON members;
+DROP TRIGGER players_version ON players;
+DROP TRIGGER records_version ON records;
+DROP TRIGGER demons_version ON demons;
+
+ALTER TABLE members DROP COLUMN version;
+ALTER TABLE players DROP COLUMN version;
+ALTER TABLE records DROP COLUMN version;
+ALTER TABLE demons DROP COLUMN version;
+
+DROP FUNCTION bump_row_version();
@@ -0,0 +1,26 @@
+-- Monotonically increasing row versions, used as the PATCH part of ETags. Only changes to columns
+-- that can be modified via PATCH requests bump the version, so that e.g. score recomputations do
+-- not invalidate the ETags of players.
+CREATE FUNCTION bump_row_version() RETURNS trigger AS $bump_row_version$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$bump_row_version$ LANGUAGE plpgsql;
+
+ALTER TABLE demons ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE records ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE players ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE members ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+CREATE TRIGGER records_version BEFORE UPDATE OF progress, video, enjoyment, status_, player, demon ON records
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+CREATE TRIGGER players_version BEFORE UPDATE OF name, banned, nationality, subdivision ON players
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+CREATE TRIGGER members_version BEFORE UPDATE OF name, permissions, display_name, youtube_channel, banned, ban_reason, banned_until ON members
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
@@ -0,0 +1,6 @@
+DROP TRIGGER demons_version ON demons;
+
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+ALTER TABLE demons DROP COLUMN discussion_url;
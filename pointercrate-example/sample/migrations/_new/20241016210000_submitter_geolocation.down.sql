@@ -0,0 +1,6 @@
+DROP INDEX submitters_geo_recorded_at_idx;
+
+ALTER TABLE submitters DROP COLUMN geo_recorded_at;
+ALTER TABLE submitters DROP COLUMN asn_organization;
+ALTER TABLE submitters DROP COLUMN asn;
+ALTER TABLE submitters DROP COLUMN country_code;
@@ -0,0 +1,9 @@
+-- Coarse geolocation data of submitters, used by list administrators to spot spam waves coming
+-- from VPN providers. Cleared again after a configurable retention period (see
+-- submissions.geo_data_retention)
+ALTER TABLE submitters ADD COLUMN country_code VARCHAR(2) NULL;
+ALTER TABLE submitters ADD COLUMN asn INTEGER NULL;
+ALTER TABLE submitters ADD COLUMN asn_organization TEXT NULL;
+ALTER TABLE submitters ADD COLUMN geo_recorded_at TIMESTAMP WITHOUT TIME ZONE NULL;
+
+CREATE INDEX submitters_geo_recorded_at_idx ON submitters (geo_recorded_at);
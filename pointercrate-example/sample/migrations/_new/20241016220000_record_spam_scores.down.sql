@@ -0,0 +1,2 @@
+ALTER TABLE records DROP COLUMN spam_reasons;
+ALTER TABLE records DROP COLUMN spam_score;
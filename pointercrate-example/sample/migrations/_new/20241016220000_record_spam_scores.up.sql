@@ -0,0 +1,4 @@
+-- Heuristic spam score of record submissions, see pointercrate_demonlist::record::spam. Computed once
+-- when a record is submitted and never updated afterwards
+ALTER TABLE records ADD COLUMN spam_score SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE records ADD COLUMN spam_reasons TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,6 @@
+DROP TRIGGER records_version ON records;
+
+CREATE TRIGGER records_version BEFORE UPDATE OF progress, video, enjoyment, status_, player, demon ON records
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+ALTER TABLE records DROP COLUMN video_timestamp;
@@ -0,0 +1,7 @@
+-- The time (in seconds) into the video at which the completion happens
+ALTER TABLE records ADD COLUMN video_timestamp INTEGER NULL CHECK (video_timestamp >= 0);
+
+DROP TRIGGER records_version ON records;
+
+CREATE TRIGGER records_version BEFORE UPDATE OF progress, video, video_timestamp, enjoyment, status_, player, demon ON records
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
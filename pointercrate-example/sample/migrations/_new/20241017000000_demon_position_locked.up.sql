@@ -0,0 +1,6 @@
+ALTER TABLE demons ADD COLUMN position_locked BOOLEAN NOT NULL DEFAULT FALSE;
+
+DROP TRIGGER demons_version ON demons;
+
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason, discussion_url, position_locked ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
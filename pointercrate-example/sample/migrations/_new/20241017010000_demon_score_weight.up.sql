@@ -0,0 +1,101 @@
+ALTER TABLE demons ADD COLUMN score_weight DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK (score_weight >= 0.0);
+
+DROP TRIGGER demons_version ON demons;
+
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason, discussion_url, position_locked, score_weight ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+-- Columns can only be appended to a view using CREATE OR REPLACE
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player, demons.score_weight
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier, demons.score_weight
+    FROM demons;
+
+CREATE OR REPLACE FUNCTION score_of_player(player_id INTEGER) RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(progress, position, 150, requirement) * score_weight)
+    FROM score_giving
+    WHERE player = player_id
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_player_scores() RETURNS void AS $$
+    UPDATE players
+    SET score = coalesce(q.score, 0)
+    FROM players p
+        LEFT OUTER JOIN (
+            SELECT player, SUM(record_score(progress, position, 150, requirement) * score_weight) as score
+            FROM score_giving
+            GROUP BY player
+        ) q
+        ON q.player = p.id
+    WHERE players.id = p.id;
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION score_of_nation(iso_country_code VARCHAR(2)) RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(q.progress, q.position, 150, q.requirement) * q.score_weight)
+    FROM (
+        SELECT DISTINCT ON (position) * from score_giving
+        INNER JOIN players
+                ON players.id=player
+        WHERE players.nationality = iso_country_code
+        ORDER BY position, progress DESC
+    ) q
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_nation_scores() RETURNS void AS $$
+    UPDATE nationalities
+    SET score = COALESCE(p.sum, 0)
+    FROM nationalities n
+        LEFT OUTER JOIN (
+            SELECT nationality, SUM(record_score(q.progress, q.position, 150, q.requirement) * q.score_weight)
+            FROM (
+                SELECT DISTINCT ON (position, nationality) * from score_giving
+                INNER JOIN players
+                        ON players.id=player
+                WHERE players.nationality IS NOT NULL
+                ORDER BY players.nationality, position, progress DESC
+            ) q
+            GROUP BY nationality
+        ) p
+        ON p.nationality = n.iso_country_code
+    WHERE n.iso_country_code = nationalities.iso_country_code
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION score_of_subdivision(iso_country_code VARCHAR(2), iso_code VARCHAR(3)) RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(q.progress, q.position, 150, q.requirement) * q.score_weight)
+    FROM (
+        SELECT DISTINCT ON (position) * from score_giving
+        INNER JOIN players
+                ON players.id=player
+        WHERE players.nationality = iso_country_code
+          AND players.subdivision = iso_code
+        ORDER BY position, progress DESC
+    ) q
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_subdivision_scores() RETURNS void AS $$
+    UPDATE subdivisions
+    SET score = COALESCE(p.sum, 0)
+    FROM subdivisions s
+        LEFT OUTER JOIN (
+            SELECT nationality, subdivision, SUM(record_score(q.progress, q.position, 150, q.requirement) * q.score_weight)
+            FROM (
+                SELECT DISTINCT ON (position, nationality, subdivision) * from score_giving
+                INNER JOIN players
+                        ON players.id=player
+                WHERE players.nationality IS NOT NULL
+                AND players.subdivision IS NOT NULL
+                ORDER BY players.nationality, players.subdivision, position, progress DESC
+            ) q
+            GROUP BY nationality, subdivision
+        ) p
+        ON s.nation = p.nationality AND s.iso_code = p.subdivision
+    WHERE s.nation = subdivisions.nation
+      AND s.iso_code = subdivisions.iso_code
+$$ LANGUAGE SQL;
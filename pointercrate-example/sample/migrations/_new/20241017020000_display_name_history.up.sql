@@ -0,0 +1,10 @@
+-- Every display name a user has had, together with the time they stopped using it. Visible to
+-- moderators, and used to enforce a cooldown between renames (see auth.rename_cooldown)
+CREATE TABLE display_name_history (
+    id SERIAL PRIMARY KEY,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    display_name TEXT NULL,
+    changed_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX display_name_history_member_id_idx ON display_name_history (member_id, changed_at);
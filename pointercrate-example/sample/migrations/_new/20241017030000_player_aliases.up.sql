@@ -0,0 +1,9 @@
+-- Former names and alternate spellings of players. Submissions naming a player by one of their
+-- aliases are attributed to that player instead of creating a duplicate
+CREATE TABLE player_aliases (
+    alias CITEXT PRIMARY KEY,
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    added_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX player_aliases_player_idx ON player_aliases (player);
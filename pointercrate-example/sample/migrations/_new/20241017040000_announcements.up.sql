@@ -0,0 +1,9 @@
+-- Site-wide announcements, shown as banners on every page and available via the API
+CREATE TABLE announcements (
+    id SERIAL PRIMARY KEY,
+    message TEXT NOT NULL,
+    severity TEXT NOT NULL DEFAULT 'info' CHECK (severity IN ('info', 'warning', 'critical')),
+    starts_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ends_at TIMESTAMP WITHOUT TIME ZONE NULL,
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
@@ -0,0 +1,4 @@
+-- When an account was created. Unknown (NULL) for accounts created before this was tracked, which
+-- is why the default is only set after adding the column
+ALTER TABLE members ADD COLUMN created_at TIMESTAMP WITHOUT TIME ZONE NULL;
+ALTER TABLE members ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
@@ -0,0 +1,19 @@
+-- Tracks demons that need to be re-verified, e.g. because a game update broke them. While a
+-- re-verification is open, submissions for the demon are closed.
+CREATE TABLE demon_reverifications (
+    id SERIAL PRIMARY KEY,
+    demon INTEGER NOT NULL REFERENCES demons(id) ON DELETE CASCADE,
+    reason TEXT NULL,
+
+    -- the verification that will replace the demon's current one once the re-verification completes
+    candidate_verifier INTEGER NULL REFERENCES players(id) ON DELETE SET NULL ON UPDATE CASCADE,
+    candidate_video TEXT NULL,
+
+    requested_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    requested_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    completed_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    completed_at TIMESTAMP WITHOUT TIME ZONE NULL
+);
+
+-- At most one re-verification per demon can be in progress at any time
+CREATE UNIQUE INDEX demon_reverifications_open_idx ON demon_reverifications (demon) WHERE completed_at IS NULL;
@@ -0,0 +1 @@
+-- Values cannot be removed from enum types, so 'SUPERSEDED' remains a valid record_status
@@ -0,0 +1,6 @@
+-- Approved records that were replaced by an approved record with higher progress by the same
+-- player on the same demon. They are kept for history, but do not count towards any stats.
+--
+-- This needs to be its own migration, as a value added to an enum cannot be used in the same
+-- transaction that adds it.
+ALTER TYPE record_status ADD VALUE IF NOT EXISTS 'SUPERSEDED';
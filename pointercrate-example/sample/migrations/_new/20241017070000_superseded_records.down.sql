@@ -0,0 +1,4 @@
+DELETE FROM records WHERE status_ = 'SUPERSEDED';
+
+DROP INDEX records_demon_player_status__key;
+ALTER TABLE records ADD CONSTRAINT records_demon_player_status__key UNIQUE (demon, player, status_) DEFERRABLE INITIALLY IMMEDIATE;
@@ -0,0 +1,3 @@
+-- A player can have any number of superseded records on a demon
+ALTER TABLE records DROP CONSTRAINT IF EXISTS records_demon_player_status__key;
+CREATE UNIQUE INDEX records_demon_player_status__key ON records (demon, player, status_) WHERE status_ <> 'SUPERSEDED';
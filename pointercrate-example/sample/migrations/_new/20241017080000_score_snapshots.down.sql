@@ -0,0 +1,2 @@
+DROP TABLE nation_score_snapshots;
+DROP TABLE player_score_snapshots;
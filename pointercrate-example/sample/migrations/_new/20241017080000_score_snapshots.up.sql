@@ -0,0 +1,17 @@
+-- Weekly snapshots of player and nation scores, taken by `score_history::take_snapshots`. Used to draw
+-- progress graphs without having to reconstruct past scores from the audit log.
+CREATE TABLE player_score_snapshots (
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    week DATE NOT NULL, -- the monday of the week the snapshot was taken in
+    score DOUBLE PRECISION NOT NULL,
+
+    PRIMARY KEY (player, week)
+);
+
+CREATE TABLE nation_score_snapshots (
+    nation VARCHAR(2) NOT NULL REFERENCES nationalities(iso_country_code) ON DELETE CASCADE ON UPDATE CASCADE,
+    week DATE NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+
+    PRIMARY KEY (nation, week)
+);
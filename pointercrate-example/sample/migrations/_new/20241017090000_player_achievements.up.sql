@@ -0,0 +1,9 @@
+-- Milestones reached by players, see `player::achievement`. Achievements are never revoked, even if
+-- the records that earned them are later removed or the demons involved are moved.
+CREATE TABLE player_achievements (
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    achievement TEXT NOT NULL,
+    achieved_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+    PRIMARY KEY (player, achievement)
+);
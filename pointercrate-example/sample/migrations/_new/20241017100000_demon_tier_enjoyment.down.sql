@@ -0,0 +1,10 @@
+DROP TRIGGER records_update_demon_enjoyment ON records;
+DROP FUNCTION update_demon_enjoyment();
+
+DROP TRIGGER demons_version ON demons;
+
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason, discussion_url, position_locked, score_weight ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+ALTER TABLE demons DROP COLUMN enjoyment;
+ALTER TABLE demons DROP COLUMN tier;
@@ -0,0 +1,36 @@
+-- The difficulty tier of a demon, independent of its position. See `demon::DemonTier` for the possible values
+ALTER TABLE demons ADD COLUMN tier TEXT CHECK (tier IN ('easy_demon', 'medium_demon', 'hard_demon', 'insane_demon', 'extreme_demon'));
+
+-- Average enjoyment rating of all approved records on a demon, kept up to date by the trigger below.
+-- NULL if no approved record has an enjoyment rating.
+ALTER TABLE demons ADD COLUMN enjoyment DOUBLE PRECISION;
+
+UPDATE demons SET enjoyment = (SELECT AVG(enjoyment) FROM records WHERE records.demon = demons.id AND records.status_ = 'APPROVED');
+
+-- Only touches the demon if its average actually changed, as every update of a demon is recorded in the audit log
+CREATE FUNCTION update_demon_enjoyment() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP <> 'INSERT' THEN
+        UPDATE demons SET enjoyment = ratings.average
+        FROM (SELECT AVG(enjoyment) AS average FROM records WHERE records.demon = OLD.demon AND records.status_ = 'APPROVED') AS ratings
+        WHERE demons.id = OLD.demon AND demons.enjoyment IS DISTINCT FROM ratings.average;
+    END IF;
+
+    IF TG_OP <> 'DELETE' THEN
+        UPDATE demons SET enjoyment = ratings.average
+        FROM (SELECT AVG(enjoyment) AS average FROM records WHERE records.demon = NEW.demon AND records.status_ = 'APPROVED') AS ratings
+        WHERE demons.id = NEW.demon AND demons.enjoyment IS DISTINCT FROM ratings.average;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER records_update_demon_enjoyment AFTER INSERT OR DELETE OR UPDATE OF enjoyment, status_, demon ON records
+    FOR EACH ROW EXECUTE PROCEDURE update_demon_enjoyment();
+
+DROP TRIGGER demons_version ON demons;
+
+-- `enjoyment` is deliberately not versioned, as it is not patchable
+CREATE TRIGGER demons_version BEFORE UPDATE OF name, position, requirement, video, thumbnail, verifier, publisher, level_id, submissions_open, submissions_closed_reason, discussion_url, position_locked, score_weight, tier ON demons
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
@@ -0,0 +1,4 @@
+-- Random, stable identifier of a submitter that can be shown publicly on records without revealing
+-- which submitter (and thus which IP address) it belongs to. Being random, it cannot be reversed, and
+-- the mapping back to submitters is only exposed to list administrators.
+ALTER TABLE submitters ADD COLUMN public_id TEXT NOT NULL UNIQUE DEFAULT substr(md5(random()::text || clock_timestamp()::text), 1, 16);
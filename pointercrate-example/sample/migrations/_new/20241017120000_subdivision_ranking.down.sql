@@ -0,0 +1,2 @@
+DROP INDEX players_subdivision_score;
+DROP VIEW ranked_subdivision_players;
@@ -0,0 +1,10 @@
+-- Like ranked_players, but ranks players only against other players from the same subdivision
+CREATE VIEW ranked_subdivision_players AS
+    SELECT
+        ROW_NUMBER() OVER(PARTITION BY nationality, subdivision ORDER BY score DESC, id) AS index,
+        RANK() OVER(PARTITION BY nationality, subdivision ORDER BY score DESC) AS rank,
+        id, name, score, nationality, subdivision, version
+    FROM players
+    WHERE NOT banned AND score > 0.0 AND subdivision IS NOT NULL;
+
+CREATE INDEX players_subdivision_score ON players (nationality, subdivision, score DESC) WHERE NOT banned AND score > 0.0;
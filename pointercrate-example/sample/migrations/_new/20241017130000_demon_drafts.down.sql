@@ -0,0 +1 @@
+DROP TABLE demon_drafts;
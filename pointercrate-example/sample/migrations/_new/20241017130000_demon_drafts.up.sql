@@ -0,0 +1,18 @@
+-- Demons that are being prepared by list staff but are not yet part of the public list. Players are
+-- referenced by name, since creating them before the draft is published would make them public.
+CREATE TABLE demon_drafts (
+    id SERIAL PRIMARY KEY,
+    name CITEXT NOT NULL,
+    requirement SMALLINT NOT NULL,
+
+    -- the position the demon will be inserted at once published
+    position SMALLINT NULL CHECK (position > 0),
+    verifier CITEXT NULL,
+    publisher CITEXT NULL,
+    creators TEXT[] NOT NULL DEFAULT '{}',
+    video TEXT NULL,
+    level_id BIGINT NULL,
+
+    created_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
@@ -0,0 +1,2 @@
+DROP TABLE scheduled_list_changes;
+DROP TABLE scheduled_list_updates;
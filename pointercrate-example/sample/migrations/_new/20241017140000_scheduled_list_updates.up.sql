@@ -0,0 +1,31 @@
+-- Batches of list changes that are applied atomically at a given point in time, see `list_update`
+-- in `pointercrate-demonlist`. An update is pending as long as it has neither been applied nor failed.
+CREATE TABLE scheduled_list_updates (
+    id SERIAL PRIMARY KEY,
+    scheduled_for TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    description TEXT NULL,
+
+    created_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+    applied_at TIMESTAMP WITHOUT TIME ZONE NULL,
+
+    -- why applying this update failed, if it did. None of its changes were applied in that case
+    error TEXT NULL
+);
+
+CREATE INDEX scheduled_list_updates_pending_idx ON scheduled_list_updates (scheduled_for) WHERE applied_at IS NULL AND error IS NULL;
+
+-- The individual changes of a scheduled update, applied in order of `ordinal`. Demons and drafts are
+-- deliberately not foreign keys: if they vanish before the update is due, applying it fails
+-- instead of silently skipping the change.
+CREATE TABLE scheduled_list_changes (
+    update_id INTEGER NOT NULL REFERENCES scheduled_list_updates(id) ON DELETE CASCADE,
+    ordinal INTEGER NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('place', 'move', 'remove')),
+    demon INTEGER NULL,
+    draft INTEGER NULL,
+    position SMALLINT NULL,
+
+    PRIMARY KEY (update_id, ordinal)
+);
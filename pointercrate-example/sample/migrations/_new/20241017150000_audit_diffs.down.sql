@@ -0,0 +1,143 @@
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    position_change SMALLINT;
+    requirement_change SMALLINT;
+    video_change VARCHAR(200);
+    thumbnail_change TEXT;
+    verifier_change INT;
+    publisher_change INT;
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.position <> NEW.position) THEN
+        position_change = OLD.position;
+    END IF;
+
+    IF (OLD.requirement <> NEW.requirement) THEN
+        requirement_change = OLD.requirement;
+    END IF;
+
+    IF (OLD.video <> NEW.video) THEN
+        video_change = OLD.video;
+    END IF;
+
+    IF (OLD.thumbnail <> NEW.thumbnail) THEN
+        thumbnail_change = OLD.thumbnail;
+    END IF;
+
+    IF (OLD.verifier <> NEW.verifier) THEN
+        verifier_change = OLD.verifier;
+    END IF;
+
+    IF (OLD.publisher <> NEW.publisher) THEN
+        publisher_change = OLD.publisher;
+    END IF;
+
+    INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id)
+        (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, thumbnail_change, NEW.id
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_modification() RETURNS trigger AS $record_modification_trigger$
+    DECLARE
+        progress_change SMALLINT;
+        video_change VARCHAR(200);
+        status_change RECORD_STATUS;
+        player_change INT;
+        demon_change INTEGER;
+    BEGIN
+        if (OLD.progress <> NEW.progress) THEN
+            progress_change = OLD.progress;
+        END IF;
+
+        IF (OLD.video <> NEW.video) THEN
+            video_change = OLD.video;
+        END IF;
+
+        IF (OLD.status_ <> NEW.status_) THEN
+            status_change = OLD.status_;
+        END IF;
+
+        IF (OLD.player <> NEW.player) THEN
+            player_change = OLD.player;
+        END IF;
+
+        IF (OLD.demon <> NEW.demon) THEN
+            demon_change = OLD.demon;
+        END IF;
+
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon)
+            (SELECT id, NEW.id, progress_change, video_change, status_change, player_change, demon_change
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$record_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_modification() RETURNS trigger as $player_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    banned_change BOOLEAN;
+    nationality_change VARCHAR(2);
+    subdivision_change VARCHAR(3);
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.nationality <> NEW.nationality) THEN
+        nationality_change = OLD.nationality;
+    end if;
+
+    IF (OLD.subdivision <> NEW.subdivision) THEN
+        subdivision_change = OLD.subdivision;
+    end if;
+
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision)
+        (SELECT id, NEW.id, name_change, banned_change, nationality_change, subdivision_change FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$player_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+DROP FUNCTION audit_diff(JSONB, JSONB);
+
+ALTER TABLE audit_log2 DROP COLUMN diff;
@@ -0,0 +1,175 @@
+-- Structured diffs for modification audit log entries. Each modification stores a JSON object mapping
+-- every changed column to its old and new value, e.g. {"position": {"old": 3, "new": 4}}. Unlike
+-- the per-column "old value" fields, this also captures changes from and to NULL, and columns that
+-- have no dedicated audit column. Entries created before this migration have a NULL diff.
+ALTER TABLE audit_log2 ADD COLUMN diff JSONB;
+
+-- The row version is bumped on every update and thus never part of a diff
+CREATE FUNCTION audit_diff(old_row JSONB, new_row JSONB) RETURNS JSONB AS $$
+    SELECT COALESCE(jsonb_object_agg(columns.key, jsonb_build_object('old', old_row -> columns.key, 'new', new_row -> columns.key)), '{}'::jsonb)
+    FROM jsonb_object_keys(new_row) AS columns(key)
+    WHERE columns.key <> 'version' AND old_row -> columns.key IS DISTINCT FROM new_row -> columns.key
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    position_change SMALLINT;
+    requirement_change SMALLINT;
+    video_change VARCHAR(200);
+    thumbnail_change TEXT;
+    verifier_change INT;
+    publisher_change INT;
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.position <> NEW.position) THEN
+        position_change = OLD.position;
+    END IF;
+
+    IF (OLD.requirement <> NEW.requirement) THEN
+        requirement_change = OLD.requirement;
+    END IF;
+
+    IF (OLD.video <> NEW.video) THEN
+        video_change = OLD.video;
+    END IF;
+
+    IF (OLD.thumbnail <> NEW.thumbnail) THEN
+        thumbnail_change = OLD.thumbnail;
+    END IF;
+
+    IF (OLD.verifier <> NEW.verifier) THEN
+        verifier_change = OLD.verifier;
+    END IF;
+
+    IF (OLD.publisher <> NEW.publisher) THEN
+        publisher_change = OLD.publisher;
+    END IF;
+
+    INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id, diff)
+        (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, thumbnail_change, NEW.id,
+                audit_diff(to_jsonb(OLD), to_jsonb(NEW))
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_modification() RETURNS trigger AS $record_modification_trigger$
+    DECLARE
+        progress_change SMALLINT;
+        video_change VARCHAR(200);
+        status_change RECORD_STATUS;
+        player_change INT;
+        demon_change INTEGER;
+    BEGIN
+        if (OLD.progress <> NEW.progress) THEN
+            progress_change = OLD.progress;
+        END IF;
+
+        IF (OLD.video <> NEW.video) THEN
+            video_change = OLD.video;
+        END IF;
+
+        IF (OLD.status_ <> NEW.status_) THEN
+            status_change = OLD.status_;
+        END IF;
+
+        IF (OLD.player <> NEW.player) THEN
+            player_change = OLD.player;
+        END IF;
+
+        IF (OLD.demon <> NEW.demon) THEN
+            demon_change = OLD.demon;
+        END IF;
+
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon, diff)
+            (SELECT id, NEW.id, progress_change, video_change, status_change, player_change, demon_change, audit_diff(to_jsonb(OLD), to_jsonb(NEW))
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$record_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_modification() RETURNS trigger as $player_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    banned_change BOOLEAN;
+    nationality_change VARCHAR(2);
+    subdivision_change VARCHAR(3);
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.nationality <> NEW.nationality) THEN
+        nationality_change = OLD.nationality;
+    end if;
+
+    IF (OLD.subdivision <> NEW.subdivision) THEN
+        subdivision_change = OLD.subdivision;
+    end if;
+
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision, diff)
+        (SELECT id, NEW.id, name_change, banned_change, nationality_change, subdivision_change, audit_diff(to_jsonb(OLD), to_jsonb(NEW))
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$player_modification_trigger$ LANGUAGE plpgsql;
+
+-- Reconstruct the list at a given point in time from the oldest value recorded in a diff after that
+-- point. Modifications from before diffs existed only ever tracked positions reliably, so for those
+-- the old behaviour (current values, positions from the dedicated audit column) is kept.
+CREATE OR REPLACE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT CASE WHEN past.old ? 'name' THEN (past.old ->> 'name')::CITEXT ELSE demons.name END,
+       CASE WHEN t.position IS NOT NULL THEN t.position WHEN past.old ? 'position' THEN (past.old ->> 'position')::SMALLINT ELSE demons.position END,
+       CASE WHEN past.old ? 'requirement' THEN (past.old ->> 'requirement')::SMALLINT ELSE demons.requirement END,
+       CASE WHEN past.old ? 'video' THEN (past.old ->> 'video')::VARCHAR(200) ELSE demons.video END,
+       CASE WHEN past.old ? 'thumbnail' THEN (past.old ->> 'thumbnail') ELSE demons.thumbnail END,
+       CASE WHEN past.old ? 'verifier' THEN (past.old ->> 'verifier')::INTEGER ELSE demons.verifier END,
+       CASE WHEN past.old ? 'publisher' THEN (past.old ->> 'publisher')::INTEGER ELSE demons.publisher END,
+       demons.id,
+       CASE WHEN past.old ? 'level_id' THEN (past.old ->> 'level_id')::BIGINT ELSE demons.level_id END,
+       demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1 AND diff IS NULL
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+         LEFT OUTER JOIN LATERAL (
+    SELECT jsonb_object_agg(changes.key, changes.old) AS old
+    FROM (
+        SELECT DISTINCT ON (change.key) change.key, change.value -> 'old' AS old
+        FROM demon_modifications, jsonb_each(demon_modifications.diff) AS change
+        WHERE demon_modifications.id = demons.id AND time >= $1
+        ORDER BY change.key, time, audit_id
+    ) changes
+) past ON TRUE
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+    LANGUAGE SQL
+    STABLE;
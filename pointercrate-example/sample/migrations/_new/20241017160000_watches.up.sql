@@ -0,0 +1,19 @@
+-- Demons and players list staff want to be notified about whenever they are modified
+CREATE TABLE watches (
+    id SERIAL PRIMARY KEY,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    demon INTEGER NULL REFERENCES demons(id) ON DELETE CASCADE,
+    player INTEGER NULL REFERENCES players(id) ON DELETE CASCADE,
+
+    -- whether to also send an email (if the member has an email address set)
+    email BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+    -- every watch targets exactly one object
+    CHECK ((demon IS NULL) <> (player IS NULL)),
+    UNIQUE (member_id, demon),
+    UNIQUE (member_id, player)
+);
+
+CREATE INDEX watches_demon_idx ON watches(demon);
+CREATE INDEX watches_player_idx ON watches(player);
@@ -0,0 +1,17 @@
+-- Appeals of submitters against the rejection of their records. Every rejection can be appealed at most once.
+CREATE TABLE record_appeals (
+    id SERIAL PRIMARY KEY,
+    record INTEGER NOT NULL UNIQUE REFERENCES records(id) ON DELETE CASCADE,
+    submitter INTEGER NOT NULL REFERENCES submitters(submitter_id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'accepted', 'denied')),
+
+    -- the staff member who rejected the record, who is not allowed to resolve the appeal. NULL if unknown.
+    rejected_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    resolved_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL,
+    resolution_note TEXT NULL
+);
+
+CREATE INDEX record_appeals_status_idx ON record_appeals(status);
+CREATE INDEX record_appeals_submitter_idx ON record_appeals(submitter);
@@ -0,0 +1,25 @@
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT records.progress::INTEGER, demons.position, demons.requirement, records.player, demons.score_weight
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier, demons.score_weight
+    FROM demons;
+
+DELETE FROM records WHERE verification;
+
+DROP INDEX records_verification_key;
+DROP INDEX records_demon_player_status__key;
+CREATE UNIQUE INDEX records_demon_player_status__key ON records (demon, player, status_) WHERE status_ <> 'SUPERSEDED';
+
+ALTER TABLE records DROP CONSTRAINT records_submitter_check;
+ALTER TABLE records ALTER COLUMN submitter SET NOT NULL;
+ALTER TABLE records DROP COLUMN verification;
+
+SELECT recompute_player_scores();
+SELECT recompute_nation_scores();
+SELECT recompute_subdivision_scores();
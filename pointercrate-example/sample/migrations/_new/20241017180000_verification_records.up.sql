@@ -0,0 +1,30 @@
+-- Every demon has exactly one verification record: an approved 100% record of its verifier, maintained
+-- by pointercrate_demonlist::record::verification. Verification records are not submitted by anyone,
+-- and they are exempt from the usual uniqueness of (demon, player, status)-records.
+ALTER TABLE records ADD COLUMN verification BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE records ALTER COLUMN submitter DROP NOT NULL;
+ALTER TABLE records ADD CONSTRAINT records_submitter_check CHECK (verification OR submitter IS NOT NULL);
+
+DROP INDEX records_demon_player_status__key;
+CREATE UNIQUE INDEX records_demon_player_status__key ON records (demon, player, status_) WHERE status_ <> 'SUPERSEDED' AND NOT verification;
+CREATE UNIQUE INDEX records_verification_key ON records (demon) WHERE verification;
+
+INSERT INTO records (progress, video, status_, player, demon, verification)
+    SELECT 100, demons.video, CASE WHEN players.banned THEN 'REJECTED'::record_status ELSE 'APPROVED'::record_status END,
+           demons.verifier, demons.id, TRUE
+    FROM demons
+    INNER JOIN players ON players.id = demons.verifier;
+
+-- Verifications are now approved records like any other, so they no longer need to be added separately.
+-- A verifier with an approved record of their own on the demon only gets points for their best one.
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT DISTINCT ON (records.demon, records.player) records.progress::INTEGER, demons.position, demons.requirement, records.player, demons.score_weight
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+    ORDER BY records.demon, records.player, records.progress DESC;
+
+SELECT recompute_player_scores();
+SELECT recompute_nation_scores();
+SELECT recompute_subdivision_scores();
@@ -0,0 +1,2 @@
+DROP TABLE archived_records;
+DROP TABLE archived_demons;
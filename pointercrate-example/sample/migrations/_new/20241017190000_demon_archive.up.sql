@@ -0,0 +1,13 @@
+-- Demons that were removed from the list entirely, together with their records. Both tables mirror
+-- the layout of their live counterparts (rows are copied by column name), so columns added to
+-- `demons` or `records` need to be added here as well.
+CREATE TABLE archived_demons (LIKE demons INCLUDING DEFAULTS);
+ALTER TABLE archived_demons ADD PRIMARY KEY (id);
+
+-- `position` is the position the demon had when it was archived
+ALTER TABLE archived_demons ADD COLUMN creators INTEGER[] NOT NULL DEFAULT '{}';
+ALTER TABLE archived_demons ADD COLUMN archived_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc');
+
+CREATE TABLE archived_records (LIKE records INCLUDING DEFAULTS);
+ALTER TABLE archived_records ADD PRIMARY KEY (id);
+CREATE INDEX archived_records_demon_idx ON archived_records(demon);
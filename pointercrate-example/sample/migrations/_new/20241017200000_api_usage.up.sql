@@ -0,0 +1,11 @@
+-- The number of API requests each member made per day (in UTC), counted whenever a request is
+-- authenticated via access token
+CREATE TABLE api_usage (
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    day DATE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')::DATE,
+    requests BIGINT NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (member_id, day)
+);
+
+CREATE INDEX api_usage_day_idx ON api_usage(day);
@@ -0,0 +1,16 @@
+-- Third-party applications accessing the API on behalf of their owner via the OAuth 2 client
+-- credentials grant. Only a hash of the client secret is stored, just like for passwords.
+CREATE TABLE applications (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    owner INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    client_id TEXT NOT NULL UNIQUE DEFAULT replace(gen_random_uuid()::TEXT, '-', ''),
+    client_secret_hash TEXT NOT NULL,
+    redirect_uris TEXT[] NOT NULL DEFAULT '{}',
+
+    -- bitmask of the permissions tokens issued to this application are granted
+    scopes BIT(16) NOT NULL DEFAULT 0::BIT(16),
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX applications_owner_idx ON applications(owner);
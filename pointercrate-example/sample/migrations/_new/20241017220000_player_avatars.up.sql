@@ -0,0 +1,14 @@
+-- Avatars uploaded by the holders of verified player claims. Only approved avatars are served
+-- publicly. The images themselves live in pointercrate's file storage.
+CREATE TABLE player_avatars (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    uploaded_by INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    uploaded_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+    -- Incremented with every upload, so that the files of different uploads never share a storage key
+    revision INTEGER NOT NULL DEFAULT 1,
+    approved BOOLEAN NOT NULL DEFAULT FALSE,
+    reviewed_by INTEGER NULL REFERENCES members(member_id) ON DELETE SET NULL
+);
+
+CREATE INDEX player_avatars_pending ON player_avatars (uploaded_at) WHERE NOT approved;
@@ -0,0 +1,12 @@
+-- Results of periodically checking whether the videos of approved records are still available.
+-- Kept separate from `records` so that checks neither show up in the audit log nor bump record
+-- versions. Changing a record's video discards its check.
+CREATE TABLE record_video_checks (
+    record_id INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    checked_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+    -- When the video was first found to be unavailable, NULL if it was available during the last check
+    dead_since TIMESTAMP WITHOUT TIME ZONE NULL
+);
+
+CREATE INDEX record_video_checks_dead ON record_video_checks(dead_since) WHERE dead_since IS NOT NULL;
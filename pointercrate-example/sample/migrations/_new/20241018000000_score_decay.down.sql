@@ -0,0 +1,14 @@
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT DISTINCT ON (records.demon, records.player) records.progress::INTEGER, demons.position, demons.requirement, records.player, demons.score_weight
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+    ORDER BY records.demon, records.player, records.progress DESC;
+
+DROP FUNCTION position_weight(SMALLINT);
+DROP TABLE score_decay;
+
+SELECT recompute_player_scores();
+SELECT recompute_nation_scores();
+SELECT recompute_subdivision_scores();
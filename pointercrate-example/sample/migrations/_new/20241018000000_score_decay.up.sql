@@ -0,0 +1,23 @@
+-- Position based weights applied on top of record_score, mirroring the list.score_decay configuration.
+-- pointercrate_demonlist::score::sync_decay replaces the contents of this table (and recomputes all
+-- scores) whenever the configuration changes. The default configuration zeroes scores past #150.
+CREATE TABLE score_decay (
+    from_position SMALLINT PRIMARY KEY CHECK (from_position > 0),
+    weight DOUBLE PRECISION NOT NULL CHECK (weight >= 0.0 AND weight <= 1.0)
+);
+
+INSERT INTO score_decay (from_position, weight) VALUES (151, 0.0);
+
+CREATE FUNCTION position_weight(pos SMALLINT) RETURNS DOUBLE PRECISION AS $$
+    SELECT COALESCE((SELECT weight FROM score_decay WHERE from_position <= pos ORDER BY from_position DESC LIMIT 1), 1.0)
+$$ LANGUAGE SQL STABLE;
+
+-- All score functions multiply by score_weight, so folding the decay into it applies it everywhere
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT DISTINCT ON (records.demon, records.player) records.progress::INTEGER, demons.position, demons.requirement, records.player,
+           demons.score_weight * position_weight(demons.position) AS score_weight
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+    ORDER BY records.demon, records.player, records.progress DESC;
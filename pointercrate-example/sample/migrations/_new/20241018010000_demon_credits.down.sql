@@ -0,0 +1,4 @@
+ALTER TABLE archived_demons DROP COLUMN co_publishers;
+ALTER TABLE archived_demons DROP COLUMN co_verifiers;
+
+DROP TABLE demon_credits;
@@ -0,0 +1,14 @@
+-- Additional verifiers and publishers of demons, for co-verifications and republishes. The primary
+-- verifier and publisher remain in demons.verifier and demons.publisher, and are never duplicated here.
+CREATE TABLE demon_credits (
+    demon INTEGER NOT NULL REFERENCES demons(id) ON DELETE CASCADE,
+    player INTEGER NOT NULL REFERENCES players(id),
+    kind TEXT NOT NULL CHECK (kind IN ('verifier', 'publisher')),
+    PRIMARY KEY (demon, player, kind)
+);
+
+CREATE INDEX demon_credits_player ON demon_credits(player);
+
+-- Archived demons keep their additional credits around, so that restoring them is lossless
+ALTER TABLE archived_demons ADD COLUMN co_verifiers INTEGER[] NOT NULL DEFAULT '{}';
+ALTER TABLE archived_demons ADD COLUMN co_publishers INTEGER[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+ALTER TABLE archived_demons DROP COLUMN creator_roles;
+
+ALTER TABLE creators DROP COLUMN role;
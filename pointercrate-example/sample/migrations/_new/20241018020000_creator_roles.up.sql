@@ -0,0 +1,6 @@
+-- What each creator of a demon contributed to it. NULL if unknown, which is the case for all creators
+-- added before roles existed.
+ALTER TABLE creators ADD COLUMN role TEXT CHECK (role IN ('host', 'decorator', 'gameplay'));
+
+-- The roles of archived demons' creators, in the same order as archived_demons.creators
+ALTER TABLE archived_demons ADD COLUMN creator_roles TEXT[] NOT NULL DEFAULT '{}';
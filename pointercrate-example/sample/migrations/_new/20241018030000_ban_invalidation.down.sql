@@ -0,0 +1,2 @@
+ALTER TABLE archived_records DROP COLUMN rejected_by_ban;
+ALTER TABLE records DROP COLUMN rejected_by_ban;
@@ -0,0 +1,4 @@
+-- Marks records that were approved until their player got banned, so that unbanning the player can
+-- approve them again
+ALTER TABLE records ADD COLUMN rejected_by_ban BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE archived_records ADD COLUMN rejected_by_ban BOOLEAN NOT NULL DEFAULT FALSE;
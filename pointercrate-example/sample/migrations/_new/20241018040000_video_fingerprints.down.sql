@@ -0,0 +1,6 @@
+DROP TRIGGER records_fingerprint_video ON records;
+DROP FUNCTION fingerprint_record_video();
+
+DROP TABLE video_fingerprints;
+
+DROP FUNCTION video_fingerprint(TEXT);
@@ -0,0 +1,43 @@
+-- Splits a canonical video URL (see `video::validate`) into the host and the video's ID on that host
+CREATE FUNCTION video_fingerprint(video TEXT, OUT host TEXT, OUT video_id TEXT) AS $$
+    SELECT substring(video FROM '^https?://([^/?#]+)'),
+           CASE WHEN video ~ '^https?://www\.youtube\.com/' THEN substring(video FROM '[?&]v=([^&#]+)')
+                ELSE substring(video FROM '/([^/?#]+)/?(?:[?#].*)?$') END
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- Fingerprints of record videos, used to warn reviewers when a video already proving an approved
+-- record of some player gets submitted for another player. Maintained by the trigger below, except
+-- for the channel that uploaded the video, which is only known once the video was checked (see
+-- `record_video_checks`). Kept separate from `records` for the same reasons as the video checks.
+CREATE TABLE video_fingerprints (
+    record_id INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    host TEXT NOT NULL,
+    video_id TEXT NOT NULL,
+    channel TEXT NULL
+);
+
+CREATE INDEX video_fingerprints_video ON video_fingerprints(host, video_id);
+CREATE INDEX video_fingerprints_channel ON video_fingerprints(channel) WHERE channel IS NOT NULL;
+
+CREATE FUNCTION fingerprint_record_video() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.video IS NOT DISTINCT FROM NEW.video THEN
+        RETURN NULL;
+    END IF;
+
+    DELETE FROM video_fingerprints WHERE record_id = NEW.id;
+
+    INSERT INTO video_fingerprints (record_id, host, video_id)
+    SELECT NEW.id, fingerprint.host, fingerprint.video_id FROM video_fingerprint(NEW.video) AS fingerprint
+    WHERE fingerprint.host IS NOT NULL AND fingerprint.video_id IS NOT NULL;
+
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER records_fingerprint_video AFTER INSERT OR UPDATE OF video ON records
+    FOR EACH ROW EXECUTE PROCEDURE fingerprint_record_video();
+
+INSERT INTO video_fingerprints (record_id, host, video_id)
+SELECT records.id, fingerprint.host, fingerprint.video_id FROM records, video_fingerprint(records.video) AS fingerprint
+WHERE fingerprint.host IS NOT NULL AND fingerprint.video_id IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE assignment_opt_outs;
+DROP TABLE record_assignments;
@@ -0,0 +1,16 @@
+-- The list helper responsible for reviewing a submission, if submissions are assigned automatically.
+-- Assignments are kept after the review, but only ones whose record is still waiting for review
+-- count as open.
+CREATE TABLE record_assignments (
+    record_id INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    assigned_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX record_assignments_member ON record_assignments(member_id);
+
+-- List helpers that do not want to be assigned submissions
+CREATE TABLE assignment_opt_outs (
+    member_id INTEGER PRIMARY KEY REFERENCES members(member_id) ON DELETE CASCADE,
+    opted_out_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
@@ -0,0 +1,2 @@
+ALTER TABLE members DROP COLUMN last_digest_at;
+ALTER TABLE members DROP COLUMN digest_frequency;
@@ -0,0 +1,6 @@
+-- How often a user wants to receive an email summarizing their notifications. NULL means never.
+ALTER TABLE members ADD COLUMN digest_frequency TEXT NULL CHECK (digest_frequency IN ('daily', 'weekly'));
+
+-- When the last digest was sent to this user, so that every notification ends up in at most one
+-- digest
+ALTER TABLE members ADD COLUMN last_digest_at TIMESTAMP WITHOUT TIME ZONE NULL;
@@ -0,0 +1,3 @@
+-- Display preferences (locale, timezone and theme) of each user, as a JSON object. Notification
+-- preferences have dedicated columns, as background jobs query them.
+ALTER TABLE members ADD COLUMN preferences JSONB NOT NULL DEFAULT '{}'::JSONB;
@@ -0,0 +1,184 @@
+DROP FUNCTION list_at(TIMESTAMP WITH TIME ZONE);
+
+CREATE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT CASE WHEN past.old ? 'name' THEN (past.old ->> 'name')::CITEXT ELSE demons.name END,
+       CASE WHEN t.position IS NOT NULL THEN t.position WHEN past.old ? 'position' THEN (past.old ->> 'position')::SMALLINT ELSE demons.position END,
+       CASE WHEN past.old ? 'requirement' THEN (past.old ->> 'requirement')::SMALLINT ELSE demons.requirement END,
+       CASE WHEN past.old ? 'video' THEN (past.old ->> 'video')::VARCHAR(200) ELSE demons.video END,
+       CASE WHEN past.old ? 'thumbnail' THEN (past.old ->> 'thumbnail') ELSE demons.thumbnail END,
+       CASE WHEN past.old ? 'verifier' THEN (past.old ->> 'verifier')::INTEGER ELSE demons.verifier END,
+       CASE WHEN past.old ? 'publisher' THEN (past.old ->> 'publisher')::INTEGER ELSE demons.publisher END,
+       demons.id,
+       CASE WHEN past.old ? 'level_id' THEN (past.old ->> 'level_id')::BIGINT ELSE demons.level_id END,
+       demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1 AND diff IS NULL
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+         LEFT OUTER JOIN LATERAL (
+    SELECT jsonb_object_agg(changes.key, changes.old) AS old
+    FROM (
+        SELECT DISTINCT ON (change.key) change.key, change.value -> 'old' AS old
+        FROM demon_modifications, jsonb_each(demon_modifications.diff) AS change
+        WHERE demon_modifications.id = demons.id AND time >= $1
+        ORDER BY change.key, time, audit_id
+    ) changes
+) past ON TRUE
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE OR REPLACE FUNCTION audit_user_modification() RETURNS trigger as $user_modification_trigger$
+DECLARE
+    display_name_change CITEXT;
+    youtube_channel_change VARCHAR(200);
+    permissions_change BIT(16);
+    banned_change BOOLEAN;
+    ban_reason_change TEXT;
+    banned_until_change TIMESTAMP WITHOUT TIME ZONE;
+BEGIN
+    IF (OLD.display_name <> NEW.display_name) THEN
+        display_name_change = OLD.display_name;
+    END IF;
+
+    IF (OLD.youtube_channel <> NEW.youtube_channel) THEN
+        youtube_channel_change = OLD.youtube_channel;
+    END IF;
+
+    IF (OLD.permissions <> NEW.permissions) THEN
+        permissions_change = OLD.permissions;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.ban_reason IS DISTINCT FROM NEW.ban_reason) THEN
+        ban_reason_change = OLD.ban_reason;
+    END IF;
+
+    IF (OLD.banned_until IS DISTINCT FROM NEW.banned_until) THEN
+        banned_until_change = OLD.banned_until;
+    END IF;
+
+    INSERT INTO user_modifications (userid, id, display_name, youtube_channel, permissions, banned, ban_reason, banned_until)
+        (SELECT id, NEW.member_id, display_name_change, youtube_channel_change, permissions_change, banned_change, ban_reason_change, banned_until_change FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$user_modification_trigger$ LANGUAGE plpgsql;
+
+ALTER TABLE audit_log2
+    ALTER COLUMN time TYPE TIMESTAMP WITHOUT TIME ZONE USING time AT TIME ZONE 'utc',
+    ALTER COLUMN time SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE user_modifications
+    ALTER COLUMN banned_until TYPE TIMESTAMP WITHOUT TIME ZONE USING banned_until AT TIME ZONE 'utc';
+
+DROP TRIGGER members_version ON members;
+
+ALTER TABLE members
+    ALTER COLUMN banned_until TYPE TIMESTAMP WITHOUT TIME ZONE USING banned_until AT TIME ZONE 'utc',
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ALTER COLUMN last_digest_at TYPE TIMESTAMP WITHOUT TIME ZONE USING last_digest_at AT TIME ZONE 'utc';
+
+CREATE TRIGGER members_version BEFORE UPDATE OF name, permissions, display_name, youtube_channel, banned, ban_reason, banned_until ON members
+    FOR EACH ROW EXECUTE PROCEDURE bump_row_version();
+
+ALTER TABLE notifications
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE reports
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE submission_settings
+    ALTER COLUMN reopen_at TYPE TIMESTAMP WITHOUT TIME ZONE USING reopen_at AT TIME ZONE 'utc';
+
+ALTER TABLE submitters
+    ALTER COLUMN geo_recorded_at TYPE TIMESTAMP WITHOUT TIME ZONE USING geo_recorded_at AT TIME ZONE 'utc';
+
+ALTER TABLE display_name_history
+    ALTER COLUMN changed_at TYPE TIMESTAMP WITHOUT TIME ZONE USING changed_at AT TIME ZONE 'utc',
+    ALTER COLUMN changed_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE player_aliases
+    ALTER COLUMN added_at TYPE TIMESTAMP WITHOUT TIME ZONE USING added_at AT TIME ZONE 'utc',
+    ALTER COLUMN added_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE announcements
+    ALTER COLUMN starts_at TYPE TIMESTAMP WITHOUT TIME ZONE USING starts_at AT TIME ZONE 'utc',
+    ALTER COLUMN starts_at SET DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ALTER COLUMN ends_at TYPE TIMESTAMP WITHOUT TIME ZONE USING ends_at AT TIME ZONE 'utc';
+
+ALTER TABLE demon_reverifications
+    ALTER COLUMN requested_at TYPE TIMESTAMP WITHOUT TIME ZONE USING requested_at AT TIME ZONE 'utc',
+    ALTER COLUMN requested_at SET DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ALTER COLUMN completed_at TYPE TIMESTAMP WITHOUT TIME ZONE USING completed_at AT TIME ZONE 'utc';
+
+ALTER TABLE player_achievements
+    ALTER COLUMN achieved_at TYPE TIMESTAMP WITHOUT TIME ZONE USING achieved_at AT TIME ZONE 'utc',
+    ALTER COLUMN achieved_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE demon_drafts
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE scheduled_list_updates
+    ALTER COLUMN scheduled_for TYPE TIMESTAMP WITHOUT TIME ZONE USING scheduled_for AT TIME ZONE 'utc',
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ALTER COLUMN applied_at TYPE TIMESTAMP WITHOUT TIME ZONE USING applied_at AT TIME ZONE 'utc';
+
+ALTER TABLE watches
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE record_appeals
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE archived_demons
+    ALTER COLUMN archived_at TYPE TIMESTAMP WITHOUT TIME ZONE USING archived_at AT TIME ZONE 'utc',
+    ALTER COLUMN archived_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE applications
+    ALTER COLUMN created_at TYPE TIMESTAMP WITHOUT TIME ZONE USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE player_avatars
+    ALTER COLUMN uploaded_at TYPE TIMESTAMP WITHOUT TIME ZONE USING uploaded_at AT TIME ZONE 'utc',
+    ALTER COLUMN uploaded_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE record_video_checks
+    ALTER COLUMN checked_at TYPE TIMESTAMP WITHOUT TIME ZONE USING checked_at AT TIME ZONE 'utc',
+    ALTER COLUMN checked_at SET DEFAULT (NOW() AT TIME ZONE 'utc'),
+    ALTER COLUMN dead_since TYPE TIMESTAMP WITHOUT TIME ZONE USING dead_since AT TIME ZONE 'utc';
+
+ALTER TABLE record_assignments
+    ALTER COLUMN assigned_at TYPE TIMESTAMP WITHOUT TIME ZONE USING assigned_at AT TIME ZONE 'utc',
+    ALTER COLUMN assigned_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
+
+ALTER TABLE assignment_opt_outs
+    ALTER COLUMN opted_out_at TYPE TIMESTAMP WITHOUT TIME ZONE USING opted_out_at AT TIME ZONE 'utc',
+    ALTER COLUMN opted_out_at SET DEFAULT (NOW() AT TIME ZONE 'utc');
@@ -0,0 +1 @@
+DROP INDEX demons_name_trgm;
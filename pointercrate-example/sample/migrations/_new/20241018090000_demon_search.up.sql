@@ -0,0 +1,5 @@
+-- Trigram index for searching demons by name while typing. GiST (rather than GIN) so that results
+-- can be ordered by similarity straight from the index.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX demons_name_trgm ON demons USING GIST ((name::TEXT) gist_trgm_ops);
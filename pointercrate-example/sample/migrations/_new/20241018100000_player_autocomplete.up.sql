@@ -0,0 +1,3 @@
+-- Trigram index answering the case-insensitive prefix matches of player name autocompletion.
+-- Unlike a btree index, it is also usable when the pattern is a query parameter.
+CREATE INDEX players_name_trgm ON players USING GIN ((name::TEXT) gin_trgm_ops);
@@ -0,0 +1 @@
+ALTER TABLE player_avatars DROP COLUMN approved_revision;
@@ -0,0 +1,5 @@
+-- The revision that was last approved by a moderator, which is the one served publicly. Uploading
+-- a new avatar does not touch it, so the previous avatar stays visible until the new one is approved.
+ALTER TABLE player_avatars ADD COLUMN approved_revision INTEGER NULL;
+
+UPDATE player_avatars SET approved_revision = revision WHERE approved;
@@ -0,0 +1,6 @@
+DROP TRIGGER submitters_public_id ON submitters;
+DROP FUNCTION set_anonymous_submitter();
+DROP FUNCTION anonymize_submitter(INTEGER);
+DROP TABLE anonymization_key;
+
+ALTER TABLE submitters ALTER COLUMN public_id SET DEFAULT substr(md5(random()::text || clock_timestamp()::text), 1, 16);
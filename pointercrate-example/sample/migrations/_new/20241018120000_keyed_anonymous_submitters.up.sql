@@ -0,0 +1,33 @@
+-- Derive the anonymized submitter identifiers from a keyed hash (HMAC) of the submitter ID instead of
+-- random values. The key is generated once and never leaves the database, so without it the
+-- identifiers cannot be recomputed by hashing candidate submitter IDs.
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+CREATE TABLE anonymization_key (
+    key BYTEA NOT NULL
+);
+
+INSERT INTO anonymization_key (key) VALUES (gen_random_bytes(32));
+
+CREATE FUNCTION anonymize_submitter(INTEGER) RETURNS TEXT AS $$
+    SELECT substr(encode(hmac($1::TEXT::BYTEA, key, 'sha256'), 'hex'), 1, 16) FROM anonymization_key
+$$ LANGUAGE SQL STABLE;
+
+-- Identifiers that are already set (for example when restoring a backup) are kept, so that they stay
+-- stable even if the key changes
+CREATE FUNCTION set_anonymous_submitter() RETURNS trigger AS $$
+BEGIN
+    IF NEW.public_id IS NULL THEN
+        NEW.public_id = anonymize_submitter(NEW.submitter_id);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE submitters ALTER COLUMN public_id DROP DEFAULT;
+
+CREATE TRIGGER submitters_public_id BEFORE INSERT ON submitters
+    FOR EACH ROW EXECUTE PROCEDURE set_anonymous_submitter();
+
+UPDATE submitters SET public_id = anonymize_submitter(submitter_id);
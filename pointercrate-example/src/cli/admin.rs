@@ -0,0 +1,71 @@
+//! Administrative commands operating directly on the database, for recovery when the web interface
+//! cannot be used (for example because no administrator account can log in anymore).
+//!
+//! All changes are attributed to the system user (id 0) in the audit logs.
+
+use chrono::NaiveDate;
+use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
+use pointercrate_demonlist::record::FullRecord;
+use pointercrate_user::{auth::AuthenticatedUser, User};
+use std::error::Error;
+
+pub const USAGE: &str = "    pointercrate admin user promote <name> --perm <permission>
+    pointercrate admin user reset-password <name>
+    pointercrate admin record purge-rejected --before <YYYY-MM-DD>";
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+fn permissions_manager() -> PermissionsManager {
+    let mut permissions_manager = pointercrate_user::default_permissions_manager();
+    permissions_manager.merge_with(pointercrate_demonlist::default_permissions_manager());
+    permissions_manager
+}
+
+pub async fn run(args: &[&str], pool: &PointercratePool) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    match args {
+        ["user", "promote", name, "--perm", permission] => {
+            // Allow both "List Administrator" and "ListAdministrator"
+            let normalized = permission.replace(' ', "").to_lowercase();
+            let permission = permissions_manager()
+                .bits_to_permissions(u16::MAX)
+                .into_iter()
+                .find(|perm| perm.name().replace(' ', "").to_lowercase() == normalized)
+                .ok_or_else(|| format!("Unknown permission '{}'", permission))?;
+
+            let mut user = User::by_name(name, &mut transaction).await?;
+            let permissions = user.permissions | permission.bit();
+
+            user.set_permissions(permissions, &mut transaction).await?;
+
+            println!("Granted {} to {}", permission.name(), user);
+        },
+        ["user", "reset-password", name] => {
+            let mut user = AuthenticatedUser::without_authentication(name, &mut transaction).await?;
+
+            println!("Enter the new password for {}:", user.user());
+
+            let mut password = String::new();
+            std::io::stdin().read_line(&mut password)?;
+
+            // Changing the password also invalidates all access tokens
+            user.set_password(password.trim_end_matches(['\r', '\n']).to_string(), &mut transaction)
+                .await?;
+
+            println!("Password of {} reset", user.user());
+        },
+        ["record", "purge-rejected", "--before", date] => {
+            let before = NaiveDate::parse_from_str(date, "%Y-%m-%d")?.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+            let purged = FullRecord::purge_rejected(before, &mut transaction).await?;
+
+            println!("Purged {} rejected records", purged);
+        },
+        _ => return Err(format!("Unknown admin command. Usage:\n{}", USAGE).into()),
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
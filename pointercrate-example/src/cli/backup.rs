@@ -0,0 +1,159 @@
+//! Logical backups of a pointercrate database
+//!
+//! A backup is a single JSON document containing the rows of every table holding list or account
+//! data, taken inside a single `REPEATABLE READ` transaction so that it is consistent. Audit logs
+//! are not part of backups.
+
+use chrono::{DateTime, Utc};
+use pointercrate_core::{error::CoreError, pool::audit_connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, Pool, Postgres, Row};
+use std::collections::BTreeMap;
+
+/// Version of the backup format. Needs to be incremented whenever a schema change affects one of the
+/// tables in [`TABLES`], and [`restore`] refuses to load backups of a different version.
+pub const FORMAT_VERSION: u32 = 22;
+
+/// The backed up tables together with their serial column (if any), in an order that satisfies
+/// foreign key constraints when restoring
+const TABLES: &[(&str, Option<&str>)] = &[
+    ("members", Some("member_id")),
+    ("display_name_history", Some("id")),
+    ("players", Some("id")),
+    ("player_aliases", None),
+    ("submitters", Some("submitter_id")),
+    ("demons", Some("id")),
+    ("creators", None),
+    ("demon_credits", None),
+    ("records", Some("id")),
+    ("record_notes", Some("id")),
+    ("archived_demons", None),
+    ("archived_records", None),
+    ("player_claims", Some("id")),
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+
+    /// Whether the `password_hash` column of `members` was preserved. If not, all accounts in the
+    /// backup have an empty password hash, meaning password login is impossible until an
+    /// administrator sets a new password
+    pub includes_password_hashes: bool,
+
+    pub tables: BTreeMap<String, Value>,
+}
+
+async fn dump_table(table: &str, connection: &mut PgConnection) -> Result<Value, sqlx::Error> {
+    let json: String = sqlx::query(&format!("SELECT COALESCE(json_agg(t), '[]'::json)::text FROM {} t", table))
+        .fetch_one(connection)
+        .await?
+        .get(0);
+
+    Ok(serde_json::from_str(&json).expect("postgres generated invalid json"))
+}
+
+pub async fn backup(pool: &Pool<Postgres>, include_password_hashes: bool) -> Result<Backup, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *transaction)
+        .await?;
+
+    let mut tables = BTreeMap::new();
+
+    for (table, _) in TABLES {
+        let mut rows = dump_table(table, &mut *transaction).await?;
+
+        if *table == "members" && !include_password_hashes {
+            if let Value::Array(ref mut members) = rows {
+                for member in members {
+                    member["password_hash"] = Value::String(String::new());
+                }
+            }
+        }
+
+        tables.insert(table.to_string(), rows);
+    }
+
+    transaction.commit().await?;
+
+    Ok(Backup {
+        version: FORMAT_VERSION,
+        created_at: Utc::now(),
+        includes_password_hashes: include_password_hashes,
+        tables,
+    })
+}
+
+#[derive(Debug)]
+pub enum RestoreError {
+    Database(CoreError),
+    UnsupportedVersion(u32),
+    MissingTable(&'static str),
+
+    /// Backups can only be restored into an empty database
+    NotEmpty(&'static str),
+}
+
+impl From<CoreError> for RestoreError {
+    fn from(err: CoreError) -> Self {
+        RestoreError::Database(err)
+    }
+}
+
+impl From<sqlx::Error> for RestoreError {
+    fn from(err: sqlx::Error) -> Self {
+        RestoreError::Database(err.into())
+    }
+}
+
+/// Restores the given backup into an empty database.
+///
+/// Everything happens inside a single transaction, so a failed restore leaves the database
+/// untouched. The restoration is attributed to the system user (id 0) in the audit logs.
+pub async fn restore(pool: &Pool<Postgres>, backup: Backup) -> Result<(), RestoreError> {
+    if backup.version != FORMAT_VERSION {
+        return Err(RestoreError::UnsupportedVersion(backup.version));
+    }
+
+    let mut transaction = pool.begin().await?;
+
+    audit_connection(&mut *transaction, 0).await?;
+
+    for (table, serial_column) in TABLES {
+        let rows = backup.tables.get(*table).ok_or(RestoreError::MissingTable(*table))?;
+
+        let non_empty: bool = sqlx::query(&format!("SELECT EXISTS (SELECT 1 FROM {})", table))
+            .fetch_one(&mut *transaction)
+            .await?
+            .get(0);
+
+        if non_empty {
+            return Err(RestoreError::NotEmpty(*table));
+        }
+
+        sqlx::query(&format!(
+            "INSERT INTO {0} SELECT * FROM json_populate_recordset(NULL::{0}, $1::json)",
+            table
+        ))
+        .bind(rows.to_string())
+        .execute(&mut *transaction)
+        .await?;
+
+        if let Some(column) = serial_column {
+            sqlx::query(&format!(
+                "SELECT setval(pg_get_serial_sequence('{0}', '{1}'), COALESCE(MAX({1}), 1)) FROM {0}",
+                table, column
+            ))
+            .execute(&mut *transaction)
+            .await?;
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Command line tool for administrative tasks on a pointercrate instance
+//!
+//! Reads the same configuration file, `.env` file and environment variables as the server. Usage:
+//!
+//! ```text
+//! pointercrate migrate
+//! pointercrate seed
+//! pointercrate backup <file> [--include-password-hashes]
+//! pointercrate restore <file>
+//! pointercrate admin user promote <name> --perm <permission>
+//! pointercrate admin user reset-password <name>
+//! pointercrate admin record purge-rejected --before <YYYY-MM-DD>
+//! ```
+
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_demonlist::seed::{self, SeedConfig};
+use std::{fs::File, process::ExitCode};
+
+mod admin;
+mod backup;
+
+const USAGE: &str = "Usage:
+    pointercrate migrate
+    pointercrate seed
+    pointercrate backup <file> [--include-password-hashes]
+    pointercrate restore <file>";
+
+#[rocket::main]
+async fn main() -> ExitCode {
+    dotenv::dotenv().ok();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    if let Err(err) = pointercrate_core::config::init() {
+        eprintln!("{}", err);
+        return ExitCode::FAILURE;
+    }
+
+    let pool = PointercratePool::init().await;
+
+    match args.as_slice() {
+        ["migrate"] => {
+            pool.run_migrations().await;
+
+            return ExitCode::SUCCESS;
+        },
+        ["seed"] => {
+            let mut transaction = pool.begin().await.expect("Failed to start transaction");
+
+            if let Err(err) = seed::seed(&SeedConfig::default(), &mut *transaction).await {
+                eprintln!("Failed to seed database: {}", err);
+                return ExitCode::FAILURE;
+            }
+
+            transaction.commit().await.expect("Failed to commit seeded data");
+
+            println!(
+                "Seeding complete. Log in as 'admin', 'moderator' or 'helper' with password '{}'",
+                seed::STAFF_PASSWORD
+            );
+
+            return ExitCode::SUCCESS;
+        },
+        ["admin", command @ ..] => {
+            if let Err(err) = admin::run(command, &pool).await {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+
+            return ExitCode::SUCCESS;
+        },
+        _ => (),
+    }
+
+    let pool = pool.clone_inner();
+
+    match args.as_slice() {
+        ["backup", file, flags @ ..] => {
+            let include_password_hashes = flags.contains(&"--include-password-hashes");
+
+            let backup = match backup::backup(&pool, include_password_hashes).await {
+                Ok(backup) => backup,
+                Err(err) => {
+                    eprintln!("Failed to create backup: {:?}", err);
+                    return ExitCode::FAILURE;
+                },
+            };
+
+            let file = File::create(file).expect("Failed to create backup file");
+            serde_json::to_writer(file, &backup).expect("Failed to write backup");
+
+            println!("Backup complete");
+        },
+        ["restore", file] => {
+            let file = File::open(file).expect("Failed to open backup file");
+            let backup = match serde_json::from_reader(file) {
+                Ok(backup) => backup,
+                Err(err) => {
+                    eprintln!("Malformed backup file: {}", err);
+                    return ExitCode::FAILURE;
+                },
+            };
+
+            if let Err(err) = backup::restore(&pool, backup).await {
+                eprintln!("Failed to restore backup: {:?}", err);
+                return ExitCode::FAILURE;
+            }
+
+            println!("Restore complete");
+        },
+        _ => {
+            eprintln!("{}\n{}", USAGE, admin::USAGE);
+            return ExitCode::FAILURE;
+        },
+    }
+
+    ExitCode::SUCCESS
+}
@@ -1,11 +1,20 @@
 use dotenv::dotenv;
 use maud::html;
-use pointercrate_core::error::CoreError;
 use pointercrate_core::pool::PointercratePool;
-use pointercrate_core_api::{error::ErrorResponder, maintenance::MaintenanceFairing};
+use pointercrate_core::{config::ThemeConfig, error::CoreError};
+use pointercrate_core_api::{
+    cache::ResponseCacheFairing,
+    documentation::DocumentationFairing,
+    error::ErrorResponder,
+    mail::MailerHandle,
+    maintenance::MaintenanceFairing,
+    normalize::UriNormalizationFairing,
+    upload::{body_limits, StorageHandle},
+};
 use pointercrate_core_pages::{
     footer::{Footer, FooterColumn, Link},
     navigation::{NavigationBar, TopLevelNavigationBarItem},
+    template::{self, Templates},
     PageConfiguration,
 };
 use pointercrate_demonlist::LIST_ADMINISTRATOR;
@@ -14,13 +23,18 @@ use pointercrate_demonlist_pages::account::{
 };
 use pointercrate_user::MODERATOR;
 use pointercrate_user_pages::account::{profile::ProfileTab, users::UsersTab, AccountPageConfig};
-use rocket::{build, catch, fs::FileServer, get, response::Redirect, uri, Rocket};
+use rocket::{catch, custom, fs::FileServer, get, response::Redirect, uri, Rocket};
 
 #[catch(404)]
 fn catch_404() -> ErrorResponder {
     CoreError::NotFound.into()
 }
 
+#[rocket::catch(413)]
+fn catch_413() -> ErrorResponder {
+    CoreError::PayloadTooLarge.into()
+}
+
 #[rocket::catch(422)]
 fn catch_422() -> ErrorResponder {
     CoreError::UnprocessableEntity.into()
@@ -34,13 +48,23 @@ fn home() -> Redirect {
 async fn configure_rocket() -> Result<Rocket<rocket::Build>, Box<dyn std::error::Error>> {
     dotenv::dotenv().unwrap();
 
+    let config = pointercrate_core::config::init()?;
     let pool = PointercratePool::init().await;
 
-    let rocket = build()
+    template::init(
+        Templates::new()
+            .directory("demonlist", &config.storage.templates_directory)
+            .directory("user", &config.storage.user_templates_directory),
+    )?;
+
+    let rocket = custom(rocket::Config::figment().merge(("limits", body_limits())))
+        .manage(config.clone())
         .manage(pool)
-        .manage(page_configuration())
-        .register("/", rocket::catchers![catch_404, catch_422])
-        .mount("/", rocket::routes![home]);
+        .manage(MailerHandle::from_config())
+        .manage(StorageHandle::from_config())
+        .manage(page_configuration(&config.theme))
+        .register("/", rocket::catchers![catch_404, catch_413, catch_422])
+        .mount("/", rocket::routes![home, pointercrate_core_api::readiness::ready]);
 
     let mut permissions_manager = pointercrate_user::default_permissions_manager();
     permissions_manager.merge_with(pointercrate_demonlist::default_permissions_manager());
@@ -56,9 +80,13 @@ async fn configure_rocket() -> Result<Rocket<rocket::Build>, Box<dyn std::error:
         .with_page(RecordsPage);
 
     let rocket = rocket.manage(account_page_config);
-    let rocket = rocket.attach(MaintenanceFairing::new(false));
-    let rocket = pointercrate_demonlist_api::setup(rocket);
-    let rocket = pointercrate_user_api::setup(rocket);
+    let rocket = rocket
+        .attach(UriNormalizationFairing)
+        .attach(MaintenanceFairing::new(false))
+        .attach(ResponseCacheFairing::new(1000))
+        .attach(DocumentationFairing);
+    let rocket = pointercrate_demonlist_api::setup(rocket).attach(pointercrate_demonlist_api::scheduler());
+    let rocket = pointercrate_user_api::setup(rocket).attach(pointercrate_user_api::digests());
 
     Ok(rocket
         .mount("/static/core", FileServer::from("pointercrate-core-pages/static"))
@@ -66,8 +94,8 @@ async fn configure_rocket() -> Result<Rocket<rocket::Build>, Box<dyn std::error:
         .mount("/static/user", FileServer::from("pointercrate-user-pages/static")))
 }
 
-fn page_configuration() -> PageConfiguration {
-    let nav_bar = NavigationBar::new("/static/core/thecscl.png")
+fn page_configuration(theme: &ThemeConfig) -> PageConfiguration {
+    let nav_bar = NavigationBar::new(&theme.logo)
         .with_item(
             TopLevelNavigationBarItem::new(
                 "/list/",
@@ -99,19 +127,17 @@ fn page_configuration() -> PageConfiguration {
         ));
 
     let footer = Footer::new(html! {
-        "The Clicksync Challenge list and Pointercrate are in no way affiliated with RobTopGamesAB ® or eachother."
+        "The " (theme.list_name) " and Pointercrate are in no way affiliated with RobTopGamesAB ® or eachother."
     })
     .with_column(FooterColumn::LinkList {
-        heading: "The Clicksync Challenge list v1.6.3",
-        links: vec![
-            Link::new("/list/1/", "Top 1 Challenge"),
-            Link::new("/list/statsviewer/", "Stats Viewer"),
-            Link::new("/account/", "User Area"),
-        ],
+        heading: format!("The {} v1.6.3", theme.list_name),
+        links: theme.footer_links.iter().map(|link| Link::new(&link.href, &link.text)).collect(),
     })
     .with_link("https://twitter.com/stadust1971", "Site Dev");
 
-    PageConfiguration::new("Clicksync Challenge List", nav_bar, footer).author("sphericle")
+    PageConfiguration::new(&theme.list_name, nav_bar, footer)
+        .author("sphericle")
+        .theme(theme)
 }
 
 #[shuttle_runtime::main]
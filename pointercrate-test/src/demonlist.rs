@@ -1,32 +1,72 @@
 use crate::{TestClient, TestRequest};
 use pointercrate_core::etag::Taggable;
 use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
+use pointercrate_core_api::{
+    cache::ResponseCacheFairing,
+    documentation::DocumentationFairing,
+    mail::{LogMailer, MailerHandle},
+    normalize::UriNormalizationFairing,
+    upload::{body_limits, LocalStorage, StorageHandle},
+};
 use pointercrate_demonlist::demon::FullDemon;
 use pointercrate_demonlist::{
     player::{claim::PlayerClaim, FullPlayer},
     record::RecordStatus,
+    seed::SeedConfig,
     submitter::Submitter,
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
-use pointercrate_user::auth::AuthenticatedUser;
+use pointercrate_user::{auth::AuthenticatedUser, MODERATOR};
 use pointercrate_user_pages::account::AccountPageConfig;
 use rocket::{http::Status, local::asynchronous::Client};
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
 use std::{net::IpAddr, str::FromStr};
 
 pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup(pool, false, false).await
+}
+
+/// Like [`setup_rocket`], but with the response cache enabled
+pub async fn setup_cached_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup(pool, true, false).await
+}
+
+/// Like [`setup_cached_rocket`], but with request URIs normalized before they reach the response
+/// cache, the same way as in production
+pub async fn setup_normalized_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup(pool, true, true).await
+}
+
+async fn setup(pool: Pool<Postgres>, response_cache: bool, normalize_uris: bool) -> (TestClient, PoolConnection<Postgres>) {
     let _ = dotenv::dotenv();
 
     let mut connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR])
+    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR, MODERATOR])
         .assigns(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_MODERATOR, LIST_HELPER);
 
-    let rocket = pointercrate_demonlist_api::setup(rocket::build().manage(PointercratePool::from(pool)))
+    let base = rocket::custom(rocket::Config::figment().merge(("limits", body_limits())));
+
+    let mut rocket = pointercrate_demonlist_api::setup(base.manage(PointercratePool::from(pool)))
         .manage(permissions)
-        .manage(AccountPageConfig::default());
+        .manage(MailerHandle::new(LogMailer))
+        .manage(StorageHandle::new(LocalStorage::new(
+            std::env::temp_dir().join("pointercrate-test-uploads"),
+        )))
+        .manage(pointercrate_core::config::get().clone())
+        .manage(AccountPageConfig::default())
+        .attach(DocumentationFairing);
+
+    // Needs to run before the response cache computes its cache key
+    if normalize_uris {
+        rocket = rocket.attach(UriNormalizationFairing);
+    }
+
+    if response_cache {
+        rocket = rocket.attach(ResponseCacheFairing::new(100));
+    }
 
     // generate some data
     Submitter::create_submitter(IpAddr::from_str("127.0.0.1").unwrap(), &mut *connection)
@@ -36,6 +76,27 @@ pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<P
     (TestClient::new(Client::tracked(rocket).await.unwrap()), connection)
 }
 
+/// Like [`setup_rocket`], but with the database already populated via [`seed`]
+pub async fn setup_seeded_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    let (client, mut connection) = setup_rocket(pool).await;
+
+    seed(&mut connection).await;
+
+    (client, connection)
+}
+
+/// Populates the database with a small, but realistic list (see [`pointercrate_demonlist::seed`])
+pub async fn seed(connection: &mut PgConnection) {
+    let config = SeedConfig {
+        demons: 20,
+        players: 50,
+        records: 200,
+        ..Default::default()
+    };
+
+    pointercrate_demonlist::seed::seed(&config, connection).await.unwrap()
+}
+
 pub async fn add_demon(
     name: impl Into<String>, position: i16, requirement: i16, verifier_id: i32, publisher_id: i32, connection: &mut PgConnection,
 ) -> i32 {
@@ -85,7 +146,7 @@ pub async fn add_simple_record(progress: i16, player: i32, demon: i32, status: R
         progress,
         status.to_sql(),
         player,
-        system_sub.id,
+        system_sub.id.0,
         demon
     )
     .fetch_one(&mut *connection)
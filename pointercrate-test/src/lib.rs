@@ -1,9 +1,13 @@
 //! Utilities for pointercrate integration tests
+//!
+//! Tests are meant to be run via `#[sqlx::test(migrations = "../migrations")]`, which gives every
+//! test its own, freshly migrated database. The `setup_rocket` functions of the submodules then
+//! mount the relevant endpoints on top of that database and return a [`TestClient`] for them.
 
 use pointercrate_user::auth::AuthenticatedUser;
 
 use rocket::{
-    http::{Header, Status},
+    http::{ContentType, Header, Status},
     local::asynchronous::{Client, LocalRequest, LocalResponse},
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -28,10 +32,20 @@ impl TestClient {
         TestRequest::new(self.0.put(url.into()))
     }
 
+    /// Like [`TestClient::put`], but with a raw body of the given content type
+    pub fn put_raw(&self, url: impl Into<String>, content_type: ContentType, body: impl Into<String>) -> TestRequest {
+        TestRequest::new(self.0.put(url.into()).header(content_type).body(body.into()))
+    }
+
     pub fn post(&self, url: impl Into<String>, body: &impl Serialize) -> TestRequest {
         TestRequest::new(self.0.post(url.into()).json(body))
     }
 
+    /// Like [`TestClient::post`], but with a raw (non-JSON) body of the given content type
+    pub fn post_raw(&self, url: impl Into<String>, content_type: ContentType, body: impl Into<String>) -> TestRequest {
+        TestRequest::new(self.0.post(url.into()).header(content_type).body(body.into()))
+    }
+
     pub fn patch(&self, url: impl Into<String>, body: &impl Serialize) -> TestRequest {
         TestRequest::new(self.0.patch(url.into()).json(body))
     }
@@ -41,6 +55,39 @@ impl TestClient {
     }
 }
 
+/// The relations contained in the `Links` header of a pagination response
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PaginationLinks(HashMap<String, String>);
+
+impl PaginationLinks {
+    /// Parses a header of the form `<url>; rel=name,<url>; rel=name,...`
+    pub fn parse(header: &str) -> Self {
+        PaginationLinks(
+            header
+                .split(',')
+                .filter(|link| !link.is_empty())
+                .map(|link| {
+                    let (url, rel) = link.split_once("; rel=").expect("malformed link");
+
+                    (rel.to_string(), url.trim_start_matches('<').trim_end_matches('>').to_string())
+                })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, rel: &str) -> Option<&str> {
+        self.0.get(rel).map(String::as_str)
+    }
+
+    pub fn next(&self) -> Option<&str> {
+        self.get("next")
+    }
+
+    pub fn prev(&self) -> Option<&str> {
+        self.get("prev")
+    }
+}
+
 pub struct TestRequest<'c> {
     request: LocalRequest<'c>,
     expected_status: Status,
@@ -114,6 +161,30 @@ impl<'c> TestRequest<'c> {
         (deserialized.unwrap(), links_header)
     }
 
+    /// Like [`TestRequest::get_pagination_result`], but with the `Links` header already parsed
+    pub async fn get_paginated<Result: DeserializeOwned + Debug>(self) -> (Vec<Result>, PaginationLinks) {
+        let (objects, links) = self.get_pagination_result().await;
+
+        (objects, PaginationLinks::parse(&links))
+    }
+
+    /// Asserts that this request fails with the given pointercrate error code (and the status code
+    /// derived from it), returning the error's `data` object
+    pub async fn expect_error(mut self, code: u16) -> serde_json::Value {
+        self.expected_status = Status::from_code(code / 100).expect("invalid error code");
+
+        let mut json: serde_json::Value = self.get_result().await;
+
+        assert_eq!(json["code"], code, "{:?}", json);
+
+        json["data"].take()
+    }
+
+    /// Dispatches this request without checking the response's status or headers
+    pub async fn dispatch(self) -> LocalResponse<'c> {
+        self.request.dispatch().await
+    }
+
     pub async fn execute(self) -> LocalResponse<'c> {
         let response = self.request.dispatch().await;
 
@@ -3,42 +3,67 @@ use pointercrate_core::{
     permission::{Permission, PermissionsManager},
     pool::PointercratePool,
 };
-use pointercrate_user::{auth::legacy::Registration, auth::AuthenticatedUser, ADMINISTRATOR, MODERATOR};
+use pointercrate_core_api::{
+    documentation::DocumentationFairing,
+    mail::{LogMailer, MailerHandle},
+};
+use pointercrate_user::{auth::legacy::Registration, auth::AuthenticatedUser};
 use pointercrate_user_pages::account::AccountPageConfig;
 use rocket::local::asynchronous::Client;
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
 
 pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup_rocket_with_permissions(pool, pointercrate_user::default_permissions_manager()).await
+}
+
+/// Like [`setup_rocket`], but with the given permissions instead of only those of the user crate
+pub async fn setup_rocket_with_permissions(
+    pool: Pool<Postgres>, permissions: PermissionsManager,
+) -> (TestClient, PoolConnection<Postgres>) {
     let _ = dotenv::dotenv();
 
     let connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![MODERATOR, ADMINISTRATOR])
-        .assigns(ADMINISTRATOR, MODERATOR)
-        .implies(ADMINISTRATOR, MODERATOR);
-
     let rocket = pointercrate_user_api::setup(rocket::build())
         .manage(PointercratePool::from(pool))
         .manage(permissions)
-        .manage(AccountPageConfig::default());
+        .manage(MailerHandle::new(LogMailer))
+        .manage(pointercrate_core::config::get().clone())
+        .manage(AccountPageConfig::default())
+        .attach(DocumentationFairing);
 
     (TestClient::new(Client::tracked(rocket).await.unwrap()), connection)
 }
 
-pub async fn system_user_with_perms(perm: Permission, connection: &mut PgConnection) -> AuthenticatedUser {
-    let user = AuthenticatedUser::register(
+/// Registers a new user. The first one is called "Patrick", later ones get a number appended, so
+/// that tests can register as many users as they need
+async fn register_user(connection: &mut PgConnection) -> AuthenticatedUser {
+    let existing = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM members"#)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .count;
+
+    AuthenticatedUser::register(
         Registration {
-            name: "Patrick".to_string(),
+            name: match existing {
+                0 => "Patrick".to_string(),
+                n => format!("Patrick{}", n + 1),
+            },
             password: "bad password".to_string(),
         },
-        &mut *connection,
+        connection,
     )
     .await
-    .unwrap();
+    .unwrap()
+}
+
+pub async fn system_user_with_perms(perm: Permission, connection: &mut PgConnection) -> AuthenticatedUser {
+    let user = register_user(&mut *connection).await;
 
     sqlx::query!(
         "UPDATE members SET permissions = $2::INTEGER::BIT(16) WHERE member_id = $1",
-        user.user().id,
+        user.user().id.0,
         perm.bit() as i16
     )
     .execute(connection)
@@ -49,13 +74,5 @@ pub async fn system_user_with_perms(perm: Permission, connection: &mut PgConnect
 }
 
 pub async fn add_normal_user(connection: &mut PgConnection) -> AuthenticatedUser {
-    AuthenticatedUser::register(
-        Registration {
-            name: "Patrick".to_string(),
-            password: "bad password".to_string(),
-        },
-        connection,
-    )
-    .await
-    .unwrap()
+    register_user(connection).await
 }
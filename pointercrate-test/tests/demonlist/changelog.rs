@@ -0,0 +1,30 @@
+use pointercrate_demonlist::player::DatabasePlayer;
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_changelog(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Tartarus", 2, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let changelog: serde_json::Value = clnt.get("/api/v1/list/changelog/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(changelog["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(changelog["entries"][0]["type"], "added");
+    assert_eq!(changelog["digest"][0], "Bloodbath was added at #1");
+
+    let changelog: serde_json::Value = clnt
+        .get("/api/v1/list/changelog/?week=2017-W01")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(changelog["week"], "2017-W01");
+    assert!(changelog["entries"].as_array().unwrap().is_empty());
+
+    clnt.get("/api/v1/list/changelog/?week=2017-01-02").expect_error(42267).await;
+}
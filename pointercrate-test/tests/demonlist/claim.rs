@@ -1,5 +1,7 @@
 use pointercrate_demonlist::player::{claim::PlayerClaim, DatabasePlayer};
+use pointercrate_user::{application::Application, auth::AuthenticatedUser};
 use rocket::http::Status;
+use serde_json::json;
 use sqlx::{Pool, Postgres};
 
 #[sqlx::test(migrations = "../migrations")]
@@ -26,10 +28,42 @@ async fn test_put_claim(pool: Pool<Postgres>) {
     assert_eq!(
         json,
         PlayerClaim {
-            user_id: user.user().id,
-            player_id,
+            user_id: user.user().id.0,
+            player_id: player_id.0,
             verified: false,
             lock_submissions: false
         }
     );
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_applications_cannot_claim_players(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    let credentials = Application::create(
+        serde_json::from_value(json!({"name": "Stat site"})).unwrap(),
+        user.user().id.0,
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+    let application =
+        AuthenticatedUser::client_credentials(&credentials.application.client_id, &credentials.client_secret, &mut *connection)
+            .await
+            .unwrap();
+
+    let player_id = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection)
+        .await
+        .unwrap()
+        .id;
+
+    client
+        .put(format!("/api/v1/players/{}/claims/", player_id))
+        .authorize_as(&application)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    assert!(PlayerClaim::get(user.user().id.0, player_id.0, &mut *connection).await.is_err());
+}
@@ -1,9 +1,10 @@
-use pointercrate_core::pagination::PaginationParameters;
+use pointercrate_core::{etag::Taggable, pagination::PaginationParameters};
 use pointercrate_core_api::pagination::LinksBuilder;
 use pointercrate_demonlist::{
-    demon::{Demon, DemonPositionPagination},
-    player::DatabasePlayer,
-    LIST_MODERATOR,
+    demon::{Demon, DemonId, DemonPositionPagination, DemonTier, FullDemon},
+    player::{DatabasePlayer, FullPlayer},
+    record::{FullRecord, RecordStatus, APPROVED_RECORDS_PER_PAGE},
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use rocket::http::Status;
 use sqlx::{Pool, Postgres};
@@ -125,16 +126,16 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     assert_eq!(links, LinksBuilder::new(URL).generate(&DemonPositionPagination::default()).unwrap());
 
     // Let's add some data to the database and do actual tests!
-    let id1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut *connection).await;
-    let id2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, player.id, player.id, &mut *connection).await;
-    let id3 = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 100, player.id, player.id, &mut *connection).await;
+    let id1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id.0, player.id.0, &mut *connection).await;
+    let id2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, player.id.0, player.id.0, &mut *connection).await;
+    let id3 = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 100, player.id.0, player.id.0, &mut *connection).await;
 
     // Test only the limit parameter in isolation. Off-by-one errors in the limit are hard to catch due to how the "next" parameter
     // is computed internally, so make sure that if limit is ignored, at least 2 more elements would be returned
     // Regression test for 148330e8f21912345e4f6f627942b48e4a7ec75a
     let base = DemonPositionPagination {
         params: PaginationParameters {
-            limit: 1,
+            limit: Some(1),
             ..Default::default()
         },
         ..Default::default()
@@ -145,14 +146,14 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
         .await;
 
     assert_eq!(demons.len(), 1);
-    assert_eq!(demons[0].base.id, id1);
+    assert_eq!(demons[0].base.id, DemonId(id1));
 
     // Normal pagination: Get the demon at position 2 via after=1 and limit=1. We should get both "next" and "previous" pages
     // for the first and third demons! Let's throw in a requirement=100 as well, to test that these parameters do indeed get propagated into the Links headers
     let base = DemonPositionPagination {
         requirement: Some(100),
         params: PaginationParameters {
-            limit: 1,
+            limit: Some(1),
             after: Some(1),
             ..Default::default()
         },
@@ -164,7 +165,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
         .await;
 
     assert_eq!(demons.len(), 1);
-    assert_eq!(demons[0].base.id, id2);
+    assert_eq!(demons[0].base.id, DemonId(id2));
 
     let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_next(2).with_previous(2);
     assert_eq!(links, expected.generate(&base).unwrap());
@@ -174,7 +175,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     let base = DemonPositionPagination {
         requirement: Some(100),
         params: PaginationParameters {
-            limit: 1,
+            limit: Some(1),
             before: Some(3),
             ..Default::default()
         },
@@ -186,7 +187,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
         .await;
 
     assert_eq!(demons.len(), 1);
-    assert_eq!(demons[0].base.id, id2);
+    assert_eq!(demons[0].base.id, DemonId(id2));
 
     let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_next(2).with_previous(2);
     assert_eq!(links, expected.generate(&base).unwrap());
@@ -239,9 +240,9 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
         .await;
 
     assert_eq!(demons.len(), 3);
-    assert_eq!(demons[0].base.id, id1);
-    assert_eq!(demons[1].base.id, id2);
-    assert_eq!(demons[2].base.id, id3);
+    assert_eq!(demons[0].base.id, DemonId(id1));
+    assert_eq!(demons[1].base.id, DemonId(id2));
+    assert_eq!(demons[2].base.id, DemonId(id3));
 
     let expected = LinksBuilder::new(URL).with_first(0).with_last(4);
 
@@ -251,7 +252,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     let base = DemonPositionPagination {
         params: PaginationParameters {
             before: Some(4),
-            limit: 2,
+            limit: Some(2),
             ..Default::default()
         },
         ..Default::default()
@@ -262,8 +263,8 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
         .await;
 
     assert_eq!(demons.len(), 2);
-    assert_eq!(demons[0].base.id, id2);
-    assert_eq!(demons[1].base.id, id3);
+    assert_eq!(demons[0].base.id, DemonId(id2));
+    assert_eq!(demons[1].base.id, DemonId(id3));
 
     let expected = LinksBuilder::new(URL)
         .with_first(0)
@@ -273,3 +274,1472 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
 
     assert_eq!(links, expected.generate(&base).unwrap());
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_seeded_list(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    pointercrate_test::demonlist::seed(&mut *connection).await;
+
+    let demons: Vec<Demon> = clnt
+        .get("/api/v2/demons/listed/?limit=100")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(demons.len(), 20);
+    assert!(demons.iter().enumerate().all(|(idx, demon)| demon.base.position == idx as i16 + 1));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_follow_pagination_links(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    let (mut demons, mut links) = clnt.get("/api/v2/demons/?limit=7").get_paginated::<Demon>().await;
+
+    assert_eq!(links.prev(), None);
+
+    while let Some(next) = links.next() {
+        let (page, next_links) = clnt.get(next).get_paginated::<Demon>().await;
+
+        assert!(page.len() <= 7);
+        assert!(next_links.prev().is_some());
+
+        demons.extend(page);
+        links = next_links;
+    }
+
+    assert_eq!(demons.len(), 20);
+    assert!(demons.windows(2).all(|pair| pair[0].base.id < pair[1].base.id));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_demon_requires_list_moderator(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    let data = clnt
+        .patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 50}))
+        .authorize_as(&user)
+        .header("If-Match", demon.etag_string())
+        .expect_error(40301)
+        .await;
+
+    assert_eq!(data["Core"]["required"], "List Moderator");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_close_submissions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon_id),
+            &serde_json::json!({"submissions_open": false, "submissions_closed_reason": "Re-verification in progress"}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(!patched.demon.submissions_open);
+
+    let submission = serde_json::json! {{"progress": 100, "demon": demon_id, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42241));
+    assert_eq!(json["data"]["reason"], "Re-verification in progress");
+
+    // Reopening submissions discards the reason
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon_id),
+            &serde_json::json!({"submissions_open": true}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", patched.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(patched.demon.submissions_open);
+    assert_eq!(patched.demon.submissions_closed_reason, None);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_delete_demon_requires_if_match(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    clnt.delete(format!("/api/v2/demons/{}/", demon_id))
+        .authorize_as(&moderator)
+        .expect_status(Status::PreconditionRequired)
+        .execute()
+        .await;
+
+    clnt.delete(format!("/api/v2/demons/{}/", demon_id))
+        .authorize_as(&moderator)
+        .header("If-Match", "W/\"1234\"")
+        .expect_error(41200)
+        .await;
+
+    clnt.delete(format!("/api/v2/demons/{}/", demon_id))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    clnt.get(format!("/api/v2/demons/{}/", demon_id))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_bumps_version(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    assert_eq!(demon.demon.version, 1);
+
+    let patched: FullDemon = clnt
+        .patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 50}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.demon.version, 2);
+
+    // The old ETag is stale now, even though it was only used once
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 60}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_error(41200)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_helper_sets_discussion_url(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    clnt.patch(
+        format!("/api/v2/demons/{}/", demon_id),
+        &serde_json::json!({"discussion_url": "https://example.com/bloodbath"}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", demon.etag_string())
+    .expect_error(42245)
+    .await;
+
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon_id),
+            &serde_json::json!({"discussion_url": "https://discord.com/channels/1/2"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.demon.discussion_url.as_deref(), Some("https://discord.com/channels/1/2"));
+
+    // Everything else still requires list moderator permissions
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 50}))
+        .authorize_as(&helper)
+        .header("If-Match", patched.etag_string())
+        .expect_error(40301)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_random_demon(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    for _ in 0..10 {
+        let demon: serde_json::Value = clnt
+            .get("/api/v2/demons/random?list=main")
+            .expect_status(Status::Ok)
+            .get_result()
+            .await;
+
+        assert!((1..=20).contains(&demon["position"].as_i64().unwrap()));
+    }
+
+    // The seeded list is not long enough to have an extended list
+    clnt.get("/api/v2/demons/random?list=extended")
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_listed_compact(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    let (demons, links) = clnt
+        .get("/api/v2/demons/listed/compact/?limit=5&after=3")
+        .get_paginated::<serde_json::Value>()
+        .await;
+
+    assert_eq!(demons.len(), 5);
+    assert_eq!(demons[0]["position"], 4);
+    assert!(demons[0].get("publisher").is_some());
+    assert!(demons[0].get("requirement").is_none());
+    assert!(links.prev().is_some());
+    assert!(links.next().is_some());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_search_demons(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 90, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Bloodlust", 2, 60, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 70, player.id.0, player.id.0, &mut *connection).await;
+
+    // Prefixes match even when too short to be similar
+    let results: Vec<serde_json::Value> = clnt.get("/api/v1/demons/search?q=bl").get_result().await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result["name"].as_str().unwrap().starts_with("Blood")));
+    assert_eq!(results[0]["publisher"]["name"], "Riot");
+
+    // Typos are tolerated
+    let results: Vec<serde_json::Value> = clnt.get("/api/v1/demons/search?q=Sonic%20Wav").get_result().await;
+
+    assert_eq!(results[0]["name"], "Sonic Wave");
+    assert_eq!(results[0]["position"], 3);
+
+    let results: Vec<serde_json::Value> = clnt.get("/api/v1/demons/search?q=%20").get_result().await;
+
+    assert!(results.is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_page_content_negotiation(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    // The test client asks for JSON, so the demon page returns the same object as the API
+    let from_page: serde_json::Value = clnt
+        .get("/list/3/")
+        .expect_status(Status::Ok)
+        .expect_header("Vary", "Accept")
+        .get_success_result()
+        .await;
+
+    assert_eq!(from_page["position"], 3);
+
+    let from_api: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", from_page["id"]))
+        .expect_status(Status::Ok)
+        .expect_header("Vary", "Accept")
+        .get_success_result()
+        .await;
+
+    assert_eq!(from_page, from_api);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_position_locked_demon_cannot_be_moved(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Tartarus", 2, 100, player.id.0, player.id.0, &mut *connection).await;
+
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    // Only list administrators can lock positions
+    clnt.patch(
+        format!("/api/v2/demons/{}/", demon_id),
+        &serde_json::json!({"position_locked": true}),
+    )
+    .authorize_as(&moderator)
+    .header("If-Match", demon.etag_string())
+    .expect_error(40301)
+    .await;
+
+    let mut locked = Demon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+    locked.set_position_locked(true, &mut *connection).await.unwrap();
+
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    assert!(demon.demon.position_locked);
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"position": 2}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_error(42248)
+        .await;
+
+    // Other fields can still be edited
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon_id),
+            &serde_json::json!({"position": 1, "requirement": 90}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.position(), 1);
+    assert_eq!(patched.demon.requirement, 90);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_swap_demons(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 2, 100, player.id.0, player.id.0, &mut *connection).await;
+    let slaughterhouse = pointercrate_test::demonlist::add_demon("Slaughterhouse", 3, 60, player.id.0, player.id.0, &mut *connection).await;
+
+    // The If-Match header lists the ETags of both demons
+    let if_match = format!(
+        "{},{}",
+        FullDemon::by_id(DemonId(bloodbath), &mut *connection).await.unwrap().etag_string(),
+        FullDemon::by_id(DemonId(slaughterhouse), &mut *connection)
+            .await
+            .unwrap()
+            .etag_string()
+    );
+
+    clnt.post(
+        format!("/api/v2/demons/{}/swap/{}", bloodbath, slaughterhouse),
+        &serde_json::json!({}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", if_match.clone())
+    .expect_error(40301)
+    .await;
+
+    // Modifying either demon in the meantime invalidates the precondition
+    sqlx::query!("UPDATE demons SET requirement = 88 WHERE id = $1", bloodbath)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    clnt.post(
+        format!("/api/v2/demons/{}/swap/{}", bloodbath, slaughterhouse),
+        &serde_json::json!({}),
+    )
+    .authorize_as(&moderator)
+    .header("If-Match", if_match)
+    .expect_status(Status::PreconditionFailed)
+    .execute()
+    .await;
+
+    let if_match = format!(
+        "{},{}",
+        FullDemon::by_id(DemonId(bloodbath), &mut *connection).await.unwrap().etag_string(),
+        FullDemon::by_id(DemonId(slaughterhouse), &mut *connection)
+            .await
+            .unwrap()
+            .etag_string()
+    );
+
+    let swapped: Vec<Demon> = clnt
+        .post(
+            format!("/api/v2/demons/{}/swap/{}", bloodbath, slaughterhouse),
+            &serde_json::json!({}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", if_match)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(swapped[0].base.position, 3);
+    assert_eq!(swapped[1].base.position, 1);
+
+    // Demons in between stay where they are
+    let demon = Demon::by_id(DemonId(tartarus), &mut *connection).await.unwrap();
+    assert_eq!(demon.base.position, 2);
+
+    let movement_log: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/audit/movement/", bloodbath))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    let last_entry = &movement_log.as_array().unwrap().last().unwrap();
+
+    assert_eq!(last_entry["reason"]["Swapped"]["other"]["id"], slaughterhouse);
+    assert_eq!(last_entry["new_position"], 3);
+
+    let mut locked = Demon::by_id(DemonId(tartarus), &mut *connection).await.unwrap();
+    locked.set_position_locked(true, &mut *connection).await.unwrap();
+
+    let if_match = format!(
+        "{},{}",
+        FullDemon::by_id(DemonId(bloodbath), &mut *connection).await.unwrap().etag_string(),
+        FullDemon::by_id(DemonId(tartarus), &mut *connection).await.unwrap().etag_string()
+    );
+
+    clnt.post(format!("/api/v2/demons/{}/swap/{}", bloodbath, tartarus), &serde_json::json!({}))
+        .authorize_as(&moderator)
+        .header("If-Match", if_match)
+        .expect_error(42248)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_score_weight(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    // Added via the API so that the verification record exists
+    let demon_id = clnt
+        .add_demon(&admin, "Bloodbath", 1, 87, "stardust1971", "stardust1971")
+        .await
+        .demon
+        .base
+        .id;
+
+    let initial_score = player.update_score(&mut *connection).await.unwrap();
+    let demon = FullDemon::by_id(demon_id, &mut *connection).await.unwrap();
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"score_weight": 11.0}))
+        .authorize_as(&admin)
+        .header("If-Match", demon.etag_string())
+        .expect_error(42249)
+        .await;
+
+    let patched: FullDemon = clnt
+        .patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"score_weight": 0.5}))
+        .authorize_as(&admin)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.demon.score_weight, 0.5);
+
+    // The verification is the player's only score-giving record
+    let score = sqlx::query!("SELECT score FROM players WHERE id = $1", player.id.0)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .score;
+
+    assert!((score - initial_score / 2.0).abs() < 1e-6);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_sort_demons_by_name(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    clnt.get("/api/v2/demons/?sort=verifier")
+        .expect_status(Status::BadRequest)
+        .execute()
+        .await;
+
+    let (mut demons, mut links) = clnt.get("/api/v2/demons/?limit=7&sort=-name").get_paginated::<Demon>().await;
+
+    // There is no id that could refer to "before the first demon" in name order
+    assert_eq!(links.get("first"), None);
+
+    while let Some(next) = links.next() {
+        let (page, next_links) = clnt.get(next).get_paginated::<Demon>().await;
+
+        demons.extend(page);
+        links = next_links;
+    }
+
+    assert_eq!(demons.len(), 20);
+    assert!(demons
+        .windows(2)
+        .all(|pair| pair[0].base.name.to_lowercase() >= pair[1].base.name.to_lowercase()));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_pagination_cursors(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    let (_, links) = clnt
+        .get("/api/v2/demons/?limit=5&requirement__gt=50")
+        .get_paginated::<Demon>()
+        .await;
+    let next = links.next().unwrap();
+
+    assert!(next.starts_with("/api/v2/demons/?cursor="));
+
+    // Filters are part of the cursor and cannot be changed
+    clnt.get(format!("{}&requirement__gt=10", next))
+        .expect_status(Status::BadRequest)
+        .execute()
+        .await;
+
+    // Cursors are bound to the endpoint they were generated for
+    clnt.get(next.replace("/api/v2/demons/", "/api/v2/demons/listed/"))
+        .expect_status(Status::BadRequest)
+        .execute()
+        .await;
+
+    let (page, _) = clnt.get(next).get_paginated::<Demon>().await;
+
+    assert!(page.len() <= 5);
+    assert!(page.iter().all(|demon| demon.requirement > 50));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_lookup_ambiguous_demon_name(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let riot = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let zoink = DatabasePlayer::by_name_or_create("Zoink", &mut *connection).await.unwrap();
+
+    let riots_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 90, riot.id.0, riot.id.0, &mut *connection).await;
+    let zoinks_id = pointercrate_test::demonlist::add_demon("Bloodbath", 2, 90, zoink.id.0, zoink.id.0, &mut *connection).await;
+
+    let candidates = clnt.get("/api/v2/demons/lookup/?name=bloodbath").expect_error(40910).await;
+
+    assert_eq!(candidates["demons"][0]["id"], riots_id);
+    assert_eq!(candidates["demons"][0]["publisher"]["name"], "Riot");
+    assert_eq!(candidates["demons"][1]["id"], zoinks_id);
+    assert_eq!(candidates["demons"][1]["publisher"]["name"], "Zoink");
+
+    let demon: FullDemon = clnt
+        .get("/api/v2/demons/lookup/?name=bloodbath&publisher=zoink")
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.demon.base.id, DemonId(zoinks_id));
+
+    clnt.get("/api/v2/demons/lookup/?name=bloodbath&publisher=Cyclic")
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_demon_by_id_or_name(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let riot = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let zoink = DatabasePlayer::by_name_or_create("Zoink", &mut *connection).await.unwrap();
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 90, riot.id.0, riot.id.0, &mut *connection).await;
+    let bloodlust = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 90, zoink.id.0, zoink.id.0, &mut *connection).await;
+
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", bloodbath))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.demon.base.id, DemonId(bloodbath));
+
+    let demon: FullDemon = clnt
+        .get("/api/v2/demons/bloodlust/")
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.demon.base.id, DemonId(bloodlust));
+
+    clnt.get("/api/v2/demons/Bloodbath%202/").expect_error(40401).await;
+
+    // Static routes take precedence over names
+    clnt.get("/api/v2/demons/lookup/?name=bloodlust")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    pointercrate_test::demonlist::add_demon("Bloodlust", 3, 90, riot.id.0, riot.id.0, &mut *connection).await;
+
+    let candidates = clnt.get("/api/v2/demons/bloodlust/").expect_error(40910).await;
+
+    assert_eq!(candidates["demons"][0]["id"], bloodlust);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_records_pages(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    for i in 0..(APPROVED_RECORDS_PER_PAGE + 5) {
+        let player = DatabasePlayer::by_name_or_create(&format!("Player {}", i), &mut *connection)
+            .await
+            .unwrap();
+
+        pointercrate_test::demonlist::add_simple_record(
+            100 - i as i16 % 50,
+            player.id.0,
+            demon_id,
+            RecordStatus::Approved,
+            &mut *connection,
+        )
+        .await;
+    }
+
+    let response = clnt
+        .get(format!("/api/v2/demons/{}/records/?page=2", demon_id))
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(
+        response.headers().get_one("X-Total-Count"),
+        Some((APPROVED_RECORDS_PER_PAGE + 5).to_string().as_str())
+    );
+
+    let second_page: Vec<serde_json::Value> = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(second_page.len(), 5);
+    // records are sorted by progress, so the lowest ones end up on the last page
+    assert!(second_page.iter().all(|record| record["progress"].as_i64().unwrap() <= 55));
+
+    clnt.get("/api/v2/demons/0/records/").expect_error(40401).await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_legacy_record_export(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+    let record_id =
+        pointercrate_test::demonlist::add_simple_record(100, player.id.0, demon_id, RecordStatus::Approved, &mut *connection).await;
+    pointercrate_test::demonlist::add_simple_record(60, player.id.0, demon_id, RecordStatus::Rejected, &mut *connection).await;
+
+    let records: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/demons/{}/records/export", demon_id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        records,
+        vec![serde_json::json!({
+            "id": record_id,
+            "progress": 100,
+            "video": null,
+            "status": "approved",
+            "player": "stardust1971",
+            "player_id": player.id,
+            "player_banned": false,
+            "nationality": null,
+            "demon": "Bloodbath",
+            "demon_id": demon_id,
+            "position": 1
+        })]
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_legacy_demon_endpoints(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let stardust = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let riot = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, riot.id.0, riot.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Cadrega City", 2, 60, stardust.id.0, stardust.id.0, &mut *connection).await;
+
+    let demons: Vec<serde_json::Value> = clnt
+        .get("/api/legacy/demons/?publisher=Riot")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(demons.len(), 1);
+    assert_eq!(demons[0]["name"], "Bloodbath");
+    assert_eq!(demons[0]["publisher"], "Riot");
+
+    let demons: Vec<serde_json::Value> = clnt.get("/api/legacy/demons/?after=1").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(demons.len(), 1);
+    assert_eq!(demons[0]["position"], 2);
+
+    let demon: serde_json::Value = clnt.get("/api/legacy/demons/2/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(demon["name"], "Cadrega City");
+    assert_eq!(demon["verifier"], "stardust1971");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_legacy_player_and_record_endpoints(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let stardust = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let riot = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let banned = DatabasePlayer::by_name_or_create("Banned", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, riot.id.0, riot.id.0, &mut *connection).await;
+    let approved =
+        pointercrate_test::demonlist::add_simple_record(100, stardust.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+    let submitted = pointercrate_test::demonlist::add_simple_record(60, riot.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    sqlx::query!("UPDATE players SET banned = TRUE WHERE id = $1", banned.id.0)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let players: Vec<serde_json::Value> = clnt.get("/api/legacy/players/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(players.len(), 2);
+    assert!(players.iter().all(|player| player["name"] != "Banned"));
+
+    let player: serde_json::Value = clnt
+        .get(format!("/api/legacy/players/{}/", stardust.id.0))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(player["name"], "stardust1971");
+    assert_eq!(player["records"][0]["demon"], "Bloodbath");
+    assert_eq!(player["records"][0]["position"], 1);
+
+    let player: serde_json::Value = clnt
+        .get(format!("/api/legacy/players/{}/", riot.id.0))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(player["published"], serde_json::json!(["Bloodbath"]));
+
+    // Only approved records are listed
+    let records: Vec<serde_json::Value> = clnt
+        .get("/api/legacy/records/?demon=Bloodbath")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"], approved);
+    assert_eq!(records[0]["player"], "stardust1971");
+    assert_eq!(records[0]["status"], "approved");
+
+    let record: serde_json::Value = clnt
+        .get(format!("/api/legacy/records/{}/", approved))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(record["demon"], "Bloodbath");
+
+    clnt.get(format!("/api/legacy/records/{}/", submitted))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_responses_cached_until_purged(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_cached_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("MISS"));
+    assert_eq!(
+        response.headers().get_one("Surrogate-Key"),
+        Some(format!("demon:{} overview", demon_id).as_str())
+    );
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("HIT"));
+
+    let body: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let demon: FullDemon = serde_json::from_value(body["data"].clone()).unwrap();
+
+    // Authenticated requests bypass the cache
+    let patched: FullDemon = clnt
+        .patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 50}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("MISS"));
+
+    let demon: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(demon["data"]["requirement"], patched.demon.requirement);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_player_rename_purges_demon_responses(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_cached_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, player.id.0, demon_id, RecordStatus::Approved, &mut *connection).await;
+
+    clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("HIT"));
+
+    let full_player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v1/players/{}/", player.id), &serde_json::json!({"name": "Renamed"}))
+        .authorize_as(&moderator)
+        .header("If-Match", full_player.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon_id)).execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("MISS"));
+
+    let demon: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(demon["data"]["records"][0]["player"]["name"], "Renamed");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_reverification(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    clnt.get(format!("/api/v2/demons/{}/reverification/", demon_id))
+        .authorize_as(&moderator)
+        .expect_error(40401)
+        .await;
+
+    clnt.post(
+        format!("/api/v2/demons/{}/reverification/", demon_id),
+        &serde_json::json!({"reason": "Broken by 2.2"}),
+    )
+    .authorize_as(&moderator)
+    .expect_status(Status::Created)
+    .execute()
+    .await;
+
+    let demon = Demon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    assert!(!demon.submissions_open);
+    assert_eq!(demon.submissions_closed_reason.as_deref(), Some("Broken by 2.2"));
+
+    clnt.post(format!("/api/v2/demons/{}/reverification/", demon_id), &serde_json::json!({}))
+        .authorize_as(&moderator)
+        .expect_error(40911)
+        .await;
+
+    // Neither a new verifier nor a new video have been set yet
+    clnt.post(
+        format!("/api/v2/demons/{}/reverification/complete/", demon_id),
+        &serde_json::json!({}),
+    )
+    .authorize_as(&moderator)
+    .expect_error(42253)
+    .await;
+
+    clnt.patch(
+        format!("/api/v2/demons/{}/reverification/", demon_id),
+        &serde_json::json!({"candidate_verifier": "Riot", "candidate_video": "https://www.youtube.com/watch?v=zebrafishes"}),
+    )
+    .authorize_as(&moderator)
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    let completed: FullDemon = clnt
+        .post(
+            format!("/api/v2/demons/{}/reverification/complete/", demon_id),
+            &serde_json::json!({}),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(completed.demon.verifier.name, "Riot");
+    assert_eq!(
+        completed.demon.video.as_deref(),
+        Some("https://www.youtube.com/watch?v=zebrafishes")
+    );
+    assert!(completed.demon.submissions_open);
+    assert_eq!(completed.demon.submissions_closed_reason, None);
+
+    // The re-verification is no longer in progress
+    clnt.get(format!("/api/v2/demons/{}/reverification/", demon_id))
+        .authorize_as(&moderator)
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_tier_and_enjoyment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Bloodlust", 2, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    for (name, enjoyment, status) in [
+        ("a", 6, RecordStatus::Approved),
+        ("b", 9, RecordStatus::Approved),
+        ("c", 1, RecordStatus::Rejected),
+    ] {
+        let rater = DatabasePlayer::by_name_or_create(name, &mut *connection).await.unwrap();
+        let record_id = pointercrate_test::demonlist::add_simple_record(100, rater.id.0, demon_id, status, &mut *connection).await;
+
+        sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", enjoyment, record_id)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    // Only approved records count towards the enjoyment score
+    assert_eq!(demon.demon.enjoyment, Some(7.5));
+    assert_eq!(demon.demon.tier, None);
+
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon_id),
+            &serde_json::json!({"tier": "extreme_demon"}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.demon.tier, Some(DemonTier::ExtremeDemon));
+
+    let demons: Vec<Demon> = clnt
+        .get("/api/v2/demons/listed/?tier=extreme_demon")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(demons.len(), 1);
+    assert_eq!(demons[0].base.id, DemonId(demon_id));
+
+    let demons: Vec<Demon> = clnt
+        .get("/api/v2/demons/listed/?enjoyment__gt=7")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(demons.len(), 1);
+    assert_eq!(demons[0].base.id, DemonId(demon_id));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_nationalities(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    for (name, nation, progress) in [
+        ("a", Some("DE"), 100),
+        ("b", Some("DE"), 60),
+        ("c", Some("AT"), 100),
+        ("d", None, 100),
+    ] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut *connection).await.unwrap();
+
+        sqlx::query!("UPDATE players SET nationality = $1 WHERE id = $2", nation, player.id.0)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+
+        pointercrate_test::demonlist::add_simple_record(progress, player.id.0, demon_id, RecordStatus::Approved, &mut *connection).await;
+    }
+
+    let json: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/nationalities", demon_id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 2);
+    assert_eq!(json[0]["country_code"], "DE");
+    assert_eq!(json[0]["records"], 2);
+    assert_eq!(json[0]["completions"], 1);
+    assert_eq!(json[1]["country_code"], "AT");
+    assert_eq!(json[1]["records"], 1);
+
+    clnt.get("/api/v2/demons/1000/nationalities").expect_error(40401).await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_drafts(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    let draft = serde_json::json!({"name": "Tartarus", "requirement": 54, "creators": ["Dolphy"]});
+
+    clnt.post("/api/v2/demons/drafts/", &draft)
+        .authorize_as(&helper)
+        .expect_error(40301)
+        .await;
+
+    let draft: serde_json::Value = clnt
+        .post("/api/v2/demons/drafts/", &draft)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let draft_id = draft["id"].as_i64().unwrap();
+
+    assert_eq!(draft["name"], "Tartarus");
+    assert_eq!(draft["position"], serde_json::Value::Null);
+
+    // Drafts are not part of the list, and do not create players
+    let demons: Vec<serde_json::Value> = clnt.get("/api/v2/demons/").get_result().await;
+
+    assert_eq!(demons.len(), 1);
+    assert!(DatabasePlayer::by_name("Dolphy", &mut *connection).await.is_err());
+
+    let drafts: Vec<serde_json::Value> = clnt.get("/api/v2/demons/drafts/").authorize_as(&helper).get_result().await;
+
+    assert_eq!(drafts.len(), 1);
+
+    clnt.post(format!("/api/v2/demons/drafts/{}/publish/", draft_id), &())
+        .authorize_as(&moderator)
+        .expect_error(42257)
+        .await;
+
+    let patched: serde_json::Value = clnt
+        .patch(
+            format!("/api/v2/demons/drafts/{}/", draft_id),
+            &serde_json::json!({"position": 1, "verifier": "Dolphy", "publisher": "Riot"}),
+        )
+        .authorize_as(&moderator)
+        .get_result()
+        .await;
+
+    assert_eq!(patched["position"], 1);
+    assert_eq!(patched["verifier"], "Dolphy");
+
+    let published: serde_json::Value = clnt
+        .post(format!("/api/v2/demons/drafts/{}/publish/", draft_id), &())
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(published["name"], "Tartarus");
+    assert_eq!(published["position"], 1);
+    assert_eq!(published["verifier"]["name"], "Dolphy");
+
+    let bloodbath = Demon::by_id(DemonId(bloodbath), &mut *connection).await.unwrap();
+
+    assert_eq!(bloodbath.base.position, 2);
+
+    clnt.get(format!("/api/v2/demons/drafts/{}/", draft_id))
+        .authorize_as(&helper)
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_audit_log_diff(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    let before_patch = sqlx::types::chrono::Utc::now();
+
+    clnt.patch(
+        format!("/api/v2/demons/{}/", demon_id),
+        &serde_json::json!({"requirement": 50, "video": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"}),
+    )
+    .authorize_as(&admin)
+    .header("If-Match", demon.etag_string())
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    let log: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/audit/", demon_id))
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    // Every field is updated by its own statement, so each of them gets its own log entry
+    let diff = log
+        .iter()
+        .filter_map(|entry| entry["type"].get("Modification"))
+        .filter_map(|modification| modification["diff"].as_object())
+        .fold(serde_json::Map::new(), |mut diff, changes| {
+            diff.extend(changes.clone());
+            diff
+        });
+
+    assert_eq!(diff["requirement"], serde_json::json!({"old": 87, "new": 50}));
+    // Changes from NULL are not captured by the legacy audit columns
+    assert_eq!(diff["video"]["old"], serde_json::Value::Null);
+    assert_eq!(diff["video"]["new"], "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    assert!(diff.get("version").is_none());
+    assert!(diff.get("name").is_none());
+
+    // The time machine reconstructs the old requirement from the diff
+    let list_then = pointercrate_demonlist::demon::list_at(&mut *connection, before_patch)
+        .await
+        .unwrap();
+
+    assert_eq!(list_then.len(), 1);
+    assert_eq!(list_then[0].current_demon.requirement, 87);
+    assert_eq!(list_then[0].current_demon.video, None);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_verification_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let demon = clnt.add_demon(&moderator, "Bloodbath", 1, 87, "stardust1971", "stardust1971").await;
+
+    assert!(demon.records.is_empty());
+
+    let verifier: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", demon.demon.verifier.id))
+        .get_success_result()
+        .await;
+
+    assert_eq!(verifier.records.len(), 1);
+    assert!(verifier.records[0].verification);
+    assert_eq!(verifier.records[0].progress, 100);
+    assert_ne!(verifier.player.score, 0.0f64);
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}", verifier.records[0].id))
+        .authorize_as(&moderator)
+        .get_success_result()
+        .await;
+
+    assert!(record.verification);
+    assert!(record.submitter.is_none());
+
+    clnt.patch(format!("/api/v1/records/{}/", record.id), &serde_json::json!({"progress": 90}))
+        .authorize_as(&moderator)
+        .header("If-Match", record.etag_string())
+        .expect_error(40917)
+        .await;
+
+    clnt.delete(format!("/api/v1/records/{}/", record.id))
+        .authorize_as(&moderator)
+        .header("If-Match", record.etag_string())
+        .expect_error(40917)
+        .await;
+
+    // The verification record follows the demon's verifier
+    let demon = FullDemon::by_id(demon.demon.base.id, &mut *connection).await.unwrap();
+
+    let patched: FullDemon = clnt
+        .patch(
+            format!("/api/v2/demons/{}/", demon.demon.base.id),
+            &serde_json::json!({"verifier": "stardust1972"}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let old_verifier: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", verifier.player.base.id))
+        .get_success_result()
+        .await;
+    let new_verifier: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", patched.demon.verifier.id))
+        .get_success_result()
+        .await;
+
+    assert!(old_verifier.records.is_empty());
+    assert_eq!(old_verifier.player.score, 0.0f64);
+    assert_eq!(new_verifier.records.len(), 1);
+    assert_eq!(new_verifier.records[0].id, record.id);
+    assert_eq!(new_verifier.player.score, verifier.player.score);
+
+    // Verifiers can still submit records of their own
+    let submission = serde_json::json! {{"progress": 90, "demon": demon.demon.base.id, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    clnt.post("/api/v1/records/", &submission).expect_status(Status::Ok).execute().await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_archive_and_restore_demon(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let bloodbath = clnt.add_demon(&moderator, "Bloodbath", 1, 87, "stardust1971", "stardust1971").await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 2, 100, verifier.id.0, verifier.id.0, &mut *connection).await;
+    let bloodbath_id = bloodbath.demon.base.id;
+
+    let player = DatabasePlayer::by_name_or_create("Aquatias", &mut *connection).await.unwrap();
+    pointercrate_test::demonlist::add_simple_record(100, player.id.0, bloodbath_id.0, RecordStatus::Approved, &mut *connection).await;
+
+    let bloodbath = FullDemon::by_id(bloodbath_id, &mut *connection).await.unwrap();
+
+    clnt.post(format!("/api/v2/demons/{}/archive", bloodbath_id), &serde_json::json!({}))
+        .authorize_as(&helper)
+        .header("If-Match", bloodbath.etag_string())
+        .expect_error(40301)
+        .await;
+
+    let archived: serde_json::Value = clnt
+        .post(format!("/api/v2/demons/{}/archive", bloodbath_id), &serde_json::json!({}))
+        .authorize_as(&moderator)
+        .header("If-Match", bloodbath.etag_string())
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    assert_eq!(archived["position"], 1);
+    assert_eq!(archived["creators"].as_array().map(Vec::len), Some(0));
+
+    clnt.get(format!("/api/v2/demons/{}/", bloodbath_id))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    // Demons below the archived one move up
+    let demon = Demon::by_id(DemonId(tartarus), &mut *connection).await.unwrap();
+    assert_eq!(demon.base.position, 1);
+
+    // The records are preserved (the verification record is not listed), but give no score
+    let records: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/archive/{}/records", bloodbath_id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(records.as_array().map(Vec::len), Some(1));
+    assert_eq!(records[0]["player"]["name"], "Aquatias");
+
+    let full_player: FullPlayer = clnt.get(format!("/api/v1/players/{}", player.id)).get_success_result().await;
+    assert_eq!(full_player.player.score, 0.0f64);
+
+    let restored: FullDemon = clnt
+        .post(
+            format!("/api/v2/demons/archive/{}/restore", bloodbath_id),
+            &serde_json::json!({"position": 1}),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(restored.position(), 1);
+    assert_eq!(restored.records.len(), 1);
+
+    let demon = Demon::by_id(DemonId(tartarus), &mut *connection).await.unwrap();
+    assert_eq!(demon.base.position, 2);
+
+    let full_player: FullPlayer = clnt.get(format!("/api/v1/players/{}", player.id)).get_success_result().await;
+    assert_ne!(full_player.player.score, 0.0f64);
+
+    clnt.get(format!("/api/v2/demons/archive/{}", bloodbath_id))
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_requirement(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    // The default policy only applies the requirements of individual demons
+    let requirement: serde_json::Value = clnt
+        .get("/api/v1/list_information/requirement")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(requirement["policy"], "per_demon");
+    assert!(requirement["requirement"].is_null());
+    assert!(requirement["summary"].is_null());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_additional_verifiers(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    clnt.post(
+        format!("/api/v2/demons/{}/verifiers", demon),
+        &serde_json::json!({"player": "Knobbelboy"}),
+    )
+    .authorize_as(&user)
+    .expect_status(Status::Created)
+    .execute()
+    .await;
+
+    let full: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // The legacy field keeps referring to the primary verifier
+    assert_eq!(full["verifier"]["name"], "Riot");
+    assert_eq!(full["verifiers"].as_array().map(Vec::len), Some(2));
+    assert_eq!(full["verifiers"][0]["name"], "Riot");
+    assert_eq!(full["verifiers"][0]["primary"], true);
+    assert_eq!(full["verifiers"][1]["name"], "Knobbelboy");
+    assert_eq!(full["verifiers"][1]["primary"], false);
+    assert_eq!(full["publishers"].as_array().map(Vec::len), Some(1));
+
+    clnt.post(
+        format!("/api/v2/demons/{}/verifiers", demon),
+        &serde_json::json!({"player": "Knobbelboy"}),
+    )
+    .authorize_as(&user)
+    .expect_error(40919)
+    .await;
+
+    // The primary verifier can only be replaced, not removed
+    clnt.delete(format!("/api/v2/demons/{}/verifiers/{}", demon, verifier.id))
+        .authorize_as(&user)
+        .expect_error(40401)
+        .await;
+
+    let co_verifier = full["verifiers"][1]["id"].as_i64().unwrap();
+
+    clnt.delete(format!("/api/v2/demons/{}/verifiers/{}", demon, co_verifier))
+        .authorize_as(&user)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_creator_roles(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    for creator in ["Riot", "Knobbelboy"] {
+        clnt.post(
+            format!("/api/v2/demons/{}/creators", demon),
+            &serde_json::json!({ "creator": creator }),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+    }
+
+    // Creators start out without a role
+    let full: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(full["creators_by_role"]["unspecified"].as_array().map(Vec::len), Some(2));
+    assert_eq!(full["creators_by_role"]["host"].as_array().map(Vec::len), Some(0));
+
+    let creator: serde_json::Value = clnt
+        .patch(
+            format!("/api/v2/demons/{}/creators/{}", demon, player.id),
+            &serde_json::json!({"role": "host"}),
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(creator["role"], "host");
+
+    let full: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // The flat list of creators is unaffected by roles
+    assert_eq!(full["creators"].as_array().map(Vec::len), Some(2));
+    assert_eq!(full["creators_by_role"]["host"][0]["name"], "Riot");
+    assert_eq!(full["creators_by_role"]["unspecified"][0]["name"], "Knobbelboy");
+
+    clnt.patch(
+        format!("/api/v2/demons/{}/creators/{}", demon, player.id),
+        &serde_json::json!({"role": "janitor"}),
+    )
+    .authorize_as(&helper)
+    .expect_status(Status::UnprocessableEntity)
+    .execute()
+    .await;
+}
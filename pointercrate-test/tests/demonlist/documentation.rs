@@ -0,0 +1,34 @@
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_documented_examples(pool: Pool<Postgres>) {
+    let (client, _) = pointercrate_test::demonlist::setup_seeded_rocket(pool).await;
+
+    let reference: serde_json::Value = client.get("/api/v1/routes/").expect_status(Status::Ok).get_result().await;
+
+    let routes = reference
+        .as_object()
+        .unwrap()
+        .values()
+        .flat_map(|routes| routes.as_array().unwrap())
+        .collect::<Vec<_>>();
+
+    let demon = routes
+        .iter()
+        .find(|route| route["method"] == "GET" && route["uri"] == "/api/v2/demons/<demon>")
+        .unwrap();
+
+    assert!(demon["description"].as_str().unwrap().starts_with("A single demon"));
+
+    let examples = routes.iter().filter_map(|route| route["example"].as_str()).collect::<Vec<_>>();
+
+    assert!(!examples.is_empty());
+
+    // The examples shown on the reference page have to work against a seeded database
+    for example in examples {
+        let response: serde_json::Value = client.get(example).expect_status(Status::Ok).get_result().await;
+
+        assert!(!response.is_null(), "empty example response for {}", example);
+    }
+}
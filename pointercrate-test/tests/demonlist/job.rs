@@ -0,0 +1,153 @@
+use pointercrate_demonlist::{player::DatabasePlayer, LIST_ADMINISTRATOR, LIST_MODERATOR};
+use rocket::http::{ContentType, Status};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_recompute_scores_job(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    // Added via the API so that the verification record exists
+    clnt.add_demon(&moderator, "Bloodbath", 1, 87, "stardust1971", "stardust1971").await;
+
+    sqlx::query!("UPDATE players SET score = 0 WHERE id = $1", player.id.0)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    clnt.post("/api/v1/jobs/recompute-scores/", &serde_json::json!({}))
+        .authorize_as(&moderator)
+        .expect_error(40301)
+        .await;
+
+    let job: serde_json::Value = clnt
+        .post("/api/v1/jobs/recompute-scores/", &serde_json::json!({}))
+        .authorize_as(&admin)
+        .expect_status(Status::Accepted)
+        .get_result()
+        .await;
+
+    let job_id = job["id"].as_u64().unwrap();
+    let mut status = job["status"].as_str().unwrap().to_string();
+
+    for _ in 0..50 {
+        if status != "running" {
+            break;
+        }
+
+        rocket::tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let job: serde_json::Value = clnt
+            .get(format!("/api/v1/jobs/{}/", job_id))
+            .authorize_as(&admin)
+            .expect_status(Status::Ok)
+            .get_result()
+            .await;
+
+        status = job["status"].as_str().unwrap().to_string();
+    }
+
+    assert_eq!(status, "completed");
+
+    let score = sqlx::query!("SELECT score FROM players WHERE id = $1", player.id.0)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .score;
+
+    assert!(score > 0.0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_import(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    clnt.post_raw(
+        "/api/v1/records/import/",
+        ContentType::CSV,
+        "player,demon,progress\nAeon Air,Bloodbath,100",
+    )
+    .authorize_as(&moderator)
+    .expect_error(40301)
+    .await;
+
+    clnt.post_raw("/api/v1/records/import/", ContentType::CSV, "player,progress\nAeon Air,100")
+        .authorize_as(&admin)
+        .expect_error(42254)
+        .await;
+
+    let csv = "Player,Demon,Progress,Video,Date\n\
+               Aeon Air,Bloodbath,100,https://www.youtube.com/watch?v=gaBWHjJEQb4,2019-08-04\n\
+               Aeon Air,Bloodbath,100,,\n\
+               Zoink,Bloodbath,lots,,\n\
+               Zoink,Sonic Wave,100,,\n\
+               Zoink,Bloodbath,20,,\n";
+
+    let job: serde_json::Value = clnt
+        .post_raw("/api/v1/records/import/", ContentType::CSV, csv)
+        .authorize_as(&admin)
+        .expect_status(Status::Accepted)
+        .get_result()
+        .await;
+
+    let job_id = job["id"].as_u64().unwrap();
+
+    assert_eq!(job["total_steps"], 5);
+
+    for _ in 0..50 {
+        let job: serde_json::Value = clnt
+            .get(format!("/api/v1/jobs/{}/", job_id))
+            .authorize_as(&admin)
+            .get_result()
+            .await;
+
+        if job["status"] != "running" {
+            assert_eq!(job["status"], "completed");
+            break;
+        }
+
+        rocket::tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let results = clnt
+        .get(format!("/api/v1/records/import/{}/", job_id))
+        .authorize_as(&admin)
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+
+    let lines: Vec<&str> = results.lines().collect();
+
+    assert_eq!(lines.len(), 6, "{}", results);
+    assert_eq!(lines[0], "line,status,record,error");
+    assert!(lines[1].starts_with("2,imported,"));
+    assert!(lines[2].starts_with("3,failed,,"));
+    assert_eq!(lines[3], "4,failed,,Malformed value in column 'progress'");
+    assert!(lines[4].starts_with("5,failed,,"));
+    assert!(lines[5].starts_with("6,failed,,"));
+
+    let record = sqlx::query!(
+        r#"SELECT records.status_::TEXT AS "status!", record_additions.time FROM records INNER JOIN record_additions ON record_additions.id = records.id"#
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap();
+
+    assert_eq!(record.status, "APPROVED");
+    assert_eq!(
+        record.time.date_naive(),
+        sqlx::types::chrono::NaiveDate::from_ymd_opt(2019, 8, 4).unwrap()
+    );
+}
@@ -1,5 +1,13 @@
+mod changelog;
 mod claim;
 mod demon;
+mod documentation;
+mod job;
 mod nationality;
+mod normalize;
 mod player;
 mod record;
+mod report;
+mod staff;
+mod submitter;
+mod widget;
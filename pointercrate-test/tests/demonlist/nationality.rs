@@ -1,6 +1,7 @@
 use pointercrate_demonlist::{
-    nationality::{Nationality, RankedNation},
+    nationality::{Nationality, RankedNation, Subdivision},
     player::{DatabasePlayer, Player},
+    record::RecordStatus,
     LIST_MODERATOR,
 };
 use rocket::http::Status;
@@ -33,3 +34,96 @@ pub async fn test_search_nation(pool: Pool<Postgres>) {
     assert_eq!(json[0].nationality.iso_country_code, "DE");
     assert_eq!(json[0].nationality.nation, "Germany");
 }
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_subdivision_ranking(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    // The verifier of the higher demon is ranked above the verifier of the lower one, and players from
+    // other subdivisions do not show up at all
+    for (position, name, subdivision) in [(1, "stardust1971", "BW"), (2, "stardust1972", "BY"), (3, "stardust1973", "BY")] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let demon = pointercrate_test::demonlist::add_demon(
+            format!("Bloodbath {}", position),
+            position,
+            100,
+            player.id.0,
+            player.id.0,
+            &mut connection,
+        )
+        .await;
+        pointercrate_test::demonlist::add_simple_record(100, player.id.0, demon, RecordStatus::Approved, &mut connection).await;
+        player.update_score(&mut connection).await.unwrap();
+
+        let mut player = Player::by_id(player.id, &mut connection).await.unwrap();
+        let nationality = Nationality {
+            iso_country_code: "DE".into(),
+            nation: "Germany".into(),
+            subdivision: Some(Subdivision::new(subdivision.into(), String::new())),
+        };
+        player.set_nationality(Some(nationality), &mut connection).await.unwrap();
+    }
+
+    let json: Vec<serde_json::Value> = client
+        .get("/api/v1/nationalities/DE/subdivisions/BY/ranking/")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 2);
+    assert_eq!(json[0]["name"], "stardust1972");
+    assert_eq!(json[0]["rank"], 1);
+    assert_eq!(json[1]["name"], "stardust1973");
+    assert_eq!(json[1]["rank"], 2);
+
+    client
+        .get("/api/v1/nationalities/DE/subdivisions/XX/ranking/")
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_continent_statistics(pool: Pool<Postgres>) {
+    const PLAYER_NAME: &str = "stardust1971";
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    client.add_demon(&moderator, "Bloodbath", 1, 100, PLAYER_NAME, PLAYER_NAME).await;
+
+    let player = DatabasePlayer::by_name_or_create(PLAYER_NAME, &mut connection).await.unwrap();
+    let mut player = Player::by_id(player.id, &mut connection).await.unwrap();
+    let nationality = Nationality {
+        iso_country_code: "DE".into(),
+        nation: "Germany".into(),
+        subdivision: None,
+    };
+    player.set_nationality(Some(nationality), &mut connection).await.unwrap();
+    // Setting a nationality only updates the score of the player's previous nation
+    player
+        .nationality
+        .as_ref()
+        .unwrap()
+        .update_nation_score(&mut connection)
+        .await
+        .unwrap();
+
+    let json: Vec<serde_json::Value> = client
+        .get("/api/v1/nationalities/continents/")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 7);
+
+    for continent in json {
+        if continent["continent"] == "europe" {
+            assert_eq!(continent["players"], 1);
+            assert_eq!(continent["completions"], 1);
+            assert_eq!(continent["demons_completed"], 1);
+            assert!(continent["score"].as_f64().unwrap() > 0.0);
+        } else {
+            assert_eq!(continent["players"], 0);
+            assert_eq!(continent["completions"], 0);
+        }
+    }
+}
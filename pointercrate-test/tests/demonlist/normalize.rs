@@ -0,0 +1,92 @@
+use pointercrate_demonlist::{player::DatabasePlayer, LIST_MODERATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_slashless_post_redirected(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_normalized_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let demon =
+        serde_json::json!({"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": []});
+
+    // 308 preserves method and body, so clients can simply repeat the request at the new location
+    clnt.post("/api/v2/demons", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::PermanentRedirect)
+        .expect_header("Location", "/api/v2/demons/")
+        .execute()
+        .await;
+
+    clnt.post("/api//v2/demons/?limit=1", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::PermanentRedirect)
+        .expect_header("Location", "/api/v2/demons/?limit=1")
+        .execute()
+        .await;
+
+    clnt.post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_percent_encoded_name_lookup(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_normalized_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_demon("Mr. Dot", 2, 100, player.id.0, player.id.0, &mut *connection).await;
+
+    let demon: serde_json::Value = clnt.get("/api/v2/demons/Blood%62ath/").get_success_result().await;
+
+    assert_eq!(demon["name"], "Bloodbath");
+
+    let demon: serde_json::Value = clnt.get("/api/v2/demons/Mr.%20Dot").get_success_result().await;
+
+    assert_eq!(demon["name"], "Mr. Dot");
+
+    // A dot (encoded or not) in the last segment makes it look like a file name, which pointercrate
+    // serves without trailing slash
+    for path in ["/api/v2/demons/Mr.%20Dot/", "/api/v2/demons/Mr%2E%20Dot/"] {
+        clnt.get(path)
+            .expect_status(Status::PermanentRedirect)
+            .expect_header("Location", "/api/v2/demons/Mr.%20Dot")
+            .execute()
+            .await;
+    }
+
+    let player: serde_json::Value = clnt.get("/api/v1/players/stardust%31971/").get_success_result().await;
+
+    assert_eq!(player["name"], "stardust1971");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_normalized_uris_share_cache_entries(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_normalized_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    let response = clnt.get("/api/v2/demons/Bloodbath/").execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("MISS"));
+
+    let response = clnt.get("/api/v2/demons/Blood%62ath/").execute().await;
+
+    assert_eq!(response.headers().get_one("X-Cache"), Some("HIT"));
+
+    // Redirects are never cached, and the redirect route must not serve another path's response
+    for _ in 0..2 {
+        let response = clnt
+            .get("/api/v2/demons/Bloodbath")
+            .expect_status(Status::PermanentRedirect)
+            .expect_header("Location", "/api/v2/demons/Bloodbath/")
+            .execute()
+            .await;
+
+        assert_eq!(response.headers().get_one("X-Cache"), None);
+    }
+}
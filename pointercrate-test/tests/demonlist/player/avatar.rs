@@ -0,0 +1,152 @@
+use pointercrate_core::{error::CoreError, etag::Taggable};
+use pointercrate_core_api::upload::{LocalStorage, Storage};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::{
+        avatar::{PlayerAvatar, AVATAR_SIZES},
+        DatabasePlayer,
+    },
+};
+use pointercrate_user::MODERATOR;
+use rocket::http::{ContentType, Status};
+use sqlx::{Pool, Postgres};
+
+fn avatar_form(contents: &str) -> (ContentType, String) {
+    (
+        ContentType::parse_flexible("multipart/form-data; boundary=X").unwrap(),
+        format!(
+            "--X\r\nContent-Disposition: form-data; name=\"file\"; filename=\"avatar.png\"\r\nContent-Type: image/png\r\n\r\n{}\r\n--X--\r\n",
+            contents
+        ),
+    )
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_avatar_upload_requires_verified_claim(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let url = format!("/api/v1/players/{}/avatar/", player.id);
+
+    let (content_type, body) = avatar_form("not an image");
+
+    clnt.put_raw(&url, content_type.clone(), body.clone())
+        .authorize_as(&user)
+        .expect_error(40401)
+        .await;
+
+    pointercrate_test::demonlist::put_claim(user.user().id.0, player.id.0, false, false, &mut *connection).await;
+
+    clnt.put_raw(&url, content_type.clone(), body.clone())
+        .authorize_as(&user)
+        .expect_error(40306)
+        .await;
+
+    sqlx::query!("UPDATE player_claims SET verified = TRUE WHERE player_id = $1", player.id.0)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    clnt.put_raw(&url, content_type, body).authorize_as(&user).expect_error(42266).await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_missing_avatar(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    clnt.get(format!("/api/v1/players/{}/avatar/", player.id)).expect_error(40401).await;
+    clnt.get(format!("/api/v1/assets/avatars/{}/", player.id)).expect_error(40401).await;
+
+    let pending: Vec<serde_json::Value> = clnt
+        .get("/api/v1/players/avatars/pending/")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(pending.is_empty());
+}
+
+/// Stores the given contents as the images of the given avatar revision, in the storage the test
+/// rocket serves avatars from
+async fn store_revision(avatar: &PlayerAvatar, contents: &str) {
+    let storage = LocalStorage::new(std::env::temp_dir().join("pointercrate-test-uploads"));
+
+    for size in AVATAR_SIZES {
+        storage
+            .write(&avatar.storage_key(avatar.revision, size), contents.as_bytes())
+            .await
+            .unwrap();
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_avatar_revisions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let asset_url = format!("/api/v1/assets/avatars/{}/", player.id);
+    let approve_url = format!("/api/v1/players/{}/avatar/approve/", player.id);
+
+    let first = PlayerAvatar::upload(player.id, user.user().id.0, &mut *connection).await.unwrap();
+    store_revision(&first, "first revision").await;
+
+    // Never approved, so not served at all
+    clnt.get(&asset_url).expect_error(40401).await;
+
+    clnt.post(&approve_url, &())
+        .authorize_as(&moderator)
+        .expect_status(Status::PreconditionRequired)
+        .execute()
+        .await;
+
+    let approved: PlayerAvatar = clnt
+        .post(&approve_url, &())
+        .authorize_as(&moderator)
+        .header("If-Match", first.etag_string())
+        .get_success_result()
+        .await;
+
+    assert_eq!(approved.approved_revision, Some(first.revision));
+
+    let second = PlayerAvatar::upload(player.id, user.user().id.0, &mut *connection).await.unwrap();
+    store_revision(&second, "second revision").await;
+
+    // The first revision stays visible until the second one is approved
+    let body = clnt.get(&asset_url).execute().await.into_string().await.unwrap();
+    assert_eq!(body, "first revision");
+
+    let response = clnt.get(&asset_url).authorize_as(&moderator).execute().await;
+    assert_eq!(response.headers().get_one("Cache-Control"), Some("no-store"));
+    assert_eq!(response.into_string().await.unwrap(), "second revision");
+
+    // Approving with the ETag of an outdated revision fails
+    clnt.post(&approve_url, &())
+        .authorize_as(&moderator)
+        .header("If-Match", approved.etag_string())
+        .expect_error(41200)
+        .await;
+
+    // Even if a new revision is uploaded between checking the ETag and approving
+    let mut stale = PlayerAvatar::by_player(player.id, &mut *connection).await.unwrap();
+    PlayerAvatar::upload(player.id, user.user().id.0, &mut *connection).await.unwrap();
+
+    assert!(matches!(
+        stale.approve(moderator.user().id.0, &mut *connection).await,
+        Err(DemonlistError::Core(CoreError::PreconditionFailed))
+    ));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_invalid_flag_code(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    clnt.get("/api/v1/assets/flags/..%2F..%2Fsecret/").expect_error(40401).await;
+    clnt.get("/api/v1/assets/flags/de/abcd.svg/").expect_error(40401).await;
+}
@@ -1,11 +1,12 @@
 use pointercrate_demonlist::{
     nationality::{Nationality, Subdivision},
-    player::{DatabasePlayer, FullPlayer, Player},
-    LIST_HELPER,
+    player::{achievement::Achievement, DatabasePlayer, FullPlayer, Player},
+    LIST_HELPER, LIST_MODERATOR,
 };
 use rocket::http::Status;
 use sqlx::{PgConnection, Pool, Postgres};
 
+mod avatar;
 mod score;
 
 async fn create_players(connection: &mut PgConnection) -> (DatabasePlayer, DatabasePlayer) {
@@ -66,6 +67,70 @@ async fn test_list_helper_pagination(pool: Pool<Postgres>) {
     assert_eq!(json[1].base.id, unbanned.id);
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_player_autocomplete(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (banned, unbanned) = create_players(&mut *connection).await;
+    let exact = DatabasePlayer::by_name_or_create("stardust", &mut *connection).await.unwrap();
+    DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    let suggestions: Vec<serde_json::Value> = client
+        .get("/api/v1/players/autocomplete?q=STARDUST")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let ids = suggestions
+        .iter()
+        .map(|suggestion| suggestion["id"].as_i64().unwrap() as i32)
+        .collect::<Vec<_>>();
+
+    assert_eq!(ids, vec![exact.id.0, banned.id.0, unbanned.id.0]);
+    assert_eq!(suggestions[1]["banned"], true);
+    assert_eq!(suggestions[0]["nationality"], serde_json::Value::Null);
+
+    // Wildcards in the query are matched literally
+    let suggestions: Vec<serde_json::Value> = client
+        .get("/api/v1/players/autocomplete?q=%25")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(suggestions.is_empty());
+
+    client
+        .get("/api/v1/players/autocomplete?q=star")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_player_by_id_or_name(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (_, player) = create_players(&mut *connection).await;
+    player.add_alias("sd1972", &mut *connection).await.unwrap();
+
+    for segment in [player.id.to_string(), "STARDUST1972".to_string(), "sd1972".to_string()] {
+        let full: FullPlayer = client
+            .get(format!("/api/v1/players/{}/", segment))
+            .expect_status(Status::Ok)
+            .get_success_result()
+            .await;
+
+        assert_eq!(full.player.base.id, player.id);
+    }
+
+    client.get("/api/v1/players/stardust1973/").expect_error(40401).await;
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -74,7 +139,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
 
     // Try to set subdivision when no nation is set. Should fail.
     let result: serde_json::Value = client
-        .patch_player(player.id, &user, serde_json::json!({"subdivision": "ENG"}))
+        .patch_player(player.id.0, &user, serde_json::json!({"subdivision": "ENG"}))
         .await
         .expect_status(Status::Conflict)
         .get_result()
@@ -85,7 +150,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     // Patch both nationality and subdivision
     let patched_player: FullPlayer = client
         .patch_player(
-            player.id,
+            player.id.0,
             &user,
             serde_json::json!({"nationality": "United Kingdom", "subdivision": "ENG"}),
         )
@@ -108,7 +173,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     // Patch only subdivision, nationality should remain untouched
     let patched_player: FullPlayer = client
         .patch_player(
-            player.id,
+            player.id.0,
             &user,
             serde_json::json!({"nationality": "United Kingdom", "subdivision": "SCT"}),
         )
@@ -130,7 +195,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
 
     // Patch nation, but to the one we already have. Shouldn't change anything.
     client
-        .patch_player(player.id, &user, serde_json::json!({"nationality": "United Kingdom"}))
+        .patch_player(player.id.0, &user, serde_json::json!({"nationality": "United Kingdom"}))
         .await
         .expect_status(Status::NotModified)
         .execute()
@@ -138,7 +203,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
 
     // Patch only nationality. Should reset subdivision
     let patched_player: FullPlayer = client
-        .patch_player(player.id, &user, serde_json::json!({"nationality": "Germany"}))
+        .patch_player(player.id.0, &user, serde_json::json!({"nationality": "Germany"}))
         .await
         .get_success_result()
         .await;
@@ -155,7 +220,7 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     // Nonsense nationality/subdivision combo should be rejected
     let result: serde_json::Value = client
         .patch_player(
-            player.id,
+            player.id.0,
             &user,
             serde_json::json!({"nationality": "Belgium", "subdivision": "ENG"}),
         )
@@ -168,3 +233,169 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     assert_eq!(result["data"]["nation_code"], "BE");
     assert_eq!(result["data"]["subdivision_code"], "ENG");
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_player_requires_list_helper(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    let result: serde_json::Value = client
+        .patch_player(player.id.0, &user, serde_json::json!({"banned": true}))
+        .await
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 40301);
+
+    // Empty patches do not require any per-field permissions, but still must not be accessible
+    let result: serde_json::Value = client
+        .patch_player(player.id.0, &user, serde_json::json!({}))
+        .await
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 40301);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_player_aliases(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let other = DatabasePlayer::by_name_or_create("Zoink", &mut *connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+
+    // Renaming keeps the old name around as an alias
+    client
+        .patch_player(player.id.0, &user, serde_json::json!({"name": "stardust1972"}))
+        .await
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let resolved = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    assert_eq!(resolved.id, player.id);
+    assert_eq!(resolved.name, "stardust1972");
+
+    client
+        .post(
+            format!("/api/v1/players/{}/aliases/", player.id),
+            &serde_json::json!({"alias": "sd  1971"}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    // Aliases need to be unique across aliases and player names
+    client
+        .post(
+            format!("/api/v1/players/{}/aliases/", other.id),
+            &serde_json::json!({"alias": "SD 1971"}),
+        )
+        .authorize_as(&user)
+        .expect_error(40909)
+        .await;
+    client
+        .post(
+            format!("/api/v1/players/{}/aliases/", other.id),
+            &serde_json::json!({"alias": "stardust1972"}),
+        )
+        .authorize_as(&user)
+        .expect_error(40909)
+        .await;
+
+    assert_eq!(
+        DatabasePlayer::by_name_or_create(" sd 1971 ", &mut *connection).await.unwrap().id,
+        player.id
+    );
+
+    let aliases: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/players/{}/aliases/", player.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(aliases.len(), 2);
+
+    client
+        .delete(format!("/api/v1/players/{}/aliases/sd%201971/", player.id))
+        .authorize_as(&user)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+    client
+        .delete(format!("/api/v1/players/{}/aliases/sd%201971/", player.id))
+        .authorize_as(&user)
+        .expect_error(40401)
+        .await;
+
+    // Merging moves the merged player's name over as an alias
+    client
+        .patch_player(other.id.0, &user, serde_json::json!({"name": "stardust1972"}))
+        .await
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(
+        DatabasePlayer::by_name_or_create("Zoink", &mut *connection).await.unwrap().id,
+        other.id
+    );
+    assert_eq!(
+        DatabasePlayer::by_name_or_create("stardust1971", &mut *connection)
+            .await
+            .unwrap()
+            .id,
+        other.id
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_achievements_awarded_on_approval(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let verifier = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let top = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id.0, verifier.id.0, &mut *connection).await;
+    let second = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, verifier.id.0, verifier.id.0, &mut *connection).await;
+
+    let submission = serde_json::json! {{"progress": 100, "demon": second, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "Approved"}};
+
+    clnt.post("/api/v1/records", &submission)
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let full: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let achievements: Vec<Achievement> = full.achievements.iter().map(|achievement| achievement.achievement).collect();
+
+    assert_eq!(achievements, vec![Achievement::FirstExtreme]);
+
+    let submission = serde_json::json! {{"progress": 100, "demon": top, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567891", "status": "Approved"}};
+
+    clnt.post("/api/v1/records", &submission)
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let full: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(full.achievements.iter().any(|a| a.achievement == Achievement::TopOneCompletion));
+    assert!(!full.achievements.iter().any(|a| a.achievement == Achievement::TenMainList));
+}
@@ -1,10 +1,10 @@
 //! Module containing all score related test cases (because I suspect over time there will be quite a few)
 
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{config::ScoreDecayConfig, etag::Taggable};
 use pointercrate_demonlist::{
     player::{DatabasePlayer, FullPlayer},
-    record::FullRecord,
-    LIST_MODERATOR,
+    record::{FullRecord, RecordId, RecordStatus},
+    score, score_history, LIST_MODERATOR,
 };
 use rocket::http::Status;
 use sqlx::{PgConnection, Pool, Postgres};
@@ -97,7 +97,7 @@ pub async fn test_player_score_reflects_to_nationality(pool: Pool<Postgres>) {
     let demon = clnt.add_demon(&helper, "Bloodbath", 1, 100, "stardust1971", "stardust1971").await;
 
     clnt.patch_player(
-        demon.demon.verifier.id,
+        demon.demon.verifier.id.0,
         &helper,
         serde_json::json!({"nationality": "GB", "subdivision": "ENG"}),
     )
@@ -108,7 +108,7 @@ pub async fn test_player_score_reflects_to_nationality(pool: Pool<Postgres>) {
     assert_ne!(nationality_score("GB", &mut connection).await, 0f64);
     assert_ne!(subdivision_score("GB", "ENG", &mut connection).await, 0f64);
 
-    clnt.patch_player(demon.demon.verifier.id, &helper, serde_json::json!({"subdivision": "SCT"}))
+    clnt.patch_player(demon.demon.verifier.id.0, &helper, serde_json::json!({"subdivision": "SCT"}))
         .await
         .execute()
         .await;
@@ -117,7 +117,7 @@ pub async fn test_player_score_reflects_to_nationality(pool: Pool<Postgres>) {
     assert_eq!(subdivision_score("GB", "ENG", &mut connection).await, 0f64);
     assert_ne!(subdivision_score("GB", "SCT", &mut connection).await, 0f64);
 
-    clnt.patch_player(demon.demon.verifier.id, &helper, serde_json::json!({"nationality": "DE"}))
+    clnt.patch_player(demon.demon.verifier.id.0, &helper, serde_json::json!({"nationality": "DE"}))
         .await
         .execute()
         .await;
@@ -141,7 +141,7 @@ pub async fn test_extended_progress_records_give_no_score(pool: Pool<Postgres>)
     for position in 1..=(list_size + 1) {
         last_demon_id = sqlx::query!(
             "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ('Bloodbath', $2, 98, $1, $1) RETURNING id",
-            player.id,
+            player.id.0,
             position
         )
         .fetch_one(&mut *connection)
@@ -181,7 +181,7 @@ pub async fn test_score_resets_if_last_record_removed(pool: Pool<Postgres>) {
     for position in 1..=list_size {
         last_demon_id = sqlx::query!(
             "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ('Bloodbath', $2, 98, $1, $1) RETURNING id",
-            player.id,
+            player.id.0,
             position
         )
         .fetch_one(&mut *connection)
@@ -233,3 +233,124 @@ pub async fn test_score_resets_if_last_record_removed(pool: Pool<Postgres>) {
         "Removal of player's last record did not reset their score to 0"
     );
 }
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_score_history(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, player.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+
+    let score = player.update_score(&mut *connection).await.unwrap();
+
+    assert_eq!(score_history::take_snapshots(&mut *connection).await.unwrap(), 1);
+    // Only one snapshot is taken per week
+    assert_eq!(score_history::take_snapshots(&mut *connection).await.unwrap(), 0);
+
+    let history: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/players/{}/score-history/", player.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["score"].as_f64(), Some(score));
+
+    clnt.get(format!("/api/v1/players/{}/score-history/", player.id.0 + 1))
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_score_decay_change_recomputes_scores(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let demon = clnt.add_demon(&helper, "Bloodbath", 1, 100, "stardust1971", "stardust1971").await;
+    let verifier = demon.demon.verifier.id;
+
+    let player: FullPlayer = clnt.get(format!("/api/v1/players/{}", verifier)).get_success_result().await;
+    let full_score = player.player.score;
+
+    // The migrations set up the default configuration
+    assert!(!score::sync_decay(pointercrate_demonlist::config::score_decay(), &mut *connection)
+        .await
+        .unwrap());
+
+    let decay = [ScoreDecayConfig { from: 1, weight: 0.5 }];
+
+    assert!(score::sync_decay(&decay, &mut *connection).await.unwrap());
+    assert!(!score::sync_decay(&decay, &mut *connection).await.unwrap());
+
+    let player: FullPlayer = clnt.get(format!("/api/v1/players/{}", verifier)).get_success_result().await;
+
+    assert!((player.player.score - full_score / 2.0).abs() < 0.01);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_ban_rejects_records_and_unban_restores_them(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = clnt.add_demon(&helper, "Bloodbath", 1, 50, "stardust1972", "stardust1972").await;
+
+    let submission = serde_json::json! {{"progress": 100, "demon": demon.demon.base.id, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "Approved"}};
+
+    let record = clnt
+        .post("/api/v1/records", &submission)
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_success_result::<FullRecord>()
+        .await;
+    let superseded =
+        pointercrate_test::demonlist::add_simple_record(40, player.id.0, demon.demon.base.id.0, RecordStatus::Superseded, &mut *connection)
+            .await;
+
+    let full: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let banned: FullPlayer = clnt
+        .patch(format!("/api/v1/players/{}", player.id), &serde_json::json!({"banned": true}))
+        .authorize_as(&helper)
+        .header("If-Match", full.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(banned.records.is_empty());
+    assert_eq!(banned.player.score, 0.0f64, "Banned player kept their score");
+    assert_eq!(
+        FullRecord::by_id(record.id, &mut *connection).await.unwrap().status,
+        RecordStatus::Rejected
+    );
+    // Superseded records are kept for history through a ban
+    assert_eq!(
+        FullRecord::by_id(RecordId(superseded), &mut *connection).await.unwrap().status,
+        RecordStatus::Superseded
+    );
+
+    let unbanned: FullPlayer = clnt
+        .patch(format!("/api/v1/players/{}", player.id), &serde_json::json!({"banned": false}))
+        .authorize_as(&helper)
+        .header("If-Match", banned.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(unbanned.records.len(), 1);
+    assert_eq!(unbanned.player.score, full.player.score, "Unbanning did not restore score");
+    assert_eq!(
+        FullRecord::by_id(record.id, &mut *connection).await.unwrap().status,
+        RecordStatus::Approved
+    );
+    assert_eq!(
+        FullRecord::by_id(RecordId(superseded), &mut *connection).await.unwrap().status,
+        RecordStatus::Superseded
+    );
+}
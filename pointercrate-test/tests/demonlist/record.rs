@@ -3,11 +3,12 @@ use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::{
     error::DemonlistError,
     player::{DatabasePlayer, FullPlayer},
-    record::{note::Note, FullRecord, RecordStatus},
-    LIST_HELPER, LIST_MODERATOR,
+    record::{assignment, fingerprint, note::Note, stale, video_check::record_video_check, FullRecord, RecordId, RecordStatus},
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
-use rocket::http::Status;
+use pointercrate_user::auth::{legacy::Registration, AuthenticatedUser};
+use rocket::http::{ContentType, Status};
 use sqlx::{PgConnection, Pool, Postgres};
 
 #[sqlx::test(migrations = "../migrations")]
@@ -33,7 +34,7 @@ async fn paginate_records_with_verified_claim(pool: Pool<Postgres>) {
     let (p1, r1, r2, _r3) = setup_pagination_tests(&mut *connection).await;
     let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
 
-    pointercrate_test::demonlist::put_claim(user.user().id, p1, true, false, &mut *connection).await;
+    pointercrate_test::demonlist::put_claim(user.user().id.0, p1, true, false, &mut *connection).await;
 
     let json: Vec<serde_json::Value> = clnt
         .get(format!("/api/v1/records/?player={}", p1))
@@ -53,7 +54,7 @@ async fn paginate_records_with_unverified_claim(pool: Pool<Postgres>) {
     let (p1, r1, _r2, _r3) = setup_pagination_tests(&mut *connection).await;
     let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
 
-    pointercrate_test::demonlist::put_claim(user.user().id, p1, false, false, &mut *connection).await;
+    pointercrate_test::demonlist::put_claim(user.user().id.0, p1, false, false, &mut *connection).await;
 
     let json: Vec<serde_json::Value> = clnt
         .get(format!("/api/v1/records/?player={}", p1))
@@ -72,7 +73,7 @@ async fn paginate_records_with_verified_claim_wrong_player(pool: Pool<Postgres>)
     let (p1, _r1, _r2, _r3) = setup_pagination_tests(&mut *connection).await;
     let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
 
-    pointercrate_test::demonlist::put_claim(user.user().id, p1, true, false, &mut *connection).await;
+    pointercrate_test::demonlist::put_claim(user.user().id.0, p1, true, false, &mut *connection).await;
 
     let json: Vec<serde_json::Value> = clnt.get("/api/v1/records/?player=2").authorize_as(&user).get_result().await;
 
@@ -83,14 +84,14 @@ async fn setup_pagination_tests(connection: &mut PgConnection) -> (i32, i32, i32
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", connection).await.unwrap();
     let player2 = DatabasePlayer::by_name_or_create("stardust1972", connection).await.unwrap();
 
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id, player1.id, connection).await;
-    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 53, player1.id, player1.id, connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id.0, player1.id.0, connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 53, player1.id.0, player1.id.0, connection).await;
 
-    let r1 = pointercrate_test::demonlist::add_simple_record(100, player1.id, demon1, RecordStatus::Approved, connection).await;
-    let r2 = pointercrate_test::demonlist::add_simple_record(70, player1.id, demon2, RecordStatus::Rejected, connection).await;
-    let r3 = pointercrate_test::demonlist::add_simple_record(100, player2.id, demon2, RecordStatus::Rejected, connection).await;
+    let r1 = pointercrate_test::demonlist::add_simple_record(100, player1.id.0, demon1, RecordStatus::Approved, connection).await;
+    let r2 = pointercrate_test::demonlist::add_simple_record(70, player1.id.0, demon2, RecordStatus::Rejected, connection).await;
+    let r3 = pointercrate_test::demonlist::add_simple_record(100, player2.id.0, demon2, RecordStatus::Rejected, connection).await;
 
-    (player1.id, r1, r2, r3)
+    (player1.id.0, r1, r2, r3)
 }
 
 #[sqlx::test(migrations = "../migrations")]
@@ -99,9 +100,9 @@ async fn unauthed_submit_for_player_with_locked_submission(pool: Pool<Postgres>)
 
     let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id, player1.id, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id.0, player1.id.0, &mut *connection).await;
 
-    pointercrate_test::demonlist::put_claim(user.user().id, player1.id, true, true, &mut *connection).await;
+    pointercrate_test::demonlist::put_claim(user.user().id.0, player1.id.0, true, true, &mut *connection).await;
 
     let submission =
         serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
@@ -123,8 +124,9 @@ async fn submit_existing_record(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
 
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
-    let existing = pointercrate_test::demonlist::add_simple_record(70, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let existing =
+        pointercrate_test::demonlist::add_simple_record(70, player1.id.0, demon1, RecordStatus::Approved, &mut *connection).await;
 
     let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
 
@@ -142,7 +144,7 @@ async fn submit_existing_record(pool: Pool<Postgres>) {
 async fn test_submit_successful(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
 
     let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
 
@@ -159,8 +161,9 @@ async fn test_no_submitter_info_on_unauthed_get(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
 
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
-    let existing = pointercrate_test::demonlist::add_simple_record(70, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let existing =
+        pointercrate_test::demonlist::add_simple_record(70, player1.id.0, demon1, RecordStatus::Approved, &mut *connection).await;
 
     let record: FullRecord = clnt.get(format!("/api/v1/records/{}", existing)).get_success_result().await;
 
@@ -175,7 +178,7 @@ async fn test_no_raw_footage_on_unauthed_get(pool: Pool<Postgres>) {
 
     let user = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
     let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": player1.name, "video": "https://youtube.com/watch?v=1234567890", "raw_footage": raw_footage, "status": "approved"}};
 
     let record: FullRecord = clnt
@@ -202,8 +205,8 @@ async fn test_record_note_creation_and_deletion(pool: Pool<Postgres>) {
 
     let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
-    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
-    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let record = add_simple_record(100, player1.id.0, demon1, RecordStatus::Approved, &mut *connection).await;
 
     // Create a record note whose author is `helper`.
     let note: Note = clnt
@@ -229,6 +232,48 @@ async fn test_record_note_creation_and_deletion(pool: Pool<Postgres>) {
         .await;
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_note_mentions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+    let record = add_simple_record(100, player.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+
+    // Jacob is not list staff, so cannot read internal notes and must not be notified about them
+    for is_public in [false, true] {
+        clnt.post(
+            format!("/api/v1/records/{}/notes", record),
+            &serde_json::json! {{
+                "content": "Can you take a look at this, @jacob?",
+                "is_public": is_public,
+            }},
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+    }
+
+    let mentioned = sqlx::query!("SELECT member_id FROM notifications WHERE kind = 'mentioned_in_note'")
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(mentioned.len(), 1);
+    assert_eq!(mentioned[0].member_id, user.user().id.0);
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_record_deletion_updates_player_score(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -269,3 +314,974 @@ async fn test_record_deletion_updates_player_score(pool: Pool<Postgres>) {
 
     assert_eq!(player.player.score, 0.0f64, "Deleting approved record failed to lower player score");
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_cooldown(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let submission =
+        serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    clnt.post("/api/v1/records/", &submission).expect_status(Status::Ok).execute().await;
+
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567891"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::TooManyRequests)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42902i64));
+    assert!(json["data"]["retry_after"].is_string());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_too_many_pending_submissions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    for position in 1..=3 {
+        let demon = pointercrate_test::demonlist::add_demon(
+            format!("Demon {}", position),
+            position,
+            50,
+            player1.id.0,
+            player1.id.0,
+            &mut *connection,
+        )
+        .await;
+
+        add_simple_record(100, player1.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+    }
+
+    let demon4 = pointercrate_test::demonlist::add_demon("Demon 4", 4, 50, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon4, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::TooManyRequests)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42901i64));
+    assert_eq!(json["data"]["limit"].as_i64(), Some(3i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_own_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (p1, r1, r2, r3) = setup_pagination_tests(&mut *connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    pointercrate_test::demonlist::put_claim(user.user().id.0, p1, true, false, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1973", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath 2", 3, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    // Submitting while logged in ties the record to the account, even though the player is not claimed
+    let submitted: FullRecord = clnt
+        .post(
+            "/api/v1/records/",
+            &serde_json::json! {{"progress": 100, "demon": demon, "player": "stardust1973", "video": "https://youtube.com/watch?v=1234567890"}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let approved = FullRecord::by_id(RecordId(r1), &mut *connection).await.unwrap();
+
+    clnt.patch(format!("/api/v1/records/{}/", r1), &serde_json::json!({"status": "rejected"}))
+        .authorize_as(&moderator)
+        .header("If-Match", approved.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let records: Vec<serde_json::Value> = clnt
+        .get("/api/v1/auth/me/records/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let ids = records
+        .iter()
+        .map(|record| record["id"].as_i64().unwrap() as i32)
+        .collect::<Vec<_>>();
+
+    assert_eq!(ids, vec![submitted.id.0, r2, r1]);
+    assert!(!ids.contains(&r3));
+
+    assert_eq!(records[2]["status"], "rejected");
+    assert_eq!(records[2]["status_history"][0]["from"], "approved");
+    assert_eq!(records[2]["status_history"][0]["to"], "rejected");
+    assert_eq!(records[0]["status_history"].as_array().map(Vec::len), Some(0));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_spam_scoring(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://bit.ly/abcdef", "website": "http://cheap-followers.example"}};
+
+    // Suspicious submissions are not rejected, and the submitter does not get to see their score
+    let submitted: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(submitted.get("spam").is_none());
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}", submitted["id"]))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    let spam = record.spam.unwrap();
+
+    assert_eq!(record.status, RecordStatus::Submitted);
+    assert_eq!(spam.score, 150);
+    assert_eq!(spam.reasons.len(), 2);
+    assert!(spam.is_suspicious());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_video_timestamp(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "video_timestamp": "1:61"}};
+
+    clnt.post("/api/v1/records/", &submission).expect_error(42247).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "enjoyment": 11}};
+
+    clnt.post("/api/v1/records/", &submission).expect_error(42246).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "video_timestamp": "1:02:03", "enjoyment": 7}};
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.video_timestamp, Some(3723));
+    assert_eq!(record.enjoyment, Some(7));
+
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json! {{"video_timestamp": "42"}},
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.video_timestamp, Some(42));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_oversized_raw_footage_rejected_early(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let record = add_simple_record(100, player1.id.0, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    let max_size = pointercrate_core::config::raw_footage_limit();
+
+    let data = clnt
+        .post(format!("/api/v1/records/{}/raw-footage/", record), &())
+        .authorize_as(&helper)
+        .header("Content-Type", "multipart/form-data; boundary=X")
+        .header("Content-Length", (max_size + 1).to_string())
+        .expect_error(41301)
+        .await;
+
+    assert_eq!(data["max_size"], max_size);
+}
+
+fn raw_footage_form(contents: &str) -> (ContentType, String) {
+    (
+        ContentType::parse_flexible("multipart/form-data; boundary=X").unwrap(),
+        format!(
+            "--X\r\nContent-Disposition: form-data; name=\"file\"; filename=\"footage.mp4\"\r\nContent-Type: video/mp4\r\n\r\n{}\r\n--X--\r\n",
+            contents
+        ),
+    )
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_raw_footage_upload_checked_before_reading_body(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let record = add_simple_record(100, player1.id.0, demon1, RecordStatus::Approved, &mut *connection).await;
+
+    // The submitter can no longer upload raw footage once the record was reviewed, which needs to
+    // be noticed before complaining about the body's size
+    clnt.post(format!("/api/v1/records/{}/raw-footage/", record), &())
+        .header("Content-Type", "multipart/form-data; boundary=X")
+        .header("Content-Length", (pointercrate_core::config::raw_footage_limit() + 1).to_string())
+        .expect_error(40301)
+        .await;
+
+    clnt.post(format!("/api/v1/records/{}/raw-footage/", record + 1), &())
+        .header("Content-Type", "multipart/form-data; boundary=X")
+        .expect_error(40401)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_raw_footage_upload_by_submitter(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+    let record = add_simple_record(100, player1.id.0, demon1, RecordStatus::Submitted, &mut *connection).await;
+    let url = format!("/api/v1/records/{}/raw-footage/", record);
+
+    let (content_type, body) = raw_footage_form("not actually a video");
+
+    for _ in 0..5 {
+        let json: serde_json::Value = clnt
+            .post_raw(&url, content_type.clone(), body.clone())
+            .expect_status(Status::Created)
+            .get_result()
+            .await;
+
+        assert_eq!(json["data"]["raw_footage"], serde_json::Value::Null); // Redacted for anonymous users
+    }
+
+    clnt.post_raw(&url, content_type.clone(), body.clone()).expect_error(42900).await;
+
+    // Helpers are not ratelimited
+    clnt.post_raw(&url, content_type, body)
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.raw_footage.as_deref(), Some(url.as_str()));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_approval_supersedes_lower_progress(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let approved = add_simple_record(60, player.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+    let submitted = add_simple_record(80, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    let record = FullRecord::by_id(RecordId(submitted), &mut *connection).await.unwrap();
+
+    clnt.patch(
+        format!("/api/v1/records/{}/", submitted),
+        &serde_json::json!({"status": "approved"}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", record.etag_string())
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    // The old record is kept, but no longer counts as approved
+    let superseded = FullRecord::by_id(RecordId(approved), &mut *connection).await.unwrap();
+
+    assert_eq!(superseded.status, RecordStatus::Superseded);
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(player.records.len(), 1);
+    assert_eq!(player.records[0].progress, 80);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_concurrent_patches_with_same_etag(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+    let record_id = add_simple_record(80, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    let etag = FullRecord::by_id(RecordId(record_id), &mut *connection)
+        .await
+        .unwrap()
+        .etag_string();
+    let url = format!("/api/v1/records/{}/", record_id);
+
+    let (approve, reject) = rocket::tokio::join!(
+        clnt.patch(url.clone(), &serde_json::json!({"status": "approved"}))
+            .authorize_as(&helper)
+            .header("If-Match", etag.clone())
+            .dispatch(),
+        clnt.patch(url, &serde_json::json!({"status": "rejected"}))
+            .authorize_as(&helper)
+            .header("If-Match", etag)
+            .dispatch()
+    );
+
+    // Both requests were made against the same version, so exactly one of them may be applied
+    let mut statuses = [approve.status(), reject.status()];
+    statuses.sort_by_key(|status| status.code);
+
+    assert_eq!(statuses, [Status::Ok, Status::PreconditionFailed]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_reapprove_superseded_record(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let approved = add_simple_record(60, player.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+    let submitted = add_simple_record(80, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    let record = FullRecord::by_id(RecordId(submitted), &mut *connection).await.unwrap();
+
+    clnt.patch(
+        format!("/api/v1/records/{}/", submitted),
+        &serde_json::json!({"status": "approved"}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", record.etag_string())
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    // The superseding record is still approved, so the superseded one cannot be approved again
+    let superseded = FullRecord::by_id(RecordId(approved), &mut *connection).await.unwrap();
+
+    let error = clnt
+        .patch(format!("/api/v1/records/{}/", approved), &serde_json::json!({"status": "approved"}))
+        .authorize_as(&helper)
+        .header("If-Match", superseded.etag_string())
+        .expect_error(42217)
+        .await;
+
+    assert_eq!(error["existing"], submitted);
+    assert_eq!(
+        FullRecord::by_id(RecordId(approved), &mut *connection).await.unwrap().status,
+        RecordStatus::Superseded
+    );
+
+    // Once the superseding record is gone, the superseded one can be approved again
+    FullRecord::delete_by_id(RecordId(submitted), &mut *connection).await.unwrap();
+
+    clnt.patch(format!("/api/v1/records/{}/", approved), &serde_json::json!({"status": "approved"}))
+        .authorize_as(&helper)
+        .header("If-Match", superseded.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(
+        FullRecord::by_id(RecordId(approved), &mut *connection).await.unwrap().status,
+        RecordStatus::Approved
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_anonymous_submitters(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (_p1, r1, _r2, _r3) = setup_pagination_tests(&mut *connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let submitter_id = FullRecord::by_id(RecordId(r1), &mut *connection)
+        .await
+        .unwrap()
+        .submitter
+        .unwrap()
+        .id;
+
+    let record: FullRecord = clnt.get(format!("/api/v1/records/{}", r1)).get_success_result().await;
+
+    assert!(record.submitter.is_none());
+
+    let anonymous_submitter = record.anonymous_submitter.expect("anonymized submitter missing on public record");
+
+    let json: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/?anonymous_submitter={}", anonymous_submitter))
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 1);
+    assert_eq!(json[0]["id"].as_i64(), Some(r1 as i64));
+
+    // Filtering by submitter would map the submitter to the anonymized identifiers in the results
+    clnt.get(format!("/api/v1/records/?submitter={}", submitter_id))
+        .authorize_as(&moderator)
+        .expect_error(40301)
+        .await;
+
+    clnt.get(format!("/api/v1/submitters/anonymous/{}", anonymous_submitter))
+        .authorize_as(&moderator)
+        .expect_error(40301)
+        .await;
+
+    // List administrators can see the mapping
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}", r1))
+        .authorize_as(&admin)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.submitter.map(|submitter| submitter.id), Some(submitter_id));
+    assert_eq!(record.anonymous_submitter.as_deref(), Some(anonymous_submitter.as_str()));
+
+    let json: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/?submitter={}", submitter_id))
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert!(!json.is_empty());
+    assert!(json
+        .iter()
+        .all(|record| record["anonymous_submitter"] == anonymous_submitter.as_str()));
+
+    let json: serde_json::Value = clnt
+        .get(format!("/api/v1/submitters/anonymous/{}", anonymous_submitter))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(json["id"].as_i64(), Some(submitter_id.0 as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_helpers_cannot_link_anonymous_submitters(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    setup_pagination_tests(&mut *connection).await;
+
+    for permission in [LIST_HELPER, LIST_MODERATOR] {
+        let staff = system_user_with_perms(permission, &mut *connection).await;
+
+        let listed: Vec<serde_json::Value> = clnt.get("/api/v1/records/").authorize_as(&staff).get_result().await;
+
+        assert!(!listed.is_empty());
+
+        for listed_record in listed {
+            assert!(listed_record["anonymous_submitter"].is_string());
+
+            let record: FullRecord = clnt
+                .get(format!("/api/v1/records/{}", listed_record["id"]))
+                .authorize_as(&staff)
+                .get_success_result()
+                .await;
+
+            assert!(
+                record.submitter.is_none(),
+                "staff below list administrator can see both the submitter and the anonymized submitter of a record"
+            );
+        }
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_transfer_record_to_other_player(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let existing = add_simple_record(100, player1.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+    let record_id = add_simple_record(90, player2.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+
+    let record: FullRecord = clnt.get(format!("/api/v1/records/{}", record_id)).get_success_result().await;
+
+    clnt.patch(
+        format!("/api/v1/records/{}", record_id),
+        &serde_json::json!({"player_id": player1.id}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", record.etag_string())
+    .expect_error(40301)
+    .await;
+
+    clnt.patch(
+        format!("/api/v1/records/{}", record_id),
+        &serde_json::json!({"player_id": player1.id, "player": "stardust1973"}),
+    )
+    .authorize_as(&moderator)
+    .header("If-Match", record.etag_string())
+    .expect_error(42229)
+    .await;
+
+    // player1 already has an approved record on this demon
+    let error = clnt
+        .patch(
+            format!("/api/v1/records/{}", record_id),
+            &serde_json::json!({"player_id": player1.id}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", record.etag_string())
+        .expect_error(42217)
+        .await;
+
+    assert_eq!(error["existing"].as_i64(), Some(existing as i64));
+
+    // Transferring to an unknown player by name creates them
+    let patched: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}", record_id),
+            &serde_json::json!({"player": "stardust1973"}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.player.name, "stardust1973");
+    assert_ne!(patched.player.id, player2.id);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_appeals(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 53, player.id.0, player.id.0, &mut *connection).await;
+
+    // Records are submitted by 127.0.0.1, the same address the test client makes its requests from
+    let record = add_simple_record(100, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+    let record2 = add_simple_record(100, player.id.0, demon2, RecordStatus::Submitted, &mut *connection).await;
+
+    let appeal = serde_json::json!({"reason": "The clicks are audible in the video"});
+
+    clnt.post(format!("/api/v1/records/{}/appeals/", record), &appeal)
+        .expect_error(42260)
+        .await;
+
+    for record_id in [record, record2] {
+        let full_record = FullRecord::by_id(RecordId(record_id), &mut *connection).await.unwrap();
+
+        clnt.patch(
+            format!("/api/v1/records/{}/", record_id),
+            &serde_json::json!({"status": "rejected"}),
+        )
+        .authorize_as(&moderator)
+        .header("If-Match", full_record.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    }
+
+    let filed: serde_json::Value = clnt
+        .post(format!("/api/v1/records/{}/appeals/", record), &appeal)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(filed["status"], "open");
+    assert_eq!(filed["rejected_by"], moderator.user().id.0);
+
+    clnt.post(format!("/api/v1/records/{}/appeals/", record), &appeal)
+        .expect_error(40915)
+        .await;
+
+    clnt.post(format!("/api/v1/records/{}/appeals/", record2), &appeal)
+        .expect_error(42903)
+        .await;
+
+    // The moderator who rejected the record neither sees the appeal nor can they resolve it
+    let queue: Vec<serde_json::Value> = clnt.get("/api/v1/records/appeals/").authorize_as(&moderator).get_result().await;
+
+    assert!(queue.is_empty());
+
+    let queue: Vec<serde_json::Value> = clnt.get("/api/v1/records/appeals/").authorize_as(&admin).get_result().await;
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0]["id"], filed["id"]);
+
+    let appeal_url = format!("/api/v1/records/appeals/{}/", filed["id"]);
+    let etag = clnt
+        .get(&appeal_url)
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await
+        .headers()
+        .get_one("ETag")
+        .unwrap()
+        .to_string();
+
+    clnt.patch(&appeal_url, &serde_json::json!({"status": "denied"}))
+        .authorize_as(&moderator)
+        .header("If-Match", etag.clone())
+        .expect_error(40311)
+        .await;
+
+    let resolved: serde_json::Value = clnt
+        .patch(
+            &appeal_url,
+            &serde_json::json!({"status": "accepted", "resolution_note": "Clicks are fine"}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(resolved["status"], "accepted");
+    assert_eq!(resolved["resolved_by"], admin.user().id.0);
+
+    let record = FullRecord::by_id(RecordId(record), &mut *connection).await.unwrap();
+
+    assert_eq!(record.status, RecordStatus::UnderConsideration);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_grandfathered_progress_below_requirement(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id.0, player1.id.0, &mut *connection).await;
+
+    let submission =
+        serde_json::json! {{"progress": 40, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42215i64));
+    assert_eq!(json["data"]["requirement"].as_i64(), Some(50i64));
+
+    let grandfathered = serde_json::json! {{"progress": 40, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "approved", "grandfathered": true}};
+
+    clnt.post("/api/v1/records/", &grandfathered)
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    clnt.post("/api/v1/records/", &grandfathered)
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &grandfathered)
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.progress, 40);
+    assert_eq!(record.status, RecordStatus::Approved);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_dead_video_queue(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let record_id = add_simple_record(100, player.id.0, demon, RecordStatus::Approved, &mut *connection).await;
+
+    let record: FullRecord = clnt.get(format!("/api/v1/records/{}", record_id)).get_success_result().await;
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}", record_id),
+            &serde_json::json!({"video": "https://youtube.com/watch?v=1234567890"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.video_dead_since, None);
+
+    record_video_check(record_id, false, &mut *connection).await.unwrap();
+
+    clnt.get("/api/v1/records/dead-videos/")
+        .authorize_as(&user)
+        .expect_error(40301)
+        .await;
+
+    let dead: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/dead-videos/")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0]["id"].as_i64(), Some(record_id as i64));
+
+    let record: FullRecord = clnt.get(format!("/api/v1/records/{}", record_id)).get_success_result().await;
+
+    assert!(record.video_dead_since.is_some());
+
+    // Replacing the dead video takes the record out of the queue
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}", record_id),
+            &serde_json::json!({"video": "https://youtube.com/watch?v=1234567891"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.video_dead_since, None);
+
+    let dead: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/dead-videos/")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(dead.is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_stolen_video_detection(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Sonic Wave", 2, 50, player.id.0, player.id.0, &mut *connection).await;
+    let demon3 = pointercrate_test::demonlist::add_demon("Tartarus", 3, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let stolen = add_simple_record(100, player.id.0, demon1, RecordStatus::Approved, &mut *connection).await;
+    let other = add_simple_record(100, player.id.0, demon2, RecordStatus::Approved, &mut *connection).await;
+
+    for (record, video) in [
+        (stolen, "https://www.youtube.com/watch?v=1234567890"),
+        (other, "https://www.youtube.com/watch?v=0987654321"),
+    ] {
+        sqlx::query!("UPDATE records SET video = $1 WHERE id = $2", video, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+        fingerprint::set_channel(record, "https://www.youtube.com/@stardust1971", &mut *connection)
+            .await
+            .unwrap();
+    }
+
+    // Same video, just linked differently (and for a demon without a recent submission, to not run into the cooldown)
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon3, "player": "stardust1972", "video": "https://youtu.be/1234567890"}};
+
+    let submitted: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}", submitted["id"]))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    assert!(record
+        .spam
+        .unwrap()
+        .reasons
+        .contains(&"Video already proves an approved record of a different player".to_string()));
+
+    let reuses: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/{}/video-reuse", record.id))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(reuses.len(), 1);
+    assert_eq!(reuses[0]["id"].as_i64(), Some(stolen as i64));
+    assert_eq!(reuses[0]["same_video"], true);
+
+    // Once the submission's channel is known, other videos from the same channel show up as well
+    fingerprint::set_channel(record.id.0, "https://www.youtube.com/@stardust1971", &mut *connection)
+        .await
+        .unwrap();
+
+    let reuses: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/{}/video-reuse", record.id))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(reuses.len(), 2);
+    assert_eq!(reuses[1]["id"].as_i64(), Some(other as i64));
+    assert_eq!(reuses[1]["same_video"], false);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_assignment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper1 = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let helper2 = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let mut assignees = Vec::new();
+
+    // A player can only have one open submission per demon
+    for name in ["stardust1972", "stardust1973", "stardust1974"] {
+        let submitter = DatabasePlayer::by_name_or_create(name, &mut *connection).await.unwrap();
+        let record = add_simple_record(100, submitter.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+        assignees.push(assignment::assign(record, &mut *connection).await.unwrap());
+    }
+
+    // Whoever has fewer open assignments gets the next one
+    let (id1, id2) = (helper1.user().id.0, helper2.user().id.0);
+
+    assert_eq!(assignees[..2], [Some(id1), Some(id2)]);
+    assert!(assignees[2].is_some());
+
+    clnt.put("/api/v1/records/assigned/opt-out")
+        .authorize_as(&helper1)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    // Opting out hands over all open assignments
+    let assigned: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/assigned")
+        .authorize_as(&helper2)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(assigned.len(), 3);
+
+    // Nobody else is available to take over
+    assert_eq!(assignment::reassign_stale(0, &mut *connection).await.unwrap(), 0);
+
+    clnt.delete("/api/v1/records/assigned/opt-out")
+        .authorize_as(&helper1)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    assert_eq!(assignment::reassign_stale(0, &mut *connection).await.unwrap(), 3);
+
+    let assigned: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/assigned")
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(assigned.len(), 3);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_stale_submissions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    let other = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+
+    let old = add_simple_record(60, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+    let fresh = add_simple_record(70, other.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    for (record, age) in [(old, 10), (fresh, 1)] {
+        sqlx::query!(
+            "INSERT INTO record_additions (userid, id, time) VALUES (0, $1, NOW() - make_interval(days => $2))",
+            record,
+            age
+        )
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    }
+
+    clnt.get("/api/v1/records/stale").authorize_as(&helper).expect_error(40301).await;
+
+    let stale: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/stale")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    // Submissions become stale after a week by default
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0]["id"].as_i64(), Some(old as i64));
+    assert!(stale[0]["pending_for"].as_i64().unwrap() >= 10 * 24 * 60 * 60);
+
+    assert_eq!(stale::stale_submission_count(0, &mut *connection).await.unwrap(), 0);
+    assert_eq!(stale::stale_submission_count(60, &mut *connection).await.unwrap(), 2);
+}
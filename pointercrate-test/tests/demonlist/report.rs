@@ -0,0 +1,47 @@
+use pointercrate_demonlist::{player::DatabasePlayer, LIST_HELPER};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_report_player(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+
+    // "hacked" only applies to records
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v1/reports/",
+            &serde_json::json!({"player": player.id, "category": "hacked", "description": "Impossible"}),
+        )
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42239);
+
+    let report: serde_json::Value = clnt
+        .post(
+            "/api/v1/reports/",
+            &serde_json::json!({"player": player.id, "category": "impersonation", "description": "That's not the real stardust"}),
+        )
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(report["status"], "open");
+
+    let queue: Vec<serde_json::Value> = clnt
+        .get("/api/v1/reports/?status=open")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0]["id"], report["id"]);
+
+    // The review queue is staff-only
+    clnt.get("/api/v1/reports/").expect_status(Status::Unauthorized).execute().await;
+}
@@ -0,0 +1,425 @@
+use pointercrate_core::{
+    etag::Taggable,
+    pool::{audit_connection, retry_on_conflict},
+};
+use pointercrate_demonlist::{
+    demon::{Demon, DemonId, FullDemon},
+    player::DatabasePlayer,
+    record::RecordStatus,
+    settings::SubmissionSettings,
+    staff_activity::StaffActivity,
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_test::demonlist::{add_demon, add_simple_record};
+use rocket::http::Status;
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_staff_activity(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let leader = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let demon2 = add_demon("Bloodlust", 2, 53, player.id.0, player.id.0, &mut *connection).await;
+
+    let reviewed = add_simple_record(100, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+    let pending = add_simple_record(100, player.id.0, demon2, RecordStatus::Submitted, &mut *connection).await;
+
+    audit_connection(&mut *connection, moderator.user().id.0).await.unwrap();
+
+    sqlx::query!("UPDATE records SET status_ = 'APPROVED' WHERE id = $1", reviewed)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    StaffActivity::aggregate(&mut *connection).await.unwrap();
+    // Aggregating twice must not count reviews twice
+    StaffActivity::aggregate(&mut *connection).await.unwrap();
+
+    let activity: serde_json::Value = clnt
+        .get("/api/v1/staff/activity")
+        .authorize_as(&leader)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(activity["weeks"].as_array().unwrap().len(), 1);
+    assert_eq!(activity["weeks"][0]["member"]["id"], moderator.user().id.0);
+    assert_eq!(activity["weeks"][0]["records_reviewed"], 1);
+    assert_eq!(activity["pending_submissions"], 1);
+    assert_eq!(activity["oldest_pending_submission"]["id"], pending);
+
+    // Only list leaders can see these statistics
+    clnt.get("/api/v1/staff/activity")
+        .authorize_as(&moderator)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_close_all_submissions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let leader = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    let settings = SubmissionSettings::load(&mut *connection).await.unwrap();
+
+    let settings: SubmissionSettings = clnt
+        .patch(
+            "/api/v1/staff/submissions",
+            &serde_json::json!({"submissions_open": false, "closed_reason": "List update", "reopen_at": "2999-01-01T00:00:00Z"}),
+        )
+        .authorize_as(&leader)
+        .header("If-Match", settings.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(!settings.submissions_open);
+
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42242));
+    assert_eq!(json["data"]["reason"], "List update");
+    assert_eq!(json["data"]["reopen_at"], "2999-01-01T00:00:00Z");
+
+    // Once the reopen time has passed, submissions are accepted again, even before the scheduler reopened them
+    clnt.patch(
+        "/api/v1/staff/submissions",
+        &serde_json::json!({"reopen_at": "2000-01-01T00:00:00Z"}),
+    )
+    .authorize_as(&leader)
+    .header("If-Match", settings.etag_string())
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    clnt.post("/api/v1/records/", &submission).expect_status(Status::Ok).execute().await;
+
+    assert!(SubmissionSettings::reopen_if_due(&mut *connection).await.unwrap());
+    assert!(SubmissionSettings::load(&mut *connection).await.unwrap().submissions_open);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_queue_tab(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    add_simple_record(100, player.id.0, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    clnt.get("/api/v1/account/records").authorize_as(&user).expect_error(40301).await;
+    clnt.get("/api/v1/account/demons").authorize_as(&helper).expect_error(40301).await;
+
+    let tab: serde_json::Value = clnt
+        .get("/api/v1/account/records")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(tab["submitted"], 1);
+    assert_eq!(tab["under_consideration"], 0);
+    assert_eq!(tab["demons"][0]["id"], demon);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_snapshot(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    add_demon("Bloodlust", 2, 53, player.id.0, player.id.0, &mut *connection).await;
+    add_demon("Sonic Wave", 3, 60, player.id.0, player.id.0, &mut *connection).await;
+
+    clnt.get("/api/v1/staff/snapshot")
+        .authorize_as(&moderator)
+        .expect_error(40301)
+        .await;
+
+    let mut snapshot: serde_json::Value = clnt.get("/api/v1/staff/snapshot").authorize_as(&admin).get_result().await;
+
+    assert_eq!(snapshot["demons"].as_array().unwrap().len(), 3);
+    assert!(sqlx::types::chrono::DateTime::parse_from_rfc3339(snapshot["created_at"].as_str().unwrap()).is_ok());
+
+    // Swap Bloodbath and Bloodlust, change a requirement, add a demon and drop Sonic Wave
+    snapshot["demons"][0]["position"] = 2.into();
+    snapshot["demons"][1]["position"] = 1.into();
+    snapshot["demons"][1]["requirement"] = 50.into();
+    snapshot["demons"][2] = serde_json::json!({
+        "name": "Tartarus",
+        "position": 3,
+        "requirement": 54,
+        "level_id": null,
+        "video": null,
+        "verifier": "Dolphy",
+        "publisher": "Riot",
+        "creators": ["Dolphy"],
+        "score_weight": 1.0,
+        "tier": "extreme_demon"
+    });
+
+    let diff: serde_json::Value = clnt
+        .post("/api/v1/staff/snapshot?dry_run=true", &snapshot)
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert_eq!(diff["added"], serde_json::json!(["Tartarus"]));
+    assert_eq!(diff["missing"], serde_json::json!(["Sonic Wave"]));
+    assert_eq!(diff["unchanged"], 0);
+    assert_eq!(diff["changed"][0]["name"], "Bloodlust");
+    assert_eq!(diff["changed"][0]["fields"], serde_json::json!(["position", "requirement"]));
+    assert_eq!(diff["changed"][1]["name"], "Bloodbath");
+    assert_eq!(diff["changed"][1]["fields"], serde_json::json!(["position"]));
+
+    // A dry run does not change anything
+    let unchanged: serde_json::Value = clnt.get("/api/v1/staff/snapshot").authorize_as(&admin).get_result().await;
+
+    assert_eq!(unchanged["demons"][0]["name"], "Bloodbath");
+    assert_eq!(unchanged["demons"].as_array().unwrap().len(), 3);
+
+    let applied: serde_json::Value = clnt
+        .post("/api/v1/staff/snapshot", &snapshot)
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert_eq!(applied, diff);
+
+    let imported: serde_json::Value = clnt.get("/api/v1/staff/snapshot").authorize_as(&admin).get_result().await;
+    let names: Vec<&str> = imported["demons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|demon| demon["name"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(names, vec!["Bloodlust", "Bloodbath", "Tartarus", "Sonic Wave"]);
+    assert_eq!(imported["demons"][0]["requirement"], 50);
+    assert_eq!(imported["demons"][2]["creators"], serde_json::json!(["Dolphy"]));
+    assert_eq!(imported["demons"][2]["tier"], "extreme_demon");
+
+    snapshot["version"] = 0.into();
+
+    clnt.post("/api/v1/staff/snapshot", &snapshot)
+        .authorize_as(&admin)
+        .expect_error(42256)
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_scheduled_list_updates(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let bloodbath = add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+    let bloodlust = add_demon("Bloodlust", 2, 53, player.id.0, player.id.0, &mut *connection).await;
+
+    let draft: serde_json::Value = clnt
+        .post(
+            "/api/v2/demons/drafts/",
+            &serde_json::json!({"name": "Tartarus", "requirement": 54, "position": 1, "verifier": "Dolphy", "publisher": "Riot"}),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let update = serde_json::json!({
+        "scheduled_for": "2020-01-01T00:00:00Z",
+        "description": "Weekly update",
+        "changes": [
+            {"action": "place", "draft": draft["id"]},
+            {"action": "move", "demon": bloodlust, "position": 2}
+        ]
+    });
+
+    clnt.post("/api/v1/staff/scheduled-updates/", &update)
+        .authorize_as(&moderator)
+        .expect_error(40301)
+        .await;
+
+    clnt.post(
+        "/api/v1/staff/scheduled-updates/",
+        &serde_json::json!({"scheduled_for": "2020-01-01T00:00:00Z", "changes": []}),
+    )
+    .authorize_as(&admin)
+    .expect_error(42258)
+    .await;
+
+    let scheduled: serde_json::Value = clnt
+        .post("/api/v1/staff/scheduled-updates/", &update)
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    assert_eq!(scheduled["changes"], update["changes"]);
+    assert_eq!(scheduled["applied_at"], serde_json::Value::Null);
+
+    let future: serde_json::Value = clnt
+        .post(
+            "/api/v1/staff/scheduled-updates/",
+            &serde_json::json!({"scheduled_for": "2100-01-01T00:00:00Z", "changes": [{"action": "remove", "demon": bloodbath}]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    // Nothing changes until the scheduler picks the update up
+    assert_eq!(Demon::by_id(DemonId(bloodlust), &mut *connection).await.unwrap().base.position, 2);
+
+    // Apply the update the way the scheduler does, on connections nothing was attributed to yet
+    let background_pool = PgPoolOptions::new().connect_with((*pool.connect_options()).clone()).await.unwrap();
+    let applied = retry_on_conflict(&background_pool, 0, |connection| {
+        Box::pin(pointercrate_demonlist::list_update::apply_due(connection))
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(applied, 1);
+
+    let modified_by: Vec<(i32,)> = sqlx::query_as("SELECT userid FROM demon_modifications WHERE id = $1")
+        .bind(bloodbath)
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    // The changes are attributed to whoever scheduled them
+    assert!(!modified_by.is_empty());
+    assert!(modified_by.iter().all(|&(user_id,)| user_id == admin.user().id.0));
+    assert_eq!(Demon::by_id(DemonId(bloodbath), &mut *connection).await.unwrap().base.position, 3);
+    assert_eq!(Demon::by_id(DemonId(bloodlust), &mut *connection).await.unwrap().base.position, 2);
+    assert_eq!(Demon::by_position(1, &mut *connection).await.unwrap().base.name, "Tartarus");
+
+    let scheduled: serde_json::Value = clnt
+        .get(format!("/api/v1/staff/scheduled-updates/{}/", scheduled["id"]))
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert_ne!(scheduled["applied_at"], serde_json::Value::Null);
+    assert_eq!(scheduled["error"], serde_json::Value::Null);
+
+    clnt.delete(format!("/api/v1/staff/scheduled-updates/{}/", scheduled["id"]))
+        .authorize_as(&admin)
+        .expect_error(40913)
+        .await;
+
+    clnt.delete(format!("/api/v1/staff/scheduled-updates/{}/", future["id"]))
+        .authorize_as(&admin)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let updates: Vec<serde_json::Value> = clnt.get("/api/v1/staff/scheduled-updates/").authorize_as(&admin).get_result().await;
+
+    assert_eq!(updates.len(), 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_watches(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon_id = add_demon("Bloodbath", 1, 87, player.id.0, player.id.0, &mut *connection).await;
+
+    clnt.post("/api/v1/staff/watches/", &serde_json::json!({"email": true}))
+        .authorize_as(&helper)
+        .expect_error(42259)
+        .await;
+
+    clnt.post(
+        "/api/v1/staff/watches/",
+        &serde_json::json!({"demon": demon_id, "player": player.id}),
+    )
+    .authorize_as(&helper)
+    .expect_error(42229)
+    .await;
+
+    let watch: serde_json::Value = clnt
+        .post("/api/v1/staff/watches/", &serde_json::json!({"demon": demon_id}))
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    clnt.post("/api/v1/staff/watches/", &serde_json::json!({"demon": demon_id}))
+        .authorize_as(&helper)
+        .expect_error(40914)
+        .await;
+
+    // The moderator making the change is watching the demon too, but should not be notified
+    clnt.post("/api/v1/staff/watches/", &serde_json::json!({"demon": demon_id}))
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    let demon = FullDemon::by_id(DemonId(demon_id), &mut *connection).await.unwrap();
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 50}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let notified = sqlx::query!("SELECT member_id FROM notifications WHERE kind = 'watched_object_modified'")
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(notified.len(), 1);
+    assert_eq!(notified[0].member_id, helper.user().id.0);
+
+    let watches: Vec<serde_json::Value> = clnt.get("/api/v1/staff/watches/").authorize_as(&helper).get_result().await;
+
+    assert_eq!(watches.len(), 1);
+    assert_eq!(watches[0]["demon"], demon_id);
+
+    // Watches of other users cannot be deleted
+    clnt.delete(format!("/api/v1/staff/watches/{}/", watch["id"]))
+        .authorize_as(&moderator)
+        .expect_error(40401)
+        .await;
+
+    clnt.delete(format!("/api/v1/staff/watches/{}/", watch["id"]))
+        .authorize_as(&helper)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let watches: Vec<serde_json::Value> = clnt.get("/api/v1/staff/watches/").authorize_as(&helper).get_result().await;
+
+    assert!(watches.is_empty());
+}
@@ -0,0 +1,89 @@
+use pointercrate_demonlist::{submitter::Submitter, LIST_ADMINISTRATOR, LIST_MODERATOR};
+use pointercrate_user::auth::{legacy::Registration, AuthenticatedUser};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+use std::{net::IpAddr, str::FromStr};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_geo_data_only_visible_to_list_administrators(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let moderator = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "UPDATE members SET permissions = $2::INTEGER::BIT(16) WHERE member_id = $1",
+        moderator.user().id.0,
+        LIST_MODERATOR.bit() as i16
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    let vpn = Submitter::create_submitter(IpAddr::from_str("10.0.0.1").unwrap(), &mut *connection)
+        .await
+        .unwrap();
+    let other_vpn = Submitter::create_submitter(IpAddr::from_str("10.0.0.2").unwrap(), &mut *connection)
+        .await
+        .unwrap();
+
+    sqlx::query!(
+        "UPDATE submitters SET country_code = 'NL', asn = 9009, asn_organization = 'M247 Europe SRL', geo_recorded_at = NOW() WHERE \
+         submitter_id = $1 OR submitter_id = $2",
+        vpn.id.0,
+        other_vpn.id.0
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    clnt.get(format!("/api/v1/submitters/{}/geo", vpn.id))
+        .authorize_as(&moderator)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let geo: serde_json::Value = clnt
+        .get(format!("/api/v1/submitters/{}/geo", vpn.id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(geo["country_code"], "NL");
+    assert_eq!(geo["asn"], 9009);
+
+    let summary: serde_json::Value = clnt
+        .get("/api/v1/submitters/geo")
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(summary[0]["asn"], 9009);
+    assert_eq!(summary[0]["submitters"], 2);
+
+    // Expired data gets deleted
+    sqlx::query!(
+        "UPDATE submitters SET geo_recorded_at = geo_recorded_at - INTERVAL '365 days' WHERE submitter_id = $1",
+        vpn.id.0
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    assert_eq!(Submitter::purge_geo_data(&mut *connection).await.unwrap(), 1);
+
+    let geo = vpn.geo_data(&mut *connection).await.unwrap();
+
+    assert_eq!(geo.data.asn, None);
+    assert_eq!(geo.recorded_at, None);
+}
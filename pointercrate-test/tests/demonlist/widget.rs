@@ -0,0 +1,69 @@
+use pointercrate_demonlist::{player::DatabasePlayer, record::RecordStatus};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_widgets(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let other_player = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id.0, player.id.0, &mut *connection).await;
+
+    for (position, name) in [(2, "Tartarus"), (3, "Sonic Wave"), (4, "Zodiac")] {
+        pointercrate_test::demonlist::add_demon(name, position, 50, player.id.0, player.id.0, &mut *connection).await;
+    }
+
+    let older = pointercrate_test::demonlist::add_simple_record(100, player.id.0, demon_id, RecordStatus::Approved, &mut *connection).await;
+    let newer =
+        pointercrate_test::demonlist::add_simple_record(80, other_player.id.0, demon_id, RecordStatus::Approved, &mut *connection).await;
+    pointercrate_test::demonlist::add_simple_record(90, other_player.id.0, demon_id, RecordStatus::Submitted, &mut *connection).await;
+
+    let top: Vec<serde_json::Value> = clnt
+        .get("/api/v1/widgets/top/")
+        .expect_status(Status::Ok)
+        .expect_header("Access-Control-Allow-Origin", "*")
+        .get_result()
+        .await;
+
+    assert_eq!(top.len(), 3);
+    assert_eq!(top[0]["name"], "Bloodbath");
+    assert_eq!(top[2]["name"], "Sonic Wave");
+
+    let top: Vec<serde_json::Value> = clnt.get("/api/v1/widgets/top/?limit=100").get_result().await;
+
+    assert_eq!(top.len(), 4);
+
+    let records: Vec<serde_json::Value> = clnt
+        .get("/api/v1/widgets/records/")
+        .expect_status(Status::Ok)
+        .expect_header("Access-Control-Allow-Origin", "*")
+        .get_result()
+        .await;
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["id"], newer);
+    assert_eq!(records[1]["id"], older);
+
+    let oembed: serde_json::Value = clnt
+        .get("/api/v1/widgets/oembed/?url=https%3A%2F%2Fexample.com%2Fwidgets%2Frecords%3Flimit%3D5")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(oembed["type"], "rich");
+    assert!(oembed["html"]
+        .as_str()
+        .unwrap()
+        .contains("src=\"https://example.com/widgets/records?limit=5\""));
+
+    clnt.get("/api/v1/widgets/oembed/?url=%2Flist%2F&format=json")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    clnt.get("/api/v1/widgets/oembed/?url=%2Flist%2F&format=xml")
+        .expect_error(50100)
+        .await;
+    clnt.get("/api/v1/widgets/oembed/?url=%2Faccount%2F").expect_error(40400).await;
+}
@@ -0,0 +1,189 @@
+use pointercrate_core::{
+    error::{CoreError, PointercrateError},
+    pool::{retry_on_conflict, PointercratePool, ReadConnection},
+};
+use rocket::tokio;
+use sqlx::{postgres::PgPoolOptions, PgConnection, Pool, Postgres};
+use std::{sync::Arc, time::Duration};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_request_timeout_spans_statements(pool: Pool<Postgres>) {
+    let pool = PointercratePool::from(pool).with_request_timeout(Duration::from_millis(500));
+    let mut connection = pool.connection().await.unwrap();
+
+    // Each of these stays within the time budget on its own, but not both together
+    sqlx::query("SELECT pg_sleep(0.3)").execute(&mut *connection).await.unwrap();
+
+    let err = sqlx::query("SELECT pg_sleep(0.3)").execute(&mut *connection).await.unwrap_err();
+
+    assert_eq!(CoreError::from(err).error_code(), 50004);
+}
+
+/// Fails the current transaction the way postgres does when it detects a conflict with a concurrent one
+async fn raise_conflict(connection: &mut PgConnection, errcode: &str) -> sqlx::Error {
+    sqlx::query(&format!(
+        "DO $$ BEGIN RAISE EXCEPTION 'simulated conflict' USING ERRCODE = '{}'; END $$",
+        errcode
+    ))
+    .execute(connection)
+    .await
+    .unwrap_err()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_conflicts_are_transaction_conflicts(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    for errcode in ["serialization_failure", "deadlock_detected"] {
+        let err = raise_conflict(&mut connection, errcode).await;
+
+        assert_eq!(CoreError::from(err).error_code(), 50006);
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_retry_on_conflict(pool: Pool<Postgres>) {
+    let retries_before = PointercratePool::from(pool.clone()).statistics().transaction_retries;
+    let mut attempts = 0;
+
+    let result = retry_on_conflict(&pool, 0, |connection| {
+        attempts += 1;
+        let attempt = attempts;
+
+        Box::pin(async move {
+            if attempt < 3 {
+                return Err(CoreError::from(raise_conflict(connection, "serialization_failure").await));
+            }
+
+            Ok(attempt)
+        })
+    })
+    .await;
+
+    assert_eq!(result, Ok(3));
+    assert!(PointercratePool::from(pool.clone()).statistics().transaction_retries >= retries_before + 2);
+
+    // Eventually, it gives up
+    let mut attempts = 0;
+
+    let result: Result<(), CoreError> = retry_on_conflict(&pool, 0, |connection| {
+        attempts += 1;
+
+        Box::pin(async move { Err(CoreError::from(raise_conflict(connection, "deadlock_detected").await)) })
+    })
+    .await;
+
+    assert_eq!(result, Err(CoreError::TransactionConflict));
+    assert_eq!(attempts, 5);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_retry_on_conflict_does_not_retry_other_errors(pool: Pool<Postgres>) {
+    let mut attempts = 0;
+
+    let result: Result<(), CoreError> = retry_on_conflict(&pool, 0, |_| {
+        attempts += 1;
+
+        Box::pin(async move { Err(CoreError::InternalServerError) })
+    })
+    .await;
+
+    assert_eq!(result, Err(CoreError::InternalServerError));
+    assert_eq!(attempts, 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_retry_on_conflict_audits_transactions(pool: Pool<Postgres>) {
+    let mut attempts = 0;
+
+    let result = retry_on_conflict(&pool, 42, |connection| {
+        attempts += 1;
+        let attempt = attempts;
+
+        Box::pin(async move {
+            if attempt < 2 {
+                return Err(CoreError::from(raise_conflict(connection, "serialization_failure").await));
+            }
+
+            // Fresh connections from the pool have no active_user table until audit_connection creates it
+            let (user_id,): (i32,) = sqlx::query_as("SELECT id FROM active_user").fetch_one(connection).await?;
+
+            Ok(user_id)
+        })
+    })
+    .await;
+
+    assert_eq!(result, Ok(42));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_request_retry_on_conflict(pool: Pool<Postgres>) {
+    let pool = PointercratePool::from(pool);
+    let transaction = pool.transaction().await.unwrap();
+    let mut attempts = 0;
+
+    let result = pool
+        .retry_on_conflict(transaction, 42, |connection| {
+            attempts += 1;
+            let attempt = attempts;
+
+            Box::pin(async move {
+                if attempt < 2 {
+                    return Err(CoreError::from(raise_conflict(connection, "serialization_failure").await));
+                }
+
+                // Retries happen outside the request's transaction, but are still attributed to its user
+                let (user_id,): (i32,) = sqlx::query_as("SELECT id FROM active_user").fetch_one(connection).await?;
+
+                Ok(user_id)
+            })
+        })
+        .await;
+
+    assert_eq!(result, Ok(42));
+    assert_eq!(attempts, 2);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_overloaded_pool_sheds_load(pool: Pool<Postgres>) {
+    let single_connection = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_with((*pool.connect_options()).clone())
+        .await
+        .unwrap();
+    let pool = Arc::new(PointercratePool::from(single_connection).with_max_waiting(1));
+
+    let connection = pool.connection().await.unwrap();
+
+    // Queues up behind the connection we are holding on to
+    let waiting = tokio::spawn({
+        let pool = Arc::clone(&pool);
+
+        async move { pool.connection().await.map(|_| ()) }
+    });
+
+    while pool.statistics().waiting == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(pool.connection().await.unwrap_err().error_code(), 50302);
+
+    drop(connection);
+
+    assert!(waiting.await.unwrap().is_ok());
+    assert_eq!(pool.statistics().waiting, 0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_read_only_connection_rejects_writes(pool: Pool<Postgres>) {
+    let pool = PointercratePool::from(pool);
+    let mut connection = pool.read_only_connection().await.unwrap();
+
+    let err = sqlx::query("INSERT INTO players (name) VALUES ('stardust1971')")
+        .execute(&mut connection.reader())
+        .await
+        .unwrap_err();
+
+    // read_only_sql_transaction
+    assert_eq!(err.as_database_error().and_then(|err| err.code()).as_deref(), Some("25006"));
+}
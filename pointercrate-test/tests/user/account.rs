@@ -0,0 +1,94 @@
+use pointercrate_user::{ADMINISTRATOR, MODERATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_account_tabs(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let administrator = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    client
+        .get("/api/v1/account/profile")
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let profile: serde_json::Value = client
+        .get("/api/v1/account/profile")
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(profile["user"]["id"], administrator.user().id.0);
+    assert_eq!(profile["permissions"], serde_json::json!(["Moderator", "Administrator"]));
+
+    let users: serde_json::Value = client
+        .get("/api/v1/account/users")
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(users["assignable_permissions"], serde_json::json!(["Moderator"]));
+
+    client.get("/api/v1/account/users").authorize_as(&user).expect_error(40300).await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_permission_matrix(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+
+    client
+        .get("/api/v1/auth/me/permissions")
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let matrix: serde_json::Value = client
+        .get("/api/v1/auth/me/permissions")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(matrix["bits"], MODERATOR.bit());
+    assert_eq!(matrix["permissions"], serde_json::json!(["Moderator"]));
+    assert_eq!(matrix["assignable_permissions"], serde_json::json!([]));
+    assert_eq!(matrix["capabilities"]["can_view_users"], true);
+    assert_eq!(matrix["capabilities"]["can_delete_users"], false);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_unauthorized_vs_forbidden(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+
+    // Missing credentials
+    client
+        .get("/api/v1/users/usage")
+        .expect_status(Status::Unauthorized)
+        .expect_header("WWW-Authenticate", r#"Bearer realm="pointercrate""#)
+        .execute()
+        .await;
+    client.get("/api/v1/users/usage").expect_error(40100).await;
+
+    // Invalid credentials
+    client
+        .get("/api/v1/users/usage")
+        .header("Authorization", "Bearer not-a-token")
+        .expect_status(Status::Unauthorized)
+        .expect_header("WWW-Authenticate", r#"Bearer realm="pointercrate""#)
+        .execute()
+        .await;
+
+    // Insufficient permissions
+    let error = client.get("/api/v1/users/usage").authorize_as(&moderator).expect_error(40301).await;
+
+    assert_eq!(error["Core"]["required"], "Administrator");
+}
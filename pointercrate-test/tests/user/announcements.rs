@@ -0,0 +1,104 @@
+use pointercrate_user::ADMINISTRATOR;
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_announcements(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let administrator = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    client
+        .post(
+            "/api/v1/announcements/",
+            &serde_json::json!({"message": "Submissions are closed this weekend"}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let active: serde_json::Value = client
+        .post(
+            "/api/v1/announcements/",
+            &serde_json::json!({"message": " Submissions are closed this weekend ", "severity": "warning"}),
+        )
+        .authorize_as(&administrator)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    assert_eq!(active["message"], "Submissions are closed this weekend");
+
+    client
+        .post(
+            "/api/v1/announcements/",
+            &serde_json::json!({"message": "Maintenance tomorrow", "starts_at": "2999-01-01T00:00:00Z"}),
+        )
+        .authorize_as(&administrator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    client
+        .post(
+            "/api/v1/announcements/",
+            &serde_json::json!({"message": "Backwards", "starts_at": "2999-01-01T00:00:00Z", "ends_at": "2998-01-01T00:00:00Z"}),
+        )
+        .authorize_as(&administrator)
+        .expect_error(42251)
+        .await;
+
+    // Scheduled announcements are not shown yet
+    let announcements: Vec<serde_json::Value> = client.get("/api/v1/announcements/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(announcements.len(), 1);
+    assert_eq!(announcements[0]["id"], active["id"]);
+    assert_eq!(announcements[0]["severity"], "warning");
+
+    client
+        .get("/api/v1/announcements/?include_inactive=true")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let announcements: Vec<serde_json::Value> = client
+        .get("/api/v1/announcements/?include_inactive=true")
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(announcements.len(), 2);
+
+    client
+        .patch(
+            format!("/api/v1/announcements/{}/", active["id"]),
+            &serde_json::json!({"ends_at": "2000-01-01T00:00:00Z"}),
+        )
+        .authorize_as(&administrator)
+        .expect_error(42251)
+        .await;
+
+    client
+        .patch(
+            format!("/api/v1/announcements/{}/", i32::MAX),
+            &serde_json::json!({"message": "Gone"}),
+        )
+        .authorize_as(&administrator)
+        .expect_error(40401)
+        .await;
+
+    client
+        .delete(format!("/api/v1/announcements/{}/", active["id"]))
+        .authorize_as(&administrator)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let announcements: Vec<serde_json::Value> = client.get("/api/v1/announcements/").expect_status(Status::Ok).get_result().await;
+
+    assert!(announcements.is_empty());
+}
@@ -0,0 +1,224 @@
+use pointercrate_user::{
+    application::{Application, ApplicationCredentials},
+    ADMINISTRATOR, MODERATOR,
+};
+use rocket::http::{ContentType, Status};
+use serde_json::{json, Value};
+use sqlx::{Pool, Postgres};
+
+fn token_request(credentials: &ApplicationCredentials) -> String {
+    format!(
+        "grant_type=client_credentials&client_id={}&client_secret={}",
+        credentials.application.client_id, credentials.client_secret
+    )
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_client_credentials_grant(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let owner = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+
+    // Applications cannot be granted more than their owner has
+    client
+        .post(
+            "/api/v1/applications/",
+            &json!({"name": "Stat site", "scopes": ADMINISTRATOR.bit()}),
+        )
+        .authorize_as(&owner)
+        .expect_error(40305)
+        .await;
+
+    client
+        .post(
+            "/api/v1/applications/",
+            &json!({"name": "Stat site", "redirect_uris": ["http://stats.example.com/callback"]}),
+        )
+        .authorize_as(&owner)
+        .expect_error(42264)
+        .await;
+
+    let credentials: ApplicationCredentials = client
+        .post(
+            "/api/v1/applications/",
+            &json!({"name": "Stat site", "redirect_uris": ["https://stats.example.com/callback"], "scopes": MODERATOR.bit()}),
+        )
+        .authorize_as(&owner)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    assert_eq!(credentials.application.owner, owner.user().id);
+    assert_eq!(credentials.application.scopes, MODERATOR.bit());
+
+    client
+        .post_raw(
+            "/api/v1/auth/token",
+            ContentType::Form,
+            token_request(&credentials).replace("client_credentials", "password"),
+        )
+        .expect_error(42265)
+        .await;
+
+    let response: Value = client
+        .post_raw("/api/v1/auth/token", ContentType::Form, token_request(&credentials))
+        .get_result()
+        .await;
+
+    assert_eq!(response["token_type"], "Bearer");
+    assert_eq!(response["scope"], "Moderator");
+
+    let access_token = response["access_token"].as_str().unwrap().to_string();
+
+    // The application acts on behalf of its owner, restricted to its scopes
+    let me: Value = client
+        .get("/api/v1/auth/me")
+        .header("Authorization", format!("Bearer {}", access_token))
+        .get_success_result()
+        .await;
+
+    assert_eq!(me["id"], owner.user().id.0);
+    assert_eq!(me["permissions"], MODERATOR.bit());
+
+    // Applications cannot manage applications
+    client
+        .get("/api/v1/applications/")
+        .header("Authorization", format!("Bearer {}", access_token))
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let rotated: ApplicationCredentials = client
+        .post(format!("/api/v1/applications/{}/secret", credentials.application.id), &())
+        .authorize_as(&owner)
+        .get_result()
+        .await;
+
+    assert_ne!(rotated.client_secret, credentials.client_secret);
+
+    // Rotating the secret revokes both the old secret and all tokens issued with it
+    client
+        .get("/api/v1/auth/me")
+        .header("Authorization", format!("Bearer {}", access_token))
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    client
+        .post_raw("/api/v1/auth/token", ContentType::Form, token_request(&credentials))
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    client
+        .post_raw("/api/v1/auth/token", ContentType::Form, token_request(&rotated))
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    client
+        .delete(format!("/api/v1/applications/{}", credentials.application.id))
+        .authorize_as(&owner)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let applications: Vec<Application> = client.get("/api/v1/applications/").authorize_as(&owner).get_result().await;
+
+    assert!(applications.is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_application_scopes_follow_owner_permissions(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let owner = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    // Scopes implied by the owner's permissions can be granted
+    let credentials: ApplicationCredentials = client
+        .post("/api/v1/applications/", &json!({"name": "Stat site", "scopes": MODERATOR.bit()}))
+        .authorize_as(&owner)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let response: Value = client
+        .post_raw("/api/v1/auth/token", ContentType::Form, token_request(&credentials))
+        .get_result()
+        .await;
+    let authorization = format!("Bearer {}", response["access_token"].as_str().unwrap());
+
+    let me: Value = client
+        .get("/api/v1/auth/me")
+        .header("Authorization", authorization.clone())
+        .get_success_result()
+        .await;
+
+    assert_eq!(me["permissions"], MODERATOR.bit());
+
+    sqlx::query!(
+        "UPDATE members SET permissions = 0::BIT(16) WHERE member_id = $1",
+        owner.user().id.0
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    // Tokens issued before the owner was demoted no longer carry the lost permissions
+    let me: Value = client
+        .get("/api/v1/auth/me")
+        .header("Authorization", authorization)
+        .get_success_result()
+        .await;
+
+    assert_eq!(me["permissions"], 0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_applications_cannot_modify_owner_account(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let owner = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+
+    let credentials: ApplicationCredentials = client
+        .post("/api/v1/applications/", &json!({"name": "Stat site"}))
+        .authorize_as(&owner)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let response: Value = client
+        .post_raw("/api/v1/auth/token", ContentType::Form, token_request(&credentials))
+        .get_result()
+        .await;
+    let authorization = format!("Bearer {}", response["access_token"].as_str().unwrap());
+
+    // Reading the owner's settings is fine, changing them is not
+    client
+        .get("/api/v1/auth/me/preferences/")
+        .header("Authorization", authorization.clone())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    client
+        .patch("/api/v1/auth/me/preferences/", &json!({"theme": "dark"}))
+        .header("Authorization", authorization.clone())
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    client
+        .patch("/api/v1/auth/me/notifications/digest/", &json!({"frequency": "weekly"}))
+        .header("Authorization", authorization.clone())
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    client
+        .post("/api/v1/auth/me/notifications/read/", &())
+        .header("Authorization", authorization)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
@@ -0,0 +1,23 @@
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_generated_api_reference(pool: Pool<Postgres>) {
+    let (client, _) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let reference: serde_json::Value = client.get("/api/v1/routes/").expect_status(Status::Ok).get_result().await;
+
+    let routes = reference
+        .as_object()
+        .unwrap()
+        .values()
+        .flat_map(|routes| routes.as_array().unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(routes
+        .iter()
+        .any(|route| route["method"] == "PATCH" && route["uri"] == "/api/v1/users/<user_id>"));
+
+    // Page routes are not part of the API reference
+    assert!(routes.iter().all(|route| route["uri"].as_str().unwrap().starts_with("/api/")));
+}
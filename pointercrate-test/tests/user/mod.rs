@@ -1,2 +1,13 @@
+mod account;
+mod announcements;
+mod applications;
+mod ban;
+mod delete;
+mod display_name;
+mod documentation;
 mod login;
+mod notifications;
+mod paginate;
+mod preferences;
 mod register;
+mod usage;
@@ -0,0 +1,144 @@
+use pointercrate_core::etag::Taggable;
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    notification::{Digest, DigestFrequency, NotificationKind},
+    ADMINISTRATOR,
+};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_permission_grant_notifies_user(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    client
+        .patch(
+            format!("/api/v1/users/{}/", user.user().id),
+            &serde_json::json!({"permissions": 0x2000}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let (notifications, _): (Vec<serde_json::Value>, _) = client
+        .get("/api/v1/auth/me/notifications/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .expect_header("X-UNREAD-COUNT", "1")
+        .get_pagination_result()
+        .await;
+
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["kind"], "permissions_granted");
+    assert_eq!(notifications[0]["read"], false);
+    // Timestamps are points in time, and thus carry their offset from UTC
+    assert!(sqlx::types::chrono::DateTime::parse_from_rfc3339(notifications[0]["created_at"].as_str().unwrap()).is_ok());
+
+    client
+        .post("/api/v1/auth/me/notifications/read/", &())
+        .authorize_as(&user)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    client
+        .get("/api/v1/auth/me/notifications/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .expect_header("X-UNREAD-COUNT", "0")
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_notification_digests(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    // Digests are sent via email, so opting in requires an email address
+    client
+        .patch("/api/v1/auth/me/notifications/digest/", &serde_json::json!({"frequency": "daily"}))
+        .authorize_as(&user)
+        .expect_error(42269)
+        .await;
+
+    sqlx::query!(
+        "UPDATE members SET email_address = 'jacob@example.com' WHERE member_id = $1",
+        user.user().id.0
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    client
+        .patch("/api/v1/auth/me/notifications/digest/", &serde_json::json!({"frequency": "daily"}))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let settings: serde_json::Value = client
+        .get("/api/v1/auth/me/notifications/digest/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(settings["frequency"], "daily");
+
+    client
+        .patch(
+            format!("/api/v1/users/{}/", user.user().id),
+            &serde_json::json!({"permissions": 0x2000}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // The first digest is only due a day after opting in
+    assert!(Digest::take_due(&mut *connection).await.unwrap().is_empty());
+
+    sqlx::query!(
+        "UPDATE members SET last_digest_at = last_digest_at - INTERVAL '1 day' WHERE member_id = $1",
+        user.user().id.0
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    let digests = Digest::take_due(&mut *connection).await.unwrap();
+
+    assert_eq!(digests.len(), 1);
+    assert_eq!(digests[0].email_address, "jacob@example.com");
+    assert_eq!(digests[0].frequency, DigestFrequency::Daily);
+    assert_eq!(digests[0].notifications.len(), 1);
+    assert_eq!(digests[0].notifications[0].kind, NotificationKind::PermissionsGranted);
+
+    // The next digest is due a day after this one
+    assert!(Digest::take_due(&mut *connection).await.unwrap().is_empty());
+}
@@ -0,0 +1,62 @@
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    ADMINISTRATOR, MODERATOR,
+};
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_user_search_filters(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let administrator = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "UPDATE members SET permissions = $2::INTEGER::BIT(16), display_name = 'stardust1971' WHERE member_id = $1",
+        user.user().id.0,
+        MODERATOR.bit() as i16
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    let users: Vec<serde_json::Value> = client
+        .get("/api/v1/users/?has_permission=moderator")
+        .authorize_as(&administrator)
+        .get_result()
+        .await;
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["id"], user.user().id.0);
+
+    let users: Vec<serde_json::Value> = client
+        .get("/api/v1/users/?display_name_contains=DUST")
+        .authorize_as(&administrator)
+        .get_result()
+        .await;
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["id"], user.user().id.0);
+
+    let users: Vec<serde_json::Value> = client
+        .get("/api/v1/users/?created_before=2000-01-01T00:00:00Z")
+        .authorize_as(&administrator)
+        .get_result()
+        .await;
+
+    assert!(users.is_empty());
+
+    client
+        .get("/api/v1/users/?has_permission=Wizard")
+        .authorize_as(&administrator)
+        .expect_error(42252)
+        .await;
+}
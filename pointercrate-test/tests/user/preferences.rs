@@ -0,0 +1,76 @@
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_preferences(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    let preferences: serde_json::Value = client
+        .get("/api/v1/auth/me/preferences/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        preferences,
+        serde_json::json!({"locale": null, "timezone": null, "theme": null, "notifications": {"digest": null}})
+    );
+
+    client
+        .patch("/api/v1/auth/me/preferences/", &serde_json::json!({"locale": "not a locale"}))
+        .authorize_as(&user)
+        .expect_error(42270)
+        .await;
+
+    client
+        .patch("/api/v1/auth/me/preferences/", &serde_json::json!({"timezone": "Europe/Berlin"}))
+        .authorize_as(&user)
+        .expect_error(42271)
+        .await;
+
+    // Only known preferences can be stored
+    client
+        .patch("/api/v1/auth/me/preferences/", &serde_json::json!({"font_size": 12}))
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+
+    let preferences: serde_json::Value = client
+        .patch(
+            "/api/v1/auth/me/preferences/",
+            &serde_json::json!({"locale": "pt-BR", "timezone": "-03:00", "theme": "dark"}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(preferences["locale"], "pt-BR");
+    assert_eq!(preferences["timezone"], "-03:00");
+    assert_eq!(preferences["theme"], "dark");
+
+    // Preferences not mentioned in a patch are left untouched
+    let preferences: serde_json::Value = client
+        .patch("/api/v1/auth/me/preferences/", &serde_json::json!({"theme": null}))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(preferences["locale"], "pt-BR");
+    assert_eq!(preferences["theme"], serde_json::Value::Null);
+
+    // Digests require an email address, regardless of how they are enabled
+    client
+        .patch(
+            "/api/v1/auth/me/preferences/",
+            &serde_json::json!({"notifications": {"digest": "weekly"}}),
+        )
+        .authorize_as(&user)
+        .expect_error(42269)
+        .await;
+}
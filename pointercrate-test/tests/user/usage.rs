@@ -0,0 +1,74 @@
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    usage::{ApiUsage, ConsumerUsage},
+    ADMINISTRATOR,
+};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_api_usage_is_tracked(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    for _ in 0..2 {
+        client
+            .get("/api/v1/auth/me")
+            .authorize_as(&user)
+            .expect_status(Status::Ok)
+            .execute()
+            .await;
+    }
+
+    // The request for the statistics counts as well
+    let usage: ApiUsage = client
+        .get("/api/v1/auth/me/usage")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(usage.today, 3);
+    assert_eq!(usage.total, 3);
+    assert_eq!(usage.days.len(), 1);
+
+    // Only administrators get to see the usage of others
+    client
+        .get("/api/v1/users/usage")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let report: Vec<ConsumerUsage> = client
+        .get("/api/v1/users/usage?days=7")
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    // The forbidden request above still counted towards the user's usage
+    assert_eq!(report[0].user_id, user.user().id.0);
+    assert_eq!(report[0].requests, 4);
+    assert_eq!(report[1].user_id, admin.user().id.0);
+    assert_eq!(report[1].requests, 1);
+
+    let usage: ApiUsage = client
+        .get(format!("/api/v1/users/{}/usage", user.user().id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(usage.total, 4);
+}
@@ -3,21 +3,30 @@ use log::{debug, warn};
 use pointercrate_core::{
     error::{CoreError, PointercrateError},
     permission::{Permission, PermissionsManager},
-    pool::{audit_connection, PointercratePool},
+    pool::{audit_connection, PointercratePool, ReadOnlyConnection, RequestTransaction, TransactionFuture},
+    redact::ViewContext,
 };
-use pointercrate_user::{auth::AuthenticatedUser, error::UserError};
+use pointercrate_core_api::{
+    error::{AuthenticationScheme, ErrorResponder},
+    response::ViewPreferences,
+};
+use pointercrate_user::{auth::AuthenticatedUser, error::UserError, preferences::parse_utc_offset, usage};
 use rocket::{
     http::{Method, Status},
     request::{FromRequest, Outcome},
     Request, State,
 };
-use sqlx::{Postgres, Transaction};
+use sqlx::PgConnection;
 use std::collections::HashSet;
 
+/// An authenticated request, together with the transaction it was authenticated in
+///
+/// Endpoints that only ever read data switch the transaction to read-only via [`Auth::read_only`],
+/// after which `connection` is a [`ReadOnlyConnection`].
 #[allow(non_upper_case_globals)]
-pub struct Auth<const IsToken: bool> {
+pub struct Auth<const IsToken: bool, C = RequestTransaction> {
     pub user: AuthenticatedUser,
-    pub connection: Transaction<'static, Postgres>,
+    pub connection: C,
     pub permissions: PermissionsManager,
 
     /* The secret, either token or password */
@@ -30,6 +39,33 @@ impl<const IsToken: bool> Auth<IsToken> {
         self.connection.commit().await.map_err(UserError::from)
     }
 
+    /// Runs the given operation in this request's transaction and commits it. Should the
+    /// transaction conflict with a concurrent one, the operation is retried in a fresh one (see
+    /// [`PointercratePool::retry_on_conflict`])
+    pub async fn retry_on_conflict<T, E, F>(self, pool: &PointercratePool, operation: F) -> Result<T, E>
+    where
+        E: PointercrateError,
+        F: for<'c> FnMut(&'c mut PgConnection) -> TransactionFuture<'c, T, E>,
+    {
+        let user_id = self.user.user().id.0;
+
+        pool.retry_on_conflict(self.connection, user_id, operation).await
+    }
+
+    /// Makes this request's transaction `READ ONLY` for the remainder of the request (see
+    /// [`ReadOnlyConnection::from_transaction`]), for endpoints that only ever read data
+    pub async fn read_only(self) -> Result<Auth<IsToken, ReadOnlyConnection>, UserError> {
+        Ok(Auth {
+            user: self.user,
+            connection: ReadOnlyConnection::from_transaction(self.connection).await?,
+            permissions: self.permissions,
+            secret: self.secret,
+        })
+    }
+}
+
+#[allow(non_upper_case_globals)]
+impl<const IsToken: bool, C> Auth<IsToken, C> {
     pub fn require_permission(&self, permission: Permission) -> Result<(), UserError> {
         self.permissions.require_permission(self.user.user().permissions, permission)?;
 
@@ -40,14 +76,80 @@ impl<const IsToken: bool> Auth<IsToken> {
         self.require_permission(permission).is_ok()
     }
 
+    /// Whether this user has the given capability, see [`PermissionsManager::grants`]
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.permissions.has_capability(self.user.user().permissions, capability)
+    }
+
+    /// Fails with [`CoreError::Forbidden`] if this request was made by an application acting on
+    /// behalf of its owner. Application scopes only cover permissions, so endpoints managing the
+    /// owner's own account have to be closed to applications explicitly
+    pub fn forbid_applications(&self) -> Result<(), UserError> {
+        if self.user.is_application() {
+            return Err(CoreError::Forbidden.into());
+        }
+
+        Ok(())
+    }
+
     pub fn assignable_permissions(&self) -> HashSet<Permission> {
         self.permissions.assignable_by_bits(self.user.user().permissions)
     }
+
+    /// The [`ViewContext`] in which objects returned to this user need to be redacted
+    pub fn view_context(&self) -> ViewContext {
+        ViewContext::new(self.permissions.implied_by_bits(self.user.user().permissions))
+    }
 }
 
 pub type BasicAuth = Auth<false>;
 pub type TokenAuth = Auth<true>;
 
+/// Token authentication for endpoints that only ever read data, see [`Auth::read_only`]
+pub type ReadOnlyTokenAuth = Auth<true, ReadOnlyConnection>;
+
+/// Counts an API request authenticated as the given user towards their usage statistics (see
+/// [`pointercrate_user::usage`]).
+///
+/// This uses a connection from the background pool, as the request's own transaction is never
+/// committed by read-only endpoints, and waiting for a second connection from the request pool
+/// while already holding one could starve it. Failures are only logged, since statistics should
+/// never fail a request.
+async fn track_usage(request: &Request<'_>, pool: &PointercratePool, user_id: i32) {
+    if !request.uri().path().starts_with("/api/") {
+        return;
+    }
+
+    let result = match pool.background_connection().await {
+        Ok(mut connection) => usage::track_request(user_id, &mut connection).await,
+        Err(err) => Err(err.into()),
+    };
+
+    if let Err(err) = result {
+        warn!("Failed to track API usage of user {}: {:?}", user_id, err);
+    }
+}
+
+/// Makes the preferences of the given user available to the [`Page`](pointercrate_core_api::response::Page)
+/// rendered in response to this request, if any. Failures are only logged, since the page can
+/// still be rendered using the default preferences.
+async fn cache_view_preferences(request: &Request<'_>, user: &AuthenticatedUser, connection: &mut PgConnection) {
+    if request.uri().path().starts_with("/api/") {
+        return;
+    }
+
+    match user.user().preferences(connection).await {
+        Ok(preferences) => {
+            request.local_cache(|| ViewPreferences {
+                locale: preferences.locale,
+                theme: preferences.theme.map(|theme| theme.to_string()),
+                utc_offset: preferences.timezone.as_deref().and_then(parse_utc_offset),
+            });
+        },
+        Err(err) => warn!("Failed to load preferences of user {}: {:?}", user.user().id, err),
+    }
+}
+
 macro_rules! try_outcome {
     ($outcome:expr) => {
         match $outcome {
@@ -62,9 +164,11 @@ impl<'r> FromRequest<'r> for Auth<true> {
     type Error = UserError;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        request.local_cache(|| AuthenticationScheme::Bearer);
+
         // No auth header set, forward to the request handler that doesnt require authorization (if one exists)
         if request.headers().get_one("Authorization").is_none() && request.cookies().get("access_token").is_none() {
-            return Outcome::Forward(Status::NotFound);
+            return Outcome::Forward(Status::Unauthorized);
         }
 
         let pool = request.guard::<&State<PointercratePool>>().await;
@@ -79,8 +183,8 @@ impl<'r> FromRequest<'r> for Auth<true> {
             Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
         };
 
-        let mut connection = match pool {
-            Outcome::Success(pool) => try_outcome!(pool.transaction().await),
+        let pool = match pool {
+            Outcome::Success(pool) => pool,
             Outcome::Error(err) => {
                 return Outcome::Error((
                     Status::InternalServerError,
@@ -89,12 +193,16 @@ impl<'r> FromRequest<'r> for Auth<true> {
             },
             Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
         };
+        let mut connection = try_outcome!(pool.transaction().await);
 
         for authorization in request.headers().get("Authorization") {
             if let ["Bearer", token] = authorization.split(' ').collect::<Vec<_>>()[..] {
-                let user = try_outcome!(AuthenticatedUser::token_auth(token, None, &mut *connection).await);
+                let mut user = try_outcome!(AuthenticatedUser::token_auth(token, None, &mut *connection).await);
+
+                user.restrict_scopes(&permission_manager);
 
-                try_outcome!(audit_connection(&mut *connection, user.user().id).await);
+                try_outcome!(audit_connection(&mut *connection, user.user().id.0).await);
+                track_usage(request, pool, user.user().id.0).await;
 
                 return Outcome::Success(Auth {
                     user,
@@ -112,9 +220,13 @@ impl<'r> FromRequest<'r> for Auth<true> {
             if request.method() == Method::Get {
                 debug!("GET request, the cookie is enough");
 
-                let user = try_outcome!(AuthenticatedUser::token_auth(access_token, None, &mut *connection).await);
+                let mut user = try_outcome!(AuthenticatedUser::token_auth(access_token, None, &mut *connection).await);
 
-                try_outcome!(audit_connection(&mut *connection, user.user().id).await);
+                user.restrict_scopes(&permission_manager);
+
+                try_outcome!(audit_connection(&mut *connection, user.user().id.0).await);
+                track_usage(request, pool, user.user().id.0).await;
+                cache_view_preferences(request, &user, &mut *connection).await;
 
                 return Outcome::Success(Auth {
                     user,
@@ -130,9 +242,12 @@ impl<'r> FromRequest<'r> for Auth<true> {
             // :tm:
 
             if let Some(csrf_token) = request.headers().get_one("X-CSRF-TOKEN") {
-                let user = try_outcome!(AuthenticatedUser::token_auth(access_token, Some(csrf_token), &mut *connection).await);
+                let mut user = try_outcome!(AuthenticatedUser::token_auth(access_token, Some(csrf_token), &mut *connection).await);
+
+                user.restrict_scopes(&permission_manager);
 
-                try_outcome!(audit_connection(&mut *connection, user.user().id).await);
+                try_outcome!(audit_connection(&mut *connection, user.user().id.0).await);
+                track_usage(request, pool, user.user().id.0).await;
 
                 return Outcome::Success(Auth {
                     user,
@@ -149,14 +264,29 @@ impl<'r> FromRequest<'r> for Auth<true> {
     }
 }
 
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Auth<true, ReadOnlyConnection> {
+    type Error = UserError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match Auth::<true>::from_request(request).await {
+            Outcome::Success(auth) => Outcome::Success(try_outcome!(auth.read_only().await)),
+            Outcome::Error(err) => Outcome::Error(err),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for Auth<false> {
     type Error = UserError;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        request.local_cache(|| AuthenticationScheme::Basic);
+
         // No auth header set, forward to the request handler that doesnt require authorization (if one exists)
         if request.headers().get_one("Authorization").is_none() {
-            return Outcome::Forward(Status::NotFound);
+            return Outcome::Forward(Status::Unauthorized);
         }
 
         let pool = request.guard::<&State<PointercratePool>>().await;
@@ -197,7 +327,7 @@ impl<'r> FromRequest<'r> for Auth<false> {
                 if let [username, password] = &decoded.splitn(2, ':').collect::<Vec<_>>()[..] {
                     let user = try_outcome!(AuthenticatedUser::basic_auth(username, password, &mut *connection).await);
 
-                    try_outcome!(audit_connection(&mut *connection, user.user().id).await);
+                    try_outcome!(audit_connection(&mut *connection, user.user().id.0).await);
 
                     return Outcome::Success(Auth {
                         user,
@@ -212,3 +342,10 @@ impl<'r> FromRequest<'r> for Auth<false> {
         Outcome::Error((Status::Unauthorized, CoreError::Unauthorized.into()))
     }
 }
+
+/// Answers requests that no endpoint accepted because they lacked credentials (see the
+/// [`FromRequest`] implementations above, which forward such requests with `401 UNAUTHORIZED`)
+#[rocket::catch(401)]
+pub fn catch_401() -> ErrorResponder {
+    CoreError::Unauthorized.into()
+}
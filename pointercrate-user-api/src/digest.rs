@@ -0,0 +1,64 @@
+//! Background job sending [notification digests](pointercrate_user::notification::Digest)
+//!
+//! Like the demonlist scheduler, this is not part of [`setup`](crate::setup), so that integration
+//! tests do not spawn background tasks. Instances attach it explicitly via [`digests`].
+
+use log::{error, info};
+use pointercrate_core::pool::{retry_on_conflict, PointercratePool, TransactionFuture};
+use pointercrate_core_api::mail::{Email, MailerHandle};
+use pointercrate_user::{error::UserError, notification::Digest};
+use rocket::fairing::AdHoc;
+use sqlx::PgConnection;
+use std::time::Duration;
+
+/// Fairing that starts sending out digests once rocket has launched
+pub fn digests() -> AdHoc {
+    AdHoc::on_liftoff("Notification digests", |rocket| {
+        Box::pin(async move {
+            let pool = rocket.state::<PointercratePool>().unwrap().clone_background();
+            let mailer = rocket.state::<MailerHandle>().unwrap().clone();
+
+            rocket::tokio::spawn(async move {
+                let mut interval = rocket::tokio::time::interval(Duration::from_secs(900));
+
+                loop {
+                    interval.tick().await;
+
+                    // Mails are only dispatched once the digests have been marked as sent, so that a
+                    // retried transaction can never send a digest twice
+                    let digests = match retry_on_conflict(&pool, 0, take_due_digests).await {
+                        Ok(digests) => digests,
+                        Err(err) => {
+                            error!("Background job 'notification digests' failed: {:?}", err);
+
+                            continue;
+                        },
+                    };
+
+                    if !digests.is_empty() {
+                        info!("Sending {} notification digests", digests.len());
+                    }
+
+                    for digest in digests {
+                        let notifications = digest
+                            .notifications
+                            .iter()
+                            .map(|notification| notification.content.clone())
+                            .collect::<Vec<_>>();
+
+                        mailer.dispatch(Email::notification_digest(
+                            digest.email_address,
+                            &digest.username,
+                            digest.frequency,
+                            &notifications,
+                        ));
+                    }
+                }
+            });
+        })
+    })
+}
+
+fn take_due_digests(connection: &mut PgConnection) -> TransactionFuture<'_, Vec<Digest>, UserError> {
+    Box::pin(Digest::take_due(connection))
+}
@@ -0,0 +1,54 @@
+//! Endpoints providing the data needed by the individual tabs of the account page, allowing them
+//! to be loaded lazily. Each endpoint is gated the same way as the tab it belongs to.
+
+use crate::auth::TokenAuth;
+use pointercrate_core::{
+    error::CoreError,
+    permission::{Permission, PermissionsManager},
+};
+use pointercrate_core_api::error::Result;
+use pointercrate_user::User;
+use rocket::{serde::json::Json, State};
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct ProfileTab {
+    pub user: User,
+
+    /// All permissions the user has, including implied ones
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UsersTab {
+    /// The permissions the user is allowed to assign to (and revoke from) other users
+    pub assignable_permissions: Vec<Permission>,
+}
+
+pub(crate) fn sorted(permissions: impl IntoIterator<Item = Permission>) -> Vec<Permission> {
+    let mut permissions = permissions.into_iter().collect::<Vec<_>>();
+    permissions.sort_by_key(|perm| perm.bit());
+    permissions
+}
+
+#[rocket::get("/profile")]
+pub fn profile(auth: TokenAuth, permissions: &State<PermissionsManager>) -> Json<ProfileTab> {
+    let user = auth.user.into_user();
+
+    Json(ProfileTab {
+        permissions: sorted(permissions.implied_by_bits(user.permissions)),
+        user,
+    })
+}
+
+#[rocket::get("/users")]
+pub fn users(auth: TokenAuth, permissions: &State<PermissionsManager>) -> Result<Json<UsersTab>> {
+    let assignable_permissions = sorted(permissions.assignable_by_bits(auth.user.user().permissions));
+
+    // Users that cannot assign any permissions have no business managing other accounts
+    if assignable_permissions.is_empty() {
+        return Err(CoreError::Forbidden.into());
+    }
+
+    Ok(Json(UsersTab { assignable_permissions }))
+}
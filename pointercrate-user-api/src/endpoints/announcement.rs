@@ -0,0 +1,109 @@
+use crate::auth::{ReadOnlyTokenAuth, TokenAuth};
+use log::error;
+use pointercrate_core::{
+    announcement::{Announcement, AnnouncementCache, PatchAnnouncement, PostAnnouncement},
+    error::CoreError,
+    pool::PointercratePool,
+};
+use pointercrate_core_api::{cache::CachePurge, documentation::documented, error::Result, query::Query, response::Response2};
+use pointercrate_user::ADMINISTRATOR;
+use rocket::{http::Status, serde::json::Json, State};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct AnnouncementQuery {
+    /// Whether to also return scheduled and already ended announcements. Only available to
+    /// administrators
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+/// Brings the [`AnnouncementCache`] up to date with the database. Failures are only logged, since
+/// they should not prevent startup, and modifications have already been committed by the time the
+/// cache is reloaded
+pub(crate) async fn reload_cache(pool: &PointercratePool, cache: &AnnouncementCache) {
+    let result = match pool.connection().await {
+        Ok(mut connection) => cache.reload(&mut connection).await,
+        Err(err) => Err(err),
+    };
+
+    if let Err(err) = result {
+        error!("Failed to reload announcements: {:?}", err);
+    }
+}
+
+/// The announcements that are currently shown on all pages. Administrators can also retrieve
+/// scheduled and already ended ones via `include_inactive=true`
+#[documented(example = "/api/v1/announcements/")]
+#[rocket::get("/")]
+pub async fn list(
+    cache: &State<AnnouncementCache>, query: Query<AnnouncementQuery>, auth: Option<ReadOnlyTokenAuth>,
+) -> Result<Json<Vec<Announcement>>> {
+    if !query.0.include_inactive {
+        return Ok(Json(cache.active()));
+    }
+
+    let mut auth = auth.ok_or(CoreError::Unauthorized)?;
+
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Json(Announcement::all(&mut auth.connection).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(
+    mut auth: TokenAuth, data: Json<PostAnnouncement>, pool: &State<PointercratePool>, cache: &State<AnnouncementCache>,
+    responses: CachePurge<'_>,
+) -> Result<Response2<Json<Announcement>>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let announcement = Announcement::create(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    reload_cache(pool, cache).await;
+    // Announcements are shown on every page
+    responses.purge_all();
+
+    let location = format!("/api/v1/announcements/{}/", announcement.id);
+
+    Ok(Response2::json(announcement)
+        .status(Status::Created)
+        .with_header("Location", location))
+}
+
+#[rocket::patch("/<announcement_id>", data = "<patch>")]
+pub async fn patch(
+    announcement_id: i32, mut auth: TokenAuth, patch: Json<PatchAnnouncement>, pool: &State<PointercratePool>,
+    cache: &State<AnnouncementCache>, responses: CachePurge<'_>,
+) -> Result<Json<Announcement>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let announcement = Announcement::by_id(announcement_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+    reload_cache(pool, cache).await;
+    responses.purge_all();
+
+    Ok(Json(announcement))
+}
+
+#[rocket::delete("/<announcement_id>")]
+pub async fn delete(
+    announcement_id: i32, mut auth: TokenAuth, pool: &State<PointercratePool>, cache: &State<AnnouncementCache>, responses: CachePurge<'_>,
+) -> Result<Status> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Announcement::by_id(announcement_id, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+    reload_cache(pool, cache).await;
+    responses.purge_all();
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,155 @@
+use crate::{
+    auth::{ReadOnlyTokenAuth, TokenAuth},
+    ratelimits::UserRatelimits,
+};
+use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
+use pointercrate_core_api::{error::Result, etag::Tagged, response::Response2};
+use pointercrate_user::{
+    application::{Application, ApplicationCredentials, ApplicationId, PostApplication},
+    auth::{AuthenticatedUser, APPLICATION_TOKEN_LIFETIME},
+    error::UserError,
+    User, UserId,
+};
+use rocket::{form::Form, http::Status, serde::json::Json, FromForm, State};
+use serde::Serialize;
+use std::net::IpAddr;
+
+#[derive(FromForm, Debug)]
+pub struct TokenRequest<'r> {
+    grant_type: &'r str,
+    client_id: &'r str,
+    client_secret: &'r str,
+}
+
+/// Response format for access token requests, as specified in RFC 6749, Section 5.1
+#[derive(Serialize, Debug)]
+pub struct TokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: u64,
+
+    /// Space separated names of the permissions granted to the token
+    scope: String,
+}
+
+/// Fails with [`UserError::PermissionNotAssignable`] unless all permissions in `scopes` are implied
+/// by `owner_permissions`
+fn ensure_scopes_grantable(permissions: &PermissionsManager, owner_permissions: u16, scopes: u16) -> Result<()> {
+    let implied_bitmask = permissions
+        .implied_by_bits(owner_permissions)
+        .iter()
+        .fold(0x0, |mask, perm| mask | perm.bit());
+
+    if scopes & implied_bitmask != scopes {
+        return Err(UserError::PermissionNotAssignable {
+            non_assignable: permissions.bits_to_permissions(scopes & !implied_bitmask),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Exchanges client credentials for an access token (OAuth 2 client credentials grant, see RFC 6749,
+/// Section 4.4)
+#[rocket::post("/token", data = "<request>")]
+pub async fn token(
+    request: Form<TokenRequest<'_>>, ip: IpAddr, pool: &State<PointercratePool>, permissions: &State<PermissionsManager>,
+    ratelimits: &State<UserRatelimits>,
+) -> Result<Json<TokenResponse>> {
+    if request.grant_type != "client_credentials" {
+        return Err(UserError::UnsupportedGrantType {
+            grant_type: request.grant_type.to_string(),
+        }
+        .into());
+    }
+
+    ratelimits.token_requests(ip)?;
+
+    let mut connection = pool.connection().await?;
+
+    let application = AuthenticatedUser::client_credentials(request.client_id, request.client_secret, &mut connection).await?;
+    let scopes = application.user().permissions;
+
+    // The owner might have lost some of their permissions since registering the application
+    let owner = User::by_id(UserId(application.user().id.0), &mut connection).await?;
+
+    ensure_scopes_grantable(permissions, owner.permissions, scopes)?;
+
+    let mut scope: Vec<_> = permissions
+        .bits_to_permissions(scopes)
+        .into_iter()
+        .map(|perm| perm.name().to_string())
+        .collect();
+    scope.sort();
+
+    Ok(Json(TokenResponse {
+        access_token: application.generate_access_token(),
+        token_type: "Bearer",
+        expires_in: APPLICATION_TOKEN_LIFETIME,
+        scope: scope.join(" "),
+    }))
+}
+
+#[rocket::get("/")]
+pub async fn list(mut auth: ReadOnlyTokenAuth) -> Result<Json<Vec<Application>>> {
+    // Applications cannot manage applications, as that would allow them to mint credentials
+    // outliving their own
+    auth.forbid_applications()?;
+
+    Ok(Json(Application::owned_by(auth.user.user().id.0, &mut auth.connection).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn register(mut auth: TokenAuth, data: Json<PostApplication>) -> Result<Response2<Json<ApplicationCredentials>>> {
+    auth.forbid_applications()?;
+    ensure_scopes_grantable(&auth.permissions, auth.user.user().permissions, data.scopes)?;
+
+    let credentials = Application::create(data.0, auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let location = format!("/api/v1/applications/{}/", credentials.application.id);
+
+    Ok(Response2::json(credentials)
+        .status(Status::Created)
+        .with_header("Location", location))
+}
+
+#[rocket::get("/<application_id>")]
+pub async fn get(application_id: i32, mut auth: ReadOnlyTokenAuth) -> Result<Tagged<Application>> {
+    auth.forbid_applications()?;
+
+    Ok(Tagged(
+        Application::by_id(ApplicationId(application_id), auth.user.user().id.0, &mut auth.connection).await?,
+    ))
+}
+
+/// Generates a new client secret, revoking all access tokens issued with the old one
+#[rocket::post("/<application_id>/secret")]
+pub async fn rotate_secret(application_id: i32, mut auth: TokenAuth) -> Result<Json<ApplicationCredentials>> {
+    auth.forbid_applications()?;
+
+    let credentials = Application::by_id(ApplicationId(application_id), auth.user.user().id.0, &mut auth.connection)
+        .await?
+        .rotate_secret(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(credentials))
+}
+
+#[rocket::delete("/<application_id>")]
+pub async fn delete(application_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.forbid_applications()?;
+
+    Application::by_id(ApplicationId(application_id), auth.user.user().id.0, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
@@ -1,26 +1,39 @@
 use crate::{
-    auth::{BasicAuth, TokenAuth},
+    auth::{BasicAuth, ReadOnlyTokenAuth, TokenAuth},
+    endpoints::account::sorted,
     ratelimits::UserRatelimits,
 };
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{
+    error::CoreError,
+    etag::Taggable,
+    permission::{Permission, PermissionsManager},
+};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, Tagged},
+    pagination::pagination_response,
+    query::Query,
     response::Response2,
 };
-use pointercrate_user::{auth::AuthenticatedUser, auth::PatchMe, error::UserError, User};
+use pointercrate_user::{
+    auth::AuthenticatedUser,
+    auth::PatchMe,
+    error::UserError,
+    notification::{Digest, DigestSettings, Notification, NotificationId, NotificationPagination, PatchNotification},
+    preferences::{PatchPreferences, Preferences},
+    usage::{ApiUsage, UsageReportQuery},
+    User,
+};
 use rocket::{
     http::Status,
     serde::json::{serde_json, Json},
     State,
 };
-use std::net::IpAddr;
+use serde::Serialize;
+use std::{collections::BTreeMap, net::IpAddr};
 
 #[cfg(feature = "legacy_accounts")]
-use {
-    pointercrate_core::pool::PointercratePool,
-    pointercrate_user::auth::legacy::{LegacyAuthenticatedUser, Registration},
-};
+use {pointercrate_core::pool::PointercratePool, pointercrate_user::auth::legacy::Registration};
 
 #[cfg(feature = "legacy_accounts")]
 #[rocket::post("/register", data = "<body>")]
@@ -31,9 +44,6 @@ pub async fn register(
 
     ratelimits.soft_registrations(ip)?;
 
-    LegacyAuthenticatedUser::validate_password(&body.password)?;
-    User::validate_name(&body.name)?;
-
     let user = AuthenticatedUser::register(body.0, &mut *connection).await?;
 
     ratelimits.registrations(ip)?;
@@ -65,6 +75,8 @@ pub async fn login(
 pub async fn invalidate(mut auth: BasicAuth) -> Result<Status> {
     match auth.user {
         AuthenticatedUser::Legacy(legacy) => legacy.invalidate_all_tokens(auth.secret, &mut auth.connection).await?,
+        // Tokens of applications are revoked by rotating their client secret
+        AuthenticatedUser::Application(_) => return Err(CoreError::Forbidden.into()),
     }
     auth.connection.commit().await.map_err(UserError::from)?;
 
@@ -76,9 +88,47 @@ pub fn get_me(auth: TokenAuth) -> Tagged<User> {
     Tagged(auth.user.into_user())
 }
 
+/// The number of API requests the logged in user made over the last few days, summed over all
+/// of their access tokens
+#[rocket::get("/me/usage")]
+pub async fn usage(mut auth: ReadOnlyTokenAuth, query: Query<UsageReportQuery>) -> Result<Json<ApiUsage>> {
+    Ok(Json(
+        ApiUsage::of(auth.user.user().id.0, query.0.days(), &mut auth.connection).await?,
+    ))
+}
+
+#[derive(Serialize, Debug)]
+pub struct PermissionMatrix {
+    /// The raw permission bitmask of the user
+    pub bits: u16,
+
+    /// All permissions the user has, including implied ones
+    pub permissions: Vec<Permission>,
+
+    /// The permissions the user is allowed to assign to (and revoke from) other users
+    pub assignable_permissions: Vec<Permission>,
+
+    /// Whether the user is allowed to perform each of the capabilities known to the server
+    pub capabilities: BTreeMap<&'static str, bool>,
+}
+
+/// Resolves the permissions of the logged in user into everything they are allowed to do, using the
+/// same [`PermissionsManager`] that all endpoints check permissions against
+#[rocket::get("/me/permissions")]
+pub fn permissions(auth: TokenAuth, permissions: &State<PermissionsManager>) -> Json<PermissionMatrix> {
+    let bits = auth.user.user().permissions;
+
+    Json(PermissionMatrix {
+        bits,
+        permissions: sorted(permissions.implied_by_bits(bits)),
+        assignable_permissions: sorted(permissions.assignable_by_bits(bits)),
+        capabilities: permissions.capabilities_of_bits(bits),
+    })
+}
+
 #[rocket::patch("/me", data = "<patch>")]
 pub async fn patch_me(mut auth: BasicAuth, patch: Json<PatchMe>, pred: Precondition) -> Result<std::result::Result<Tagged<User>, Status>> {
-    pred.require_etag_match(auth.user.user())?;
+    pred.require_etag_match(&User::lock_by_id(auth.user.user().id, &mut auth.connection).await?)?;
 
     let changes_password = patch.changes_password();
 
@@ -95,10 +145,90 @@ pub async fn patch_me(mut auth: BasicAuth, patch: Json<PatchMe>, pred: Precondit
 
 #[rocket::delete("/me")]
 pub async fn delete_me(mut auth: BasicAuth, pred: Precondition) -> Result<Status> {
-    pred.require_etag_match(auth.user.user())?;
+    pred.require_etag_match(&User::lock_by_id(auth.user.user().id, &mut auth.connection).await?)?;
 
     auth.user.delete(&mut auth.connection).await?;
     auth.connection.commit().await.map_err(UserError::from)?;
 
     Ok(Status::NoContent)
 }
+
+#[rocket::get("/me/preferences")]
+pub async fn preferences(mut auth: ReadOnlyTokenAuth) -> Result<Json<Preferences>> {
+    Ok(Json(auth.user.user().preferences(&mut auth.connection).await?))
+}
+
+#[rocket::patch("/me/preferences", data = "<patch>")]
+pub async fn patch_preferences(mut auth: TokenAuth, patch: Json<PatchPreferences>) -> Result<Json<Preferences>> {
+    auth.forbid_applications()?;
+
+    let preferences = auth.user.user().patch_preferences(patch.0, &mut auth.connection).await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(Json(preferences))
+}
+
+#[rocket::get("/me/notifications")]
+pub async fn notifications(
+    mut auth: ReadOnlyTokenAuth, query: Query<NotificationPagination>,
+) -> Result<Response2<Json<Vec<Notification>>>> {
+    let mut pagination = query.0;
+
+    pagination.user_id = auth.user.user().id.0;
+
+    let unread = Notification::unread_count(pagination.user_id, &mut auth.connection).await?;
+
+    Ok(
+        pagination_response("/api/v1/auth/me/notifications", pagination, &mut auth.connection)
+            .await?
+            .with_header("X-UNREAD-COUNT", unread.to_string()),
+    )
+}
+
+#[rocket::patch("/me/notifications/<notification_id>", data = "<patch>")]
+pub async fn patch_notification(
+    mut auth: TokenAuth, notification_id: i32, patch: Json<PatchNotification>, pred: Precondition,
+) -> Result<Tagged<Notification>> {
+    auth.forbid_applications()?;
+
+    let notification = Notification::by_id(NotificationId(notification_id), auth.user.user().id.0, &mut auth.connection).await?;
+
+    pred.require_etag_match(&notification)?;
+
+    let notification = notification.apply_patch(patch.0, &mut auth.connection).await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(Tagged(notification))
+}
+
+#[rocket::post("/me/notifications/read")]
+pub async fn read_notifications(mut auth: TokenAuth) -> Result<Status> {
+    auth.forbid_applications()?;
+
+    Notification::mark_all_read(auth.user.user().id.0, &mut auth.connection).await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::get("/me/notifications/digest")]
+pub async fn digest_settings(mut auth: ReadOnlyTokenAuth) -> Result<Json<DigestSettings>> {
+    Ok(Json(DigestSettings {
+        frequency: Digest::frequency_of(auth.user.user().id.0, &mut auth.connection).await?,
+    }))
+}
+
+/// Opts into (or out of) periodic email digests of unread notifications
+#[rocket::patch("/me/notifications/digest", data = "<settings>")]
+pub async fn patch_digest_settings(mut auth: TokenAuth, settings: Json<DigestSettings>) -> Result<Json<DigestSettings>> {
+    auth.forbid_applications()?;
+
+    Digest::set_frequency(auth.user.user().id.0, settings.frequency, &mut auth.connection).await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(settings)
+}
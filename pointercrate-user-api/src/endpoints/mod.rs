@@ -1,2 +1,5 @@
+pub(crate) mod account;
+pub(crate) mod announcement;
+pub(crate) mod application;
 pub(crate) mod auth;
 pub(crate) mod user;
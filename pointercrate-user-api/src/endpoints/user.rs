@@ -1,18 +1,27 @@
-use crate::auth::TokenAuth;
+use crate::auth::{ReadOnlyTokenAuth, TokenAuth};
 use log::info;
-use pointercrate_core::error::CoreError;
+use pointercrate_core::{error::CoreError, patch::Patch, pool::PointercratePool, redact::Redacted};
 use pointercrate_core_api::{
+    documentation::documented,
     error::Result,
     etag::{Precondition, Tagged},
-    pagination::pagination_response,
+    mail::{Email, MailerHandle},
+    pagination::redacted_pagination_response,
     query::Query,
     response::Response2,
 };
-use pointercrate_user::{error::UserError, PatchUser, User, UserPagination, ADMINISTRATOR, MODERATOR};
-use rocket::{http::Status, serde::json::Json};
+use pointercrate_user::{
+    error::UserError,
+    usage::{self, ApiUsage, ConsumerUsage, UsageReportQuery},
+    DisplayNameChange, PatchUser, User, UserId, UserPagination, ADMINISTRATOR, MODERATOR,
+};
+use rocket::{http::Status, serde::json::Json, State};
+
+/// The maximal number of users listed in a usage report
+const USAGE_REPORT_LIMIT: i64 = 100;
 
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, data: Query<UserPagination>) -> Result<Response2<Json<Vec<User>>>> {
+pub async fn paginate(mut auth: ReadOnlyTokenAuth, data: Query<UserPagination>) -> Result<Response2<Json<Vec<Redacted<User>>>>> {
     let mut pagination = data.0;
     // Rule of thumb: If you can assign permissions, you can see all users that currently have those
     // permissions
@@ -21,6 +30,15 @@ pub async fn paginate(mut auth: TokenAuth, data: Query<UserPagination>) -> Resul
         return Err(CoreError::Forbidden.into());
     }
 
+    if let Some(name) = pagination.has_permission.take() {
+        let permission = auth
+            .permissions
+            .by_name(&name)
+            .ok_or(UserError::UnknownPermission { permission: name })?;
+
+        pagination.has_permissions = Some(pagination.has_permissions.unwrap_or(0) | permission.bit());
+    }
+
     // Pointercrate staff need to be able to see all users, not only those whose permissions they can
     // assign
     if !auth.has_permission(MODERATOR) {
@@ -32,12 +50,14 @@ pub async fn paginate(mut auth: TokenAuth, data: Query<UserPagination>) -> Resul
         }
     }
 
-    Ok(pagination_response("/api/v1/users", pagination, &mut auth.connection).await?)
+    let context = auth.view_context();
+
+    Ok(redacted_pagination_response("/api/v1/users", pagination, &context, &mut auth.connection).await?)
 }
 
 #[rocket::get("/<user_id>")]
-pub async fn get_user(mut auth: TokenAuth, user_id: i32) -> Result<Tagged<User>> {
-    let user = User::by_id(user_id, &mut auth.connection).await?;
+pub async fn get_user(mut auth: ReadOnlyTokenAuth, user_id: i32) -> Result<Tagged<Redacted<User>>> {
+    let user = User::by_id(UserId(user_id), &mut auth.connection).await?;
 
     // We are only allowed to retrieve users who already have permissions we can set.
     if !auth.has_permission(MODERATOR) && !auth.has_permission(ADMINISTRATOR) {
@@ -49,31 +69,61 @@ pub async fn get_user(mut auth: TokenAuth, user_id: i32) -> Result<Tagged<User>>
         }
     }
 
-    Ok(Tagged(user))
+    Ok(Tagged(Redacted::new(user, &auth.view_context())))
 }
 
-#[rocket::patch("/<user_id>", data = "<patch>")]
-pub async fn patch_user(mut auth: TokenAuth, precondition: Precondition, user_id: i32, mut patch: Json<PatchUser>) -> Result<Tagged<User>> {
-    let user = User::by_id(user_id, &mut auth.connection).await?;
+/// The heaviest API consumers over the last few days. Only accessible to administrators
+#[rocket::get("/usage")]
+pub async fn usage_report(mut auth: ReadOnlyTokenAuth, query: Query<UsageReportQuery>) -> Result<Json<Vec<ConsumerUsage>>> {
+    auth.require_permission(ADMINISTRATOR)?;
 
-    if !auth.has_permission(MODERATOR) && !auth.has_permission(ADMINISTRATOR) {
-        let can_assign_any = auth.assignable_permissions().iter().any(|perm| user.has_permission(*perm));
+    Ok(Json(
+        usage::top_consumers(query.0.days(), USAGE_REPORT_LIMIT, &mut auth.connection).await?,
+    ))
+}
 
-        if !can_assign_any {
-            // don't leak information about what users exist
-            return Err(UserError::UserNotFound { user_id }.into());
-        }
-    }
+/// The API usage of a single user over the last few days. Only accessible to administrators
+#[rocket::get("/<user_id>/usage")]
+pub async fn user_usage(mut auth: ReadOnlyTokenAuth, user_id: i32, query: Query<UsageReportQuery>) -> Result<Json<ApiUsage>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let user = User::by_id(UserId(user_id), &mut auth.connection).await?;
+
+    Ok(Json(ApiUsage::of(user.id.0, query.0.days(), &mut auth.connection).await?))
+}
+
+/// Previous display names of a user, most recent first. Only accessible to moderators
+#[rocket::get("/<user_id>/display-names")]
+pub async fn display_name_history(mut auth: ReadOnlyTokenAuth, user_id: i32) -> Result<Response2<Json<Vec<DisplayNameChange>>>> {
+    auth.require_permission(MODERATOR)?;
+
+    let user = User::by_id(UserId(user_id), &mut auth.connection).await?;
+
+    Ok(Response2::json(user.display_name_history(&mut auth.connection).await?))
+}
 
-    if patch.youtube_channel.is_some() || patch.display_name.is_some() {
-        auth.require_permission(MODERATOR)?;
+/// Modifies a user. Changing permissions is limited to those the requesting user can assign,
+/// everything else requires the Moderator permission.
+///
+/// The exception are bans (`banned`, together with `ban_reason` and `banned_until`), which
+/// everyone with the `can_ban_users` capability can issue and lift, so list moderators as well as
+/// site moderators. Users who are not site moderators cannot ban users holding permissions they do
+/// not have themselves
+#[documented]
+#[rocket::patch("/<user_id>", data = "<patch>")]
+pub async fn patch_user(
+    auth: TokenAuth, precondition: Precondition, user_id: i32, patch: Json<PatchUser>, pool: &State<PointercratePool>,
+    mailer: &State<MailerHandle>,
+) -> Result<Tagged<Redacted<User>>> {
+    for permission in patch.required_permissions() {
+        auth.require_permission(permission)?;
     }
 
-    if let Some(ref mut permissions) = patch.permissions {
-        let assignable_bitmask = auth.assignable_permissions().iter().fold(0x0, |mask, perm| mask | perm.bit());
+    let assignable_bitmask = auth.assignable_permissions().iter().fold(0x0, |mask, perm| mask | perm.bit());
 
-        if *permissions & assignable_bitmask != *permissions {
-            let unassignable_permissions = (*permissions & assignable_bitmask) ^ *permissions;
+    if let Some(permissions) = patch.permissions {
+        if permissions & assignable_bitmask != permissions {
+            let unassignable_permissions = (permissions & assignable_bitmask) ^ permissions;
 
             return Err(UserError::PermissionNotAssignable {
                 non_assignable: auth.permissions.bits_to_permissions(unassignable_permissions),
@@ -83,42 +133,100 @@ pub async fn patch_user(mut auth: TokenAuth, precondition: Precondition, user_id
 
         info!("assignable permissions are {:b}", assignable_bitmask);
         info!("assigned permissions are {:b}", permissions);
-        info!("User currently has permissions {:b}", user.permissions);
-
-        // we clear all the assignable bits in the user's permissions bitstring. Since we already verified
-        // that permissions is a subset of assignable_permissions, we can then set the new permissions via
-        // simple OR
-        *permissions |= user.permissions & !assignable_bitmask;
-    }
-
-    if user_id == auth.user.user().id {
-        return Err(UserError::PatchSelf.into());
     }
 
-    precondition.require_etag_match(&user)?;
+    let is_moderator = auth.has_permission(MODERATOR) || auth.has_permission(ADMINISTRATOR);
+    let is_ban = patch.banned.is_some();
 
-    let user = user.apply_patch(patch.0, &mut auth.connection).await?;
+    if is_ban && !auth.has_capability("can_ban_users") {
+        return Err(CoreError::Forbidden.into());
+    }
 
-    auth.commit().await?;
+    // The bits of all permissions the requesting user has, directly or implied
+    let held_bitmask = auth
+        .permissions
+        .implied_by_bits(auth.user.user().permissions)
+        .iter()
+        .fold(0x0, |mask, perm| mask | perm.bit());
+    let is_self = UserId(user_id) == auth.user.user().id;
+    let permissions = auth.permissions.clone();
+    let context = auth.view_context();
+
+    let (user, old_permissions, email_address) = auth
+        .retry_on_conflict(pool, |connection| {
+            let precondition = precondition.clone();
+            let mut patch = patch.0.clone();
+
+            Box::pin(async move {
+                let user = User::lock_by_id(UserId(user_id), connection).await?;
+
+                // don't leak information about what users exist
+                if !is_moderator && !is_ban && user.permissions & assignable_bitmask == 0 {
+                    return Err(UserError::UserNotFound { user_id });
+                }
+
+                // list moderators must not be able to lock out the staff above them
+                if is_ban && !is_moderator && user.permissions & !held_bitmask != 0 {
+                    return Err(CoreError::Forbidden.into());
+                }
+
+                if is_self {
+                    return Err(UserError::PatchSelf);
+                }
+
+                // we clear all the assignable bits in the user's permissions bitstring. Since we already
+                // verified that permissions is a subset of assignable_permissions, we can then set the new
+                // permissions via simple OR
+                if let Some(ref mut permissions) = patch.permissions {
+                    info!("User currently has permissions {:b}", user.permissions);
+
+                    *permissions |= user.permissions & !assignable_bitmask;
+                }
+
+                precondition.require_etag_match(&user)?;
+
+                let old_permissions = user.permissions;
+                let user = user.apply_patch(patch, connection).await?;
+                let email_address = user.email_address(connection).await?;
+
+                Ok((user, old_permissions, email_address))
+            })
+        })
+        .await?;
+
+    let granted = permissions
+        .bits_to_permissions(user.permissions & !old_permissions)
+        .into_iter()
+        .map(|perm| perm.name().to_string())
+        .collect::<Vec<_>>();
+
+    if let (Some(email_address), false) = (email_address, granted.is_empty()) {
+        mailer.dispatch(Email::permissions_granted(email_address, user.name(), &granted));
+    }
 
-    Ok(Tagged(user))
+    Ok(Tagged(Redacted::new(user, &context)))
 }
 
 #[rocket::delete("/<user_id>")]
-pub async fn delete_user(mut auth: TokenAuth, precondition: Precondition, user_id: i32) -> Result<Status> {
+pub async fn delete_user(auth: TokenAuth, precondition: Precondition, user_id: i32, pool: &State<PointercratePool>) -> Result<Status> {
     auth.require_permission(ADMINISTRATOR)?;
 
-    if user_id == auth.user.user().id {
+    if UserId(user_id) == auth.user.user().id {
         return Err(UserError::DeleteSelf.into());
     }
 
-    let to_delete = User::by_id(user_id, &mut auth.connection).await?;
+    auth.retry_on_conflict(pool, |connection| {
+        let precondition = precondition.clone();
 
-    precondition.require_etag_match(&to_delete)?;
+        Box::pin(async move {
+            let to_delete = User::lock_by_id(UserId(user_id), connection).await?;
 
-    to_delete.delete(&mut auth.connection).await?;
+            precondition.require_etag_match(&to_delete)?;
 
-    auth.commit().await?;
+            to_delete.delete(connection).await
+        })
+    })
+    .await?;
 
     Ok(Status::NoContent)
 }
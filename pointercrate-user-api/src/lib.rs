@@ -1,8 +1,12 @@
 use crate::ratelimits::UserRatelimits;
 
-use rocket::{Build, Rocket};
+use pointercrate_core::{announcement::AnnouncementCache, pool::PointercratePool};
+use rocket::{fairing::AdHoc, Build, Rocket};
+
+pub use self::digest::digests;
 
 pub mod auth;
+mod digest;
 mod endpoints;
 mod pages;
 mod ratelimits;
@@ -10,30 +14,77 @@ mod ratelimits;
 pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
     let ratelimits = UserRatelimits::new();
 
-    let mut auth_routes = rocket::routes![
+    let mut auth_routes = pointercrate_core_api::routes![
         endpoints::auth::login,
         endpoints::auth::invalidate,
+        endpoints::application::token,
         endpoints::auth::get_me,
+        endpoints::auth::permissions,
+        endpoints::auth::usage,
         endpoints::auth::patch_me,
         endpoints::auth::delete_me,
+        endpoints::auth::preferences,
+        endpoints::auth::patch_preferences,
+        endpoints::auth::notifications,
+        endpoints::auth::patch_notification,
+        endpoints::auth::read_notifications,
+        endpoints::auth::digest_settings,
+        endpoints::auth::patch_digest_settings,
     ];
     let mut page_routes = rocket::routes![pages::login_page, pages::account_page, pages::login];
     #[cfg(feature = "legacy_accounts")]
-    auth_routes.extend(rocket::routes![endpoints::auth::register]);
+    auth_routes.extend(pointercrate_core_api::routes![endpoints::auth::register]);
     #[cfg(feature = "legacy_accounts")]
     page_routes.extend(rocket::routes![pages::register]);
 
     rocket
         .manage(ratelimits)
+        .manage(AnnouncementCache::default())
+        .attach(AdHoc::on_ignite("Announcements", |rocket| {
+            Box::pin(async move {
+                if let (Some(pool), Some(cache)) = (rocket.state::<PointercratePool>(), rocket.state::<AnnouncementCache>()) {
+                    endpoints::announcement::reload_cache(pool, cache).await;
+                }
+
+                rocket
+            })
+        }))
+        .register("/", rocket::catchers![auth::catch_401])
         .mount("/api/v1/auth/", auth_routes)
         .mount(
             "/api/v1/users/",
-            rocket::routes![
+            pointercrate_core_api::routes![
                 endpoints::user::paginate,
                 endpoints::user::get_user,
+                endpoints::user::display_name_history,
+                endpoints::user::usage_report,
+                endpoints::user::user_usage,
                 endpoints::user::patch_user,
                 endpoints::user::delete_user
             ],
         )
+        .mount(
+            "/api/v1/account/",
+            pointercrate_core_api::routes![endpoints::account::profile, endpoints::account::users],
+        )
+        .mount(
+            "/api/v1/applications/",
+            pointercrate_core_api::routes![
+                endpoints::application::list,
+                endpoints::application::register,
+                endpoints::application::get,
+                endpoints::application::rotate_secret,
+                endpoints::application::delete
+            ],
+        )
+        .mount(
+            "/api/v1/announcements/",
+            pointercrate_core_api::routes![
+                endpoints::announcement::list,
+                endpoints::announcement::post,
+                endpoints::announcement::patch,
+                endpoints::announcement::delete
+            ],
+        )
         .mount("/", page_routes)
 }
@@ -1,8 +1,8 @@
 use crate::{
-    auth::{BasicAuth, TokenAuth},
+    auth::{BasicAuth, ReadOnlyTokenAuth, TokenAuth},
     ratelimits::UserRatelimits,
 };
-use pointercrate_core::permission::PermissionsManager;
+use pointercrate_core::{permission::PermissionsManager, pool::ReadConnection};
 use pointercrate_core_api::response::Page;
 use pointercrate_core_pages::head::HeadLike;
 use pointercrate_user::error::UserError;
@@ -18,11 +18,7 @@ use std::net::IpAddr;
 #[cfg(feature = "legacy_accounts")]
 use {
     pointercrate_core::pool::PointercratePool,
-    pointercrate_user::{
-        auth::legacy::{LegacyAuthenticatedUser, Registration},
-        auth::AuthenticatedUser,
-        User,
-    },
+    pointercrate_user::{auth::legacy::Registration, auth::AuthenticatedUser},
     rocket::serde::json::Json,
 };
 
@@ -64,9 +60,6 @@ pub async fn register(
 
     ratelimits.soft_registrations(ip)?;
 
-    LegacyAuthenticatedUser::validate_password(&registration.password)?;
-    User::validate_name(&registration.name)?;
-
     ratelimits.registrations(ip)?;
 
     let user = AuthenticatedUser::register(registration.0, &mut *connection).await?;
@@ -89,13 +82,13 @@ pub async fn register(
 
 #[rocket::get("/account")]
 pub async fn account_page(
-    auth: Option<TokenAuth>, permissions: &State<PermissionsManager>, tabs: &State<AccountPageConfig>,
+    auth: Option<ReadOnlyTokenAuth>, permissions: &State<PermissionsManager>, tabs: &State<AccountPageConfig>,
 ) -> Result<Page, Redirect> {
     match auth {
         Some(mut auth) => {
             let csrf_token = auth.user.generate_csrf_token();
 
-            Ok(Page::new(tabs.account_page(auth.user, permissions, &mut auth.connection).await).meta("csrf_token", csrf_token))
+            Ok(Page::new(tabs.account_page(auth.user, permissions, &mut auth.connection.reader()).await).meta("csrf_token", csrf_token))
         },
         None => Err(Redirect::to(rocket::uri!(login_page))),
     }
@@ -1,11 +1,12 @@
-use maud::{html, Markup, PreEscaped};
-use pointercrate_core::{etag::Taggable, permission::PermissionsManager};
+use maud::{Markup, PreEscaped};
+use pointercrate_core::{etag::Taggable, permission::PermissionsManager, pool::Reader};
 use pointercrate_core_pages::{
     head::{HeadLike, Script},
+    template::{self, Context},
     PageFragment,
 };
 use pointercrate_user::auth::AuthenticatedUser;
-use sqlx::PgConnection;
+use serde::Serialize;
 
 pub mod profile;
 pub mod users;
@@ -20,7 +21,7 @@ pub trait AccountPageTab {
 
     fn tab_id(&self) -> u8;
     fn tab(&self) -> Markup;
-    async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, connection: &mut PgConnection) -> Markup;
+    async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, connection: &mut Reader<'_>) -> Markup;
 }
 
 pub struct AccountPageConfig {
@@ -40,7 +41,7 @@ impl AccountPageConfig {
     }
 
     pub async fn account_page(
-        &self, user: AuthenticatedUser, permissions: &PermissionsManager, connection: &mut PgConnection,
+        &self, user: AuthenticatedUser, permissions: &PermissionsManager, connection: &mut Reader<'_>,
     ) -> AccountPage {
         let mut page = AccountPage {
             user,
@@ -64,6 +65,14 @@ impl AccountPageConfig {
     }
 }
 
+/// What the `user/account/page.html` template gets to know about each tab of the account page
+#[derive(Serialize)]
+struct RenderedTab<'a> {
+    id: u8,
+    tab: &'a str,
+    content: &'a str,
+}
+
 pub struct AccountPage {
     user: AuthenticatedUser,
     scripts: Vec<Script>,
@@ -90,37 +99,21 @@ impl From<AccountPage> for PageFragment {
 
 impl AccountPage {
     fn body(&self) -> Markup {
-        html! {
-            div.tab-display #account-tabber {
-                div.tab-selection.flex.wrap.m-center.fade style="text-align: center;" {
-                    @for (i, (tab, _, _, id)) in self.tabs.iter().enumerate() {
-                        @if i == 0 {
-                            div.tab.tab-active.button.white.hover.no-shadow data-tab-id=(id) {
-                                (*tab)
-                            }
-                        }
-                        @else {
-                            div.tab.button.white.hover.no-shadow data-tab-id=(id) {
-                                (*tab)
-                            }
-                        }
-                    }
-                }
-
-                @for (i, (_, content, _, id)) in self.tabs.iter().enumerate() {
-                    @if i == 0 {
-                        div.m-center.flex.tab-content.tab-content-active.container data-tab-id = (id){
-                            (*content)
-                        }
-                    }
-                    @else {
-                        div.m-center.flex.tab-content.container data-tab-id = (id){
-                            (*content)
-                        }
-                    }
-                }
-            }
-        }
+        let tabs = self
+            .tabs
+            .iter()
+            .map(|(tab, content, _, id)| RenderedTab {
+                id: *id,
+                tab: &tab.0,
+                content: &content.0,
+            })
+            .collect::<Vec<_>>();
+
+        let mut context = Context::new();
+
+        context.insert("tabs", &tabs);
+
+        template::render("user/account/page.html", &context)
     }
 
     fn initialization_script(&self) -> String {
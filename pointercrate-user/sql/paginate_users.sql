@@ -1,4 +1,4 @@
-SELECT member_id, name, permissions::INTEGER, display_name::TEXT, youtube_channel::TEXT
+SELECT member_id, name, permissions::INTEGER, display_name::TEXT, youtube_channel::TEXT, banned, ban_reason, banned_until, version
 FROM members
 WHERE (member_id < $1 OR $1 IS NULL)
   AND (member_id > $2 OR $2 is NULL)
@@ -7,6 +7,9 @@ WHERE (member_id < $1 OR $1 IS NULL)
   AND (permissions & CAST($6::INTEGER AS BIT(16)) = CAST($6::INTEGER AS BIT(16)) OR $6 IS NULL)
   AND (permissions & CAST($7::INTEGER AS BIT(16)) <> 0::BIT(16) OR $7 IS NULL)
   AND (STRPOS(name, $8::CITEXT) > 0 OR $8 is NULL)
+  AND (STRPOS(display_name::CITEXT, $9::CITEXT) > 0 OR $9 IS NULL)
+  AND (created_at >= $10 OR $10 IS NULL)
+  AND (created_at < $11 OR $11 IS NULL)
 ORDER BY member_id {}
-LIMIT $9
+LIMIT $12
 -- This entire query works because every comparison with NULL not done via IS evaluated to NULL, and NULL is false-y
\ No newline at end of file
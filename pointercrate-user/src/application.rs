@@ -0,0 +1,225 @@
+//! Third-party applications registered to access the API
+//!
+//! Community sites and bots should not need to borrow a member's access token to use the API.
+//! Instead, any member can register an application, which gets its own client credentials. These
+//! can be exchanged for short-lived access tokens via the OAuth 2 client credentials grant (see
+//! [`AuthenticatedUser::client_credentials`](crate::auth::AuthenticatedUser::client_credentials)).
+//!
+//! Requests made with such tokens act on behalf of the application's owner, but only with the
+//! permissions in the application's [`scopes`](Application::scopes). Rotating the client secret (or
+//! deleting the application) revokes all tokens issued to it.
+
+use crate::{
+    error::{Result, UserError},
+    UserId,
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use pointercrate_core::{
+    error::CoreError,
+    etag::Taggable,
+    pool::ReadConnection,
+    validate::{validated, Validate, Validator},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use url::Url;
+
+pointercrate_core::id_type!(
+    /// The ID of an [`Application`]
+    ApplicationId
+);
+
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct Application {
+    pub id: ApplicationId,
+    pub name: String,
+
+    /// The id of the member that registered this application
+    pub owner: UserId,
+
+    /// The public half of this application's client credentials
+    pub client_id: String,
+
+    /// The URIs this application may redirect to once it authenticates its users. Not used by the
+    /// client credentials grant itself
+    pub redirect_uris: Vec<String>,
+
+    /// Bitmask of the permissions tokens issued to this application are granted. These can never
+    /// exceed the permissions of the owner
+    pub scopes: u16,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Taggable for Application {}
+
+/// An application together with its client secret
+///
+/// Only the hash of the secret is stored, so it is only ever returned right after registering an
+/// application or rotating its secret.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplicationCredentials {
+    #[serde(flatten)]
+    pub application: Application,
+    pub client_secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostApplication {
+    name: String,
+
+    #[serde(default)]
+    redirect_uris: Vec<String>,
+
+    #[serde(default)]
+    pub scopes: u16,
+}
+
+impl Validate for PostApplication {
+    type Error = UserError;
+
+    fn normalize(&mut self) {
+        self.name = self.name.trim().to_string();
+    }
+
+    fn validate(&self, validator: &mut Validator<UserError>) {
+        validator.length("name", &self.name, 1..=64, || UserError::InvalidApplicationName);
+
+        for uri in &self.redirect_uris {
+            validator.check("redirect_uris", is_valid_redirect_uri(uri), || UserError::InvalidRedirectUri {
+                uri: uri.clone(),
+            });
+        }
+    }
+}
+
+/// Redirect URIs need to be absolute `https` URLs without fragment. Plain `http` is only allowed
+/// for `localhost`, to allow for local development
+fn is_valid_redirect_uri(uri: &str) -> bool {
+    match Url::parse(uri) {
+        Ok(url) if url.fragment().is_none() => matches!(
+            (url.scheme(), url.host_str()),
+            ("https", Some(_)) | ("http", Some("localhost" | "127.0.0.1"))
+        ),
+        _ => false,
+    }
+}
+
+/// Generates a new client secret and its hash
+async fn generate_secret(connection: &mut PgConnection) -> Result<(String, String)> {
+    let secret = sqlx::query!(r#"SELECT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', '') AS "secret!""#)
+        .fetch_one(connection)
+        .await?
+        .secret;
+
+    // See LegacyAuthenticatedUser::set_password for why errors here are bugs
+    let hash = bcrypt::hash(&secret, bcrypt::DEFAULT_COST).map_err(|_| CoreError::internal_server_error("bcrypt library bug"))?;
+
+    Ok((secret, hash))
+}
+
+impl Application {
+    /// Gets the application with the given id, if it is owned by the given member
+    pub async fn by_id(
+        ApplicationId(application_id): ApplicationId, owner: i32, connection: &mut impl ReadConnection,
+    ) -> Result<Application> {
+        let connection = &mut connection.reader();
+        let row = sqlx::query!(
+            r#"SELECT id, name, owner, client_id, redirect_uris, scopes::INTEGER AS "scopes!", created_at FROM applications WHERE id = $1 AND
+               owner = $2"#,
+            application_id,
+            owner
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(UserError::ApplicationNotFound { application_id })?;
+
+        Ok(Application {
+            id: ApplicationId(row.id),
+            name: row.name,
+            owner: UserId(row.owner),
+            client_id: row.client_id,
+            redirect_uris: row.redirect_uris,
+            scopes: row.scopes as u16,
+            created_at: row.created_at,
+        })
+    }
+
+    /// All applications registered by the given member, oldest first
+    pub async fn owned_by(owner: i32, connection: &mut impl ReadConnection) -> Result<Vec<Application>> {
+        let connection = &mut connection.reader();
+        let ids = sqlx::query!("SELECT id FROM applications WHERE owner = $1 ORDER BY id", owner)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut applications = Vec::new();
+
+        for row in ids {
+            applications.push(Application::by_id(ApplicationId(row.id), owner, &mut *connection).await?);
+        }
+
+        Ok(applications)
+    }
+
+    /// Registers a new application owned by the given member
+    ///
+    /// The caller is responsible for checking that the requested scopes do not exceed the
+    /// permissions of the owner.
+    pub async fn create(data: PostApplication, owner: i32, connection: &mut PgConnection) -> Result<ApplicationCredentials> {
+        let data = validated(data)?;
+        let (client_secret, hash) = generate_secret(&mut *connection).await?;
+
+        let id = sqlx::query!(
+            "INSERT INTO applications (name, owner, client_secret_hash, redirect_uris, scopes) VALUES ($1, $2, $3, $4, \
+             CAST($5::INTEGER AS BIT(16))) RETURNING id",
+            data.name,
+            owner,
+            hash,
+            &data.redirect_uris,
+            data.scopes as i32
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        let application = Application::by_id(ApplicationId(id), owner, connection).await?;
+
+        info!(
+            "Member {} registered application {} (ID: {})",
+            owner, application.name, application.id
+        );
+
+        Ok(ApplicationCredentials {
+            application,
+            client_secret,
+        })
+    }
+
+    /// Replaces the client secret of this application, revoking all access tokens issued with the
+    /// old one
+    pub async fn rotate_secret(self, connection: &mut PgConnection) -> Result<ApplicationCredentials> {
+        let (client_secret, hash) = generate_secret(&mut *connection).await?;
+
+        sqlx::query!("UPDATE applications SET client_secret_hash = $1 WHERE id = $2", hash, self.id.0)
+            .execute(connection)
+            .await?;
+
+        info!("Rotated client secret of application {} (ID: {})", self.name, self.id);
+
+        Ok(ApplicationCredentials {
+            application: self,
+            client_secret,
+        })
+    }
+
+    /// Deletes this application, revoking all access tokens issued to it
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM applications WHERE id = $1", self.id.0)
+            .execute(connection)
+            .await?;
+
+        info!("Deleted application {} (ID: {})", self.name, self.id);
+
+        Ok(())
+    }
+}
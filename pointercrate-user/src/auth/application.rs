@@ -0,0 +1,171 @@
+//! Authentication of [`Application`](crate::application::Application)s via the OAuth 2 client
+//! credentials grant
+
+use crate::{
+    auth::AuthenticatedUser,
+    error::{Result, UserError},
+    User,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Validation};
+use log::{info, warn};
+use pointercrate_core::{error::CoreError, permission::PermissionsManager};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long (in seconds) access tokens issued to applications stay valid
+pub const APPLICATION_TOKEN_LIFETIME: u64 = 3600;
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+pub struct ApplicationClaims {
+    /// The id of the application this token was issued to
+    pub app: i32,
+    pub exp: u64,
+}
+
+/// An application acting on behalf of its owner
+///
+/// The wrapped [`User`] is the application's owner, but with its permissions replaced by the
+/// application's scopes.
+pub struct ApplicationAuthenticatedUser {
+    user: User,
+    application_id: i32,
+    client_secret_hash: String,
+
+    /// The permissions the owner currently has, which can be fewer than when the application was
+    /// registered
+    owner_permissions: u16,
+}
+
+impl ApplicationAuthenticatedUser {
+    pub fn into_user(self) -> User {
+        self.user
+    }
+
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub fn application_id(&self) -> i32 {
+        self.application_id
+    }
+
+    /// Tokens are signed with the hash of the current client secret, so that rotating the secret
+    /// revokes all of them
+    pub(super) fn salt(&self) -> Vec<u8> {
+        self.client_secret_hash.as_bytes().to_vec()
+    }
+
+    fn verify(&self, client_secret: &str) -> Result<()> {
+        match bcrypt::verify(client_secret, &self.client_secret_hash) {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                warn!("Wrong client secret for application {}", self.application_id);
+
+                Err(CoreError::Unauthorized.into())
+            },
+            Err(err) => {
+                log::error!(
+                    "Internal Error during client secret verification for application {}: {:?}",
+                    self.application_id,
+                    err
+                );
+
+                Err(CoreError::Unauthorized.into())
+            },
+        }
+    }
+
+    pub(super) fn generate_access_token(&self, secret: &[u8]) -> String {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).expect("time went backwards");
+
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &ApplicationClaims {
+                app: self.application_id,
+                exp: since_epoch.as_secs() + APPLICATION_TOKEN_LIFETIME,
+            },
+            &EncodingKey::from_secret(secret),
+        )
+        .unwrap()
+    }
+}
+
+impl AuthenticatedUser {
+    /// Whether this is an application acting on behalf of a user, instead of the user themselves
+    pub fn is_application(&self) -> bool {
+        matches!(self, AuthenticatedUser::Application(_))
+    }
+
+    /// Authenticates an application using its client credentials
+    ///
+    /// Checking that the application's scopes do not exceed its owner's permissions is up to the
+    /// caller, as this requires a [`PermissionsManager`](pointercrate_core::permission::PermissionsManager).
+    pub async fn client_credentials(client_id: &str, client_secret: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
+        info!("We are expected to perform client credentials authentication");
+
+        let application = Self::application_by(None, Some(client_id), connection).await?;
+
+        application.verify(client_secret)?;
+
+        AuthenticatedUser::Application(application).ensure_not_banned()
+    }
+
+    /// Restricts the scopes of an application to the permissions its owner currently has (including
+    /// implied ones), so that revoking a permission from the owner also revokes it from their
+    /// applications' access tokens. Does nothing for users authenticating themselves
+    pub fn restrict_scopes(&mut self, permissions: &PermissionsManager) {
+        if let AuthenticatedUser::Application(application) = self {
+            let grantable = permissions
+                .implied_by_bits(application.owner_permissions)
+                .iter()
+                .fold(0x0, |mask, perm| mask | perm.bit());
+
+            application.user.permissions &= grantable;
+        }
+    }
+
+    pub(in crate::auth) async fn application_token_auth(
+        access_token: &str, application_id: i32, connection: &mut PgConnection,
+    ) -> Result<Self> {
+        let user = AuthenticatedUser::Application(Self::application_by(Some(application_id), None, connection).await?);
+
+        // Unlike access tokens of users, these expire
+        let mut validation = Validation::default();
+        validation.required_spec_claims = ["exp".to_string()].into();
+
+        jsonwebtoken::decode::<ApplicationClaims>(access_token, &DecodingKey::from_secret(&user.jwt_secret()), &validation).map_err(
+            |err| {
+                warn!("Token validation FAILED for application {}: {}", application_id, err);
+
+                UserError::from(CoreError::Unauthorized)
+            },
+        )?;
+
+        user.ensure_not_banned()
+    }
+
+    async fn application_by(
+        application_id: Option<i32>, client_id: Option<&str>, connection: &mut PgConnection,
+    ) -> Result<ApplicationAuthenticatedUser> {
+        let row = sqlx::query!(
+            r#"SELECT applications.id AS application_id, client_secret_hash, scopes::INTEGER AS permissions,
+                      members.permissions::INTEGER AS owner_permissions, member_id, members.name,
+                      display_name, youtube_channel::text, banned, ban_reason, banned_until, version
+               FROM applications INNER JOIN members ON members.member_id = applications.owner
+               WHERE applications.id = $1 OR applications.client_id = $2"#,
+            application_id,
+            client_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(CoreError::Unauthorized)?;
+
+        Ok(ApplicationAuthenticatedUser {
+            application_id: row.application_id,
+            client_secret_hash: row.client_secret_hash,
+            owner_permissions: row.owner_permissions.unwrap() as u16,
+            user: construct_from_row!(row),
+        })
+    }
+}
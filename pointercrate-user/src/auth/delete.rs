@@ -1,13 +1,44 @@
 //! Deleting your own account
 
-use crate::{auth::AuthenticatedUser, error::Result};
-use log::warn;
+use crate::{auth::AuthenticatedUser, error::Result, DELETED_USER_ID};
+use log::{info, warn};
 use sqlx::PgConnection;
 
 impl AuthenticatedUser {
+    /// Deletes this account and anonymizes all references to it
+    ///
+    /// Audit log entries (and with them the authorship of record notes) are not deleted, as that
+    /// would leave holes in the list's history. Instead, they are reassigned to the placeholder
+    /// [`DELETED_USER_ID`], and the display name and YouTube channel snapshots kept in the user
+    /// audit log are cleared.
+    ///
+    /// Must be called inside a transaction
     pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
         warn!("Self-Deleting user account {}", self.user());
 
-        self.into_user().delete(connection).await
+        let user_id = self.user().id;
+
+        self.into_user().delete(&mut *connection).await?;
+
+        // Since audit_log2 is the parent of all audit log tables, this updates every audit log
+        // entry, including the ones generated by the deletion above.
+        let anonymized = sqlx::query!("UPDATE audit_log2 SET userid = $1 WHERE userid = $2", DELETED_USER_ID, user_id.0)
+            .execute(&mut *connection)
+            .await?;
+
+        sqlx::query!(
+            "UPDATE user_modifications SET display_name = NULL, youtube_channel = NULL WHERE id = $1",
+            user_id.0
+        )
+        .execute(connection)
+        .await?;
+
+        info!(
+            "Anonymized {} audit log entries while deleting account with id {}",
+            anonymized.rows_affected(),
+            user_id
+        );
+
+        Ok(())
     }
 }
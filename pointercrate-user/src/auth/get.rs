@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 
 use crate::{
-    auth::{AccessClaims, AuthenticatedUser},
-    error::Result,
+    auth::{AccessClaims, ApplicationClaims, AuthenticatedUser},
+    error::{Result, UserError},
     User,
 };
 use jsonwebtoken::{DecodingKey, Validation};
@@ -21,16 +21,28 @@ impl AuthenticatedUser {
         no_validation.validate_exp = false;
         no_validation.required_spec_claims = HashSet::new();
 
-        let AccessClaims { id, .. } = jsonwebtoken::decode(access_token, &DecodingKey::from_secret(b""), &no_validation)
-            .map_err(|_| CoreError::Unauthorized)?
-            .claims;
+        let user = match jsonwebtoken::decode::<ApplicationClaims>(access_token, &DecodingKey::from_secret(b""), &no_validation) {
+            Ok(token_data) => {
+                debug!("The token was issued to application {}, validating...", token_data.claims.app);
 
-        debug!("The token identified the user with id {}, validating...", id);
+                Self::application_token_auth(access_token, token_data.claims.app, connection).await?
+            },
+            Err(_) => {
+                let AccessClaims { id, .. } = jsonwebtoken::decode(access_token, &DecodingKey::from_secret(b""), &no_validation)
+                    .map_err(|_| CoreError::Unauthorized)?
+                    .claims;
 
-        // Note that at this point we haven't validated the access token OR the csrf token yet.
-        // However, the key they are signed with encompasses the password salt for the user they supposedly
-        // identify, so we need to retrieve that.
-        let user = Self::by_id(id, connection).await?.validate_access_token(access_token)?;
+                debug!("The token identified the user with id {}, validating...", id);
+
+                // Note that at this point we haven't validated the access token OR the csrf token yet.
+                // However, the key they are signed with encompasses the password salt for the user they supposedly
+                // identify, so we need to retrieve that.
+                Self::by_id(id, connection)
+                    .await?
+                    .validate_access_token(access_token)?
+                    .ensure_not_banned()?
+            },
+        };
 
         if let Some(csrf_token) = csrf_token {
             user.validate_csrf_token(csrf_token)?
@@ -39,9 +51,22 @@ impl AuthenticatedUser {
         Ok(user)
     }
 
+    /// Loads the account with the given name without performing any authentication.
+    ///
+    /// Only meant for administrative tooling running with direct database access (for example
+    /// resetting passwords from the command line), never call this while handling a request!
+    pub async fn without_authentication(name: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
+        match AuthenticatedUser::by_name(name, connection).await {
+            Err(UserError::Core(CoreError::Unauthorized)) => Err(UserError::UserNotFoundName {
+                user_name: name.to_string(),
+            }),
+            result => result,
+        }
+    }
+
     pub(in crate::auth) async fn by_id(id: i32, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash FROM members WHERE member_id = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, banned, ban_reason, banned_until, version, password_hash, token_generation FROM members WHERE member_id = $1"#,
             id
         )
         .fetch_one(connection)
@@ -50,13 +75,17 @@ impl AuthenticatedUser {
         match row {
             Err(Error::RowNotFound) => Err(CoreError::Unauthorized.into()),
             Err(err) => Err(err.into()),
-            Ok(row) => Ok(AuthenticatedUser::legacy(construct_from_row!(row), row.password_hash)),
+            Ok(row) => {
+                let token_generation = row.token_generation;
+
+                Ok(AuthenticatedUser::legacy(construct_from_row!(row), row.password_hash).with_token_generation(token_generation))
+            },
         }
     }
 
     pub(in crate::auth) async fn by_name(name: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash FROM members WHERE members.name = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, banned, ban_reason, banned_until, version, password_hash, token_generation FROM members WHERE members.name = $1"#,
             name.to_string()
         )
         .fetch_one(connection)
@@ -65,7 +94,11 @@ impl AuthenticatedUser {
         match row {
             Err(Error::RowNotFound) => Err(CoreError::Unauthorized.into()),
             Err(err) => Err(err.into()),
-            Ok(row) => Ok(AuthenticatedUser::legacy(construct_from_row!(row), row.password_hash)),
+            Ok(row) => {
+                let token_generation = row.token_generation;
+
+                Ok(AuthenticatedUser::legacy(construct_from_row!(row), row.password_hash).with_token_generation(token_generation))
+            },
         }
     }
 }
@@ -7,6 +7,9 @@ impl AuthenticatedUser {
     pub async fn basic_auth(username: &str, password: &str, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
         log::info!("We are expected to perform basic authentication for user {}", username);
 
-        Self::by_name(username, connection).await?.verify_password(password)
+        Self::by_name(username, connection)
+            .await?
+            .verify_password(password)?
+            .ensure_not_banned()
     }
 }
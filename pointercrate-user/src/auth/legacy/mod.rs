@@ -14,6 +14,7 @@ mod post;
 pub struct LegacyAuthenticatedUser {
     user: User,
     password_hash: String,
+    token_generation: i32,
 }
 
 impl LegacyAuthenticatedUser {
@@ -25,6 +26,10 @@ impl LegacyAuthenticatedUser {
         &self.user
     }
 
+    pub(super) fn token_generation(&self) -> i32 {
+        self.token_generation
+    }
+
     pub(super) fn salt(&self) -> Vec<u8> {
         let raw_parts: Vec<_> = self.password_hash.split('$').filter(|s| !s.is_empty()).collect();
 
@@ -67,7 +72,21 @@ impl LegacyAuthenticatedUser {
 
 impl AuthenticatedUser {
     pub fn legacy(user: User, password_hash: String) -> Self {
-        AuthenticatedUser::Legacy(LegacyAuthenticatedUser { user, password_hash })
+        AuthenticatedUser::Legacy(LegacyAuthenticatedUser {
+            user,
+            password_hash,
+            token_generation: 0,
+        })
+    }
+
+    pub(in crate::auth) fn with_token_generation(self, token_generation: i32) -> Self {
+        match self {
+            AuthenticatedUser::Legacy(legacy) => AuthenticatedUser::Legacy(LegacyAuthenticatedUser {
+                token_generation,
+                ..legacy
+            }),
+            other => other,
+        }
     }
 }
 
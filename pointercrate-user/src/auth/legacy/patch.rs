@@ -21,7 +21,7 @@ impl LegacyAuthenticatedUser {
         sqlx::query!(
             "UPDATE members SET password_hash = $1 WHERE member_id = $2",
             self.password_hash,
-            self.user.id
+            self.user.id.0
         )
         .execute(connection)
         .await?;
@@ -24,7 +24,8 @@ impl LegacyAuthenticatedUser {
 #[cfg(feature = "legacy_accounts")]
 mod register {
     use super::*;
-    use crate::{auth::AuthenticatedUser, error::UserError, User};
+    use crate::{auth::AuthenticatedUser, error::UserError, User, UserId};
+    use pointercrate_core::validate::{validated, Validate, Validator};
     use serde::{Deserialize, Serialize};
     use sqlx::PgConnection;
 
@@ -34,10 +35,22 @@ mod register {
         pub password: String,
     }
 
+    impl Validate for Registration {
+        type Error = UserError;
+
+        fn validate(&self, validator: &mut Validator<UserError>) {
+            validator
+                .result("name", User::validate_name(&self.name))
+                .result("password", LegacyAuthenticatedUser::validate_password(&self.password));
+        }
+    }
+
     impl AuthenticatedUser {
         pub async fn register(registration: Registration, connection: &mut PgConnection) -> Result<AuthenticatedUser> {
             log::info!("Attempting registration of new user under name {}", registration.name);
 
+            let registration = validated(registration)?;
+
             log::trace!("Registration request is formally correct");
 
             match User::by_name(&registration.name, connection).await {
@@ -45,24 +58,31 @@ mod register {
                 Err(UserError::UserNotFoundName { .. }) => {
                     let hash = bcrypt::hash(&registration.password, bcrypt::DEFAULT_COST).unwrap();
 
-                    let id = sqlx::query!(
-                        "INSERT INTO members (name, password_hash) VALUES ($1, $2) RETURNING member_id",
+                    let inserted = sqlx::query!(
+                        "INSERT INTO members (name, password_hash) VALUES ($1, $2) RETURNING member_id, version",
                         registration.name,
                         hash
                     )
                     .fetch_one(connection)
-                    .await?
-                    .member_id;
+                    .await?;
 
-                    log::info!("Newly registered user with name {} has been assigned ID {}", registration.name, id);
+                    log::info!(
+                        "Newly registered user with name {} has been assigned ID {}",
+                        registration.name,
+                        inserted.member_id
+                    );
 
                     Ok(AuthenticatedUser::legacy(
                         User {
-                            id,
+                            id: UserId(inserted.member_id),
                             name: registration.name,
                             permissions: 0,
                             display_name: None,
                             youtube_channel: None,
+                            banned: false,
+                            ban_reason: None,
+                            banned_until: None,
+                            version: inserted.version,
                         },
                         hash,
                     ))
@@ -5,8 +5,14 @@
 //! * Deletion of own account
 //! * Modification of own account
 
-pub use self::patch::PatchMe;
-use crate::{error::Result, User};
+pub use self::{
+    application::{ApplicationAuthenticatedUser, ApplicationClaims, APPLICATION_TOKEN_LIFETIME},
+    patch::PatchMe,
+};
+use crate::{
+    error::{Result, UserError},
+    User,
+};
 use jsonwebtoken::{DecodingKey, EncodingKey};
 use legacy::LegacyAuthenticatedUser;
 use log::warn;
@@ -17,6 +23,7 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+mod application;
 mod delete;
 mod get;
 pub mod legacy;
@@ -24,6 +31,7 @@ mod patch;
 
 pub enum AuthenticatedUser {
     Legacy(LegacyAuthenticatedUser),
+    Application(ApplicationAuthenticatedUser),
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone)]
@@ -42,25 +50,40 @@ impl AuthenticatedUser {
     pub fn into_user(self) -> User {
         match self {
             AuthenticatedUser::Legacy(legacy) => legacy.into_user(),
+            AuthenticatedUser::Application(application) => application.into_user(),
         }
     }
 
     pub fn user(&self) -> &User {
         match self {
             AuthenticatedUser::Legacy(legacy) => legacy.user(),
+            AuthenticatedUser::Application(application) => application.user(),
         }
     }
 
     fn jwt_secret(&self) -> Vec<u8> {
         let mut key: Vec<u8> = pointercrate_core::config::secret();
         key.extend(self.salt());
+
+        // Only mix in the generation once it was bumped, so that tokens issued before generations
+        // existed stay valid
+        if self.token_generation() != 0 {
+            key.extend(self.token_generation().to_be_bytes());
+        }
+
         key
     }
 
+    /// Access tokens of applications expire after [`APPLICATION_TOKEN_LIFETIME`] seconds, those
+    /// of users stay valid until invalidated
     pub fn generate_access_token(&self) -> String {
+        if let AuthenticatedUser::Application(application) = self {
+            return application.generate_access_token(&self.jwt_secret());
+        }
+
         jsonwebtoken::encode(
             &jsonwebtoken::Header::default(),
-            &AccessClaims { id: self.user().id },
+            &AccessClaims { id: self.user().id.0 },
             &EncodingKey::from_secret(&self.jwt_secret()),
         )
         .unwrap()
@@ -80,7 +103,7 @@ impl AuthenticatedUser {
             })
             .and_then(move |token_data| {
                 // sanity check, should never fail
-                if token_data.claims.id != self.user().id {
+                if token_data.claims.id != self.user().id.0 {
                     log::error!(
                         "Access token for user {} decoded successfully even though user {} is logged in",
                         token_data.claims.id,
@@ -101,9 +124,9 @@ impl AuthenticatedUser {
             .expect("time went backwards (and this is probably gonna bite me in the ass when it comes to daytimesaving crap)");
 
         let claim = CSRFClaims {
-            id: self.user().id,
+            id: self.user().id.0,
             iat: since_epoch.as_secs(),
-            exp: (since_epoch + Duration::from_secs(3600)).as_secs(),
+            exp: (since_epoch + Duration::from_secs(pointercrate_core::config::get().auth.csrf_token_lifetime)).as_secs(),
         };
 
         jsonwebtoken::encode(
@@ -126,7 +149,7 @@ impl AuthenticatedUser {
                 CoreError::Unauthorized.into()
             })
             .and_then(|token_data| {
-                if token_data.claims.id != self.user().id {
+                if token_data.claims.id != self.user().id.0 {
                     warn!(
                         "User {} attempt to authenticate using CSRF token generated for user {}",
                         self.user(),
@@ -143,9 +166,33 @@ impl AuthenticatedUser {
     fn salt(&self) -> Vec<u8> {
         match self {
             AuthenticatedUser::Legacy(legacy) => legacy.salt(),
+            AuthenticatedUser::Application(application) => application.salt(),
         }
     }
 
+    fn token_generation(&self) -> i32 {
+        match self {
+            AuthenticatedUser::Legacy(legacy) => legacy.token_generation(),
+            AuthenticatedUser::Application(_) => 0,
+        }
+    }
+
+    /// Fails with [`UserError::UserBanned`] if this account is currently banned
+    pub fn ensure_not_banned(self) -> Result<Self> {
+        let user = self.user();
+
+        if user.is_banned() {
+            warn!("Banned user {} attempted to authenticate", user);
+
+            return Err(UserError::UserBanned {
+                reason: user.ban_reason.clone(),
+                until: user.banned_until,
+            });
+        }
+
+        Ok(self)
+    }
+
     pub fn verify_password(self, password: &str) -> Result<Self> {
         match &self {
             AuthenticatedUser::Legacy(legacy) => legacy.verify(password).map(|_| self),
@@ -156,16 +203,23 @@ impl AuthenticatedUser {
 
 #[cfg(test)]
 mod tests {
-    use crate::auth::{AuthenticatedUser, User};
+    use crate::{
+        auth::{AuthenticatedUser, User},
+        UserId,
+    };
 
     fn patrick() -> AuthenticatedUser {
         AuthenticatedUser::legacy(
             User {
-                id: 0,
+                id: UserId(0),
                 name: "Patrick".to_string(),
                 permissions: 0,
                 display_name: None,
                 youtube_channel: None,
+                banned: false,
+                ban_reason: None,
+                banned_until: None,
+                version: 1,
             },
             bcrypt::hash("bad password", bcrypt::DEFAULT_COST).unwrap(),
         )
@@ -174,11 +228,15 @@ mod tests {
     fn jacob() -> AuthenticatedUser {
         AuthenticatedUser::legacy(
             User {
-                id: 1,
+                id: UserId(1),
                 name: "".to_string(),
                 permissions: 0,
                 display_name: None,
                 youtube_channel: None,
+                banned: false,
+                ban_reason: None,
+                banned_until: None,
+                version: 1,
             },
             bcrypt::hash("bad password", bcrypt::DEFAULT_COST).unwrap(),
         )
@@ -1,8 +1,7 @@
 use crate::{
     auth::AuthenticatedUser,
     error::{Result, UserError},
-    patch::PatchUser,
-    User,
+    PatchUser, User,
 };
 use pointercrate_core::util::{non_nullable, nullable};
 use serde::Deserialize;
@@ -19,6 +18,9 @@ pub struct PatchMe {
 
     #[serde(default, deserialize_with = "nullable")]
     pub(super) youtube_channel: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub(super) email_address: Option<Option<String>>,
 }
 
 impl PatchMe {
@@ -33,22 +35,36 @@ impl Debug for PatchMe {
         f.debug_struct("PatchMe")
             .field("display_name", &self.display_name)
             .field("youtube_channel", &self.youtube_channel)
+            .field("email_address", &self.email_address)
             .finish()
     }
 }
 
 impl AuthenticatedUser {
     pub async fn apply_patch(mut self, patch: PatchMe, connection: &mut PgConnection) -> Result<User> {
+        if let Some(ref display_name) = patch.display_name {
+            if *display_name != self.user().display_name {
+                self.user().check_rename_cooldown(connection).await?;
+            }
+        }
+
         if let Some(password) = patch.password {
             self.set_password(password, connection).await?;
         }
 
+        if let Some(email_address) = patch.email_address {
+            self.set_email_address(email_address, connection).await?;
+        }
+
         self.into_user()
             .apply_patch(
                 PatchUser {
                     display_name: patch.display_name,
                     youtube_channel: patch.youtube_channel,
                     permissions: None,
+                    banned: None,
+                    ban_reason: None,
+                    banned_until: None,
                 },
                 connection,
             )
@@ -61,4 +77,30 @@ impl AuthenticatedUser {
             _ => Err(UserError::NonLegacyAccount),
         }
     }
+
+    /// Sets the address transactional emails for this account are sent to. [`None`] opts out of
+    /// all emails
+    pub async fn set_email_address(&mut self, email_address: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        if let Some(ref address) = email_address {
+            match address.split_once('@') {
+                Some((local, domain)) if !local.is_empty() && domain.contains('.') && !address.contains(char::is_whitespace) => (),
+                _ => return Err(UserError::InvalidEmailAddress),
+            }
+        }
+
+        // The EMAIL domain of the column is stricter than the check above
+        let result = sqlx::query!(
+            "UPDATE members SET email_address = $1::TEXT WHERE member_id = $2",
+            email_address,
+            self.user().id.0
+        )
+        .execute(connection)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(err)) if err.code().as_deref() == Some("23514") => Err(UserError::InvalidEmailAddress),
+            Err(err) => Err(err.into()),
+        }
+    }
 }
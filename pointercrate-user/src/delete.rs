@@ -6,7 +6,7 @@ impl User {
     pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
         warn!("Deleting user account {}", self);
 
-        sqlx::query!("DELETE FROM members WHERE member_id = $1", self.id)
+        sqlx::query!("DELETE FROM members WHERE member_id = $1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use derive_more::Display;
 
 use pointercrate_core::{
@@ -31,6 +32,15 @@ pub enum UserError {
     #[display(fmt = "You cannot modify your own account via this endpoint. Use PATCH /api/v1/auth/me/")]
     PatchSelf,
 
+    /// `403 FORBIDDEN` error returned when a banned user attempts to authenticate in any way
+    ///
+    /// Error Code `40309`
+    #[display(fmt = "This account has been banned")]
+    UserBanned {
+        reason: Option<String>,
+        until: Option<DateTime<Utc>>,
+    },
+
     #[display(fmt = "You cannot assign the following permissions: {:?}", non_assignable)]
     PermissionNotAssignable { non_assignable: HashSet<Permission> },
 
@@ -40,6 +50,20 @@ pub enum UserError {
     #[display(fmt = "No user with name {} found", user_name)]
     UserNotFoundName { user_name: String },
 
+    /// `404 NOT FOUND` error returned if a notification does not exist, or is addressed to a
+    /// different user
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No notification with id {} found", notification_id)]
+    NotificationNotFound { notification_id: i32 },
+
+    /// `404 NOT FOUND` error returned if an application does not exist, or is owned by a different
+    /// user
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No application with id {} found", application_id)]
+    ApplicationNotFound { application_id: i32 },
+
     /// `409 CONFLICT` error returned if a user tries to register with a name that's already taken
     ///
     /// Error Code `40902`
@@ -75,6 +99,79 @@ pub enum UserError {
         fmt = "The given operation (change password) is invalid on non-legacy account, as password login is not supported for these"
     )]
     NonLegacyAccount,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a temporary ban is set to expire at a point
+    /// in time that already lies in the past
+    ///
+    /// Error Code `42236`
+    #[display(fmt = "The expiry of a ban must lie in the future")]
+    InvalidBanExpiry,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the email address set for an account is
+    /// malformed
+    ///
+    /// Error Code `42237`
+    #[display(fmt = "Invalid email address")]
+    InvalidEmailAddress,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if users are filtered by a permission that does
+    /// not exist
+    ///
+    /// Error Code `42252`
+    #[display(fmt = "No permission named '{}' exists", permission)]
+    UnknownPermission { permission: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the name of an application is empty or longer
+    /// than 64 characters
+    ///
+    /// Error Code `42263`
+    #[display(fmt = "Application names must be between 1 and 64 characters long")]
+    InvalidApplicationName,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a redirect URI of an application is not an
+    /// absolute `https` URL (plain `http` is only allowed for `localhost`), or contains a fragment
+    ///
+    /// Error Code `42264`
+    #[display(fmt = "Invalid redirect URI '{}'", uri)]
+    InvalidRedirectUri { uri: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an access token is requested using any grant
+    /// type other than `client_credentials`
+    ///
+    /// Error Code `42265`
+    #[display(fmt = "Unsupported grant type '{}'. Only 'client_credentials' is supported", grant_type)]
+    UnsupportedGrantType { grant_type: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a user without an email address opts into
+    /// notification digests
+    ///
+    /// Error Code `42269`
+    #[display(fmt = "Notification digests require an email address to be set on your account")]
+    DigestWithoutEmailAddress,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the preferred locale is not a valid language
+    /// tag
+    ///
+    /// Error Code `42270`
+    #[display(fmt = "'{}' is not a valid language tag", locale)]
+    InvalidLocale { locale: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the preferred timezone is not an offset from
+    /// UTC of the form `+HH:MM` or `-HH:MM`
+    ///
+    /// Error Code `42271`
+    #[display(
+        fmt = "'{}' is not a valid UTC offset. Offsets need to be of the form '+HH:MM' or '-HH:MM'",
+        timezone
+    )]
+    InvalidTimezone { timezone: String },
+
+    /// `429 TOO MANY REQUESTS` variant returned if a user tries to change their display name again
+    /// before the rename cooldown has passed
+    ///
+    /// Error Code `42903`
+    #[display(fmt = "You have recently changed your display name. Try again after {}", retry_after)]
+    RenameCooldown { retry_after: DateTime<Utc> },
 }
 
 impl std::error::Error for UserError {}
@@ -96,13 +193,26 @@ impl PointercrateError for UserError {
             DeleteSelf => 40302,
             PatchSelf => 40303,
             PermissionNotAssignable { .. } => 40305,
+            UserBanned { .. } => 40309,
             UserNotFound { .. } => 40401,
             UserNotFoundName { .. } => 40401,
+            NotificationNotFound { .. } => 40401,
+            ApplicationNotFound { .. } => 40401,
             NameTaken => 40902,
             InvalidUsername => 42202,
             InvalidPassword => 42204,
             NotYouTube => 42226,
             NonLegacyAccount => 42234,
+            InvalidBanExpiry => 42236,
+            InvalidEmailAddress => 42237,
+            UnknownPermission { .. } => 42252,
+            InvalidApplicationName => 42263,
+            InvalidRedirectUri { .. } => 42264,
+            UnsupportedGrantType { .. } => 42265,
+            DigestWithoutEmailAddress => 42269,
+            InvalidLocale { .. } => 42270,
+            InvalidTimezone { .. } => 42271,
+            RenameCooldown { .. } => 42903,
         }
     }
 }
@@ -1,25 +1,31 @@
 use crate::{
     error::{Result, UserError},
-    User,
+    User, UserId,
 };
+use pointercrate_core::pool::ReadConnection;
 use sqlx::{Error, PgConnection};
 
 macro_rules! construct_from_row {
     ($row:expr) => {
         User {
-            id: $row.member_id,
+            id: crate::UserId($row.member_id),
             name: $row.name,
             permissions: $row.permissions.unwrap() as u16,
             display_name: $row.display_name,
             youtube_channel: $row.youtube_channel,
+            banned: $row.banned,
+            ban_reason: $row.ban_reason,
+            banned_until: $row.banned_until,
+            version: $row.version,
         }
     };
 }
 
 impl User {
-    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<User> {
+    pub async fn by_id(UserId(id): UserId, connection: &mut impl ReadConnection) -> Result<User> {
+        let connection = &mut connection.reader();
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text FROM members WHERE member_id = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, banned, ban_reason, banned_until, version FROM members WHERE member_id = $1"#,
             id
         )
         .fetch_one(connection)
@@ -32,9 +38,20 @@ impl User {
         }
     }
 
+    /// Like [`User::by_id`], but additionally locks the user's row until the current transaction
+    /// ends, so that their version cannot change between checking it against an `If-Match` header
+    /// and modifying the user
+    pub async fn lock_by_id(UserId(id): UserId, connection: &mut PgConnection) -> Result<User> {
+        sqlx::query!("SELECT member_id FROM members WHERE member_id = $1 FOR UPDATE", id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        User::by_id(UserId(id), connection).await
+    }
+
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<User> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, CAST(permissions AS integer), display_name, youtube_channel::text FROM members WHERE members.name = $1"#,
+            r#"SELECT member_id, members.name, CAST(permissions AS integer), display_name, youtube_channel::text, banned, ban_reason, banned_until, version FROM members WHERE members.name = $1"#,
             name
         )
         .fetch_one(connection)
@@ -48,4 +65,14 @@ impl User {
             Ok(row) => Ok(construct_from_row!(row)),
         }
     }
+
+    /// The address transactional emails for this user should be sent to, if they provided one
+    pub async fn email_address(&self, connection: &mut PgConnection) -> Result<Option<String>> {
+        Ok(
+            sqlx::query!("SELECT email_address::TEXT FROM members WHERE member_id = $1", self.id.0)
+                .fetch_one(connection)
+                .await?
+                .email_address,
+        )
+    }
 }
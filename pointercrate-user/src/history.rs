@@ -0,0 +1,95 @@
+//! History of user display names
+//!
+//! Whenever a user's display name changes, the previous one is recorded together with the time of
+//! the change. Moderators can view this history to spot users that repeatedly rename themselves to
+//! impersonate others, and it is used to enforce a cooldown between renames (see
+//! [`User::check_rename_cooldown`]).
+
+use crate::{
+    error::{Result, UserError},
+    User,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use pointercrate_core::pool::ReadConnection;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Hash, PartialEq, Eq)]
+pub struct DisplayNameChange {
+    /// The display name the user had before the change, or [`None`] if they did not have one
+    pub display_name: Option<String>,
+
+    /// The point in time at which the user stopped using [`display_name`](DisplayNameChange::display_name)
+    pub changed_at: DateTime<Utc>,
+}
+
+impl User {
+    /// All previous display names of this [`User`], most recent first
+    pub async fn display_name_history(&self, connection: &mut impl ReadConnection) -> Result<Vec<DisplayNameChange>> {
+        let connection = &mut connection.reader();
+        let mut stream = sqlx::query!(
+            "SELECT display_name, changed_at FROM display_name_history WHERE member_id = $1 ORDER BY changed_at DESC, id DESC",
+            self.id.0
+        )
+        .fetch(connection);
+
+        let mut history = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            history.push(DisplayNameChange {
+                display_name: row.display_name,
+                changed_at: row.changed_at,
+            })
+        }
+
+        Ok(history)
+    }
+
+    /// Records this [`User`]'s current display name in their history. Needs to be called right
+    /// before the display name is changed
+    pub(crate) async fn record_display_name_change(&self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO display_name_history (member_id, display_name) VALUES ($1, $2)",
+            self.id.0,
+            self.display_name
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Errors out if this [`User`] changed their display name less than the configured
+    /// [`rename_cooldown`](pointercrate_core::config::AuthConfig::rename_cooldown) ago.
+    ///
+    /// Renames done by moderators count towards the cooldown as well, so that a user whose offensive
+    /// display name was removed cannot immediately pick a new one.
+    pub async fn check_rename_cooldown(&self, connection: &mut PgConnection) -> Result<()> {
+        let cooldown = pointercrate_core::config::get().auth.rename_cooldown;
+
+        if cooldown == 0 {
+            return Ok(());
+        }
+
+        let last_change = sqlx::query!(
+            "SELECT MAX(changed_at) AS last_change FROM display_name_history WHERE member_id = $1",
+            self.id.0
+        )
+        .fetch_one(connection)
+        .await?
+        .last_change;
+
+        if let Some(last_change) = last_change {
+            let retry_after = last_change + Duration::seconds(cooldown as i64);
+
+            if retry_after > Utc::now() {
+                return Err(UserError::RenameCooldown { retry_after });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -5,11 +5,14 @@
 //! * Modifying other people's accounts (assign permissions, change offensive names, etc)
 //! * Querying account information
 
-pub use self::{paginate::UserPagination, patch::PatchUser};
+pub use self::{history::DisplayNameChange, paginate::UserPagination};
 use crate::error::{Result, UserError};
+use chrono::{DateTime, Utc};
 use pointercrate_core::{
     etag::Taggable,
+    patch::Patchable,
     permission::{Permission, PermissionsManager},
+    redact::{Redact, ViewContext},
 };
 use serde::Serialize;
 use std::{
@@ -19,31 +22,54 @@ use std::{
 
 #[macro_use]
 mod get;
+pub mod application;
 pub mod auth;
 mod delete;
 pub mod error;
+mod history;
+pub mod notification;
 mod paginate;
 mod patch;
+pub mod preferences;
+pub mod usage;
 mod video;
 
 pub const ADMINISTRATOR: Permission = Permission::new("Administrator", 0x4000);
 pub const MODERATOR: Permission = Permission::new("Moderator", 0x2000);
 
+/// The user id that audit log entries of self-deleted accounts get reassigned to
+///
+/// No member with this id exists, so anything joining against `members` will treat these entries
+/// just like entries made by some deleted user, without being able to tell which one.
+pub const DELETED_USER_ID: i32 = -1;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`User`], i.e. the `member_id` column of the `members` table
+    UserId
+);
+
 pub fn default_permissions_manager() -> PermissionsManager {
     PermissionsManager::new(vec![ADMINISTRATOR, MODERATOR])
         .assigns(ADMINISTRATOR, MODERATOR)
         .implies(ADMINISTRATOR, MODERATOR)
+        .grants("can_view_users", MODERATOR)
+        .grants("can_ban_users", MODERATOR)
+        .grants("can_delete_users", ADMINISTRATOR)
+        .grants("can_manage_announcements", ADMINISTRATOR)
+        .grants("can_view_api_usage", ADMINISTRATOR)
 }
 
 /// Model representing a user in the database
-#[derive(Debug, Serialize, Hash, Eq, PartialEq)]
+#[derive(Debug, Serialize, Hash, Eq, PartialEq, Patchable)]
 pub struct User {
     /// The [`User`]'s unique ID. This is used to identify users and cannot be changed.
-    pub id: i32,
+    pub id: UserId,
 
     /// The [`User`]'s unique username. This is used to log-in and cannot be changed.
     pub name: String,
 
+    /// Which permissions can be assigned via patches is checked separately, see [`PermissionsManager`]
+    #[patch]
     pub permissions: u16,
 
     /// A user-customizable name for each [`User`].
@@ -51,13 +77,50 @@ pub struct User {
     /// If set to anything other than [`None`], the value set here will be displayed everywhere the
     /// username would be displayed otherwise. This value is not guaranteed to be unique and
     /// cannot be used to identify a user. In particular, this value cannot be used for log-in
+    #[patch(permission = MODERATOR)]
     pub display_name: Option<String>,
 
     /// A user-customizable link to a [YouTube](https://youtube.com) channel
+    #[patch(permission = MODERATOR)]
     pub youtube_channel: Option<String>,
+
+    /// Whether this [`User`] has been banned. Banned users cannot authenticate in any way.
+    ///
+    /// Note that a ban might have expired, see [`User::is_banned`]. Who can patch this field is
+    /// checked separately, since besides moderators it is also open to anyone with the
+    /// `can_ban_users` capability
+    #[patch]
+    pub banned: bool,
+
+    /// The reason given by the staff member who banned this [`User`], if any. Only taken into
+    /// account by patches that set `banned` to `true`
+    #[patch]
+    pub ban_reason: Option<String>,
+
+    /// The point in time at which the ban on this [`User`] expires. [`None`] for permanent bans.
+    /// Only taken into account by patches that set `banned` to `true`
+    #[patch]
+    pub banned_until: Option<DateTime<Utc>>,
+
+    /// Incremented whenever one of this [`User`]'s publicly visible fields changes. Used as the
+    /// `PATCH` part of ETags
+    pub version: i32,
+}
+
+impl Taggable for User {
+    fn patch_part(&self) -> u64 {
+        self.version as u64
+    }
 }
 
-impl Taggable for User {}
+/// Ban reasons are only visible to moderators
+impl Redact for User {
+    fn redact(&mut self, context: &ViewContext) {
+        if !context.has_permission(MODERATOR) {
+            self.ban_reason = None;
+        }
+    }
+}
 
 impl Display for User {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
@@ -81,6 +144,11 @@ impl User {
         perms.into_iter().any(|perm| self.has_permissions(perm))
     }
 
+    /// Whether this [`User`] is currently banned, taking expiry of temporary bans into account
+    pub fn is_banned(&self) -> bool {
+        self.banned && self.banned_until.is_none_or(|until| until > Utc::now())
+    }
+
     pub fn validate_name(name: &str) -> Result<()> {
         if name.len() < 3 || name != name.trim() {
             return Err(UserError::InvalidUsername);
@@ -0,0 +1,161 @@
+//! Periodic email digests of notifications
+//!
+//! Users with an email address can opt into receiving a daily or weekly email listing the
+//! notifications they have not read since their previous digest, instead of having to check the
+//! website. Digests are collected by a background job (see [`Digest::take_due`]), which marks them
+//! as sent in the same transaction, so that no notification ends up in two digests.
+
+use crate::{
+    error::{Result, UserError},
+    notification::{Notification, NotificationId, NotificationKind},
+};
+use derive_more::Display;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    #[display(fmt = "daily")]
+    Daily,
+
+    #[display(fmt = "weekly")]
+    Weekly,
+}
+
+impl DigestFrequency {
+    fn to_sql(self) -> &'static str {
+        match self {
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "daily" => DigestFrequency::Daily,
+            "weekly" => DigestFrequency::Weekly,
+            _ => panic!("invalid digest frequency: {}", sql),
+        }
+    }
+}
+
+/// A user's digest preference, as exposed via the API
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DigestSettings {
+    /// How often to send digests. [`None`] if the user does not want to receive any
+    pub frequency: Option<DigestFrequency>,
+}
+
+/// The notifications a single user has not read since their previous digest
+#[derive(Debug)]
+pub struct Digest {
+    pub user_id: i32,
+
+    /// The name to address the user by, i.e. their display name if they set one
+    pub username: String,
+
+    pub email_address: String,
+    pub frequency: DigestFrequency,
+    pub notifications: Vec<Notification>,
+}
+
+impl Digest {
+    /// Collects the digests of all users whose next digest is due, and marks them as sent
+    ///
+    /// Users without unread notifications are marked as well, but no digest is returned for them.
+    /// Should be run inside a transaction that is committed before the digests are actually sent
+    pub async fn take_due(connection: &mut PgConnection) -> Result<Vec<Digest>> {
+        let due = sqlx::query!(
+            r#"UPDATE members SET last_digest_at = NOW()
+               FROM (SELECT member_id, last_digest_at FROM members
+                     WHERE digest_frequency IS NOT NULL AND email_address IS NOT NULL AND NOT banned
+                       AND (last_digest_at IS NULL
+                            OR last_digest_at <= NOW() - CASE digest_frequency WHEN 'daily' THEN INTERVAL '1 day' ELSE INTERVAL '1 week' END)
+                     FOR UPDATE) AS previous
+               WHERE members.member_id = previous.member_id
+               RETURNING members.member_id, COALESCE(members.display_name, members.name::TEXT) AS "username!",
+                         members.email_address::TEXT AS "email_address!", members.digest_frequency AS "digest_frequency!",
+                         previous.last_digest_at"#
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        let mut digests = Vec::new();
+
+        for row in due {
+            let frequency = DigestFrequency::from_sql(&row.digest_frequency);
+
+            let notifications = sqlx::query!(
+                "SELECT id, kind, content, link, created_at, read FROM notifications WHERE member_id = $1 AND NOT read AND (created_at > \
+                 $2 OR $2 IS NULL) ORDER BY id",
+                row.member_id,
+                row.last_digest_at
+            )
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|notification| Notification {
+                id: NotificationId(notification.id),
+                user_id: row.member_id,
+                kind: NotificationKind::from_sql(&notification.kind),
+                content: notification.content,
+                link: notification.link,
+                created_at: notification.created_at,
+                read: notification.read,
+            })
+            .collect::<Vec<_>>();
+
+            if !notifications.is_empty() {
+                digests.push(Digest {
+                    user_id: row.member_id,
+                    username: row.username,
+                    email_address: row.email_address,
+                    frequency,
+                    notifications,
+                })
+            }
+        }
+
+        Ok(digests)
+    }
+
+    /// The frequency the given user wants to receive digests at, if they opted into them
+    pub async fn frequency_of(user_id: i32, connection: &mut impl ReadConnection) -> Result<Option<DigestFrequency>> {
+        let connection = &mut connection.reader();
+        Ok(sqlx::query!("SELECT digest_frequency FROM members WHERE member_id = $1", user_id)
+            .fetch_one(connection)
+            .await?
+            .digest_frequency
+            .as_deref()
+            .map(DigestFrequency::from_sql))
+    }
+
+    /// Opts the given user into (or, for [`None`], out of) digests. The first digest is sent one
+    /// period after opting in (or changing the frequency), and only contains notifications received
+    /// after that
+    pub async fn set_frequency(user_id: i32, frequency: Option<DigestFrequency>, connection: &mut PgConnection) -> Result<()> {
+        if frequency.is_some() {
+            let email_address = sqlx::query!("SELECT email_address::TEXT FROM members WHERE member_id = $1", user_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .email_address;
+
+            if email_address.is_none() {
+                return Err(UserError::DigestWithoutEmailAddress);
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE members SET last_digest_at = CASE WHEN digest_frequency IS DISTINCT FROM $1 THEN NOW() ELSE \
+             last_digest_at END, digest_frequency = $1 WHERE member_id = $2",
+            frequency.map(DigestFrequency::to_sql),
+            user_id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
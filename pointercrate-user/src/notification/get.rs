@@ -0,0 +1,47 @@
+use crate::{
+    error::{Result, UserError},
+    notification::{Notification, NotificationId, NotificationKind},
+};
+use pointercrate_core::pool::ReadConnection;
+use sqlx::{Error, PgConnection};
+
+impl Notification {
+    /// Retrieves the notification with the given id, if it is addressed to the given user
+    pub async fn by_id(
+        NotificationId(notification_id): NotificationId, user_id: i32, connection: &mut PgConnection,
+    ) -> Result<Notification> {
+        let row = sqlx::query!(
+            "SELECT id, kind, content, link, created_at, read FROM notifications WHERE id = $1 AND member_id = $2",
+            notification_id,
+            user_id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(Notification {
+                id: NotificationId(row.id),
+                user_id,
+                kind: NotificationKind::from_sql(&row.kind),
+                content: row.content,
+                link: row.link,
+                created_at: row.created_at,
+                read: row.read,
+            }),
+            Err(Error::RowNotFound) => Err(UserError::NotificationNotFound { notification_id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The number of unread notifications addressed to the given user
+    pub async fn unread_count(user_id: i32, connection: &mut impl ReadConnection) -> Result<i64> {
+        let connection = &mut connection.reader();
+        Ok(
+            sqlx::query!("SELECT COUNT(*) FROM notifications WHERE member_id = $1 AND NOT read", user_id)
+                .fetch_one(connection)
+                .await?
+                .count
+                .unwrap_or_default(),
+        )
+    }
+}
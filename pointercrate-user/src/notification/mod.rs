@@ -0,0 +1,106 @@
+//! In-app notifications for users
+//!
+//! Notifications are generated by the operations that cause them (e.g. a record changing status,
+//! or permissions being granted), inside the same transaction, so that a rolled back operation
+//! never leaves behind a notification about something that didn't happen. Users can additionally
+//! opt into periodic email [digests](Digest) of their unread notifications.
+
+pub use self::{
+    digest::{Digest, DigestFrequency, DigestSettings},
+    paginate::NotificationPagination,
+    patch::PatchNotification,
+};
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use pointercrate_core::etag::Taggable;
+use serde::{Serialize, Serializer};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+mod digest;
+mod get;
+mod paginate;
+mod patch;
+mod post;
+
+pointercrate_core::id_type!(
+    /// The ID of a [`Notification`]
+    NotificationId
+);
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Display)]
+pub enum NotificationKind {
+    #[display(fmt = "record_status_changed")]
+    RecordStatusChanged,
+
+    #[display(fmt = "permissions_granted")]
+    PermissionsGranted,
+
+    #[display(fmt = "claimed_player_modified")]
+    ClaimedPlayerModified,
+
+    #[display(fmt = "watched_object_modified")]
+    WatchedObjectModified,
+
+    #[display(fmt = "mentioned_in_note")]
+    MentionedInNote,
+}
+
+impl NotificationKind {
+    pub fn to_sql(self) -> String {
+        self.to_string()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "record_status_changed" => NotificationKind::RecordStatusChanged,
+            "permissions_granted" => NotificationKind::PermissionsGranted,
+            "claimed_player_modified" => NotificationKind::ClaimedPlayerModified,
+            "watched_object_modified" => NotificationKind::WatchedObjectModified,
+            "mentioned_in_note" => NotificationKind::MentionedInNote,
+            _ => panic!("invalid notification kind: {}", sql),
+        }
+    }
+}
+
+impl Serialize for NotificationKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Hash, Display)]
+#[display(fmt = "{} notification (ID: {})", kind, id)]
+pub struct Notification {
+    pub id: NotificationId,
+
+    /// The id of the user this notification is addressed to
+    #[serde(skip)]
+    pub user_id: i32,
+
+    pub kind: NotificationKind,
+
+    /// Human readable description of what happened
+    pub content: String,
+
+    /// Relative link to the object this notification is about, if any
+    pub link: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+
+    pub read: bool,
+}
+
+impl Taggable for Notification {
+    fn patch_part(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.id.hash(&mut hasher);
+        self.read.hash(&mut hasher);
+        hasher.finish()
+    }
+}
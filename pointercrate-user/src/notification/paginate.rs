@@ -0,0 +1,81 @@
+use crate::notification::{Notification, NotificationKind};
+use futures::StreamExt;
+use pointercrate_core::{
+    first_and_last,
+    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pool::Reader,
+    util::non_nullable,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize)]
+pub struct NotificationPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub read: Option<bool>,
+
+    /// The user whose notifications should be retrieved. Always set by the endpoint to the
+    /// currently authenticated user, never taken from the query string
+    #[serde(skip)]
+    pub user_id: i32,
+}
+
+impl PaginationQuery for NotificationPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..*self
+        }
+    }
+}
+
+impl Paginatable<NotificationPagination> for Notification {
+    first_and_last!("notifications");
+
+    async fn page(query: &NotificationPagination, connection: &mut Reader<'_>) -> Result<(Vec<Notification>, PageContext), sqlx::Error> {
+        let order = query.params.order();
+
+        let sql_query = format!(
+            "SELECT id, kind, content, link, created_at, read FROM notifications WHERE member_id = $1 AND (id < $2 OR $2 IS NULL) AND \
+             (id > $3 OR $3 IS NULL) AND (read = $4 OR $4 IS NULL) ORDER BY id {} LIMIT $5",
+            order
+        );
+
+        let mut stream = sqlx::query(&sql_query)
+            .bind(query.user_id)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(query.read)
+            .bind(query.params.limit() + 1)
+            .fetch(connection);
+
+        let mut notifications = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            notifications.push(Notification {
+                id: row.get("id"),
+                user_id: query.user_id,
+                kind: NotificationKind::from_sql(row.get("kind")),
+                content: row.get("content"),
+                link: row.get("link"),
+                created_at: row.get("created_at"),
+                read: row.get("read"),
+            })
+        }
+
+        Ok(__pagination_compat(&query.params, notifications))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.id.0
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{error::Result, notification::Notification};
+use pointercrate_core::util::non_nullable;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchNotification {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub read: Option<bool>,
+}
+
+impl Notification {
+    pub async fn apply_patch(mut self, patch: PatchNotification, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(read) = patch.read {
+            self.set_read(read, connection).await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn set_read(&mut self, read: bool, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE notifications SET read = $1 WHERE id = $2", read, self.id.0)
+            .execute(connection)
+            .await?;
+
+        self.read = read;
+
+        Ok(())
+    }
+
+    /// Marks all notifications of the given user as read, returning the number of notifications
+    /// that were previously unread
+    pub async fn mark_all_read(user_id: i32, connection: &mut PgConnection) -> Result<u64> {
+        Ok(
+            sqlx::query!("UPDATE notifications SET read = TRUE WHERE member_id = $1 AND NOT read", user_id)
+                .execute(connection)
+                .await?
+                .rows_affected(),
+        )
+    }
+}
@@ -0,0 +1,36 @@
+use crate::notification::{Notification, NotificationId, NotificationKind};
+use log::debug;
+use sqlx::PgConnection;
+
+impl Notification {
+    /// Notifies the user with the given id
+    ///
+    /// Should be run inside the same transaction as the operation that caused the notification.
+    /// Returns a plain [`sqlx::Error`] so that other crates can generate notifications without
+    /// having to deal with [`UserError`](crate::error::UserError)
+    pub async fn create(
+        user_id: i32, kind: NotificationKind, content: String, link: Option<String>, connection: &mut PgConnection,
+    ) -> Result<Notification, sqlx::Error> {
+        debug!("Notifying user with id {} about {}: {}", user_id, kind, content);
+
+        let row = sqlx::query!(
+            "INSERT INTO notifications (member_id, kind, content, link) VALUES ($1, $2, $3, $4) RETURNING id, created_at",
+            user_id,
+            kind.to_sql(),
+            content,
+            link
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(Notification {
+            id: NotificationId(row.id),
+            user_id,
+            kind,
+            content,
+            link,
+            created_at: row.created_at,
+            read: false,
+        })
+    }
+}
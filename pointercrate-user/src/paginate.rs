@@ -1,13 +1,15 @@
-use crate::{error::Result, User};
+use crate::{error::Result, User, UserId};
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
     pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
     permission::Permission,
+    pool::{ReadConnection, Reader},
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{postgres::PgRow, Row};
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct UserPagination {
@@ -28,6 +30,25 @@ pub struct UserPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub any_permissions: Option<u16>,
+
+    /// Name of a permission (e.g. `ListHelper`) all returned users need to have. Since this crate
+    /// does not know about all permissions, it is not evaluated by [`User::page`]. Instead, the API
+    /// resolves it into [`UserPagination::has_permissions`]
+    #[serde(default, deserialize_with = "non_nullable", skip_serializing_if = "Option::is_none")]
+    pub has_permission: Option<String>,
+
+    /// Only return users whose display name contains this string (case-insensitively)
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub display_name_contains: Option<String>,
+
+    /// Only return users created at or after this point in time. Accounts created before creation
+    /// dates were tracked are never matched by this filter
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub created_after: Option<DateTime<Utc>>,
+
+    /// Only return users created before this point in time
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub created_before: Option<DateTime<Utc>>,
 }
 
 impl PaginationQuery for UserPagination {
@@ -46,7 +67,7 @@ impl PaginationQuery for UserPagination {
 impl Paginatable<UserPagination> for User {
     first_and_last!("members", "member_id");
 
-    async fn page(query: &UserPagination, connection: &mut PgConnection) -> std::result::Result<(Vec<User>, PageContext), sqlx::Error> {
+    async fn page(query: &UserPagination, connection: &mut Reader<'_>) -> std::result::Result<(Vec<User>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../sql/paginate_users.sql"), order);
@@ -60,7 +81,10 @@ impl Paginatable<UserPagination> for User {
             .bind(query.has_permissions.map(|p| p as i32))
             .bind(query.any_permissions.map(|p| p as i32))
             .bind(query.name_contains.as_ref())
-            .bind(query.params.limit + 1)
+            .bind(query.display_name_contains.as_ref())
+            .bind(query.created_after)
+            .bind(query.created_before)
+            .bind(query.params.limit() + 1)
             .fetch(connection);
 
         let mut users = Vec::new();
@@ -76,6 +100,10 @@ impl Paginatable<UserPagination> for User {
                 permissions: perms_as_i32 as u16,
                 display_name: row.get("display_name"),
                 youtube_channel: row.get("youtube_channel"),
+                banned: row.get("banned"),
+                ban_reason: row.get("ban_reason"),
+                banned_until: row.get("banned_until"),
+                version: row.get("version"),
             })
         }
 
@@ -83,20 +111,21 @@ impl Paginatable<UserPagination> for User {
     }
 
     fn pagination_id(&self) -> i32 {
-        self.id
+        self.id.0
     }
 }
 
 impl User {
-    pub async fn by_permission(permission: Permission, connection: &mut PgConnection) -> Result<Vec<User>> {
+    pub async fn by_permission(permission: Permission, connection: &mut impl ReadConnection) -> Result<Vec<User>> {
         User::by_permissions(permission.bit(), connection).await
     }
 
     /// Gets all users that have the given permission bits all set
-    pub async fn by_permissions(permissions: u16, connection: &mut PgConnection) -> Result<Vec<User>> {
+    pub async fn by_permissions(permissions: u16, connection: &mut impl ReadConnection) -> Result<Vec<User>> {
+        let connection = &mut connection.reader();
         let mut stream = sqlx::query!(
-            "SELECT member_id, name, permissions::integer, display_name, youtube_channel::text FROM members WHERE permissions & \
-             CAST($1::INTEGER AS BIT(16)) = CAST($1::INTEGER AS BIT(16))",
+            "SELECT member_id, name, permissions::integer, display_name, youtube_channel::text, banned, ban_reason, banned_until, \
+             version FROM members WHERE permissions & CAST($1::INTEGER AS BIT(16)) = CAST($1::INTEGER AS BIT(16))",
             permissions as i32
         )
         .fetch(connection);
@@ -107,11 +136,15 @@ impl User {
             let row = row?;
 
             users.push(User {
-                id: row.member_id,
+                id: UserId(row.member_id),
                 name: row.name,
                 permissions: row.permissions.unwrap() as u16,
                 display_name: row.display_name,
                 youtube_channel: row.youtube_channel,
+                banned: row.banned,
+                ban_reason: row.ban_reason,
+                banned_until: row.banned_until,
+                version: row.version,
             })
         }
 
@@ -1,23 +1,12 @@
-use crate::{error::Result, User};
-use log::info;
-use pointercrate_core::util::{non_nullable, nullable};
-use serde::Deserialize;
+use crate::{
+    error::{Result, UserError},
+    notification::{Notification, NotificationKind},
+    PatchUser, User,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
-pub struct PatchUser {
-    #[serde(default, deserialize_with = "nullable")]
-    pub display_name: Option<Option<String>>,
-
-    #[serde(default, deserialize_with = "nullable")]
-    #[allow(clippy::option_option)]
-    pub youtube_channel: Option<Option<String>>,
-
-    #[serde(default, deserialize_with = "non_nullable")]
-    #[allow(clippy::option_option)]
-    pub permissions: Option<u16>,
-}
-
 impl User {
     /// Must run inside a transaction
     pub async fn apply_patch(mut self, patch: PatchUser, connection: &mut PgConnection) -> Result<Self> {
@@ -27,6 +16,15 @@ impl User {
             self.set_permissions(permissions, connection).await?;
         }
 
+        match patch.banned {
+            Some(true) => {
+                self.ban(patch.ban_reason.flatten(), patch.banned_until.flatten(), connection)
+                    .await?
+            },
+            Some(false) => self.unban(connection).await?,
+            None => (),
+        }
+
         if let Some(display_name) = patch.display_name {
             match display_name {
                 Some(display_name) => self.set_display_name(display_name, connection).await?,
@@ -41,6 +39,11 @@ impl User {
             }
         }
 
+        self.version = sqlx::query!("SELECT version FROM members WHERE member_id = $1", self.id.0)
+            .fetch_one(connection)
+            .await?
+            .version;
+
         Ok(self)
     }
 
@@ -48,20 +51,81 @@ impl User {
         sqlx::query!(
             "UPDATE members SET permissions = cast($1::integer as BIT(16)) WHERE member_id = $2", // FIXME(sqlx)
             permissions as i32,
-            self.id
+            self.id.0
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        if permissions & !self.permissions != 0 {
+            Notification::create(
+                self.id.0,
+                NotificationKind::PermissionsGranted,
+                "You have been granted new permissions".to_string(),
+                Some("/account/".to_string()),
+                connection,
+            )
+            .await?;
+        }
+
         self.permissions = permissions;
 
         Ok(())
     }
 
+    /// Bans this user, invalidating all their access tokens
+    ///
+    /// Banning an already banned user updates reason and expiry of the ban.
+    pub async fn ban(&mut self, reason: Option<String>, until: Option<DateTime<Utc>>, connection: &mut PgConnection) -> Result<()> {
+        if until.is_some_and(|until| until <= Utc::now()) {
+            return Err(UserError::InvalidBanExpiry);
+        }
+
+        warn!("Banning user {} until {:?} (reason: {:?})", self, until, reason);
+
+        sqlx::query!(
+            "UPDATE members SET banned = TRUE, ban_reason = $1, banned_until = $2, token_generation = token_generation + 1 WHERE \
+             member_id = $3",
+            reason,
+            until,
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.banned = true;
+        self.ban_reason = reason;
+        self.banned_until = until;
+
+        Ok(())
+    }
+
+    pub async fn unban(&mut self, connection: &mut PgConnection) -> Result<()> {
+        info!("Unbanning user {}", self);
+
+        sqlx::query!(
+            "UPDATE members SET banned = FALSE, ban_reason = NULL, banned_until = NULL WHERE member_id = $1",
+            self.id.0
+        )
+        .execute(connection)
+        .await?;
+
+        self.banned = false;
+        self.ban_reason = None;
+        self.banned_until = None;
+
+        Ok(())
+    }
+
     pub async fn set_display_name(&mut self, display_name: String, connection: &mut PgConnection) -> Result<()> {
         Self::validate_name(&display_name)?;
 
-        sqlx::query!("UPDATE members SET display_name = $1 WHERE member_id = $2", display_name, self.id)
+        if self.display_name.as_ref() == Some(&display_name) {
+            return Ok(());
+        }
+
+        self.record_display_name_change(&mut *connection).await?;
+
+        sqlx::query!("UPDATE members SET display_name = $1 WHERE member_id = $2", display_name, self.id.0)
             .execute(connection)
             .await?;
 
@@ -71,7 +135,13 @@ impl User {
     }
 
     pub async fn reset_display_name(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE members SET display_name = NULL WHERE member_id = $1", self.id)
+        if self.display_name.is_none() {
+            return Ok(());
+        }
+
+        self.record_display_name_change(&mut *connection).await?;
+
+        sqlx::query!("UPDATE members SET display_name = NULL WHERE member_id = $1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -86,7 +156,7 @@ impl User {
         sqlx::query!(
             "UPDATE members SET youtube_channel = $1::text WHERE member_id = $2",
             youtube_channel,
-            self.id
+            self.id.0
         )
         .execute(connection)
         .await?;
@@ -97,7 +167,7 @@ impl User {
     }
 
     pub async fn reset_youtube_channel(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE members SET youtube_channel = NULL WHERE member_id = $1", self.id)
+        sqlx::query!("UPDATE members SET youtube_channel = NULL WHERE member_id = $1", self.id.0)
             .execute(connection)
             .await?;
 
@@ -0,0 +1,187 @@
+//! Per-user preferences
+//!
+//! Preferences are a fixed set of settings that only affect how the website presents itself to a
+//! user (language, timezone and theme) and how they get notified. Notification settings are read by
+//! background jobs, and thus live in their own columns (see [`Digest`]). Everything else is stored
+//! as a single JSON object in the `members.preferences` column, whose keys are fixed by
+//! [`Preferences`].
+
+use crate::{
+    error::{Result, UserError},
+    notification::{Digest, DigestFrequency},
+    User,
+};
+use chrono::FixedOffset;
+use derive_more::Display;
+use log::info;
+use pointercrate_core::{
+    pool::ReadConnection,
+    util::{non_nullable, nullable},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[display(fmt = "light")]
+    Light,
+
+    #[display(fmt = "dark")]
+    Dark,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preferences {
+    /// Language tag (e.g. `en` or `pt-BR`) of the language pages should be displayed in. [`None`]
+    /// means the site's default language
+    pub locale: Option<String>,
+
+    /// Offset from UTC (e.g. `+02:00`) timestamps should be displayed in. [`None`] means UTC
+    pub timezone: Option<String>,
+
+    /// [`None`] means the site's default theme
+    pub theme: Option<Theme>,
+
+    #[serde(default)]
+    pub notifications: NotificationPreferences,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    /// How often to receive email digests of unread notifications, if at all
+    pub digest: Option<DigestFrequency>,
+}
+
+/// The part of [`Preferences`] stored in the `preferences` column
+#[derive(Default, Serialize, Deserialize)]
+struct StoredPreferences {
+    #[serde(default)]
+    locale: Option<String>,
+
+    #[serde(default)]
+    timezone: Option<String>,
+
+    #[serde(default)]
+    theme: Option<Theme>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchPreferences {
+    #[serde(default, deserialize_with = "nullable")]
+    pub locale: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub timezone: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    pub theme: Option<Option<Theme>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub notifications: Option<PatchNotificationPreferences>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchNotificationPreferences {
+    #[serde(default, deserialize_with = "nullable")]
+    pub digest: Option<Option<DigestFrequency>>,
+}
+
+/// Parses an offset from UTC of the form `+HH:MM` or `-HH:MM`, as stored in
+/// [`Preferences::timezone`]
+pub fn parse_utc_offset(offset: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+        (Some(rest), _) => (1, rest),
+        (_, Some(rest)) => (-1, rest),
+        _ => return None,
+    };
+
+    let (hours, minutes) = rest.split_once(':')?;
+
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+
+    let hours = hours.parse::<i32>().ok().filter(|hours| *hours <= 14)?;
+    let minutes = minutes.parse::<i32>().ok().filter(|minutes| *minutes < 60)?;
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Whether the given string looks like a BCP 47 language tag, i.e. a two or three letter language
+/// code optionally followed by subtags such as a region (`pt-BR`) or script (`zh-Hant`)
+fn is_language_tag(locale: &str) -> bool {
+    let mut subtags = locale.split('-');
+
+    let language_valid = subtags
+        .next()
+        .is_some_and(|language| (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic()));
+
+    language_valid
+        && locale.len() <= 35
+        && subtags.all(|subtag| (2..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+impl User {
+    pub async fn preferences(&self, connection: &mut impl ReadConnection) -> Result<Preferences> {
+        let connection = &mut connection.reader();
+        let stored = sqlx::query!(
+            r#"SELECT preferences::TEXT AS "preferences!" FROM members WHERE member_id = $1"#,
+            self.id.0
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .preferences;
+        let stored: StoredPreferences = serde_json::from_str(&stored).unwrap_or_default();
+
+        Ok(Preferences {
+            locale: stored.locale,
+            timezone: stored.timezone,
+            theme: stored.theme,
+            notifications: NotificationPreferences {
+                digest: Digest::frequency_of(self.id.0, connection).await?,
+            },
+        })
+    }
+
+    pub async fn patch_preferences(&self, patch: PatchPreferences, connection: &mut PgConnection) -> Result<Preferences> {
+        info!("Patching preferences of {} with {:?}", self, patch);
+
+        if let Some(Some(ref locale)) = patch.locale {
+            if !is_language_tag(locale) {
+                return Err(UserError::InvalidLocale { locale: locale.clone() });
+            }
+        }
+
+        if let Some(Some(ref timezone)) = patch.timezone {
+            if parse_utc_offset(timezone).is_none() {
+                return Err(UserError::InvalidTimezone {
+                    timezone: timezone.clone(),
+                });
+            }
+        }
+
+        let current = self.preferences(&mut *connection).await?;
+        let stored = StoredPreferences {
+            locale: patch.locale.unwrap_or(current.locale),
+            timezone: patch.timezone.unwrap_or(current.timezone),
+            theme: patch.theme.unwrap_or(current.theme),
+        };
+
+        sqlx::query!(
+            "UPDATE members SET preferences = $1::TEXT::JSONB WHERE member_id = $2",
+            serde_json::to_string(&stored).unwrap(),
+            self.id.0
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        if let Some(digest) = patch.notifications.and_then(|notifications| notifications.digest) {
+            Digest::set_frequency(self.id.0, digest, &mut *connection).await?;
+        }
+
+        self.preferences(connection).await
+    }
+}
@@ -0,0 +1,128 @@
+//! Per-user statistics about API usage
+//!
+//! Every API request authenticated via an access token counts towards its user's usage on that day
+//! (in UTC). These counters make it possible to tell which consumers (for example third-party bots)
+//! put the most load on the API, as a first step towards enforcing fair use.
+//!
+//! Counts are kept per user only. Requests made with different access tokens of the same user,
+//! including those of applications acting on their behalf, are not told apart.
+
+use crate::error::Result;
+use chrono::NaiveDate;
+use pointercrate_core::pool::ReadConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// The number of days covered by usage reports, unless specified otherwise
+pub const DEFAULT_REPORT_DAYS: i32 = 30;
+
+/// The maximal number of days a usage report can cover
+pub const MAX_REPORT_DAYS: i32 = 365;
+
+/// The number of requests a user made on a single day
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    pub requests: i64,
+}
+
+/// The API usage of a single user over the last few days
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ApiUsage {
+    /// The number of requests made today
+    pub today: i64,
+
+    /// The number of requests made on all days covered by this report
+    pub total: i64,
+
+    /// Days without any requests are left out. Most recent day first
+    pub days: Vec<DailyUsage>,
+}
+
+/// A user's share in the overall API usage over the last few days
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ConsumerUsage {
+    pub user_id: i32,
+    pub name: String,
+    pub requests: i64,
+
+    /// The most recent day the user made any requests on
+    pub last_active: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageReportQuery {
+    /// How many days (including today) to cover, see [`DEFAULT_REPORT_DAYS`] and
+    /// [`MAX_REPORT_DAYS`]
+    #[serde(default)]
+    days: Option<i32>,
+}
+
+impl UsageReportQuery {
+    pub fn days(&self) -> i32 {
+        self.days.unwrap_or(DEFAULT_REPORT_DAYS).clamp(1, MAX_REPORT_DAYS)
+    }
+}
+
+/// Counts a request towards today's usage of the given user
+pub async fn track_request(user_id: i32, connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO api_usage (member_id, requests) VALUES ($1, 1) ON CONFLICT (member_id, day) DO UPDATE SET requests = \
+         api_usage.requests + 1",
+        user_id
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+impl ApiUsage {
+    /// The usage of the given user over the last `days` days (including today)
+    pub async fn of(user_id: i32, days: i32, connection: &mut impl ReadConnection) -> Result<ApiUsage> {
+        let connection = &mut connection.reader();
+        let days = sqlx::query_as!(
+            DailyUsage,
+            "SELECT day, requests FROM api_usage WHERE member_id = $1 AND day > (NOW() AT TIME ZONE 'utc')::DATE - $2::INTEGER ORDER BY \
+             day DESC",
+            user_id,
+            days
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        let today = sqlx::query!(r#"SELECT (NOW() AT TIME ZONE 'utc')::DATE AS "today!""#)
+            .fetch_one(connection)
+            .await?
+            .today;
+
+        Ok(ApiUsage {
+            today: days
+                .iter()
+                .find(|usage| usage.day == today)
+                .map(|usage| usage.requests)
+                .unwrap_or(0),
+            total: days.iter().map(|usage| usage.requests).sum(),
+            days,
+        })
+    }
+}
+
+/// The users that made the most requests over the last `days` days (including today), heaviest
+/// consumer first
+pub async fn top_consumers(days: i32, limit: i64, connection: &mut impl ReadConnection) -> Result<Vec<ConsumerUsage>> {
+    let connection = &mut connection.reader();
+    Ok(sqlx::query_as!(
+        ConsumerUsage,
+        r#"SELECT members.member_id AS user_id, members.name, SUM(requests)::BIGINT AS "requests!", MAX(day) AS "last_active!"
+           FROM api_usage INNER JOIN members ON members.member_id = api_usage.member_id
+           WHERE day > (NOW() AT TIME ZONE 'utc')::DATE - $1::INTEGER
+           GROUP BY members.member_id
+           ORDER BY 3 DESC, members.member_id
+           LIMIT $2"#,
+        days,
+        limit
+    )
+    .fetch_all(connection)
+    .await?)
+}